@@ -23,22 +23,59 @@ use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 
 use mcp_guard_core::{
-    audit::{AuditLogger, AuditLoggerHandle},
+    alerting::spawn_alerting,
+    approval::ApprovalService,
+    audit::{
+        audit_files, parse_report_time, AccessReport, AuditLogger, AuditLoggerHandle,
+        CompiledRedactionRules,
+    },
     auth::{
-        ApiKeyProvider, AuthProvider, DatabaseAuthProvider, JwtProvider, MtlsAuthProvider, MultiProvider,
-        OAuthAuthProvider,
+        ApiKeyProvider, AuthProvider, AuthorizationServer, DatabaseAuthProvider, HmacAuthProvider, JwtProvider, MtlsAuthProvider, MultiProvider,
+        OAuthAuthProvider, SamlBridgeProvider, SessionStore, registered_auth_providers,
+        report_api_key_expiry, API_KEY_EXPIRY_CHECK_INTERVAL,
+    },
+    authz::ResponseFilterChain,
+    bench::{self, BenchOptions},
+    break_glass::BreakGlass,
+    capture::{
+        capture_redaction, read_captures, redact_message, responses_match, CaptureRecord,
+        CaptureRecorder,
     },
     cli::{
-        apply_key_to_config, generate_api_key, generate_config_with_demo_key, hash_api_key, Cli,
-        Commands,
+        apply_key_to_config, generate_api_key, generate_config_with_demo_key, hash_api_key,
+        hash_password, rotate_key_in_config, AuditCommands, Cli, Commands, ConfigCommands,
+        DbCommands, KeysCommands,
     },
-    config::{Config, TransportType},
+    config::{
+        apply_lint_fixes, config_schema, find_unknown_keys, is_yaml_path, lint_config, Config,
+        LintSeverity, ServerRouteConfig, TransportType,
+    },
+    doctor::{self, CheckStatus, DoctorCheck, EXPIRY_WARNING_DAYS},
+    egress::configure_egress,
+    fair_queue::FairQueue,
+    honeypot::Honeypot,
+    identity_store::IdentityStore,
+    journal::Journal,
+    load_shed::LoadShedder,
     mcp_server::{McpServer, McpServerConfig},
-    observability::{init_metrics, init_tracing},
-    rate_limit::RateLimitService,
+    method_policy::MethodPolicy,
+    network_acl::NetworkAcl,
+    observability::{
+        configure_identity_metrics, configure_label_limits, configure_telemetry_privacy,
+        init_metrics, init_tracing,
+    },
+    rate_limit::{IpRateLimiter, RateLimitService},
+    read_only::ReadOnlyPolicy,
     router::ServerRouter,
+    scrub::Scrubber,
     server::{self, new_oauth_state_store, AppState},
-    transport::{HttpTransport, SseTransport, StdioTransport, Transport},
+    sessions::SessionRegistry,
+    tenancy::TenantRegistry,
+    transport::{
+        check_upstream, configure_request_signing, configure_ssrf, HttpTransport, Message,
+        MockUpstreamTransport, SseTransport, StdioOptions, StdioTransport, Transport,
+        TransportError, UnixSocketTransport, UpstreamHeaders, UpstreamTarget, UpstreamTls,
+    },
 };
 
 /// Result of bootstrapping the server state.
@@ -51,18 +88,46 @@ pub struct BootstrapResult {
 
 /// Bootstrap the server state from configuration.
 /// This extracts all initialization logic to make it testable.
-pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
+///
+/// `license_expires_at` comes from [`enforce_license`]; `/ready` fails once it passes.
+pub async fn bootstrap(
+    config: Config,
+    license_expires_at: Option<std::time::SystemTime>,
+) -> anyhow::Result<BootstrapResult> {
     // Create shutdown token for graceful shutdown coordination
     let shutdown_token = CancellationToken::new();
 
     // Initialize Prometheus metrics
     let metrics_handle = init_metrics();
+    configure_identity_metrics(&config.metrics);
+    configure_label_limits(&config.metrics);
+    configure_telemetry_privacy(&config.telemetry_privacy);
+    configure_ssrf(&config.ssrf);
+    configure_egress(&config.egress);
+    configure_request_signing(&config.request_signing)
+        .map_err(|e| anyhow::anyhow!("Failed to load request signing key: {}", e))?;
 
     // Set up database connection
-    let db = if let Some(url) = &config.database_url {
+    let db = if let Some(database) = config.database_config() {
         tracing::info!("Initializing database connection");
-        Some(mcp_guard_core::db::Database::new(url).await
-            .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?)
+        let db = mcp_guard_core::db::Database::connect(&database)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?;
+        if !database.migrate {
+            match db.migration_status().await {
+                Ok(status) => {
+                    let pending = status.iter().filter(|m| !m.applied).count();
+                    if pending > 0 {
+                        tracing::warn!(
+                            "Database has {} pending migration(s); run `mcp-guard db migrate`",
+                            pending
+                        );
+                    }
+                }
+                Err(e) => tracing::warn!("Could not read database migration status: {}", e),
+            }
+        }
+        Some(db)
     } else {
         None
     };
@@ -81,6 +146,22 @@ pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
             None
         };
 
+    // Set up the built-in authorization server (issues tokens at /oauth/token)
+    let authorization_server: Option<Arc<AuthorizationServer>> =
+        if let Some(server_config) = config.auth.authorization_server.clone() {
+            tracing::info!(issuer = %server_config.issuer, "Enabling built-in authorization server");
+            Some(Arc::new(
+                AuthorizationServer::new(
+                    server_config,
+                    config.auth.api_keys.clone(),
+                    db.as_ref().map(|database| database.refresh_tokens()),
+                )
+                .map_err(|e| anyhow::anyhow!("Failed to initialize authorization server: {}", e))?,
+            ))
+        } else {
+            None
+        };
+
     // Set up authentication provider(s)
     let (auth_provider, jwt_provider_arc): (Arc<dyn AuthProvider>, Option<Arc<JwtProvider>>) = {
         let mut providers: Vec<Arc<dyn AuthProvider>> = Vec::new();
@@ -102,11 +183,17 @@ pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
         }
 
         // Add JWT provider if configured
-        if let Some(jwt_config) = &config.auth.jwt {
-            tracing::info!("Enabling JWT authentication");
+        if !config.auth.jwt.is_empty() {
+            tracing::info!(
+                issuers = config.auth.jwt.len(),
+                "Enabling JWT authentication"
+            );
             let jwt_provider = Arc::new(
-                JwtProvider::new(jwt_config.clone())
-                    .map_err(|e| anyhow::anyhow!("Failed to initialize JWT provider: {}", e))?,
+                JwtProvider::with_issuers(
+                    config.auth.jwt.clone(),
+                    db.as_ref().map(|database| database.revoked_tokens()),
+                )
+                .map_err(|e| anyhow::anyhow!("Failed to initialize JWT provider: {}", e))?,
             );
             // Start background refresh for JWKS mode with shutdown coordination
             jwt_provider.start_background_refresh(shutdown_token.clone());
@@ -114,11 +201,42 @@ pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
             jwt_provider_arc = Some(jwt_provider);
         }
 
+        // Add SAML bridge provider if configured
+        if let Some(saml_config) = &config.auth.saml {
+            tracing::info!("Enabling SAML federation");
+            let saml_provider = Arc::new(
+                SamlBridgeProvider::new(saml_config.clone())
+                    .map_err(|e| anyhow::anyhow!("Failed to initialize SAML provider: {}", e))?,
+            );
+            saml_provider.start_background_refresh(shutdown_token.clone());
+            providers.push(saml_provider);
+        }
+
         // Add OAuth provider for token validation (shares with oauth_provider)
         if let Some(ref oauth_prov) = oauth_provider {
             providers.push(oauth_prov.clone());
         }
 
+        // Add the built-in authorization server to validate its own tokens
+        if let Some(ref server) = authorization_server {
+            providers.push(server.clone());
+        }
+
+        // Add custom providers registered by downstream crates
+        if !config.auth.custom.is_empty() {
+            let registry = registered_auth_providers();
+            for custom in &config.auth.custom {
+                tracing::info!(
+                    name = %custom.name,
+                    provider = %custom.provider,
+                    "Enabling custom authentication provider"
+                );
+                providers.push(registry.build(custom).map_err(|e| {
+                    anyhow::anyhow!("Failed to initialize custom auth provider: {}", e)
+                })?);
+            }
+        }
+
         // Select appropriate provider setup
         if providers.is_empty() {
             tracing::warn!(
@@ -141,12 +259,62 @@ pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
     // Create OAuth state store for PKCE
     let oauth_state_store = new_oauth_state_store();
 
+    // Share OAuth state, sessions and rate limits with other replicas
+    let cluster = match (&db, config.cluster.enabled) {
+        (Some(db), true) => {
+            tracing::info!("Enabling cluster mode (state shared through the database)");
+            let cluster = Arc::new(mcp_guard_core::cluster::ClusterState::new(
+                db.storage(),
+                std::time::Duration::from_secs(mcp_guard_core::server::OAUTH_STATE_EXPIRY_SECS),
+            ));
+            cluster
+                .start_cleanup_task(shutdown_token.clone(), config.cluster.cleanup_interval_secs);
+            Some(cluster)
+        }
+        _ => None,
+    };
+
+    // Send requests for stdio upstreams to the replica running their session
+    let affinity = match mcp_guard_core::cluster::affinity::Affinity::from_config(&config) {
+        Some(affinity) => {
+            tracing::info!(
+                "Enabling session affinity for stdio upstreams (mode: {})",
+                affinity.mode_label()
+            );
+            Some(Arc::new(affinity))
+        }
+        None => {
+            if config.cluster.affinity.is_some() {
+                tracing::warn!("cluster.affinity is set but no route uses a stdio upstream");
+            }
+            None
+        }
+    };
+
+    // Set up server-side OAuth sessions if configured
+    let session_store = config
+        .auth
+        .oauth
+        .as_ref()
+        .and_then(|oauth| oauth.session.clone())
+        .map(|session_config| {
+            tracing::info!("Enabling server-side OAuth sessions");
+            let store = SessionStore::new(session_config);
+            Arc::new(match cluster {
+                Some(ref cluster) => store.with_storage(cluster.storage()),
+                None => store,
+            })
+        });
+
     // Set up mTLS provider if configured
     let mtls_provider: Option<Arc<MtlsAuthProvider>> =
         if let Some(mtls_config) = config.auth.mtls.clone() {
             if mtls_config.enabled {
                 tracing::info!("Enabling mTLS client certificate authentication");
-                Some(Arc::new(MtlsAuthProvider::new(mtls_config)))
+                let mtls_provider = Arc::new(MtlsAuthProvider::new(mtls_config));
+                // Keep the SPIFFE trust bundle (if any) current
+                mtls_provider.start_background_refresh(shutdown_token.clone());
+                Some(mtls_provider)
             } else {
                 None
             }
@@ -154,13 +322,95 @@ pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
             None
         };
 
+    // Set up HMAC request signing provider if configured
+    let hmac_provider = config.auth.hmac.clone().map(|hmac_config| {
+        tracing::info!(
+            "Enabling HMAC request signing ({} keys)",
+            hmac_config.keys.len()
+        );
+        Arc::new(HmacAuthProvider::new(hmac_config))
+    });
+
+    // Set up network access control (loads the GeoIP database if configured)
+    let network_acl = NetworkAcl::new(&config)
+        .map_err(|e| anyhow::anyhow!("Failed to initialize network ACL: {}", e))?;
+
+    // Set up secret scrubbing for forwarded requests
+    let scrubber = Scrubber::new(&config.scrubbing)
+        .map_err(|e| anyhow::anyhow!("Failed to initialize request scrubbing: {}", e))?;
+
+    // Set up the response filter pipeline (list authorization plus redaction rules)
+    let response_filters = ResponseFilterChain::new(&config.response_filtering)
+        .map_err(|e| anyhow::anyhow!("Failed to initialize response filtering: {}", e))?;
+
     // Set up rate limiter
     let rate_limiter = RateLimitService::new(&config.rate_limit);
 
+    // Track active identities for the admin API and active identities gauge
+    let identity_store = Arc::new(IdentityStore::new(std::time::Duration::from_secs(
+        config.admin.active_window_secs,
+    )));
+
+    // Track client sessions opened by `initialize`
+    let sessions = SessionRegistry::new(&config.sessions);
+
+    // Set up load shedder
+    let load_shedder = LoadShedder::new(&config.load_shedding);
+
+    // Set up fair queueing for the single-server upstream
+    let fair_queue = FairQueue::new("default", &config.upstream.fair_queue);
+
+    // Set up tenant assignment and isolation
+    let tenants =
+        TenantRegistry::new(&config.tenancy).with_namespaced_tools(config.is_aggregated());
+
+    // Set up approval of tool calls that need a human in the loop
+    let approvals = ApprovalService::new(&config.approval, &config.admin.identities);
+    let honeypot = Honeypot::new(&config.honeypot);
+    let break_glass = BreakGlass::new(&config.break_glass, &config.auth.api_keys);
+    let break_glass = match db {
+        Some(ref db) => break_glass.with_storage(db.storage()),
+        None => break_glass,
+    };
+    let read_only =
+        ReadOnlyPolicy::new(&config.read_only).with_namespaced_tools(config.is_aggregated());
+    let method_policy = MethodPolicy::new(&config);
+    let registration_limiter = IpRateLimiter::for_registration(&config);
+
+    // Set up capture of sampled traffic; the writer flushes after each burst
+    // and stops once the state is dropped
+    let audit_rules = config.audit.effective_redaction_rules()?;
+    let (capture, _capture_handle) = CaptureRecorder::with_task(&config.capture, &audit_rules)?;
+
+    // Recover journaled tool calls left undelivered by the last run
+    let journal = Journal::open(&config.journal).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to open journal {}: {}",
+            config.journal.path.display(),
+            e
+        )
+    })?;
+
     // Set up audit logger with background tasks for non-blocking I/O
     let (audit_logger, audit_handle) = AuditLogger::with_tasks(&config.audit)?;
     let audit_logger = Arc::new(audit_logger);
 
+    // Report API keys nearing expiry at startup and periodically afterwards
+    if config.auth.api_keys.iter().any(|key| key.expires_at.is_some()) {
+        let keys = config.auth.api_keys.clone();
+        let audit_logger = audit_logger.clone();
+        let cancel_token = shutdown_token.clone();
+        tokio::spawn(async move {
+            loop {
+                report_api_key_expiry(&keys, &audit_logger);
+                tokio::select! {
+                    _ = cancel_token.cancelled() => break,
+                    _ = tokio::time::sleep(API_KEY_EXPIRY_CHECK_INTERVAL) => {}
+                }
+            }
+        });
+    }
+
     // Set up transport/router based on configuration
     let (transport, router): (Option<Arc<dyn Transport>>, Option<Arc<ServerRouter>>) =
         if config.is_multi_server() {
@@ -183,13 +433,22 @@ pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
             (None, Some(Arc::new(server_router)))
         } else {
             // Single-server mode
+            let upstream_headers = UpstreamHeaders::compile(&config.upstream.headers)
+                .map_err(|e| anyhow::anyhow!("Invalid upstream.headers: {}", e))?;
             let transport: Arc<dyn Transport> = match &config.upstream.transport {
                 mcp_guard_core::config::TransportType::Stdio => {
                     let command = config.upstream.command.as_ref().ok_or_else(|| {
                         anyhow::anyhow!("stdio transport requires 'command' in config")
                     })?;
                     tracing::info!(command = %command, "Using stdio transport");
-                    Arc::new(StdioTransport::spawn(command, &config.upstream.args).await?)
+                    Arc::new(
+                        StdioTransport::spawn_with(
+                            command,
+                            &config.upstream.args,
+                            StdioOptions::from_upstream(&config.upstream),
+                        )
+                        .await?,
+                    )
                 }
                 mcp_guard_core::config::TransportType::Http => {
                     let url = config
@@ -205,7 +464,18 @@ pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
                     let transport = HttpTransport::new(url)
                         .await
                         .map_err(|e| anyhow::anyhow!("Failed to create HTTP transport: {}", e))?;
-                    Arc::new(transport)
+                    let transport = match load_upstream_tls(&config)? {
+                        Some(tls) => transport.with_tls(&tls)?,
+                        None => transport,
+                    };
+                    Arc::new(
+                        transport
+                            .with_upstream_headers(upstream_headers)
+                            .with_timeout(config.upstream.timeouts.max())
+                            .with_dns_refresh(std::time::Duration::from_secs(
+                                config.upstream.dns_refresh_secs,
+                            )),
+                    )
                 }
                 mcp_guard_core::config::TransportType::Sse => {
                     let url = config
@@ -219,7 +489,32 @@ pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
                     let transport = SseTransport::connect_unchecked(url).await?;
                     #[cfg(not(test))]
                     let transport = SseTransport::connect(url).await?;
-                    Arc::new(transport)
+                    let transport = match load_upstream_tls(&config)? {
+                        Some(tls) => transport.with_tls(&tls)?,
+                        None => transport,
+                    };
+                    Arc::new(
+                        transport
+                            .with_upstream_headers(upstream_headers)
+                            .with_timeout(config.upstream.timeouts.max())
+                            .with_dns_refresh(std::time::Duration::from_secs(
+                                config.upstream.dns_refresh_secs,
+                            )),
+                    )
+                }
+                mcp_guard_core::config::TransportType::Unix => {
+                    let path = config.upstream.socket_path.as_ref().ok_or_else(|| {
+                        anyhow::anyhow!("unix transport requires 'socket_path' in config")
+                    })?;
+                    tracing::info!(path = %path.display(), "Using unix socket transport");
+                    Arc::new(UnixSocketTransport::connect(path).await?)
+                }
+                mcp_guard_core::config::TransportType::Mock => {
+                    tracing::info!(
+                        tools = config.upstream.mock.tools.len(),
+                        "Using mock upstream"
+                    );
+                    Arc::new(MockUpstreamTransport::new(config.upstream.mock.clone()))
                 }
             };
             (Some(transport), None)
@@ -233,19 +528,46 @@ pub async fn bootstrap(config: Config) -> anyhow::Result<BootstrapResult> {
         config,
         auth_provider,
         rate_limiter,
+        load_shedder,
+        fair_queue,
+        tenants,
+        approvals,
+        honeypot,
+        read_only,
+        break_glass,
+        method_policy,
+        capture,
+        journal,
+        upstream_requests: Default::default(),
+        registration_limiter,
         audit_logger,
         transport,
         router,
         metrics_handle,
         oauth_provider,
         oauth_state_store,
+        session_store,
+        cluster,
+        affinity,
+        authorization_server,
         started_at: Instant::now(),
         ready,
         mtls_provider,
+        hmac_provider,
+        network_acl,
+        scrubber,
+        response_filters,
+        identity_store,
+        sessions,
+        upstream_stats: Default::default(),
+        license_expires_at,
         jwt_provider: jwt_provider_arc,
         db: db.clone(),
     });
 
+    // Mail alerts for an expiring license, upstream outages and audit export failures
+    spawn_alerting(state.clone(), shutdown_token.clone())?;
+
     Ok(BootstrapResult {
         state,
         audit_handle,
@@ -270,6 +592,12 @@ async fn run_cli(cli: Cli) -> anyhow::Result<()> {
     match cli.command {
         Commands::Init { format, force } => handle_init(&format, force, cli.verbose),
         Commands::Validate => handle_validate(&cli.config, cli.verbose),
+        Commands::Config {
+            command: ConfigCommands::Lint { fix },
+        } => handle_config_lint(&cli.config, fix),
+        Commands::Config {
+            command: ConfigCommands::Schema,
+        } => handle_config_schema(),
         Commands::Keygen {
             user_id,
             rate_limit,
@@ -283,13 +611,105 @@ async fn run_cli(cli: Cli) -> anyhow::Result<()> {
             apply_to_config,
             cli.verbose,
         ),
+        Commands::Keys {
+            command: KeysCommands::Rotate { id, grace_hours },
+        } => handle_keys_rotate(&cli.config, &id, grace_hours),
         Commands::HashKey { key } => handle_hash_key(&key),
+        Commands::HashPassword { password } => handle_hash_password(&password),
         Commands::Version => handle_version(),
         Commands::CheckUpstream { timeout } => {
             handle_check_upstream(&cli.config, timeout, cli.verbose).await
         }
-        Commands::Run { host, port } => handle_run(&cli.config, host, port, cli.verbose).await,
+        Commands::Doctor { timeout, json } => {
+            handle_doctor(&cli.config, timeout, json, cli.verbose).await
+        }
+        Commands::Run {
+            stdio: true,
+            token,
+            server,
+            ..
+        } => handle_run_stdio(&cli.config, token, server, cli.verbose).await,
+        Commands::Run { host, port, .. } => handle_run(&cli.config, host, port, cli.verbose).await,
         Commands::Serve => handle_serve(&cli.config, cli.verbose).await,
+        Commands::TestCall {
+            token,
+            url,
+            server,
+            direct,
+            tool,
+            args,
+            timeout,
+        } => {
+            let options = TestCallOptions {
+                token,
+                url,
+                server,
+                direct,
+                tool,
+                args,
+                timeout: std::time::Duration::from_secs(timeout),
+            };
+            handle_test_call(&cli.config, options, cli.verbose).await
+        }
+        Commands::Replay {
+            file,
+            server,
+            url,
+            method,
+            limit,
+            timeout,
+        } => {
+            let options = ReplayOptions {
+                file,
+                server,
+                url,
+                method,
+                limit,
+                timeout: std::time::Duration::from_secs(timeout),
+            };
+            handle_replay(&cli.config, options, cli.verbose).await
+        }
+        Commands::Bench {
+            requests,
+            concurrency,
+            method,
+            payload_bytes,
+            rate_limit,
+            max_p99_ms,
+            min_rps,
+            json,
+        } => {
+            let options = BenchOptions {
+                requests,
+                concurrency,
+                method,
+                payload_bytes,
+                rate_limit,
+            };
+            handle_bench(&cli.config, options, max_p99_ms, min_rps, json).await
+        }
+        Commands::Audit {
+            command:
+                AuditCommands::Report {
+                    since,
+                    until,
+                    identity,
+                    format,
+                    output,
+                    file,
+                },
+        } => {
+            let options = AuditReportOptions {
+                since,
+                until,
+                identity,
+                format,
+                output,
+                file,
+            };
+            handle_audit_report(&cli.config, options)
+        }
+        Commands::Db { command } => handle_db(&cli.config, command).await,
     }
 }
 
@@ -299,7 +719,7 @@ async fn run_cli(cli: Cli) -> anyhow::Result<()> {
 
 /// Handle the `init` command: create a new configuration file with a demo API key.
 fn handle_init(format: &str, force: bool, verbose: bool) -> anyhow::Result<()> {
-    let _guard = init_tracing(verbose, None);
+    let _guard = init_tracing(verbose, None, None);
 
     let filename = if format == "yaml" {
         "mcp-guard.yaml"
@@ -335,7 +755,14 @@ fn handle_init(format: &str, force: bool, verbose: bool) -> anyhow::Result<()> {
 
 /// Handle the `validate` command: validate a configuration file.
 fn handle_validate(config_path: &std::path::PathBuf, verbose: bool) -> anyhow::Result<()> {
-    let _guard = init_tracing(verbose, None);
+    let _guard = init_tracing(verbose, None, None);
+
+    // Report keys serde would silently ignore before any validation error
+    if let Ok(unknown) = find_unknown_keys(config_path) {
+        for key in &unknown {
+            eprintln!("warning: {}", key);
+        }
+    }
 
     match Config::from_file(config_path) {
         Ok(_) => {
@@ -348,6 +775,72 @@ fn handle_validate(config_path: &std::path::PathBuf, verbose: bool) -> anyhow::R
     }
 }
 
+/// Handle the `config lint` command: report (and optionally fix) insecure settings.
+fn handle_config_lint(config_path: &std::path::Path, fix: bool) -> anyhow::Result<()> {
+    let config = Config::parse_file(config_path)
+        .map_err(|e| anyhow::anyhow!("Error loading config: {}", e))?;
+    let mut findings = lint_config(&config);
+
+    if fix && findings.iter().any(|f| f.fix.is_some()) {
+        if is_yaml_path(config_path) {
+            anyhow::bail!("--fix only supports TOML config files; apply the fixes below by hand");
+        }
+        let content = std::fs::read_to_string(config_path)?;
+        let (fixed, applied) = apply_lint_fixes(&content, &findings)?;
+        std::fs::write(config_path, &fixed)?;
+        println!("Applied {} fix(es) to {}", applied, config_path.display());
+        println!();
+
+        let config = Config::parse_file(config_path)
+            .map_err(|e| anyhow::anyhow!("Error reloading fixed config: {}", e))?;
+        findings = lint_config(&config);
+    }
+
+    if findings.is_empty() {
+        println!("No issues found in {}", config_path.display());
+        return Ok(());
+    }
+
+    for finding in &findings {
+        println!("{}[{}] {}", finding.severity, finding.rule, finding.key);
+        println!("  {}", finding.message);
+        println!("  help: {}", finding.suggestion);
+        if let Some(ref fix) = finding.fix {
+            println!("  fix:  {}", fix);
+        }
+        println!();
+    }
+
+    let errors = findings
+        .iter()
+        .filter(|f| f.severity == LintSeverity::Error)
+        .count();
+    let fixable = findings.iter().filter(|f| f.fix.is_some()).count();
+    println!(
+        "{} issue(s) found: {} error(s), {} warning(s)",
+        findings.len(),
+        errors,
+        findings.len() - errors
+    );
+    if fixable > 0 {
+        println!(
+            "{} can be fixed automatically with `mcp-guard config lint --fix`",
+            fixable
+        );
+    }
+
+    if errors > 0 {
+        anyhow::bail!("{} insecure setting(s) must be fixed", errors);
+    }
+    Ok(())
+}
+
+/// Handle the `config schema` command: print the config JSON Schema.
+fn handle_config_schema() -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(&config_schema())?);
+    Ok(())
+}
+
 /// Handle the `keygen` command: generate a new API key.
 fn handle_keygen(
     config_path: &std::path::Path,
@@ -357,7 +850,7 @@ fn handle_keygen(
     apply_to_config: bool,
     verbose: bool,
 ) -> anyhow::Result<()> {
-    let _guard = init_tracing(verbose, None);
+    let _guard = init_tracing(verbose, None, None);
 
     let key = generate_api_key();
     let hash = hash_api_key(&key);
@@ -423,6 +916,38 @@ fn handle_keygen(
     Ok(())
 }
 
+/// Handle the `keys rotate` command: add a new key and give the old one a grace period.
+fn handle_keys_rotate(
+    config_path: &std::path::Path,
+    id: &str,
+    grace_hours: u64,
+) -> anyhow::Result<()> {
+    if !config_path.exists() {
+        anyhow::bail!("Config file not found: {}", config_path.display());
+    }
+    if is_yaml_path(config_path) {
+        anyhow::bail!("keys rotate only supports TOML config files");
+    }
+
+    let key = generate_api_key();
+    let hash = hash_api_key(&key);
+    let grace = std::time::Duration::from_secs(grace_hours * 60 * 60);
+    let old_expires_at = rotate_key_in_config(config_path, id, &hash, grace)
+        .map_err(|e| anyhow::anyhow!("Failed to rotate key: {}", e))?;
+
+    println!("✓ API key for '{}' rotated in {}", id, config_path.display());
+    println!();
+    println!("New API Key (save this, shown only once):");
+    println!("  {}", key);
+    println!();
+    println!(
+        "The previous key stays valid until {}.",
+        old_expires_at.format("%Y-%m-%dT%H:%M:%SZ")
+    );
+    println!("Restart or reload mcp-guard to apply the change.");
+    Ok(())
+}
+
 /// Handle the `hash-key` command: hash an existing API key.
 fn handle_hash_key(key: &str) -> anyhow::Result<()> {
     let hash = hash_api_key(key);
@@ -430,6 +955,12 @@ fn handle_hash_key(key: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Handle the `hash-password` command: print an argon2id hash of the password.
+fn handle_hash_password(password: &str) -> anyhow::Result<()> {
+    println!("{}", hash_password(password));
+    Ok(())
+}
+
 /// Handle the `version` command: print version information.
 fn handle_version() -> anyhow::Result<()> {
     use mcp_guard_core::tier;
@@ -507,18 +1038,28 @@ fn print_startup_summary(config: &Config) {
         "✓ Server:     http://{}:{}",
         config.server.host, config.server.port
     );
+    if let Some(ref ops) = config.server.ops {
+        let auth = if ops.auth.is_some() { "" } else { " (no auth)" };
+        println!("✓ Ops:        http://{}:{}{}", ops.host, ops.port, auth);
+    }
 
     // Auth providers
     let mut providers = Vec::new();
     if !config.auth.api_keys.is_empty() {
         providers.push(format!("API Keys ({})", config.auth.api_keys.len()));
     }
-    if config.auth.jwt.is_some() {
+    if !config.auth.jwt.is_empty() {
         providers.push("JWT".to_string());
     }
     if config.auth.oauth.is_some() {
         providers.push("OAuth 2.1".to_string());
     }
+    if config.auth.saml.is_some() {
+        providers.push("SAML".to_string());
+    }
+    if let Some(ref hmac) = config.auth.hmac {
+        providers.push(format!("HMAC ({})", hmac.keys.len()));
+    }
     if config
         .auth
         .mtls
@@ -536,7 +1077,12 @@ fn print_startup_summary(config: &Config) {
     }
 
     // Transport
-    if config.is_multi_server() {
+    if config.is_aggregated() {
+        println!(
+            "✓ Transport:  Aggregated ({} servers)",
+            config.upstream.servers.len()
+        );
+    } else if config.is_multi_server() {
         println!(
             "✓ Transport:  Multi-server ({} routes)",
             config.upstream.servers.len()
@@ -555,6 +1101,21 @@ fn print_startup_summary(config: &Config) {
                 let url = config.upstream.url.as_deref().unwrap_or("?");
                 println!("✓ Transport:  SSE → {}", url);
             }
+            TransportType::Unix => {
+                let path = config
+                    .upstream
+                    .socket_path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "?".to_string());
+                println!("✓ Transport:  unix → {}", path);
+            }
+            TransportType::Mock => {
+                println!(
+                    "✓ Transport:  mock ({} tools)",
+                    config.upstream.mock.tools.len()
+                );
+            }
         }
     }
 
@@ -592,166 +1153,1033 @@ async fn handle_check_upstream(
     timeout: u64,
     verbose: bool,
 ) -> anyhow::Result<()> {
-    let _guard = init_tracing(verbose, None);
+    let _guard = init_tracing(verbose, None, None);
 
     let config = Config::from_file(config_path)
         .map_err(|e| anyhow::anyhow!("Error loading config: {}", e))?;
+    configure_request_signing(&config.request_signing)
+        .map_err(|e| anyhow::anyhow!("Failed to load request signing key: {}", e))?;
 
     println!("Checking upstream connectivity...");
     println!();
 
-    let timeout_duration = std::time::Duration::from_secs(timeout);
-
-    match &config.upstream.transport {
-        mcp_guard_core::config::TransportType::Stdio => {
-            let command =
-                config.upstream.command.as_ref().ok_or_else(|| {
-                    anyhow::anyhow!("stdio transport requires 'command' in config")
-                })?;
+    let target = UpstreamTarget::from_upstream(&config.upstream)
+        .map_err(|e| anyhow::anyhow!("{} in config", e))?;
 
+    match target {
+        UpstreamTarget::Stdio {
+            command,
+            args,
+            ref options,
+        } => {
             println!("Transport: stdio");
             println!("Command:   {}", command);
-            println!("Args:      {:?}", config.upstream.args);
-            println!();
-
-            match tokio::time::timeout(
-                timeout_duration,
-                check_stdio_upstream(command, &config.upstream.args),
-            )
-            .await
-            {
-                Ok(Ok(())) => println!("✓ Upstream is reachable and responding"),
-                Ok(Err(e)) => anyhow::bail!("✗ Upstream check failed: {}", e),
-                Err(_) => anyhow::bail!("✗ Upstream check timed out after {}s", timeout),
+            println!("Args:      {:?}", args);
+            if let Some(cwd) = &options.cwd {
+                println!("Cwd:       {}", cwd.display());
             }
         }
-        mcp_guard_core::config::TransportType::Http => {
-            let url = config
-                .upstream
-                .url
-                .as_ref()
-                .ok_or_else(|| anyhow::anyhow!("HTTP transport requires 'url' in config"))?;
-
+        UpstreamTarget::Http { url, .. } => {
             println!("Transport: HTTP");
             println!("URL:       {}", url);
-            println!();
-
-            match tokio::time::timeout(timeout_duration, check_http_upstream(url)).await {
-                Ok(Ok(())) => println!("✓ Upstream is reachable"),
-                Ok(Err(e)) => anyhow::bail!("✗ Upstream check failed: {}", e),
-                Err(_) => anyhow::bail!("✗ Upstream check timed out after {}s", timeout),
-            }
         }
-        mcp_guard_core::config::TransportType::Sse => {
-            let url = config
-                .upstream
-                .url
-                .as_ref()
-                .ok_or_else(|| anyhow::anyhow!("SSE transport requires 'url' in config"))?;
-
+        UpstreamTarget::Sse { url, .. } => {
             println!("Transport: SSE");
             println!("URL:       {}", url);
-            println!();
+        }
+        UpstreamTarget::Unix(path) => {
+            println!("Transport: unix");
+            println!("Socket:    {}", path.display());
+        }
+        UpstreamTarget::Mock(mock) => {
+            println!("Transport: mock");
+            println!("Tools:     {}", mock.tools.len());
+        }
+    }
+    println!();
 
-            match tokio::time::timeout(timeout_duration, check_sse_upstream(url)).await {
-                Ok(Ok(())) => println!("✓ Upstream is reachable"),
-                Ok(Err(e)) => anyhow::bail!("✗ Upstream check failed: {}", e),
-                Err(_) => anyhow::bail!("✗ Upstream check timed out after {}s", timeout),
+    match check_upstream(&target, std::time::Duration::from_secs(timeout)).await {
+        Ok(check) => {
+            if let Some(name) = &check.server_name {
+                println!(
+                    "Server: {} v{}",
+                    name,
+                    check.server_version.as_deref().unwrap_or("unknown")
+                );
+            }
+            if let Some(status) = check.http_status {
+                println!("HTTP Status: {}", status);
+            }
+            if let Some(content_type) = &check.content_type {
+                println!("Content-Type: {}", content_type);
+            }
+            match target {
+                UpstreamTarget::Stdio { .. }
+                | UpstreamTarget::Unix(_)
+                | UpstreamTarget::Mock(_) => {
+                    println!("✓ Upstream is reachable and responding")
+                }
+                UpstreamTarget::Http { .. } | UpstreamTarget::Sse { .. } => {
+                    println!("✓ Upstream is reachable")
+                }
             }
         }
+        Err(TransportError::Timeout) => {
+            anyhow::bail!("✗ Upstream check timed out after {}s", timeout)
+        }
+        Err(e) => anyhow::bail!("✗ Upstream check failed: {}", e),
     }
 
     Ok(())
 }
 
-/// Validate that the user has a valid license for the features they're using
-///
-/// This is the CRITICAL security boundary that prevents users from bypassing licensing
-/// by compiling with `--features pro,enterprise` without paying.
-///
-/// This function is called BEFORE starting the server to ensure license compliance.
-#[allow(unused_variables)] // config unused in free tier (no pro/enterprise features)
-fn validate_license_for_config(config: &Config) -> anyhow::Result<()> {
-    // SECURITY: Allow bypassing license check in development mode
-    if std::env::var("MCP_GUARD_SKIP_LICENSE_CHECK").is_ok() {
-        tracing::warn!("Bypassing license check (MCP_GUARD_SKIP_LICENSE_CHECK is set)");
-        return Ok(());
+/// Handle the `doctor` command: run startup diagnostics and print the report
+async fn handle_doctor(
+    config_path: &std::path::PathBuf,
+    timeout: u64,
+    json: bool,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    let _guard = init_tracing(verbose, None, None);
+
+    // Validation runs as a check, so an invalid config still gets a full report
+    let mut config = Config::parse_file_with_env(config_path, std::env::vars())
+        .map_err(|e| anyhow::anyhow!("✗ Error loading config: {}", e))?;
+    config.apply_env_overrides();
+    configure_ssrf(&config.ssrf);
+    configure_egress(&config.egress);
+    // A bad signing key is reported by the config check
+    let _ = configure_request_signing(&config.request_signing);
+
+    let mut report = doctor::run(&config, std::time::Duration::from_secs(timeout)).await;
+    report.push(license_check(&config));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for check in &report.checks {
+            println!("{}  {:<32} {}", check.status, check.name, check.detail);
+        }
+        println!();
+        println!(
+            "{} passed, {} warning(s), {} failed",
+            report.count(CheckStatus::Pass),
+            report.count(CheckStatus::Warn),
+            report.count(CheckStatus::Fail)
+        );
     }
 
-    // Check if Pro features are being used
-    #[cfg(feature = "pro")]
-    if config.requires_pro_features() {
-        use mcp_guard_pro::license::ProLicense;
-
-        let license_key = std::env::var("MCP_GUARD_LICENSE_KEY").map_err(|_| {
-            anyhow::anyhow!(
-                "Pro features detected but no license key found.\n\n\
-                 Your configuration uses Pro tier features:\n\
-                 - OAuth 2.1 authentication\n\
-                 - JWT JWKS mode\n\
-                 - HTTP/SSE transports\n\n\
-                 Set your license key:\n\
-                   export MCP_GUARD_LICENSE_KEY=\"pro_xxx...\"\n\n\
-                 Don't have a license? Get one at:\n\
-                   https://mcp-guard.io/pricing\n\
-                   Pro tier: $12/month"
-            )
-        })?;
+    if report.status() == CheckStatus::Fail {
+        anyhow::bail!("✗ {} check(s) failed", report.count(CheckStatus::Fail));
+    }
+    Ok(())
+}
 
-        let license = ProLicense::validate(&license_key).map_err(|e| {
-            anyhow::anyhow!(
-                "Pro license validation failed: {}\n\n\
-                 Your license key may be:\n\
-                 - Expired\n\
-                 - Invalid\n\
-                 - For a different tier\n\n\
-                 To renew or upgrade:\n\
-                   https://mcp-guard.io/account\n\
-                 For support:\n\
-                   austin@botzr.dev",
-                e
-            )
-        })?;
+/// License check for `doctor`: the license must cover the features the config
+/// uses and not be about to expire
+fn license_check(config: &Config) -> DoctorCheck {
+    use mcp_guard_core::tier;
 
-        // Log successful validation
-        tracing::info!(
-            licensee = %license.payload.licensee,
-            expires_at = %license.payload.expires_at.format("%Y-%m-%d"),
-            "Pro license validated successfully"
+    let (licensed, expires_at) = match licensed_tier(config) {
+        Ok(licensed) => licensed,
+        Err(e) => {
+            let reason = e.to_string();
+            return DoctorCheck::fail("license", reason.lines().next().unwrap_or_default());
+        }
+    };
+    let enforcement = match tier::enforce_tier(&mut config.clone(), licensed) {
+        Ok(enforcement) => enforcement,
+        Err(e) => return DoctorCheck::fail("license", e.to_string()),
+    };
+    if !enforcement.disabled.is_empty() {
+        let disabled: Vec<&str> = enforcement.disabled.iter().map(|f| f.name()).collect();
+        return DoctorCheck::warn(
+            "license",
+            format!("{} tier; would disable {}", licensed, disabled.join(", ")),
         );
+    }
+    if let Some(expires_at) = expires_at {
+        let remaining = expires_at
+            .duration_since(std::time::SystemTime::now())
+            .map(|d| d.as_secs() / 86_400);
+        return match remaining {
+            Err(_) => DoctorCheck::fail("license", format!("{} license has expired", licensed)),
+            Ok(days) if days < EXPIRY_WARNING_DAYS as u64 => DoctorCheck::warn(
+                "license",
+                format!("{} license expires in {} day(s)", licensed, days),
+            ),
+            Ok(days) => DoctorCheck::pass(
+                "license",
+                format!("{} tier, expires in {} days", licensed, days),
+            ),
+        };
+    }
+    DoctorCheck::pass("license", format!("{} tier", licensed))
+}
 
-        // Warn if license is expiring soon
-        if license.is_expiring_soon() {
-            tracing::warn!(
-                days_remaining = license.days_until_expiry(),
-                "Pro license expires soon! Renew at https://mcp-guard.io/account"
-            );
+/// Options for the `test-call` command
+struct TestCallOptions {
+    token: Option<String>,
+    url: Option<String>,
+    server: Option<String>,
+    direct: bool,
+    tool: Option<String>,
+    args: Option<String>,
+    timeout: std::time::Duration,
+}
+
+/// Where `test-call` sends its requests
+enum TestCallTarget {
+    /// The gateway's MCP endpoint, authenticated with a bearer credential
+    Gateway {
+        client: reqwest::Client,
+        url: String,
+        token: String,
+    },
+    /// The upstream transport itself, with no auth or authorization applied
+    Direct(Arc<dyn Transport>),
+}
+
+impl TestCallTarget {
+    async fn call(&self, request: Message) -> anyhow::Result<Message> {
+        match self {
+            TestCallTarget::Gateway { client, url, token } => {
+                let response = client
+                    .post(url)
+                    .bearer_auth(token)
+                    .json(&request)
+                    .send()
+                    .await?;
+
+                let status = response.status();
+                if !status.is_success() {
+                    let body = response.text().await.unwrap_or_default();
+                    anyhow::bail!("gateway returned HTTP {}: {}", status, body.trim());
+                }
+                Ok(response.json().await?)
+            }
+            TestCallTarget::Direct(transport) => {
+                transport.send(request).await?;
+                Ok(transport.receive().await?)
+            }
         }
     }
+}
 
-    // Check if Enterprise features are being used
-    #[cfg(feature = "enterprise")]
-    if config.requires_enterprise_features() {
-        use mcp_guard_enterprise::license::EnterpriseLicense;
+/// Handle the `test-call` command: exercise a full MCP session and print the results.
+async fn handle_test_call(
+    config_path: &std::path::PathBuf,
+    options: TestCallOptions,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    let _guard = init_tracing(verbose, None, None);
+
+    let arguments = match options.args.as_deref() {
+        Some(raw) => {
+            let value: serde_json::Value = serde_json::from_str(raw)
+                .map_err(|e| anyhow::anyhow!("--args is not valid JSON: {}", e))?;
+            if !value.is_object() {
+                anyhow::bail!("--args must be a JSON object");
+            }
+            value
+        }
+        None => serde_json::json!({}),
+    };
 
-        // Enterprise licenses are validated via keygen.sh (online with offline cache)
-        let license = tokio::task::block_in_place(|| {
+    // A running gateway needs no local config
+    if let Some(url) = options.url {
+        let target = gateway_target(url, options.token, options.timeout)?;
+        return run_test_call(&target, options.tool.as_deref(), arguments).await;
+    }
+
+    let mut config = Config::from_file(config_path)
+        .map_err(|e| anyhow::anyhow!("Error loading config: {}", e))?;
+
+    if options.direct {
+        println!("Mode:      direct (authentication and authorization are skipped)");
+        let transport = connect_direct_transport(&config, options.server.as_deref()).await?;
+        let target = TestCallTarget::Direct(transport.clone());
+        let result = tokio::time::timeout(
+            options.timeout,
+            run_test_call(&target, options.tool.as_deref(), arguments),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(anyhow::anyhow!(
+                "timed out after {}s",
+                options.timeout.as_secs()
+            ))
+        });
+        let _ = transport.close().await;
+        return result;
+    }
+
+    // SECURITY: the in-process gateway is held to the same license checks as `run`
+    let license_expires_at = enforce_license(&mut config)?;
+    let path = gateway_mcp_path(&config, options.server.as_deref())?;
+
+    let BootstrapResult {
+        state,
+        audit_handle,
+        shutdown_token,
+    } = bootstrap(config, license_expires_at).await?;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server_task = tokio::spawn(server::serve(listener, state));
+    println!("Mode:      in-process gateway on {}", addr);
+
+    let result = match gateway_target(
+        format!("http://{}{}", addr, path),
+        options.token,
+        options.timeout,
+    ) {
+        Ok(target) => run_test_call(&target, options.tool.as_deref(), arguments).await,
+        Err(e) => Err(e),
+    };
+
+    server_task.abort();
+    shutdown_token.cancel();
+    audit_handle.shutdown().await;
+    result
+}
+
+/// Build a gateway target, requiring a credential
+fn gateway_target(
+    url: String,
+    token: Option<String>,
+    timeout: std::time::Duration,
+) -> anyhow::Result<TestCallTarget> {
+    let token = token.ok_or_else(|| {
+        anyhow::anyhow!(
+            "test-call needs a credential: pass --token or set MCP_GUARD_TOKEN \
+             (or use --direct to skip the gateway)"
+        )
+    })?;
+    let client = reqwest::Client::builder().timeout(timeout).build()?;
+    println!("Endpoint:  {}", url);
+    Ok(TestCallTarget::Gateway { client, url, token })
+}
+
+/// MCP endpoint path for the configured routing mode
+fn gateway_mcp_path(config: &Config, server: Option<&str>) -> anyhow::Result<String> {
+    if !config.is_multi_server() || config.is_aggregated() {
+        if server.is_some() {
+            anyhow::bail!("--server only applies to multi-server route mode");
+        }
+        return Ok("/mcp".to_string());
+    }
+
+    let route = find_server_route(config, server)?;
+    Ok(format!("/mcp/{}", route.name))
+}
+
+/// Look up a named server route, listing the options when the name is missing or wrong
+fn find_server_route<'a>(
+    config: &'a Config,
+    server: Option<&str>,
+) -> anyhow::Result<&'a ServerRouteConfig> {
+    let names: Vec<&str> = config
+        .upstream
+        .servers
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect();
+    let name = server.ok_or_else(|| {
+        anyhow::anyhow!(
+            "multi-server config: choose a route with --server ({})",
+            names.join(", ")
+        )
+    })?;
+    config
+        .upstream
+        .servers
+        .iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "unknown server '{}' (available: {})",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// Load the single-server upstream's TLS settings, if configured
+fn load_upstream_tls(config: &Config) -> anyhow::Result<Option<UpstreamTls>> {
+    config
+        .upstream
+        .tls
+        .as_ref()
+        .map(UpstreamTls::load)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Failed to load upstream TLS settings: {}", e))
+}
+
+/// Connect to the upstream transport without the gateway in front
+async fn connect_direct_transport(
+    config: &Config,
+    server: Option<&str>,
+) -> anyhow::Result<Arc<dyn Transport>> {
+    configure_ssrf(&config.ssrf);
+    configure_egress(&config.egress);
+    configure_request_signing(&config.request_signing)
+        .map_err(|e| anyhow::anyhow!("Failed to load request signing key: {}", e))?;
+    if config.is_multi_server() {
+        let route = find_server_route(config, server)?.clone();
+        let name = route.name.clone();
+        let router = ServerRouter::new(vec![route])
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to '{}': {}", name, e))?;
+        let route = router
+            .find_route_by_name(&name)
+            .ok_or_else(|| anyhow::anyhow!("route '{}' was not initialized", name))?;
+        return Ok(route.transport.clone());
+    }
+    if server.is_some() {
+        anyhow::bail!("--server only applies to multi-server configs");
+    }
+
+    let upstream = &config.upstream;
+    let transport: Arc<dyn Transport> = match upstream.transport {
+        TransportType::Stdio => {
+            let command = upstream
+                .command
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("stdio transport requires 'command' in config"))?;
+            Arc::new(
+                StdioTransport::spawn_with(
+                    command,
+                    &upstream.args,
+                    StdioOptions::from_upstream(upstream),
+                )
+                .await?,
+            )
+        }
+        TransportType::Http => {
+            let url = upstream
+                .url
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("HTTP transport requires 'url' in config"))?;
+            let transport = HttpTransport::new(url.clone()).await?;
+            match load_upstream_tls(config)? {
+                Some(tls) => Arc::new(transport.with_tls(&tls)?),
+                None => Arc::new(transport),
+            }
+        }
+        TransportType::Sse => {
+            let url = upstream
+                .url
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("SSE transport requires 'url' in config"))?;
+            let transport = SseTransport::connect(url.clone()).await?;
+            match load_upstream_tls(config)? {
+                Some(tls) => Arc::new(transport.with_tls(&tls)?),
+                None => Arc::new(transport),
+            }
+        }
+        TransportType::Unix => {
+            let path = upstream.socket_path.as_ref().ok_or_else(|| {
+                anyhow::anyhow!("unix transport requires 'socket_path' in config")
+            })?;
+            Arc::new(UnixSocketTransport::connect(path).await?)
+        }
+        TransportType::Mock => Arc::new(MockUpstreamTransport::new(upstream.mock.clone())),
+    };
+    Ok(transport)
+}
+
+/// Run initialize, tools/list and an optional tools/call against a target
+async fn run_test_call(
+    target: &TestCallTarget,
+    tool: Option<&str>,
+    arguments: serde_json::Value,
+) -> anyhow::Result<()> {
+    println!();
+
+    let initialize = Message::request(
+        1,
+        "initialize",
+        Some(serde_json::json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {
+                "name": "mcp-guard-test-call",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        })),
+    );
+    let response = test_call_step(target, "initialize", initialize).await?;
+    if let Some(server_info) = response.get("serverInfo") {
+        println!(
+            "  Server: {} v{}",
+            server_info
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown"),
+            server_info
+                .get("version")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+        );
+    }
+    println!();
+
+    let response = test_call_step(
+        target,
+        "tools/list",
+        Message::request(2, "tools/list", None),
+    )
+    .await?;
+    let tools = response
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .cloned()
+        .unwrap_or_default();
+    println!("  {} tool(s) visible to this identity:", tools.len());
+    for tool in &tools {
+        let name = tool.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+        match tool.get("description").and_then(|v| v.as_str()) {
+            Some(description) => println!("    - {}: {}", name, description),
+            None => println!("    - {}", name),
+        }
+    }
+
+    if let Some(tool) = tool {
+        println!();
+        let call = Message::request(
+            3,
+            "tools/call",
+            Some(serde_json::json!({ "name": tool, "arguments": arguments })),
+        );
+        let response = test_call_step(target, "tools/call", call).await?;
+        println!("{}", serde_json::to_string_pretty(&response)?);
+    }
+
+    Ok(())
+}
+
+/// Send one request, print its status line and return the JSON-RPC result
+async fn test_call_step(
+    target: &TestCallTarget,
+    label: &str,
+    request: Message,
+) -> anyhow::Result<serde_json::Value> {
+    let started = Instant::now();
+    let response = match target.call(request).await {
+        Ok(response) => response,
+        Err(e) => anyhow::bail!("✗ {} failed: {}", label, e),
+    };
+    let elapsed = started.elapsed().as_millis();
+
+    if let Some(error) = response.error {
+        anyhow::bail!(
+            "✗ {} returned a JSON-RPC error ({}ms):\n{}",
+            label,
+            elapsed,
+            serde_json::to_string_pretty(&error)?
+        );
+    }
+    println!("✓ {} ({}ms)", label, elapsed);
+    Ok(response.result.unwrap_or(serde_json::Value::Null))
+}
+
+/// Options for the `replay` command
+struct ReplayOptions {
+    file: std::path::PathBuf,
+    server: Option<String>,
+    url: Option<String>,
+    method: Option<String>,
+    limit: Option<usize>,
+    timeout: std::time::Duration,
+}
+
+/// Handle the `replay` command: re-send captured requests and compare the responses.
+async fn handle_replay(
+    config_path: &std::path::PathBuf,
+    options: ReplayOptions,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    let _guard = init_tracing(verbose, None, None);
+
+    let config = Config::from_file(config_path)
+        .map_err(|e| anyhow::anyhow!("Error loading config: {}", e))?;
+    // Replayed responses get the same redaction as the captures they are compared with
+    let audit_rules = config.audit.effective_redaction_rules()?;
+    let redaction = capture_redaction(&config.capture, &audit_rules)
+        .map_err(|e| anyhow::anyhow!("Invalid capture redaction rule: {}", e))?;
+    let records = read_captures(&options.file)
+        .map_err(|e| anyhow::anyhow!("Error reading {}: {}", options.file.display(), e))?;
+
+    // Multi-server captures are replayed one route at a time
+    let server = if config.is_multi_server() && options.url.is_none() {
+        let route = find_server_route(&config, options.server.as_deref())?;
+        Some(route.name.clone())
+    } else {
+        options.server.clone()
+    };
+    let total = records.len();
+    let records: Vec<CaptureRecord> = records
+        .into_iter()
+        .filter(|r| server.is_none() || r.server == server)
+        .filter(|r| options.method.is_none() || r.method == options.method)
+        .take(options.limit.unwrap_or(usize::MAX))
+        .collect();
+    println!(
+        "Replaying {} of {} capture(s) from {}",
+        records.len(),
+        total,
+        options.file.display()
+    );
+
+    let transport: Arc<dyn Transport> = match options.url {
+        // The operator chose this endpoint explicitly, often a local build
+        Some(url) => {
+            println!("Target:    {}", url);
+            Arc::new(HttpTransport::new_unchecked(url))
+        }
+        None => connect_direct_transport(&config, server.as_deref()).await?,
+    };
+    let target = TestCallTarget::Direct(transport.clone());
+    let result = replay_captures(&target, &records, &redaction, &config, options.timeout).await;
+    let _ = transport.close().await;
+    result
+}
+
+/// Replay captures in order, printing one line per request and a summary
+async fn replay_captures(
+    target: &TestCallTarget,
+    records: &[CaptureRecord],
+    redaction: &CompiledRedactionRules,
+    config: &Config,
+    timeout: std::time::Duration,
+) -> anyhow::Result<()> {
+    println!();
+
+    // Stateful upstreams expect a session to start with initialize
+    if records.first().and_then(|r| r.method.as_deref()) != Some("initialize") {
+        let initialize = Message::request(
+            0,
+            "initialize",
+            Some(serde_json::json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": {
+                    "name": "mcp-guard-replay",
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            })),
+        );
+        match tokio::time::timeout(timeout, target.call(initialize)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => anyhow::bail!("✗ initialize failed: {}", e),
+            Err(_) => anyhow::bail!("✗ initialize timed out after {}s", timeout.as_secs()),
+        }
+    }
+
+    let (mut same, mut different, mut failed) = (0, 0, 0);
+    for record in records {
+        let method = record.method.as_deref().unwrap_or("?");
+        let mut request = record.request.clone();
+        // Aggregate-mode captures carry the namespaced tool name the client used
+        if let (true, Some(server)) = (config.is_aggregated(), record.server.as_deref()) {
+            let prefix = format!("{}{}", server, mcp_guard_core::router::NAMESPACE_SEPARATOR);
+            if let Some(params) = request.params.as_mut() {
+                if let Some(tool) = params
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .and_then(|n| n.strip_prefix(&prefix))
+                {
+                    params["name"] = serde_json::Value::String(tool.to_string());
+                }
+            }
+        }
+
+        let started = Instant::now();
+        let response = tokio::time::timeout(timeout, target.call(request))
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("timed out after {}s", timeout.as_secs())));
+        let elapsed = started.elapsed().as_millis();
+
+        match response {
+            Ok(response) => {
+                let response = redact_message(redaction, &response);
+                if responses_match(&record.response, &response) {
+                    same += 1;
+                    println!(
+                        "✓ {} {} ({}ms, captured {}ms)",
+                        record.id, method, elapsed, record.duration_ms
+                    );
+                } else {
+                    different += 1;
+                    println!(
+                        "≠ {} {} ({}ms): response differs",
+                        record.id, method, elapsed
+                    );
+                    println!("    captured: {}", serde_json::to_string(&record.response)?);
+                    println!("    replayed: {}", serde_json::to_string(&response)?);
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                println!("✗ {} {}: {}", record.id, method, e);
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{} replayed: {} same, {} different, {} failed",
+        records.len(),
+        same,
+        different,
+        failed
+    );
+    if different > 0 || failed > 0 {
+        anyhow::bail!("replay found {} regression(s)", different + failed);
+    }
+    Ok(())
+}
+
+/// Handle the `bench` command: load test the middleware stack and check targets.
+async fn handle_bench(
+    config_path: &std::path::PathBuf,
+    options: BenchOptions,
+    max_p99_ms: Option<f64>,
+    min_rps: Option<f64>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let config = Config::from_file(config_path)
+        .map_err(|e| anyhow::anyhow!("Error loading config: {}", e))?;
+    if options.requests == 0 || options.concurrency == 0 {
+        anyhow::bail!("--requests and --concurrency must be greater than zero");
+    }
+
+    if !json {
+        println!(
+            "Sending {} {} request(s), {} at a time, with {} byte results...",
+            options.requests, options.method, options.concurrency, options.payload_bytes
+        );
+    }
+    let report = bench::run(config, &options)
+        .await
+        .map_err(|e| anyhow::anyhow!("Benchmark failed: {}", e))?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        let latency = &report.latency_ms;
+        println!();
+        println!(
+            "Requests:    {} ({} ok, {} failed)",
+            report.requests, report.succeeded, report.errors
+        );
+        let statuses: Vec<String> = report
+            .statuses
+            .iter()
+            .map(|(status, count)| format!("{}×{}", status, count))
+            .collect();
+        println!("Statuses:    {}", statuses.join(", "));
+        println!("Duration:    {:.2}s", report.elapsed_secs);
+        println!("Throughput:  {:.0} req/s", report.requests_per_sec);
+        println!(
+            "Latency:     mean {:.2}ms, p50 {:.2}ms, p90 {:.2}ms, p99 {:.2}ms, max {:.2}ms",
+            latency.mean, latency.p50, latency.p90, latency.p99, latency.max
+        );
+    }
+
+    let mut missed = Vec::new();
+    if report.succeeded < report.requests {
+        missed.push(format!(
+            "{} of {} requests did not return 200",
+            report.requests - report.succeeded,
+            report.requests
+        ));
+    }
+    if let Some(max) = max_p99_ms.filter(|max| report.latency_ms.p99 > *max) {
+        missed.push(format!(
+            "p99 latency {:.2}ms exceeds {:.2}ms",
+            report.latency_ms.p99, max
+        ));
+    }
+    if let Some(min) = min_rps.filter(|min| report.requests_per_sec < *min) {
+        missed.push(format!(
+            "throughput {:.0} req/s is below {:.0} req/s",
+            report.requests_per_sec, min
+        ));
+    }
+    if !missed.is_empty() {
+        anyhow::bail!("benchmark missed its targets: {}", missed.join("; "));
+    }
+    Ok(())
+}
+
+/// Options for the `audit report` command
+struct AuditReportOptions {
+    since: Option<String>,
+    until: Option<String>,
+    identity: Option<String>,
+    format: String,
+    output: Option<std::path::PathBuf>,
+    file: Option<std::path::PathBuf>,
+}
+
+/// Handle the `audit report` command: aggregate audit files into a per-identity access report.
+fn handle_audit_report(
+    config_path: &std::path::PathBuf,
+    options: AuditReportOptions,
+) -> anyhow::Result<()> {
+    if options.format != "json" && options.format != "csv" {
+        anyhow::bail!("Unknown format '{}' (expected json or csv)", options.format);
+    }
+    let since = options
+        .since
+        .as_deref()
+        .map(parse_report_time)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --since: {}", e))?;
+    let until = options
+        .until
+        .as_deref()
+        .map(parse_report_time)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --until: {}", e))?;
+
+    let path = match options.file {
+        Some(path) => path,
+        None => {
+            let config = Config::from_file(config_path)
+                .map_err(|e| anyhow::anyhow!("Error loading config: {}", e))?;
+            config.audit.file.ok_or_else(|| {
+                anyhow::anyhow!("No audit file configured; set audit.file or pass --file")
+            })?
+        }
+    };
+    let files = audit_files(&path)
+        .map_err(|e| anyhow::anyhow!("Error finding audit files for {}: {}", path.display(), e))?;
+
+    let mut report = AccessReport::new(since, until);
+    if let Some(identity) = options.identity {
+        report = report.with_identity(identity);
+    }
+    for file in &files {
+        report
+            .read_file(file)
+            .map_err(|e| anyhow::anyhow!("Error reading {}: {}", file.display(), e))?;
+    }
+    let report = report.finish();
+    if report.skipped_lines > 0 {
+        eprintln!(
+            "warning: skipped {} line(s) that are not audit entries",
+            report.skipped_lines
+        );
+    }
+
+    let rendered = if options.format == "csv" {
+        report.to_csv()
+    } else {
+        report.to_json()? + "\n"
+    };
+    match options.output {
+        Some(output) => {
+            std::fs::write(&output, rendered)
+                .map_err(|e| anyhow::anyhow!("Error writing {}: {}", output.display(), e))?;
+            eprintln!(
+                "Wrote access report for {} identities to {}",
+                report.identities.len(),
+                output.display()
+            );
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
+/// Handle the `db` commands: apply, inspect or reset the database schema.
+async fn handle_db(config_path: &std::path::PathBuf, command: DbCommands) -> anyhow::Result<()> {
+    let config = Config::from_file(config_path)
+        .map_err(|e| anyhow::anyhow!("Error loading config: {}", e))?;
+    let mut database = config
+        .database_config()
+        .ok_or_else(|| anyhow::anyhow!("No database configured; set [database] or database_url"))?;
+    // Never migrate implicitly: `status` must report what is pending
+    database.migrate = false;
+    let db = mcp_guard_core::db::Database::connect(&database)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?;
+
+    match command {
+        DbCommands::Migrate => {
+            let pending = db
+                .migration_status()
+                .await?
+                .iter()
+                .filter(|m| !m.applied)
+                .count();
+            db.migrate()
+                .await
+                .map_err(|e| anyhow::anyhow!("Migration failed: {}", e))?;
+            println!("Applied {} migration(s)", pending);
+        }
+        DbCommands::Status => {
+            let status = db.migration_status().await?;
+            let mut problems = 0;
+            for migration in &status {
+                let state = if migration.checksum_mismatch {
+                    problems += 1;
+                    "modified"
+                } else if migration.applied {
+                    "applied"
+                } else {
+                    problems += 1;
+                    "pending"
+                };
+                println!(
+                    "{:<16} {:<10} {}",
+                    migration.version, state, migration.description
+                );
+            }
+            if problems > 0 {
+                anyhow::bail!(
+                    "{} migration(s) pending or modified; run `mcp-guard db migrate`",
+                    problems
+                );
+            }
+        }
+        DbCommands::Reset { yes } => {
+            if !yes {
+                anyhow::bail!("Refusing to delete all data without --yes");
+            }
+            db.reset()
+                .await
+                .map_err(|e| anyhow::anyhow!("Reset failed: {}", e))?;
+            println!("Database reset; all migrations re-applied");
+        }
+    }
+    Ok(())
+}
+
+/// Validate the license and enforce its tier on the configuration
+///
+/// This is the CRITICAL security boundary that prevents users from bypassing licensing
+/// by compiling with `--features pro,enterprise` without paying.
+///
+/// This function is called BEFORE starting the server to ensure license compliance.
+/// Paid features the license does not cover are either disabled with a warning
+/// (telemetry exports) or refuse startup (see [`tier::enforce_tier`]).
+///
+/// Returns when the validated license expires, if it does.
+fn enforce_license(config: &mut Config) -> anyhow::Result<Option<std::time::SystemTime>> {
+    use mcp_guard_core::tier;
+
+    let (licensed, expires_at) = licensed_tier(config)?;
+    let enforcement = tier::enforce_tier(config, licensed)?;
+    for feature in &enforcement.disabled {
+        eprintln!(
+            "warning: {} requires the {} tier and has been disabled (licensed tier: {})",
+            feature.name(),
+            feature.tier(),
+            licensed
+        );
+    }
+    Ok(expires_at)
+}
+
+/// Determine the tier granted by the license for the features the config uses,
+/// and when that license expires
+#[allow(unused_variables)] // config unused in free tier (no pro/enterprise features)
+fn licensed_tier(
+    config: &Config,
+) -> anyhow::Result<(mcp_guard_core::tier::Tier, Option<std::time::SystemTime>)> {
+    use mcp_guard_core::tier::Tier;
+
+    // SECURITY: Allow bypassing license check in development mode
+    if std::env::var("MCP_GUARD_SKIP_LICENSE_CHECK").is_ok() {
+        tracing::warn!("Bypassing license check (MCP_GUARD_SKIP_LICENSE_CHECK is set)");
+        return Ok((Tier::compiled(), None));
+    }
+
+    // Check if Enterprise features are being used
+    #[cfg(feature = "enterprise")]
+    if config.requires_enterprise_features() {
+        use mcp_guard_enterprise::license::EnterpriseLicense;
+
+        // Enterprise licenses are validated via keygen.sh (online with offline cache)
+        let result = tokio::task::block_in_place(|| {
             tokio::runtime::Handle::current()
                 .block_on(async { EnterpriseLicense::validate().await })
-        })
-        .map_err(|e| {
+        });
+
+        match result {
+            Ok(license) => {
+                // Log successful validation
+                tracing::info!(
+                    licensee = %license.data.licensee,
+                    seats = ?license.data.max_seats,
+                    from_cache = license.from_cache,
+                    "Enterprise license validated successfully"
+                );
+                return Ok((Tier::Enterprise, license.data.expires_at.map(Into::into)));
+            }
+            // Features that can be switched off are degraded by the caller
+            Err(e)
+                if mcp_guard_core::tier::gated_features(config)
+                    .iter()
+                    .filter(|feature| feature.tier() == Tier::Enterprise)
+                    .all(|feature| feature.degradable()) =>
+            {
+                eprintln!("warning: Enterprise license validation failed: {}", e);
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Enterprise license validation failed: {}\n\n\
+                     Your configuration uses Enterprise tier features:\n\
+                     - mTLS authentication\n\
+                     - Multi-server routing\n\
+                     - SIEM audit log shipping\n\
+                     - OpenTelemetry tracing\n\n\
+                     Set your license key:\n\
+                       export MCP_GUARD_LICENSE_KEY=\"ent_xxx...\"\n\n\
+                     Don't have a license? Get one at:\n\
+                       https://mcp-guard.io/pricing\n\
+                       Enterprise tier: $29/user/month\n\n\
+                     For support:\n\
+                       austin@botzr.dev",
+                    e
+                ));
+            }
+        }
+    }
+
+    // Check if Pro features are being used
+    #[cfg(feature = "pro")]
+    if config.requires_pro_features() {
+        use mcp_guard_pro::license::ProLicense;
+
+        let license_key = std::env::var("MCP_GUARD_LICENSE_KEY").map_err(|_| {
             anyhow::anyhow!(
-                "Enterprise license validation failed: {}\n\n\
-                 Your configuration uses Enterprise tier features:\n\
-                 - mTLS authentication\n\
-                 - Multi-server routing\n\
-                 - SIEM audit log shipping\n\
-                 - OpenTelemetry tracing\n\n\
+                "Pro features detected but no license key found.\n\n\
+                 Your configuration uses Pro tier features:\n\
+                 - OAuth 2.1 authentication\n\
+                 - JWT JWKS mode\n\
+                 - HTTP/SSE transports\n\n\
                  Set your license key:\n\
-                   export MCP_GUARD_LICENSE_KEY=\"ent_xxx...\"\n\n\
+                   export MCP_GUARD_LICENSE_KEY=\"pro_xxx...\"\n\n\
                  Don't have a license? Get one at:\n\
                    https://mcp-guard.io/pricing\n\
-                   Enterprise tier: $29/user/month\n\n\
+                   Pro tier: $12/month"
+            )
+        })?;
+
+        let license = ProLicense::validate(&license_key).map_err(|e| {
+            anyhow::anyhow!(
+                "Pro license validation failed: {}\n\n\
+                 Your license key may be:\n\
+                 - Expired\n\
+                 - Invalid\n\
+                 - For a different tier\n\n\
+                 To renew or upgrade:\n\
+                   https://mcp-guard.io/account\n\
                  For support:\n\
                    austin@botzr.dev",
                 e
@@ -760,14 +2188,23 @@ fn validate_license_for_config(config: &Config) -> anyhow::Result<()> {
 
         // Log successful validation
         tracing::info!(
-            licensee = %license.data.licensee,
-            seats = ?license.data.max_seats,
-            from_cache = license.from_cache,
-            "Enterprise license validated successfully"
+            licensee = %license.payload.licensee,
+            expires_at = %license.payload.expires_at.format("%Y-%m-%d"),
+            "Pro license validated successfully"
         );
+
+        // Warn if license is expiring soon
+        if license.is_expiring_soon() {
+            tracing::warn!(
+                days_remaining = license.days_until_expiry(),
+                "Pro license expires soon! Renew at https://mcp-guard.io/account"
+            );
+        }
+
+        return Ok((Tier::Pro, Some(license.payload.expires_at.into())));
     }
 
-    Ok(())
+    Ok((Tier::Free, None))
 }
 
 /// Handle the `run` command: start the MCP Guard server.
@@ -781,7 +2218,7 @@ async fn handle_run(
 
     // CRITICAL: Validate licenses BEFORE starting server
     // This prevents users from bypassing licensing by compiling with --features
-    validate_license_for_config(&config)?;
+    let license_expires_at = enforce_license(&mut config)?;
 
     // Override with CLI args
     if let Some(h) = host {
@@ -792,7 +2229,7 @@ async fn handle_run(
     }
 
     // Initialize tracing with OpenTelemetry (if configured)
-    let _tracing_guard = init_tracing(verbose, Some(&config.tracing));
+    let _tracing_guard = init_tracing(verbose, Some(&config.tracing), Some(&config.logging));
 
     // Log tracing configuration
     if config.tracing.enabled {
@@ -809,7 +2246,7 @@ async fn handle_run(
         state,
         audit_handle,
         shutdown_token,
-    } = bootstrap(config.clone()).await?;
+    } = bootstrap(config.clone(), license_expires_at).await?;
 
     // Print startup summary
     print_startup_summary(&config);
@@ -836,22 +2273,77 @@ async fn handle_run(
     Ok(())
 }
 
+/// Handle `run --stdio`: serve the guarded upstream over stdin/stdout.
+///
+/// Every message goes through the same pipeline as HTTP requests, authenticated
+/// with the configured token. Nothing but JSON-RPC may be written to stdout.
+async fn handle_run_stdio(
+    config_path: &std::path::PathBuf,
+    token: Option<String>,
+    server: Option<String>,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    let mut config = Config::from_file(config_path)?;
+
+    // CRITICAL: Validate licenses BEFORE starting server
+    let license_expires_at = enforce_license(&mut config)?;
+
+    let token = token.ok_or_else(|| {
+        anyhow::anyhow!("--stdio requires a token: pass --token or set MCP_GUARD_TOKEN")
+    })?;
+    if config.audit.stdout {
+        anyhow::bail!("audit.stdout cannot be used with --stdio: stdout carries the MCP protocol");
+    }
+
+    // stdout carries the protocol, so logs must go to stderr
+    config.logging.stderr = true;
+    let _tracing_guard = init_tracing(verbose, Some(&config.tracing), Some(&config.logging));
+
+    let BootstrapResult {
+        state,
+        audit_handle,
+        shutdown_token,
+    } = bootstrap(config, license_expires_at).await?;
+    let bridge = server::StdioBridge::new(state, &token, server.as_deref())?;
+
+    tracing::info!("MCP Guard serving over stdio");
+    tokio::select! {
+        result = bridge.serve_stdio() => {
+            result?;
+        }
+        _ = tokio::signal::ctrl_c() => {
+            tracing::info!("Received SIGINT, initiating graceful shutdown...");
+        }
+    }
+
+    shutdown_token.cancel();
+    audit_handle.shutdown().await;
+    Ok(())
+}
+
 /// Handle the `serve` command: run as an MCP server over stdio.
 ///
 /// This mode is designed for use with Claude Desktop or other MCP clients
 /// that spawn mcp-guard as a subprocess and communicate via stdin/stdout.
 async fn handle_serve(config_path: &std::path::PathBuf, verbose: bool) -> anyhow::Result<()> {
-    let config = Config::from_file(config_path)?;
+    let mut config = Config::from_file(config_path)?;
 
     // CRITICAL: Validate licenses BEFORE starting server
     // This prevents users from bypassing licensing by compiling with --features
-    validate_license_for_config(&config)?;
+    enforce_license(&mut config)?;
 
     // Initialize minimal tracing for stdio mode (logs go to stderr to not interfere with MCP)
-    let _tracing_guard = init_tracing(verbose, Some(&config.tracing));
+    config.logging.stderr = true;
+    let _tracing_guard = init_tracing(verbose, Some(&config.tracing), Some(&config.logging));
 
     // Initialize metrics (always available in serve mode)
     let metrics_handle = Some(std::sync::Arc::new(init_metrics()));
+    configure_label_limits(&config.metrics);
+    configure_telemetry_privacy(&config.telemetry_privacy);
+    configure_ssrf(&config.ssrf);
+    configure_egress(&config.egress);
+    configure_request_signing(&config.request_signing)
+        .map_err(|e| anyhow::anyhow!("Failed to load request signing key: {}", e))?;
 
     // Set up upstream transport if configured
     let upstream: Option<std::sync::Arc<dyn Transport>> = match config.upstream.transport {
@@ -862,7 +2354,12 @@ async fn handle_serve(config_path: &std::path::PathBuf, verbose: bool) -> anyhow
                     args = ?config.upstream.args,
                     "Connecting to upstream MCP server via stdio"
                 );
-                let transport = StdioTransport::spawn(command, &config.upstream.args).await?;
+                let transport = StdioTransport::spawn_with(
+                    command,
+                    &config.upstream.args,
+                    StdioOptions::from_upstream(&config.upstream),
+                )
+                .await?;
                 Some(std::sync::Arc::new(transport))
             } else {
                 tracing::warn!("Stdio transport configured but no command specified");
@@ -873,6 +2370,13 @@ async fn handle_serve(config_path: &std::path::PathBuf, verbose: bool) -> anyhow
             if let Some(url) = &config.upstream.url {
                 tracing::info!(url = %url, "Connecting to upstream MCP server via HTTP");
                 let transport = HttpTransport::new(url.clone()).await?;
+                let transport = match load_upstream_tls(&config)? {
+                    Some(tls) => transport.with_tls(&tls)?,
+                    None => transport,
+                };
+                let transport = transport.with_dns_refresh(std::time::Duration::from_secs(
+                    config.upstream.dns_refresh_secs,
+                ));
                 Some(std::sync::Arc::new(transport))
             } else {
                 tracing::warn!("HTTP transport configured but no URL specified");
@@ -883,12 +2387,37 @@ async fn handle_serve(config_path: &std::path::PathBuf, verbose: bool) -> anyhow
             if let Some(url) = &config.upstream.url {
                 tracing::info!(url = %url, "Connecting to upstream MCP server via SSE");
                 let transport = SseTransport::connect(url.clone()).await?;
+                let transport = match load_upstream_tls(&config)? {
+                    Some(tls) => transport.with_tls(&tls)?,
+                    None => transport,
+                };
+                let transport = transport.with_dns_refresh(std::time::Duration::from_secs(
+                    config.upstream.dns_refresh_secs,
+                ));
                 Some(std::sync::Arc::new(transport))
             } else {
                 tracing::warn!("SSE transport configured but no URL specified");
                 None
             }
         }
+        TransportType::Unix => {
+            if let Some(path) = &config.upstream.socket_path {
+                tracing::info!(
+                    path = %path.display(),
+                    "Connecting to upstream MCP server via unix socket"
+                );
+                let transport = UnixSocketTransport::connect(path).await?;
+                Some(std::sync::Arc::new(transport))
+            } else {
+                tracing::warn!("Unix transport configured but no socket_path specified");
+                None
+            }
+        }
+        TransportType::Mock => {
+            tracing::info!("Using mock upstream");
+            let transport = MockUpstreamTransport::new(config.upstream.mock.clone());
+            Some(std::sync::Arc::new(transport))
+        }
     };
 
     // Create and run MCP server
@@ -901,129 +2430,6 @@ async fn handle_serve(config_path: &std::path::PathBuf, verbose: bool) -> anyhow
     Ok(())
 }
 
-/// Check stdio upstream connectivity by spawning the process and sending an initialize request
-async fn check_stdio_upstream(command: &str, args: &[String]) -> anyhow::Result<()> {
-    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-    use tokio::process::Command;
-
-    // Spawn the upstream process
-    let mut child = Command::new(command)
-        .args(args)
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::null())
-        .spawn()?;
-
-    let mut stdin = child
-        .stdin
-        .take()
-        .ok_or_else(|| anyhow::anyhow!("Failed to open stdin"))?;
-    let stdout = child
-        .stdout
-        .take()
-        .ok_or_else(|| anyhow::anyhow!("Failed to open stdout"))?;
-    let mut reader = BufReader::new(stdout);
-
-    // Send MCP initialize request
-    let init_request = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "initialize",
-        "params": {
-            "protocolVersion": "2024-11-05",
-            "capabilities": {},
-            "clientInfo": {
-                "name": "mcp-guard-check",
-                "version": env!("CARGO_PKG_VERSION")
-            }
-        }
-    });
-
-    let msg = format!("{}\n", serde_json::to_string(&init_request)?);
-    stdin.write_all(msg.as_bytes()).await?;
-    stdin.flush().await?;
-
-    // Read response
-    let mut line = String::new();
-    reader.read_line(&mut line).await?;
-
-    if line.is_empty() {
-        return Err(anyhow::anyhow!("No response from upstream"));
-    }
-
-    // Parse response to verify it's valid JSON-RPC
-    let response: serde_json::Value = serde_json::from_str(&line)?;
-    if response.get("result").is_some() || response.get("error").is_some() {
-        // Valid JSON-RPC response
-        if let Some(result) = response.get("result") {
-            if let Some(server_info) = result.get("serverInfo") {
-                println!(
-                    "Server: {} v{}",
-                    server_info
-                        .get("name")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown"),
-                    server_info
-                        .get("version")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown")
-                );
-            }
-        }
-        Ok(())
-    } else {
-        Err(anyhow::anyhow!("Invalid JSON-RPC response: {}", line))
-    }
-}
-
-/// Check HTTP upstream connectivity by sending a simple request
-async fn check_http_upstream(url: &str) -> anyhow::Result<()> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()?;
-
-    // Try to send an empty POST to check if the server is responding
-    let response = client
-        .post(url)
-        .header("Content-Type", "application/json")
-        .body("{}")
-        .send()
-        .await?;
-
-    let status = response.status();
-    println!("HTTP Status: {}", status);
-
-    // Any response (even 400/500) means the server is reachable
-    Ok(())
-}
-
-/// Check SSE upstream connectivity by attempting to connect
-async fn check_sse_upstream(url: &str) -> anyhow::Result<()> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()?;
-
-    // Try to connect to the SSE endpoint
-    let response = client
-        .get(url)
-        .header("Accept", "text/event-stream")
-        .send()
-        .await?;
-
-    let status = response.status();
-    println!("HTTP Status: {}", status);
-
-    // Check if the content type suggests SSE
-    if let Some(content_type) = response.headers().get("content-type") {
-        println!(
-            "Content-Type: {}",
-            content_type.to_str().unwrap_or("unknown")
-        );
-    }
-
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1031,6 +2437,7 @@ mod tests {
     use tempfile::NamedTempFile;
 
     // Helper to create a minimal valid config for testing
+    #[cfg_attr(not(feature = "pro"), allow(dead_code))]
     fn create_test_config_http(url: &str) -> Config {
         let config_str = format!(
             r#"
@@ -1188,7 +2595,7 @@ enabled = false
 
         let config = create_test_config_http(&mock_server.uri());
 
-        let result = bootstrap(config).await;
+        let result = bootstrap(config, None).await;
         assert!(result.is_ok(), "bootstrap failed: {:?}", result.err());
 
         let bootstrap_result = result.unwrap();
@@ -1224,7 +2631,7 @@ key_hash = "abc123"
         std::fs::write(temp_file.path(), config_str).unwrap();
         let config = Config::from_file(&temp_file.path().to_path_buf()).unwrap();
 
-        let result = bootstrap(config).await;
+        let result = bootstrap(config, None).await;
         assert!(result.is_ok(), "bootstrap failed: {:?}", result.err());
 
         let bootstrap_result = result.unwrap();
@@ -1237,7 +2644,7 @@ key_hash = "abc123"
         // Use stdio transport which is available in free tier
         let config = create_test_config_stdio();
 
-        let result = bootstrap(config).await;
+        let result = bootstrap(config, None).await;
         assert!(result.is_ok(), "bootstrap failed: {:?}", result.err());
 
         let bootstrap_result = result.unwrap();
@@ -1245,75 +2652,6 @@ key_hash = "abc123"
         bootstrap_result.audit_handle.shutdown().await;
     }
 
-    #[tokio::test]
-    async fn test_check_http_upstream_success() {
-        use wiremock::matchers::method;
-        use wiremock::{Mock, MockServer, ResponseTemplate};
-
-        let mock_server = MockServer::start().await;
-        Mock::given(method("POST"))
-            .respond_with(ResponseTemplate::new(200))
-            .mount(&mock_server)
-            .await;
-
-        let result = check_http_upstream(&mock_server.uri()).await;
-        assert!(result.is_ok());
-    }
-
-    #[tokio::test]
-    async fn test_check_http_upstream_server_error() {
-        use wiremock::matchers::method;
-        use wiremock::{Mock, MockServer, ResponseTemplate};
-
-        let mock_server = MockServer::start().await;
-        Mock::given(method("POST"))
-            .respond_with(ResponseTemplate::new(500))
-            .mount(&mock_server)
-            .await;
-
-        // Even 500 is "reachable"
-        let result = check_http_upstream(&mock_server.uri()).await;
-        assert!(result.is_ok());
-    }
-
-    #[tokio::test]
-    async fn test_check_sse_upstream_success() {
-        use wiremock::matchers::method;
-        use wiremock::{Mock, MockServer, ResponseTemplate};
-
-        let mock_server = MockServer::start().await;
-        Mock::given(method("GET"))
-            .respond_with(
-                ResponseTemplate::new(200).insert_header("content-type", "text/event-stream"),
-            )
-            .mount(&mock_server)
-            .await;
-
-        let result = check_sse_upstream(&mock_server.uri()).await;
-        assert!(result.is_ok());
-    }
-
-    #[tokio::test]
-    async fn test_check_sse_upstream_no_content_type() {
-        use wiremock::matchers::method;
-        use wiremock::{Mock, MockServer, ResponseTemplate};
-
-        let mock_server = MockServer::start().await;
-        Mock::given(method("GET"))
-            .respond_with(ResponseTemplate::new(200))
-            .mount(&mock_server)
-            .await;
-
-        let result = check_sse_upstream(&mock_server.uri()).await;
-        assert!(result.is_ok());
-    }
-
-    #[tokio::test]
-    async fn test_check_stdio_upstream_invalid_command() {
-        let result = check_stdio_upstream("/nonexistent/command", &[]).await;
-        assert!(result.is_err());
-    }
-
     #[tokio::test]
     async fn test_run_cli_check_upstream_missing_config() {
         let cli = Cli {
@@ -1376,7 +2714,7 @@ audience = "test"
         std::fs::write(temp_file.path(), config_str).unwrap();
         let config = Config::from_file(&temp_file.path().to_path_buf()).unwrap();
 
-        let result = bootstrap(config).await;
+        let result = bootstrap(config, None).await;
         assert!(result.is_ok(), "bootstrap failed: {:?}", result.err());
 
         let bootstrap_result = result.unwrap();
@@ -1430,7 +2768,7 @@ transport = { type = "http", url = "not-a-valid-url:::" }
         std::fs::write(temp_file.path(), config_str).unwrap();
 
         if let Ok(config) = Config::from_file(&temp_file.path().to_path_buf()) {
-            let result = bootstrap(config).await;
+            let result = bootstrap(config, None).await;
             assert!(result.is_err());
         }
     }
@@ -1440,6 +2778,173 @@ transport = { type = "http", url = "not-a-valid-url:::" }
     // -------------------------------------------------------------------------
 
     #[cfg(not(feature = "pro"))]
+    #[test]
+    fn test_gateway_mcp_path() {
+        let config = create_test_config_stdio();
+        assert_eq!(gateway_mcp_path(&config, None).unwrap(), "/mcp");
+        assert!(gateway_mcp_path(&config, Some("github")).is_err());
+
+        let mut config = create_test_config_stdio();
+        config.upstream.servers = vec![ServerRouteConfig {
+            name: "github".to_string(),
+            path_prefix: "/github".to_string(),
+            transport: TransportType::Stdio,
+            command: Some("/bin/echo".to_string()),
+            args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
+            url: None,
+            strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
+        }];
+        assert_eq!(
+            gateway_mcp_path(&config, Some("github")).unwrap(),
+            "/mcp/github"
+        );
+        let err = gateway_mcp_path(&config, None).unwrap_err();
+        assert!(err.to_string().contains("--server (github)"));
+        let err = gateway_mcp_path(&config, Some("slack")).unwrap_err();
+        assert!(err.to_string().contains("unknown server 'slack'"));
+
+        config.upstream.mode = mcp_guard_core::config::UpstreamMode::Aggregate;
+        assert_eq!(gateway_mcp_path(&config, None).unwrap(), "/mcp");
+    }
+
+    #[tokio::test]
+    async fn test_run_test_call_against_gateway() {
+        use wiremock::matchers::{body_partial_json, header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("authorization", "Bearer mcp_test"))
+            .and(body_partial_json(
+                serde_json::json!({"method": "tools/list"}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "result": {"tools": [{"name": "read_file"}]}
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(
+                serde_json::json!({"method": "tools/call"}),
+            ))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+                "error": "Tool 'write_file' not allowed"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {"serverInfo": {"name": "test", "version": "1.0"}}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let target = gateway_target(
+            format!("{}/mcp", mock_server.uri()),
+            Some("mcp_test".to_string()),
+            std::time::Duration::from_secs(5),
+        )
+        .unwrap();
+
+        assert!(run_test_call(&target, None, serde_json::json!({}))
+            .await
+            .is_ok());
+
+        let err = run_test_call(&target, Some("write_file"), serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("tools/call failed"));
+        assert!(err.to_string().contains("403"));
+    }
+
+    #[tokio::test]
+    async fn test_replay_captures_reports_differences() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let tools = serde_json::json!({"tools": [{"name": "read_file"}]});
+        for (rpc_method, result) in [
+            ("initialize", serde_json::json!({})),
+            ("tools/list", tools.clone()),
+            ("tools/call", serde_json::json!({"text": "v2"})),
+        ] {
+            Mock::given(method("POST"))
+                .and(body_partial_json(serde_json::json!({"method": rpc_method})))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 99,
+                    "result": result
+                })))
+                .mount(&mock_server)
+                .await;
+        }
+
+        let record = |method: &str, result: serde_json::Value| -> CaptureRecord {
+            serde_json::from_value(serde_json::json!({
+                "id": method,
+                "timestamp": "2026-01-01T00:00:00Z",
+                "identity_id": "user",
+                "method": method,
+                "duration_ms": 1,
+                "request": {"jsonrpc": "2.0", "id": 1, "method": method},
+                "response": {"jsonrpc": "2.0", "id": 1, "result": result}
+            }))
+            .unwrap()
+        };
+        let target = TestCallTarget::Direct(Arc::new(HttpTransport::new_unchecked(format!(
+            "{}/mcp",
+            mock_server.uri()
+        ))));
+        let config = create_test_config_stdio();
+        let rules = CompiledRedactionRules::empty();
+        let timeout = std::time::Duration::from_secs(5);
+
+        let unchanged = [record("tools/list", tools)];
+        replay_captures(&target, &unchanged, &rules, &config, timeout)
+            .await
+            .unwrap();
+
+        let changed = [record("tools/call", serde_json::json!({"text": "v1"}))];
+        let err = replay_captures(&target, &changed, &rules, &config, timeout)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("1 regression(s)"));
+    }
+
+    #[test]
+    fn test_gateway_target_requires_token() {
+        let result = gateway_target(
+            "http://127.0.0.1:3000/mcp".to_string(),
+            None,
+            std::time::Duration::from_secs(5),
+        );
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_tier_validation_http_requires_pro() {
         let config_str = r#"
@@ -74,6 +74,97 @@ fn test_validate_invalid_config() {
         .failure();
 }
 
+#[test]
+fn test_config_lint_clean_config() {
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("config")
+        .arg("lint")
+        .arg("--config")
+        .arg("tests/fixtures/valid_config.toml")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No issues found"));
+}
+
+#[test]
+fn test_config_lint_fix() {
+    let config_content = r#"# Gateway config
+[server]
+host = "0.0.0.0"
+port = 3000
+
+[upstream]
+transport = "stdio"
+command = "echo"
+
+[auth.mtls]
+enabled = true
+"#;
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("mcp-guard.toml");
+    fs::write(&config_path, config_content).unwrap();
+
+    // Error-level findings fail the lint
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("config")
+        .arg("lint")
+        .arg("--config")
+        .arg(&config_path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("error[mtls-untrusted-proxy]"))
+        .stdout(predicate::str::contains("warning[public-bind-without-tls]"))
+        .stdout(predicate::str::contains("2 can be fixed automatically"));
+
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("config")
+        .arg("lint")
+        .arg("--fix")
+        .arg("--config")
+        .arg(&config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 2 fix(es)"))
+        .stdout(predicate::str::contains("No issues found"));
+
+    let fixed = fs::read_to_string(&config_path).unwrap();
+    assert!(fixed.starts_with("# Gateway config"));
+    assert!(fixed.contains(r#"host = "127.0.0.1""#));
+    assert!(fixed.contains("trusted_proxy_ips"));
+}
+
+#[test]
+fn test_config_schema() {
+    let mut cmd = common::cargo_bin("mcp-guard");
+    let output = cmd.arg("config").arg("schema").output().unwrap();
+    assert!(output.status.success());
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["title"], "Config");
+    assert!(schema["properties"]["upstream"].is_object());
+}
+
+#[test]
+fn test_validate_warns_on_unknown_keys() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("mcp-guard.toml");
+    fs::write(
+        &config_path,
+        "[server]\nprot = 3000\n\n[upstream]\ntransport = \"stdio\"\ncommand = \"echo\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("validate")
+        .arg("--config")
+        .arg(&config_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "unknown key 'server.prot' (did you mean 'port'?)",
+        ));
+}
+
 #[test]
 fn test_keygen() {
     let mut cmd = common::cargo_bin("mcp-guard");
@@ -92,6 +183,59 @@ fn test_keygen() {
     assert!(stdout.contains("id = \"test-user\""));
 }
 
+#[test]
+fn test_keys_rotate() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("mcp-guard.toml");
+    fs::write(
+        &config_path,
+        r#"[upstream]
+transport = "stdio"
+command = "echo"
+
+[[auth.api_keys]]
+id = "ci"
+key_hash = "old-hash"
+allowed_tools = ["read_file"]
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("keys")
+        .arg("rotate")
+        .arg("--id")
+        .arg("ci")
+        .arg("--grace-hours")
+        .arg("2")
+        .arg("--config")
+        .arg(&config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("New API Key"));
+
+    let config = mcp_guard_core::config::Config::parse_file(&config_path).unwrap();
+    let keys = &config.auth.api_keys;
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0].key_hash, "old-hash");
+    assert!(keys[0].expires_at.is_some());
+    assert_eq!(keys[1].id, "ci");
+    assert_ne!(keys[1].key_hash, "old-hash");
+    assert_eq!(keys[1].allowed_tools, vec!["read_file".to_string()]);
+    assert!(keys[1].expires_at.is_none());
+
+    // Unknown ids leave the file untouched
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("keys")
+        .arg("rotate")
+        .arg("--id")
+        .arg("missing")
+        .arg("--config")
+        .arg(&config_path)
+        .assert()
+        .failure();
+}
+
 #[test]
 fn test_hash_key() {
     // Generate a key first
@@ -229,3 +373,173 @@ enabled = false
         .failure()
         .stderr(predicate::str::contains("Pro license"));
 }
+
+fn write_test_call_config(temp: &tempfile::TempDir) -> std::path::PathBuf {
+    let script_path = std::env::current_dir()
+        .unwrap()
+        .join("tests/fixtures/echo_server.sh");
+    let config_content = format!(
+        r#"
+[server]
+host = "127.0.0.1"
+port = 3000
+
+[upstream]
+transport = "stdio"
+command = "{}"
+args = []
+
+[rate_limit]
+enabled = false
+
+[[auth.api_keys]]
+id = "test"
+key_hash = "K7gNU3sdo+OL0wNhqoVWhr3g6s1xYv72ol/pe/Unols=" # hash of "secret"
+"#,
+        script_path.display()
+    );
+    let config_path = temp.path().join("mcp-guard.toml");
+    fs::write(&config_path, config_content).unwrap();
+    config_path
+}
+
+#[test]
+fn test_test_call_through_in_process_gateway() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = write_test_call_config(&temp);
+
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("test-call")
+        .arg("--config")
+        .arg(config_path)
+        .arg("--token")
+        .arg("secret")
+        .arg("--tool")
+        .arg("echo")
+        .arg("--args")
+        .arg(r#"{"text": "hi"}"#)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("in-process gateway"))
+        .stdout(predicate::str::contains("✓ initialize"))
+        .stdout(predicate::str::contains("✓ tools/list"))
+        .stdout(predicate::str::contains("✓ tools/call"));
+}
+
+#[test]
+fn test_test_call_rejected_credential() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = write_test_call_config(&temp);
+
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("test-call")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--token")
+        .arg("wrong")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("initialize failed"))
+        .stderr(predicate::str::contains("401"));
+
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("test-call")
+        .arg("--config")
+        .arg(&config_path)
+        .env_remove("MCP_GUARD_TOKEN")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--token"));
+}
+
+#[test]
+fn test_test_call_direct() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = write_test_call_config(&temp);
+
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("test-call")
+        .arg("--config")
+        .arg(config_path)
+        .arg("--direct")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("direct"))
+        .stdout(predicate::str::contains("✓ tools/list"));
+}
+
+#[test]
+fn test_run_stdio_passthrough() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = write_test_call_config(&temp);
+
+    let mut cmd = Command::from_std(common::cargo_bin("mcp-guard"));
+    let output = cmd
+        .arg("run")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--stdio")
+        .arg("--token")
+        .arg("secret")
+        .write_stdin("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}\n")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // stdout carries only the protocol: one JSON-RPC line per request
+    let stdout = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "unexpected stdout: {}", stdout);
+    let response: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(response["id"], 1);
+
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("run")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--stdio")
+        .env_remove("MCP_GUARD_TOKEN")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MCP_GUARD_TOKEN"));
+}
+
+#[test]
+fn test_bench() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = write_test_call_config(&temp);
+
+    let mut cmd = common::cargo_bin("mcp-guard");
+    let output = cmd
+        .arg("bench")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--requests")
+        .arg("50")
+        .arg("--concurrency")
+        .arg("4")
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["succeeded"], 50);
+    assert!(report["latency_ms"]["p99"].as_f64().unwrap() > 0.0);
+
+    // An unreachable target fails the run
+    let mut cmd = common::cargo_bin("mcp-guard");
+    cmd.arg("bench")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("-n")
+        .arg("10")
+        .arg("--min-rps")
+        .arg("1000000000")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("below"));
+}
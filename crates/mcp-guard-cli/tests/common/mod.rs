@@ -1,23 +1,7 @@
-use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::time::Duration;
 use tokio::net::TcpListener;
 
-pub struct TestContext {
-    pub config_path: PathBuf,
-    pub work_dir: tempfile::TempDir,
-}
-
-pub async fn setup_test_context() -> TestContext {
-    let work_dir = tempfile::tempdir().expect("Failed to create temp dir");
-    let config_path = work_dir.path().join("config.toml");
-
-    TestContext {
-        config_path,
-        work_dir,
-    }
-}
-
 pub async fn get_free_port() -> u16 {
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
     listener.local_addr().unwrap().port()
@@ -5,7 +5,6 @@
 use reqwest::{header, StatusCode};
 use serde_json::{json, Value};
 use std::fs;
-use std::time::Duration;
 
 mod common;
 
@@ -74,49 +73,6 @@ key_hash = "{}"
     )
 }
 
-/// Create a config with multiple API keys with different permissions
-fn config_with_multiple_keys() -> String {
-    let cwd = std::env::current_dir().unwrap();
-    let script_path = cwd.join("tests/fixtures/echo_server.sh");
-    let admin_hash = mcp_guard_core::cli::hash_api_key("admin-key");
-    let restricted_hash = mcp_guard_core::cli::hash_api_key("restricted-key");
-
-    format!(
-        r#"
-[server]
-host = "127.0.0.1"
-port = PORT_PLACEHOLDER
-
-[upstream]
-transport = "stdio"
-command = "{}"
-args = []
-
-[rate_limit]
-enabled = true
-requests_per_second = 10
-burst_size = 5
-
-[audit]
-enabled = true
-stdout = false
-
-[[auth.api_keys]]
-id = "admin"
-key_hash = "{}"
-
-[[auth.api_keys]]
-id = "restricted"
-key_hash = "{}"
-allowed_tools = ["read_file", "list_directory"]
-rate_limit = 5
-"#,
-        script_path.display(),
-        admin_hash,
-        restricted_hash
-    )
-}
-
 /// Create a config with CORS enabled
 fn config_with_cors(api_key_hash: &str) -> String {
     let cwd = std::env::current_dir().unwrap();
@@ -565,9 +521,9 @@ async fn test_method_not_allowed_for_wrong_http_method() {
 
     let client = reqwest::Client::new();
 
-    // GET to /mcp should fail (only POST allowed)
+    // PUT to /mcp should fail (only GET and POST allowed)
     let resp = client
-        .get(format!("{}/mcp", base_url))
+        .put(format!("{}/mcp", base_url))
         .header(header::AUTHORIZATION, format!("Bearer {}", api_key))
         .send()
         .await
@@ -575,6 +531,55 @@ async fn test_method_not_allowed_for_wrong_http_method() {
 
     assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
 
+    // GET asking for a standalone event stream is not supported either
+    let resp = client
+        .get(format!("{}/mcp", base_url))
+        .header(header::ACCEPT, "text/event-stream")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    child.kill().unwrap();
+}
+
+#[tokio::test]
+async fn test_get_mcp_describes_capabilities() {
+    let hash = mcp_guard_core::cli::hash_api_key("test-secret-key");
+    let (mut child, base_url, _) =
+        spawn_server_with_config(&basic_config_with_api_key(&hash)).await;
+
+    let client = reqwest::Client::new();
+
+    // No credentials needed to discover how to authenticate
+    let resp = client
+        .get(format!("{}/mcp", base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = resp.json().await.unwrap();
+    assert!(!body["protocol_versions"].as_array().unwrap().is_empty());
+    assert_eq!(body["auth"]["required"], true);
+    assert_eq!(body["auth"]["providers"][0]["type"], "api_key");
+    assert_eq!(
+        body["auth"]["protected_resource"]["resource"],
+        format!("{}/mcp", base_url)
+    );
+
+    let resp = client
+        .get(format!(
+            "{}/.well-known/oauth-protected-resource/mcp",
+            base_url
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let metadata: Value = resp.json().await.unwrap();
+    assert_eq!(metadata, body["auth"]["protected_resource"]);
+
     child.kill().unwrap();
 }
 
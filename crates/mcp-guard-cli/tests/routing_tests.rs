@@ -498,9 +498,9 @@ async fn test_method_not_allowed_on_routed_endpoint() {
 
     let client = reqwest::Client::new();
 
-    // GET to /mcp/server1 should fail (only POST allowed)
+    // PUT to /mcp/server1 should fail (only GET and POST allowed)
     let resp = client
-        .get(format!("{}/mcp/server1", base_url))
+        .put(format!("{}/mcp/server1", base_url))
         .header(header::AUTHORIZATION, "Bearer test-key")
         .send()
         .await
@@ -24,8 +24,12 @@ fn make_identity(id: &str, allowed_tools: Option<Vec<String>>) -> Identity {
         id: id.to_string(),
         name: None,
         allowed_tools,
+        allowed_resources: None,
+        allowed_prompts: None,
         rate_limit: None,
         claims: HashMap::new(),
+        tenant: None,
+        argument_constraints: None,
     }
 }
 
@@ -43,7 +47,18 @@ fn bench_api_key_auth(c: &mut Criterion) {
                     id: format!("user_{}", i),
                     key_hash: hash_api_key(&key),
                     allowed_tools: vec!["read".to_string(), "write".to_string()],
+                    allowed_resources: vec![],
+                    allowed_prompts: vec![],
                     rate_limit: None,
+                    network: None,
+                    tenant: None,
+                    description: None,
+                    not_before: None,
+                    expires_at: None,
+                    constraints: vec![],
+                    access_windows: vec![],
+                    grants: vec![],
+                    break_glass: false,
                 }
             })
             .collect();
@@ -56,7 +71,18 @@ fn bench_api_key_auth(c: &mut Criterion) {
             id: "test_user".to_string(),
             key_hash: valid_hash,
             allowed_tools: vec!["read".to_string()],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: Some(100),
+            network: None,
+            tenant: None,
+            description: None,
+            not_before: None,
+            expires_at: None,
+            constraints: vec![],
+            access_windows: vec![],
+            grants: vec![],
+            break_glass: false,
         });
 
         let provider = ApiKeyProvider::new(all_keys);
@@ -102,6 +128,9 @@ fn bench_rate_limiting(c: &mut Criterion) {
         requests_per_second: 1000,
         burst_size: 100,
         tool_limits: vec![],
+        schedules: Vec::new(),
+        warmup: Default::default(),
+        expose_in_tools_list: false,
     };
     let rate_limiter = RateLimitService::new(&config);
 
@@ -0,0 +1,148 @@
+//! Full middleware stack benchmarks
+//!
+//! Run with: cargo bench --bench stack
+//!
+//! Each iteration sends one request through the standalone router (network
+//! ACL, auth, rate limiting, authorization, audit) to an in-memory upstream,
+//! so the numbers are the gateway's per-request overhead. Compare runs with
+//! criterion's saved baselines:
+//!
+//! ```text
+//! cargo bench --bench stack -- --save-baseline main
+//! cargo bench --bench stack -- --baseline main
+//! ```
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::Request;
+use axum::Router;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mcp_guard_core::{
+    bench::EchoTransport,
+    cli::{generate_api_key, hash_api_key},
+    config::{ApiKeyConfig, Config},
+    server::Guard,
+    transport::Message,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Router in front of an echo upstream returning `payload_bytes` of text
+fn make_router(payload_bytes: usize) -> (Router, String) {
+    let mut config: Config = toml::from_str(
+        r#"
+        [upstream]
+        transport = "stdio"
+        command = "echo"
+
+        [rate_limit]
+        enabled = false
+        "#,
+    )
+    .unwrap();
+    let key = generate_api_key();
+    config.auth.api_keys.push(ApiKeyConfig {
+        id: "bench".to_string(),
+        key_hash: hash_api_key(&key),
+        allowed_tools: vec![],
+        allowed_resources: vec![],
+        allowed_prompts: vec![],
+        rate_limit: None,
+        network: None,
+        tenant: None,
+        description: None,
+        not_before: None,
+        expires_at: None,
+        constraints: vec![],
+        access_windows: vec![],
+        grants: vec![],
+        break_glass: false,
+    });
+
+    let router = Guard::builder(config)
+        .transport(Arc::new(EchoTransport::new(payload_bytes)))
+        .build()
+        .unwrap()
+        .into_router();
+    (router, key)
+}
+
+fn mcp_request(key: &str, body: &[u8]) -> Request<Body> {
+    let mut request = Request::post("/mcp")
+        .header("Authorization", format!("Bearer {}", key))
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_vec()))
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 3000))));
+    request
+}
+
+/// Benchmark tools/call through the full stack by result size
+fn bench_tools_call(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("stack/tools_call");
+    group.throughput(Throughput::Elements(1));
+
+    let body = serde_json::to_vec(&Message::request(
+        1,
+        "tools/call",
+        Some(serde_json::json!({"name": "echo", "arguments": {}})),
+    ))
+    .unwrap();
+
+    for payload_bytes in [128, 16 * 1024, 1024 * 1024] {
+        // Building may spawn background tasks, so do it inside the runtime
+        let (router, key) = runtime.block_on(async { make_router(payload_bytes) });
+        group.bench_with_input(
+            BenchmarkId::from_parameter(payload_bytes),
+            &payload_bytes,
+            |b, _| {
+                b.to_async(&runtime).iter(|| async {
+                    let response = router
+                        .clone()
+                        .oneshot(mcp_request(&key, &body))
+                        .await
+                        .unwrap();
+                    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                        .await
+                        .unwrap();
+                    black_box(bytes);
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Benchmark tools/list, which is parsed and filtered rather than passed through
+fn bench_tools_list(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("stack/tools_list");
+    group.throughput(Throughput::Elements(1));
+
+    let body = serde_json::to_vec(&Message::request(1, "tools/list", None)).unwrap();
+    let (router, key) = runtime.block_on(async { make_router(0) });
+    group.bench_function("single_tool", |b| {
+        b.to_async(&runtime).iter(|| async {
+            let response = router
+                .clone()
+                .oneshot(mcp_request(&key, &body))
+                .await
+                .unwrap();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            black_box(bytes);
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_tools_call, bench_tools_list);
+
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+-- Create oauth_clients table (dynamic client registration, RFC 7591)
+-- Rows are written by /oauth/register when oauth.registration.mode is 'local'.
+CREATE TABLE IF NOT EXISTS oauth_clients (
+    client_id TEXT PRIMARY KEY,
+    -- SHA-256 of the client secret; NULL for public clients
+    client_secret_hash TEXT,
+    client_name TEXT,
+    redirect_uris JSONB NOT NULL DEFAULT '[]',
+    -- Registered client metadata as returned to the client
+    metadata JSONB NOT NULL DEFAULT '{}',
+    -- Secrets past client_secret_expires_at are rejected (NULL = never expires)
+    client_secret_expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
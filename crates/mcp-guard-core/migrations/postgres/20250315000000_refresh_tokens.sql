@@ -0,0 +1,12 @@
+-- Create refresh_tokens table (built-in authorization server)
+-- Refresh tokens are single use: redeeming one marks it used and issues a new one.
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    -- SHA-256 of the refresh token
+    token_hash TEXT PRIMARY KEY,
+    subject TEXT NOT NULL,
+    -- How the subject logged in: 'password' or 'api_key'
+    login TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
@@ -0,0 +1,36 @@
+-- Create quota_usage table (usage counters per identity and quota window)
+CREATE TABLE IF NOT EXISTS quota_usage (
+    identity_id TEXT NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    used BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (identity_id, window_start)
+);
+
+-- Create audit_log table (audit entries written to the database)
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    timestamp TIMESTAMPTZ NOT NULL,
+    event_type TEXT NOT NULL,
+    identity_id TEXT,
+    method TEXT,
+    tool TEXT,
+    success BOOLEAN NOT NULL,
+    message TEXT,
+    duration_ms BIGINT,
+    request_id TEXT,
+    tenant TEXT,
+    severity TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_timestamp ON audit_log(timestamp);
+CREATE INDEX IF NOT EXISTS idx_audit_log_identity ON audit_log(identity_id, timestamp);
+
+-- Create routes table (upstream routes added at runtime)
+CREATE TABLE IF NOT EXISTS routes (
+    name TEXT PRIMARY KEY,
+    -- Route definition, same shape as an [[upstream.servers]] entry
+    config JSONB NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
@@ -0,0 +1,2 @@
+-- Authorization rule that decided an authz_denied entry (JSON)
+ALTER TABLE audit_log ADD COLUMN authz_rule TEXT;
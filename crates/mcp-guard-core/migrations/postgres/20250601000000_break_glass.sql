@@ -0,0 +1,8 @@
+-- Break-glass key activations (break_glass), so every replica sees the same
+-- expiry and a restart does not re-arm an expired key
+CREATE TABLE IF NOT EXISTS break_glass_activations (
+    identity_id TEXT PRIMARY KEY,
+    justification TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    activated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,25 @@
+-- Create users table
+CREATE TABLE IF NOT EXISTS users (
+    id TEXT PRIMARY KEY,
+    email TEXT UNIQUE NOT NULL,
+    name TEXT,
+    role TEXT NOT NULL CHECK (role IN ('user', 'developer', 'admin')),
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Create api_keys table
+CREATE TABLE IF NOT EXISTS api_keys (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id TEXT REFERENCES users(id) ON DELETE CASCADE,
+    key_hash TEXT UNIQUE NOT NULL,
+    name TEXT,
+    -- JSON array of tool names
+    allowed_tools TEXT,
+    rate_limit INTEGER,
+    expires_at TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Index for faster API key lookups
+CREATE INDEX IF NOT EXISTS idx_api_keys_hash ON api_keys(key_hash);
@@ -0,0 +1,12 @@
+-- Create revoked_tokens table (JWT denylist)
+-- Each row revokes either a single token (jti) or every token for a subject.
+CREATE TABLE IF NOT EXISTS revoked_tokens (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    jti TEXT,
+    subject TEXT,
+    reason TEXT,
+    -- Rows past expires_at are ignored (set to the token's exp to auto-prune)
+    expires_at TEXT,
+    revoked_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    CHECK (jti IS NOT NULL OR subject IS NOT NULL)
+);
@@ -0,0 +1,28 @@
+-- Shared state for cluster mode (cluster.enabled), so any replica can
+-- complete an OAuth flow or serve a session started on another one.
+
+-- Pending OAuth authorization code flows (state -> PKCE verifier)
+CREATE TABLE IF NOT EXISTS oauth_states (
+    state TEXT PRIMARY KEY,
+    code_verifier TEXT NOT NULL,
+    -- Client IP that started the flow; the callback must come from the same IP
+    client_ip TEXT NOT NULL,
+    redirect_uri TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Server-side OAuth sessions (auth.oauth.session)
+CREATE TABLE IF NOT EXISTS oauth_sessions (
+    -- SHA-256 of the session token
+    token_hash TEXT PRIMARY KEY,
+    identity_id TEXT NOT NULL,
+    -- Identity resolved from the provider (JSON)
+    identity TEXT NOT NULL,
+    -- Provider refresh token, kept on the gateway
+    refresh_token TEXT,
+    access_expires_at TEXT,
+    expires_at TEXT NOT NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_sessions_identity ON oauth_sessions(identity_id);
@@ -0,0 +1,608 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Alert emails for conditions that need an operator
+//!
+//! [`spawn_alerting`] checks the running gateway for a license about to
+//! expire, upstreams unhealthy for longer than
+//! `alerting.upstream_down_minutes` and an audit export endpoint that drops
+//! batches. The [`AlertMonitor`] raises each condition once when it starts
+//! and re-arms it when it clears. Alerts then wait in an [`Outbox`], which
+//! collects them into one email per `batch_secs` and holds them back once
+//! `max_emails_per_hour` is reached, so a flapping upstream cannot flood an
+//! inbox.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message as Email, Tokio1Executor};
+use serde::Serialize;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{AlertingConfig, SmtpAlertConfig, SmtpSecurity};
+use crate::observability::record_alert_email;
+use crate::server::AppState;
+
+/// Timeout for each SMTP conversation
+const SMTP_TIMEOUT_SECS: u64 = 30;
+
+/// Alerts kept while waiting to be sent; older ones are dropped first
+const MAX_PENDING_ALERTS: usize = 100;
+
+/// Window of `max_emails_per_hour`
+const RATE_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Errors setting up alert delivery
+#[derive(Debug, thiserror::Error)]
+pub enum AlertingError {
+    #[error("Invalid email address '{0}': {1}")]
+    Address(String, String),
+
+    #[error("SMTP error: {0}")]
+    Smtp(String),
+}
+
+/// Condition an alert is raised for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    LicenseExpiring,
+    UpstreamDown,
+    AuditExportFailing,
+}
+
+impl AlertKind {
+    /// Short human-readable name
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::LicenseExpiring => "License expiring",
+            Self::UpstreamDown => "Upstream down",
+            Self::AuditExportFailing => "Audit export failing",
+        }
+    }
+}
+
+/// A condition that started
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub message: String,
+    pub raised_at: DateTime<Utc>,
+}
+
+/// State of the gateway the alert conditions are checked against
+#[derive(Debug, Clone)]
+pub struct Conditions {
+    /// When the license stops being accepted, if one was loaded
+    pub license_expires_at: Option<SystemTime>,
+    /// Upstream route name and whether it is healthy
+    pub upstreams: Vec<(String, bool)>,
+    /// Whether the last audit batch reached `audit.export_url`
+    pub audit_export_healthy: bool,
+}
+
+impl Conditions {
+    /// Read the conditions of a running gateway
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            license_expires_at: state.license_expires_at,
+            upstreams: state.upstream_health(),
+            audit_export_healthy: state.audit_logger.export_healthy(),
+        }
+    }
+}
+
+/// Turns conditions into alerts, once per occurrence
+#[derive(Debug)]
+pub struct AlertMonitor {
+    license_window: Duration,
+    upstream_down_after: Duration,
+    /// Upstream route name -> first check that found it unhealthy
+    down_since: HashMap<String, Instant>,
+    /// Conditions already alerted that have not cleared yet
+    active: HashSet<String>,
+}
+
+impl AlertMonitor {
+    /// Create a monitor with the thresholds from `config`
+    pub fn new(config: &AlertingConfig) -> Self {
+        Self {
+            license_window: Duration::from_secs(config.license_expiry_days.saturating_mul(86_400)),
+            upstream_down_after: Duration::from_secs(
+                config.upstream_down_minutes.saturating_mul(60),
+            ),
+            down_since: HashMap::new(),
+            active: HashSet::new(),
+        }
+    }
+
+    /// Alerts for conditions that started since the last check
+    pub fn check(&mut self, conditions: &Conditions, now: Instant) -> Vec<Alert> {
+        let mut firing = Vec::new();
+
+        if let Some(expires_at) = conditions.license_expires_at {
+            let remaining = expires_at
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            if remaining <= self.license_window {
+                let message = if remaining.is_zero() {
+                    "The license has expired; /ready fails until it is renewed".to_string()
+                } else {
+                    format!(
+                        "The license expires on {}",
+                        DateTime::<Utc>::from(expires_at).format("%Y-%m-%d %H:%M UTC")
+                    )
+                };
+                firing.push(("license".to_string(), AlertKind::LicenseExpiring, message));
+            }
+        }
+
+        self.down_since.retain(|name, _| {
+            conditions
+                .upstreams
+                .iter()
+                .any(|(upstream, healthy)| upstream == name && !healthy)
+        });
+        for (name, _) in conditions.upstreams.iter().filter(|(_, healthy)| !healthy) {
+            let since = *self.down_since.entry(name.clone()).or_insert(now);
+            let down_for = now.duration_since(since);
+            if down_for >= self.upstream_down_after {
+                firing.push((
+                    format!("upstream:{}", name),
+                    AlertKind::UpstreamDown,
+                    format!(
+                        "Upstream '{}' has been unhealthy for {} minutes",
+                        name,
+                        down_for.as_secs() / 60
+                    ),
+                ));
+            }
+        }
+
+        if !conditions.audit_export_healthy {
+            firing.push((
+                "audit_export".to_string(),
+                AlertKind::AuditExportFailing,
+                "Audit batches could not be shipped to audit.export_url and were dropped"
+                    .to_string(),
+            ));
+        }
+
+        // Conditions that cleared can alert again
+        self.active
+            .retain(|key| firing.iter().any(|(firing_key, _, _)| firing_key == key));
+        firing
+            .into_iter()
+            .filter(|(key, _, _)| self.active.insert(key.clone()))
+            .map(|(_, kind, message)| Alert {
+                kind,
+                message,
+                raised_at: Utc::now(),
+            })
+            .collect()
+    }
+}
+
+/// Alerts to send in one email
+#[derive(Debug)]
+pub struct Batch {
+    pub alerts: Vec<Alert>,
+    /// Alerts dropped while waiting, oldest first
+    pub dropped: usize,
+}
+
+/// Alerts waiting for the batch window and the hourly email limit
+#[derive(Debug)]
+pub struct Outbox {
+    batch: Duration,
+    max_per_hour: usize,
+    pending: Vec<Alert>,
+    /// When the oldest pending alert arrived
+    since: Option<Instant>,
+    dropped: usize,
+    /// When the emails of the last hour were sent
+    sent: VecDeque<Instant>,
+}
+
+impl Outbox {
+    /// Create an outbox with the batching and rate limits from `config`
+    pub fn new(config: &SmtpAlertConfig) -> Self {
+        Self {
+            batch: Duration::from_secs(config.batch_secs),
+            max_per_hour: config.max_emails_per_hour as usize,
+            pending: Vec::new(),
+            since: None,
+            dropped: 0,
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// Queue alerts for the next email
+    pub fn push(&mut self, alerts: Vec<Alert>, now: Instant) {
+        if alerts.is_empty() {
+            return;
+        }
+        self.since.get_or_insert(now);
+        self.pending.extend(alerts);
+        if self.pending.len() > MAX_PENDING_ALERTS {
+            let excess = self.pending.len() - MAX_PENDING_ALERTS;
+            self.pending.drain(..excess);
+            self.dropped += excess;
+        }
+    }
+
+    /// When the pending alerts may be sent, if there are any
+    pub fn next_send(&self) -> Option<Instant> {
+        let batched = self.since? + self.batch;
+        Some(self.next_slot().map_or(batched, |slot| slot.max(batched)))
+    }
+
+    /// Take the pending alerts if they may be sent at `now`
+    ///
+    /// `flush` skips the batch window (on shutdown) but not the hourly
+    /// limit.
+    pub fn take(&mut self, now: Instant, flush: bool) -> Option<Batch> {
+        let since = self.since?;
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= RATE_WINDOW)
+        {
+            self.sent.pop_front();
+        }
+        if (!flush && now < since + self.batch) || self.next_slot().is_some() {
+            return None;
+        }
+        self.sent.push_back(now);
+        self.since = None;
+        Some(Batch {
+            alerts: std::mem::take(&mut self.pending),
+            dropped: std::mem::take(&mut self.dropped),
+        })
+    }
+
+    /// When the hourly limit allows the next email, if it is reached
+    fn next_slot(&self) -> Option<Instant> {
+        if self.sent.len() < self.max_per_hour {
+            return None;
+        }
+        self.sent.front().map(|sent| *sent + RATE_WINDOW)
+    }
+}
+
+/// Sends batches of alerts through an SMTP server
+struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    subject_prefix: String,
+}
+
+impl Mailer {
+    fn new(config: &SmtpAlertConfig) -> Result<Self, AlertingError> {
+        let builder = match config.security {
+            SmtpSecurity::Starttls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                    .map_err(|e| AlertingError::Smtp(e.to_string()))?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+                .map_err(|e| AlertingError::Smtp(e.to_string()))?,
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            }
+        };
+        let mut builder = builder
+            .port(config.port)
+            .timeout(Some(Duration::from_secs(SMTP_TIMEOUT_SECS)));
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: mailbox(&config.from)?,
+            to: config
+                .to
+                .iter()
+                .map(|to| mailbox(to))
+                .collect::<Result<_, _>>()?,
+            subject_prefix: config.subject_prefix.clone(),
+        })
+    }
+
+    /// Send a batch, logging rather than returning failures
+    async fn deliver(&self, batch: &Batch) {
+        let mut email = Email::builder()
+            .from(self.from.clone())
+            .subject(subject(&self.subject_prefix, batch));
+        for to in &self.to {
+            email = email.to(to.clone());
+        }
+        let result = match email.header(ContentType::TEXT_PLAIN).body(body(batch)) {
+            Ok(email) => self
+                .transport
+                .send(email)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match result {
+            Ok(()) => {
+                record_alert_email("sent");
+                tracing::info!(alerts = batch.alerts.len(), "Alert email sent");
+            }
+            Err(e) => {
+                record_alert_email("failed");
+                tracing::error!(
+                    alerts = batch.alerts.len(),
+                    error = %e,
+                    "Failed to send alert email"
+                );
+            }
+        }
+    }
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, AlertingError> {
+    address.parse().map_err(|e: lettre::address::AddressError| {
+        AlertingError::Address(address.to_string(), e.to_string())
+    })
+}
+
+fn subject(prefix: &str, batch: &Batch) -> String {
+    match batch.alerts.as_slice() {
+        [alert] if batch.dropped == 0 => format!("{} {}", prefix, alert.kind.title()),
+        alerts => format!("{} {} alerts", prefix, alerts.len() + batch.dropped),
+    }
+}
+
+fn body(batch: &Batch) -> String {
+    let mut body = String::new();
+    for alert in &batch.alerts {
+        body.push_str(&format!(
+            "[{}] {}: {}\n",
+            alert.raised_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            alert.kind.title(),
+            alert.message
+        ));
+    }
+    if batch.dropped > 0 {
+        body.push_str(&format!(
+            "\n{} older alerts were dropped while waiting to be sent.\n",
+            batch.dropped
+        ));
+    }
+    body.push_str(&format!("\n-- \nmcp-guard {}\n", env!("CARGO_PKG_VERSION")));
+    body
+}
+
+/// Start checking for alert conditions in the background
+///
+/// Returns `None` when `[alerting]` has no SMTP server. Pending alerts are
+/// sent, within the hourly limit, when `shutdown_token` is cancelled.
+pub fn spawn_alerting(
+    state: Arc<AppState>,
+    shutdown_token: CancellationToken,
+) -> Result<Option<tokio::task::JoinHandle<()>>, AlertingError> {
+    let Some(ref smtp) = state.config.alerting.smtp else {
+        return Ok(None);
+    };
+    let mailer = Mailer::new(smtp)?;
+    let mut outbox = Outbox::new(smtp);
+    let mut monitor = AlertMonitor::new(&state.config.alerting);
+    let check_interval = Duration::from_secs(state.config.alerting.check_interval_secs);
+
+    Ok(Some(tokio::spawn(async move {
+        let mut next_check = Instant::now();
+        loop {
+            let wake = outbox
+                .next_send()
+                .map_or(next_check, |send| send.min(next_check));
+            tokio::select! {
+                _ = shutdown_token.cancelled() => {
+                    if let Some(batch) = outbox.take(Instant::now(), true) {
+                        mailer.deliver(&batch).await;
+                    }
+                    tracing::debug!("Alerting task received shutdown signal");
+                    break;
+                }
+                _ = tokio::time::sleep_until(wake) => {}
+            }
+
+            let now = Instant::now();
+            if now >= next_check {
+                let alerts = monitor.check(&Conditions::from_state(&state), now);
+                for alert in &alerts {
+                    tracing::warn!(kind = ?alert.kind, message = %alert.message, "Alert raised");
+                }
+                outbox.push(alerts, now);
+                next_check = now + check_interval;
+            }
+            if let Some(batch) = outbox.take(now, false) {
+                mailer.deliver(&batch).await;
+            }
+        }
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conditions() -> Conditions {
+        Conditions {
+            license_expires_at: None,
+            upstreams: vec![("github".to_string(), true)],
+            audit_export_healthy: true,
+        }
+    }
+
+    fn smtp(batch_secs: u64, max_emails_per_hour: u32) -> SmtpAlertConfig {
+        SmtpAlertConfig {
+            host: "smtp.example.com".to_string(),
+            port: 587,
+            security: SmtpSecurity::Starttls,
+            username: None,
+            password: None,
+            from: "mcp-guard@example.com".to_string(),
+            to: vec!["oncall@example.com".to_string()],
+            subject_prefix: "[mcp-guard]".to_string(),
+            batch_secs,
+            max_emails_per_hour,
+        }
+    }
+
+    fn alert(message: &str) -> Alert {
+        Alert {
+            kind: AlertKind::UpstreamDown,
+            message: message.to_string(),
+            raised_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_upstream_down_alerts_once_after_threshold() {
+        let mut monitor = AlertMonitor::new(&AlertingConfig::default());
+        let start = Instant::now();
+        let mut down = conditions();
+        down.upstreams[0].1 = false;
+
+        assert!(monitor.check(&down, start).is_empty());
+        let alerts = monitor.check(&down, start + Duration::from_secs(300));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AlertKind::UpstreamDown);
+        assert!(alerts[0].message.contains("'github'"));
+        // Still down: not alerted again
+        assert!(monitor
+            .check(&down, start + Duration::from_secs(600))
+            .is_empty());
+
+        // Recovery re-arms the alert and restarts the clock
+        assert!(monitor
+            .check(&conditions(), start + Duration::from_secs(630))
+            .is_empty());
+        assert!(monitor
+            .check(&down, start + Duration::from_secs(660))
+            .is_empty());
+        assert_eq!(
+            monitor.check(&down, start + Duration::from_secs(960)).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_license_and_audit_export_alerts() {
+        let mut monitor = AlertMonitor::new(&AlertingConfig::default());
+        let now = Instant::now();
+
+        let mut far = conditions();
+        far.license_expires_at = Some(SystemTime::now() + Duration::from_secs(30 * 86_400));
+        assert!(monitor.check(&far, now).is_empty());
+
+        let failing = Conditions {
+            license_expires_at: Some(SystemTime::now() + Duration::from_secs(86_400)),
+            audit_export_healthy: false,
+            ..conditions()
+        };
+        let kinds: Vec<_> = monitor
+            .check(&failing, now)
+            .into_iter()
+            .map(|alert| alert.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![AlertKind::LicenseExpiring, AlertKind::AuditExportFailing]
+        );
+    }
+
+    #[test]
+    fn test_outbox_batches_and_rate_limits() {
+        let mut outbox = Outbox::new(&smtp(60, 2));
+        let start = Instant::now();
+        assert!(outbox.next_send().is_none());
+
+        outbox.push(vec![alert("a")], start);
+        outbox.push(vec![alert("b")], start + Duration::from_secs(10));
+        assert_eq!(outbox.next_send(), Some(start + Duration::from_secs(60)));
+        assert!(outbox
+            .take(start + Duration::from_secs(30), false)
+            .is_none());
+        let batch = outbox.take(start + Duration::from_secs(60), false).unwrap();
+        assert_eq!(batch.alerts.len(), 2);
+
+        let later = start + Duration::from_secs(120);
+        outbox.push(vec![alert("c")], later);
+        assert!(outbox.take(later, true).is_some());
+
+        // The hourly limit holds further alerts back, even on flush
+        outbox.push(vec![alert("d")], later);
+        assert!(outbox.take(later, true).is_none());
+        assert_eq!(outbox.next_send(), Some(start + Duration::from_secs(3660)));
+        assert!(outbox
+            .take(start + Duration::from_secs(3660), false)
+            .is_some());
+    }
+
+    #[test]
+    fn test_outbox_drops_oldest_alerts() {
+        let mut outbox = Outbox::new(&smtp(0, 1));
+        let now = Instant::now();
+        outbox.push(
+            (0..MAX_PENDING_ALERTS + 5)
+                .map(|i| alert(&i.to_string()))
+                .collect(),
+            now,
+        );
+        let batch = outbox.take(now, false).unwrap();
+        assert_eq!(batch.alerts.len(), MAX_PENDING_ALERTS);
+        assert_eq!(batch.alerts[0].message, "5");
+        assert_eq!(batch.dropped, 5);
+
+        assert_eq!(subject("[mcp-guard]", &batch), "[mcp-guard] 105 alerts");
+        assert!(body(&batch).contains("5 older alerts were dropped"));
+    }
+
+    #[test]
+    fn test_email_content() {
+        let batch = Batch {
+            alerts: vec![alert("Upstream 'github' has been unhealthy for 5 minutes")],
+            dropped: 0,
+        };
+        assert_eq!(subject("[mcp-guard]", &batch), "[mcp-guard] Upstream down");
+        assert!(body(&batch).contains("Upstream down: Upstream 'github' has been unhealthy"));
+    }
+
+    #[test]
+    fn test_invalid_addresses_are_rejected() {
+        assert!(Mailer::new(&smtp(60, 6)).is_ok());
+        let config = SmtpAlertConfig {
+            to: vec!["not an address".to_string()],
+            ..smtp(60, 6)
+        };
+        assert!(matches!(
+            Mailer::new(&config),
+            Err(AlertingError::Address(..))
+        ));
+    }
+}
@@ -0,0 +1,471 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Human approval for dangerous tool calls
+//!
+//! Tool calls matching `approval.requires_approval` are parked by the
+//! [`ApprovalService`] until an approver decides on them through the admin
+//! API. A parked call resumes when approved and is rejected when denied or
+//! when nobody decides within `approval.timeout_secs`.
+//!
+//! Pending calls live in memory: they are lost (and their callers rejected)
+//! when the gateway restarts. Identities can never decide on their own calls.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use glob::Pattern;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::config::{ApprovalConfig, ApprovalWebhookFormat};
+
+/// Timeout for webhook notifications
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// Why a tool call could not be parked, or a decision could not be recorded
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApprovalError {
+    #[error("Too many tool calls are awaiting approval")]
+    TooManyPending,
+
+    #[error("No pending approval with ID '{0}'")]
+    NotFound(String),
+
+    #[error("Identity '{0}' cannot decide on its own tool call")]
+    SelfApproval(String),
+}
+
+/// A tool call waiting for an approver
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequest {
+    /// Approval ID, used in the admin API
+    pub id: String,
+    /// Identity that made the call
+    pub identity_id: String,
+    /// Tool being called
+    pub tool: String,
+    /// Call arguments, after secret scrubbing
+    pub arguments: Value,
+    pub requested_at: DateTime<Utc>,
+    /// When the call is rejected if nobody decides
+    pub expires_at: DateTime<Utc>,
+}
+
+/// How a parked tool call was resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalOutcome {
+    Approved {
+        approver: String,
+    },
+    Denied {
+        approver: String,
+        reason: Option<String>,
+    },
+    TimedOut,
+}
+
+struct Pending {
+    request: ApprovalRequest,
+    decide: oneshot::Sender<ApprovalOutcome>,
+}
+
+/// Parks tool calls that need approval and records approvers' decisions
+pub struct ApprovalService {
+    patterns: Vec<Pattern>,
+    approvers: Vec<String>,
+    timeout: Duration,
+    max_pending: usize,
+    pending: DashMap<String, Pending>,
+    webhook: Option<Webhook>,
+}
+
+impl std::fmt::Debug for ApprovalService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApprovalService")
+            .field("pattern_count", &self.patterns.len())
+            .field("pending", &self.pending.len())
+            .field("webhook", &self.webhook.is_some())
+            .finish()
+    }
+}
+
+impl Default for ApprovalService {
+    fn default() -> Self {
+        Self::new(&ApprovalConfig::default(), &[])
+    }
+}
+
+impl ApprovalService {
+    /// Build the service from configuration
+    ///
+    /// `admins` approve calls when `approval.approvers` is empty. Invalid
+    /// patterns are skipped; configuration validation rejects them first.
+    pub fn new(config: &ApprovalConfig, admins: &[String]) -> Self {
+        let approvers = if config.approvers.is_empty() {
+            admins.to_vec()
+        } else {
+            config.approvers.clone()
+        };
+        Self {
+            patterns: config
+                .requires_approval
+                .iter()
+                .filter_map(|p| Pattern::new(p).ok())
+                .collect(),
+            approvers,
+            timeout: Duration::from_secs(config.timeout_secs),
+            max_pending: config.max_pending,
+            pending: DashMap::new(),
+            webhook: config.webhook_url.as_ref().map(|url| Webhook {
+                client: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                    .build()
+                    .unwrap_or_default(),
+                url: url.clone(),
+                format: config.webhook_format,
+                headers: config.webhook_headers.clone(),
+            }),
+        }
+    }
+
+    /// Whether any tool requires approval
+    pub fn is_enabled(&self) -> bool {
+        !self.patterns.is_empty()
+    }
+
+    /// Whether calls to `tool` must be approved
+    pub fn requires_approval(&self, tool: &str) -> bool {
+        self.patterns.iter().any(|p| p.matches(tool))
+    }
+
+    /// Whether `identity_id` may decide on pending calls
+    pub fn is_approver(&self, identity_id: &str) -> bool {
+        self.approvers.iter().any(|a| a == identity_id)
+    }
+
+    /// Park a tool call until an approver decides
+    ///
+    /// The webhook, if configured, is notified in the background.
+    pub fn park(
+        &self,
+        identity_id: &str,
+        tool: &str,
+        arguments: Value,
+    ) -> Result<ParkedCall<'_>, ApprovalError> {
+        if self.pending.len() >= self.max_pending {
+            return Err(ApprovalError::TooManyPending);
+        }
+
+        let requested_at = Utc::now();
+        let request = ApprovalRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            identity_id: identity_id.to_string(),
+            tool: tool.to_string(),
+            arguments,
+            requested_at,
+            expires_at: requested_at
+                + chrono::Duration::from_std(self.timeout).unwrap_or(chrono::Duration::zero()),
+        };
+        let (decide, decision) = oneshot::channel();
+
+        if let Some(ref webhook) = self.webhook {
+            let webhook = webhook.clone();
+            let request = request.clone();
+            tokio::spawn(async move { webhook.notify(&request).await });
+        }
+
+        let id = request.id.clone();
+        self.pending.insert(id.clone(), Pending { request, decide });
+        Ok(ParkedCall {
+            service: self,
+            id,
+            decision,
+        })
+    }
+
+    /// Calls currently awaiting approval, oldest first
+    pub fn pending(&self) -> Vec<ApprovalRequest> {
+        let mut pending: Vec<ApprovalRequest> = self
+            .pending
+            .iter()
+            .map(|entry| entry.request.clone())
+            .collect();
+        pending.sort_by_key(|request| request.requested_at);
+        pending
+    }
+
+    /// Approve or deny a pending call on behalf of `approver`
+    ///
+    /// Returns the call that was decided.
+    pub fn decide(
+        &self,
+        id: &str,
+        approver: &str,
+        approved: bool,
+        reason: Option<String>,
+    ) -> Result<ApprovalRequest, ApprovalError> {
+        let Some((_, pending)) = self
+            .pending
+            .remove_if(id, |_, pending| pending.request.identity_id != approver)
+        else {
+            return Err(if self.pending.contains_key(id) {
+                ApprovalError::SelfApproval(approver.to_string())
+            } else {
+                ApprovalError::NotFound(id.to_string())
+            });
+        };
+
+        let approver = approver.to_string();
+        let outcome = if approved {
+            ApprovalOutcome::Approved { approver }
+        } else {
+            ApprovalOutcome::Denied { approver, reason }
+        };
+        // The caller may have gone away in the meantime; the decision still stands
+        let _ = pending.decide.send(outcome);
+        Ok(pending.request)
+    }
+}
+
+/// A tool call parked by [`ApprovalService::park`]
+///
+/// Dropping it (e.g. because the client disconnected) withdraws the request.
+pub struct ParkedCall<'a> {
+    service: &'a ApprovalService,
+    id: String,
+    decision: oneshot::Receiver<ApprovalOutcome>,
+}
+
+impl ParkedCall<'_> {
+    /// Approval ID of the parked call
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Wait for an approver's decision, up to the configured timeout
+    pub async fn wait(mut self) -> ApprovalOutcome {
+        match tokio::time::timeout(self.service.timeout, &mut self.decision).await {
+            Ok(Ok(outcome)) => outcome,
+            _ => ApprovalOutcome::TimedOut,
+        }
+    }
+}
+
+impl Drop for ParkedCall<'_> {
+    fn drop(&mut self) {
+        self.service.pending.remove(&self.id);
+    }
+}
+
+// ============================================================================
+// Webhook Notifications
+// ============================================================================
+
+#[derive(Clone)]
+struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    format: ApprovalWebhookFormat,
+    headers: HashMap<String, String>,
+}
+
+impl Webhook {
+    async fn notify(&self, request: &ApprovalRequest) {
+        let mut builder = self.client.post(&self.url).json(&self.payload(request));
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        match builder.send().await {
+            Ok(response) if response.status().is_success() => {
+                tracing::debug!(approval_id = %request.id, "Approval webhook delivered");
+            }
+            Ok(response) => {
+                tracing::warn!(
+                    approval_id = %request.id,
+                    status = %response.status(),
+                    "Approval webhook rejected"
+                );
+            }
+            Err(e) => {
+                tracing::warn!(approval_id = %request.id, error = %e, "Approval webhook failed");
+            }
+        }
+    }
+
+    fn payload(&self, request: &ApprovalRequest) -> Value {
+        match self.format {
+            ApprovalWebhookFormat::Json => serde_json::json!({
+                "type": "approval_requested",
+                "approval": request,
+            }),
+            ApprovalWebhookFormat::Slack => serde_json::json!({
+                "text": format!(
+                    ":warning: *Approval required*: `{}` wants to call `{}`\n\
+                     Approve with `POST /admin/approvals/{}/approve` or deny with \
+                     `POST /admin/approvals/{}/deny` before {}.",
+                    request.identity_id,
+                    request.tool,
+                    request.id,
+                    request.id,
+                    request.expires_at.to_rfc3339(),
+                ),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(timeout_secs: u64) -> ApprovalService {
+        ApprovalService::new(
+            &ApprovalConfig {
+                requires_approval: vec!["execute_*".to_string()],
+                timeout_secs,
+                max_pending: 1,
+                ..Default::default()
+            },
+            &["ops".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_requires_approval() {
+        let approvals = service(60);
+        assert!(approvals.is_enabled());
+        assert!(approvals.requires_approval("execute_sql"));
+        assert!(!approvals.requires_approval("read_file"));
+        assert!(approvals.is_approver("ops"));
+        assert!(!approvals.is_approver("dev"));
+        assert!(!ApprovalService::default().is_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_approve_resumes_call() {
+        let approvals = service(60);
+        let call = approvals
+            .park("dev", "execute_sql", serde_json::json!({"query": "DROP"}))
+            .unwrap();
+        let id = call.id().to_string();
+
+        let pending = approvals.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].tool, "execute_sql");
+
+        // Only one call may wait at a time
+        assert_eq!(
+            approvals
+                .park("dev", "execute_sql", Value::Null)
+                .err()
+                .unwrap(),
+            ApprovalError::TooManyPending
+        );
+
+        // Callers cannot approve their own calls
+        assert_eq!(
+            approvals.decide(&id, "dev", true, None).unwrap_err(),
+            ApprovalError::SelfApproval("dev".to_string())
+        );
+
+        let decided = approvals.decide(&id, "ops", true, None).unwrap();
+        assert_eq!(decided.identity_id, "dev");
+        assert_eq!(
+            call.wait().await,
+            ApprovalOutcome::Approved {
+                approver: "ops".to_string()
+            }
+        );
+        assert!(approvals.pending().is_empty());
+        assert_eq!(
+            approvals.decide(&id, "ops", true, None).unwrap_err(),
+            ApprovalError::NotFound(id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deny_and_timeout() {
+        let approvals = service(60);
+        let call = approvals.park("dev", "execute_sql", Value::Null).unwrap();
+        let id = call.id().to_string();
+        approvals
+            .decide(&id, "ops", false, Some("not today".to_string()))
+            .unwrap();
+        assert_eq!(
+            call.wait().await,
+            ApprovalOutcome::Denied {
+                approver: "ops".to_string(),
+                reason: Some("not today".to_string())
+            }
+        );
+
+        let approvals = service(1);
+        let call = approvals.park("dev", "execute_sql", Value::Null).unwrap();
+        assert_eq!(call.wait().await, ApprovalOutcome::TimedOut);
+        assert!(approvals.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_call_is_withdrawn() {
+        let approvals = service(60);
+        let call = approvals.park("dev", "execute_sql", Value::Null).unwrap();
+        drop(call);
+        assert!(approvals.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_notified() {
+        use wiremock::matchers::{body_partial_json, header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("x-token", "secret"))
+            .and(body_partial_json(serde_json::json!({
+                "type": "approval_requested",
+                "approval": {"identity_id": "dev", "tool": "execute_sql"}
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let approvals = ApprovalService::new(
+            &ApprovalConfig {
+                requires_approval: vec!["execute_*".to_string()],
+                webhook_url: Some(mock_server.uri()),
+                webhook_headers: HashMap::from([("x-token".to_string(), "secret".to_string())]),
+                ..Default::default()
+            },
+            &["ops".to_string()],
+        );
+        let call = approvals.park("dev", "execute_sql", Value::Null).unwrap();
+
+        for _ in 0..50 {
+            if !mock_server.received_requests().await.unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        drop(call);
+        mock_server.verify().await;
+    }
+}
@@ -21,10 +21,16 @@
 //! - File: Append audit entries to a local file (with optional rotation)
 //! - Stdout: Print audit entries to console
 //! - HTTP Export: Batch and ship audit entries to an HTTP endpoint (SIEM integration)
+//! - Object storage: Archive batches to S3-compatible buckets for retention
+//!
+//! Audit files (including rotated backups) can be summarized into per-identity
+//! access reports for compliance evidence, see [`AccessReport`].
 //!
 //! Features:
 //! - Secret redaction: Configurable regex patterns to prevent credential leakage
 //! - Log rotation: Size and time-based rotation with optional gzip compression
+//! - Backpressure: Full queues drop new or old entries, block briefly, or
+//!   fail requests, as set by `audit.overflow`
 //!
 //! All I/O is performed asynchronously via background tasks to avoid blocking
 //! the async runtime.
@@ -33,15 +39,27 @@ use chrono::{DateTime, Utc};
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufWriter, Write};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
+use tokio::sync::broadcast;
+
+use crate::authz::AuthzRule;
+use crate::config::{AuditOverflowStrategy, FsyncPolicy, LogRotationConfig, RedactionRule};
+use crate::observability::{record_audit_dropped, telemetry_identity, telemetry_privacy_enabled};
+
+mod object_storage;
+mod queue;
+mod report;
 
-use crate::config::{LogRotationConfig, RedactionRule};
+use object_storage::ObjectStorageArchiver;
+use queue::{Overflow, QueueReceiver, QueueSender};
+pub use report::{audit_files, parse_report_time, AccessReport, IdentityAccess};
 
 // ============================================================================
 // Constants
@@ -52,6 +70,9 @@ use crate::config::{LogRotationConfig, RedactionRule};
 /// preventing backpressure while keeping memory usage bounded.
 const AUDIT_CHANNEL_SIZE: usize = 1000;
 
+/// Entries buffered for each live stream subscriber before it lags.
+const AUDIT_STREAM_BUFFER: usize = 256;
+
 /// HTTP request timeout for audit export.
 /// 30 seconds allows for slow SIEM endpoints while preventing indefinite hangs.
 const AUDIT_HTTP_TIMEOUT_SECS: u64 = 30;
@@ -150,6 +171,16 @@ impl CompiledRedactionRules {
         }
         result
     }
+
+    /// Redact every string in a JSON value, leaving its structure intact
+    pub fn redact_json(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(s) => *s = self.redact(s),
+            serde_json::Value::Array(values) => values.iter_mut().for_each(|v| self.redact_json(v)),
+            serde_json::Value::Object(map) => map.values_mut().for_each(|v| self.redact_json(v)),
+            _ => {}
+        }
+    }
 }
 
 // ============================================================================
@@ -171,6 +202,8 @@ pub struct RotatingFileWriter {
     created_at: Instant,
     /// Rotation configuration
     config: LogRotationConfig,
+    /// Fsync the file before it is rotated
+    sync_on_rotate: bool,
 }
 
 impl RotatingFileWriter {
@@ -186,9 +219,16 @@ impl RotatingFileWriter {
             current_size,
             created_at: Instant::now(),
             config,
+            sync_on_rotate: false,
         })
     }
 
+    /// Fsync the file before each rotation
+    pub fn with_sync_on_rotate(mut self, sync_on_rotate: bool) -> Self {
+        self.sync_on_rotate = sync_on_rotate;
+        self
+    }
+
     /// Write data to the log file, rotating if necessary
     pub fn write(&mut self, data: &[u8]) -> io::Result<()> {
         // Check if rotation is needed before writing
@@ -207,11 +247,17 @@ impl RotatingFileWriter {
         self.write(b"\n")
     }
 
-    /// Flush buffered data to disk
+    /// Flush buffered data to the OS
     pub fn flush(&mut self) -> io::Result<()> {
         self.file.flush()
     }
 
+    /// Flush buffered data and fsync it to disk
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.file.get_ref().sync_data()
+    }
+
     /// Check if rotation is needed based on size or age
     fn should_rotate(&self) -> bool {
         if !self.config.enabled {
@@ -238,7 +284,11 @@ impl RotatingFileWriter {
     /// Rotate the current log file
     fn rotate(&mut self) -> io::Result<()> {
         // Flush and close current file
-        self.file.flush()?;
+        if self.sync_on_rotate {
+            self.sync()?;
+        } else {
+            self.file.flush()?;
+        }
 
         // Generate backup filename with timestamp
         let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
@@ -352,7 +402,7 @@ impl RotatingFileWriter {
 }
 
 /// Audit event types
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EventType {
     AuthSuccess,
@@ -361,11 +411,33 @@ pub enum EventType {
     ToolResponse,
     RateLimited,
     AuthzDenied,
+    NetworkBlocked,
+    SecretScrubbed,
+    IdentityExpired,
+    RateLimitChanged,
+    UpstreamChanged,
+    ReadOnlyChanged,
+    ApprovalRequested,
+    ApprovalGranted,
+    ApprovalDenied,
+    KeyExpiring,
+    HoneypotTriggered,
+    BreakGlass,
     Error,
 }
 
+/// Severity of an audit event, for SIEM triage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSeverity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
 /// Audit log entry
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEntry {
     pub timestamp: DateTime<Utc>,
     pub event_type: EventType,
@@ -376,11 +448,20 @@ pub struct AuditEntry {
     pub message: Option<String>,
     pub duration_ms: Option<u64>,
     pub request_id: Option<String>,
+    /// Tenant of the identity, when tenancy is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// Set on events that need attention
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<AuditSeverity>,
+    /// Authorization rule that decided the request, on `authz_denied` events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authz_rule: Option<AuthzRule>,
 }
 
 /// Maximum length for string fields in audit entries
 /// SECURITY: Prevents memory exhaustion and log bloat from malicious input
-const MAX_AUDIT_FIELD_LEN: usize = 1024;
+pub(crate) const MAX_AUDIT_FIELD_LEN: usize = 1024;
 
 /// Sanitize a string for audit logging
 ///
@@ -423,6 +504,9 @@ impl AuditEntry {
             message: None,
             duration_ms: None,
             request_id: None,
+            tenant: None,
+            severity: None,
+            authz_rule: None,
         }
     }
 
@@ -460,6 +544,37 @@ impl AuditEntry {
         self.request_id = Some(sanitize_audit_string(request_id));
         self
     }
+
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(sanitize_audit_string(tenant));
+        self
+    }
+
+    pub fn with_severity(mut self, severity: AuditSeverity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    pub fn with_authz_rule(mut self, rule: AuthzRule) -> Self {
+        self.authz_rule = Some(rule);
+        self
+    }
+}
+
+tokio::task_local! {
+    static AUDIT_TENANT: Option<String>;
+}
+
+/// Run `f` with entries logged inside it tagged with `tenant`
+///
+/// Entries that set their own tenant keep it.
+pub async fn with_tenant<F: std::future::Future>(tenant: Option<String>, f: F) -> F::Output {
+    AUDIT_TENANT.scope(tenant, f).await
+}
+
+/// Tenant of the request being handled, if any
+fn current_tenant() -> Option<String> {
+    AUDIT_TENANT.try_with(|t| t.clone()).ok().flatten()
 }
 
 /// Internal message type for the audit writer task
@@ -477,11 +592,64 @@ enum AuditMessage {
 pub struct AuditLogger {
     enabled: bool,
     /// Channel for sending entries to the local writer task (file + stdout)
-    writer_tx: Option<mpsc::Sender<AuditMessage>>,
+    writer_tx: Option<QueueSender<AuditMessage>>,
     /// Channel for sending entries to the HTTP shipper task
-    export_tx: Option<mpsc::Sender<AuditEntry>>,
+    export_tx: Option<QueueSender<AuditEntry>>,
+    /// Channel for sending serialized entries to the object storage archiver
+    archive_tx: Option<QueueSender<String>>,
     /// Compiled redaction rules for secret filtering
     redaction_rules: CompiledRedactionRules,
+    /// Live feed of redacted entries for `/admin/audit/stream`
+    live_tx: broadcast::Sender<AuditEntry>,
+    /// Reject requests while a queue is full (`overflow = "fail_request"`)
+    fail_on_overflow: bool,
+    /// Entries dropped because the writer channel was full
+    writer_dropped: AtomicU64,
+    /// Entries dropped because the export channel was full
+    export_dropped: AtomicU64,
+    /// Entries dropped because the archive channel was full
+    archive_dropped: AtomicU64,
+    /// Whether the last file write succeeded (`None` without a file)
+    file_ok: Option<Arc<AtomicBool>>,
+    /// Whether the last export batch was shipped (`None` without export)
+    export_ok: Option<Arc<AtomicBool>>,
+    /// Whether the last archive object was stored (`None` without archive)
+    archive_ok: Option<Arc<AtomicBool>>,
+}
+
+/// Fill level of one audit channel
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AuditQueueHealth {
+    /// Entries waiting to be processed
+    pub queued: usize,
+    /// Channel capacity
+    pub capacity: usize,
+    /// Entries dropped since startup because the channel was full
+    pub dropped: u64,
+}
+
+/// Audit pipeline health, as reported by `/health?deep=true`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AuditHealth {
+    pub enabled: bool,
+    /// Whether the last write to the audit file succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_writable: Option<bool>,
+    /// Whether the last batch reached the export endpoint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export_healthy: Option<bool>,
+    /// Whether the last batch was stored in the object storage archive
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_healthy: Option<bool>,
+    /// Local writer (file + stdout) channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub writer: Option<AuditQueueHealth>,
+    /// HTTP export channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export: Option<AuditQueueHealth>,
+    /// Object storage archive channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<AuditQueueHealth>,
 }
 
 /// Handle for audit logger background tasks
@@ -490,8 +658,10 @@ pub struct AuditLoggerHandle {
     writer_task: Option<tokio::task::JoinHandle<()>>,
     /// Handle to the HTTP shipper task
     shipper_task: Option<tokio::task::JoinHandle<()>>,
+    /// Handle to the object storage archiver task
+    archiver_task: Option<tokio::task::JoinHandle<()>>,
     /// Channel to signal shutdown to writer
-    shutdown_tx: Option<mpsc::Sender<AuditMessage>>,
+    shutdown_tx: Option<QueueSender<AuditMessage>>,
 }
 
 impl AuditLoggerHandle {
@@ -499,7 +669,7 @@ impl AuditLoggerHandle {
     pub async fn shutdown(self) {
         // Signal writer to shutdown
         if let Some(tx) = self.shutdown_tx {
-            let _ = tx.send(AuditMessage::Shutdown).await;
+            tx.send_control(AuditMessage::Shutdown);
         }
 
         // Wait for writer task to complete
@@ -507,10 +677,13 @@ impl AuditLoggerHandle {
             let _ = task.await;
         }
 
-        // Shipper will shutdown when its channel is dropped
+        // Shipper and archiver will shutdown when their channels are dropped
         if let Some(task) = self.shipper_task {
             let _ = task.await;
         }
+        if let Some(task) = self.archiver_task {
+            let _ = task.await;
+        }
     }
 }
 
@@ -521,14 +694,24 @@ pub struct AuditShipperHandle {
 }
 
 impl AuditLogger {
+    /// Compile the configured redaction rules and DLP detectors
+    fn compile_redaction_rules(
+        config: &crate::config::AuditConfig,
+    ) -> std::io::Result<CompiledRedactionRules> {
+        let rules = config
+            .effective_redaction_rules()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        CompiledRedactionRules::new(&rules)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+    }
+
     /// Create a new audit logger from configuration (sync version for compatibility)
     ///
     /// Note: This creates a logger without background tasks. For production use,
     /// prefer `with_tasks()` which properly handles async I/O.
     pub fn new(config: &crate::config::AuditConfig) -> std::io::Result<Self> {
         // Compile redaction rules
-        let redaction_rules = CompiledRedactionRules::new(&config.redaction_rules)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        let redaction_rules = Self::compile_redaction_rules(config)?;
 
         // For backward compatibility, create a synchronous logger
         // This is used in tests and simple cases
@@ -536,7 +719,16 @@ impl AuditLogger {
             enabled: config.enabled,
             writer_tx: None, // No background task in sync mode
             export_tx: None,
+            archive_tx: None,
             redaction_rules,
+            fail_on_overflow: false,
+            live_tx: broadcast::channel(AUDIT_STREAM_BUFFER).0,
+            writer_dropped: AtomicU64::new(0),
+            export_dropped: AtomicU64::new(0),
+            archive_dropped: AtomicU64::new(0),
+            file_ok: None,
+            export_ok: None,
+            archive_ok: None,
         })
     }
 
@@ -548,8 +740,7 @@ impl AuditLogger {
         config: &crate::config::AuditConfig,
     ) -> std::io::Result<(Self, AuditLoggerHandle)> {
         // Compile redaction rules first (before checking enabled)
-        let redaction_rules = CompiledRedactionRules::new(&config.redaction_rules)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        let redaction_rules = Self::compile_redaction_rules(config)?;
 
         if !config.enabled {
             return Ok((
@@ -557,18 +748,33 @@ impl AuditLogger {
                     enabled: false,
                     writer_tx: None,
                     export_tx: None,
+                    archive_tx: None,
                     redaction_rules: CompiledRedactionRules::empty(),
+                    fail_on_overflow: false,
+                    live_tx: broadcast::channel(AUDIT_STREAM_BUFFER).0,
+                    writer_dropped: AtomicU64::new(0),
+                    export_dropped: AtomicU64::new(0),
+                    archive_dropped: AtomicU64::new(0),
+                    file_ok: None,
+                    export_ok: None,
+                    archive_ok: None,
                 },
                 AuditLoggerHandle {
                     writer_task: None,
                     shipper_task: None,
+                    archiver_task: None,
                     shutdown_tx: None,
                 },
             ));
         }
 
+        let overflow = Overflow::new(
+            config.overflow,
+            Duration::from_millis(config.overflow_block_ms),
+        );
+
         // Create channel for local writes (file + stdout)
-        let (writer_tx, writer_rx) = mpsc::channel::<AuditMessage>(AUDIT_CHANNEL_SIZE);
+        let (writer_tx, writer_rx) = queue::bounded::<AuditMessage>(AUDIT_CHANNEL_SIZE, overflow);
         let shutdown_tx = writer_tx.clone();
 
         // Create file writer (with or without rotation)
@@ -576,63 +782,104 @@ impl AuditLogger {
             if let Some(ref rotation_config) = config.rotation {
                 if rotation_config.enabled {
                     // Use rotating file writer
-                    Some(FileWriter::Rotating(RotatingFileWriter::new(
-                        path.clone(),
-                        rotation_config.clone(),
-                    )?))
+                    Some(FileWriter::Rotating(
+                        RotatingFileWriter::new(path.clone(), rotation_config.clone())?
+                            .with_sync_on_rotate(config.file_fsync != FsyncPolicy::Never),
+                    ))
                 } else {
                     // Rotation configured but disabled - use simple file
-                    Some(FileWriter::Simple(
+                    Some(FileWriter::Simple(BufWriter::new(
                         OpenOptions::new().create(true).append(true).open(path)?,
-                    ))
+                    )))
                 }
             } else {
                 // No rotation config - use simple file
-                Some(FileWriter::Simple(
+                Some(FileWriter::Simple(BufWriter::new(
                     OpenOptions::new().create(true).append(true).open(path)?,
-                ))
+                )))
             }
         } else {
             None
         };
+        let flush_policy = FileFlushPolicy {
+            batch_size: config.file_batch_size.max(1),
+            interval: Duration::from_millis(config.file_flush_interval_ms.max(1)),
+            fsync: config.file_fsync,
+        };
 
         let stdout_enabled = config.stdout;
+        let file_ok = file_writer
+            .as_ref()
+            .map(|_| Arc::new(AtomicBool::new(true)));
+        let writer_file_ok = file_ok.clone();
 
         // Spawn writer task
         let writer_task = tokio::spawn(async move {
-            run_audit_writer(writer_rx, file_writer, stdout_enabled).await;
+            run_audit_writer(
+                writer_rx,
+                file_writer,
+                flush_policy,
+                stdout_enabled,
+                writer_file_ok,
+            )
+            .await;
         });
 
         // Create HTTP shipper if configured
-        let (export_tx, shipper_task) = if let Some(ref export_url) = config.export_url {
-            let (tx, rx) = mpsc::channel::<AuditEntry>(AUDIT_CHANNEL_SIZE);
+        let (export_tx, shipper_task, export_ok) = if let Some(ref export_url) = config.export_url {
+            let (tx, rx) = queue::bounded::<AuditEntry>(AUDIT_CHANNEL_SIZE, overflow);
+            let export_ok = Arc::new(AtomicBool::new(true));
 
             let shipper = AuditShipper::new(
                 export_url.clone(),
                 config.export_headers.clone(),
                 config.export_batch_size,
                 config.export_interval_secs,
-            );
+            )
+            .with_status(export_ok.clone());
 
             let task = tokio::spawn(async move {
                 shipper.run(rx).await;
             });
 
-            (Some(tx), Some(task))
+            (Some(tx), Some(task), Some(export_ok))
         } else {
-            (None, None)
+            (None, None, None)
         };
 
+        // Create object storage archiver if configured
+        let (archive_tx, archiver_task, archive_ok) =
+            if let Some(ref storage_config) = config.object_storage {
+                let (tx, rx) = queue::bounded::<String>(AUDIT_CHANNEL_SIZE, overflow);
+                let archive_ok = Arc::new(AtomicBool::new(true));
+                let archiver = ObjectStorageArchiver::new(storage_config.clone())?
+                    .with_status(archive_ok.clone());
+                let task = tokio::spawn(archiver.run(rx));
+                (Some(tx), Some(task), Some(archive_ok))
+            } else {
+                (None, None, None)
+            };
+
         Ok((
             Self {
                 enabled: true,
                 writer_tx: Some(writer_tx),
                 export_tx,
+                archive_tx,
                 redaction_rules,
+                fail_on_overflow: config.overflow == AuditOverflowStrategy::FailRequest,
+                live_tx: broadcast::channel(AUDIT_STREAM_BUFFER).0,
+                writer_dropped: AtomicU64::new(0),
+                export_dropped: AtomicU64::new(0),
+                archive_dropped: AtomicU64::new(0),
+                file_ok,
+                export_ok,
+                archive_ok,
             },
             AuditLoggerHandle {
                 writer_task: Some(writer_task),
                 shipper_task,
+                archiver_task,
                 shutdown_tx: Some(shutdown_tx),
             },
         ))
@@ -658,14 +905,107 @@ impl AuditLogger {
             enabled: false,
             writer_tx: None,
             export_tx: None,
+            archive_tx: None,
             redaction_rules: CompiledRedactionRules::empty(),
+            fail_on_overflow: false,
+            live_tx: broadcast::channel(AUDIT_STREAM_BUFFER).0,
+            writer_dropped: AtomicU64::new(0),
+            export_dropped: AtomicU64::new(0),
+            archive_dropped: AtomicU64::new(0),
+            file_ok: None,
+            export_ok: None,
+            archive_ok: None,
         }
     }
 
-    /// Log an audit entry (non-blocking)
+    /// Whether the audit file is accepting writes
     ///
-    /// This method never blocks the async runtime. Entries are sent to background
-    /// tasks for writing. If the channel is full, entries may be dropped.
+    /// Reflects the most recent write, so a file that became unwritable is
+    /// reported once the next entry fails. Always `true` without a file.
+    pub fn file_writable(&self) -> bool {
+        self.file_ok
+            .as_ref()
+            .map_or(true, |ok| ok.load(Ordering::Relaxed))
+    }
+
+    /// Whether the audit export endpoint is accepting batches
+    ///
+    /// Reflects the most recent batch, which is only dropped after all its
+    /// retries failed. Always `true` without `export_url`.
+    pub fn export_healthy(&self) -> bool {
+        self.export_ok
+            .as_ref()
+            .map_or(true, |ok| ok.load(Ordering::Relaxed))
+    }
+
+    /// Whether the object storage archive is accepting uploads
+    ///
+    /// Reflects the most recent upload, which is only dropped after all its
+    /// retries failed. Always `true` without `[audit.object_storage]`.
+    pub fn archive_healthy(&self) -> bool {
+        self.archive_ok
+            .as_ref()
+            .map_or(true, |ok| ok.load(Ordering::Relaxed))
+    }
+
+    /// Subscribe to redacted entries as they are logged
+    ///
+    /// Subscribers that fall more than 256 entries behind skip the oldest
+    /// and get [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<AuditEntry> {
+        self.live_tx.subscribe()
+    }
+
+    /// Whether requests may proceed
+    ///
+    /// With `overflow = "fail_request"`, `false` while any audit queue is
+    /// full, so requests are rejected rather than served unaudited. Always
+    /// `true` with other strategies.
+    pub fn accepting_requests(&self) -> bool {
+        if !self.fail_on_overflow {
+            return true;
+        }
+        fn full<T>(tx: &Option<QueueSender<T>>) -> bool {
+            tx.as_ref().is_some_and(QueueSender::is_full)
+        }
+        !(full(&self.writer_tx) || full(&self.export_tx) || full(&self.archive_tx))
+    }
+
+    /// Channel fill levels and dropped entry counts
+    pub fn health(&self) -> AuditHealth {
+        fn queue<T>(tx: &QueueSender<T>, dropped: &AtomicU64) -> AuditQueueHealth {
+            AuditQueueHealth {
+                queued: tx.len(),
+                capacity: tx.capacity(),
+                dropped: dropped.load(Ordering::Relaxed),
+            }
+        }
+        AuditHealth {
+            enabled: self.enabled,
+            file_writable: self.file_ok.as_ref().map(|_| self.file_writable()),
+            export_healthy: self.export_ok.as_ref().map(|_| self.export_healthy()),
+            archive_healthy: self.archive_ok.as_ref().map(|_| self.archive_healthy()),
+            writer: self
+                .writer_tx
+                .as_ref()
+                .map(|tx| queue(tx, &self.writer_dropped)),
+            export: self
+                .export_tx
+                .as_ref()
+                .map(|tx| queue(tx, &self.export_dropped)),
+            archive: self
+                .archive_tx
+                .as_ref()
+                .map(|tx| queue(tx, &self.archive_dropped)),
+        }
+    }
+
+    /// Log an audit entry
+    ///
+    /// Entries are sent to background tasks for writing. If a channel is full,
+    /// `audit.overflow` decides whether this entry or the oldest queued one is
+    /// dropped; only `block` makes this method wait, for at most
+    /// `audit.overflow_block_ms`.
     ///
     /// Secret redaction is applied before serialization if redaction rules are configured.
     pub fn log(&self, entry: &AuditEntry) {
@@ -674,11 +1014,14 @@ impl AuditLogger {
         }
 
         // Apply redaction to the entry before serializing
-        let redacted_entry = if self.redaction_rules.is_empty() {
+        let mut redacted_entry = if self.redaction_rules.is_empty() {
             entry.clone()
         } else {
             self.redact_entry(entry)
         };
+        if redacted_entry.tenant.is_none() {
+            redacted_entry.tenant = current_tenant().map(sanitize_audit_string);
+        }
 
         let json = match serde_json::to_string(&redacted_entry) {
             Ok(j) => j,
@@ -694,17 +1037,49 @@ impl AuditLogger {
             }
         };
 
+        // Send to live stream subscribers, if any
+        if self.live_tx.receiver_count() > 0 {
+            let _ = self.live_tx.send(redacted_entry.clone());
+        }
+
+        // In privacy mode, copies leaving the host carry the hashed identity
+        let hashed_entry = (telemetry_privacy_enabled()
+            && (self.export_tx.is_some() || self.archive_tx.is_some()))
+        .then(|| AuditEntry {
+            identity_id: redacted_entry
+                .identity_id
+                .as_deref()
+                .map(telemetry_identity),
+            ..redacted_entry.clone()
+        });
+
+        // Send to object storage archiver if configured
+        if let Some(ref tx) = self.archive_tx {
+            let archived = match hashed_entry {
+                Some(ref entry) => serde_json::to_string(entry).ok(),
+                None => Some(json.clone()),
+            };
+            if archived.is_some_and(|archived| tx.send(archived).dropped()) {
+                self.archive_dropped.fetch_add(1, Ordering::Relaxed);
+                record_audit_dropped("archive");
+            }
+        }
+
         // Send to local writer (file + stdout)
         if let Some(ref tx) = self.writer_tx {
-            // Use try_send to avoid blocking
-            if tx.try_send(AuditMessage::Entry(json.clone())).is_err() {
+            if tx.send(AuditMessage::Entry(json.clone())).dropped() {
+                self.writer_dropped.fetch_add(1, Ordering::Relaxed);
+                record_audit_dropped("writer");
                 tracing::warn!("Audit log channel full, entry dropped");
             }
         }
 
         // Send to HTTP shipper if configured (use redacted entry)
         if let Some(ref tx) = self.export_tx {
-            let _ = tx.try_send(redacted_entry);
+            if tx.send(hashed_entry.unwrap_or(redacted_entry)).dropped() {
+                self.export_dropped.fetch_add(1, Ordering::Relaxed);
+                record_audit_dropped("export");
+            }
         }
     }
 
@@ -732,6 +1107,9 @@ impl AuditLogger {
                 .request_id
                 .as_ref()
                 .map(|s| self.redaction_rules.redact(s)),
+            tenant: entry.tenant.clone(),
+            severity: entry.severity,
+            authz_rule: entry.authz_rule.clone(),
         }
     }
 
@@ -775,16 +1153,200 @@ impl AuditLogger {
         );
     }
 
-    /// Log authorization denial
-    pub fn log_authz_denied(&self, identity_id: &str, tool: &str, reason: &str) {
+    /// Log authorization denial, with the rule that denied when known
+    pub fn log_authz_denied(
+        &self,
+        identity_id: &str,
+        tool: &str,
+        reason: &str,
+        rule: Option<&AuthzRule>,
+    ) {
+        let mut entry = AuditEntry::new(EventType::AuthzDenied)
+            .with_identity(identity_id)
+            .with_tool(tool)
+            .with_success(false)
+            .with_message(reason);
+
+        if let Some(rule) = rule {
+            entry = entry.with_authz_rule(rule.clone());
+        }
+
+        self.log(&entry);
+    }
+
+    /// Log a request blocked by network access control
+    pub fn log_network_blocked(&self, identity_id: Option<&str>, client_ip: &str, reason: &str) {
+        let mut entry = AuditEntry::new(EventType::NetworkBlocked)
+            .with_success(false)
+            .with_message(format!("{}: {}", client_ip, reason));
+
+        if let Some(id) = identity_id {
+            entry = entry.with_identity(id);
+        }
+
+        self.log(&entry);
+    }
+
+    /// Log an identity force-expired through the admin API
+    pub fn log_identity_expired(&self, admin_id: &str, identity_id: &str) {
+        self.log(
+            &AuditEntry::new(EventType::IdentityExpired)
+                .with_identity(identity_id)
+                .with_success(true)
+                .with_message(format!("expired by {}", admin_id)),
+        );
+    }
+
+    /// Log an admin override or reset of an identity's rate limit
+    pub fn log_rate_limit_changed(&self, admin_id: &str, identity_id: &str, change: &str) {
         self.log(
-            &AuditEntry::new(EventType::AuthzDenied)
+            &AuditEntry::new(EventType::RateLimitChanged)
+                .with_identity(identity_id)
+                .with_success(true)
+                .with_message(format!("{} by {}", change, admin_id)),
+        );
+    }
+
+    /// Log an admin restart or drain of an upstream server
+    pub fn log_upstream_changed(&self, admin_id: &str, server: &str, change: &str) {
+        self.log(
+            &AuditEntry::new(EventType::UpstreamChanged)
+                .with_identity(admin_id)
+                .with_success(true)
+                .with_message(format!("{} of upstream '{}'", change, server)),
+        );
+    }
+
+    /// Log an admin switching read-only mode for every identity
+    pub fn log_read_only_changed(&self, admin_id: &str, enabled: bool) {
+        self.log(
+            &AuditEntry::new(EventType::ReadOnlyChanged)
+                .with_identity(admin_id)
+                .with_success(true)
+                .with_message(if enabled {
+                    "read-only mode enabled"
+                } else {
+                    "read-only mode disabled"
+                }),
+        );
+    }
+
+    /// Log a tool call parked until an approver decides
+    pub fn log_approval_requested(&self, identity_id: &str, tool: &str, approval_id: &str) {
+        self.log(
+            &AuditEntry::new(EventType::ApprovalRequested)
                 .with_identity(identity_id)
                 .with_tool(tool)
+                .with_success(true)
+                .with_message(format!("approval {} requested", approval_id)),
+        );
+    }
+
+    /// Log an approver's decision on a parked tool call, or its timeout
+    ///
+    /// `approver` is `None` when nobody decided in time.
+    pub fn log_approval_decided(
+        &self,
+        identity_id: &str,
+        tool: &str,
+        approval_id: &str,
+        approved: bool,
+        approver: Option<&str>,
+    ) {
+        let (event_type, verb) = if approved {
+            (EventType::ApprovalGranted, "approved")
+        } else {
+            (EventType::ApprovalDenied, "denied")
+        };
+        let message = match approver {
+            Some(approver) => format!("approval {} {} by {}", approval_id, verb, approver),
+            None => format!("approval {} timed out", approval_id),
+        };
+        self.log(
+            &AuditEntry::new(event_type)
+                .with_identity(identity_id)
+                .with_tool(tool)
+                .with_success(approved)
+                .with_message(message),
+        );
+    }
+
+    /// Log a call to a decoy tool
+    pub fn log_honeypot_triggered(&self, identity_id: &str, tool: &str, locked_out: bool) {
+        let message = if locked_out {
+            "decoy tool called; identity locked out"
+        } else {
+            "decoy tool called"
+        };
+        self.log(
+            &AuditEntry::new(EventType::HoneypotTriggered)
+                .with_identity(identity_id)
+                .with_method("tools/call")
+                .with_tool(tool)
                 .with_success(false)
-                .with_message(reason),
+                .with_message(message)
+                .with_severity(AuditSeverity::High),
         );
     }
+
+    /// Log a request made with a break-glass key
+    pub fn log_break_glass(&self, used: &crate::break_glass::BreakGlassUse) {
+        let message = format!(
+            "{}break-glass access until {}; justification: {}",
+            if used.activated { "activated " } else { "" },
+            used.expires_at.to_rfc3339(),
+            used.justification
+        );
+        self.log(
+            &AuditEntry::new(EventType::BreakGlass)
+                .with_identity(&used.identity_id)
+                .with_success(true)
+                .with_message(message)
+                .with_severity(AuditSeverity::Critical),
+        );
+    }
+
+    /// Log a rejected request made with a break-glass key
+    pub fn log_break_glass_rejected(&self, identity_id: &str, reason: &str) {
+        self.log(
+            &AuditEntry::new(EventType::BreakGlass)
+                .with_identity(identity_id)
+                .with_success(false)
+                .with_message(format!("break-glass request rejected: {}", reason))
+                .with_severity(AuditSeverity::Critical),
+        );
+    }
+
+    /// Log an API key that expires within the warning window
+    pub fn log_key_expiring(&self, key_id: &str, expires_at: DateTime<Utc>) {
+        self.log(
+            &AuditEntry::new(EventType::KeyExpiring)
+                .with_identity(key_id)
+                .with_success(true)
+                .with_message(format!("API key expires at {}", expires_at.to_rfc3339())),
+        );
+    }
+
+    /// Log a request whose params matched a scrubbing rule
+    pub fn log_secret_scrubbed(
+        &self,
+        identity_id: &str,
+        tool: Option<&str>,
+        rule: &str,
+        blocked: bool,
+    ) {
+        let action = if blocked { "blocked" } else { "redacted" };
+        let mut entry = AuditEntry::new(EventType::SecretScrubbed)
+            .with_identity(identity_id)
+            .with_success(!blocked)
+            .with_message(format!("{} by scrubbing rule '{}'", action, rule));
+
+        if let Some(tool) = tool {
+            entry = entry.with_tool(tool);
+        }
+
+        self.log(&entry);
+    }
 }
 
 impl Default for AuditLogger {
@@ -801,7 +1363,7 @@ pub fn default_audit_path() -> PathBuf {
 /// File writer abstraction supporting both simple and rotating writes
 enum FileWriter {
     /// Simple append-only file
-    Simple(File),
+    Simple(BufWriter<File>),
     /// Rotating file with size/time-based rotation
     Rotating(RotatingFileWriter),
 }
@@ -817,45 +1379,119 @@ impl FileWriter {
         }
     }
 
-    /// Flush buffered data
+    /// Flush buffered data to the OS
     fn flush(&mut self) -> io::Result<()> {
         match self {
             FileWriter::Simple(f) => f.flush(),
             FileWriter::Rotating(r) => r.flush(),
         }
     }
+
+    /// Flush buffered data and fsync it to disk
+    fn sync(&mut self) -> io::Result<()> {
+        match self {
+            FileWriter::Simple(f) => {
+                f.flush()?;
+                f.get_ref().sync_data()
+            }
+            FileWriter::Rotating(r) => r.sync(),
+        }
+    }
+}
+
+/// When the audit writer flushes and fsyncs the file
+#[derive(Debug, Clone, Copy)]
+struct FileFlushPolicy {
+    /// Entries written before the file is flushed
+    batch_size: usize,
+    /// Longest a partial batch waits before it is flushed
+    interval: Duration,
+    fsync: FsyncPolicy,
+}
+
+impl FileFlushPolicy {
+    /// Flush a batch, fsyncing it if the policy says so
+    fn flush(&self, writer: &mut FileWriter) -> io::Result<()> {
+        match self.fsync {
+            FsyncPolicy::Flush => writer.sync(),
+            FsyncPolicy::Never | FsyncPolicy::Shutdown => writer.flush(),
+        }
+    }
+
+    /// Final flush on shutdown
+    fn close(&self, writer: &mut FileWriter) -> io::Result<()> {
+        match self.fsync {
+            FsyncPolicy::Flush | FsyncPolicy::Shutdown => writer.sync(),
+            FsyncPolicy::Never => writer.flush(),
+        }
+    }
+}
+
+/// Record the outcome of a file write in the health flag
+fn report_file_result(result: io::Result<()>, file_ok: &Option<Arc<AtomicBool>>) {
+    if let Err(ref e) = result {
+        tracing::error!(error = %e, "Failed to write audit entry to file");
+    }
+    if let Some(ref ok) = file_ok {
+        ok.store(result.is_ok(), Ordering::Relaxed);
+    }
 }
 
 /// Background task that writes audit entries to file and/or stdout
 ///
-/// Supports both simple file writes and rotating file writes.
+/// Supports both simple file writes and rotating file writes. File writes
+/// are buffered and flushed every `batch_size` entries, or once `interval`
+/// has passed with entries pending.
 async fn run_audit_writer(
-    mut rx: mpsc::Receiver<AuditMessage>,
+    mut rx: QueueReceiver<AuditMessage>,
     mut file_writer: Option<FileWriter>,
+    policy: FileFlushPolicy,
     stdout_enabled: bool,
+    file_ok: Option<Arc<AtomicBool>>,
 ) {
-    while let Some(msg) = rx.recv().await {
-        match msg {
-            AuditMessage::Entry(json) => {
-                // Write to stdout (quick, unlikely to block significantly)
-                if stdout_enabled {
-                    println!("{}", json);
-                }
+    let mut pending = 0usize;
+    let mut flush_interval = tokio::time::interval(policy.interval);
+    flush_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            msg = rx.recv() => match msg {
+                Some(AuditMessage::Entry(json)) => {
+                    // Write to stdout (quick, unlikely to block significantly)
+                    if stdout_enabled {
+                        println!("{}", json);
+                    }
 
-                // Write to file (with or without rotation)
-                if let Some(ref mut writer) = file_writer {
-                    if let Err(e) = writer.write_line(&json) {
-                        tracing::error!(error = %e, "Failed to write audit entry to file");
+                    // Write to file (with or without rotation)
+                    if let Some(ref mut writer) = file_writer {
+                        let mut result = writer.write_line(&json);
+                        pending += 1;
+                        if pending == 1 {
+                            // A new batch waits at most one interval
+                            flush_interval.reset();
+                        }
+                        if pending >= policy.batch_size {
+                            result = result.and_then(|()| policy.flush(writer));
+                            pending = 0;
+                        }
+                        report_file_result(result, &file_ok);
                     }
                 }
-            }
-            AuditMessage::Shutdown => {
-                tracing::debug!("Audit writer received shutdown signal");
-                // Flush file before exiting
+                Some(AuditMessage::Shutdown) | None => {
+                    tracing::debug!("Audit writer received shutdown signal");
+                    // Flush file before exiting
+                    if let Some(ref mut writer) = file_writer {
+                        let _ = policy.close(writer);
+                    }
+                    break;
+                }
+            },
+            // Partial batch has waited long enough
+            _ = flush_interval.tick(), if pending > 0 => {
                 if let Some(ref mut writer) = file_writer {
-                    let _ = writer.flush();
+                    report_file_result(policy.flush(writer), &file_ok);
                 }
-                break;
+                pending = 0;
             }
         }
     }
@@ -879,6 +1515,8 @@ struct AuditShipper {
     flush_interval: Duration,
     /// HTTP client
     client: reqwest::Client,
+    /// Set after each batch: shipped, or dropped after all retries
+    status: Option<Arc<AtomicBool>>,
 }
 
 /// Batch of audit entries to ship
@@ -904,6 +1542,7 @@ impl AuditShipper {
     ) -> Self {
         let client = reqwest::Client::builder()
             .timeout(Duration::from_secs(AUDIT_HTTP_TIMEOUT_SECS))
+            .redirect(crate::egress::redirect_policy())
             .build()
             .unwrap_or_else(|e| {
                 tracing::warn!(
@@ -919,11 +1558,24 @@ impl AuditShipper {
             batch_size,
             flush_interval: Duration::from_secs(flush_interval_secs),
             client,
+            status: None,
+        }
+    }
+
+    /// Report the outcome of each batch to `status`
+    fn with_status(mut self, status: Arc<AtomicBool>) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    fn set_status(&self, shipped: bool) {
+        if let Some(ref status) = self.status {
+            status.store(shipped, Ordering::Relaxed);
         }
     }
 
     /// Run the shipper, receiving entries from the channel and batching them
-    async fn run(self, mut rx: mpsc::Receiver<AuditEntry>) {
+    async fn run(self, mut rx: QueueReceiver<AuditEntry>) {
         let mut batch: Vec<AuditEntry> = Vec::with_capacity(self.batch_size);
         let mut interval = tokio::time::interval(self.flush_interval);
 
@@ -981,6 +1633,7 @@ impl AuditShipper {
             match self.send_batch(&payload).await {
                 Ok(()) => {
                     tracing::debug!(count = count, "Shipped audit batch");
+                    self.set_status(true);
                     return;
                 }
                 Err(e) => {
@@ -1002,6 +1655,7 @@ impl AuditShipper {
             count = count,
             "Failed to ship audit batch after 3 retries, dropping"
         );
+        self.set_status(false);
     }
 
     /// Send a batch to the HTTP endpoint
@@ -1049,6 +1703,11 @@ mod tests {
         AuditConfig {
             enabled: true,
             file: None,
+            file_batch_size: 1,
+            file_flush_interval_ms: 1000,
+            file_fsync: FsyncPolicy::Never,
+            overflow: AuditOverflowStrategy::DropNewest,
+            overflow_block_ms: 100,
             stdout: false,
             export_url: None,
             export_headers: HashMap::new(),
@@ -1056,6 +1715,8 @@ mod tests {
             export_interval_secs: 30,
             redaction_rules: Vec::new(),
             rotation: None,
+            object_storage: None,
+            detectors: Vec::new(),
         }
     }
 
@@ -1170,6 +1831,11 @@ mod tests {
         let config = AuditConfig {
             enabled: true,
             file: Some(file_path.clone()),
+            file_batch_size: 1,
+            file_flush_interval_ms: 1000,
+            file_fsync: FsyncPolicy::Never,
+            overflow: AuditOverflowStrategy::DropNewest,
+            overflow_block_ms: 100,
             stdout: false,
             export_url: None,
             export_headers: HashMap::new(),
@@ -1181,6 +1847,8 @@ mod tests {
                 replacement: "Bearer [REDACTED]".to_string(),
             }],
             rotation: None,
+            object_storage: None,
+            detectors: Vec::new(),
         };
 
         let (logger, handle) = AuditLogger::with_tasks(&config).expect("Should create logger");
@@ -1306,6 +1974,11 @@ mod tests {
         let config = AuditConfig {
             enabled: true,
             file: Some(log_path.clone()),
+            file_batch_size: 1,
+            file_flush_interval_ms: 1000,
+            file_fsync: FsyncPolicy::Never,
+            overflow: AuditOverflowStrategy::DropNewest,
+            overflow_block_ms: 100,
             stdout: false,
             export_url: None,
             export_headers: HashMap::new(),
@@ -1319,6 +1992,8 @@ mod tests {
                 max_backups: 3,
                 compress: false,
             }),
+            object_storage: None,
+            detectors: Vec::new(),
         };
 
         let (logger, handle) = AuditLogger::with_tasks(&config).expect("Should create logger");
@@ -1389,6 +2064,23 @@ mod tests {
         assert!(json.contains("\"success\":false"));
     }
 
+    #[test]
+    fn test_audit_entry_authz_rule_serialization() {
+        let entry = AuditEntry::new(EventType::AuthzDenied).with_identity("user1");
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(!json.contains("authz_rule"));
+
+        let entry = entry.with_authz_rule(AuthzRule {
+            rule: "constraints".to_string(),
+            pattern: Some("read_file:path".to_string()),
+            order: Some(2),
+        });
+        let json: serde_json::Value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["authz_rule"]["rule"], "constraints");
+        assert_eq!(json["authz_rule"]["pattern"], "read_file:path");
+        assert_eq!(json["authz_rule"]["order"], 2);
+    }
+
     #[test]
     fn test_audit_batch_serialization() {
         let entries = vec![
@@ -1421,7 +2113,7 @@ mod tests {
         logger.log_auth_failure("bad credentials");
         logger.log_tool_call("user1", "read_file", Some("req-1"));
         logger.log_rate_limited("user1");
-        logger.log_authz_denied("user1", "write_file", "not allowed");
+        logger.log_authz_denied("user1", "write_file", "not allowed", None);
     }
 
     #[test]
@@ -1457,6 +2149,18 @@ mod tests {
             (EventType::ToolResponse, "tool_response"),
             (EventType::RateLimited, "rate_limited"),
             (EventType::AuthzDenied, "authz_denied"),
+            (EventType::NetworkBlocked, "network_blocked"),
+            (EventType::SecretScrubbed, "secret_scrubbed"),
+            (EventType::IdentityExpired, "identity_expired"),
+            (EventType::RateLimitChanged, "rate_limit_changed"),
+            (EventType::UpstreamChanged, "upstream_changed"),
+            (EventType::ReadOnlyChanged, "read_only_changed"),
+            (EventType::ApprovalRequested, "approval_requested"),
+            (EventType::ApprovalGranted, "approval_granted"),
+            (EventType::ApprovalDenied, "approval_denied"),
+            (EventType::KeyExpiring, "key_expiring"),
+            (EventType::HoneypotTriggered, "honeypot_triggered"),
+            (EventType::BreakGlass, "break_glass"),
             (EventType::Error, "error"),
         ];
 
@@ -1531,6 +2235,169 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_audit_logger_file_batching() {
+        let temp_file = NamedTempFile::new().expect("Should create temp file");
+        let file_path = temp_file.path().to_path_buf();
+        let line_count = || {
+            std::fs::read_to_string(&file_path)
+                .expect("Should read file")
+                .lines()
+                .count()
+        };
+
+        let mut config = test_config();
+        config.file = Some(file_path.clone());
+        config.file_batch_size = 3;
+        config.file_flush_interval_ms = 60_000;
+
+        let (logger, handle) = AuditLogger::with_tasks(&config).expect("Should create logger");
+
+        // A partial batch stays buffered
+        logger.log_auth_success("user1");
+        logger.log_auth_success("user2");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(line_count(), 0);
+
+        // A full batch is flushed
+        logger.log_auth_success("user3");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(line_count(), 3);
+
+        // Shutdown flushes the rest
+        logger.log_auth_success("user4");
+        handle.shutdown().await;
+        assert_eq!(line_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_file_flush_interval() {
+        let temp_file = NamedTempFile::new().expect("Should create temp file");
+        let file_path = temp_file.path().to_path_buf();
+
+        let mut config = test_config();
+        config.file = Some(file_path.clone());
+        config.file_batch_size = 100;
+        config.file_flush_interval_ms = 50;
+        config.file_fsync = FsyncPolicy::Flush;
+
+        let (logger, handle) = AuditLogger::with_tasks(&config).expect("Should create logger");
+
+        logger.log_auth_success("interval_user");
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let contents = std::fs::read_to_string(&file_path).expect("Should read file");
+        assert!(contents.contains("interval_user"));
+
+        handle.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_health_counts_drops() {
+        let config = test_config();
+        let (logger, handle) = AuditLogger::with_tasks(&config).expect("Should create logger");
+
+        // The writer task can't run until we yield, so the channel fills up
+        for _ in 0..AUDIT_CHANNEL_SIZE + 5 {
+            logger.log_auth_success("user1");
+        }
+
+        let health = logger.health();
+        assert!(health.enabled);
+        assert!(health.export.is_none());
+        let writer = health.writer.expect("writer channel");
+        assert_eq!(writer.queued, AUDIT_CHANNEL_SIZE);
+        assert_eq!(writer.capacity, AUDIT_CHANNEL_SIZE);
+        assert_eq!(writer.dropped, 5);
+
+        handle.shutdown().await;
+        assert!(AuditLogger::disabled().health().writer.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_subscribe() {
+        let mut config = test_config();
+        config.redaction_rules = vec![RedactionRule {
+            name: "tokens".to_string(),
+            pattern: "tok_[a-z]+".to_string(),
+            replacement: "[REDACTED]".to_string(),
+        }];
+        let logger = AuditLogger::new(&config).expect("Should create logger");
+
+        // Entries logged before subscribing are not replayed
+        logger.log_auth_success("early");
+        let mut live = logger.subscribe();
+        logger.log_auth_failure("bad token tok_secret");
+
+        let entry = live.try_recv().expect("live entry");
+        assert_eq!(entry.event_type, EventType::AuthFailure);
+        assert_eq!(entry.message.as_deref(), Some("bad token [REDACTED]"));
+        assert!(live.try_recv().is_err());
+
+        // Disabled loggers publish nothing
+        let disabled = AuditLogger::disabled();
+        let mut live = disabled.subscribe();
+        disabled.log_auth_success("user1");
+        assert!(live.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_overflow_drop_oldest() {
+        let mut config = test_config();
+        config.overflow = AuditOverflowStrategy::DropOldest;
+        let (logger, handle) = AuditLogger::with_tasks(&config).expect("Should create logger");
+
+        // The writer task can't run until we yield, so the queue stays full
+        for _ in 0..AUDIT_CHANNEL_SIZE + 5 {
+            logger.log_auth_success("user1");
+        }
+
+        let writer = logger.health().writer.expect("writer channel");
+        assert_eq!(writer.queued, AUDIT_CHANNEL_SIZE);
+        assert_eq!(writer.dropped, 5);
+        assert!(logger.accepting_requests());
+
+        handle.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_overflow_fail_request() {
+        let mut config = test_config();
+        config.overflow = AuditOverflowStrategy::FailRequest;
+        let (logger, handle) = AuditLogger::with_tasks(&config).expect("Should create logger");
+        assert!(logger.accepting_requests());
+
+        for _ in 0..AUDIT_CHANNEL_SIZE {
+            logger.log_auth_success("user1");
+        }
+        assert!(!logger.accepting_requests());
+
+        // Requests are accepted again once the writer catches up
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(logger.accepting_requests());
+
+        handle.shutdown().await;
+        assert!(AuditLogger::disabled().accepting_requests());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_audit_logger_reports_unwritable_file() {
+        let mut config = test_config();
+        // Every write to /dev/full fails with ENOSPC
+        config.file = Some(PathBuf::from("/dev/full"));
+
+        let (logger, handle) = AuditLogger::with_tasks(&config).expect("Should create logger");
+        assert!(logger.file_writable());
+
+        logger.log_auth_success("user1");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!logger.file_writable());
+        assert_eq!(logger.health().file_writable, Some(false));
+
+        handle.shutdown().await;
+        assert!(AuditLogger::disabled().file_writable());
+    }
+
     #[tokio::test]
     async fn test_audit_logger_log_method_with_entry() {
         let config = test_config();
@@ -1564,6 +2431,24 @@ mod tests {
         assert_eq!(shipper.flush_interval, Duration::from_secs(30));
     }
 
+    #[tokio::test]
+    async fn test_audit_shipper_reports_dropped_batches() {
+        // Nothing listens on port 9 of localhost
+        let status = Arc::new(AtomicBool::new(true));
+        let shipper = AuditShipper::new(
+            "http://127.0.0.1:9/logs".to_string(),
+            HashMap::new(),
+            100,
+            30,
+        )
+        .with_status(status.clone());
+
+        let mut batch = vec![AuditEntry::new(EventType::AuthSuccess)];
+        shipper.flush(&mut batch).await;
+        assert!(!status.load(Ordering::Relaxed));
+        assert!(AuditLogger::disabled().export_healthy());
+    }
+
     #[test]
     fn test_audit_shipper_with_headers() {
         let mut headers = HashMap::new();
@@ -1606,7 +2491,7 @@ mod tests {
         let (logger, handle) = AuditLogger::with_tasks(&config).expect("Should create logger");
 
         // Flood the channel with many messages (channel size is 1000)
-        // Send 5000 messages which forces buffer overflow logic (try_send)
+        // Send 5000 messages which forces buffer overflow logic (drop_newest)
         let start = std::time::Instant::now();
         for i in 0..5000 {
             logger.log_auth_success(&format!("user{}", i));
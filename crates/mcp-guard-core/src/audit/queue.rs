@@ -0,0 +1,327 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Bounded queues between the audit logger and its background tasks
+//!
+//! Like a bounded `tokio::sync::mpsc` channel, but sending is synchronous and
+//! a full queue either drops the new entry, drops its oldest entry, or makes
+//! the sender wait for space, as set by `audit.overflow`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::Notify;
+
+use crate::config::AuditOverflowStrategy;
+
+/// What a sender does when the queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Overflow {
+    /// Drop the entry being sent
+    DropNewest,
+    /// Drop the oldest queued entry to make room
+    DropOldest,
+    /// Wait up to the timeout for space, then drop the entry being sent
+    Block(Duration),
+}
+
+impl Overflow {
+    /// Queue behaviour for a configured strategy
+    ///
+    /// `fail_request` drops new entries; rejecting requests is up to the caller.
+    pub(crate) fn new(strategy: AuditOverflowStrategy, block_timeout: Duration) -> Self {
+        match strategy {
+            AuditOverflowStrategy::DropNewest | AuditOverflowStrategy::FailRequest => {
+                Overflow::DropNewest
+            }
+            AuditOverflowStrategy::DropOldest => Overflow::DropOldest,
+            AuditOverflowStrategy::Block => Overflow::Block(block_timeout),
+        }
+    }
+}
+
+/// Outcome of [`QueueSender::send`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Sent {
+    /// Queued without loss
+    Queued,
+    /// Queued after dropping the oldest entry
+    DroppedOldest,
+    /// Not queued, because the queue stayed full or the receiver is gone
+    Dropped,
+}
+
+impl Sent {
+    /// Whether an entry was lost
+    pub(crate) fn dropped(self) -> bool {
+        self != Sent::Queued
+    }
+}
+
+struct State<T> {
+    items: VecDeque<T>,
+    senders: usize,
+    receiver_alive: bool,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    capacity: usize,
+    overflow: Overflow,
+    /// Wakes the receiver when an entry arrives or the last sender is dropped
+    item_ready: Notify,
+    /// Wakes blocked senders when the receiver takes an entry
+    space_free: Condvar,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wait up to `timeout` for the queue to have room
+    fn wait_for_space<'a>(
+        &'a self,
+        state: MutexGuard<'a, State<T>>,
+        timeout: Duration,
+    ) -> MutexGuard<'a, State<T>> {
+        let wait = || {
+            self.space_free
+                .wait_timeout_while(state, timeout, |state| {
+                    state.receiver_alive && state.items.len() >= self.capacity
+                })
+                .map_or_else(|e| e.into_inner().0, |(state, _)| state)
+        };
+        // Let the runtime move other tasks, such as the receiver, off this worker
+        match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(wait)
+            }
+            _ => wait(),
+        }
+    }
+}
+
+/// Create a queue holding up to `capacity` entries
+pub(crate) fn bounded<T>(
+    capacity: usize,
+    overflow: Overflow,
+) -> (QueueSender<T>, QueueReceiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            items: VecDeque::with_capacity(capacity),
+            senders: 1,
+            receiver_alive: true,
+        }),
+        capacity,
+        overflow,
+        item_ready: Notify::new(),
+        space_free: Condvar::new(),
+    });
+    (
+        QueueSender {
+            shared: shared.clone(),
+        },
+        QueueReceiver { shared },
+    )
+}
+
+/// Sending half of an audit queue
+pub(crate) struct QueueSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> QueueSender<T> {
+    /// Queue an entry, applying the overflow behaviour if the queue is full
+    ///
+    /// Never waits unless the overflow behaviour is [`Overflow::Block`].
+    pub(crate) fn send(&self, item: T) -> Sent {
+        let mut state = self.shared.lock();
+        if !state.receiver_alive {
+            return Sent::Dropped;
+        }
+        let mut sent = Sent::Queued;
+        if state.items.len() >= self.shared.capacity {
+            match self.shared.overflow {
+                Overflow::DropNewest => return Sent::Dropped,
+                Overflow::DropOldest => {
+                    state.items.pop_front();
+                    sent = Sent::DroppedOldest;
+                }
+                Overflow::Block(timeout) => {
+                    state = self.shared.wait_for_space(state, timeout);
+                    if !state.receiver_alive || state.items.len() >= self.shared.capacity {
+                        return Sent::Dropped;
+                    }
+                }
+            }
+        }
+        state.items.push_back(item);
+        drop(state);
+        self.shared.item_ready.notify_one();
+        sent
+    }
+
+    /// Queue a control message even if the queue is full
+    pub(crate) fn send_control(&self, item: T) {
+        self.shared.lock().items.push_back(item);
+        self.shared.item_ready.notify_one();
+    }
+
+    /// Entries waiting to be received
+    pub(crate) fn len(&self) -> usize {
+        self.shared.lock().items.len()
+    }
+
+    /// Whether the queue has no room for another entry
+    pub(crate) fn is_full(&self) -> bool {
+        self.len() >= self.shared.capacity
+    }
+
+    /// Maximum number of queued entries
+    pub(crate) fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+}
+
+impl<T> Clone for QueueSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for QueueSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            drop(state);
+            self.shared.item_ready.notify_one();
+        }
+    }
+}
+
+/// Receiving half of an audit queue
+pub(crate) struct QueueReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> QueueReceiver<T> {
+    /// Wait for the next entry
+    ///
+    /// Returns `None` once all senders are dropped and the queue is empty.
+    /// Cancel safe: an entry is only taken when this returns.
+    pub(crate) async fn recv(&mut self) -> Option<T> {
+        loop {
+            {
+                let mut state = self.shared.lock();
+                if let Some(item) = state.items.pop_front() {
+                    drop(state);
+                    self.shared.space_free.notify_one();
+                    return Some(item);
+                }
+                if state.senders == 0 {
+                    return None;
+                }
+            }
+            self.shared.item_ready.notified().await;
+        }
+    }
+}
+
+impl<T> Drop for QueueReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.lock().receiver_alive = false;
+        self.shared.space_free.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drop_newest() {
+        let (tx, mut rx) = bounded(2, Overflow::DropNewest);
+        assert_eq!(tx.send(1), Sent::Queued);
+        assert_eq!(tx.send(2), Sent::Queued);
+        assert!(tx.is_full());
+        assert_eq!(tx.send(3), Sent::Dropped);
+
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, Some(2));
+        drop(tx);
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest() {
+        let (tx, mut rx) = bounded(2, Overflow::DropOldest);
+        tx.send(1);
+        tx.send(2);
+        assert_eq!(tx.send(3), Sent::DroppedOldest);
+        assert_eq!(tx.len(), 2);
+
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(rx.recv().await, Some(3));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_block_waits_for_receiver() {
+        let (tx, mut rx) = bounded(1, Overflow::Block(Duration::from_secs(5)));
+        tx.send(1);
+        let receiver = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let mut received = Vec::new();
+            while let Some(item) = rx.recv().await {
+                received.push(item);
+            }
+            received
+        });
+
+        assert_eq!(tx.send(2), Sent::Queued);
+        drop(tx);
+        assert_eq!(receiver.await.unwrap(), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_block_times_out() {
+        let (tx, _rx) = bounded(1, Overflow::Block(Duration::from_millis(20)));
+        tx.send(1);
+        assert_eq!(tx.send(2), Sent::Dropped);
+    }
+
+    #[tokio::test]
+    async fn test_send_control_ignores_capacity() {
+        let (tx, mut rx) = bounded(1, Overflow::DropNewest);
+        tx.send(1);
+        tx.send_control(2);
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_send_after_receiver_dropped() {
+        let (tx, rx) = bounded(1, Overflow::Block(Duration::from_secs(5)));
+        drop(rx);
+        assert_eq!(tx.send(1), Sent::Dropped);
+    }
+}
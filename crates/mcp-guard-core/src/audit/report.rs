@@ -0,0 +1,409 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Per-identity access reports from audit files
+//!
+//! Reads the JSON Lines audit file and its rotated (optionally gzipped)
+//! backups and aggregates, for each identity, the tools it called, the
+//! requests that were denied and the rate-limit events over a time range.
+//! Reports render as JSON or CSV for SOC 2 / ISO 27001 evidence.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use flate2::read::GzDecoder;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use super::{AuditEntry, EventType};
+
+/// Access report over a time range
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessReport {
+    /// When the report was generated
+    pub generated_at: DateTime<Utc>,
+    /// Start of the range (inclusive), `None` for the beginning of the logs
+    pub since: Option<DateTime<Utc>>,
+    /// End of the range (exclusive), `None` for the end of the logs
+    pub until: Option<DateTime<Utc>>,
+    /// Audit entries counted
+    pub entries: u64,
+    /// Lines that were not valid audit entries
+    pub skipped_lines: u64,
+    /// Per-identity activity, ordered by identity ID
+    pub identities: Vec<IdentityAccess>,
+    #[serde(skip)]
+    by_identity: BTreeMap<String, IdentityAccess>,
+    #[serde(skip)]
+    identity_filter: Option<String>,
+}
+
+/// One identity's activity in an [`AccessReport`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IdentityAccess {
+    pub identity_id: String,
+    /// Tenant of the most recent entry, when tenancy is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+    pub auth_successes: u64,
+    pub auth_failures: u64,
+    pub tool_calls: u64,
+    /// Calls per tool
+    pub tools: BTreeMap<String, u64>,
+    /// Authorization, network and approval denials
+    pub denials: u64,
+    /// Denials per tool (denials without a tool are only in `denials`)
+    pub denied_tools: BTreeMap<String, u64>,
+    pub rate_limited: u64,
+}
+
+impl AccessReport {
+    /// Empty report for the range `[since, until)`
+    pub fn new(since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Self {
+        Self {
+            generated_at: Utc::now(),
+            since,
+            until,
+            entries: 0,
+            skipped_lines: 0,
+            identities: Vec::new(),
+            by_identity: BTreeMap::new(),
+            identity_filter: None,
+        }
+    }
+
+    /// Only report on one identity
+    pub fn with_identity(mut self, identity_id: impl Into<String>) -> Self {
+        self.identity_filter = Some(identity_id.into());
+        self
+    }
+
+    /// Count an entry, if it is in range and names an identity
+    pub fn add(&mut self, entry: &AuditEntry) {
+        if self.since.is_some_and(|since| entry.timestamp < since)
+            || self.until.is_some_and(|until| entry.timestamp >= until)
+        {
+            return;
+        }
+        let Some(ref identity_id) = entry.identity_id else {
+            return;
+        };
+        if self
+            .identity_filter
+            .as_ref()
+            .is_some_and(|filter| filter != identity_id)
+        {
+            return;
+        }
+
+        self.entries += 1;
+        let access = self
+            .by_identity
+            .entry(identity_id.clone())
+            .or_insert_with(|| IdentityAccess {
+                identity_id: identity_id.clone(),
+                ..Default::default()
+            });
+        access.first_seen = Some(
+            access
+                .first_seen
+                .map_or(entry.timestamp, |seen| seen.min(entry.timestamp)),
+        );
+        if access
+            .last_seen
+            .map_or(true, |seen| entry.timestamp >= seen)
+        {
+            access.last_seen = Some(entry.timestamp);
+            if entry.tenant.is_some() {
+                access.tenant = entry.tenant.clone();
+            }
+        }
+
+        match entry.event_type {
+            EventType::AuthSuccess => access.auth_successes += 1,
+            EventType::AuthFailure => access.auth_failures += 1,
+            EventType::ToolCall => {
+                access.tool_calls += 1;
+                if let Some(ref tool) = entry.tool {
+                    *access.tools.entry(tool.clone()).or_default() += 1;
+                }
+            }
+            EventType::AuthzDenied | EventType::NetworkBlocked | EventType::ApprovalDenied => {
+                access.denials += 1;
+                if let Some(ref tool) = entry.tool {
+                    *access.denied_tools.entry(tool.clone()).or_default() += 1;
+                }
+            }
+            EventType::RateLimited => access.rate_limited += 1,
+            _ => {}
+        }
+    }
+
+    /// Count the entries of one audit file (plain or `.gz`)
+    ///
+    /// Lines that are not audit entries are skipped and counted in
+    /// `skipped_lines`.
+    pub fn read_file(&mut self, path: &Path) -> io::Result<()> {
+        let file = File::open(path)?;
+        let reader: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(GzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<AuditEntry>(&line) {
+                Ok(entry) => self.add(&entry),
+                Err(_) => self.skipped_lines += 1,
+            }
+        }
+        Ok(())
+    }
+
+    /// Collect the per-identity results into `identities`
+    pub fn finish(mut self) -> Self {
+        self.identities = self.by_identity.values().cloned().collect();
+        self
+    }
+
+    /// JSON rendering
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// CSV rendering, one row per identity
+    ///
+    /// `tools` and `denied_tools` are `name=count` pairs joined with `;`.
+    pub fn to_csv(&self) -> String {
+        fn counts(map: &BTreeMap<String, u64>) -> String {
+            map.iter()
+                .map(|(name, count)| format!("{}={}", name, count))
+                .collect::<Vec<_>>()
+                .join(";")
+        }
+        fn time(t: Option<DateTime<Utc>>) -> String {
+            t.map(|t| t.to_rfc3339()).unwrap_or_default()
+        }
+
+        let mut csv = String::from(
+            "identity_id,tenant,first_seen,last_seen,auth_successes,auth_failures,\
+             tool_calls,denials,rate_limited,tools,denied_tools\n",
+        );
+        for access in &self.identities {
+            let row = [
+                csv_field(&access.identity_id),
+                csv_field(access.tenant.as_deref().unwrap_or("")),
+                time(access.first_seen),
+                time(access.last_seen),
+                access.auth_successes.to_string(),
+                access.auth_failures.to_string(),
+                access.tool_calls.to_string(),
+                access.denials.to_string(),
+                access.rate_limited.to_string(),
+                csv_field(&counts(&access.tools)),
+                csv_field(&counts(&access.denied_tools)),
+            ];
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Quote a CSV field when needed, neutralizing spreadsheet formulas
+fn csv_field(value: &str) -> String {
+    // SECURITY: Identity IDs and tool names come from clients; a leading
+    // `=`, `+`, `-` or `@` would be evaluated by spreadsheet software
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// The audit file followed by its rotated backups, oldest backup first
+///
+/// Backups are the files named `<file name>.<suffix>` next to it, as written
+/// by `[audit.rotation]`. A missing audit file is skipped when backups exist.
+pub fn audit_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let base_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let prefix = format!("{}.", base_name);
+
+    let mut backups: Vec<PathBuf> = fs::read_dir(parent)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+        .map(|e| e.path())
+        .collect();
+    // Backup suffixes are timestamps, so name order is age order
+    backups.sort();
+
+    if path.exists() {
+        backups.push(path.to_path_buf());
+    }
+    if backups.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No audit files found at {}", path.display()),
+        ));
+    }
+    Ok(backups)
+}
+
+/// Parse a report boundary: RFC 3339 (`2025-01-31T12:00:00Z`) or a date
+/// (`2025-01-31`, midnight UTC)
+pub fn parse_report_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+        .ok_or_else(|| format!("'{}' is not an RFC 3339 time or YYYY-MM-DD date", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn entry(event_type: EventType, identity: &str, tool: Option<&str>, day: u32) -> AuditEntry {
+        let mut entry = AuditEntry::new(event_type).with_identity(identity);
+        if let Some(tool) = tool {
+            entry = entry.with_tool(tool);
+        }
+        entry.timestamp = Utc.with_ymd_and_hms(2025, 1, day, 12, 0, 0).unwrap();
+        entry
+    }
+
+    #[test]
+    fn test_report_aggregates_per_identity() {
+        let mut report = AccessReport::new(None, None);
+        report.add(&entry(EventType::AuthSuccess, "alice", None, 1));
+        report.add(&entry(EventType::ToolCall, "alice", Some("read_file"), 2));
+        report.add(&entry(EventType::ToolCall, "alice", Some("read_file"), 3));
+        report.add(&entry(EventType::AuthzDenied, "alice", Some("delete"), 3));
+        report.add(&entry(EventType::RateLimited, "bob", None, 4));
+        // No identity: not attributable
+        report.add(&AuditEntry::new(EventType::AuthFailure));
+        let report = report.finish();
+
+        assert_eq!(report.entries, 5);
+        assert_eq!(report.identities.len(), 2);
+        let alice = &report.identities[0];
+        assert_eq!(alice.identity_id, "alice");
+        assert_eq!(alice.auth_successes, 1);
+        assert_eq!(alice.tool_calls, 2);
+        assert_eq!(alice.tools["read_file"], 2);
+        assert_eq!(alice.denials, 1);
+        assert_eq!(alice.denied_tools["delete"], 1);
+        assert_eq!(
+            alice.first_seen,
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap())
+        );
+        assert_eq!(report.identities[1].rate_limited, 1);
+    }
+
+    #[test]
+    fn test_report_range_and_identity_filter() {
+        let since = parse_report_time("2025-01-02").unwrap();
+        let until = parse_report_time("2025-01-03T00:00:00Z").unwrap();
+        let mut report = AccessReport::new(Some(since), Some(until)).with_identity("alice");
+        for day in 1..=3 {
+            report.add(&entry(EventType::ToolCall, "alice", Some("read_file"), day));
+        }
+        report.add(&entry(EventType::ToolCall, "bob", Some("read_file"), 2));
+        let report = report.finish();
+
+        assert_eq!(report.entries, 1);
+        assert_eq!(report.identities.len(), 1);
+        assert_eq!(report.identities[0].tool_calls, 1);
+    }
+
+    #[test]
+    fn test_report_csv() {
+        let mut report = AccessReport::new(None, None);
+        report.add(&entry(EventType::ToolCall, "=cmd,x", Some("read_file"), 1));
+        report.add(&entry(EventType::ToolCall, "=cmd,x", Some("write_file"), 1));
+        let csv = report.finish().to_csv();
+
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("identity_id,tenant,"));
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("\"'=cmd,x\","));
+        assert!(row.ends_with(",read_file=1;write_file=1,"));
+    }
+
+    #[test]
+    fn test_read_rotated_and_compressed_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("audit.log");
+        let line = |day| serde_json::to_string(&entry(EventType::ToolCall, "svc", Some("t"), day));
+
+        let backup = dir.path().join("audit.log.20250101-000000.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&backup).unwrap(),
+            flate2::Compression::default(),
+        );
+        writeln!(encoder, "{}", line(1).unwrap()).unwrap();
+        encoder.finish().unwrap();
+        fs::write(&path, format!("{}\nnot json\n", line(2).unwrap())).unwrap();
+
+        let files = audit_files(&path).unwrap();
+        assert_eq!(files, vec![backup, path.clone()]);
+
+        let mut report = AccessReport::new(None, None);
+        for file in &files {
+            report.read_file(file).unwrap();
+        }
+        let report = report.finish();
+        assert_eq!(report.identities[0].tool_calls, 2);
+        assert_eq!(report.skipped_lines, 1);
+
+        assert!(audit_files(&dir.path().join("missing.log")).is_err());
+    }
+
+    #[test]
+    fn test_parse_report_time() {
+        assert_eq!(
+            parse_report_time("2025-01-31").unwrap(),
+            Utc.with_ymd_and_hms(2025, 1, 31, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_report_time("2025-01-31T12:00:00+02:00").unwrap(),
+            Utc.with_ymd_and_hms(2025, 1, 31, 10, 0, 0).unwrap()
+        );
+        assert!(parse_report_time("last week").is_err());
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Access windows and temporary grants for API keys
+//!
+//! A key with `access_windows` is only accepted inside one of them; `grants`
+//! widen its allow lists until they expire:
+//!
+//! ```toml
+//! [[auth.api_keys.access_windows]]
+//! days = ["mon", "tue", "wed", "thu", "fri"]
+//! start = "08:00"
+//! end = "18:00"
+//! timezone = "Europe/Berlin"
+//!
+//! [[auth.api_keys.grants]]
+//! tools = ["deploy_*"]
+//! expires_at = "2026-03-01T18:00:00Z"
+//! reason = "INC-1234"
+//! ```
+//!
+//! Both are evaluated on every authentication, including re-resolution of
+//! tokens issued for a key, so they take effect at the boundary rather than
+//! when a key or token is next refreshed.
+
+use chrono::{DateTime, Datelike, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+
+use crate::config::{AccessGrantConfig, AccessWindowConfig};
+
+/// Minutes in a day; `end = "24:00"`
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Access window setup error
+#[derive(Debug, thiserror::Error)]
+pub enum AccessScheduleError {
+    #[error("invalid day '{0}'")]
+    Day(String),
+
+    #[error("invalid time '{0}' (expected HH:MM)")]
+    Time(String),
+
+    #[error("invalid time zone '{0}'")]
+    TimeZone(String),
+
+    #[error("window starting at {0} must not end at the same time")]
+    Empty(String),
+}
+
+#[derive(Debug)]
+struct CompiledWindow {
+    /// Days the window opens on; empty means every day
+    days: Vec<Weekday>,
+    /// Minutes after local midnight
+    start: u32,
+    end: u32,
+    timezone: Tz,
+}
+
+/// Compiled access windows of one API key
+#[derive(Debug, Default)]
+pub struct AccessSchedule {
+    windows: Vec<CompiledWindow>,
+    deny_all: bool,
+}
+
+impl AccessSchedule {
+    /// Compile configured windows
+    pub fn new(configs: &[AccessWindowConfig]) -> Result<Self, AccessScheduleError> {
+        let windows = configs.iter().map(compile).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            windows,
+            deny_all: false,
+        })
+    }
+
+    /// Schedule that is never open
+    ///
+    /// Used when a key's windows fail to compile, so a typo can never widen
+    /// access.
+    pub fn deny_all() -> Self {
+        Self {
+            windows: Vec::new(),
+            deny_all: true,
+        }
+    }
+
+    /// Whether the key may be used at `now`
+    pub fn allows(&self, now: DateTime<Utc>) -> bool {
+        if self.deny_all {
+            return false;
+        }
+        self.windows.is_empty() || self.windows.iter().any(|window| window.contains(now))
+    }
+}
+
+impl CompiledWindow {
+    fn contains(&self, now: DateTime<Utc>) -> bool {
+        let local = now.with_timezone(&self.timezone);
+        let minute = local.hour() * 60 + local.minute();
+        let today = local.weekday();
+        let opens_on = |day: Weekday| self.days.is_empty() || self.days.contains(&day);
+
+        if self.start < self.end {
+            return opens_on(today) && (self.start..self.end).contains(&minute);
+        }
+        // Runs past midnight: the evening of an open day, or the morning after it
+        (opens_on(today) && minute >= self.start) || (opens_on(today.pred()) && minute < self.end)
+    }
+}
+
+fn compile(config: &AccessWindowConfig) -> Result<CompiledWindow, AccessScheduleError> {
+    let days = config
+        .days
+        .iter()
+        .map(|day| {
+            day.parse::<Weekday>()
+                .map_err(|_| AccessScheduleError::Day(day.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let start = parse_time(&config.start)?;
+    if start == MINUTES_PER_DAY {
+        return Err(AccessScheduleError::Time(config.start.clone()));
+    }
+    let end = parse_time(&config.end)?;
+    if start == end {
+        return Err(AccessScheduleError::Empty(config.start.clone()));
+    }
+    let timezone = config
+        .timezone
+        .parse::<Tz>()
+        .map_err(|_| AccessScheduleError::TimeZone(config.timezone.clone()))?;
+
+    Ok(CompiledWindow {
+        days,
+        start,
+        end,
+        timezone,
+    })
+}
+
+/// Minutes after midnight of an "HH:MM" time; "24:00" is the end of the day
+fn parse_time(value: &str) -> Result<u32, AccessScheduleError> {
+    let invalid = || AccessScheduleError::Time(value.to_string());
+    let (hours, minutes) = value.split_once(':').ok_or_else(invalid)?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return Err(invalid());
+    }
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    let total = hours * 60 + minutes;
+    if minutes >= 60 || total > MINUTES_PER_DAY {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// Allow-list entries of the grants that are still active at `now`
+///
+/// `select` picks the list of a grant, e.g. its `tools`.
+pub(crate) fn granted<'a>(
+    grants: &'a [AccessGrantConfig],
+    now: DateTime<Utc>,
+    select: impl Fn(&'a AccessGrantConfig) -> &'a [String],
+) -> impl Iterator<Item = &'a String> {
+    grants
+        .iter()
+        .filter(move |grant| grant.expires_at > now)
+        .flat_map(select)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn window(days: &[&str], start: &str, end: &str, timezone: &str) -> AccessWindowConfig {
+        AccessWindowConfig {
+            days: days.iter().map(|day| day.to_string()).collect(),
+            start: start.to_string(),
+            end: end.to_string(),
+            timezone: timezone.to_string(),
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // 2026-03-02 is a Monday
+        Utc.with_ymd_and_hms(2026, 3, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_office_hours_in_time_zone() {
+        let schedule = AccessSchedule::new(&[window(
+            &["mon", "tue", "wed", "thu", "friday"],
+            "08:00",
+            "18:00",
+            "Europe/Berlin",
+        )])
+        .unwrap();
+
+        // Berlin is UTC+1 in March before the DST switch
+        assert!(schedule.allows(at(2, 7, 0)));
+        assert!(!schedule.allows(at(2, 6, 59)));
+        assert!(!schedule.allows(at(2, 17, 0)));
+        // Saturday
+        assert!(!schedule.allows(at(7, 10, 0)));
+
+        assert!(AccessSchedule::default().allows(at(7, 10, 0)));
+        assert!(!AccessSchedule::deny_all().allows(at(2, 10, 0)));
+    }
+
+    #[test]
+    fn test_overnight_window() {
+        let schedule = AccessSchedule::new(&[window(&["fri"], "22:00", "06:00", "UTC")]).unwrap();
+
+        assert!(schedule.allows(at(6, 23, 30)));
+        // Saturday morning belongs to Friday's window
+        assert!(schedule.allows(at(7, 5, 59)));
+        assert!(!schedule.allows(at(7, 6, 0)));
+        // Friday morning belongs to Thursday, which is not listed
+        assert!(!schedule.allows(at(6, 5, 0)));
+    }
+
+    #[test]
+    fn test_invalid_windows() {
+        assert!(AccessSchedule::new(&[window(&["someday"], "08:00", "18:00", "UTC")]).is_err());
+        assert!(AccessSchedule::new(&[window(&[], "8:00", "18:00", "UTC")]).is_err());
+        assert!(AccessSchedule::new(&[window(&[], "08:00", "24:01", "UTC")]).is_err());
+        assert!(AccessSchedule::new(&[window(&[], "24:00", "06:00", "UTC")]).is_err());
+        assert!(AccessSchedule::new(&[window(&[], "08:00", "08:00", "UTC")]).is_err());
+        assert!(AccessSchedule::new(&[window(&[], "08:00", "18:00", "Mars/Olympus")]).is_err());
+        assert!(AccessSchedule::new(&[window(&[], "00:00", "24:00", "UTC")]).is_ok());
+    }
+
+    #[test]
+    fn test_expired_grants_are_ignored() {
+        let grant = |tool: &str, expires_at: DateTime<Utc>| AccessGrantConfig {
+            tools: vec![tool.to_string()],
+            resources: vec![],
+            prompts: vec![],
+            expires_at,
+            reason: None,
+        };
+        let grants = [
+            grant("deploy", at(3, 0, 0)),
+            grant("rollback", at(2, 12, 0)),
+        ];
+
+        let tools: Vec<_> = granted(&grants, at(2, 12, 0), |g| g.tools.as_slice()).collect();
+        assert_eq!(tools, ["deploy"]);
+        assert_eq!(
+            granted(&grants, at(3, 0, 0), |g| g.tools.as_slice()).count(),
+            0
+        );
+    }
+}
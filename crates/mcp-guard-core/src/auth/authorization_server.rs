@@ -0,0 +1,607 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Built-in OAuth authorization server
+//!
+//! For deployments without an identity provider. Users log in with a
+//! password, or callers exchange an API key, at `/oauth/token`; the gateway
+//! answers with a short-lived HS256 access token and, when a database is
+//! configured, a single-use refresh token.
+//!
+//! Access tokens only name the subject and how it logged in. The identity,
+//! with its tool, resource and prompt limits, is resolved from the current
+//! configuration whenever a token is presented, so removing a user or an API
+//! key ends their tokens at once.
+//!
+//! Passwords are stored as argon2id hashes (from `mcp-guard hash-password`).
+//! Repeated failed logins lock out the username and the client IP, for
+//! `lockout_secs` at first and twice as long after each further failure.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use dashmap::DashMap;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::auth::{allow_list, ApiKeyProvider, AuthError, AuthProvider, Identity, JwtProvider};
+use crate::config::{ApiKeyConfig, AuthorizationServerConfig, JwtConfig, JwtMode};
+use crate::db::RefreshTokenRepository;
+
+/// Claim recording how the subject logged in
+const LOGIN_CLAIM: &str = "login";
+
+/// Length of issued refresh tokens
+const REFRESH_TOKEN_LENGTH: usize = 48;
+
+/// Longest lockout after repeated failed logins, unless `lockout_secs` is longer
+const MAX_LOCKOUT: Duration = Duration::from_secs(3600);
+
+/// Usernames and client IPs with failed logins tracked before idle ones are dropped
+const MAX_TRACKED_LOGINS: usize = 10_000;
+
+/// Whether `hash` is an argon2id PHC string
+pub fn is_password_hash(hash: &str) -> bool {
+    argon2::PasswordHash::new(hash)
+        .is_ok_and(|hash| hash.algorithm == argon2::Algorithm::Argon2id.ident())
+}
+
+/// Check a password against an argon2 PHC string
+fn verify_password(password: &str, hash: &str) -> bool {
+    use argon2::PasswordVerifier;
+
+    argon2::PasswordHash::new(hash).is_ok_and(|hash| {
+        argon2::Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+/// Hash checked for unknown usernames, so they take as long as known ones
+fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| crate::cli::hash_password("unknown user"))
+}
+
+/// Consecutive failed logins of a username or client IP
+struct FailedLogins {
+    count: u32,
+    last: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// Locks out usernames and client IPs after repeated failed logins
+struct LoginThrottle {
+    max_failures: u32,
+    lockout: Duration,
+    failures: DashMap<String, FailedLogins>,
+}
+
+impl LoginThrottle {
+    fn new(config: &AuthorizationServerConfig) -> Self {
+        Self {
+            max_failures: config.max_failed_logins,
+            lockout: Duration::from_secs(config.lockout_secs),
+            failures: DashMap::new(),
+        }
+    }
+
+    /// Time left on `key`'s lockout
+    fn locked_for(&self, key: &str, now: Instant) -> Option<Duration> {
+        let until = self.failures.get(key)?.locked_until?;
+        until
+            .checked_duration_since(now)
+            .filter(|left| !left.is_zero())
+    }
+
+    fn record_failure(&self, key: String, now: Instant) {
+        if self.max_failures == 0 {
+            return;
+        }
+        let max_lockout = MAX_LOCKOUT.max(self.lockout);
+        if self.failures.len() >= MAX_TRACKED_LOGINS {
+            self.failures
+                .retain(|_, failed| now.duration_since(failed.last) < max_lockout);
+        }
+
+        let mut failed = self.failures.entry(key).or_insert(FailedLogins {
+            count: 0,
+            last: now,
+            locked_until: None,
+        });
+        // Failures older than the longest lockout are forgotten
+        if now.duration_since(failed.last) >= max_lockout {
+            failed.count = 0;
+        }
+        failed.count += 1;
+        failed.last = now;
+        if failed.count >= self.max_failures {
+            let doublings = (failed.count - self.max_failures).min(16);
+            let lockout = self.lockout.saturating_mul(1 << doublings).min(max_lockout);
+            failed.locked_until = Some(now + lockout);
+        }
+    }
+
+    fn clear(&self, key: &str) {
+        self.failures.remove(key);
+    }
+}
+
+fn user_key(username: &str) -> String {
+    format!("user:{}", username)
+}
+
+fn ip_key(client_ip: IpAddr) -> String {
+    format!("ip:{}", client_ip)
+}
+
+/// How a subject logged in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Login {
+    /// Username and password (`password` grant)
+    Password,
+    /// API key from `auth.api_keys` (`client_credentials` grant)
+    ApiKey,
+}
+
+impl Login {
+    fn as_str(self) -> &'static str {
+        match self {
+            Login::Password => "password",
+            Login::ApiKey => "api_key",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "password" => Some(Login::Password),
+            "api_key" => Some(Login::ApiKey),
+            _ => None,
+        }
+    }
+}
+
+/// Tokens issued by a successful login or refresh
+#[derive(Debug, Clone)]
+pub struct IssuedTokens {
+    pub access_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: u64,
+    /// Present when refresh tokens are stored in a database
+    pub refresh_token: Option<String>,
+}
+
+/// Issues and verifies the gateway's own access tokens
+pub struct AuthorizationServer {
+    config: AuthorizationServerConfig,
+    encoding_key: EncodingKey,
+    verifier: JwtProvider,
+    api_keys: ApiKeyProvider,
+    refresh_tokens: Option<RefreshTokenRepository>,
+    throttle: LoginThrottle,
+}
+
+impl AuthorizationServer {
+    /// Create the server; `api_keys` are the keys that may be exchanged for
+    /// tokens, `refresh_tokens` enables refresh tokens
+    pub fn new(
+        mut config: AuthorizationServerConfig,
+        api_keys: Vec<ApiKeyConfig>,
+        refresh_tokens: Option<RefreshTokenRepository>,
+    ) -> Result<Self, AuthError> {
+        // Issued tokens, verification and metadata all use the issuer without a trailing slash
+        config.issuer = config.issuer.trim_end_matches('/').to_string();
+        let verifier = JwtProvider::new(JwtConfig {
+            mode: JwtMode::Simple {
+                secret: config.signing_secret.clone(),
+            },
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            user_id_claim: "sub".to_string(),
+            scopes_claim: String::new(),
+            scope_tool_mapping: HashMap::new(),
+            leeway_secs: 0,
+            revocation: None,
+            identity: None,
+        })?;
+        let api_keys = if config.api_key_login {
+            api_keys
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            encoding_key: EncodingKey::from_secret(config.signing_secret.as_bytes()),
+            verifier,
+            api_keys: ApiKeyProvider::new(api_keys),
+            refresh_tokens,
+            throttle: LoginThrottle::new(&config),
+            config,
+        })
+    }
+
+    /// Issuer identifier of issued tokens
+    pub fn issuer(&self) -> &str {
+        &self.config.issuer
+    }
+
+    /// Whether logins also return a refresh token
+    pub fn supports_refresh(&self) -> bool {
+        self.refresh_tokens.is_some()
+    }
+
+    /// Whether API keys may be exchanged for tokens
+    pub fn supports_api_key_login(&self) -> bool {
+        self.config.api_key_login
+    }
+
+    /// How long logins for `username` or from `client_ip` are locked out
+    pub fn login_lockout(&self, username: &str, client_ip: IpAddr) -> Option<Duration> {
+        let now = Instant::now();
+        let user = self.throttle.locked_for(&user_key(username), now);
+        let ip = self.throttle.locked_for(&ip_key(client_ip), now);
+        user.max(ip)
+    }
+
+    /// Check a username and password
+    ///
+    /// Fails without checking the password while the username or client IP
+    /// is locked out. Argon2 is deliberately slow, so call this off the async
+    /// runtime.
+    ///
+    /// SECURITY: Unknown usernames are checked against a dummy hash so timing
+    /// does not reveal which usernames exist.
+    pub fn password_login(
+        &self,
+        username: &str,
+        password: &str,
+        client_ip: IpAddr,
+    ) -> Result<Identity, AuthError> {
+        if self.login_lockout(username, client_ip).is_some() {
+            return Err(AuthError::OAuth("Too many failed logins".into()));
+        }
+
+        let user = self
+            .config
+            .users
+            .iter()
+            .find(|user| user.username == username);
+        let hash = user.map_or(dummy_password_hash(), |user| user.password_hash.as_str());
+        if !verify_password(password, hash) || user.is_none() {
+            let now = Instant::now();
+            self.throttle.record_failure(user_key(username), now);
+            self.throttle.record_failure(ip_key(client_ip), now);
+            return Err(AuthError::OAuth("Invalid username or password".into()));
+        }
+        self.throttle.clear(&user_key(username));
+        self.resolve(username, Login::Password)
+    }
+
+    /// Check an API key from `auth.api_keys`
+    pub async fn api_key_login(&self, key: &str) -> Result<Identity, AuthError> {
+        if !self.config.api_key_login {
+            return Err(AuthError::InvalidApiKey);
+        }
+        self.api_keys.authenticate(key).await
+    }
+
+    /// Issue tokens for an identity returned by a login
+    pub async fn issue(
+        &self,
+        identity: &Identity,
+        login: Login,
+    ) -> Result<IssuedTokens, AuthError> {
+        let now = Utc::now();
+        let mut claims = HashMap::new();
+        claims.insert("sub", serde_json::json!(identity.id));
+        claims.insert("iss", serde_json::json!(self.config.issuer));
+        claims.insert("aud", serde_json::json!(self.config.audience));
+        claims.insert("iat", serde_json::json!(now.timestamp()));
+        claims.insert(
+            "exp",
+            serde_json::json!(now.timestamp() + self.config.access_token_ttl_secs as i64),
+        );
+        claims.insert("jti", serde_json::json!(uuid::Uuid::new_v4().to_string()));
+        claims.insert(LOGIN_CLAIM, serde_json::json!(login.as_str()));
+
+        let access_token =
+            jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+                .map_err(|e| AuthError::Internal(format!("Failed to sign token: {}", e)))?;
+
+        let refresh_token = match self.refresh_tokens {
+            Some(ref repository) => {
+                let token = random_token();
+                let expires_at =
+                    now + chrono::Duration::seconds(self.config.refresh_token_ttl_secs as i64);
+                repository
+                    .create(
+                        &ApiKeyProvider::hash_key(&token),
+                        &identity.id,
+                        login.as_str(),
+                        expires_at,
+                    )
+                    .await
+                    .map_err(|e| {
+                        AuthError::Internal(format!("Failed to store refresh token: {}", e))
+                    })?;
+                Some(token)
+            }
+            None => None,
+        };
+
+        Ok(IssuedTokens {
+            access_token,
+            expires_in: self.config.access_token_ttl_secs,
+            refresh_token,
+        })
+    }
+
+    /// Redeem a refresh token for new tokens
+    ///
+    /// The refresh token is used up; the subject must still be configured.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<IssuedTokens, AuthError> {
+        let repository = self
+            .refresh_tokens
+            .as_ref()
+            .ok_or_else(|| AuthError::OAuth("Refresh tokens are not enabled".into()))?;
+        let stored = repository
+            .consume(&ApiKeyProvider::hash_key(refresh_token))
+            .await
+            .map_err(|e| AuthError::Internal(format!("Failed to look up refresh token: {}", e)))?
+            .ok_or(AuthError::TokenExpired)?;
+        let login = Login::parse(&stored.login)
+            .ok_or_else(|| AuthError::OAuth("Unknown login in refresh token".into()))?;
+
+        let identity = self.resolve(&stored.subject, login)?;
+        self.issue(&identity, login).await
+    }
+
+    /// Identity of a subject as currently configured
+    fn resolve(&self, subject: &str, login: Login) -> Result<Identity, AuthError> {
+        match login {
+            Login::ApiKey => self.api_keys.identity_for_id(subject),
+            Login::Password => {
+                let user = self
+                    .config
+                    .users
+                    .iter()
+                    .find(|user| user.username == subject)
+                    .ok_or_else(|| AuthError::OAuth("Unknown user".into()))?;
+                Ok(Identity {
+                    id: user.username.clone(),
+                    name: Some(user.username.clone()),
+                    allowed_tools: allow_list(&user.allowed_tools),
+                    allowed_resources: allow_list(&user.allowed_resources),
+                    allowed_prompts: allow_list(&user.allowed_prompts),
+                    rate_limit: user.rate_limit,
+                    claims: HashMap::new(),
+                    tenant: None,
+                    argument_constraints: None,
+                })
+            }
+        }
+    }
+}
+
+fn random_token() -> String {
+    use base64::Engine;
+    use rand::rngs::OsRng;
+    use rand::RngCore;
+
+    let mut bytes = [0u8; REFRESH_TOKEN_LENGTH * 3 / 4];
+    OsRng.fill_bytes(&mut bytes);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[async_trait]
+impl AuthProvider for AuthorizationServer {
+    async fn authenticate(&self, token: &str) -> Result<Identity, AuthError> {
+        let verified = self.verifier.authenticate(token).await?;
+        let login = verified
+            .claims
+            .get(LOGIN_CLAIM)
+            .and_then(|value| value.as_str())
+            .and_then(Login::parse)
+            .ok_or_else(|| AuthError::InvalidJwt("Token was not issued by the gateway".into()))?;
+
+        let mut identity = self.resolve(&verified.id, login)?;
+        identity.claims = verified.claims;
+        Ok(identity)
+    }
+
+    fn name(&self) -> &str {
+        "authorization_server"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AuthorizationServerUser;
+
+    fn config() -> AuthorizationServerConfig {
+        AuthorizationServerConfig {
+            issuer: "https://mcp.example.com".to_string(),
+            signing_secret: "built-in-authorization-server-secret".to_string(),
+            audience: "mcp-guard".to_string(),
+            access_token_ttl_secs: 300,
+            refresh_token_ttl_secs: 3600,
+            users: vec![AuthorizationServerUser {
+                username: "alice".to_string(),
+                password_hash: crate::cli::hash_password("correct horse"),
+                allowed_tools: vec!["read_file".to_string()],
+                allowed_resources: vec![],
+                allowed_prompts: vec![],
+                rate_limit: Some(5),
+            }],
+            api_key_login: true,
+            max_failed_logins: 3,
+            lockout_secs: 60,
+        }
+    }
+
+    fn client() -> IpAddr {
+        "203.0.113.7".parse().unwrap()
+    }
+
+    fn api_key() -> ApiKeyConfig {
+        serde_json::from_value(serde_json::json!({
+            "id": "ci",
+            "key_hash": crate::cli::hash_api_key("mcp_ci_key"),
+            "allowed_tools": ["deploy"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_password_login() {
+        let server = AuthorizationServer::new(config(), vec![], None).unwrap();
+
+        let identity = server
+            .password_login("alice", "correct horse", client())
+            .unwrap();
+        assert_eq!(identity.id, "alice");
+        assert_eq!(identity.allowed_tools, Some(vec!["read_file".to_string()]));
+        assert_eq!(identity.rate_limit, Some(5));
+
+        assert!(server.password_login("alice", "wrong", client()).is_err());
+        assert!(server
+            .password_login("bob", "correct horse", client())
+            .is_err());
+    }
+
+    #[test]
+    fn test_password_hash_format() {
+        let hash = crate::cli::hash_password("correct horse");
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(is_password_hash(&hash));
+        // Salted: the same password hashes differently
+        assert_ne!(hash, crate::cli::hash_password("correct horse"));
+        // Unsalted SHA-256 from `hash-key` is not accepted
+        assert!(!is_password_hash(&crate::cli::hash_api_key(
+            "correct horse"
+        )));
+    }
+
+    #[test]
+    fn test_failed_logins_lock_out() {
+        let server = AuthorizationServer::new(config(), vec![], None).unwrap();
+        let other: IpAddr = "198.51.100.1".parse().unwrap();
+
+        for _ in 0..3 {
+            assert!(server.password_login("alice", "wrong", client()).is_err());
+        }
+        let lockout = server.login_lockout("alice", client()).unwrap();
+        assert!(lockout <= Duration::from_secs(60));
+        // Locked out even with the right password, from any address
+        assert!(server
+            .password_login("alice", "correct horse", other)
+            .is_err());
+
+        // The client IP is locked out for other usernames too
+        assert!(server.login_lockout("bob", client()).is_some());
+        assert!(server.login_lockout("bob", other).is_none());
+    }
+
+    #[test]
+    fn test_login_throttle_backs_off() {
+        let throttle = LoginThrottle::new(&config());
+        let now = Instant::now();
+        for _ in 0..2 {
+            throttle.record_failure("user:alice".to_string(), now);
+        }
+        assert_eq!(throttle.locked_for("user:alice", now), None);
+
+        throttle.record_failure("user:alice".to_string(), now);
+        assert_eq!(
+            throttle.locked_for("user:alice", now),
+            Some(Duration::from_secs(60))
+        );
+        throttle.record_failure("user:alice".to_string(), now);
+        assert_eq!(
+            throttle.locked_for("user:alice", now),
+            Some(Duration::from_secs(120))
+        );
+
+        throttle.clear("user:alice");
+        assert_eq!(throttle.locked_for("user:alice", now), None);
+    }
+
+    #[tokio::test]
+    async fn test_issued_token_authenticates() {
+        let server = AuthorizationServer::new(config(), vec![api_key()], None).unwrap();
+
+        let identity = server.api_key_login("mcp_ci_key").await.unwrap();
+        let tokens = server.issue(&identity, Login::ApiKey).await.unwrap();
+        assert_eq!(tokens.expires_in, 300);
+        assert!(tokens.refresh_token.is_none());
+
+        let authenticated = server.authenticate(&tokens.access_token).await.unwrap();
+        assert_eq!(authenticated.id, "ci");
+        assert_eq!(
+            authenticated.allowed_tools,
+            Some(vec!["deploy".to_string()])
+        );
+        assert_eq!(authenticated.claims["iss"], "https://mcp.example.com");
+    }
+
+    #[tokio::test]
+    async fn test_tokens_end_with_their_subject() {
+        let server = AuthorizationServer::new(config(), vec![api_key()], None).unwrap();
+        let identity = server
+            .password_login("alice", "correct horse", client())
+            .unwrap();
+        let tokens = server.issue(&identity, Login::Password).await.unwrap();
+
+        // Same signing secret, but alice has been removed from the config
+        let mut without_alice = config();
+        without_alice.users.clear();
+        let restarted = AuthorizationServer::new(without_alice, vec![api_key()], None).unwrap();
+        assert!(restarted.authenticate(&tokens.access_token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_api_key_login_can_be_disabled() {
+        let mut config = config();
+        config.api_key_login = false;
+        let server = AuthorizationServer::new(config, vec![api_key()], None).unwrap();
+        assert!(server.api_key_login("mcp_ci_key").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_tokens_from_other_issuers() {
+        let server = AuthorizationServer::new(config(), vec![], None).unwrap();
+
+        // Signed with the same secret but without the login claim
+        let claims = serde_json::json!({
+            "sub": "alice",
+            "iss": "https://mcp.example.com",
+            "aud": "mcp-guard",
+            "exp": Utc::now().timestamp() + 60,
+        });
+        let token = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(b"built-in-authorization-server-secret"),
+        )
+        .unwrap();
+        assert!(server.authenticate(&token).await.is_err());
+        assert!(server.refresh("anything").await.is_err());
+    }
+}
@@ -0,0 +1,437 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Claims-to-identity mapping
+//!
+//! Applies an `[auth.jwt.identity]` section to a validated token:
+//! - `id_template` builds `Identity.id` from claims (e.g. `"{tenant}:{sub}"`)
+//! - `rules` grant tools when a group/role claim carries a value
+//! - `claims` attaches templated values for downstream policy and authz
+//!
+//! Templates are parsed once when the provider is built, so a bad template
+//! fails startup (and `config validate`) rather than a request.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::auth::{AuthError, Identity};
+use crate::config::{ClaimToolRule, IdentityMappingConfig};
+
+/// A template over token claims
+///
+/// `{name}` is replaced by the claim's value; `{{` and `}}` are literal
+/// braces. Arrays of scalars render comma-separated.
+#[derive(Debug, Clone)]
+pub struct ClaimTemplate {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Claim(String),
+}
+
+impl ClaimTemplate {
+    /// Parse a template, rejecting unbalanced braces and empty placeholders
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => {
+                                return Err(format!("unclosed '{{' in '{}'", template));
+                            }
+                            Some(c) => name.push(c),
+                        }
+                    }
+                    let name = name.trim();
+                    if name.is_empty() {
+                        return Err(format!("empty placeholder in '{}'", template));
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Claim(name.to_string()));
+                }
+                '}' => return Err(format!("unmatched '}}' in '{}'", template)),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Render against claims, or `None` if a placeholder's claim is missing
+    /// or is not a string, number, boolean or array of those
+    pub fn render(&self, claims: &HashMap<String, Value>) -> Option<String> {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Claim(name) => {
+                    let values = scalar_values(lookup_claim(claims, name)?)?;
+                    out.push_str(&values.join(","));
+                }
+            }
+        }
+        Some(out)
+    }
+
+    /// Claim names the template refers to
+    fn claims(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Claim(name) => Some(name.as_str()),
+            Segment::Literal(_) => None,
+        })
+    }
+}
+
+/// Find a claim by exact name, then as a dotted path into nested objects
+///
+/// The exact match comes first because namespaced claims (Auth0's
+/// `https://example.com/roles`) often contain dots themselves.
+fn lookup_claim<'a>(claims: &'a HashMap<String, Value>, name: &str) -> Option<&'a Value> {
+    if let Some(value) = claims.get(name) {
+        return Some(value);
+    }
+    let mut parts = name.split('.');
+    let mut value = claims.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_object()?.get(part)?;
+    }
+    Some(value)
+}
+
+/// String forms of a scalar claim, or of each element of an array claim
+fn scalar_values(value: &Value) -> Option<Vec<String>> {
+    fn scalar(value: &Value) -> Option<String> {
+        match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        }
+    }
+
+    match value {
+        Value::Array(items) => items.iter().map(scalar).collect(),
+        other => scalar(other).map(|s| vec![s]),
+    }
+}
+
+/// Compiled `[auth.jwt.identity]` section
+#[derive(Debug, Clone)]
+pub struct IdentityMapper {
+    id_template: Option<ClaimTemplate>,
+    rules: Vec<ClaimToolRule>,
+    claims: Vec<(String, ClaimTemplate)>,
+}
+
+impl IdentityMapper {
+    /// Compile the templates in a mapping configuration
+    pub fn new(config: &IdentityMappingConfig) -> Result<Self, AuthError> {
+        let compile = |field: &str, template: &str| {
+            ClaimTemplate::parse(template)
+                .map_err(|e| AuthError::Internal(format!("identity.{}: {}", field, e)))
+        };
+
+        let id_template = config
+            .id_template
+            .as_deref()
+            .map(|template| compile("id_template", template))
+            .transpose()?;
+        let mut claims = config
+            .claims
+            .iter()
+            .map(|(name, template)| Ok((name.clone(), compile("claims", template)?)))
+            .collect::<Result<Vec<_>, AuthError>>()?;
+        // Stable order, so a template reading another attached claim behaves
+        // the same on every start
+        claims.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(Self {
+            id_template,
+            rules: config.rules.clone(),
+            claims,
+        })
+    }
+
+    /// Apply the mapping to an identity built from the token's claims
+    ///
+    /// `scopes_mapped` says whether `identity.allowed_tools` came from a
+    /// non-empty scope mapping; when it did not, only the rules decide the
+    /// tools.
+    pub fn apply(&self, identity: &mut Identity, scopes_mapped: bool) -> Result<(), AuthError> {
+        if let Some(ref template) = self.id_template {
+            identity.id = template.render(&identity.claims).ok_or_else(|| {
+                let missing: Vec<&str> = template
+                    .claims()
+                    .filter(|name| {
+                        lookup_claim(&identity.claims, name)
+                            .and_then(scalar_values)
+                            .is_none()
+                    })
+                    .collect();
+                AuthError::InvalidJwt(format!(
+                    "Cannot build identity ID: missing or non-scalar claim(s) {}",
+                    missing.join(", ")
+                ))
+            })?;
+        }
+
+        if !self.rules.is_empty() {
+            let granted = self.granted_tools(&identity.claims);
+            identity.allowed_tools = if scopes_mapped {
+                merge_tools(identity.allowed_tools.take(), granted)
+            } else {
+                granted
+            };
+        }
+
+        // Attached claims are best effort: a claim the token lacks is skipped
+        for (name, template) in &self.claims {
+            if let Some(value) = template.render(&identity.claims) {
+                identity.claims.insert(name.clone(), Value::String(value));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tools granted by matching rules (`None` means every tool)
+    fn granted_tools(&self, claims: &HashMap<String, Value>) -> Option<Vec<String>> {
+        let mut tools = Vec::new();
+        for rule in &self.rules {
+            let matches = lookup_claim(claims, &rule.claim)
+                .and_then(scalar_values)
+                .is_some_and(|values| values.contains(&rule.value));
+            if !matches {
+                continue;
+            }
+            if rule.tools.iter().any(|t| t == "*") {
+                return None;
+            }
+            tools.extend(rule.tools.iter().cloned());
+        }
+        tools.sort();
+        tools.dedup();
+        Some(tools)
+    }
+}
+
+/// Union of two allow lists, where `None` allows everything
+fn merge_tools(a: Option<Vec<String>>, b: Option<Vec<String>>) -> Option<Vec<String>> {
+    let (mut a, b) = (a?, b?);
+    a.extend(b);
+    a.sort();
+    a.dedup();
+    Some(a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn to_claims(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn identity(claims: HashMap<String, Value>, allowed_tools: Option<Vec<String>>) -> Identity {
+        Identity {
+            id: "user".to_string(),
+            name: None,
+            allowed_tools,
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims,
+            tenant: None,
+            argument_constraints: None,
+        }
+    }
+
+    fn rule(claim: &str, value: &str, tools: &[&str]) -> ClaimToolRule {
+        ClaimToolRule {
+            claim: claim.to_string(),
+            value: value.to_string(),
+            tools: tools.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_template_render() {
+        let template = ClaimTemplate::parse("{tenant}:{sub}").unwrap();
+        let claims = to_claims(json!({"tenant": "acme", "sub": "alice"}));
+        assert_eq!(template.render(&claims).unwrap(), "acme:alice");
+
+        let template = ClaimTemplate::parse("{{{org.id}}}/{groups}/{admin}/{n}").unwrap();
+        let claims = to_claims(json!({
+            "org": {"id": 42},
+            "groups": ["a", "b"],
+            "admin": true,
+            "n": 7
+        }));
+        assert_eq!(template.render(&claims).unwrap(), "{42}/a,b/true/7");
+    }
+
+    #[test]
+    fn test_template_exact_claim_name_wins_over_path() {
+        let template = ClaimTemplate::parse("{https://example.com/tenant}").unwrap();
+        let claims = to_claims(json!({"https://example.com/tenant": "acme"}));
+        assert_eq!(template.render(&claims).unwrap(), "acme");
+    }
+
+    #[test]
+    fn test_template_missing_or_unrenderable_claim() {
+        let template = ClaimTemplate::parse("{tenant}:{sub}").unwrap();
+        assert!(template
+            .render(&to_claims(json!({"sub": "alice"})))
+            .is_none());
+
+        let template = ClaimTemplate::parse("{org}").unwrap();
+        assert!(template
+            .render(&to_claims(json!({"org": {"id": 1}})))
+            .is_none());
+    }
+
+    #[test]
+    fn test_template_parse_errors() {
+        assert!(ClaimTemplate::parse("{tenant").is_err());
+        assert!(ClaimTemplate::parse("tenant}").is_err());
+        assert!(ClaimTemplate::parse("{}").is_err());
+        assert!(ClaimTemplate::parse("{a{b}}").is_err());
+        assert!(ClaimTemplate::parse("plain").is_ok());
+    }
+
+    #[test]
+    fn test_id_template_applied() {
+        let mapper = IdentityMapper::new(&IdentityMappingConfig {
+            id_template: Some("{tenant}:{sub}".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut id = identity(to_claims(json!({"tenant": "acme", "sub": "alice"})), None);
+        mapper.apply(&mut id, false).unwrap();
+        assert_eq!(id.id, "acme:alice");
+
+        let mut id = identity(to_claims(json!({"sub": "alice"})), None);
+        let err = mapper.apply(&mut id, false).unwrap_err();
+        assert!(err.to_string().contains("tenant"));
+    }
+
+    #[test]
+    fn test_rules_grant_tools() {
+        let mapper = IdentityMapper::new(&IdentityMappingConfig {
+            rules: vec![
+                rule("groups", "engineering", &["write_file", "read_file"]),
+                rule("roles", "viewer", &["read_file"]),
+                rule("roles", "admin", &["*"]),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut id = identity(to_claims(json!({"groups": ["engineering"]})), None);
+        mapper.apply(&mut id, false).unwrap();
+        assert_eq!(
+            id.allowed_tools,
+            Some(vec!["read_file".to_string(), "write_file".to_string()])
+        );
+
+        // Scalar claims match too
+        let mut id = identity(to_claims(json!({"roles": "admin"})), None);
+        mapper.apply(&mut id, false).unwrap();
+        assert_eq!(id.allowed_tools, None);
+
+        // No matching rule and no scope mapping: no tools
+        let mut id = identity(to_claims(json!({"groups": ["sales"]})), None);
+        mapper.apply(&mut id, false).unwrap();
+        assert_eq!(id.allowed_tools, Some(vec![]));
+    }
+
+    #[test]
+    fn test_rules_merge_with_scope_mapping() {
+        let mapper = IdentityMapper::new(&IdentityMappingConfig {
+            rules: vec![rule("groups", "engineering", &["write_file"])],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut id = identity(
+            to_claims(json!({"groups": ["engineering"]})),
+            Some(vec!["read_file".to_string()]),
+        );
+        mapper.apply(&mut id, true).unwrap();
+        assert_eq!(
+            id.allowed_tools,
+            Some(vec!["read_file".to_string(), "write_file".to_string()])
+        );
+
+        // A wildcard scope keeps every tool
+        let mut id = identity(to_claims(json!({"groups": ["engineering"]})), None);
+        mapper.apply(&mut id, true).unwrap();
+        assert_eq!(id.allowed_tools, None);
+    }
+
+    #[test]
+    fn test_attached_claims() {
+        let mapper = IdentityMapper::new(&IdentityMappingConfig {
+            claims: HashMap::from([
+                ("department".to_string(), "{org.department}".to_string()),
+                ("region".to_string(), "{missing}".to_string()),
+            ]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut id = identity(to_claims(json!({"org": {"department": "finance"}})), None);
+        mapper.apply(&mut id, false).unwrap();
+        assert_eq!(id.claims["department"], json!("finance"));
+        assert!(!id.claims.contains_key("region"));
+    }
+
+    #[test]
+    fn test_invalid_template_rejected() {
+        let result = IdentityMapper::new(&IdentityMappingConfig {
+            id_template: Some("{tenant".to_string()),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+    }
+}
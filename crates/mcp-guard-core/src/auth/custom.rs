@@ -0,0 +1,429 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Custom authentication providers compiled in by downstream crates
+//!
+//! Built-in providers are configured through their own sections. Anything else
+//! (an internal SSO, a legacy token service) implements [`AuthProvider`] plus an
+//! [`AuthProviderFactory`] and is registered under a provider type:
+//!
+//! ```ignore
+//! mcp_guard_core::auth::register_auth_provider(InternalSsoFactory);
+//! ```
+//!
+//! Each `[[auth.custom]]` entry names a registered type and passes its
+//! `settings` table to the factory when the server starts:
+//!
+//! ```toml
+//! [[auth.custom]]
+//! name = "corp-sso"
+//! provider = "internal-sso"
+//! settings = { endpoint = "https://sso.corp.example/verify" }
+//! ```
+//!
+//! [`CachingAuthProvider`] wraps a provider whose checks are expensive, and
+//! [`StaticTokenProvider`] stands in for a real provider in tests.
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use super::{AuthError, AuthProvider, Identity};
+use crate::config::CustomAuthConfig;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Default cap on cached identities in [`CachingAuthProvider`].
+/// Bounds memory when many distinct tokens are presented.
+const DEFAULT_CACHE_MAX_ENTRIES: usize = 10_000;
+
+// ============================================================================
+// Factory and Registry
+// ============================================================================
+
+/// Builds an [`AuthProvider`] from an `[[auth.custom]]` entry
+pub trait AuthProviderFactory: Send + Sync {
+    /// Provider type referenced by `auth.custom.provider`
+    fn provider_type(&self) -> &str;
+
+    /// Create a provider from the entry's `settings` value
+    ///
+    /// Invalid settings should be reported as [`AuthError::Internal`] with a
+    /// message naming the offending field; startup fails with it.
+    fn build(&self, config: &CustomAuthConfig) -> Result<Arc<dyn AuthProvider>, AuthError>;
+}
+
+/// Factories for custom providers, keyed by provider type
+#[derive(Clone, Default)]
+pub struct AuthProviderRegistry {
+    factories: HashMap<String, Arc<dyn AuthProviderFactory>>,
+}
+
+impl std::fmt::Debug for AuthProviderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut types: Vec<_> = self.factories.keys().collect();
+        types.sort();
+        f.debug_struct("AuthProviderRegistry")
+            .field("provider_types", &types)
+            .finish()
+    }
+}
+
+impl AuthProviderRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a factory, replacing any previous one for the same type
+    pub fn register(&mut self, factory: impl AuthProviderFactory + 'static) {
+        self.factories
+            .insert(factory.provider_type().to_string(), Arc::new(factory));
+    }
+
+    /// Whether a factory is registered for a provider type
+    pub fn contains(&self, provider_type: &str) -> bool {
+        self.factories.contains_key(provider_type)
+    }
+
+    /// Registered provider types, sorted
+    pub fn provider_types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self.factories.keys().cloned().collect();
+        types.sort();
+        types
+    }
+
+    /// Build the provider for one `[[auth.custom]]` entry
+    pub fn build(&self, config: &CustomAuthConfig) -> Result<Arc<dyn AuthProvider>, AuthError> {
+        let factory = self.factories.get(&config.provider).ok_or_else(|| {
+            AuthError::Internal(format!(
+                "auth.custom '{}': unknown provider type '{}' (registered: {})",
+                config.name,
+                config.provider,
+                self.provider_types().join(", ")
+            ))
+        })?;
+        factory.build(config)
+    }
+}
+
+fn global_registry() -> &'static RwLock<AuthProviderRegistry> {
+    static REGISTRY: OnceLock<RwLock<AuthProviderRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Register a custom provider factory for the server to use at startup
+///
+/// Call before the server is bootstrapped; later registrations are not seen by
+/// a running server.
+pub fn register_auth_provider(factory: impl AuthProviderFactory + 'static) {
+    global_registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .register(factory);
+}
+
+/// Snapshot of the process-wide registry
+pub fn registered_auth_providers() -> AuthProviderRegistry {
+    global_registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+// ============================================================================
+// Identity Caching
+// ============================================================================
+
+struct CachedIdentity {
+    identity: Identity,
+    cached_at: Instant,
+}
+
+/// Caches successful authentications of a wrapped provider
+///
+/// Tokens are keyed by SHA-256 hash, never stored raw. Failures are not cached
+/// so a fixed credential works on the next request.
+pub struct CachingAuthProvider<P> {
+    inner: P,
+    ttl: Duration,
+    max_entries: usize,
+    cache: DashMap<String, CachedIdentity>,
+}
+
+impl<P: AuthProvider> CachingAuthProvider<P> {
+    /// Wrap a provider, caching identities for `ttl`
+    pub fn new(inner: P, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            max_entries: DEFAULT_CACHE_MAX_ENTRIES,
+            cache: DashMap::new(),
+        }
+    }
+
+    /// Set the maximum number of cached identities
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// The wrapped provider
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Number of cached entries, including expired ones not yet swept
+    pub fn cached_len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Drop cached entries for an identity; returns how many were removed
+    pub fn invalidate_identity(&self, identity_id: &str) -> usize {
+        let before = self.cache.len();
+        self.cache
+            .retain(|_, cached| cached.identity.id != identity_id);
+        before - self.cache.len()
+    }
+
+    /// Drop every cached entry
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+
+    fn hash_token(token: &str) -> String {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(token.as_bytes());
+        base64::Engine::encode(
+            &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+            hasher.finalize(),
+        )
+    }
+}
+
+#[async_trait]
+impl<P: AuthProvider> AuthProvider for CachingAuthProvider<P> {
+    async fn authenticate(&self, token: &str) -> Result<Identity, AuthError> {
+        let key = Self::hash_token(token);
+        if let Some(cached) = self.cache.get(&key) {
+            if cached.cached_at.elapsed() < self.ttl {
+                return Ok(cached.identity.clone());
+            }
+        }
+
+        let identity = self.inner.authenticate(token).await?;
+
+        if self.cache.len() >= self.max_entries {
+            self.cache
+                .retain(|_, cached| cached.cached_at.elapsed() < self.ttl);
+        }
+        if self.cache.len() < self.max_entries {
+            self.cache.insert(
+                key,
+                CachedIdentity {
+                    identity: identity.clone(),
+                    cached_at: Instant::now(),
+                },
+            );
+        }
+        Ok(identity)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+// ============================================================================
+// Test Utilities
+// ============================================================================
+
+/// Provider accepting a fixed set of tokens, for tests of custom integrations
+///
+/// Unknown tokens fail with [`AuthError::InvalidApiKey`]. Not meant for
+/// production: tokens are compared as plain strings.
+#[derive(Clone, Default)]
+pub struct StaticTokenProvider {
+    name: String,
+    tokens: HashMap<String, Identity>,
+}
+
+impl StaticTokenProvider {
+    /// Create a provider with no tokens
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tokens: HashMap::new(),
+        }
+    }
+
+    /// Accept `token` as `identity`
+    pub fn with_token(mut self, token: impl Into<String>, identity: Identity) -> Self {
+        self.tokens.insert(token.into(), identity);
+        self
+    }
+
+    /// Accept `token` as an unrestricted identity with the given ID
+    pub fn with_identity(self, token: impl Into<String>, identity_id: impl Into<String>) -> Self {
+        let identity = Identity {
+            id: identity_id.into(),
+            name: None,
+            allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims: HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
+        };
+        self.with_token(token, identity)
+    }
+}
+
+#[async_trait]
+impl AuthProvider for StaticTokenProvider {
+    async fn authenticate(&self, token: &str) -> Result<Identity, AuthError> {
+        self.tokens
+            .get(token)
+            .cloned()
+            .ok_or(AuthError::InvalidApiKey)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts calls so tests can observe caching
+    struct CountingProvider {
+        inner: StaticTokenProvider,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl AuthProvider for CountingProvider {
+        async fn authenticate(&self, token: &str) -> Result<Identity, AuthError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.authenticate(token).await
+        }
+
+        fn name(&self) -> &str {
+            "counting"
+        }
+    }
+
+    struct StaticFactory;
+
+    impl AuthProviderFactory for StaticFactory {
+        fn provider_type(&self) -> &str {
+            "static"
+        }
+
+        fn build(&self, config: &CustomAuthConfig) -> Result<Arc<dyn AuthProvider>, AuthError> {
+            let token = config
+                .settings
+                .get("token")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| AuthError::Internal("settings.token is required".into()))?;
+            Ok(Arc::new(
+                StaticTokenProvider::new(&config.name).with_identity(token, "svc"),
+            ))
+        }
+    }
+
+    fn custom_config(provider: &str, settings: serde_json::Value) -> CustomAuthConfig {
+        CustomAuthConfig {
+            name: "test".to_string(),
+            provider: provider.to_string(),
+            settings,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_builds_registered_providers() {
+        let mut registry = AuthProviderRegistry::new();
+        registry.register(StaticFactory);
+        assert!(registry.contains("static"));
+
+        let provider = registry
+            .build(&custom_config(
+                "static",
+                serde_json::json!({"token": "t0k"}),
+            ))
+            .unwrap();
+        assert_eq!(provider.name(), "test");
+        assert_eq!(provider.authenticate("t0k").await.unwrap().id, "svc");
+
+        // Factory rejects bad settings; unknown types name what is registered
+        assert!(registry
+            .build(&custom_config("static", serde_json::json!({})))
+            .is_err());
+        let err = registry
+            .build(&custom_config("ldap", serde_json::Value::Null))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("registered: static"));
+    }
+
+    #[test]
+    fn test_global_registration() {
+        register_auth_provider(StaticFactory);
+        assert!(registered_auth_providers().contains("static"));
+    }
+
+    #[tokio::test]
+    async fn test_caching_provider() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = CachingAuthProvider::new(
+            CountingProvider {
+                inner: StaticTokenProvider::new("static")
+                    .with_identity("a", "alice")
+                    .with_identity("b", "bob"),
+                calls: calls.clone(),
+            },
+            Duration::from_secs(60),
+        )
+        .with_max_entries(1);
+
+        assert_eq!(provider.authenticate("a").await.unwrap().id, "alice");
+        assert_eq!(provider.authenticate("a").await.unwrap().id, "alice");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Failures are not cached
+        assert!(provider.authenticate("nope").await.is_err());
+        assert!(provider.authenticate("nope").await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // At capacity, new identities are served but not cached
+        assert_eq!(provider.authenticate("b").await.unwrap().id, "bob");
+        assert_eq!(provider.cached_len(), 1);
+
+        assert_eq!(provider.invalidate_identity("alice"), 1);
+        provider.authenticate("a").await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+}
@@ -0,0 +1,396 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! HMAC-signed request authentication for mcp-guard
+//!
+//! Machine-to-machine callers sign every request with a shared secret instead
+//! of sending a static bearer token, in the style of AWS SigV4:
+//!
+//! ```text
+//! Authorization: MCPG-HMAC-SHA256 KeyId=<key id>, Signature=<hex signature>
+//! X-MCPG-Timestamp: <unix seconds>
+//! X-MCPG-Nonce: <unique random string>
+//! ```
+//!
+//! The signature is the hex-encoded HMAC-SHA256 of the string to sign:
+//!
+//! ```text
+//! MCPG-HMAC-SHA256\n<timestamp>\n<nonce>\n<METHOD>\n<path and query>\n<hex SHA-256 of body>
+//! ```
+//!
+//! Requests outside `max_clock_skew_secs` are rejected, and each nonce is
+//! accepted only once per key within that window to prevent replay.
+
+use ::hmac::{Hmac, Mac};
+use axum::http::HeaderMap;
+use dashmap::DashMap;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use subtle::ConstantTimeEq;
+
+use crate::auth::{allow_list, AuthError, Identity};
+use crate::config::{HmacConfig, HmacKeyConfig};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Authorization scheme for signed requests
+pub const HMAC_AUTH_SCHEME: &str = "MCPG-HMAC-SHA256";
+
+/// Header carrying the signing timestamp (unix seconds)
+pub const HEADER_HMAC_TIMESTAMP: &str = "X-MCPG-Timestamp";
+
+/// Header carrying the per-request nonce
+pub const HEADER_HMAC_NONCE: &str = "X-MCPG-Nonce";
+
+/// Nonce length bounds.
+/// SECURITY: Short nonces are guessable by other holders of the key; long ones
+/// would let a signer bloat the replay cache.
+const MIN_NONCE_LEN: usize = 16;
+const MAX_NONCE_LEN: usize = 128;
+
+// ============================================================================
+// Signing
+// ============================================================================
+
+/// Build the canonical string that is signed
+pub fn string_to_sign(
+    timestamp: &str,
+    nonce: &str,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        HMAC_AUTH_SCHEME,
+        timestamp,
+        nonce,
+        method.to_ascii_uppercase(),
+        path_and_query,
+        to_hex(&Sha256::digest(body))
+    )
+}
+
+/// Compute the hex-encoded signature for a request
+///
+/// Exposed so clients and tests written in Rust can sign requests.
+pub fn sign_request(
+    secret: &str,
+    timestamp: &str,
+    nonce: &str,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(string_to_sign(timestamp, nonce, method, path_and_query, body).as_bytes());
+    to_hex(&mac.finalize().into_bytes())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parse `KeyId=<id>, Signature=<hex>` from the Authorization header value
+fn parse_authorization(value: &str) -> Option<(&str, &str)> {
+    let params = value.strip_prefix(HMAC_AUTH_SCHEME)?.strip_prefix(' ')?;
+    let mut key_id = None;
+    let mut signature = None;
+    for param in params.split(',') {
+        match param.trim().split_once('=') {
+            Some(("KeyId", v)) => key_id = Some(v),
+            Some(("Signature", v)) => signature = Some(v),
+            _ => return None,
+        }
+    }
+    Some((key_id?, signature?))
+}
+
+// ============================================================================
+// HMAC Auth Provider
+// ============================================================================
+
+/// Verifies HMAC-signed requests
+///
+/// Unlike bearer-token providers this needs the method, path and body, so the
+/// auth middleware calls [`HmacAuthProvider::verify`] directly rather than
+/// going through [`super::AuthProvider`].
+pub struct HmacAuthProvider {
+    keys: HashMap<String, HmacKeyConfig>,
+    max_clock_skew_secs: u64,
+    nonce_cache_size: usize,
+    /// "key_id:nonce" -> unix time after which the entry can be dropped
+    nonces: DashMap<String, u64>,
+}
+
+impl HmacAuthProvider {
+    /// Create a provider from configuration
+    pub fn new(config: HmacConfig) -> Self {
+        Self {
+            keys: config
+                .keys
+                .into_iter()
+                .map(|key| (key.id.clone(), key))
+                .collect(),
+            max_clock_skew_secs: config.max_clock_skew_secs,
+            nonce_cache_size: config.nonce_cache_size,
+            nonces: DashMap::new(),
+        }
+    }
+
+    /// Whether the request carries an HMAC Authorization header
+    pub fn is_signed_request(headers: &HeaderMap) -> bool {
+        headers
+            .get("Authorization")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with(HMAC_AUTH_SCHEME))
+    }
+
+    /// Verify a signed request and return the caller's identity
+    pub fn verify(
+        &self,
+        headers: &HeaderMap,
+        method: &str,
+        path_and_query: &str,
+        body: &[u8],
+    ) -> Result<Identity, AuthError> {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+        let (key_id, signature) = header("Authorization")
+            .and_then(parse_authorization)
+            .ok_or_else(|| AuthError::InvalidSignature("Malformed Authorization header".into()))?;
+        let timestamp = header(HEADER_HMAC_TIMESTAMP).ok_or_else(|| {
+            AuthError::InvalidSignature(format!("Missing {} header", HEADER_HMAC_TIMESTAMP))
+        })?;
+        let nonce = header(HEADER_HMAC_NONCE).ok_or_else(|| {
+            AuthError::InvalidSignature(format!("Missing {} header", HEADER_HMAC_NONCE))
+        })?;
+
+        let key = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| AuthError::InvalidSignature(format!("Unknown key ID: {}", key_id)))?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| AuthError::Internal(format!("System clock error: {}", e)))?
+            .as_secs();
+        let signed_at: u64 = timestamp
+            .parse()
+            .map_err(|_| AuthError::InvalidSignature("Invalid timestamp".into()))?;
+        if now.abs_diff(signed_at) > self.max_clock_skew_secs {
+            return Err(AuthError::InvalidSignature(
+                "Timestamp outside allowed clock skew".into(),
+            ));
+        }
+
+        if !(MIN_NONCE_LEN..=MAX_NONCE_LEN).contains(&nonce.len()) {
+            return Err(AuthError::InvalidSignature(format!(
+                "Nonce must be {}-{} characters",
+                MIN_NONCE_LEN, MAX_NONCE_LEN
+            )));
+        }
+
+        // SECURITY: Constant-time comparison prevents timing attacks on the signature
+        let expected = sign_request(&key.secret, timestamp, nonce, method, path_and_query, body);
+        if !bool::from(expected.as_bytes().ct_eq(signature.as_bytes())) {
+            return Err(AuthError::InvalidSignature("Signature mismatch".into()));
+        }
+
+        // Record the nonce only after the signature checks out, so unsigned
+        // requests cannot fill the cache
+        self.record_nonce(key_id, nonce, now)?;
+
+        Ok(Identity {
+            id: key.id.clone(),
+            name: None,
+            allowed_tools: allow_list(&key.allowed_tools),
+            allowed_resources: allow_list(&key.allowed_resources),
+            allowed_prompts: allow_list(&key.allowed_prompts),
+            rate_limit: key.rate_limit,
+            claims: HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
+        })
+    }
+
+    /// Remember a nonce, rejecting it if already seen
+    fn record_nonce(&self, key_id: &str, nonce: &str, now: u64) -> Result<(), AuthError> {
+        if self.nonces.len() >= self.nonce_cache_size {
+            self.cleanup_expired_nonces(now);
+            // SECURITY: Fail closed rather than forget nonces that could still be replayed
+            if self.nonces.len() >= self.nonce_cache_size {
+                tracing::warn!(
+                    capacity = self.nonce_cache_size,
+                    "HMAC nonce cache full, rejecting signed request"
+                );
+                return Err(AuthError::Internal("Nonce cache full".into()));
+            }
+        }
+
+        // A timestamp can be up to max_clock_skew_secs in the future, so keep the
+        // nonce until no timestamp it could be paired with is still accepted
+        let expires_at = now + 2 * self.max_clock_skew_secs;
+        match self.nonces.entry(format!("{}:{}", key_id, nonce)) {
+            dashmap::mapref::entry::Entry::Occupied(_) => {
+                tracing::warn!(key_id = %key_id, "Rejected replayed HMAC-signed request");
+                Err(AuthError::InvalidSignature("Nonce already used".into()))
+            }
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                entry.insert(expires_at);
+                Ok(())
+            }
+        }
+    }
+
+    /// Drop nonces whose replay window has passed
+    fn cleanup_expired_nonces(&self, now: u64) {
+        self.nonces.retain(|_, expires_at| *expires_at > now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "billing-service-secret-32-characters!!";
+    const NONCE: &str = "0123456789abcdef";
+    const BODY: &[u8] = br#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#;
+
+    fn create_provider(nonce_cache_size: usize) -> HmacAuthProvider {
+        HmacAuthProvider::new(HmacConfig {
+            keys: vec![HmacKeyConfig {
+                id: "billing".to_string(),
+                secret: SECRET.to_string(),
+                allowed_tools: vec!["read_file".to_string()],
+                allowed_resources: vec![],
+                allowed_prompts: vec![],
+                rate_limit: Some(50),
+            }],
+            max_clock_skew_secs: 300,
+            nonce_cache_size,
+        })
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn signed_headers(timestamp: u64, nonce: &str, body: &[u8]) -> HeaderMap {
+        let timestamp = timestamp.to_string();
+        let signature = sign_request(SECRET, &timestamp, nonce, "POST", "/mcp", body);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Authorization",
+            format!(
+                "{} KeyId=billing, Signature={}",
+                HMAC_AUTH_SCHEME, signature
+            )
+            .parse()
+            .unwrap(),
+        );
+        headers.insert(HEADER_HMAC_TIMESTAMP, timestamp.parse().unwrap());
+        headers.insert(HEADER_HMAC_NONCE, nonce.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_valid_signature() {
+        let provider = create_provider(100);
+        let headers = signed_headers(now(), NONCE, BODY);
+        assert!(HmacAuthProvider::is_signed_request(&headers));
+
+        let identity = provider.verify(&headers, "POST", "/mcp", BODY).unwrap();
+        assert_eq!(identity.id, "billing");
+        assert_eq!(identity.allowed_tools, Some(vec!["read_file".to_string()]));
+        assert_eq!(identity.rate_limit, Some(50));
+    }
+
+    #[test]
+    fn test_tampered_body_rejected() {
+        let provider = create_provider(100);
+        let headers = signed_headers(now(), NONCE, BODY);
+
+        assert!(matches!(
+            provider.verify(&headers, "POST", "/mcp", b"{}"),
+            Err(AuthError::InvalidSignature(_))
+        ));
+        assert!(matches!(
+            provider.verify(&headers, "POST", "/mcp/other", BODY),
+            Err(AuthError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_replayed_nonce_rejected() {
+        let provider = create_provider(100);
+        let headers = signed_headers(now(), NONCE, BODY);
+
+        assert!(provider.verify(&headers, "POST", "/mcp", BODY).is_ok());
+        assert!(matches!(
+            provider.verify(&headers, "POST", "/mcp", BODY),
+            Err(AuthError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_stale_timestamp_rejected() {
+        let provider = create_provider(100);
+        let headers = signed_headers(now() - 600, NONCE, BODY);
+
+        assert!(matches!(
+            provider.verify(&headers, "POST", "/mcp", BODY),
+            Err(AuthError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_full_nonce_cache_fails_closed() {
+        let provider = create_provider(1);
+        let first = signed_headers(now(), NONCE, BODY);
+        let second = signed_headers(now(), "fedcba9876543210", BODY);
+
+        assert!(provider.verify(&first, "POST", "/mcp", BODY).is_ok());
+        assert!(matches!(
+            provider.verify(&second, "POST", "/mcp", BODY),
+            Err(AuthError::Internal(_))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_authorization_rejected() {
+        assert_eq!(
+            parse_authorization("MCPG-HMAC-SHA256 KeyId=a, Signature=b"),
+            Some(("a", "b"))
+        );
+        assert_eq!(parse_authorization("MCPG-HMAC-SHA256 KeyId=a"), None);
+        assert_eq!(parse_authorization("Bearer abc"), None);
+        assert_eq!(
+            parse_authorization("MCPG-HMAC-SHA256 KeyId=a, Signature=b, Extra=c"),
+            None
+        );
+    }
+}
@@ -20,6 +20,9 @@
 //! Supports two modes:
 //! - Simple: HS256 with local secret
 //! - JWKS: RS256/ES256 with remote JWKS endpoint
+//!
+//! Either mode can consult a [`RevocationStore`] to reject tokens revoked
+//! before their expiry.
 
 use async_trait::async_trait;
 
@@ -40,11 +43,11 @@ const JWKS_REFRESH_FRACTION_DENOMINATOR: u64 = 4;
 /// Maximum JWT token size in bytes.
 /// SECURITY: Typical JWTs are <2KB; 16KB prevents memory exhaustion from tokens
 /// with maliciously large claim values (e.g., 100MB base64 blobs).
-const MAX_JWT_CLAIMS_SIZE: usize = 16 * 1024; // 16KB
+pub(crate) const MAX_JWT_CLAIMS_SIZE: usize = 16 * 1024; // 16KB
 
 use jsonwebtoken::{
     decode, decode_header, errors::ErrorKind as JwtErrorKind, Algorithm, DecodingKey, EncodingKey,
-    Validation,
+    Header, Validation,
 };
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -52,8 +55,11 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 
-use crate::auth::{map_scopes_to_tools, AuthError, AuthProvider, Identity};
+use crate::auth::{
+    map_scopes_to_tools, AuthError, AuthProvider, Identity, IdentityMapper, RevocationStore,
+};
 use crate::config::{JwtConfig, JwtMode};
+use crate::db::RevokedTokenRepository;
 
 /// JWKS key entry with decoded key and algorithm
 struct JwksKey {
@@ -66,24 +72,65 @@ struct JwksCache {
     keys: HashMap<String, JwksKey>,
     fetched_at: Instant,
     cache_duration: Duration,
+    /// Minimum time between refetches triggered by an unknown `kid`
+    min_refresh_interval: Duration,
+    /// When the last unknown-`kid` refetch was started
+    last_miss_refresh: Option<Instant>,
 }
 
 impl JwksCache {
-    fn new(cache_duration: Duration) -> Self {
+    fn new(cache_duration: Duration, min_refresh_interval: Duration) -> Self {
         Self {
             keys: HashMap::new(),
             fetched_at: Instant::now() - cache_duration - Duration::from_secs(1), // Start expired
             cache_duration,
+            min_refresh_interval,
+            last_miss_refresh: None,
         }
     }
 
     fn is_expired(&self) -> bool {
         self.fetched_at.elapsed() > self.cache_duration
     }
+
+    /// Claim the next unknown-`kid` refetch slot, if the rate limit allows one.
+    ///
+    /// SECURITY: Anyone can send a token with a made-up `kid`, so without this
+    /// bound every such request would turn into a call to the IdP.
+    fn try_claim_miss_refresh(&mut self) -> bool {
+        let allowed = self
+            .last_miss_refresh
+            .map_or(true, |at| at.elapsed() >= self.min_refresh_interval);
+        if allowed {
+            self.last_miss_refresh = Some(Instant::now());
+        }
+        allowed
+    }
+}
+
+/// JWKS cache freshness, as reported by `/health?deep=true`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JwksStatus {
+    /// Keys currently cached
+    pub keys: usize,
+    /// Seconds since the last successful fetch (`None` if never fetched)
+    pub age_secs: Option<u64>,
+    /// Configured cache lifetime
+    pub cache_duration_secs: u64,
+    /// Whether the keys have outlived the cache lifetime
+    pub stale: bool,
 }
 
 /// JWT authentication provider
+///
+/// Trusts one or more issuers. A token is checked against the issuers whose
+/// `issuer` matches its `iss` claim, in configuration order.
 pub struct JwtProvider {
+    issuers: Vec<Arc<JwtIssuer>>,
+}
+
+/// Keys, validation rules and claim mappings for one trusted issuer
+struct JwtIssuer {
     config: JwtConfig,
     /// For simple mode: pre-computed decoding key
     simple_key: Option<DecodingKey>,
@@ -93,11 +140,151 @@ pub struct JwtProvider {
     jwks_cache: Option<Arc<RwLock<JwksCache>>>,
     /// HTTP client for JWKS fetching
     http_client: Option<reqwest::Client>,
+    /// Revoked token IDs and subjects (if configured)
+    revocation: Option<Arc<RevocationStore>>,
+    /// Claims-to-identity mapping (if configured)
+    identity_mapper: Option<IdentityMapper>,
 }
 
 impl JwtProvider {
     /// Create a new JWT provider from configuration
     pub fn new(config: JwtConfig) -> Result<Self, AuthError> {
+        Self::with_database(config, None)
+    }
+
+    /// Create a new JWT provider with a database for the `database` revocation source
+    pub fn with_database(
+        config: JwtConfig,
+        revoked_tokens: Option<RevokedTokenRepository>,
+    ) -> Result<Self, AuthError> {
+        Self::with_issuers(vec![config], revoked_tokens)
+    }
+
+    /// Create a JWT provider trusting several issuers (`[[auth.jwt]]`)
+    pub fn with_issuers(
+        configs: Vec<JwtConfig>,
+        revoked_tokens: Option<RevokedTokenRepository>,
+    ) -> Result<Self, AuthError> {
+        if configs.is_empty() {
+            return Err(AuthError::Internal("No JWT issuers configured".into()));
+        }
+        let issuers = configs
+            .into_iter()
+            .map(|config| JwtIssuer::new(config, revoked_tokens.clone()).map(Arc::new))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { issuers })
+    }
+
+    /// Start background refresh tasks for every issuer's JWKS cache (JWKS
+    /// mode) and revocation list (if configured)
+    ///
+    /// The tasks will run until the cancellation token is triggered.
+    /// Pass `CancellationToken::new()` if you don't need graceful shutdown.
+    pub fn start_background_refresh(&self, cancel_token: CancellationToken) {
+        for issuer in &self.issuers {
+            issuer.start_background_refresh(cancel_token.clone());
+        }
+    }
+
+    /// Whether JWKS keys have been fetched at least once for every JWKS
+    /// issuer (always `true` outside JWKS mode)
+    pub async fn jwks_loaded(&self) -> bool {
+        for issuer in &self.issuers {
+            if let Some(status) = issuer.jwks_status().await {
+                if status.keys == 0 {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Freshness of the JWKS cache, or `None` outside JWKS mode
+    ///
+    /// Background refreshes run at 75% of the cache lifetime, so a stale cache
+    /// means refreshes have been failing. With several JWKS issuers this
+    /// reports the one in the worst shape, so a failing IdP is not hidden
+    /// behind a healthy one.
+    pub async fn jwks_status(&self) -> Option<JwksStatus> {
+        let mut worst: Option<JwksStatus> = None;
+        for issuer in &self.issuers {
+            let Some(status) = issuer.jwks_status().await else {
+                continue;
+            };
+            let worse = match &worst {
+                None => true,
+                Some(current) => {
+                    (status.stale, status.keys == 0) > (current.stale, current.keys == 0)
+                }
+            };
+            if worse {
+                worst = Some(status);
+            }
+        }
+        worst
+    }
+
+    /// Mint a new JWT for the given identity, signed as the first issuer
+    pub fn mint_token(&self, identity: &Identity) -> Result<String, AuthError> {
+        self.issuers[0].mint_token(identity)
+    }
+
+    /// Issuers a token should be checked against, in configuration order
+    fn candidates(&self, token: &str) -> Result<Vec<&JwtIssuer>, AuthError> {
+        // A single issuer validates `iss` itself, with its usual errors
+        if self.issuers.len() == 1 {
+            return Ok(vec![&self.issuers[0]]);
+        }
+
+        // The claim is read unverified only to pick the issuer; the chosen
+        // issuer then checks the signature and `iss` as usual
+        let iss = unverified_issuer(token)
+            .ok_or_else(|| AuthError::InvalidJwt("Missing 'iss' claim".into()))?;
+        let candidates: Vec<&JwtIssuer> = self
+            .issuers
+            .iter()
+            .filter(|issuer| issuer.config.issuer == iss)
+            .map(|issuer| issuer.as_ref())
+            .collect();
+        if candidates.is_empty() {
+            return Err(AuthError::InvalidJwt("Invalid issuer".into()));
+        }
+        Ok(candidates)
+    }
+}
+
+/// Read the `iss` claim from a token without verifying it
+fn unverified_issuer(token: &str) -> Option<String> {
+    use base64::Engine;
+
+    #[derive(serde::Deserialize)]
+    struct Claims {
+        iss: Option<String>,
+    }
+
+    let payload = token.split('.').nth(1)?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload)
+        .ok()?;
+    serde_json::from_slice::<Claims>(&bytes).ok()?.iss
+}
+
+impl JwtIssuer {
+    fn new(
+        config: JwtConfig,
+        revoked_tokens: Option<RevokedTokenRepository>,
+    ) -> Result<Self, AuthError> {
+        let revocation = config
+            .revocation
+            .as_ref()
+            .map(|r| RevocationStore::new(r, revoked_tokens).map(Arc::new))
+            .transpose()?;
+        let identity_mapper = config
+            .identity
+            .as_ref()
+            .map(IdentityMapper::new)
+            .transpose()?;
+
         match &config.mode {
             JwtMode::Simple { secret } => {
                 let key = DecodingKey::from_secret(secret.as_bytes());
@@ -108,14 +295,21 @@ impl JwtProvider {
                     simple_encoding_key: Some(encoding_key),
                     jwks_cache: None,
                     http_client: None,
+                    revocation,
+                    identity_mapper,
                 })
             }
             JwtMode::Jwks {
                 cache_duration_secs,
+                min_refresh_interval_secs,
                 ..
             } => {
                 let cache_duration = Duration::from_secs(*cache_duration_secs);
-                let cache = Arc::new(RwLock::new(JwksCache::new(cache_duration)));
+                let min_refresh_interval = Duration::from_secs(*min_refresh_interval_secs);
+                let cache = Arc::new(RwLock::new(JwksCache::new(
+                    cache_duration,
+                    min_refresh_interval,
+                )));
                 let client = reqwest::Client::builder()
                     .timeout(Duration::from_secs(JWKS_HTTP_TIMEOUT_SECS))
                     .build()
@@ -129,16 +323,35 @@ impl JwtProvider {
                     simple_encoding_key: None,
                     jwks_cache: Some(cache),
                     http_client: Some(client),
+                    revocation,
+                    identity_mapper,
                 })
             }
         }
     }
 
-    /// Start background JWKS refresh task (for JWKS mode)
-    ///
-    /// The task will run until the cancellation token is triggered.
-    /// Pass `CancellationToken::new()` if you don't need graceful shutdown.
-    pub fn start_background_refresh(self: &Arc<Self>, cancel_token: CancellationToken) {
+    /// Start background refresh tasks for the JWKS cache (JWKS mode) and
+    /// the revocation list (if configured)
+    fn start_background_refresh(self: &Arc<Self>, cancel_token: CancellationToken) {
+        if let Some(revocation) = &self.revocation {
+            let revocation = Arc::clone(revocation);
+            let cancel_token = cancel_token.clone();
+            tokio::spawn(async move {
+                loop {
+                    if let Err(e) = revocation.refresh().await {
+                        tracing::warn!(error = %e, "Background revocation list refresh failed");
+                    }
+                    tokio::select! {
+                        _ = cancel_token.cancelled() => {
+                            tracing::debug!("Revocation list refresh task shutting down");
+                            break;
+                        }
+                        _ = tokio::time::sleep(revocation.refresh_interval()) => {}
+                    }
+                }
+            });
+        }
+
         if let JwtMode::Jwks {
             cache_duration_secs,
             ..
@@ -151,24 +364,38 @@ impl JwtProvider {
                     / JWKS_REFRESH_FRACTION_DENOMINATOR,
             );
 
+            // Fetch immediately so `/ready` can report the keys as loaded
+            // before the first token arrives
             tokio::spawn(async move {
                 loop {
+                    if let Err(e) = provider.refresh_jwks().await {
+                        tracing::warn!(error = %e, "Background JWKS refresh failed");
+                    }
                     tokio::select! {
                         _ = cancel_token.cancelled() => {
                             tracing::debug!("JWKS refresh task shutting down");
                             break;
                         }
-                        _ = tokio::time::sleep(refresh_interval) => {
-                            if let Err(e) = provider.refresh_jwks().await {
-                                tracing::warn!(error = %e, "Background JWKS refresh failed");
-                            }
-                        }
+                        _ = tokio::time::sleep(refresh_interval) => {}
                     }
                 }
             });
         }
     }
 
+    /// Freshness of the JWKS cache, or `None` outside JWKS mode
+    async fn jwks_status(&self) -> Option<JwksStatus> {
+        let cache = self.jwks_cache.as_ref()?.read().await;
+        // A successful fetch always stores at least one key
+        let fetched = !cache.keys.is_empty();
+        Some(JwksStatus {
+            keys: cache.keys.len(),
+            age_secs: fetched.then(|| cache.fetched_at.elapsed().as_secs()),
+            cache_duration_secs: cache.cache_duration.as_secs(),
+            stale: cache.is_expired(),
+        })
+    }
+
     /// Refresh JWKS from remote endpoint
     async fn refresh_jwks(&self) -> Result<(), AuthError> {
         let JwtMode::Jwks {
@@ -277,12 +504,37 @@ impl JwtProvider {
         }
 
         // Get key from cache
-        let cache_guard = cache.read().await;
-        cache_guard
+        if let Some(key) = Self::cached_jwks_key(cache, kid).await {
+            return Ok(key);
+        }
+
+        // Unknown kid: the IdP may have rotated its keys since the last fetch,
+        // so refetch now instead of waiting for the next scheduled refresh
+        let claimed = cache.write().await.try_claim_miss_refresh();
+        if claimed {
+            tracing::debug!(kid = %kid, "Unknown JWKS key ID, refetching keys");
+            if let Err(e) = self.refresh_jwks().await {
+                tracing::warn!(error = %e, "JWKS refetch for unknown key ID failed");
+            }
+            if let Some(key) = Self::cached_jwks_key(cache, kid).await {
+                return Ok(key);
+            }
+        }
+
+        Err(AuthError::InvalidJwt(format!("Unknown key ID: {}", kid)))
+    }
+
+    /// Look up a kid in the JWKS cache
+    async fn cached_jwks_key(
+        cache: &RwLock<JwksCache>,
+        kid: &str,
+    ) -> Option<(DecodingKey, Algorithm)> {
+        cache
+            .read()
+            .await
             .keys
             .get(kid)
             .map(|k| (k.key.clone(), k.algorithm))
-            .ok_or_else(|| AuthError::InvalidJwt(format!("Unknown key ID: {}", kid)))
     }
 
     /// Build validation parameters
@@ -313,7 +565,7 @@ impl JwtProvider {
     }
 
     /// Mint a new JWT for the given identity
-    pub fn mint_token(&self, identity: &Identity) -> Result<String, AuthError> {
+    fn mint_token(&self, identity: &Identity) -> Result<String, AuthError> {
         let encoding_key = self
             .simple_encoding_key
             .as_ref()
@@ -371,6 +623,28 @@ impl AuthProvider for JwtProvider {
         let header = decode_header(token)
             .map_err(|e| AuthError::InvalidJwt(format!("Invalid JWT header: {}", e)))?;
 
+        // Try each matching issuer in order; report the first one's error if
+        // none accepts the token
+        let mut first_err = None;
+        for issuer in self.candidates(token)? {
+            match issuer.authenticate(token, &header).await {
+                Ok(identity) => return Ok(identity),
+                Err(e) => {
+                    first_err.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_err.unwrap_or_else(|| AuthError::InvalidJwt("Invalid issuer".into())))
+    }
+
+    fn name(&self) -> &str {
+        "jwt"
+    }
+}
+
+impl JwtIssuer {
+    /// Validate a token against this issuer
+    async fn authenticate(&self, token: &str, header: &Header) -> Result<Identity, AuthError> {
         // Get decoding key and algorithm based on mode
         let (decoding_key, algorithm) = match &self.config.mode {
             JwtMode::Simple { .. } => {
@@ -427,6 +701,12 @@ impl AuthProvider for JwtProvider {
             })?
             .to_string();
 
+        // Reject tokens revoked before expiry
+        if let Some(revocation) = &self.revocation {
+            let jti = token_data.claims.get("jti").and_then(|v| v.as_str());
+            revocation.check(jti, &user_id).await?;
+        }
+
         // Extract scopes and map to tools
         let scopes = self.extract_scopes(&token_data.claims);
         let allowed_tools = map_scopes_to_tools(&scopes, &self.config.scope_tool_mapping);
@@ -438,17 +718,21 @@ impl AuthProvider for JwtProvider {
             .and_then(|v| v.as_str())
             .map(String::from);
 
-        Ok(Identity {
+        let mut identity = Identity {
             id: user_id,
             name,
             allowed_tools,
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None, // Could be extracted from claims if needed
             claims: token_data.claims,
-        })
-    }
-
-    fn name(&self) -> &str {
-        "jwt"
+            tenant: None,
+            argument_constraints: None,
+        };
+        if let Some(mapper) = &self.identity_mapper {
+            mapper.apply(&mut identity, !self.config.scope_tool_mapping.is_empty())?;
+        }
+        Ok(identity)
     }
 }
 
@@ -510,6 +794,8 @@ mod tests {
             scopes_claim: "scope".to_string(),
             scope_tool_mapping: HashMap::new(),
             leeway_secs: 0,
+            revocation: None,
+            identity: None,
         };
         JwtProvider::new(config).unwrap()
     }
@@ -640,6 +926,64 @@ mod tests {
         assert!(matches!(result, Err(AuthError::InvalidJwt(_))));
     }
 
+    #[tokio::test]
+    async fn test_revoked_token() {
+        use std::io::Write;
+
+        let mut list = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            list,
+            r#"{{"jti": ["revoked-jti"], "subjects": ["banned-user"]}}"#
+        )
+        .unwrap();
+
+        let config = JwtConfig {
+            mode: JwtMode::Simple {
+                secret: TEST_SECRET.to_string(),
+            },
+            issuer: "test-issuer".to_string(),
+            audience: "test-audience".to_string(),
+            user_id_claim: "sub".to_string(),
+            scopes_claim: "scope".to_string(),
+            scope_tool_mapping: HashMap::new(),
+            leeway_secs: 0,
+            revocation: Some(crate::config::JwtRevocationConfig {
+                source: crate::config::RevocationSource::File {
+                    path: list.path().to_path_buf(),
+                },
+                refresh_interval_secs: 60,
+            }),
+            identity: None,
+        };
+        let provider = JwtProvider::new(config).unwrap();
+        let now = now_secs();
+
+        let token_for = |sub: &str, jti: &str| {
+            let mut claims = HashMap::new();
+            claims.insert("sub".to_string(), serde_json::json!(sub));
+            claims.insert("jti".to_string(), serde_json::json!(jti));
+            claims.insert("iss".to_string(), serde_json::json!("test-issuer"));
+            claims.insert("aud".to_string(), serde_json::json!("test-audience"));
+            claims.insert("exp".to_string(), serde_json::json!(now + 3600));
+            create_test_token(&claims)
+        };
+
+        let result = provider
+            .authenticate(&token_for("user123", "revoked-jti"))
+            .await;
+        assert!(matches!(result, Err(AuthError::TokenRevoked)));
+
+        let result = provider
+            .authenticate(&token_for("banned-user", "fresh-jti"))
+            .await;
+        assert!(matches!(result, Err(AuthError::TokenRevoked)));
+
+        let result = provider
+            .authenticate(&token_for("user123", "fresh-jti"))
+            .await;
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_scope_extraction_string() {
         let mut scope_mapping = HashMap::new();
@@ -656,6 +1000,8 @@ mod tests {
             scopes_claim: "scope".to_string(),
             scope_tool_mapping: scope_mapping,
             leeway_secs: 0,
+            revocation: None,
+            identity: None,
         };
         let provider = JwtProvider::new(config).unwrap();
 
@@ -695,6 +1041,8 @@ mod tests {
             scopes_claim: "permissions".to_string(), // Array style
             scope_tool_mapping: scope_mapping,
             leeway_secs: 0,
+            revocation: None,
+            identity: None,
         };
         let provider = JwtProvider::new(config).unwrap();
 
@@ -732,6 +1080,8 @@ mod tests {
             scopes_claim: "scope".to_string(),
             scope_tool_mapping: scope_mapping,
             leeway_secs: 0,
+            revocation: None,
+            identity: None,
         };
         let provider = JwtProvider::new(config).unwrap();
 
@@ -812,6 +1162,8 @@ mod tests {
             scopes_claim: "scope".to_string(),
             scope_tool_mapping: HashMap::new(),
             leeway_secs: 0,
+            revocation: None,
+            identity: None,
         };
         let provider = JwtProvider::new(config).unwrap();
 
@@ -980,7 +1332,7 @@ mod tests {
 
     #[test]
     fn test_jwks_cache_new_starts_expired() {
-        let cache = JwksCache::new(Duration::from_secs(3600));
+        let cache = JwksCache::new(Duration::from_secs(3600), Duration::from_secs(30));
         // Cache should start expired to trigger immediate refresh
         assert!(cache.is_expired());
         assert!(cache.keys.is_empty());
@@ -988,7 +1340,7 @@ mod tests {
 
     #[test]
     fn test_jwks_cache_is_expired_after_duration() {
-        let mut cache = JwksCache::new(Duration::from_millis(1));
+        let mut cache = JwksCache::new(Duration::from_millis(1), Duration::from_secs(30));
         cache.fetched_at = Instant::now();
         // Should not be expired immediately
         assert!(!cache.is_expired());
@@ -999,11 +1351,21 @@ mod tests {
 
     #[test]
     fn test_jwks_cache_not_expired_within_duration() {
-        let mut cache = JwksCache::new(Duration::from_secs(3600));
+        let mut cache = JwksCache::new(Duration::from_secs(3600), Duration::from_secs(30));
         cache.fetched_at = Instant::now();
         assert!(!cache.is_expired());
     }
 
+    #[test]
+    fn test_jwks_cache_miss_refresh_rate_limited() {
+        let mut cache = JwksCache::new(Duration::from_secs(3600), Duration::from_millis(20));
+        assert!(cache.try_claim_miss_refresh());
+        // Second miss inside the interval must not refetch
+        assert!(!cache.try_claim_miss_refresh());
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.try_claim_miss_refresh());
+    }
+
     // -------------------------------------------------------------------------
     // JWKS Provider Initialization Tests
     // -------------------------------------------------------------------------
@@ -1015,6 +1377,7 @@ mod tests {
                 jwks_url: "https://example.com/.well-known/jwks.json".to_string(),
                 algorithms: vec!["RS256".to_string()],
                 cache_duration_secs: 3600,
+                min_refresh_interval_secs: 30,
             },
             issuer: "https://example.com".to_string(),
             audience: "test-audience".to_string(),
@@ -1022,15 +1385,68 @@ mod tests {
             scopes_claim: "scope".to_string(),
             scope_tool_mapping: HashMap::new(),
             leeway_secs: 0,
+            revocation: None,
+            identity: None,
         };
 
         let provider = JwtProvider::new(config);
         assert!(provider.is_ok());
 
         let provider = provider.unwrap();
-        assert!(provider.jwks_cache.is_some());
-        assert!(provider.http_client.is_some());
-        assert!(provider.simple_key.is_none());
+        assert!(provider.issuers[0].jwks_cache.is_some());
+        assert!(provider.issuers[0].http_client.is_some());
+        assert!(provider.issuers[0].simple_key.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_jwks_status() {
+        assert!(create_simple_provider().jwks_status().await.is_none());
+
+        let config = JwtConfig {
+            mode: JwtMode::Jwks {
+                jwks_url: "https://example.com/.well-known/jwks.json".to_string(),
+                algorithms: vec!["RS256".to_string()],
+                cache_duration_secs: 3600,
+                min_refresh_interval_secs: 30,
+            },
+            issuer: "https://example.com".to_string(),
+            audience: "test-audience".to_string(),
+            user_id_claim: "sub".to_string(),
+            scopes_claim: "scope".to_string(),
+            scope_tool_mapping: HashMap::new(),
+            leeway_secs: 0,
+            revocation: None,
+            identity: None,
+        };
+        let provider = JwtProvider::new(config).unwrap();
+
+        // Never fetched
+        let status = provider.jwks_status().await.unwrap();
+        assert_eq!(status.keys, 0);
+        assert!(status.age_secs.is_none());
+        assert_eq!(status.cache_duration_secs, 3600);
+        assert!(status.stale);
+
+        {
+            let mut cache = provider.issuers[0]
+                .jwks_cache
+                .as_ref()
+                .unwrap()
+                .write()
+                .await;
+            cache.keys.insert(
+                "kid-1".to_string(),
+                JwksKey {
+                    key: DecodingKey::from_secret(b"unused"),
+                    algorithm: Algorithm::RS256,
+                },
+            );
+            cache.fetched_at = Instant::now();
+        }
+        let status = provider.jwks_status().await.unwrap();
+        assert_eq!(status.keys, 1);
+        assert_eq!(status.age_secs, Some(0));
+        assert!(!status.stale);
     }
 
     #[tokio::test]
@@ -1040,6 +1456,7 @@ mod tests {
                 jwks_url: "https://example.com/.well-known/jwks.json".to_string(),
                 algorithms: vec!["RS256".to_string()],
                 cache_duration_secs: 3600,
+                min_refresh_interval_secs: 30,
             },
             issuer: "test-issuer".to_string(),
             audience: "test-audience".to_string(),
@@ -1047,6 +1464,8 @@ mod tests {
             scopes_claim: "scope".to_string(),
             scope_tool_mapping: HashMap::new(),
             leeway_secs: 0,
+            revocation: None,
+            identity: None,
         };
 
         let provider = JwtProvider::new(config).unwrap();
@@ -1077,7 +1496,7 @@ mod tests {
     #[test]
     fn test_build_validation_sets_correct_params() {
         let provider = create_simple_provider();
-        let validation = provider.build_validation(Algorithm::HS256);
+        let validation = provider.issuers[0].build_validation(Algorithm::HS256);
 
         // Validation should be configured with issuer and audience
         // We can't directly inspect private fields, but we can verify it works
@@ -1091,18 +1510,18 @@ mod tests {
         // Test with number value (should return empty)
         let mut claims = HashMap::new();
         claims.insert("scope".to_string(), serde_json::json!(123));
-        let scopes = provider.extract_scopes(&claims);
+        let scopes = provider.issuers[0].extract_scopes(&claims);
         assert!(scopes.is_empty());
 
         // Test with object value (should return empty)
         let mut claims = HashMap::new();
         claims.insert("scope".to_string(), serde_json::json!({"nested": "value"}));
-        let scopes = provider.extract_scopes(&claims);
+        let scopes = provider.issuers[0].extract_scopes(&claims);
         assert!(scopes.is_empty());
 
         // Test with missing scope claim (should return empty)
         let claims: HashMap<String, serde_json::Value> = HashMap::new();
-        let scopes = provider.extract_scopes(&claims);
+        let scopes = provider.issuers[0].extract_scopes(&claims);
         assert!(scopes.is_empty());
     }
 
@@ -1112,7 +1531,7 @@ mod tests {
 
         let mut claims = HashMap::new();
         claims.insert("scope".to_string(), serde_json::json!(""));
-        let scopes = provider.extract_scopes(&claims);
+        let scopes = provider.issuers[0].extract_scopes(&claims);
         assert!(scopes.is_empty());
     }
 
@@ -1122,7 +1541,7 @@ mod tests {
 
         let mut claims = HashMap::new();
         claims.insert("scope".to_string(), serde_json::json!([]));
-        let scopes = provider.extract_scopes(&claims);
+        let scopes = provider.issuers[0].extract_scopes(&claims);
         assert!(scopes.is_empty());
     }
 
@@ -1136,10 +1555,119 @@ mod tests {
             "scope".to_string(),
             serde_json::json!(["valid", 123, "also_valid", null]),
         );
-        let scopes = provider.extract_scopes(&claims);
+        let scopes = provider.issuers[0].extract_scopes(&claims);
         assert_eq!(scopes, vec!["valid", "also_valid"]);
     }
 
+    // -------------------------------------------------------------------------
+    // Multiple Issuer Tests
+    // -------------------------------------------------------------------------
+
+    const OTHER_SECRET: &str = "another-secret-key-at-least-32-characters";
+
+    fn simple_issuer(secret: &str, issuer: &str, audience: &str) -> JwtConfig {
+        JwtConfig {
+            mode: JwtMode::Simple {
+                secret: secret.to_string(),
+            },
+            issuer: issuer.to_string(),
+            audience: audience.to_string(),
+            user_id_claim: "sub".to_string(),
+            scopes_claim: "scope".to_string(),
+            scope_tool_mapping: HashMap::new(),
+            leeway_secs: 0,
+            revocation: None,
+            identity: None,
+        }
+    }
+
+    fn signed_token(secret: &str, issuer: &str, audience: &str, sub: &str) -> String {
+        let mut claims = HashMap::new();
+        claims.insert("sub".to_string(), serde_json::json!(sub));
+        claims.insert("iss".to_string(), serde_json::json!(issuer));
+        claims.insert("aud".to_string(), serde_json::json!(audience));
+        claims.insert("exp".to_string(), serde_json::json!(now_secs() + 3600));
+        encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_multiple_issuers_routed_by_iss() {
+        let mut auth0 = simple_issuer(OTHER_SECRET, "https://auth0.example.com/", "mcp");
+        auth0.user_id_claim = "email".to_string();
+        let provider = JwtProvider::with_issuers(
+            vec![
+                simple_issuer(TEST_SECRET, "https://okta.example.com", "mcp"),
+                auth0,
+            ],
+            None,
+        )
+        .unwrap();
+
+        let okta_token = signed_token(TEST_SECRET, "https://okta.example.com", "mcp", "alice");
+        assert_eq!(
+            provider.authenticate(&okta_token).await.unwrap().id,
+            "alice"
+        );
+
+        // Each issuer keeps its own claim mappings
+        let mut claims = HashMap::new();
+        claims.insert("email".to_string(), serde_json::json!("bob@example.com"));
+        claims.insert(
+            "iss".to_string(),
+            serde_json::json!("https://auth0.example.com/"),
+        );
+        claims.insert("aud".to_string(), serde_json::json!("mcp"));
+        claims.insert("exp".to_string(), serde_json::json!(now_secs() + 3600));
+        let auth0_token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(OTHER_SECRET.as_bytes()),
+        )
+        .unwrap();
+        assert_eq!(
+            provider.authenticate(&auth0_token).await.unwrap().id,
+            "bob@example.com"
+        );
+
+        // A token claiming one issuer but signed with another's key is rejected
+        let forged = signed_token(OTHER_SECRET, "https://okta.example.com", "mcp", "mallory");
+        assert!(provider.authenticate(&forged).await.is_err());
+
+        let unknown = signed_token(TEST_SECRET, "https://evil.example.com", "mcp", "alice");
+        let err = provider.authenticate(&unknown).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid issuer"), "got: {}", err);
+    }
+
+    #[tokio::test]
+    async fn test_multiple_issuers_same_iss_tried_in_order() {
+        let provider = JwtProvider::with_issuers(
+            vec![
+                simple_issuer(TEST_SECRET, "https://idp.example.com", "api-one"),
+                simple_issuer(TEST_SECRET, "https://idp.example.com", "api-two"),
+            ],
+            None,
+        )
+        .unwrap();
+
+        let token = signed_token(TEST_SECRET, "https://idp.example.com", "api-two", "alice");
+        assert_eq!(provider.authenticate(&token).await.unwrap().id, "alice");
+
+        // Neither entry accepts it: the first entry's error is reported
+        let token = signed_token(TEST_SECRET, "https://idp.example.com", "api-three", "alice");
+        let err = provider.authenticate(&token).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid audience"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_with_issuers_requires_one() {
+        assert!(JwtProvider::with_issuers(Vec::new(), None).is_err());
+    }
+
     // -------------------------------------------------------------------------
     // JWKS Integration Tests (requires wiremock)
     // -------------------------------------------------------------------------
@@ -1164,6 +1692,7 @@ mod tests {
                 jwks_url: format!("{}/.well-known/jwks.json", mock_server.uri()),
                 algorithms: vec!["RS256".to_string()],
                 cache_duration_secs: 0, // Force refresh
+                min_refresh_interval_secs: 30,
             },
             issuer: "test-issuer".to_string(),
             audience: "test-audience".to_string(),
@@ -1171,6 +1700,8 @@ mod tests {
             scopes_claim: "scope".to_string(),
             scope_tool_mapping: HashMap::new(),
             leeway_secs: 0,
+            revocation: None,
+            identity: None,
         };
 
         let provider = JwtProvider::new(config).unwrap();
@@ -1200,6 +1731,7 @@ mod tests {
                 jwks_url: mock_server.uri(),
                 algorithms: vec!["RS256".to_string()],
                 cache_duration_secs: 0,
+                min_refresh_interval_secs: 30,
             },
             issuer: "test".to_string(),
             audience: "test".to_string(),
@@ -1207,6 +1739,8 @@ mod tests {
             scopes_claim: "scope".into(),
             scope_tool_mapping: HashMap::new(),
             leeway_secs: 0,
+            revocation: None,
+            identity: None,
         };
 
         let provider = JwtProvider::new(config).unwrap();
@@ -1217,4 +1751,93 @@ mod tests {
         let result = provider.authenticate(token).await;
         assert!(result.is_err());
     }
+
+    /// JWKS document with a single RSA key
+    fn jwks_with_kid(kid: &str) -> serde_json::Value {
+        serde_json::json!({
+            "keys": [{
+                "kid": kid,
+                "kty": "RSA",
+                "alg": "RS256",
+                "n": "sXchDaQebHnPiGvyDOAT4saGEUetSyo9MKLOoWFsueri23bOdgWp4Dy1WlUzewbgBHod5pcM9H95GQRV3JDXboIRROSBigeC5yjU1hGzHHyXss8UDprecbAYxknTcQkhslANGRUZmdTOQ5qTRsLAt6BTYuyvVRdhS8exSZEy_c4gs_7svlJJQ4H9_NxsiIoLwAEk7-Q3UXERGYw_75IDrGA84-lA_-Ct4eTlXHBIY2EaV7t7LjJaynVJCpkv4LKjTTAumiGUIuQhrNhZLuF_RJLqHpM2kgWFLU7-VTdL1VbC2tejvcI2BlMkEpk1BzBZI0KQB0GaDWFLN-aEAw3vRw",
+                "e": "AQAB"
+            }]
+        })
+    }
+
+    /// RS256 token header with `kid: "kid-b"`; the signature is never checked
+    /// because these tests only exercise key lookup
+    const KID_B_TOKEN: &str =
+        "eyJhbGciOiJSUzI1NiIsImtpZCI6ImtpZC1iIiwidHlwIjoiSldUIn0.eyJzdWIiOiJ1c2VyIn0.signature";
+
+    fn jwks_config(jwks_url: String) -> JwtConfig {
+        JwtConfig {
+            mode: JwtMode::Jwks {
+                jwks_url,
+                algorithms: vec!["RS256".to_string()],
+                cache_duration_secs: 3600,
+                min_refresh_interval_secs: 30,
+            },
+            issuer: "test".to_string(),
+            audience: "test".to_string(),
+            user_id_claim: "sub".into(),
+            scopes_claim: "scope".into(),
+            scope_tool_mapping: HashMap::new(),
+            leeway_secs: 0,
+            revocation: None,
+            identity: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_jwks_refetch_on_unknown_kid() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+
+        // First fetch only knows kid-a; the IdP has rotated to kid-b by the next one
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(jwks_with_kid("kid-a")))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(jwks_with_kid("kid-b")))
+            .mount(&mock_server)
+            .await;
+
+        let provider = JwtProvider::new(jwks_config(mock_server.uri())).unwrap();
+
+        // The key is found after the refetch, so only the bogus signature fails
+        let err = provider.authenticate(KID_B_TOKEN).await.unwrap_err();
+        assert!(
+            !err.to_string().contains("Unknown key ID"),
+            "kid-b should have been refetched, got: {}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_jwks_unknown_kid_refetch_rate_limited() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+
+        // Initial fetch plus one unknown-kid refetch; the second miss must not
+        // reach the IdP
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(jwks_with_kid("kid-a")))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let provider = JwtProvider::new(jwks_config(mock_server.uri())).unwrap();
+
+        for _ in 0..2 {
+            let err = provider.authenticate(KID_B_TOKEN).await.unwrap_err();
+            assert!(err.to_string().contains("Unknown key ID"), "got: {}", err);
+        }
+    }
 }
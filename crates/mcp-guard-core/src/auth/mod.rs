@@ -20,21 +20,62 @@
 //! This module provides pluggable authentication for MCP requests:
 //! - API Key: Simple hash-based key validation
 //! - JWT: HS256 (simple) or RS256/ES256 (JWKS) token validation
-//! - OAuth 2.1: Token introspection and userinfo validation with PKCE
-//! - mTLS: Client certificate authentication via reverse proxy headers
+//! - OAuth 2.1: Token introspection and userinfo validation with PKCE, plus
+//!   optional server-side sessions that keep refresh tokens on the gateway
+//! - mTLS: Client certificate authentication via reverse proxy headers, including
+//!   SPIFFE workload IDs checked against a refreshable trust bundle
+//! - SAML: Tokens minted by a SAML bridge, with attribute-to-scope mapping
+//! - HMAC: Per-request signatures with replay protection for machine callers
+//! - Authorization server: Tokens issued by the gateway itself at `/oauth/token`
+//! - Custom: Providers from downstream crates, registered through an
+//!   [`AuthProviderFactory`] and configured under `[[auth.custom]]`
+//!
+//! API keys can be limited to recurring access windows and given temporary
+//! grants; see [`AccessSchedule`].
+//!
+//! JWT issuers can also reshape the identity from token claims (ID templates,
+//! claim-to-tool rules, attached claims) via [`IdentityMapper`].
 //!
 //! All providers implement the [`AuthProvider`] trait, allowing them to be
 //! combined via [`MultiProvider`] for fallback authentication.
 
+mod access;
+mod authorization_server;
+mod claims;
+mod custom;
+mod hmac;
 mod jwt;
 mod mtls;
 mod oauth;
-
-pub use jwt::JwtProvider;
+mod revocation;
+mod saml;
+mod session;
+mod spiffe;
+
+pub use access::{AccessSchedule, AccessScheduleError};
+pub use authorization_server::{is_password_hash, AuthorizationServer, IssuedTokens, Login};
+pub use claims::{ClaimTemplate, IdentityMapper};
+pub use custom::{
+    register_auth_provider, registered_auth_providers, AuthProviderFactory, AuthProviderRegistry,
+    CachingAuthProvider, StaticTokenProvider,
+};
+pub use hmac::{
+    sign_request, string_to_sign, HmacAuthProvider, HEADER_HMAC_NONCE, HEADER_HMAC_TIMESTAMP,
+    HMAC_AUTH_SCHEME,
+};
+pub(crate) use jwt::MAX_JWT_CLAIMS_SIZE;
+pub use jwt::{JwksStatus, JwtProvider};
 pub use mtls::{
-    ClientCertInfo, MtlsAuthProvider, HEADER_CLIENT_CERT_CN, HEADER_CLIENT_CERT_VERIFIED,
+    ClientCertInfo, MtlsAuthProvider, TrustedProxyValidator, HEADER_CLIENT_CERT,
+    HEADER_CLIENT_CERT_CN, HEADER_CLIENT_CERT_SAN_URI, HEADER_CLIENT_CERT_VERIFIED,
+};
+pub use oauth::{
+    DeviceAuthorization, DevicePoll, DeviceTokens, OAuthAuthProvider, RefreshedTokens,
 };
-pub use oauth::OAuthAuthProvider;
+pub use revocation::{RevocationList, RevocationStore};
+pub use saml::SamlBridgeProvider;
+pub use session::{SessionStore, SESSION_TOKEN_PREFIX};
+pub use spiffe::{SpiffeId, SpiffeTrustBundle};
 
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -59,12 +100,21 @@ pub enum AuthError {
     #[error("Token expired")]
     TokenExpired,
 
+    #[error("Token revoked")]
+    TokenRevoked,
+
+    #[error("Outside the key's access windows")]
+    OutsideAccessWindow,
+
     #[error("OAuth error: {0}")]
     OAuth(String),
 
     #[error("Invalid client certificate: {0}")]
     InvalidClientCert(String),
 
+    #[error("Invalid request signature: {0}")]
+    InvalidSignature(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -85,17 +135,49 @@ pub struct Identity {
     /// Allowed tools (None means all allowed)
     pub allowed_tools: Option<Vec<String>>,
 
+    /// Allowed resource URIs (None means all allowed)
+    pub allowed_resources: Option<Vec<String>>,
+
+    /// Allowed prompt names (None means all allowed)
+    pub allowed_prompts: Option<Vec<String>>,
+
     /// Custom rate limit for this identity
     pub rate_limit: Option<u32>,
 
     /// Additional claims/metadata from the authentication token
     pub claims: std::collections::HashMap<String, serde_json::Value>,
+
+    /// Tenant the identity belongs to, if tenancy is configured
+    pub tenant: Option<String>,
+
+    /// Constraints on tool-call arguments (None means unconstrained)
+    pub argument_constraints: Option<Arc<crate::authz::ArgumentConstraints>>,
 }
 
 // ============================================================================
 // Utility Functions
 // ============================================================================
 
+/// Convert a configured allow list to its [`Identity`] form (empty means all allowed)
+pub(crate) fn allow_list(patterns: &[String]) -> Option<Vec<String>> {
+    if patterns.is_empty() {
+        None
+    } else {
+        Some(patterns.to_vec())
+    }
+}
+
+/// Add granted entries to an allow list; unrestricted lists stay unrestricted
+fn with_grants<'a>(
+    allowed: Option<Vec<String>>,
+    granted: impl Iterator<Item = &'a String>,
+) -> Option<Vec<String>> {
+    allowed.map(|mut patterns| {
+        patterns.extend(granted.cloned());
+        patterns
+    })
+}
+
 /// Map OAuth/JWT scopes to allowed tools based on a scope-to-tool mapping
 ///
 /// # Arguments
@@ -159,11 +241,52 @@ pub trait AuthProvider: Send + Sync {
 /// SECURITY: Uses constant-time comparison to prevent timing attacks.
 pub struct ApiKeyProvider {
     keys: Vec<crate::config::ApiKeyConfig>,
+    /// Compiled argument constraints, indexed like `keys`
+    constraints: Vec<Option<Arc<crate::authz::ArgumentConstraints>>>,
+    /// Compiled access windows, indexed like `keys`
+    schedules: Vec<AccessSchedule>,
 }
 
 impl ApiKeyProvider {
     pub fn new(configs: Vec<crate::config::ApiKeyConfig>) -> Self {
-        Self { keys: configs }
+        use crate::authz::ArgumentConstraints;
+
+        let constraints = configs
+            .iter()
+            .map(|config| {
+                if config.constraints.is_empty() {
+                    return None;
+                }
+                let compiled = ArgumentConstraints::new(&config.constraints).unwrap_or_else(|e| {
+                    tracing::error!(
+                        key_id = %config.id,
+                        error = %e,
+                        "Invalid argument constraints; denying all tool calls for this key"
+                    );
+                    ArgumentConstraints::deny_all()
+                });
+                Some(Arc::new(compiled))
+            })
+            .collect();
+        let schedules = configs
+            .iter()
+            .map(|config| {
+                AccessSchedule::new(&config.access_windows).unwrap_or_else(|e| {
+                    tracing::error!(
+                        key_id = %config.id,
+                        error = %e,
+                        "Invalid access windows; rejecting this key"
+                    );
+                    AccessSchedule::deny_all()
+                })
+            })
+            .collect();
+
+        Self {
+            keys: configs,
+            constraints,
+            schedules,
+        }
     }
 
     fn hash_key(key: &str) -> String {
@@ -180,7 +303,7 @@ impl ApiKeyProvider {
     ///
     /// SECURITY: Prevents timing attacks by ensuring comparison takes the same
     /// amount of time regardless of where the hashes differ.
-    fn constant_time_compare(a: &str, b: &str) -> bool {
+    pub(crate) fn constant_time_compare(a: &str, b: &str) -> bool {
         use subtle::ConstantTimeEq;
 
         // First, compare lengths in constant time
@@ -199,6 +322,59 @@ impl ApiKeyProvider {
         // Both length and content must match
         (len_eq & bytes_eq).into()
     }
+
+    /// Identity of the key with the given ID, if it is configured and valid
+    ///
+    /// Used to re-resolve identities from tokens issued for an API key, so
+    /// removing or expiring the key also ends the tokens.
+    pub fn identity_for_id(&self, id: &str) -> Result<Identity, AuthError> {
+        let index = self
+            .keys
+            .iter()
+            .position(|config| config.id == id)
+            .ok_or(AuthError::InvalidApiKey)?;
+        self.identity(index)
+    }
+
+    /// Identity of the key at `index`, checking its validity and access
+    /// windows and adding its active grants
+    fn identity(&self, index: usize) -> Result<Identity, AuthError> {
+        let config = &self.keys[index];
+        let now = chrono::Utc::now();
+        if config.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(AuthError::TokenExpired);
+        }
+        if config.not_before.is_some_and(|not_before| now < not_before) {
+            tracing::debug!(key_id = %config.id, "API key used before its not_before time");
+            return Err(AuthError::InvalidApiKey);
+        }
+        if !self.schedules[index].allows(now) {
+            tracing::debug!(key_id = %config.id, "API key used outside its access windows");
+            return Err(AuthError::OutsideAccessWindow);
+        }
+
+        let grants = &config.grants;
+        Ok(Identity {
+            id: config.id.clone(),
+            name: Some(config.id.clone()),
+            allowed_tools: with_grants(
+                allow_list(&config.allowed_tools),
+                access::granted(grants, now, |grant| grant.tools.as_slice()),
+            ),
+            allowed_resources: with_grants(
+                allow_list(&config.allowed_resources),
+                access::granted(grants, now, |grant| grant.resources.as_slice()),
+            ),
+            allowed_prompts: with_grants(
+                allow_list(&config.allowed_prompts),
+                access::granted(grants, now, |grant| grant.prompts.as_slice()),
+            ),
+            rate_limit: config.rate_limit,
+            claims: std::collections::HashMap::new(),
+            tenant: config.tenant.clone(),
+            argument_constraints: self.constraints[index].clone(),
+        })
+    }
 }
 
 #[async_trait]
@@ -209,28 +385,19 @@ impl AuthProvider for ApiKeyProvider {
         // SECURITY: Iterate through ALL keys to prevent timing-based enumeration.
         // The loop always runs for the same number of iterations regardless of
         // which key matches (or if any matches at all).
-        let mut matched_config: Option<&crate::config::ApiKeyConfig> = None;
+        let mut matched_index: Option<usize> = None;
 
-        for config in &self.keys {
+        for (index, config) in self.keys.iter().enumerate() {
             if Self::constant_time_compare(&provided_hash, &config.key_hash) {
-                matched_config = Some(config);
+                matched_index = Some(index);
                 // Don't break - continue iterating to maintain constant time
             }
         }
 
-        matched_config
-            .map(|config| Identity {
-                id: config.id.clone(),
-                name: Some(config.id.clone()),
-                allowed_tools: if config.allowed_tools.is_empty() {
-                    None
-                } else {
-                    Some(config.allowed_tools.clone())
-                },
-                rate_limit: config.rate_limit,
-                claims: std::collections::HashMap::new(),
-            })
-            .ok_or(AuthError::InvalidApiKey)
+        let index = matched_index.ok_or(AuthError::InvalidApiKey)?;
+
+        // Validity window is checked after the full scan so timing stays uniform
+        self.identity(index)
     }
 
     fn name(&self) -> &str {
@@ -238,6 +405,50 @@ impl AuthProvider for ApiKeyProvider {
     }
 }
 
+/// How long before `expires_at` an API key is reported as expiring
+pub const API_KEY_EXPIRY_WARNING: std::time::Duration =
+    std::time::Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How often a running server re-checks API key expiry
+pub const API_KEY_EXPIRY_CHECK_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(60 * 60);
+
+/// Report configured API keys that have an `expires_at`
+///
+/// Sets the `mcp_guard_api_key_expiry_seconds` gauge for every key with an
+/// expiry, and logs a warning plus a `key_expiring` audit event for keys that
+/// expire within [`API_KEY_EXPIRY_WARNING`]. Returns the number of keys in the
+/// warning window. Called at startup and periodically while the server runs.
+pub fn report_api_key_expiry(
+    keys: &[crate::config::ApiKeyConfig],
+    audit_logger: &crate::audit::AuditLogger,
+) -> usize {
+    let now = chrono::Utc::now();
+    let warning_window = chrono::Duration::from_std(API_KEY_EXPIRY_WARNING)
+        .unwrap_or_else(|_| chrono::Duration::days(7));
+    let mut expiring = 0;
+
+    for key in keys {
+        let Some(expires_at) = key.expires_at else {
+            continue;
+        };
+        let remaining = expires_at - now;
+        crate::observability::set_api_key_expiry(&key.id, remaining.num_seconds());
+
+        if remaining > chrono::Duration::zero() && remaining <= warning_window {
+            expiring += 1;
+            tracing::warn!(
+                key_id = %key.id,
+                expires_at = %expires_at.to_rfc3339(),
+                "API key expires soon; rotate it with `mcp-guard keys rotate`"
+            );
+            audit_logger.log_key_expiring(&key.id, expires_at);
+        }
+    }
+
+    expiring
+}
+
 /// Database-backed authentication provider
 pub struct DatabaseAuthProvider {
     repository: crate::db::ApiKeyRepository,
@@ -281,8 +492,12 @@ impl AuthProvider for DatabaseAuthProvider {
                 allowed_tools: key.allowed_tools.and_then(|v| {
                     serde_json::from_value(v).ok()
                 }),
+                allowed_resources: None,
+                allowed_prompts: None,
                 rate_limit: key.rate_limit.map(|r| r as u32),
                 claims: HashMap::new(),
+                tenant: None,
+                argument_constraints: None,
             })
         } else {
             Err(AuthError::InvalidApiKey)
@@ -328,6 +543,8 @@ impl AuthProvider for MultiProvider {
                         (None, _) => true,
                         // Token expired is more specific than generic errors
                         (Some(AuthError::InvalidApiKey), AuthError::TokenExpired) => true,
+                        (Some(AuthError::InvalidApiKey), AuthError::TokenRevoked) => true,
+                        (Some(AuthError::InvalidApiKey), AuthError::OutsideAccessWindow) => true,
                         (Some(AuthError::InvalidApiKey), AuthError::InvalidJwt(_)) => true,
                         (Some(AuthError::InvalidApiKey), AuthError::OAuth(_)) => true,
                         (Some(AuthError::MissingCredentials), _) => true,
@@ -398,7 +615,18 @@ mod tests {
             id: "test-user".to_string(),
             key_hash: hash,
             allowed_tools: vec!["read".to_string()],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: Some(100),
+            network: None,
+            tenant: None,
+            description: None,
+            not_before: None,
+            expires_at: None,
+            constraints: vec![],
+            access_windows: vec![],
+            grants: vec![],
+            break_glass: false,
         };
 
         let provider = ApiKeyProvider::new(vec![config]);
@@ -419,7 +647,18 @@ mod tests {
             id: "test-user".to_string(),
             key_hash: hash,
             allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: None,
+            network: None,
+            tenant: None,
+            description: None,
+            not_before: None,
+            expires_at: None,
+            constraints: vec![],
+            access_windows: vec![],
+            grants: vec![],
+            break_glass: false,
         };
 
         let provider = ApiKeyProvider::new(vec![config]);
@@ -427,4 +666,133 @@ mod tests {
 
         assert!(matches!(result, Err(AuthError::InvalidApiKey)));
     }
+
+    #[tokio::test]
+    async fn test_api_key_provider_validity_window() {
+        let key = "rotating-key";
+        let now = chrono::Utc::now();
+
+        let mut config = crate::config::ApiKeyConfig {
+            id: "test-user".to_string(),
+            key_hash: ApiKeyProvider::hash_key(key),
+            allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
+            rate_limit: None,
+            network: None,
+            tenant: None,
+            description: Some("rotated key".to_string()),
+            not_before: Some(now - chrono::Duration::hours(1)),
+            expires_at: Some(now + chrono::Duration::hours(1)),
+            constraints: vec![],
+            access_windows: vec![],
+            grants: vec![],
+            break_glass: false,
+        };
+        let provider = ApiKeyProvider::new(vec![config.clone()]);
+        assert!(provider.authenticate(key).await.is_ok());
+
+        config.expires_at = Some(now - chrono::Duration::seconds(1));
+        config.not_before = None;
+        let provider = ApiKeyProvider::new(vec![config.clone()]);
+        assert!(matches!(
+            provider.authenticate(key).await,
+            Err(AuthError::TokenExpired)
+        ));
+
+        config.expires_at = None;
+        config.not_before = Some(now + chrono::Duration::hours(1));
+        let provider = ApiKeyProvider::new(vec![config.clone()]);
+        assert!(matches!(
+            provider.authenticate(key).await,
+            Err(AuthError::InvalidApiKey)
+        ));
+
+        // Only keys inside the warning window are reported
+        config.not_before = None;
+        config.expires_at = Some(now + chrono::Duration::days(2));
+        let mut later = config.clone();
+        later.expires_at = Some(now + chrono::Duration::days(90));
+        let audit = crate::audit::AuditLogger::disabled();
+        assert_eq!(report_api_key_expiry(&[config, later], &audit), 1);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_provider_access_windows_and_grants() {
+        use crate::config::{AccessGrantConfig, AccessWindowConfig};
+
+        let key = "scheduled-key";
+        let now = chrono::Utc::now();
+        let mut config = crate::config::ApiKeyConfig {
+            id: "auditor".to_string(),
+            key_hash: ApiKeyProvider::hash_key(key),
+            allowed_tools: vec!["read_file".to_string()],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
+            rate_limit: None,
+            network: None,
+            tenant: None,
+            description: None,
+            not_before: None,
+            expires_at: None,
+            constraints: vec![],
+            access_windows: vec![],
+            grants: vec![
+                AccessGrantConfig {
+                    tools: vec!["deploy".to_string()],
+                    resources: vec!["file:///tmp/*".to_string()],
+                    prompts: vec![],
+                    expires_at: now + chrono::Duration::hours(1),
+                    reason: Some("INC-1234".to_string()),
+                },
+                AccessGrantConfig {
+                    tools: vec!["rollback".to_string()],
+                    resources: vec![],
+                    prompts: vec![],
+                    expires_at: now - chrono::Duration::seconds(1),
+                    reason: None,
+                },
+            ],
+            break_glass: false,
+        };
+
+        // Active grants extend restricted lists only
+        let identity = ApiKeyProvider::new(vec![config.clone()])
+            .authenticate(key)
+            .await
+            .unwrap();
+        assert_eq!(
+            identity.allowed_tools,
+            Some(vec!["read_file".to_string(), "deploy".to_string()])
+        );
+        assert_eq!(identity.allowed_resources, None);
+
+        // A window that excludes the current minute rejects the key
+        let current = now.format("%H:%M").to_string();
+        let closed = (now + chrono::Duration::minutes(2))
+            .format("%H:%M")
+            .to_string();
+        config.access_windows = vec![AccessWindowConfig {
+            days: vec![],
+            start: closed,
+            end: current,
+            timezone: "UTC".to_string(),
+        }];
+        let provider = ApiKeyProvider::new(vec![config.clone()]);
+        assert!(matches!(
+            provider.authenticate(key).await,
+            Err(AuthError::OutsideAccessWindow)
+        ));
+        assert!(matches!(
+            provider.identity_for_id("auditor"),
+            Err(AuthError::OutsideAccessWindow)
+        ));
+
+        // Invalid windows fail closed
+        config.access_windows[0].timezone = "Nowhere/Special".to_string();
+        assert!(ApiKeyProvider::new(vec![config])
+            .authenticate(key)
+            .await
+            .is_err());
+    }
 }
@@ -30,19 +30,29 @@
 //! SECURITY: When using header-based mTLS, you MUST configure `trusted_proxy_ips`
 //! to prevent header spoofing attacks. Only requests from trusted proxy IPs will
 //! have their mTLS headers honored.
+//!
+//! With `identity_source = "spiffe"`, the identity is the SPIFFE ID from the
+//! certificate's URI SAN (X-Client-Cert-SAN-URI), mapped to permissions by
+//! `[[auth.mtls.spiffe.identities]]` patterns. See [`SpiffeTrustBundle`] for
+//! verifying the forwarded certificate against the mesh CAs.
 
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::net::IpAddr;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
-use crate::auth::{AuthError, AuthProvider, Identity};
-use crate::config::{MtlsConfig, MtlsIdentitySource};
+use crate::auth::{allow_list, AuthError, AuthProvider, Identity, SpiffeId, SpiffeTrustBundle};
+use crate::config::{MtlsConfig, MtlsIdentitySource, SpiffeConfig};
 
 /// Header names for client certificate info (from reverse proxy)
 pub const HEADER_CLIENT_CERT_CN: &str = "X-Client-Cert-CN";
 pub const HEADER_CLIENT_CERT_SAN_DNS: &str = "X-Client-Cert-SAN-DNS";
 pub const HEADER_CLIENT_CERT_SAN_EMAIL: &str = "X-Client-Cert-SAN-Email";
+pub const HEADER_CLIENT_CERT_SAN_URI: &str = "X-Client-Cert-SAN-URI";
 pub const HEADER_CLIENT_CERT_VERIFIED: &str = "X-Client-Cert-Verified";
+/// URL-encoded PEM of the client certificate (nginx `$ssl_client_escaped_cert`)
+pub const HEADER_CLIENT_CERT: &str = "X-Client-Cert";
 
 /// Trusted proxy IP validator
 ///
@@ -177,8 +187,24 @@ impl TrustedProxyValidator {
 pub struct MtlsAuthProvider {
     config: MtlsConfig,
     proxy_validator: TrustedProxyValidator,
+    spiffe: Option<SpiffeState>,
+}
+
+/// SPIFFE settings compiled for matching
+struct SpiffeState {
+    config: SpiffeConfig,
+    bundle: Option<Arc<SpiffeTrustBundle>>,
+    /// One pattern per `config.identities` entry
+    patterns: Vec<glob::Pattern>,
 }
 
+/// `*` must not cross path segments in SPIFFE ID patterns
+const SPIFFE_MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
 impl MtlsAuthProvider {
     /// Create a new mTLS auth provider
     pub fn new(config: MtlsConfig) -> Self {
@@ -192,9 +218,34 @@ impl MtlsAuthProvider {
             );
         }
 
+        let spiffe = match (&config.identity_source, &config.spiffe) {
+            (MtlsIdentitySource::Spiffe, Some(spiffe)) => Some(SpiffeState {
+                bundle: SpiffeTrustBundle::new(spiffe).map(Arc::new),
+                // Patterns are checked by config validation
+                patterns: spiffe
+                    .identities
+                    .iter()
+                    .filter_map(|rule| glob::Pattern::new(&rule.spiffe_id).ok())
+                    .collect(),
+                config: spiffe.clone(),
+            }),
+            _ => None,
+        };
+
         Self {
             config,
             proxy_validator,
+            spiffe,
+        }
+    }
+
+    /// Load the SPIFFE trust bundle and keep reloading it in the background
+    ///
+    /// No-op unless a SPIFFE trust bundle is configured. Until the first load
+    /// succeeds, SPIFFE authentication fails closed.
+    pub fn start_background_refresh(&self, cancel_token: CancellationToken) {
+        if let Some(bundle) = self.spiffe.as_ref().and_then(|s| s.bundle.as_ref()) {
+            bundle.start_background_refresh(cancel_token);
         }
     }
 
@@ -215,6 +266,7 @@ impl MtlsAuthProvider {
     pub fn extract_identity(&self, cert_info: &ClientCertInfo) -> Result<Identity, AuthError> {
         // Extract identity based on configured source
         let id = match self.config.identity_source {
+            MtlsIdentitySource::Spiffe => return self.extract_spiffe_identity(cert_info),
             MtlsIdentitySource::Cn => cert_info
                 .common_name
                 .clone()
@@ -231,12 +283,6 @@ impl MtlsAuthProvider {
             }
         };
 
-        let allowed_tools = if self.config.allowed_tools.is_empty() {
-            None
-        } else {
-            Some(self.config.allowed_tools.clone())
-        };
-
         let mut claims = HashMap::new();
         claims.insert(
             "auth_method".to_string(),
@@ -249,9 +295,113 @@ impl MtlsAuthProvider {
         Ok(Identity {
             id,
             name: cert_info.common_name.clone(),
-            allowed_tools,
+            allowed_tools: allow_list(&self.config.allowed_tools),
+            allowed_resources: allow_list(&self.config.allowed_resources),
+            allowed_prompts: allow_list(&self.config.allowed_prompts),
             rate_limit: self.config.rate_limit,
             claims,
+            tenant: None,
+            argument_constraints: None,
+        })
+    }
+
+    /// Identity from the SPIFFE ID in the certificate's URI SAN
+    fn extract_spiffe_identity(&self, cert_info: &ClientCertInfo) -> Result<Identity, AuthError> {
+        let spiffe = self
+            .spiffe
+            .as_ref()
+            .ok_or_else(|| AuthError::Internal("SPIFFE is not configured".into()))?;
+
+        // An SVID carries exactly one SPIFFE ID
+        let mut uris = cert_info
+            .san_uri
+            .iter()
+            .filter(|uri| uri.starts_with("spiffe://"));
+        let (Some(uri), None) = (uris.next(), uris.next()) else {
+            return Err(AuthError::InvalidClientCert(
+                "Expected exactly one SPIFFE ID in the certificate URI SAN".into(),
+            ));
+        };
+        let spiffe_id = SpiffeId::parse(uri)
+            .ok_or_else(|| AuthError::InvalidClientCert(format!("Malformed SPIFFE ID: {}", uri)))?;
+
+        if !spiffe
+            .config
+            .trust_domains
+            .iter()
+            .any(|domain| domain == spiffe_id.trust_domain())
+        {
+            return Err(AuthError::InvalidClientCert(format!(
+                "Untrusted SPIFFE trust domain: {}",
+                spiffe_id.trust_domain()
+            )));
+        }
+
+        if let Some(ref bundle) = spiffe.bundle {
+            let certificate = cert_info.certificate.as_deref().ok_or_else(|| {
+                AuthError::InvalidClientCert(
+                    "No client certificate forwarded for trust bundle verification".into(),
+                )
+            })?;
+            bundle.verify(certificate, &spiffe_id)?;
+        }
+
+        let (allowed_tools, allowed_resources, allowed_prompts, rate_limit) =
+            if spiffe.patterns.is_empty() {
+                (
+                    &self.config.allowed_tools,
+                    &self.config.allowed_resources,
+                    &self.config.allowed_prompts,
+                    self.config.rate_limit,
+                )
+            } else {
+                let rule = spiffe
+                    .patterns
+                    .iter()
+                    .position(|p| p.matches_with(spiffe_id.as_str(), SPIFFE_MATCH_OPTIONS))
+                    .map(|i| &spiffe.config.identities[i])
+                    .ok_or_else(|| {
+                        AuthError::InvalidClientCert(format!(
+                            "No SPIFFE identity rule matches {}",
+                            spiffe_id
+                        ))
+                    })?;
+                (
+                    &rule.allowed_tools,
+                    &rule.allowed_resources,
+                    &rule.allowed_prompts,
+                    rule.rate_limit,
+                )
+            };
+
+        let mut claims = HashMap::new();
+        claims.insert(
+            "auth_method".to_string(),
+            serde_json::Value::String("mtls".to_string()),
+        );
+        claims.insert(
+            "spiffe_id".to_string(),
+            serde_json::Value::String(spiffe_id.to_string()),
+        );
+        claims.insert(
+            "trust_domain".to_string(),
+            serde_json::Value::String(spiffe_id.trust_domain().to_string()),
+        );
+        claims.insert(
+            "path".to_string(),
+            serde_json::Value::String(spiffe_id.path().to_string()),
+        );
+
+        Ok(Identity {
+            id: spiffe_id.to_string(),
+            name: cert_info.common_name.clone(),
+            allowed_tools: allow_list(allowed_tools),
+            allowed_resources: allow_list(allowed_resources),
+            allowed_prompts: allow_list(allowed_prompts),
+            rate_limit,
+            claims,
+            tenant: None,
+            argument_constraints: None,
         })
     }
 }
@@ -275,6 +425,8 @@ impl AuthProvider for MtlsAuthProvider {
             common_name: Some(token.to_string()),
             san_dns: vec![],
             san_email: vec![],
+            san_uri: vec![],
+            certificate: None,
             verified: true,
         };
 
@@ -295,6 +447,10 @@ pub struct ClientCertInfo {
     pub san_dns: Vec<String>,
     /// Email addresses from Subject Alternative Name (SAN) extension
     pub san_email: Vec<String>,
+    /// URIs from Subject Alternative Name (SAN) extension (SPIFFE IDs)
+    pub san_uri: Vec<String>,
+    /// PEM-encoded certificate, when the proxy forwards it
+    pub certificate: Option<String>,
     /// Whether the certificate was verified
     pub verified: bool,
 }
@@ -309,6 +465,8 @@ impl ClientCertInfo {
     /// - X-Client-Cert-CN: Common Name from certificate
     /// - X-Client-Cert-SAN-DNS: Comma-separated DNS SANs
     /// - X-Client-Cert-SAN-Email: Comma-separated email SANs
+    /// - X-Client-Cert-SAN-URI: Comma-separated URI SANs
+    /// - X-Client-Cert: URL-encoded PEM certificate (optional)
     /// - X-Client-Cert-Verified: "SUCCESS" if verified
     pub fn from_headers_if_trusted(
         headers: &axum::http::HeaderMap,
@@ -368,10 +526,24 @@ impl ClientCertInfo {
             .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
             .unwrap_or_default();
 
+        let san_uri = headers
+            .get(HEADER_CLIENT_CERT_SAN_URI)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
+            .unwrap_or_default();
+
+        let certificate = headers
+            .get(HEADER_CLIENT_CERT)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| urlencoding::decode(s).ok())
+            .map(|s| s.into_owned());
+
         Some(ClientCertInfo {
             common_name,
             san_dns,
             san_email,
+            san_uri,
+            certificate,
             verified,
         })
     }
@@ -388,8 +560,11 @@ mod tests {
             enabled: true,
             identity_source: MtlsIdentitySource::Cn,
             allowed_tools: vec!["read_file".to_string()],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: Some(100),
             trusted_proxy_ips: vec!["127.0.0.1".to_string()],
+            spiffe: None,
         };
 
         let provider = MtlsAuthProvider::new(config);
@@ -455,8 +630,11 @@ mod tests {
             enabled: true,
             identity_source: MtlsIdentitySource::Cn,
             allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: None,
             trusted_proxy_ips: vec!["10.0.0.1".to_string()],
+            spiffe: None,
         };
         let provider = MtlsAuthProvider::new(config);
 
@@ -480,8 +658,11 @@ mod tests {
             enabled: true,
             identity_source: MtlsIdentitySource::Cn,
             allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: None,
             trusted_proxy_ips: vec!["10.0.0.1".to_string()],
+            spiffe: None,
         };
         let provider = MtlsAuthProvider::new(config);
 
@@ -502,8 +683,11 @@ mod tests {
             enabled: true,
             identity_source: MtlsIdentitySource::Cn,
             allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: None,
-            trusted_proxy_ips: vec![], // No trusted IPs!
+            trusted_proxy_ips: vec![], // No trusted IPs!,
+            spiffe: None,
         };
         let provider = MtlsAuthProvider::new(config);
 
@@ -528,8 +712,11 @@ mod tests {
             enabled: true,
             identity_source: MtlsIdentitySource::Cn,
             allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: None,
             trusted_proxy_ips: vec![],
+            spiffe: None,
         };
 
         let provider = MtlsAuthProvider::new(config);
@@ -537,6 +724,8 @@ mod tests {
             common_name: Some("service-client".to_string()),
             san_dns: vec!["client.example.com".to_string()],
             san_email: vec![],
+            san_uri: vec![],
+            certificate: None,
             verified: true,
         };
 
@@ -552,8 +741,11 @@ mod tests {
             enabled: true,
             identity_source: MtlsIdentitySource::SanDns,
             allowed_tools: vec!["read_file".to_string()],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: Some(50),
             trusted_proxy_ips: vec![],
+            spiffe: None,
         };
 
         let provider = MtlsAuthProvider::new(config);
@@ -561,6 +753,8 @@ mod tests {
             common_name: Some("service-client".to_string()),
             san_dns: vec!["client.example.com".to_string()],
             san_email: vec![],
+            san_uri: vec![],
+            certificate: None,
             verified: true,
         };
 
@@ -576,8 +770,11 @@ mod tests {
             enabled: true,
             identity_source: MtlsIdentitySource::Cn,
             allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: None,
             trusted_proxy_ips: vec![],
+            spiffe: None,
         };
 
         let provider = MtlsAuthProvider::new(config);
@@ -585,6 +782,8 @@ mod tests {
             common_name: None,
             san_dns: vec!["client.example.com".to_string()],
             san_email: vec![],
+            san_uri: vec![],
+            certificate: None,
             verified: true,
         };
 
@@ -624,8 +823,11 @@ mod tests {
             enabled: true,
             identity_source: MtlsIdentitySource::Cn,
             allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: None,
             trusted_proxy_ips: vec![],
+            spiffe: None,
         };
 
         let provider = MtlsAuthProvider::new(config);
@@ -633,6 +835,153 @@ mod tests {
         assert_eq!(identity.id, "my-client-cn");
     }
 
+    // --------------------------------------------------------------------------
+    // SPIFFE Tests
+    // --------------------------------------------------------------------------
+
+    fn spiffe_provider(
+        identities: Vec<crate::config::SpiffeIdentityConfig>,
+        trust_bundle_path: Option<std::path::PathBuf>,
+    ) -> MtlsAuthProvider {
+        MtlsAuthProvider::new(MtlsConfig {
+            enabled: true,
+            identity_source: MtlsIdentitySource::Spiffe,
+            allowed_tools: vec!["default_tool".to_string()],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
+            rate_limit: None,
+            trusted_proxy_ips: vec!["10.0.0.1".to_string()],
+            spiffe: Some(SpiffeConfig {
+                trust_domains: vec!["prod.example.com".to_string()],
+                trust_bundle_path,
+                trust_bundle_url: None,
+                refresh_interval_secs: 300,
+                identities,
+            }),
+        })
+    }
+
+    fn spiffe_cert(uri: &str) -> ClientCertInfo {
+        ClientCertInfo {
+            san_uri: vec![uri.to_string()],
+            verified: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_spiffe_identity_default_permissions() {
+        let provider = spiffe_provider(vec![], None);
+        let identity = provider
+            .extract_identity(&spiffe_cert("spiffe://prod.example.com/ns/payments/sa/api"))
+            .unwrap();
+
+        assert_eq!(identity.id, "spiffe://prod.example.com/ns/payments/sa/api");
+        assert_eq!(
+            identity.allowed_tools,
+            Some(vec!["default_tool".to_string()])
+        );
+        assert_eq!(identity.claims["trust_domain"], "prod.example.com");
+        assert_eq!(identity.claims["path"], "/ns/payments/sa/api");
+    }
+
+    #[test]
+    fn test_spiffe_identity_rules_first_match() {
+        let rule = |pattern: &str, tool: &str, rate_limit| crate::config::SpiffeIdentityConfig {
+            spiffe_id: pattern.to_string(),
+            allowed_tools: vec![tool.to_string()],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
+            rate_limit,
+        };
+        let provider = spiffe_provider(
+            vec![
+                rule(
+                    "spiffe://prod.example.com/ns/payments/sa/*",
+                    "pay",
+                    Some(10),
+                ),
+                rule("spiffe://prod.example.com/ns/**", "read_file", None),
+            ],
+            None,
+        );
+
+        let identity = provider
+            .extract_identity(&spiffe_cert("spiffe://prod.example.com/ns/payments/sa/api"))
+            .unwrap();
+        assert_eq!(identity.allowed_tools, Some(vec!["pay".to_string()]));
+        assert_eq!(identity.rate_limit, Some(10));
+
+        // `*` does not cross path segments, so the second rule applies
+        let identity = provider
+            .extract_identity(&spiffe_cert(
+                "spiffe://prod.example.com/ns/payments/sa/api/extra",
+            ))
+            .unwrap();
+        assert_eq!(identity.allowed_tools, Some(vec!["read_file".to_string()]));
+
+        // No rule matches
+        assert!(provider
+            .extract_identity(&spiffe_cert("spiffe://prod.example.com/batch/job"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_spiffe_rejects_untrusted_or_ambiguous_ids() {
+        let provider = spiffe_provider(vec![], None);
+
+        assert!(provider
+            .extract_identity(&spiffe_cert("spiffe://evil.example.com/ns/payments/sa/api"))
+            .is_err());
+        assert!(provider
+            .extract_identity(&spiffe_cert("spiffe://prod.example.com/../admin"))
+            .is_err());
+        assert!(provider
+            .extract_identity(&ClientCertInfo::default())
+            .is_err());
+
+        let mut two_ids = spiffe_cert("spiffe://prod.example.com/a");
+        two_ids
+            .san_uri
+            .push("spiffe://prod.example.com/b".to_string());
+        assert!(provider.extract_identity(&two_ids).is_err());
+    }
+
+    #[test]
+    fn test_spiffe_bundle_requires_forwarded_certificate() {
+        let provider = spiffe_provider(vec![], Some("/nonexistent/bundle.pem".into()));
+        let err = provider
+            .extract_identity(&spiffe_cert("spiffe://prod.example.com/workload"))
+            .unwrap_err();
+        assert!(err.to_string().contains("No client certificate forwarded"));
+    }
+
+    #[test]
+    fn test_client_cert_info_san_uri_and_certificate_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(HEADER_CLIENT_CERT_VERIFIED, "SUCCESS".parse().unwrap());
+        headers.insert(
+            HEADER_CLIENT_CERT_SAN_URI,
+            "spiffe://prod.example.com/workload".parse().unwrap(),
+        );
+        headers.insert(
+            HEADER_CLIENT_CERT,
+            "-----BEGIN%20CERTIFICATE-----%0AMIIB%0A-----END%20CERTIFICATE-----%0A"
+                .parse()
+                .unwrap(),
+        );
+
+        let cert_info = ClientCertInfo::from_headers_unchecked(&headers).unwrap();
+        assert_eq!(
+            cert_info.san_uri,
+            vec!["spiffe://prod.example.com/workload"]
+        );
+        assert_eq!(
+            cert_info.certificate.as_deref(),
+            Some("-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n")
+        );
+    }
+
     #[tokio::test]
     async fn test_authenticate_empty_token() {
         let config = MtlsConfig::default();
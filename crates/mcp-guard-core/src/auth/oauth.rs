@@ -20,6 +20,9 @@
 //! Supports multiple OAuth providers with token validation via:
 //! - Token introspection (RFC 7662) for opaque tokens
 //! - UserInfo endpoint as fallback
+//!
+//! Also drives the device authorization grant (RFC 8628) for headless clients
+//! that cannot follow browser redirects.
 
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -36,6 +39,7 @@ struct ProviderEndpoints {
     token_url: &'static str,
     userinfo_url: &'static str,
     introspection_url: Option<&'static str>,
+    device_authorization_url: Option<&'static str>,
 }
 
 impl ProviderEndpoints {
@@ -46,12 +50,14 @@ impl ProviderEndpoints {
                 token_url: "https://github.com/login/oauth/access_token",
                 userinfo_url: "https://api.github.com/user",
                 introspection_url: None, // GitHub doesn't support introspection
+                device_authorization_url: Some("https://github.com/login/device/code"),
             }),
             OAuthProviderType::Google => Some(Self {
                 authorization_url: "https://accounts.google.com/o/oauth2/v2/auth",
                 token_url: "https://oauth2.googleapis.com/token",
                 userinfo_url: "https://openidconnect.googleapis.com/v1/userinfo",
                 introspection_url: Some("https://oauth2.googleapis.com/tokeninfo"),
+                device_authorization_url: Some("https://oauth2.googleapis.com/device/code"),
             }),
             OAuthProviderType::Okta => None, // Requires tenant-specific URLs
             OAuthProviderType::Custom => None,
@@ -59,6 +65,69 @@ impl ProviderEndpoints {
     }
 }
 
+/// Tokens returned by the provider's token endpoint on a refresh grant
+#[derive(Debug, Clone)]
+pub struct RefreshedTokens {
+    /// New access token
+    pub access_token: String,
+    /// Access token lifetime in seconds, if the provider reported one
+    pub expires_in: Option<u64>,
+    /// Rotated refresh token, if the provider issued a new one
+    pub refresh_token: Option<String>,
+}
+
+/// Device authorization response (RFC 8628 section 3.2)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DeviceAuthorization {
+    /// Code the client polls the token endpoint with
+    pub device_code: String,
+    /// Code the user enters at the verification URI
+    pub user_code: String,
+    /// Where the user enters the code
+    #[serde(alias = "verification_url")] // Google's pre-RFC field name
+    pub verification_uri: String,
+    /// Verification URI with the user code embedded, if supported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_uri_complete: Option<String>,
+    /// Lifetime of the device and user codes in seconds
+    pub expires_in: u64,
+    /// Minimum polling interval in seconds
+    #[serde(default = "default_device_poll_interval")]
+    pub interval: u64,
+}
+
+fn default_device_poll_interval() -> u64 {
+    5 // RFC 8628 section 3.2 default
+}
+
+/// Outcome of polling the token endpoint with a device code
+#[derive(Debug, Clone)]
+pub enum DevicePoll {
+    /// User has not finished authorizing yet
+    Pending,
+    /// Client is polling too fast and must add 5 seconds to its interval
+    SlowDown,
+    /// User denied the authorization request
+    Denied,
+    /// Device code expired before the user finished
+    Expired,
+    /// User approved; tokens issued
+    Complete(DeviceTokens),
+}
+
+/// Tokens issued at the end of the device code flow
+#[derive(Debug, Clone)]
+pub struct DeviceTokens {
+    /// Provider access token
+    pub access_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: Option<u64>,
+    /// Refresh token, if issued
+    pub refresh_token: Option<String>,
+    /// Granted scopes
+    pub scope: Option<String>,
+}
+
 /// Token info from introspection or userinfo response
 #[derive(Debug, Clone, Default)]
 struct TokenInfo {
@@ -149,6 +218,14 @@ impl TokenCache {
         }
     }
 
+    /// Remove every cached token belonging to a user; returns how many
+    fn remove_user(&mut self, user_id: &str) -> usize {
+        let before = self.entries.len();
+        self.entries
+            .retain(|_, cached| cached.info.user_id.as_deref() != Some(user_id));
+        before - self.entries.len()
+    }
+
     fn cleanup_expired(&mut self) {
         let before = self.entries.len();
         self.entries
@@ -173,7 +250,7 @@ impl TokenCache {
             .collect();
 
         // Sort by age (oldest first)
-        entries.sort_by(|a, b| a.1.cmp(&b.1));
+        entries.sort_by_key(|a| a.1);
 
         // Remove oldest entries until we're under the limit
         let to_remove = self.entries.len() - CACHE_MAX_ENTRIES + 50; // Remove 50 extra to avoid frequent eviction
@@ -196,6 +273,7 @@ pub struct OAuthAuthProvider {
     token_url: String,
     userinfo_url: String,
     introspection_url: Option<String>,
+    device_authorization_url: Option<String>,
     http_client: reqwest::Client,
     token_cache: Arc<RwLock<TokenCache>>,
 }
@@ -232,8 +310,29 @@ impl OAuthAuthProvider {
                 .and_then(|e| e.introspection_url.map(String::from))
         });
 
+        let device_authorization_url = config.device_authorization_url.clone().or_else(|| {
+            endpoints
+                .as_ref()
+                .and_then(|e| e.device_authorization_url.map(String::from))
+        });
+
+        // Back-channel endpoints the guard calls itself; the authorization
+        // URL is only visited by the user's browser
+        for url in [Some(&token_url), Some(&userinfo_url)]
+            .into_iter()
+            .chain([
+                introspection_url.as_ref(),
+                device_authorization_url.as_ref(),
+            ])
+            .flatten()
+        {
+            crate::egress::check_egress(url)
+                .map_err(|e| AuthError::OAuth(format!("egress: {}", e)))?;
+        }
+
         let http_client = reqwest::Client::builder()
             .timeout(Duration::from_secs(HTTP_REQUEST_TIMEOUT_SECS))
+            .redirect(crate::egress::redirect_policy())
             .build()
             .map_err(|e| AuthError::Internal(format!("Failed to create HTTP client: {}", e)))?;
 
@@ -248,6 +347,7 @@ impl OAuthAuthProvider {
             token_url,
             userinfo_url,
             introspection_url,
+            device_authorization_url,
             http_client,
             token_cache,
         })
@@ -280,6 +380,254 @@ impl OAuthAuthProvider {
         &self.token_url
     }
 
+    /// Endpoints the guard calls itself: token, userinfo and, when
+    /// available, introspection and device authorization
+    pub fn back_channel_urls(&self) -> Vec<&str> {
+        [Some(&self.token_url), Some(&self.userinfo_url)]
+            .into_iter()
+            .chain([
+                self.introspection_url.as_ref(),
+                self.device_authorization_url.as_ref(),
+            ])
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Origin of the provider's authorization endpoint, advertised to MCP
+    /// clients as the authorization server
+    pub fn issuer(&self) -> Option<String> {
+        url::Url::parse(&self.authorization_url)
+            .ok()
+            .map(|url| url.origin().ascii_serialization())
+            .filter(|origin| origin != "null")
+    }
+
+    /// Whether the provider supports the device code flow
+    pub fn supports_device_flow(&self) -> bool {
+        self.device_authorization_url.is_some()
+    }
+
+    /// Forward a dynamic client registration request to the provider's
+    /// registration endpoint (RFC 7591 section 3)
+    ///
+    /// Returns the provider's status code and JSON body so its registration
+    /// errors reach the client unchanged.
+    pub async fn register_client(
+        &self,
+        metadata: &serde_json::Value,
+    ) -> Result<(u16, serde_json::Value), AuthError> {
+        let registration = self
+            .config
+            .registration
+            .as_ref()
+            .ok_or_else(|| AuthError::OAuth("Client registration not configured".into()))?;
+        let registration_url = registration
+            .registration_url
+            .as_ref()
+            .ok_or_else(|| AuthError::OAuth("registration_url not configured".into()))?;
+
+        let mut request = self
+            .http_client
+            .post(registration_url)
+            .header("Accept", "application/json")
+            .json(metadata);
+        if let Some(ref token) = registration.initial_access_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Registration request failed: {}", e)))?;
+        let status = response.status().as_u16();
+
+        let body = self.read_limited_body(response).await?;
+        let body: serde_json::Value = serde_json::from_slice(&body).map_err(|e| {
+            AuthError::OAuth(format!("Failed to parse registration response: {}", e))
+        })?;
+
+        Ok((status, body))
+    }
+
+    /// Start a device authorization request (RFC 8628 section 3.1)
+    pub async fn start_device_authorization(&self) -> Result<DeviceAuthorization, AuthError> {
+        let device_url = self
+            .device_authorization_url
+            .as_ref()
+            .ok_or_else(|| AuthError::OAuth("Device flow not supported by provider".into()))?;
+
+        let scope = self.config.scopes.join(" ");
+        let mut form = vec![
+            ("client_id", self.config.client_id.as_str()),
+            ("scope", scope.as_str()),
+        ];
+        if let Some(ref secret) = self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response = self
+            .http_client
+            .post(device_url)
+            .header("Accept", "application/json")
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Device authorization failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AuthError::OAuth(format!(
+                "Device authorization endpoint returned {}",
+                response.status()
+            )));
+        }
+
+        let body = self.read_limited_body(response).await?;
+        serde_json::from_slice(&body)
+            .map_err(|e| AuthError::OAuth(format!("Failed to parse device authorization: {}", e)))
+    }
+
+    /// Poll the token endpoint with a device code (RFC 8628 section 3.4)
+    pub async fn poll_device_token(&self, device_code: &str) -> Result<DevicePoll, AuthError> {
+        let mut form = vec![
+            ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+            ("device_code", device_code),
+            ("client_id", self.config.client_id.as_str()),
+        ];
+        if let Some(ref secret) = self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response = self
+            .http_client
+            .post(&self.token_url)
+            .header("Accept", "application/json")
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Device token request failed: {}", e)))?;
+
+        let status = response.status();
+        let body = self.read_limited_body(response).await?;
+        let body: serde_json::Value = serde_json::from_slice(&body)
+            .map_err(|e| AuthError::OAuth(format!("Failed to parse device token: {}", e)))?;
+
+        // Some providers (GitHub) report pending states with a 200 status,
+        // so check for an error code before trusting the status
+        match body.get("error").and_then(|v| v.as_str()) {
+            Some("authorization_pending") => return Ok(DevicePoll::Pending),
+            Some("slow_down") => return Ok(DevicePoll::SlowDown),
+            Some("access_denied") => return Ok(DevicePoll::Denied),
+            Some("expired_token") => return Ok(DevicePoll::Expired),
+            Some(error) => {
+                return Err(AuthError::OAuth(format!("Device flow failed: {}", error)));
+            }
+            None if !status.is_success() => {
+                return Err(AuthError::OAuth(format!(
+                    "Token endpoint returned {}",
+                    status
+                )));
+            }
+            None => {}
+        }
+
+        let access_token = body
+            .get("access_token")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| AuthError::OAuth("No access_token in device token response".into()))?
+            .to_string();
+
+        Ok(DevicePoll::Complete(DeviceTokens {
+            access_token,
+            expires_in: body.get("expires_in").and_then(|v| v.as_u64()),
+            refresh_token: body
+                .get("refresh_token")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            scope: body.get("scope").and_then(|v| v.as_str()).map(String::from),
+        }))
+    }
+
+    /// Read a provider response body, enforcing the size limit
+    ///
+    /// SECURITY: Prevents memory exhaustion from oversized provider responses.
+    async fn read_limited_body(&self, response: reqwest::Response) -> Result<Vec<u8>, AuthError> {
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Failed to read provider response: {}", e)))?;
+        if body.len() > MAX_OAUTH_RESPONSE_SIZE {
+            return Err(AuthError::OAuth(format!(
+                "Response size {} exceeds maximum {}",
+                body.len(),
+                MAX_OAUTH_RESPONSE_SIZE
+            )));
+        }
+        Ok(body.to_vec())
+    }
+
+    /// Exchange a refresh token for a new access token (refresh_token grant)
+    pub async fn refresh_access_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<RefreshedTokens, AuthError> {
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", self.config.client_id.as_str()),
+        ];
+        if let Some(ref secret) = self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response = self
+            .http_client
+            .post(&self.token_url)
+            .header("Accept", "application/json")
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Refresh request failed: {}", e)))?;
+
+        // Providers answer 400 invalid_grant for revoked or expired refresh tokens
+        if response.status() == reqwest::StatusCode::BAD_REQUEST
+            || response.status() == reqwest::StatusCode::UNAUTHORIZED
+        {
+            return Err(AuthError::TokenExpired);
+        }
+        if !response.status().is_success() {
+            return Err(AuthError::OAuth(format!(
+                "Token endpoint returned {}",
+                response.status()
+            )));
+        }
+
+        let body = self.read_limited_body(response).await?;
+        let body: serde_json::Value = serde_json::from_slice(&body)
+            .map_err(|e| AuthError::OAuth(format!("Failed to parse refresh response: {}", e)))?;
+
+        let access_token = body
+            .get("access_token")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| AuthError::OAuth("No access_token in refresh response".into()))?
+            .to_string();
+
+        Ok(RefreshedTokens {
+            access_token,
+            expires_in: body.get("expires_in").and_then(|v| v.as_u64()),
+            refresh_token: body
+                .get("refresh_token")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+        })
+    }
+
+    /// Drop cached introspection results for a user so their next request
+    /// is re-validated with the provider; returns how many were removed
+    pub async fn evict_identity(&self, user_id: &str) -> usize {
+        self.token_cache.write().await.remove_user(user_id)
+    }
+
     /// Hash a token for cache key (don't store raw tokens)
     fn hash_token(token: &str) -> String {
         use sha2::{Digest, Sha256};
@@ -522,8 +870,12 @@ impl AuthProvider for OAuthAuthProvider {
             id: user_id,
             name: info.username,
             allowed_tools,
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: info.claims,
+            tenant: None,
+            argument_constraints: None,
         })
     }
 
@@ -550,6 +902,9 @@ mod tests {
             user_id_claim: "sub".to_string(),
             scope_tool_mapping: HashMap::new(),
             token_cache_ttl_secs: 300,
+            session: None,
+            registration: None,
+            device_authorization_url: None,
         }
     }
 
@@ -573,6 +928,40 @@ mod tests {
             "https://github.com/login/oauth/access_token"
         );
         assert_eq!(provider.userinfo_url, "https://api.github.com/user");
+        assert_eq!(provider.issuer().as_deref(), Some("https://github.com"));
+        assert_eq!(
+            provider.device_authorization_url.as_deref(),
+            Some("https://github.com/login/device/code")
+        );
+        assert!(provider.supports_device_flow());
+    }
+
+    #[tokio::test]
+    async fn test_poll_device_token_pending_with_ok_status() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // GitHub reports pending/denied states with a 200 status
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"error": "access_denied"})),
+            )
+            .mount(&server)
+            .await;
+
+        let mut config = create_test_config();
+        config.provider = OAuthProviderType::Custom;
+        config.authorization_url = Some(format!("{}/authorize", server.uri()));
+        config.token_url = Some(format!("{}/token", server.uri()));
+        config.userinfo_url = Some(format!("{}/userinfo", server.uri()));
+        let provider = OAuthAuthProvider::new(config).unwrap();
+
+        assert!(!provider.supports_device_flow());
+        let poll = provider.poll_device_token("device-code").await.unwrap();
+        assert!(matches!(poll, DevicePoll::Denied));
     }
 
     #[test]
@@ -611,6 +1000,9 @@ mod tests {
             user_id_claim: "sub".to_string(),
             scope_tool_mapping: HashMap::new(),
             token_cache_ttl_secs: 300,
+            session: None,
+            registration: None,
+            device_authorization_url: None,
         };
 
         let result = OAuthAuthProvider::new(config);
@@ -703,4 +1095,22 @@ mod tests {
         assert_ne!(hash1, hash2);
         assert_eq!(hash1, hash1_again);
     }
+
+    #[test]
+    fn test_token_cache_remove_user() {
+        let mut cache = TokenCache::new(Duration::from_secs(60));
+        let info = |user: &str| TokenInfo {
+            active: true,
+            user_id: Some(user.to_string()),
+            ..Default::default()
+        };
+        cache.insert("a".into(), info("alice"));
+        cache.insert("b".into(), info("alice"));
+        cache.insert("c".into(), info("bob"));
+
+        assert_eq!(cache.remove_user("alice"), 2);
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.remove_user("alice"), 0);
+    }
 }
@@ -0,0 +1,342 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! JWT revocation list for mcp-guard
+//!
+//! Lets operators revoke individual tokens (by `jti`) or every token issued
+//! to a subject before the tokens expire. The list is loaded from a file, an
+//! HTTP endpoint or the `revoked_tokens` database table, and refreshed in the
+//! background alongside the JWKS cache.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::auth::AuthError;
+use crate::config::{JwtRevocationConfig, RevocationSource};
+use crate::db::RevokedTokenRepository;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// HTTP request timeout for revocation list fetches.
+const REVOCATION_HTTP_TIMEOUT_SECS: u64 = 10;
+
+/// Maximum size of a file or HTTP revocation list.
+/// SECURITY: Bounds memory use if the source is misconfigured or compromised.
+const MAX_REVOCATION_LIST_BYTES: usize = 8 * 1024 * 1024; // 8MB
+
+// ============================================================================
+// Revocation List
+// ============================================================================
+
+/// Snapshot of revoked token IDs and subjects
+///
+/// File and HTTP sources serve this as JSON:
+/// `{"jti": ["token-id", ...], "subjects": ["user-id", ...]}`
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct RevocationList {
+    /// Revoked token IDs (`jti` claim)
+    #[serde(default)]
+    pub jti: HashSet<String>,
+    /// Subjects whose tokens are all revoked
+    #[serde(default)]
+    pub subjects: HashSet<String>,
+}
+
+impl RevocationList {
+    /// Whether a token with the given `jti` and subject is revoked
+    pub fn is_revoked(&self, jti: Option<&str>, subject: &str) -> bool {
+        self.subjects.contains(subject) || jti.is_some_and(|jti| self.jti.contains(jti))
+    }
+
+    /// Total number of entries
+    pub fn len(&self) -> usize {
+        self.jti.len() + self.subjects.len()
+    }
+
+    /// Whether the list has no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+enum Loader {
+    File(PathBuf),
+    Http {
+        url: String,
+        client: reqwest::Client,
+    },
+    Database(RevokedTokenRepository),
+}
+
+// ============================================================================
+// Revocation Store
+// ============================================================================
+
+/// Periodically refreshed revocation list consulted by [`super::JwtProvider`]
+pub struct RevocationStore {
+    loader: Loader,
+    refresh_interval: Duration,
+    /// `None` until the first successful load
+    list: RwLock<Option<Arc<RevocationList>>>,
+}
+
+impl RevocationStore {
+    /// Create a store from configuration
+    ///
+    /// The `database` repository is required for the `database` source and
+    /// ignored otherwise.
+    pub fn new(
+        config: &JwtRevocationConfig,
+        database: Option<RevokedTokenRepository>,
+    ) -> Result<Self, AuthError> {
+        let loader = match &config.source {
+            RevocationSource::File { path } => Loader::File(path.clone()),
+            RevocationSource::Http { url } => {
+                let client = reqwest::Client::builder()
+                    .timeout(Duration::from_secs(REVOCATION_HTTP_TIMEOUT_SECS))
+                    .build()
+                    .map_err(|e| {
+                        AuthError::Internal(format!("Failed to create HTTP client: {}", e))
+                    })?;
+                Loader::Http {
+                    url: url.clone(),
+                    client,
+                }
+            }
+            RevocationSource::Database => Loader::Database(database.ok_or_else(|| {
+                AuthError::Internal(
+                    "JWT revocation source 'database' requires a database connection".into(),
+                )
+            })?),
+        };
+
+        Ok(Self {
+            loader,
+            refresh_interval: Duration::from_secs(config.refresh_interval_secs),
+            list: RwLock::new(None),
+        })
+    }
+
+    /// Interval between background refreshes
+    pub fn refresh_interval(&self) -> Duration {
+        self.refresh_interval
+    }
+
+    /// Reload the list from its source, returning the number of entries
+    ///
+    /// On failure the previously loaded list stays in effect.
+    pub async fn refresh(&self) -> Result<usize, AuthError> {
+        let list = self.load().await?;
+        let len = list.len();
+        *self.list.write().await = Some(Arc::new(list));
+        tracing::debug!(entries = len, "JWT revocation list refreshed");
+        Ok(len)
+    }
+
+    /// Reject the token if its `jti` or subject has been revoked
+    ///
+    /// SECURITY: Fails closed - if the list has never been loaded and cannot be
+    /// loaded now, the token is rejected rather than accepted unchecked.
+    pub async fn check(&self, jti: Option<&str>, subject: &str) -> Result<(), AuthError> {
+        let current = self.list.read().await.clone();
+        let list = match current {
+            Some(list) => list,
+            None => {
+                self.refresh().await?;
+                self.list.read().await.clone().unwrap_or_default()
+            }
+        };
+
+        if list.is_revoked(jti, subject) {
+            tracing::warn!(
+                subject = %subject,
+                jti = ?jti,
+                "Rejected revoked JWT"
+            );
+            return Err(AuthError::TokenRevoked);
+        }
+        Ok(())
+    }
+
+    async fn load(&self) -> Result<RevocationList, AuthError> {
+        match &self.loader {
+            Loader::File(path) => {
+                let bytes = tokio::fs::read(path).await.map_err(|e| {
+                    AuthError::Internal(format!(
+                        "Failed to read revocation list {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                parse_list(&bytes)
+            }
+            Loader::Http { url, client } => {
+                let response = client.get(url).send().await.map_err(|e| {
+                    AuthError::Internal(format!("Revocation list fetch failed: {}", e))
+                })?;
+                if !response.status().is_success() {
+                    return Err(AuthError::Internal(format!(
+                        "Revocation list endpoint returned {}",
+                        response.status()
+                    )));
+                }
+                let bytes = response.bytes().await.map_err(|e| {
+                    AuthError::Internal(format!("Revocation list fetch failed: {}", e))
+                })?;
+                parse_list(&bytes)
+            }
+            Loader::Database(repository) => {
+                let rows = repository
+                    .list_active()
+                    .await
+                    .map_err(|e| AuthError::Internal(e.to_string()))?;
+                let mut list = RevocationList::default();
+                for row in rows {
+                    if let Some(jti) = row.jti {
+                        list.jti.insert(jti);
+                    }
+                    if let Some(subject) = row.subject {
+                        list.subjects.insert(subject);
+                    }
+                }
+                Ok(list)
+            }
+        }
+    }
+}
+
+fn parse_list(bytes: &[u8]) -> Result<RevocationList, AuthError> {
+    if bytes.len() > MAX_REVOCATION_LIST_BYTES {
+        return Err(AuthError::Internal(format!(
+            "Revocation list size {} exceeds maximum {}",
+            bytes.len(),
+            MAX_REVOCATION_LIST_BYTES
+        )));
+    }
+    serde_json::from_slice(bytes)
+        .map_err(|e| AuthError::Internal(format!("Revocation list parse failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn file_store(file: &tempfile::NamedTempFile) -> RevocationStore {
+        let config = JwtRevocationConfig {
+            source: RevocationSource::File {
+                path: file.path().to_path_buf(),
+            },
+            refresh_interval_secs: 60,
+        };
+        RevocationStore::new(&config, None).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_file_source_revokes_jti_and_subject() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, r#"{{"jti": ["tok-1"], "subjects": ["mallory"]}}"#).unwrap();
+        let store = file_store(&file);
+
+        assert!(matches!(
+            store.check(Some("tok-1"), "alice").await,
+            Err(AuthError::TokenRevoked)
+        ));
+        assert!(matches!(
+            store.check(None, "mallory").await,
+            Err(AuthError::TokenRevoked)
+        ));
+        assert!(store.check(Some("tok-2"), "alice").await.is_ok());
+        assert!(store.check(None, "alice").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unloadable_list_fails_closed() {
+        let config = JwtRevocationConfig {
+            source: RevocationSource::File {
+                path: PathBuf::from("/nonexistent/revoked.json"),
+            },
+            refresh_interval_secs: 60,
+        };
+        let store = RevocationStore::new(&config, None).unwrap();
+
+        assert!(matches!(
+            store.check(Some("tok-1"), "alice").await,
+            Err(AuthError::Internal(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_keeps_previous_list() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, r#"{{"jti": ["tok-1"]}}"#).unwrap();
+        let store = file_store(&file);
+        assert_eq!(store.refresh().await.unwrap(), 1);
+
+        std::fs::write(file.path(), "not json").unwrap();
+        assert!(store.refresh().await.is_err());
+        assert!(matches!(
+            store.check(Some("tok-1"), "alice").await,
+            Err(AuthError::TokenRevoked)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_http_source_refresh() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/revoked.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"jti": ["a", "b"], "subjects": ["c"]})),
+            )
+            .mount(&server)
+            .await;
+
+        let config = JwtRevocationConfig {
+            source: RevocationSource::Http {
+                url: format!("{}/revoked.json", server.uri()),
+            },
+            refresh_interval_secs: 30,
+        };
+        let store = RevocationStore::new(&config, None).unwrap();
+
+        assert_eq!(store.refresh_interval(), Duration::from_secs(30));
+        assert_eq!(store.refresh().await.unwrap(), 3);
+        assert!(matches!(
+            store.check(Some("b"), "alice").await,
+            Err(AuthError::TokenRevoked)
+        ));
+    }
+
+    #[test]
+    fn test_database_source_requires_repository() {
+        let config = JwtRevocationConfig {
+            source: RevocationSource::Database,
+            refresh_interval_secs: 60,
+        };
+        assert!(RevocationStore::new(&config, None).is_err());
+    }
+}
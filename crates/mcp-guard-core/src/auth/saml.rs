@@ -0,0 +1,241 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! SAML federation provider for mcp-guard
+//!
+//! mcp-guard does not verify raw SAML XML assertions. Instead a SAML bridge
+//! (the SP-side broker that completes the SAML exchange with the IdP) mints a
+//! signed JWT carrying the NameID and attribute statements. This provider
+//! verifies that token like [`JwtProvider`] does, then maps SAML attributes to
+//! scopes and scopes to tools.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use crate::auth::{map_scopes_to_tools, AuthError, AuthProvider, Identity, JwtProvider};
+use crate::config::{JwtConfig, SamlConfig};
+
+/// Authenticates bearer tokens minted by a SAML bridge
+pub struct SamlBridgeProvider {
+    inner: Arc<JwtProvider>,
+    config: SamlConfig,
+}
+
+impl SamlBridgeProvider {
+    /// Create a provider from configuration
+    pub fn new(config: SamlConfig) -> Result<Self, AuthError> {
+        // Scope mapping is attribute-driven here, so the inner provider only
+        // verifies signature, issuer, audience and expiry.
+        let jwt_config = JwtConfig {
+            mode: config.mode.clone(),
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            user_id_claim: config.user_id_claim.clone(),
+            scopes_claim: String::new(),
+            scope_tool_mapping: HashMap::new(),
+            leeway_secs: config.leeway_secs,
+            revocation: None,
+            identity: None,
+        };
+        Ok(Self {
+            inner: Arc::new(JwtProvider::new(jwt_config)?),
+            config,
+        })
+    }
+
+    /// Start background JWKS refresh (no-op in simple mode)
+    pub fn start_background_refresh(&self, cancel_token: CancellationToken) {
+        self.inner.start_background_refresh(cancel_token);
+    }
+
+    /// Values of a SAML attribute, from the attributes claim or a top-level claim
+    fn attribute_values(
+        &self,
+        claims: &HashMap<String, serde_json::Value>,
+        attribute: &str,
+    ) -> Vec<String> {
+        let value = claims
+            .get(&self.config.attributes_claim)
+            .and_then(|attrs| attrs.get(attribute))
+            .or_else(|| claims.get(attribute));
+
+        match value {
+            Some(serde_json::Value::String(s)) => vec![s.clone()],
+            Some(serde_json::Value::Array(arr)) => arr
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(String::from)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Derive scopes from the configured attribute rules
+    fn map_attributes_to_scopes(&self, claims: &HashMap<String, serde_json::Value>) -> Vec<String> {
+        let mut scopes: Vec<String> = Vec::new();
+        for rule in &self.config.attribute_scopes {
+            if self
+                .attribute_values(claims, &rule.attribute)
+                .iter()
+                .any(|v| v == &rule.value)
+            {
+                for scope in &rule.scopes {
+                    if !scopes.contains(scope) {
+                        scopes.push(scope.clone());
+                    }
+                }
+            }
+        }
+        scopes
+    }
+}
+
+#[async_trait]
+impl AuthProvider for SamlBridgeProvider {
+    async fn authenticate(&self, token: &str) -> Result<Identity, AuthError> {
+        let mut identity = self.inner.authenticate(token).await?;
+
+        let scopes = self.map_attributes_to_scopes(&identity.claims);
+        identity.allowed_tools = map_scopes_to_tools(&scopes, &self.config.scope_tool_mapping);
+
+        if let Some(name_attribute) = &self.config.name_attribute {
+            identity.name = self
+                .attribute_values(&identity.claims, name_attribute)
+                .into_iter()
+                .next()
+                .or(identity.name);
+        }
+
+        Ok(identity)
+    }
+
+    fn name(&self) -> &str {
+        "saml"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{JwtMode, SamlAttributeScope};
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const TEST_SECRET: &str = "saml-bridge-secret-at-least-32-characters";
+
+    fn create_provider() -> SamlBridgeProvider {
+        let mut scope_tool_mapping = HashMap::new();
+        scope_tool_mapping.insert(
+            "tools:read".to_string(),
+            vec!["read_file".to_string(), "list_directory".to_string()],
+        );
+        scope_tool_mapping.insert("tools:write".to_string(), vec!["write_file".to_string()]);
+
+        SamlBridgeProvider::new(SamlConfig {
+            mode: JwtMode::Simple {
+                secret: TEST_SECRET.to_string(),
+            },
+            issuer: "saml-bridge".to_string(),
+            audience: "mcp-guard".to_string(),
+            user_id_claim: "sub".to_string(),
+            name_attribute: Some("displayName".to_string()),
+            attributes_claim: "attributes".to_string(),
+            attribute_scopes: vec![
+                SamlAttributeScope {
+                    attribute: "memberOf".to_string(),
+                    value: "engineering".to_string(),
+                    scopes: vec!["tools:read".to_string()],
+                },
+                SamlAttributeScope {
+                    attribute: "department".to_string(),
+                    value: "platform".to_string(),
+                    scopes: vec!["tools:write".to_string()],
+                },
+            ],
+            scope_tool_mapping,
+            leeway_secs: 0,
+        })
+        .unwrap()
+    }
+
+    fn create_token(extra: serde_json::Value) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let mut claims = serde_json::json!({
+            "sub": "alice@example.com",
+            "iss": "saml-bridge",
+            "aud": "mcp-guard",
+            "exp": now + 3600,
+            "iat": now,
+        });
+        if let (Some(claims), serde_json::Value::Object(extra)) = (claims.as_object_mut(), extra) {
+            claims.extend(extra);
+        }
+        encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(TEST_SECRET.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_attributes_map_to_tools() {
+        let provider = create_provider();
+        let token = create_token(serde_json::json!({
+            "attributes": {
+                "memberOf": ["staff", "engineering"],
+                "displayName": "Alice"
+            },
+            "department": "platform"
+        }));
+
+        let identity = provider.authenticate(&token).await.unwrap();
+        assert_eq!(identity.id, "alice@example.com");
+        assert_eq!(identity.name.as_deref(), Some("Alice"));
+
+        let mut tools = identity.allowed_tools.unwrap();
+        tools.sort();
+        assert_eq!(tools, vec!["list_directory", "read_file", "write_file"]);
+    }
+
+    #[tokio::test]
+    async fn test_no_matching_attributes_grants_no_tools() {
+        let provider = create_provider();
+        let token = create_token(serde_json::json!({
+            "attributes": { "memberOf": "sales" }
+        }));
+
+        let identity = provider.authenticate(&token).await.unwrap();
+        assert_eq!(identity.allowed_tools, Some(vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_wrong_issuer() {
+        let provider = create_provider();
+        let token = create_token(serde_json::json!({ "iss": "someone-else" }));
+
+        assert!(matches!(
+            provider.authenticate(&token).await,
+            Err(AuthError::InvalidJwt(_))
+        ));
+    }
+}
@@ -0,0 +1,750 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Server-side OAuth session store for mcp-guard
+//!
+//! After the OAuth callback, the provider's refresh token stays on the gateway.
+//! The client receives an opaque session token (also set as a cookie) and the
+//! gateway refreshes the provider access token shortly before it expires, so
+//! clients never handle provider tokens or refresh logic.
+//!
+//! Sessions live in process memory unless a shared [`Storage`] is attached
+//! with [`SessionStore::with_storage`] (cluster mode), in which case every
+//! replica reads and writes the same `oauth_sessions` table.
+
+use chrono::Utc;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::auth::{AuthError, AuthProvider, Identity, OAuthAuthProvider};
+use crate::config::OAuthSessionConfig;
+use crate::db::{DbOAuthSession, Storage};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Prefix identifying gateway session tokens in the Authorization header.
+pub const SESSION_TOKEN_PREFIX: &str = "mcpg_sess_";
+
+/// Random bytes in a session token (256 bits).
+const SESSION_TOKEN_BYTES: usize = 32;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Mutable part of a session, locked while a refresh is in flight so
+/// concurrent requests don't spend the same refresh token twice
+struct SessionState {
+    identity: Identity,
+    refresh_token: Option<String>,
+    access_expires_at: Option<Instant>,
+}
+
+struct Session {
+    /// Identity the session was started for, readable without the state lock
+    identity_id: String,
+    expires_at: Instant,
+    state: Mutex<SessionState>,
+}
+
+/// Identity as kept in the shared session table
+///
+/// OAuth identities never carry argument constraints, so those are not stored.
+#[derive(Serialize, Deserialize)]
+struct StoredIdentity {
+    id: String,
+    name: Option<String>,
+    allowed_tools: Option<Vec<String>>,
+    allowed_resources: Option<Vec<String>>,
+    allowed_prompts: Option<Vec<String>>,
+    rate_limit: Option<u32>,
+    #[serde(default)]
+    claims: HashMap<String, serde_json::Value>,
+    tenant: Option<String>,
+}
+
+impl From<&Identity> for StoredIdentity {
+    fn from(identity: &Identity) -> Self {
+        Self {
+            id: identity.id.clone(),
+            name: identity.name.clone(),
+            allowed_tools: identity.allowed_tools.clone(),
+            allowed_resources: identity.allowed_resources.clone(),
+            allowed_prompts: identity.allowed_prompts.clone(),
+            rate_limit: identity.rate_limit,
+            claims: identity.claims.clone(),
+            tenant: identity.tenant.clone(),
+        }
+    }
+}
+
+impl From<StoredIdentity> for Identity {
+    fn from(stored: StoredIdentity) -> Self {
+        Self {
+            id: stored.id,
+            name: stored.name,
+            allowed_tools: stored.allowed_tools,
+            allowed_resources: stored.allowed_resources,
+            allowed_prompts: stored.allowed_prompts,
+            rate_limit: stored.rate_limit,
+            claims: stored.claims,
+            tenant: stored.tenant,
+            argument_constraints: None,
+        }
+    }
+}
+
+/// OAuth session store keyed by session token hash
+pub struct SessionStore {
+    config: OAuthSessionConfig,
+    sessions: DashMap<String, Arc<Session>>,
+    /// Shared session table used instead of `sessions` in cluster mode
+    storage: Option<Arc<dyn Storage>>,
+}
+
+impl SessionStore {
+    /// Create an empty session store
+    pub fn new(config: OAuthSessionConfig) -> Self {
+        Self {
+            config,
+            sessions: DashMap::new(),
+            storage: None,
+        }
+    }
+
+    /// Keep sessions in shared storage so every replica can resolve them
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Whether a bearer token is a gateway session token
+    pub fn is_session_token(token: &str) -> bool {
+        token.starts_with(SESSION_TOKEN_PREFIX)
+    }
+
+    /// Session lifetime in seconds
+    pub fn ttl_secs(&self) -> u64 {
+        self.config.ttl_secs
+    }
+
+    /// Number of live sessions held in this process (always 0 with shared storage)
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Whether the store holds no sessions
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Start a session for an authenticated identity and return a new
+    /// session token for the client
+    ///
+    /// `expires_in` is the provider access token lifetime; the access token
+    /// itself is not needed since the identity has already been resolved.
+    pub async fn create(
+        &self,
+        identity: Identity,
+        expires_in: Option<u64>,
+        refresh_token: Option<String>,
+    ) -> Result<String, AuthError> {
+        if let Some(ref storage) = self.storage {
+            return self
+                .create_shared(storage.as_ref(), identity, expires_in, refresh_token)
+                .await;
+        }
+
+        if self.sessions.len() >= self.config.max_sessions {
+            self.cleanup_expired();
+            if self.sessions.len() >= self.config.max_sessions {
+                return Err(AuthError::Internal("Session store at capacity".into()));
+            }
+        }
+
+        let token = new_session_token();
+        let now = Instant::now();
+        self.sessions.insert(
+            hash_token(&token),
+            Arc::new(Session {
+                identity_id: identity.id.clone(),
+                expires_at: now + Duration::from_secs(self.config.ttl_secs),
+                state: Mutex::new(SessionState {
+                    identity,
+                    refresh_token,
+                    access_expires_at: expires_in.map(|secs| now + Duration::from_secs(secs)),
+                }),
+            }),
+        );
+        Ok(token)
+    }
+
+    /// Resolve a session token to its identity, refreshing the provider
+    /// access token first if it is about to expire
+    ///
+    /// A session whose refresh fails is dropped; the client must log in again.
+    pub async fn resolve(
+        &self,
+        token: &str,
+        provider: &OAuthAuthProvider,
+    ) -> Result<Identity, AuthError> {
+        if let Some(ref storage) = self.storage {
+            return self.resolve_shared(storage.as_ref(), token, provider).await;
+        }
+
+        let key = hash_token(token);
+        let session = self
+            .sessions
+            .get(&key)
+            .map(|entry| Arc::clone(entry.value()))
+            .ok_or_else(|| AuthError::OAuth("Unknown session".into()))?;
+
+        if session.expires_at <= Instant::now() {
+            self.sessions.remove(&key);
+            return Err(AuthError::TokenExpired);
+        }
+
+        let mut state = session.state.lock().await;
+        let skew = Duration::from_secs(self.config.refresh_before_expiry_secs);
+        let Some(access_expires_at) = state.access_expires_at else {
+            return Ok(state.identity.clone());
+        };
+        if access_expires_at > Instant::now() + skew {
+            return Ok(state.identity.clone());
+        }
+
+        let result = match state.refresh_token.clone() {
+            Some(refresh_token) => refresh_session(&mut state, &refresh_token, provider).await,
+            // Without a refresh token the session lasts as long as the access token
+            None if access_expires_at > Instant::now() => return Ok(state.identity.clone()),
+            None => Err(AuthError::TokenExpired),
+        };
+
+        match result {
+            Ok(()) => Ok(state.identity.clone()),
+            Err(e) => {
+                tracing::info!(
+                    user_id = %state.identity.id,
+                    error = %e,
+                    "OAuth session refresh failed, ending session"
+                );
+                drop(state);
+                self.sessions.remove(&key);
+                Err(e)
+            }
+        }
+    }
+
+    /// End a session; returns whether it existed
+    pub async fn revoke(&self, token: &str) -> bool {
+        if let Some(ref storage) = self.storage {
+            return storage
+                .delete_oauth_session(&hash_token(token))
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "Failed to delete shared OAuth session");
+                    false
+                });
+        }
+        self.sessions.remove(&hash_token(token)).is_some()
+    }
+
+    /// End every session belonging to an identity; returns how many ended
+    pub async fn revoke_identity(&self, identity_id: &str) -> usize {
+        if let Some(ref storage) = self.storage {
+            return match storage.delete_identity_oauth_sessions(identity_id).await {
+                Ok(count) => count as usize,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to delete shared OAuth sessions");
+                    0
+                }
+            };
+        }
+        let before = self.sessions.len();
+        self.sessions
+            .retain(|_, session| session.identity_id != identity_id);
+        before - self.sessions.len()
+    }
+
+    async fn create_shared(
+        &self,
+        storage: &dyn Storage,
+        identity: Identity,
+        expires_in: Option<u64>,
+        refresh_token: Option<String>,
+    ) -> Result<String, AuthError> {
+        let count = storage
+            .count_oauth_sessions()
+            .await
+            .map_err(storage_error)?;
+        if count >= self.config.max_sessions as i64 {
+            storage
+                .purge_oauth_sessions()
+                .await
+                .map_err(storage_error)?;
+            let count = storage
+                .count_oauth_sessions()
+                .await
+                .map_err(storage_error)?;
+            if count >= self.config.max_sessions as i64 {
+                return Err(AuthError::Internal("Session store at capacity".into()));
+            }
+        }
+
+        let token = new_session_token();
+        let now = Utc::now();
+        let session = DbOAuthSession {
+            token_hash: hash_token(&token),
+            identity_id: identity.id.clone(),
+            identity: stored_identity(&identity)?,
+            refresh_token,
+            access_expires_at: expires_in.map(|secs| now + seconds(secs)),
+            expires_at: now + seconds(self.config.ttl_secs),
+        };
+        storage
+            .put_oauth_session(&session)
+            .await
+            .map_err(storage_error)?;
+        Ok(token)
+    }
+
+    /// [`resolve`](Self::resolve) against shared storage
+    ///
+    /// There is no cross-replica lock around a refresh. If another replica
+    /// refreshes the same session first and the provider rejects the old
+    /// refresh token, the session written by that replica is used.
+    async fn resolve_shared(
+        &self,
+        storage: &dyn Storage,
+        token: &str,
+        provider: &OAuthAuthProvider,
+    ) -> Result<Identity, AuthError> {
+        let key = hash_token(token);
+        let session = storage
+            .find_oauth_session(&key)
+            .await
+            .map_err(storage_error)?
+            .ok_or_else(|| AuthError::OAuth("Unknown session".into()))?;
+
+        let now = Utc::now();
+        if session.expires_at <= now {
+            let _ = storage.delete_oauth_session(&key).await;
+            return Err(AuthError::TokenExpired);
+        }
+
+        let identity = load_identity(&session)?;
+        let skew = seconds(self.config.refresh_before_expiry_secs);
+        let Some(access_expires_at) = session.access_expires_at else {
+            return Ok(identity);
+        };
+        if access_expires_at > now + skew {
+            return Ok(identity);
+        }
+
+        let result = match session.refresh_token.clone() {
+            Some(ref refresh_token) => refresh_tokens(refresh_token, provider).await,
+            None if access_expires_at > now => return Ok(identity),
+            None => Err(AuthError::TokenExpired),
+        };
+
+        match result {
+            Ok((identity, expires_in, rotated)) => {
+                let refreshed = DbOAuthSession {
+                    identity_id: identity.id.clone(),
+                    identity: stored_identity(&identity)?,
+                    refresh_token: rotated.or(session.refresh_token),
+                    access_expires_at: expires_in.map(|secs| Utc::now() + seconds(secs)),
+                    ..session
+                };
+                storage
+                    .put_oauth_session(&refreshed)
+                    .await
+                    .map_err(storage_error)?;
+                Ok(identity)
+            }
+            Err(e) => {
+                // Another replica may have refreshed (and rotated) the token first
+                if let Ok(Some(current)) = storage.find_oauth_session(&key).await {
+                    if current.refresh_token != session.refresh_token
+                        && current
+                            .access_expires_at
+                            .is_some_and(|expires_at| expires_at > Utc::now())
+                    {
+                        return load_identity(&current);
+                    }
+                }
+                tracing::info!(
+                    user_id = %session.identity_id,
+                    error = %e,
+                    "OAuth session refresh failed, ending session"
+                );
+                let _ = storage.delete_oauth_session(&key).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Remove sessions past their lifetime
+    pub fn cleanup_expired(&self) {
+        let now = Instant::now();
+        self.sessions.retain(|_, session| session.expires_at > now);
+    }
+
+    /// `Set-Cookie` value carrying a session token
+    pub fn session_cookie(&self, token: &str) -> String {
+        self.cookie(token, self.config.ttl_secs)
+    }
+
+    /// `Set-Cookie` value clearing the session cookie
+    pub fn clear_cookie(&self) -> String {
+        self.cookie("", 0)
+    }
+
+    fn cookie(&self, value: &str, max_age: u64) -> String {
+        let mut cookie = format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}",
+            self.config.cookie_name, value, max_age
+        );
+        if self.config.cookie_secure {
+            cookie.push_str("; Secure");
+        }
+        cookie
+    }
+
+    /// Session token from the request's `Cookie` header, if present
+    pub fn token_from_cookies<'a>(&self, headers: &'a axum::http::HeaderMap) -> Option<&'a str> {
+        headers
+            .get_all(axum::http::header::COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == self.config.cookie_name)
+            .map(|(_, value)| value)
+            .filter(|value| Self::is_session_token(value))
+    }
+}
+
+/// Exchange the refresh token and re-resolve the identity from the new
+/// access token so scope changes at the provider take effect
+async fn refresh_session(
+    state: &mut SessionState,
+    refresh_token: &str,
+    provider: &OAuthAuthProvider,
+) -> Result<(), AuthError> {
+    let (identity, expires_in, rotated) = refresh_tokens(refresh_token, provider).await?;
+
+    state.identity = identity;
+    state.access_expires_at = expires_in.map(|secs| Instant::now() + Duration::from_secs(secs));
+    if let Some(rotated) = rotated {
+        state.refresh_token = Some(rotated);
+    }
+    Ok(())
+}
+
+/// Refresh grant plus identity lookup; returns the identity, the new access
+/// token lifetime and the rotated refresh token, if any
+async fn refresh_tokens(
+    refresh_token: &str,
+    provider: &OAuthAuthProvider,
+) -> Result<(Identity, Option<u64>, Option<String>), AuthError> {
+    let tokens = provider.refresh_access_token(refresh_token).await?;
+    let identity = provider.authenticate(&tokens.access_token).await?;
+
+    tracing::debug!(user_id = %identity.id, "Refreshed OAuth session access token");
+    Ok((identity, tokens.expires_in, tokens.refresh_token))
+}
+
+fn stored_identity(identity: &Identity) -> Result<serde_json::Value, AuthError> {
+    serde_json::to_value(StoredIdentity::from(identity))
+        .map_err(|e| AuthError::Internal(format!("Failed to store session identity: {}", e)))
+}
+
+fn load_identity(session: &DbOAuthSession) -> Result<Identity, AuthError> {
+    serde_json::from_value::<StoredIdentity>(session.identity.clone())
+        .map(Identity::from)
+        .map_err(|e| AuthError::Internal(format!("Corrupt session identity: {}", e)))
+}
+
+fn storage_error(e: sqlx::Error) -> AuthError {
+    AuthError::Internal(format!("Session storage error: {}", e))
+}
+
+/// Lifetime as a chrono duration, capped at 100 years so adding it to the
+/// current time cannot overflow
+fn seconds(secs: u64) -> chrono::Duration {
+    const MAX_SECS: u64 = 100 * 365 * 24 * 60 * 60;
+    chrono::Duration::seconds(secs.min(MAX_SECS) as i64)
+}
+
+/// Generate a session token from the OS CSPRNG
+fn new_session_token() -> String {
+    use base64::Engine;
+    use rand::rngs::OsRng;
+    use rand::RngCore;
+
+    let mut bytes = [0u8; SESSION_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    format!(
+        "{}{}",
+        SESSION_TOKEN_PREFIX,
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    )
+}
+
+/// Hash a session token for use as the store key (don't store raw tokens)
+fn hash_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    base64::Engine::encode(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        hasher.finalize(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OAuthConfig, OAuthProvider};
+    use std::collections::HashMap;
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn test_identity() -> Identity {
+        Identity {
+            id: "user-1".to_string(),
+            name: None,
+            allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims: HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
+        }
+    }
+
+    fn test_provider(base: &str) -> OAuthAuthProvider {
+        OAuthAuthProvider::new(OAuthConfig {
+            provider: OAuthProvider::Custom,
+            client_id: "client".to_string(),
+            client_secret: Some("secret".to_string()),
+            authorization_url: Some(format!("{}/authorize", base)),
+            token_url: Some(format!("{}/token", base)),
+            introspection_url: None,
+            userinfo_url: Some(format!("{}/userinfo", base)),
+            redirect_uri: "http://localhost:3000/oauth/callback".to_string(),
+            scopes: vec![],
+            user_id_claim: "sub".to_string(),
+            scope_tool_mapping: HashMap::new(),
+            token_cache_ttl_secs: 0,
+            session: None,
+            registration: None,
+            device_authorization_url: None,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_session_resolves_without_refresh() {
+        let store = SessionStore::new(OAuthSessionConfig::default());
+        let provider = test_provider("http://127.0.0.1:1");
+        let token = store
+            .create(test_identity(), Some(3600), Some("rt".into()))
+            .await
+            .unwrap();
+
+        assert!(SessionStore::is_session_token(&token));
+        let identity = store.resolve(&token, &provider).await.unwrap();
+        assert_eq!(identity.id, "user-1");
+
+        assert!(store.resolve("mcpg_sess_unknown", &provider).await.is_err());
+        assert!(store.revoke(&token).await);
+        assert!(store.resolve(&token, &provider).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_refreshes_near_expiry() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=refresh_token"))
+            .and(body_string_contains("refresh_token=rt-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "at-2",
+                "expires_in": 3600,
+                "refresh_token": "rt-2"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/userinfo"))
+            .and(header("authorization", "Bearer at-2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"sub": "user-1"})),
+            )
+            .mount(&server)
+            .await;
+
+        let store = SessionStore::new(OAuthSessionConfig::default());
+        let provider = test_provider(&server.uri());
+        // Access token expires inside the refresh window
+        let token = store
+            .create(test_identity(), Some(10), Some("rt-1".into()))
+            .await
+            .unwrap();
+
+        let identity = store.resolve(&token, &provider).await.unwrap();
+        assert_eq!(identity.id, "user-1");
+        // Second call uses the refreshed token without another grant
+        assert!(store.resolve(&token, &provider).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_ends_session() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(400)
+                    .set_body_json(serde_json::json!({"error": "invalid_grant"})),
+            )
+            .mount(&server)
+            .await;
+
+        let store = SessionStore::new(OAuthSessionConfig::default());
+        let provider = test_provider(&server.uri());
+        let token = store
+            .create(test_identity(), Some(0), Some("rt".into()))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            store.resolve(&token, &provider).await,
+            Err(AuthError::TokenExpired)
+        ));
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_session_capacity_and_cookies() {
+        let store = SessionStore::new(OAuthSessionConfig {
+            max_sessions: 1,
+            ..Default::default()
+        });
+        let token = store.create(test_identity(), None, None).await.unwrap();
+        assert!(store.create(test_identity(), None, None).await.is_err());
+
+        let cookie = store.session_cookie(&token);
+        assert!(cookie.starts_with(&format!("mcp_guard_session={}", token)));
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.contains("Secure"));
+        assert!(store.clear_cookie().contains("Max-Age=0"));
+
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(
+            axum::http::header::COOKIE,
+            format!("theme=dark; mcp_guard_session={}", token)
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(store.token_from_cookies(&headers), Some(token.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_revoke_identity_ends_all_sessions() {
+        let store = SessionStore::new(OAuthSessionConfig::default());
+        store.create(test_identity(), None, None).await.unwrap();
+        store.create(test_identity(), None, None).await.unwrap();
+
+        assert_eq!(store.revoke_identity("someone-else").await, 0);
+        assert_eq!(store.revoke_identity(&test_identity().id).await, 2);
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shared_sessions_resolve_on_any_replica() {
+        let db = crate::db::Database::new("sqlite::memory:").await.unwrap();
+        let replica_a = SessionStore::new(OAuthSessionConfig::default()).with_storage(db.storage());
+        let replica_b = SessionStore::new(OAuthSessionConfig::default()).with_storage(db.storage());
+        let provider = test_provider("http://127.0.0.1:1");
+
+        let mut identity = test_identity();
+        identity.allowed_tools = Some(vec!["read_file".to_string()]);
+        identity.tenant = Some("acme".to_string());
+        let token = replica_a
+            .create(identity, Some(3600), Some("rt".into()))
+            .await
+            .unwrap();
+        assert!(replica_a.is_empty());
+
+        let resolved = replica_b.resolve(&token, &provider).await.unwrap();
+        assert_eq!(resolved.id, "user-1");
+        assert_eq!(resolved.allowed_tools, Some(vec!["read_file".to_string()]));
+        assert_eq!(resolved.tenant.as_deref(), Some("acme"));
+
+        assert!(replica_b.revoke(&token).await);
+        assert!(replica_a.resolve(&token, &provider).await.is_err());
+
+        replica_a.create(test_identity(), None, None).await.unwrap();
+        assert_eq!(replica_b.revoke_identity("user-1").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_shared_session_refresh_is_visible_to_other_replicas() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("refresh_token=rt-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "at-2",
+                "expires_in": 3600,
+                "refresh_token": "rt-2"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/userinfo"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"sub": "user-1"})),
+            )
+            .mount(&server)
+            .await;
+
+        let db = crate::db::Database::new("sqlite::memory:").await.unwrap();
+        let replica_a = SessionStore::new(OAuthSessionConfig::default()).with_storage(db.storage());
+        let replica_b = SessionStore::new(OAuthSessionConfig::default()).with_storage(db.storage());
+        let provider = test_provider(&server.uri());
+
+        let token = replica_a
+            .create(test_identity(), Some(10), Some("rt-1".into()))
+            .await
+            .unwrap();
+        replica_a.resolve(&token, &provider).await.unwrap();
+        // Replica B sees the refreshed session and does not refresh again
+        replica_b.resolve(&token, &provider).await.unwrap();
+    }
+}
@@ -0,0 +1,455 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! SPIFFE workload identities for mTLS
+//!
+//! Service meshes (Istio, Linkerd, SPIRE) issue X.509 SVIDs whose URI SAN is a
+//! SPIFFE ID such as `spiffe://prod.example.com/ns/payments/sa/api`. The proxy
+//! in front of mcp-guard forwards that ID, and optionally the certificate
+//! itself, which is then checked against a trust bundle of CA certificates.
+//!
+//! The bundle is reloaded in the background so CA rotation needs no restart.
+
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use base64::Engine;
+use tokio_util::sync::CancellationToken;
+use x509_parser::pem::Pem;
+use x509_parser::prelude::{parse_x509_certificate, GeneralName, X509Certificate};
+
+use crate::auth::AuthError;
+use crate::config::SpiffeConfig;
+
+/// HTTP request timeout for SPIFFE bundle endpoint calls
+const BUNDLE_HTTP_TIMEOUT_SECS: u64 = 10;
+
+/// A parsed SPIFFE ID (`spiffe://<trust domain>/<path>`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpiffeId {
+    id: String,
+    trust_domain_len: usize,
+}
+
+impl SpiffeId {
+    /// Parse a SPIFFE ID, following the SPIFFE-ID specification: lowercase
+    /// trust domain, no port, userinfo, query or fragment, and no empty, `.`
+    /// or `..` path segments
+    pub fn parse(id: &str) -> Option<Self> {
+        let rest = id.strip_prefix("spiffe://")?;
+        let (trust_domain, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, ""),
+        };
+        if !Self::is_valid_trust_domain(trust_domain) {
+            return None;
+        }
+        if !path.is_empty() {
+            for segment in path[1..].split('/') {
+                let valid_chars = segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+                if segment.is_empty() || segment == "." || segment == ".." || !valid_chars {
+                    return None;
+                }
+            }
+        }
+        Some(Self {
+            id: id.to_string(),
+            trust_domain_len: trust_domain.len(),
+        })
+    }
+
+    /// Whether a trust domain name is valid (lowercase letters, digits, `.`,
+    /// `-` and `_`)
+    pub fn is_valid_trust_domain(trust_domain: &str) -> bool {
+        !trust_domain.is_empty()
+            && trust_domain.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '-' | '_')
+            })
+    }
+
+    /// The trust domain (e.g. `prod.example.com`)
+    pub fn trust_domain(&self) -> &str {
+        let start = "spiffe://".len();
+        &self.id[start..start + self.trust_domain_len]
+    }
+
+    /// The workload path, including the leading `/` (may be empty)
+    pub fn path(&self) -> &str {
+        &self.id["spiffe://".len() + self.trust_domain_len..]
+    }
+
+    /// The full ID
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+}
+
+impl std::fmt::Display for SpiffeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+/// Where the trust bundle is loaded from
+enum BundleSource {
+    /// PEM file of CA certificates
+    File(PathBuf),
+    /// SPIFFE bundle endpoint (JWKS document with `x5c` keys)
+    Http {
+        url: String,
+        client: reqwest::Client,
+    },
+}
+
+/// CA certificates that SVIDs must chain to, reloaded at runtime
+pub struct SpiffeTrustBundle {
+    source: BundleSource,
+    refresh_interval: Duration,
+    /// DER-encoded CA certificates; empty until the first successful load
+    cas: RwLock<Arc<Vec<Vec<u8>>>>,
+}
+
+impl SpiffeTrustBundle {
+    /// Create a bundle from configuration, or `None` if no bundle source is set
+    pub fn new(config: &SpiffeConfig) -> Option<Self> {
+        let source = match (&config.trust_bundle_path, &config.trust_bundle_url) {
+            (Some(path), _) => BundleSource::File(path.clone()),
+            (None, Some(url)) => BundleSource::Http {
+                url: url.clone(),
+                client: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(BUNDLE_HTTP_TIMEOUT_SECS))
+                    .build()
+                    .unwrap_or_else(|_| reqwest::Client::new()),
+            },
+            (None, None) => return None,
+        };
+        Some(Self {
+            source,
+            refresh_interval: Duration::from_secs(config.refresh_interval_secs),
+            cas: RwLock::new(Arc::new(Vec::new())),
+        })
+    }
+
+    /// Reload the bundle from its source, returning the number of CAs
+    ///
+    /// On failure the previously loaded bundle stays in effect.
+    pub async fn refresh(&self) -> Result<usize, AuthError> {
+        let cas = match &self.source {
+            BundleSource::File(path) => {
+                let bytes = tokio::fs::read(path).await.map_err(|e| {
+                    AuthError::Internal(format!(
+                        "Failed to read SPIFFE trust bundle {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                parse_pem_bundle(&bytes)?
+            }
+            BundleSource::Http { url, client } => {
+                let response = client.get(url).send().await.map_err(|e| {
+                    AuthError::Internal(format!("SPIFFE bundle fetch failed: {}", e))
+                })?;
+                if !response.status().is_success() {
+                    return Err(AuthError::Internal(format!(
+                        "SPIFFE bundle endpoint returned {}",
+                        response.status()
+                    )));
+                }
+                let document: serde_json::Value = response.json().await.map_err(|e| {
+                    AuthError::Internal(format!("SPIFFE bundle parse failed: {}", e))
+                })?;
+                parse_jwks_bundle(&document)?
+            }
+        };
+
+        let count = cas.len();
+        *self.cas.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(cas);
+        tracing::debug!(cas = count, "SPIFFE trust bundle refreshed");
+        Ok(count)
+    }
+
+    /// Reload the bundle now and then every `refresh_interval_secs`
+    ///
+    /// The task runs until the cancellation token is triggered.
+    pub fn start_background_refresh(self: &Arc<Self>, cancel_token: CancellationToken) {
+        let bundle = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = bundle.refresh().await {
+                    tracing::warn!(error = %e, "SPIFFE trust bundle refresh failed");
+                }
+                tokio::select! {
+                    _ = cancel_token.cancelled() => {
+                        tracing::debug!("SPIFFE trust bundle refresh task shutting down");
+                        break;
+                    }
+                    _ = tokio::time::sleep(bundle.refresh_interval) => {}
+                }
+            }
+        });
+    }
+
+    /// Check that a PEM certificate is a currently valid SVID for `id`,
+    /// signed by a CA in the bundle
+    ///
+    /// SECURITY: Fails closed while the bundle has never loaded.
+    pub fn verify(&self, certificate_pem: &str, id: &SpiffeId) -> Result<(), AuthError> {
+        let cas = Arc::clone(&self.cas.read().unwrap_or_else(|e| e.into_inner()));
+        if cas.is_empty() {
+            return Err(AuthError::InvalidClientCert(
+                "SPIFFE trust bundle is not loaded".into(),
+            ));
+        }
+
+        let der = parse_pem_bundle(certificate_pem.as_bytes())?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AuthError::InvalidClientCert("No certificate forwarded".into()))?;
+        let (_, leaf) = parse_x509_certificate(&der)
+            .map_err(|e| AuthError::InvalidClientCert(format!("Unparseable certificate: {}", e)))?;
+
+        if !leaf.validity().is_valid() {
+            return Err(AuthError::InvalidClientCert(
+                "Certificate is expired or not yet valid".into(),
+            ));
+        }
+        // The forwarded ID must be the one the certificate was issued for
+        if !uri_sans(&leaf).iter().any(|uri| *uri == id.as_str()) {
+            return Err(AuthError::InvalidClientCert(format!(
+                "Certificate URI SAN does not contain {}",
+                id
+            )));
+        }
+
+        let signed_by_bundle = cas.iter().any(|ca_der| {
+            parse_x509_certificate(ca_der).is_ok_and(|(_, ca)| {
+                ca.subject() == leaf.issuer()
+                    && leaf.verify_signature(Some(ca.public_key())).is_ok()
+            })
+        });
+        if !signed_by_bundle {
+            return Err(AuthError::InvalidClientCert(
+                "Certificate is not signed by the SPIFFE trust bundle".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// URI SANs of a certificate
+fn uri_sans<'a>(cert: &'a X509Certificate<'a>) -> Vec<&'a str> {
+    let Ok(Some(san)) = cert.subject_alternative_name() else {
+        return Vec::new();
+    };
+    san.value
+        .general_names
+        .iter()
+        .filter_map(|name| match name {
+            GeneralName::URI(uri) => Some(*uri),
+            _ => None,
+        })
+        .collect()
+}
+
+/// DER certificates from a PEM document
+fn parse_pem_bundle(bytes: &[u8]) -> Result<Vec<Vec<u8>>, AuthError> {
+    let mut certs = Vec::new();
+    for pem in Pem::iter_from_buffer(bytes) {
+        let pem = pem.map_err(|e| AuthError::InvalidClientCert(format!("Invalid PEM: {}", e)))?;
+        if pem.label == "CERTIFICATE" {
+            certs.push(pem.contents);
+        }
+    }
+    if certs.is_empty() {
+        return Err(AuthError::InvalidClientCert(
+            "No certificates in PEM data".into(),
+        ));
+    }
+    Ok(certs)
+}
+
+/// DER certificates from a SPIFFE bundle endpoint document
+///
+/// X.509 authorities are JWKS keys with `"use": "x509-svid"` and the CA
+/// certificate as the single `x5c` entry.
+fn parse_jwks_bundle(document: &serde_json::Value) -> Result<Vec<Vec<u8>>, AuthError> {
+    let certs: Vec<Vec<u8>> = document["keys"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|key| key["use"] == "x509-svid")
+        .filter_map(|key| key["x5c"][0].as_str())
+        .filter_map(|x5c| base64::engine::general_purpose::STANDARD.decode(x5c).ok())
+        .collect();
+    if certs.is_empty() {
+        return Err(AuthError::Internal(
+            "SPIFFE bundle has no x509-svid authorities".into(),
+        ));
+    }
+    Ok(certs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trust bundle CA for example.org (test only)
+    const CA_PEM: &str = "-----BEGIN CERTIFICATE-----\n\
+MIIBuzCCAWGgAwIBAgIUNxqTK1E1AZ2cD/p0AphILX+PaegwCgYIKoZIzj0EAwIw\n\
+KjEPMA0GA1UECgwGU1BJRkZFMRcwFQYDVQQDDA5leGFtcGxlLm9yZyBDQTAgFw0y\n\
+NTAxMDEwMDAwMDBaGA8yMTI1MDEwMTAwMDAwMFowKjEPMA0GA1UECgwGU1BJRkZF\n\
+MRcwFQYDVQQDDA5leGFtcGxlLm9yZyBDQTBZMBMGByqGSM49AgEGCCqGSM49AwEH\n\
+A0IABGUyieIF15TaC3gEoikZRF4J5sgFCYYR59Ztva3ij+iAknlye3JwQ7+ZxskJ\n\
+07/4xuIoJUkkJ3g1BYnJo5P/LrajYzBhMB0GA1UdDgQWBBQbEriFNpbS3+scJ7dS\n\
+RaxaOIX4jTAfBgNVHSMEGDAWgBQbEriFNpbS3+scJ7dSRaxaOIX4jTAPBgNVHRMB\n\
+Af8EBTADAQH/MA4GA1UdDwEB/wQEAwIBBjAKBggqhkjOPQQDAgNIADBFAiEAkM36\n\
+oyOuQM+NnRh2OMYxNNiXzOEKt0V/o2VvSJa1tekCIA1OASU8c2G+4G+Ykv55xznc\n\
+SbTQnwIYqaKrCmbNjtah\n\
+-----END CERTIFICATE-----\n";
+
+    /// X.509-SVID for spiffe://example.org/ns/payments/sa/api, issued by `CA_PEM`
+    const SVID_PEM: &str = "-----BEGIN CERTIFICATE-----\n\
+MIIB1jCCAXugAwIBAgIUIvG9E8AbGzkmtA4CrU9ehTjya+8wCgYIKoZIzj0EAwIw\n\
+KjEPMA0GA1UECgwGU1BJRkZFMRcwFQYDVQQDDA5leGFtcGxlLm9yZyBDQTAgFw0y\n\
+NTAxMDEwMDAwMDBaGA8yMTI1MDEwMTAwMDAwMFowETEPMA0GA1UECgwGU1BJRkZF\n\
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEUQcjRLWBtR64gfBGJ+a+xncDMqjX\n\
+I3Nuco4NLwzylG0zOTiZjjjBQmvuObbvVeN55bQRgAvzKD4Yo5SSNntoOqOBlTCB\n\
+kjAMBgNVHRMBAf8EAjAAMA4GA1UdDwEB/wQEAwIHgDAyBgNVHREEKzAphidzcGlm\n\
+ZmU6Ly9leGFtcGxlLm9yZy9ucy9wYXltZW50cy9zYS9hcGkwHQYDVR0OBBYEFLRd\n\
+6x1QE1mo+wbcpoEfB9SqarV5MB8GA1UdIwQYMBaAFBsSuIU2ltLf6xwnt1JFrFo4\n\
+hfiNMAoGCCqGSM49BAMCA0kAMEYCIQCw77SzbERoI6INjXhSnJlMUX2kZJWw1asH\n\
+ehmtMqYkUAIhAJ0mrG6HS5T8iBE+WNLszzqTF4JOmofa+LxmVmPqrzUh\n\
+-----END CERTIFICATE-----\n";
+
+    /// A different CA with the same subject as `CA_PEM`
+    const OTHER_CA_PEM: &str = "-----BEGIN CERTIFICATE-----\n\
+MIIBqzCCAVGgAwIBAgIUbAnPjSA/xR2OwoS36X8DFGqgRQQwCgYIKoZIzj0EAwIw\n\
+KjEPMA0GA1UECgwGU1BJRkZFMRcwFQYDVQQDDA5leGFtcGxlLm9yZyBDQTAgFw0y\n\
+NTAxMDEwMDAwMDBaGA8yMTI1MDEwMTAwMDAwMFowKjEPMA0GA1UECgwGU1BJRkZF\n\
+MRcwFQYDVQQDDA5leGFtcGxlLm9yZyBDQTBZMBMGByqGSM49AgEGCCqGSM49AwEH\n\
+A0IABPW1PfE4l618lOFwJ9rvTzCnjACJTzZ4n+rDDLd4Png3js2VTJaxxXzGidcS\n\
+IMJ6rurxVr4pcEWPUPtKtjrjibqjUzBRMB0GA1UdDgQWBBRuXhc9OmJaUuo+TAnT\n\
+7PMMXOSRMjAfBgNVHSMEGDAWgBRuXhc9OmJaUuo+TAnT7PMMXOSRMjAPBgNVHRMB\n\
+Af8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCIQCbh6a6n7PhOVdEWQHQfwqRNLhn\n\
+r/0+HwOJOP+Hf/orhQIgMN4pwVwY0SSSjs0PO0ETNo/tMBBlNUXjejXQQ3/CBoU=\n\
+-----END CERTIFICATE-----\n";
+
+    #[test]
+    fn test_spiffe_id_parse() {
+        let id = SpiffeId::parse("spiffe://prod.example.com/ns/payments/sa/api").unwrap();
+        assert_eq!(id.trust_domain(), "prod.example.com");
+        assert_eq!(id.path(), "/ns/payments/sa/api");
+        assert_eq!(
+            id.to_string(),
+            "spiffe://prod.example.com/ns/payments/sa/api"
+        );
+
+        let id = SpiffeId::parse("spiffe://example.org").unwrap();
+        assert_eq!(id.trust_domain(), "example.org");
+        assert_eq!(id.path(), "");
+    }
+
+    #[test]
+    fn test_spiffe_id_rejects_invalid() {
+        for invalid in [
+            "https://example.org/workload",
+            "spiffe://",
+            "spiffe://Example.org/workload",
+            "spiffe://example.org:8443/workload",
+            "spiffe://user@example.org/workload",
+            "spiffe://example.org/",
+            "spiffe://example.org//workload",
+            "spiffe://example.org/../admin",
+            "spiffe://example.org/workload?x=1",
+            "spiffe://example.org/workload#frag",
+        ] {
+            assert!(SpiffeId::parse(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_jwks_bundle_parse() {
+        let document = serde_json::json!({
+            "keys": [
+                {"use": "x509-svid", "kty": "EC", "x5c": ["AQID"]},
+                {"use": "jwt-svid", "kty": "EC", "kid": "k1"}
+            ],
+            "spiffe_sequence": 1
+        });
+        let certs = parse_jwks_bundle(&document).unwrap();
+        assert_eq!(certs, vec![vec![1, 2, 3]]);
+
+        assert!(parse_jwks_bundle(&serde_json::json!({"keys": []})).is_err());
+    }
+
+    #[test]
+    fn test_pem_bundle_requires_certificates() {
+        assert!(parse_pem_bundle(b"not pem").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_fails_closed_before_load() {
+        let bundle = SpiffeTrustBundle::new(&SpiffeConfig {
+            trust_domains: vec!["example.org".to_string()],
+            trust_bundle_path: Some(PathBuf::from("/nonexistent/bundle.pem")),
+            trust_bundle_url: None,
+            refresh_interval_secs: 300,
+            identities: vec![],
+        })
+        .unwrap();
+
+        assert!(bundle.refresh().await.is_err());
+        let id = SpiffeId::parse("spiffe://example.org/workload").unwrap();
+        let err = bundle.verify("", &id).unwrap_err();
+        assert!(err.to_string().contains("not loaded"));
+    }
+
+    async fn loaded_bundle(ca_pem: &str) -> (SpiffeTrustBundle, tempfile::NamedTempFile) {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), ca_pem).unwrap();
+        let bundle = SpiffeTrustBundle::new(&SpiffeConfig {
+            trust_domains: vec!["example.org".to_string()],
+            trust_bundle_path: Some(file.path().to_path_buf()),
+            trust_bundle_url: None,
+            refresh_interval_secs: 300,
+            identities: vec![],
+        })
+        .unwrap();
+        assert_eq!(bundle.refresh().await.unwrap(), 1);
+        (bundle, file)
+    }
+
+    #[tokio::test]
+    async fn test_verify_svid() {
+        let (bundle, _file) = loaded_bundle(CA_PEM).await;
+        let id = SpiffeId::parse("spiffe://example.org/ns/payments/sa/api").unwrap();
+        bundle.verify(SVID_PEM, &id).unwrap();
+
+        // The certificate was issued for another workload
+        let other = SpiffeId::parse("spiffe://example.org/ns/payments/sa/admin").unwrap();
+        let err = bundle.verify(SVID_PEM, &other).unwrap_err();
+        assert!(err.to_string().contains("URI SAN"));
+
+        // Same issuer name, different key
+        let (bundle, _file) = loaded_bundle(OTHER_CA_PEM).await;
+        let err = bundle.verify(SVID_PEM, &id).unwrap_err();
+        assert!(err.to_string().contains("not signed"));
+    }
+}
@@ -0,0 +1,394 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Tool-call argument constraints
+//!
+//! An API key can narrow what a tool may be called with, not just which tools
+//! it may call. Each `[[auth.api_keys.constraints]]` entry names a tool (glob),
+//! an argument path and the values it accepts:
+//!
+//! ```toml
+//! [[auth.api_keys.constraints]]
+//! tool = "read_file"
+//! argument = "path"
+//! allow = ["/srv/data/**"]
+//!
+//! [[auth.api_keys.constraints]]
+//! tool = "execute_sql"
+//! argument = "query"
+//! pattern = "(?i)^\\s*select\\b"
+//! deny_pattern = ";"
+//! ```
+//!
+//! A call is allowed only if every constraint whose tool matches is satisfied.
+//! A missing argument fails the constraint. `allow` globs are matched against
+//! the lexically normalized value, so `/srv/data/../etc/passwd` does not match
+//! `/srv/data/**`.
+
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::config::ToolArgumentConstraint;
+
+/// Argument constraint setup error
+#[derive(Debug, thiserror::Error)]
+pub enum ArgumentConstraintError {
+    #[error("invalid tool pattern '{pattern}': {source}")]
+    ToolPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+
+    #[error("invalid allow pattern '{pattern}': {source}")]
+    AllowPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+
+    #[error("invalid regex '{pattern}': {source}")]
+    Regex {
+        pattern: String,
+        source: regex::Error,
+    },
+
+    #[error(
+        "constraint on '{0}' must set argument and at least one of allow, pattern or deny_pattern"
+    )]
+    Empty(String),
+}
+
+/// A constraint that a tool call failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolation {
+    /// 1-based position of the constraint in the key's list; `None` when
+    /// the constraints failed to compile
+    pub position: Option<usize>,
+    /// The constraint as `tool:argument`, e.g. `read_file:path`
+    pub constraint: Option<String>,
+    /// Why the call failed it
+    pub reason: String,
+}
+
+/// `*` stays within a path segment, `**` crosses them
+const ALLOW_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+#[derive(Debug)]
+struct CompiledConstraint {
+    tool: Pattern,
+    argument: Vec<String>,
+    allow: Vec<Pattern>,
+    pattern: Option<Regex>,
+    deny_pattern: Option<Regex>,
+}
+
+/// Compiled argument constraints of one identity
+#[derive(Debug, Default)]
+pub struct ArgumentConstraints {
+    rules: Vec<CompiledConstraint>,
+    deny_all: bool,
+}
+
+impl ArgumentConstraints {
+    /// Compile configured constraints
+    pub fn new(configs: &[ToolArgumentConstraint]) -> Result<Self, ArgumentConstraintError> {
+        let rules = configs.iter().map(compile).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            rules,
+            deny_all: false,
+        })
+    }
+
+    /// Constraints that reject every tool call
+    ///
+    /// Used when a key's constraints fail to compile, so a bad pattern can
+    /// never widen access.
+    pub fn deny_all() -> Self {
+        Self {
+            rules: Vec::new(),
+            deny_all: true,
+        }
+    }
+
+    /// Whether no constraints are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && !self.deny_all
+    }
+
+    /// Check a `tools/call` against the constraints
+    ///
+    /// Returns the reason for the first failed constraint.
+    pub fn check(&self, tool: &str, arguments: Option<&Value>) -> Result<(), String> {
+        self.evaluate(tool, arguments)
+            .map_err(|violation| violation.reason)
+    }
+
+    /// Check a `tools/call` against the constraints, in configured order
+    ///
+    /// Returns the first constraint the call fails.
+    pub fn evaluate(
+        &self,
+        tool: &str,
+        arguments: Option<&Value>,
+    ) -> Result<(), ConstraintViolation> {
+        if self.deny_all {
+            return Err(ConstraintViolation {
+                position: None,
+                constraint: None,
+                reason: "argument constraints for this key are invalid".to_string(),
+            });
+        }
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !rule.tool.matches(tool) {
+                continue;
+            }
+            let path = rule.argument.join(".");
+            let violation = |reason: String| ConstraintViolation {
+                position: Some(index + 1),
+                constraint: Some(format!("{}:{}", rule.tool.as_str(), path)),
+                reason,
+            };
+            let value = arguments.and_then(|args| lookup(args, &rule.argument));
+            let Some(value) = value else {
+                return Err(violation(format!("argument '{}' is required", path)));
+            };
+
+            let values: Vec<&Value> = match value {
+                Value::Array(items) => items.iter().collect(),
+                other => vec![other],
+            };
+            for value in values {
+                let text = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Number(_) | Value::Bool(_) => value.to_string(),
+                    _ => {
+                        return Err(violation(format!(
+                            "argument '{}' must be a scalar value",
+                            path
+                        )))
+                    }
+                };
+                rule.check_value(&text)
+                    .map_err(|reason| violation(format!("argument '{}' {}", path, reason)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CompiledConstraint {
+    fn check_value(&self, value: &str) -> Result<(), &'static str> {
+        if !self.allow.is_empty() {
+            let normalized = normalize_path(value).ok_or("escapes its root")?;
+            if !self
+                .allow
+                .iter()
+                .any(|p| p.matches_with(&normalized, ALLOW_MATCH_OPTIONS))
+            {
+                return Err("is not in the allowed values");
+            }
+        }
+        if let Some(ref pattern) = self.pattern {
+            if !pattern.is_match(value) {
+                return Err("does not match the required pattern");
+            }
+        }
+        if let Some(ref deny) = self.deny_pattern {
+            if deny.is_match(value) {
+                return Err("matches a denied pattern");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn compile(config: &ToolArgumentConstraint) -> Result<CompiledConstraint, ArgumentConstraintError> {
+    if config.argument.trim().is_empty()
+        || (config.allow.is_empty() && config.pattern.is_none() && config.deny_pattern.is_none())
+    {
+        return Err(ArgumentConstraintError::Empty(config.tool.clone()));
+    }
+
+    let tool =
+        Pattern::new(&config.tool).map_err(|source| ArgumentConstraintError::ToolPattern {
+            pattern: config.tool.clone(),
+            source,
+        })?;
+    let allow = config
+        .allow
+        .iter()
+        .map(|p| {
+            Pattern::new(p).map_err(|source| ArgumentConstraintError::AllowPattern {
+                pattern: p.clone(),
+                source,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let regex = |p: &Option<String>| {
+        p.as_deref()
+            .map(|p| {
+                Regex::new(p).map_err(|source| ArgumentConstraintError::Regex {
+                    pattern: p.to_string(),
+                    source,
+                })
+            })
+            .transpose()
+    };
+
+    Ok(CompiledConstraint {
+        tool,
+        argument: config.argument.split('.').map(str::to_string).collect(),
+        allow,
+        pattern: regex(&config.pattern)?,
+        deny_pattern: regex(&config.deny_pattern)?,
+    })
+}
+
+fn lookup<'a>(arguments: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(arguments, |value, key| value.get(key.as_str()))
+        .filter(|value| !value.is_null())
+}
+
+/// Resolve `.` and `..` segments without touching the filesystem
+///
+/// Returns `None` if `..` climbs above the start of the value.
+fn normalize_path(value: &str) -> Option<String> {
+    let absolute = value.starts_with('/');
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in value.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            s => segments.push(s),
+        }
+    }
+    let joined = segments.join("/");
+    Some(if absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn constraint(tool: &str, argument: &str) -> ToolArgumentConstraint {
+        ToolArgumentConstraint {
+            tool: tool.to_string(),
+            argument: argument.to_string(),
+            allow: vec![],
+            pattern: None,
+            deny_pattern: None,
+        }
+    }
+
+    #[test]
+    fn test_allow_globs_with_normalization() {
+        let mut read = constraint("read_file", "path");
+        read.allow = vec!["/srv/data/**".to_string()];
+        let constraints = ArgumentConstraints::new(&[read]).unwrap();
+
+        let ok = json!({"path": "/srv/data/reports/q3.csv"});
+        assert!(constraints.check("read_file", Some(&ok)).is_ok());
+
+        for path in ["/etc/passwd", "/srv/data/../../etc/passwd", "/srv/database"] {
+            let args = json!({ "path": path });
+            assert!(
+                constraints.check("read_file", Some(&args)).is_err(),
+                "{}",
+                path
+            );
+        }
+
+        // Missing arguments fail, other tools are unaffected
+        assert!(constraints.check("read_file", Some(&json!({}))).is_err());
+        assert!(constraints.check("read_file", None).is_err());
+        assert!(constraints.check("write_file", None).is_ok());
+    }
+
+    #[test]
+    fn test_regex_constraints() {
+        let mut sql = constraint("execute_*", "query.text");
+        sql.pattern = Some(r"(?i)^\s*select\b".to_string());
+        sql.deny_pattern = Some(";".to_string());
+        let constraints = ArgumentConstraints::new(&[sql]).unwrap();
+
+        let select = json!({"query": {"text": "SELECT * FROM users"}});
+        assert!(constraints.check("execute_sql", Some(&select)).is_ok());
+
+        let delete = json!({"query": {"text": "DELETE FROM users"}});
+        assert!(constraints.check("execute_sql", Some(&delete)).is_err());
+
+        let stacked = json!({"query": {"text": "select 1; drop table users"}});
+        assert!(constraints.check("execute_sql", Some(&stacked)).is_err());
+    }
+
+    #[test]
+    fn test_array_values_and_invalid_config() {
+        let mut tags = constraint("tag", "labels");
+        tags.allow = vec!["team-*".to_string()];
+        let constraints = ArgumentConstraints::new(&[tags]).unwrap();
+        assert!(constraints
+            .check("tag", Some(&json!({"labels": ["team-a", "team-b"]})))
+            .is_ok());
+        assert!(constraints
+            .check("tag", Some(&json!({"labels": ["team-a", "admin"]})))
+            .is_err());
+
+        assert!(ArgumentConstraints::new(&[constraint("tag", "labels")]).is_err());
+        let mut bad = constraint("tag", "labels");
+        bad.pattern = Some("(".to_string());
+        assert!(ArgumentConstraints::new(&[bad]).is_err());
+
+        assert!(ArgumentConstraints::deny_all().check("any", None).is_err());
+    }
+
+    #[test]
+    fn test_violation_names_the_failed_constraint() {
+        let mut read = constraint("read_*", "path");
+        read.allow = vec!["/srv/**".to_string()];
+        let mut sql = constraint("execute_sql", "query");
+        sql.deny_pattern = Some(";".to_string());
+        let constraints = ArgumentConstraints::new(&[read, sql]).unwrap();
+
+        let violation = constraints
+            .evaluate("execute_sql", Some(&json!({"query": "select 1; drop"})))
+            .unwrap_err();
+        assert_eq!(violation.position, Some(2));
+        assert_eq!(violation.constraint.as_deref(), Some("execute_sql:query"));
+        assert_eq!(
+            violation.reason,
+            "argument 'query' matches a denied pattern"
+        );
+
+        let violation = ArgumentConstraints::deny_all()
+            .evaluate("any", None)
+            .unwrap_err();
+        assert_eq!(violation.position, None);
+    }
+}
@@ -17,26 +17,108 @@
 // along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
 //! Authorization logic for mcp-guard
 //!
-//! This module implements tool-level authorization for MCP requests.
+//! This module implements tool, resource and prompt authorization for MCP requests.
 //!
 //! Authorization model:
-//! - Each identity can have an optional `allowed_tools` list
-//! - `None` = unrestricted access to all tools
+//! - Each identity can have optional `allowed_tools`, `allowed_resources`
+//!   (resource URIs) and `allowed_prompts` (prompt names) lists
+//! - `None` = unrestricted access
 //! - `Some(["*"])` = wildcard, equivalent to unrestricted
-//! - `Some(["tool1", "tool2"])` = only these specific tools
+//! - `Some(["tool1", "file:///docs/*"])` = only matching names/URIs
 //!
 //! Key functions:
+//! - [`evaluate_request`] - Authorize a request and report the [`AuthzRule`] that decided it
 //! - [`authorize_tool_call`] - Check if identity can call a specific tool
+//! - [`ArgumentConstraints`] - Per-key limits on tool-call arguments
+//! - [`authorize_resource_read`] - Check if identity can read a specific resource
+//! - [`authorize_prompt_get`] - Check if identity can get a specific prompt
 //! - [`filter_tools_list_response`] - Filter `tools/list` to show only authorized tools (FR-AUTHZ-03)
+//! - [`ResponseFilterChain`] - Filter pipeline applied to every upstream response
+
+mod constraints;
+mod filters;
+
+pub use constraints::{ArgumentConstraintError, ArgumentConstraints, ConstraintViolation};
+pub use filters::{
+    FieldRedactionFilter, ListFilter, ResponseFilter, ResponseFilterChain, ResponseFilterError,
+};
+
+use serde::{Deserialize, Serialize};
 
 use crate::auth::Identity;
 use crate::transport::Message;
-use serde_json::Value;
 
 // ============================================================================
 // Authorization Functions
 // ============================================================================
 
+/// Check a name against an allow list
+///
+/// Supports glob patterns:
+/// - `*` - matches everything (wildcard)
+/// - `read_*` - matches names starting with "read_"
+/// - `fs/*` - matches names like "fs/read", "fs/write"
+/// - Exact matches also work
+pub(crate) fn is_allowed(allowed: &Option<Vec<String>>, name: &str) -> bool {
+    match allowed {
+        None => true, // No restrictions
+        Some(patterns) => patterns
+            .iter()
+            .any(|pattern| pattern_matches(pattern, name)),
+    }
+}
+
+/// Check a name against one allow-list entry
+fn pattern_matches(pattern: &str, name: &str) -> bool {
+    // Exact match or wildcard
+    if pattern == name || pattern == "*" {
+        return true;
+    }
+    // Try glob pattern matching for patterns containing wildcards
+    if pattern.contains('*') || pattern.contains('?') || pattern.contains('[') {
+        if let Ok(glob_pattern) = glob::Pattern::new(pattern) {
+            return glob_pattern.matches(name);
+        }
+    }
+    false
+}
+
+/// Check a name against the allow list `rule`, reporting the deciding entry
+///
+/// `Ok(None)` means the list is unset; `Err` carries the rule that denied.
+fn match_rule(
+    rule: &str,
+    allowed: &Option<Vec<String>>,
+    name: &str,
+) -> Result<Option<AuthzRule>, AuthzRule> {
+    let Some(patterns) = allowed else {
+        return Ok(None);
+    };
+    match patterns
+        .iter()
+        .position(|pattern| pattern_matches(pattern, name))
+    {
+        Some(index) => Ok(Some(AuthzRule {
+            rule: rule.to_string(),
+            pattern: Some(patterns[index].clone()),
+            order: Some(index + 1),
+        })),
+        None => Err(AuthzRule {
+            rule: rule.to_string(),
+            pattern: None,
+            order: None,
+        }),
+    }
+}
+
+/// Whether an allow list grants everything (`None` or contains `*`)
+fn is_unrestricted(allowed: &Option<Vec<String>>) -> bool {
+    match allowed {
+        None => true,
+        Some(patterns) => patterns.iter().any(|p| p == "*"),
+    }
+}
+
 /// Check if an identity is authorized to call a specific tool
 ///
 /// Supports glob patterns in allowed_tools:
@@ -45,34 +127,50 @@ use serde_json::Value;
 /// - `fs/*` - matches tools like "fs/read", "fs/write"
 /// - Exact matches also work
 pub fn authorize_tool_call(identity: &Identity, tool_name: &str) -> bool {
-    match &identity.allowed_tools {
-        None => true, // No restrictions
-        Some(tools) => tools.iter().any(|pattern| {
-            // Exact match or wildcard
-            if pattern == tool_name || pattern == "*" {
-                return true;
-            }
-            // Try glob pattern matching for patterns containing wildcards
-            if pattern.contains('*') || pattern.contains('?') || pattern.contains('[') {
-                if let Ok(glob_pattern) = glob::Pattern::new(pattern) {
-                    return glob_pattern.matches(tool_name);
-                }
-            }
-            false
-        }),
-    }
+    is_allowed(&identity.allowed_tools, tool_name)
+}
+
+/// Check if an identity is authorized to read a resource URI
+///
+/// Glob patterns match against the full URI, e.g. `file:///docs/*`.
+pub fn authorize_resource_read(identity: &Identity, uri: &str) -> bool {
+    is_allowed(&identity.allowed_resources, uri)
+}
+
+/// Check if an identity is authorized to get a prompt by name
+pub fn authorize_prompt_get(identity: &Identity, prompt_name: &str) -> bool {
+    is_allowed(&identity.allowed_prompts, prompt_name)
 }
 
 /// Extract tool name from a MCP request message
 pub fn extract_tool_name(message: &Message) -> Option<&str> {
-    if let Some(method) = &message.method {
-        if method == "tools/call" {
-            if let Some(params) = &message.params {
-                return params.get("name").and_then(|v| v.as_str());
-            }
-        }
+    extract_param(message, "tools/call", "name")
+}
+
+/// Extract the resource URI from a `resources/read` request
+pub fn extract_resource_uri(message: &Message) -> Option<&str> {
+    extract_param(message, "resources/read", "uri")
+}
+
+/// Extract the prompt name from a `prompts/get` request
+pub fn extract_prompt_name(message: &Message) -> Option<&str> {
+    extract_param(message, "prompts/get", "name")
+}
+
+/// Extract the tool name, resource URI or prompt name a request targets
+///
+/// Used to label authorization denials in audit logs.
+pub fn extract_authz_target(message: &Message) -> Option<&str> {
+    extract_tool_name(message)
+        .or_else(|| extract_resource_uri(message))
+        .or_else(|| extract_prompt_name(message))
+}
+
+fn extract_param<'a>(message: &'a Message, method: &str, key: &str) -> Option<&'a str> {
+    if message.method.as_deref() != Some(method) {
+        return None;
     }
-    None
+    message.params.as_ref()?.get(key).and_then(|v| v.as_str())
 }
 
 // ============================================================================
@@ -86,19 +184,116 @@ pub enum AuthzDecision {
     Deny(String),
 }
 
+/// The allow-list entry or argument constraint that decided a request
+///
+/// Recorded on `authz_denied` audit entries and reported to admins in the
+/// `x-mcp-guard-authz` response header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthzRule {
+    /// `allowed_tools`, `allowed_resources`, `allowed_prompts`, `constraints`
+    /// or `read_only`
+    pub rule: String,
+    /// Entry that matched, or the failed constraint as `tool:argument`;
+    /// `None` when no allow-list entry matched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// 1-based position of that entry in evaluation order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<usize>,
+}
+
+impl std::fmt::Display for AuthzRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rule={}", self.rule)?;
+        if let Some(ref pattern) = self.pattern {
+            write!(f, "; pattern={:?}", pattern)?;
+        }
+        if let Some(order) = self.order {
+            write!(f, "; order={}", order)?;
+        }
+        Ok(())
+    }
+}
+
 /// Authorize a request based on identity and message
 pub fn authorize_request(identity: &Identity, message: &Message) -> AuthzDecision {
+    evaluate_request(identity, message).0
+}
+
+/// Authorize a request and report the rule that decided it
+///
+/// Denials always carry a rule. Allowed requests carry the allow-list entry
+/// that matched, or `None` when no list restricts the request.
+pub fn evaluate_request(
+    identity: &Identity,
+    message: &Message,
+) -> (AuthzDecision, Option<AuthzRule>) {
+    let mut matched = None;
+
     // Check tool-level authorization for tool calls
     if let Some(tool_name) = extract_tool_name(message) {
-        if !authorize_tool_call(identity, tool_name) {
-            return AuthzDecision::Deny(format!(
-                "Identity '{}' is not authorized to call tool '{}'",
-                identity.id, tool_name
-            ));
+        match match_rule("allowed_tools", &identity.allowed_tools, tool_name) {
+            Ok(rule) => matched = rule,
+            Err(rule) => {
+                return (
+                    AuthzDecision::Deny(format!(
+                        "Identity '{}' is not authorized to call tool '{}'",
+                        identity.id, tool_name
+                    )),
+                    Some(rule),
+                )
+            }
+        }
+
+        if let Some(ref constraints) = identity.argument_constraints {
+            let arguments = message.params.as_ref().and_then(|p| p.get("arguments"));
+            if let Err(violation) = constraints.evaluate(tool_name, arguments) {
+                return (
+                    AuthzDecision::Deny(format!(
+                        "Identity '{}' is not authorized to call tool '{}': {}",
+                        identity.id, tool_name, violation.reason
+                    )),
+                    Some(AuthzRule {
+                        rule: "constraints".to_string(),
+                        pattern: violation.constraint,
+                        order: violation.position,
+                    }),
+                );
+            }
+        }
+    }
+
+    if let Some(uri) = extract_resource_uri(message) {
+        match match_rule("allowed_resources", &identity.allowed_resources, uri) {
+            Ok(rule) => matched = rule,
+            Err(rule) => {
+                return (
+                    AuthzDecision::Deny(format!(
+                        "Identity '{}' is not authorized to read resource '{}'",
+                        identity.id, uri
+                    )),
+                    Some(rule),
+                )
+            }
+        }
+    }
+
+    if let Some(prompt_name) = extract_prompt_name(message) {
+        match match_rule("allowed_prompts", &identity.allowed_prompts, prompt_name) {
+            Ok(rule) => matched = rule,
+            Err(rule) => {
+                return (
+                    AuthzDecision::Deny(format!(
+                        "Identity '{}' is not authorized to get prompt '{}'",
+                        identity.id, prompt_name
+                    )),
+                    Some(rule),
+                )
+            }
         }
     }
 
-    AuthzDecision::Allow
+    (AuthzDecision::Allow, matched)
 }
 
 // ============================================================================
@@ -127,40 +322,26 @@ pub fn is_tools_list_request(message: &Message) -> bool {
 /// ```
 ///
 /// This function filters the tools array to only include tools the identity can call.
-pub fn filter_tools_list_response(mut response: Message, identity: &Identity) -> Message {
-    // If identity has unrestricted access, return as-is
-    if identity.allowed_tools.is_none() {
-        return response;
-    }
+pub fn filter_tools_list_response(response: Message, identity: &Identity) -> Message {
+    apply_filter(&ListFilter::tools(), response, identity)
+}
 
-    // If identity has wildcard access, return as-is
-    if let Some(tools) = &identity.allowed_tools {
-        if tools.iter().any(|t| t == "*") {
-            return response;
-        }
-    }
+/// Filter a resources/list response to only include resources the identity may read
+pub fn filter_resources_list_response(response: Message, identity: &Identity) -> Message {
+    apply_filter(&ListFilter::resources(), response, identity)
+}
 
-    // Try to filter the tools array in the result
-    if let Some(ref mut result) = response.result {
-        if let Some(tools) = result.get_mut("tools") {
-            if let Some(tools_array) = tools.as_array() {
-                let filtered: Vec<Value> = tools_array
-                    .iter()
-                    .filter(|tool| {
-                        if let Some(name) = tool.get("name").and_then(|n| n.as_str()) {
-                            authorize_tool_call(identity, name)
-                        } else {
-                            false
-                        }
-                    })
-                    .cloned()
-                    .collect();
-
-                *tools = Value::Array(filtered);
-            }
-        }
-    }
+/// Filter a prompts/list response to only include prompts the identity may get
+pub fn filter_prompts_list_response(response: Message, identity: &Identity) -> Message {
+    apply_filter(&ListFilter::prompts(), response, identity)
+}
 
+fn apply_filter(
+    filter: &dyn ResponseFilter,
+    mut response: Message,
+    identity: &Identity,
+) -> Message {
+    filter.filter(&mut response, identity);
     response
 }
 
@@ -183,8 +364,12 @@ mod tests {
             id: "test".to_string(),
             name: None,
             allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         assert!(authorize_tool_call(&identity, "any_tool"));
@@ -197,8 +382,12 @@ mod tests {
             id: "test".to_string(),
             name: None,
             allowed_tools: Some(vec!["read".to_string(), "list".to_string()]),
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         assert!(authorize_tool_call(&identity, "read"));
@@ -213,8 +402,12 @@ mod tests {
             id: "test".to_string(),
             name: None,
             allowed_tools: Some(vec!["*".to_string()]),
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         assert!(authorize_tool_call(&identity, "any_tool"));
@@ -251,8 +444,12 @@ mod tests {
             id: "test".to_string(),
             name: None,
             allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         let response = Message {
@@ -282,8 +479,12 @@ mod tests {
             id: "test".to_string(),
             name: None,
             allowed_tools: Some(vec!["read_file".to_string()]),
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         let response = Message {
@@ -314,8 +515,12 @@ mod tests {
             id: "test".to_string(),
             name: None,
             allowed_tools: Some(vec!["*".to_string()]),
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         let response = Message {
@@ -344,8 +549,12 @@ mod tests {
             id: "test".to_string(),
             name: None,
             allowed_tools: Some(vec!["read_file".to_string(), "list_files".to_string()]),
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         let response = Message {
@@ -423,8 +632,12 @@ mod tests {
             id: "test".to_string(),
             name: None,
             allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         let message = Message {
@@ -448,8 +661,12 @@ mod tests {
             id: "test".to_string(),
             name: None,
             allowed_tools: Some(vec!["read_file".to_string()]),
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         let message = Message {
@@ -469,14 +686,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_authorize_request_checks_argument_constraints() {
+        let constraints = ArgumentConstraints::new(&[crate::config::ToolArgumentConstraint {
+            tool: "read_file".to_string(),
+            argument: "path".to_string(),
+            allow: vec!["/srv/data/**".to_string()],
+            pattern: None,
+            deny_pattern: None,
+        }])
+        .unwrap();
+        let identity = Identity {
+            id: "test".to_string(),
+            name: None,
+            allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: Some(std::sync::Arc::new(constraints)),
+        };
+
+        let call = |path: &str| Message {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::json!(1)),
+            method: Some("tools/call".to_string()),
+            params: Some(serde_json::json!({
+                "name": "read_file",
+                "arguments": { "path": path }
+            })),
+            result: None,
+            error: None,
+        };
+
+        assert!(matches!(
+            authorize_request(&identity, &call("/srv/data/a.txt")),
+            AuthzDecision::Allow
+        ));
+        match authorize_request(&identity, &call("/etc/passwd")) {
+            AuthzDecision::Allow => panic!("Expected Deny"),
+            AuthzDecision::Deny(reason) => assert!(reason.contains("argument 'path'")),
+        }
+    }
+
     #[test]
     fn test_authorize_request_allows_non_tool_calls() {
         let identity = Identity {
             id: "test".to_string(),
             name: None,
             allowed_tools: Some(vec!["read_file".to_string()]),
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
 
         // This is not a tools/call request, so authorization should pass
@@ -494,4 +759,154 @@ mod tests {
             AuthzDecision::Deny(_) => panic!("Expected Allow for non-tool call"),
         }
     }
+
+    fn scoped_identity() -> Identity {
+        Identity {
+            id: "test".to_string(),
+            name: None,
+            allowed_tools: None,
+            allowed_resources: Some(vec!["file:///docs/*".to_string()]),
+            allowed_prompts: Some(vec!["summarize".to_string()]),
+            rate_limit: None,
+            claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
+        }
+    }
+
+    fn request(method: &str, params: serde_json::Value) -> Message {
+        Message {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::json!(1)),
+            method: Some(method.to_string()),
+            params: Some(params),
+            result: None,
+            error: None,
+        }
+    }
+
+    /// Verify resources/read and prompts/get are checked against their allow lists
+    #[test]
+    fn test_authorize_request_resources_and_prompts() {
+        let identity = scoped_identity();
+
+        let allowed = request(
+            "resources/read",
+            serde_json::json!({"uri": "file:///docs/readme.md"}),
+        );
+        assert!(matches!(
+            authorize_request(&identity, &allowed),
+            AuthzDecision::Allow
+        ));
+
+        let denied = request(
+            "resources/read",
+            serde_json::json!({"uri": "file:///etc/passwd"}),
+        );
+        match authorize_request(&identity, &denied) {
+            AuthzDecision::Allow => panic!("Expected Deny"),
+            AuthzDecision::Deny(reason) => assert!(reason.contains("file:///etc/passwd")),
+        }
+        assert_eq!(extract_authz_target(&denied), Some("file:///etc/passwd"));
+
+        let prompt = request("prompts/get", serde_json::json!({"name": "summarize"}));
+        assert!(matches!(
+            authorize_request(&identity, &prompt),
+            AuthzDecision::Allow
+        ));
+        let prompt = request("prompts/get", serde_json::json!({"name": "jailbreak"}));
+        assert!(matches!(
+            authorize_request(&identity, &prompt),
+            AuthzDecision::Deny(_)
+        ));
+
+        // Tools remain unrestricted for this identity
+        let tool = request("tools/call", serde_json::json!({"name": "anything"}));
+        assert!(matches!(
+            authorize_request(&identity, &tool),
+            AuthzDecision::Allow
+        ));
+    }
+
+    /// Verify the deciding rule is reported with its pattern and position
+    #[test]
+    fn test_evaluate_request_reports_rule() {
+        let mut identity = scoped_identity();
+        identity.allowed_tools = Some(vec!["list_*".to_string(), "read_*".to_string()]);
+
+        let (decision, rule) = evaluate_request(
+            &identity,
+            &request("tools/call", serde_json::json!({"name": "read_file"})),
+        );
+        assert!(matches!(decision, AuthzDecision::Allow));
+        let rule = rule.unwrap();
+        assert_eq!(rule.rule, "allowed_tools");
+        assert_eq!(rule.pattern.as_deref(), Some("read_*"));
+        assert_eq!(rule.order, Some(2));
+        assert_eq!(
+            rule.to_string(),
+            "rule=allowed_tools; pattern=\"read_*\"; order=2"
+        );
+
+        let (decision, rule) = evaluate_request(
+            &identity,
+            &request("tools/call", serde_json::json!({"name": "delete_file"})),
+        );
+        assert!(matches!(decision, AuthzDecision::Deny(_)));
+        assert_eq!(
+            rule,
+            Some(AuthzRule {
+                rule: "allowed_tools".to_string(),
+                pattern: None,
+                order: None,
+            })
+        );
+
+        let (_, rule) = evaluate_request(
+            &identity,
+            &request("prompts/get", serde_json::json!({"name": "jailbreak"})),
+        );
+        assert_eq!(rule.unwrap().rule, "allowed_prompts");
+
+        // Unrestricted lists decide nothing
+        identity.allowed_tools = None;
+        let (decision, rule) = evaluate_request(
+            &identity,
+            &request("tools/call", serde_json::json!({"name": "anything"})),
+        );
+        assert!(matches!(decision, AuthzDecision::Allow));
+        assert!(rule.is_none());
+    }
+
+    /// Verify resources/list and prompts/list responses are filtered
+    #[test]
+    fn test_filter_resources_and_prompts_list() {
+        let identity = scoped_identity();
+
+        let response = Message::response(
+            serde_json::json!(1),
+            serde_json::json!({
+                "resources": [
+                    {"uri": "file:///docs/a.md", "name": "a"},
+                    {"uri": "file:///secrets/b.env", "name": "b"},
+                    {"name": "no-uri"}
+                ]
+            }),
+        );
+        let filtered = filter_resources_list_response(response, &identity);
+        let resources = filtered.result.unwrap()["resources"].clone();
+        assert_eq!(resources.as_array().unwrap().len(), 1);
+        assert_eq!(resources[0]["uri"], "file:///docs/a.md");
+
+        let response = Message::response(
+            serde_json::json!(1),
+            serde_json::json!({
+                "prompts": [{"name": "summarize"}, {"name": "jailbreak"}]
+            }),
+        );
+        let filtered = filter_prompts_list_response(response, &identity);
+        let prompts = filtered.result.unwrap()["prompts"].clone();
+        assert_eq!(prompts.as_array().unwrap().len(), 1);
+        assert_eq!(prompts[0]["name"], "summarize");
+    }
 }
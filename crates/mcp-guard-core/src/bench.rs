@@ -0,0 +1,403 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Load testing the full middleware stack (`mcp-guard bench`)
+//!
+//! [`run`] serves the standalone router on a loopback port, backed by an
+//! [`EchoTransport`] instead of a real upstream, and drives it with
+//! concurrent authenticated requests. The upstream answers instantly, so the
+//! measured latency is the gateway's own overhead: network ACLs, auth, rate
+//! limiting, authorization, response filtering and audit logging.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::value::{to_raw_value, RawValue};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+
+use crate::cli::{generate_api_key, hash_api_key};
+use crate::config::{ApiKeyConfig, Config};
+use crate::server::Guard;
+use crate::transport::{Message, RawMessage, Transport, TransportError};
+
+/// Identity the benchmark's API key authenticates as
+pub const BENCH_IDENTITY: &str = "mcp-guard-bench";
+
+/// Tool exposed by [`EchoTransport`]
+const BENCH_TOOL: &str = "echo";
+
+// ============================================================================
+// Mock Upstream
+// ============================================================================
+
+/// In-memory upstream that answers every request immediately
+///
+/// `tools/list` returns a single `echo` tool; every other request gets a
+/// text result of a fixed size. Results are pre-serialized, so the transport
+/// adds next to nothing to the measured latency.
+pub struct EchoTransport {
+    tools: Box<RawValue>,
+    content: Box<RawValue>,
+    tx: mpsc::UnboundedSender<(Option<Value>, bool)>,
+    rx: Mutex<mpsc::UnboundedReceiver<(Option<Value>, bool)>>,
+}
+
+impl EchoTransport {
+    /// Create a transport whose results carry `payload_bytes` of text
+    pub fn new(payload_bytes: usize) -> Self {
+        let tools = json!({
+            "tools": [{
+                "name": BENCH_TOOL,
+                "description": "Returns a fixed-size text result",
+                "inputSchema": {"type": "object"}
+            }]
+        });
+        let content = json!({
+            "content": [{"type": "text", "text": "x".repeat(payload_bytes)}]
+        });
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tools: to_raw_value(&tools).expect("static JSON serializes"),
+            content: to_raw_value(&content).expect("static JSON serializes"),
+            tx,
+            rx: Mutex::new(rx),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for EchoTransport {
+    async fn send(&self, message: Message) -> Result<(), TransportError> {
+        if message.is_request() {
+            let is_list = message.method.as_deref() == Some("tools/list");
+            self.tx
+                .send((message.id, is_list))
+                .map_err(|e| TransportError::Send(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Message, TransportError> {
+        self.receive_raw().await?.into_message()
+    }
+
+    async fn receive_raw(&self) -> Result<RawMessage, TransportError> {
+        let (id, is_list) = self
+            .rx
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(TransportError::ConnectionClosed)?;
+        let result = if is_list { &self.tools } else { &self.content };
+        Ok(RawMessage {
+            jsonrpc: "2.0".to_string(),
+            id,
+            method: None,
+            params: None,
+            result: Some(result.clone()),
+            error: None,
+        })
+    }
+
+    async fn close(&self) -> Result<(), TransportError> {
+        Ok(())
+    }
+
+    fn transport_type(&self) -> &'static str {
+        "bench"
+    }
+}
+
+// ============================================================================
+// Load Generator
+// ============================================================================
+
+/// What to send and how hard
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Total requests to send
+    pub requests: usize,
+    /// Requests in flight at once
+    pub concurrency: usize,
+    /// MCP method of every request (`tools/call` calls the echo tool)
+    pub method: String,
+    /// Size of the text in each upstream result
+    pub payload_bytes: usize,
+    /// Keep `[rate_limit]` enabled (otherwise it is turned off so the run
+    /// is not throttled)
+    pub rate_limit: bool,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            requests: 10_000,
+            concurrency: 32,
+            method: "tools/call".to_string(),
+            payload_bytes: 1024,
+            rate_limit: false,
+        }
+    }
+}
+
+/// Latency distribution in milliseconds
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencySummary {
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl LatencySummary {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let percentile = |p: f64| {
+            let index = ((samples.len() - 1) as f64 * p / 100.0).round() as usize;
+            ms(samples[index])
+        };
+        let total: Duration = samples.iter().sum();
+        Self {
+            mean: ms(total) / samples.len() as f64,
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p99: percentile(99.0),
+            max: ms(samples[samples.len() - 1]),
+        }
+    }
+}
+
+/// Outcome of a benchmark run
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    /// Requests sent
+    pub requests: usize,
+    /// Responses with status 200
+    pub succeeded: usize,
+    /// Requests that failed before a response arrived
+    pub errors: usize,
+    /// Response count by HTTP status
+    pub statuses: BTreeMap<u16, usize>,
+    /// Wall-clock time of the run
+    pub elapsed_secs: f64,
+    /// Completed requests per second
+    pub requests_per_sec: f64,
+    /// Latency of completed requests
+    pub latency_ms: LatencySummary,
+}
+
+/// Serve `config`'s middleware stack in front of an [`EchoTransport`] and
+/// load it with `options.requests` requests
+///
+/// The config's upstream is replaced by the mock and multi-server routes are
+/// dropped; an API key for [`BENCH_IDENTITY`] is added so requests
+/// authenticate whatever else `[auth]` configures.
+pub async fn run(mut config: Config, options: &BenchOptions) -> Result<BenchReport, crate::Error> {
+    let key = generate_api_key();
+    config.auth.api_keys.push(ApiKeyConfig {
+        id: BENCH_IDENTITY.to_string(),
+        key_hash: hash_api_key(&key),
+        allowed_tools: vec![],
+        allowed_resources: vec![],
+        allowed_prompts: vec![],
+        rate_limit: None,
+        network: None,
+        tenant: None,
+        description: Some("mcp-guard bench".to_string()),
+        not_before: None,
+        expires_at: None,
+        constraints: vec![],
+        access_windows: vec![],
+        grants: vec![],
+        break_glass: false,
+    });
+    config.upstream.servers.clear();
+    if !options.rate_limit {
+        config.rate_limit.enabled = false;
+    }
+
+    let guard = Guard::builder(config)
+        .transport(Arc::new(EchoTransport::new(options.payload_bytes)))
+        .build()?;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let app = guard
+        .into_router()
+        .into_make_service_with_connect_info::<SocketAddr>();
+    let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let report = drive(addr, &key, options).await;
+    server.abort();
+    report
+}
+
+/// Send the requests from `options.concurrency` workers and collect results
+async fn drive(
+    addr: SocketAddr,
+    key: &str,
+    options: &BenchOptions,
+) -> Result<BenchReport, crate::Error> {
+    let params =
+        (options.method == "tools/call").then(|| json!({"name": BENCH_TOOL, "arguments": {}}));
+    let body = serde_json::to_vec(&Message::request(1, &options.method, params))
+        .map_err(|e| crate::Error::Other(e.to_string()))?;
+    let client = reqwest::Client::builder()
+        .pool_max_idle_per_host(options.concurrency)
+        .build()
+        .map_err(|e| crate::Error::Other(e.to_string()))?;
+    let url = format!("http://{}/mcp", addr);
+    let auth = format!("Bearer {}", key);
+    let next = Arc::new(AtomicUsize::new(0));
+
+    let start = Instant::now();
+    let workers: Vec<_> = (0..options.concurrency.max(1))
+        .map(|_| {
+            let (client, url, auth, body, next) = (
+                client.clone(),
+                url.clone(),
+                auth.clone(),
+                body.clone(),
+                next.clone(),
+            );
+            let requests = options.requests;
+            tokio::spawn(async move {
+                let mut results = Vec::new();
+                while next.fetch_add(1, Ordering::Relaxed) < requests {
+                    let sent = Instant::now();
+                    let response = client
+                        .post(&url)
+                        .header("Authorization", &auth)
+                        .header("Content-Type", "application/json")
+                        .body(body.clone())
+                        .send()
+                        .await;
+                    let status = match response {
+                        // Read the body so the latency covers the whole response
+                        Ok(response) => {
+                            let status = response.status().as_u16();
+                            response.bytes().await.ok().map(|_| status)
+                        }
+                        Err(_) => None,
+                    };
+                    results.push((status, sent.elapsed()));
+                }
+                results
+            })
+        })
+        .collect();
+
+    let mut statuses = BTreeMap::new();
+    let mut latencies = Vec::with_capacity(options.requests);
+    let mut errors = 0;
+    for worker in workers {
+        let results = worker
+            .await
+            .map_err(|e| crate::Error::Other(e.to_string()))?;
+        for (status, latency) in results {
+            match status {
+                Some(status) => {
+                    *statuses.entry(status).or_insert(0) += 1;
+                    latencies.push(latency);
+                }
+                None => errors += 1,
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+
+    Ok(BenchReport {
+        requests: options.requests,
+        succeeded: statuses.get(&200).copied().unwrap_or(0),
+        errors,
+        statuses,
+        elapsed_secs: elapsed.as_secs_f64(),
+        requests_per_sec: latencies.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        latency_ms: LatencySummary::from_samples(latencies),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_percentiles() {
+        let samples = (1..=100).map(Duration::from_millis).collect();
+        let summary = LatencySummary::from_samples(samples);
+        assert_eq!(summary.p50, 51.0);
+        assert_eq!(summary.p99, 99.0);
+        assert_eq!(summary.max, 100.0);
+        assert!((summary.mean - 50.5).abs() < 1e-9);
+
+        assert_eq!(LatencySummary::from_samples(vec![]).max, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_echo_transport_answers_by_method() {
+        let transport = EchoTransport::new(16);
+        transport
+            .send(Message::request(1, "tools/list", None))
+            .await
+            .unwrap();
+        transport
+            .send(Message::request(2, "tools/call", None))
+            .await
+            .unwrap();
+
+        let list = transport.receive().await.unwrap();
+        assert_eq!(list.id, Some(json!(1)));
+        assert_eq!(list.result.unwrap()["tools"][0]["name"], BENCH_TOOL);
+
+        let call = transport.receive().await.unwrap();
+        let text = call.result.unwrap()["content"][0]["text"].clone();
+        assert_eq!(text.as_str().unwrap().len(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_run_drives_full_stack() {
+        let config: Config = toml::from_str(
+            r#"
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+            "#,
+        )
+        .unwrap();
+        let options = BenchOptions {
+            requests: 20,
+            concurrency: 4,
+            ..Default::default()
+        };
+
+        let report = run(config, &options).await.unwrap();
+        assert_eq!(report.requests, 20);
+        assert_eq!(report.succeeded, 20, "statuses: {:?}", report.statuses);
+        assert_eq!(report.errors, 0);
+        assert!(report.latency_ms.max > 0.0);
+    }
+}
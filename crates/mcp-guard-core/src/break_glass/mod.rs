@@ -0,0 +1,546 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Emergency "break glass" access
+//!
+//! API keys with `break_glass = true` are not limited by allow lists, so
+//! [`BreakGlass::admit`] makes every request using one account for itself:
+//! it must carry a justification of at least `min_justification_length`
+//! characters, the first accepted request activates the key for
+//! `duration_secs`, and a webhook is told about the activation and about
+//! every change of justification. Requests after the key's time is up are
+//! rejected.
+//!
+//! With a database configured, activations are stored there
+//! ([`BreakGlass::with_storage`]): every replica sees the same expiry and an
+//! expired key stays expired across restarts. Without one they are kept in
+//! memory only, so each replica runs its own clock and a restart re-arms
+//! every key, expired or not.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::{
+    ApiKeyConfig, ApprovalWebhookFormat, BreakGlassConfig, MAX_BREAK_GLASS_DURATION_SECS,
+};
+use crate::db::{DbBreakGlassActivation, Storage};
+
+/// Header carrying the justification of a break-glass request
+pub const JUSTIFICATION_HEADER: &str = "x-mcp-guard-justification";
+
+/// Timeout for webhook notifications
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// Why a break-glass request was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BreakGlassError {
+    #[error("missing justification")]
+    MissingJustification,
+
+    #[error("justification shorter than {0} characters")]
+    JustificationTooShort(usize),
+
+    #[error("break-glass access expired at {0}")]
+    Expired(DateTime<Utc>),
+
+    #[error("break-glass activations unavailable: {0}")]
+    Storage(String),
+}
+
+/// Break-glass keys and their activations
+pub struct BreakGlass {
+    keys: HashSet<String>,
+    duration: chrono::Duration,
+    min_justification_length: usize,
+    activations: Mutex<HashMap<String, Activation>>,
+    storage: Option<Arc<dyn Storage>>,
+    webhook: Option<Webhook>,
+}
+
+impl Default for BreakGlass {
+    fn default() -> Self {
+        Self::new(&BreakGlassConfig::default(), &[])
+    }
+}
+
+impl std::fmt::Debug for BreakGlass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BreakGlass")
+            .field("keys", &self.keys)
+            .field("duration", &self.duration)
+            .field("storage", &self.storage.is_some())
+            .field("webhook", &self.webhook.is_some())
+            .finish()
+    }
+}
+
+struct Activation {
+    expires_at: DateTime<Utc>,
+    justification: String,
+}
+
+/// An accepted break-glass request
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakGlassUse {
+    pub identity_id: String,
+    pub justification: String,
+    /// Whether this request activated the key
+    pub activated: bool,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: DateTime<Utc>,
+}
+
+impl BreakGlass {
+    /// Collect the break-glass keys among `keys`
+    pub fn new(config: &BreakGlassConfig, keys: &[ApiKeyConfig]) -> Self {
+        Self {
+            keys: keys
+                .iter()
+                .filter(|key| key.break_glass)
+                .map(|key| key.id.clone())
+                .collect(),
+            // Validation rejects longer durations; clamp so they can't overflow
+            duration: chrono::Duration::seconds(
+                config.duration_secs.min(MAX_BREAK_GLASS_DURATION_SECS) as i64,
+            ),
+            min_justification_length: config.min_justification_length,
+            activations: Mutex::new(HashMap::new()),
+            storage: None,
+            webhook: config.webhook_url.as_ref().map(|url| Webhook {
+                client: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                    .build()
+                    .unwrap_or_default(),
+                url: url.clone(),
+                format: config.webhook_format,
+                headers: config.webhook_headers.clone(),
+            }),
+        }
+    }
+
+    /// Keep activations in the database so they are shared by every replica
+    /// and survive restarts
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Whether `identity_id` is a break-glass key
+    pub fn is_break_glass(&self, identity_id: &str) -> bool {
+        self.keys.contains(identity_id)
+    }
+
+    /// Check a request of the break-glass key `identity_id`, activating the
+    /// key on its first accepted request
+    pub async fn admit(
+        &self,
+        identity_id: &str,
+        justification: Option<&str>,
+    ) -> Result<BreakGlassUse, BreakGlassError> {
+        self.admit_at(identity_id, justification, Utc::now()).await
+    }
+
+    async fn admit_at(
+        &self,
+        identity_id: &str,
+        justification: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<BreakGlassUse, BreakGlassError> {
+        let justification = justification
+            .map(str::trim)
+            .filter(|justification| !justification.is_empty())
+            .ok_or(BreakGlassError::MissingJustification)?;
+        if justification.chars().count() < self.min_justification_length {
+            return Err(BreakGlassError::JustificationTooShort(
+                self.min_justification_length,
+            ));
+        }
+
+        let (activated, changed, expires_at) = match self.storage {
+            Some(ref storage) => {
+                self.activate_shared(storage.as_ref(), identity_id, justification, now)
+                    .await?
+            }
+            None => self.activate(identity_id, justification, now)?,
+        };
+
+        let used = BreakGlassUse {
+            identity_id: identity_id.to_string(),
+            justification: justification.to_string(),
+            activated,
+            expires_at,
+            used_at: now,
+        };
+        if changed {
+            if let Some(ref webhook) = self.webhook {
+                let webhook = webhook.clone();
+                let used = used.clone();
+                tokio::spawn(async move { webhook.notify(&used).await });
+            }
+        }
+        Ok(used)
+    }
+
+    /// Activate the key or check its activation in memory; returns whether
+    /// this request activated it, whether the justification changed, and
+    /// when the key expires
+    fn activate(
+        &self,
+        identity_id: &str,
+        justification: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(bool, bool, DateTime<Utc>), BreakGlassError> {
+        let mut activations = self.activations.lock().unwrap_or_else(|e| e.into_inner());
+        match activations.get_mut(identity_id) {
+            Some(activation) if activation.expires_at <= now => {
+                Err(BreakGlassError::Expired(activation.expires_at))
+            }
+            Some(activation) => {
+                let changed = activation.justification != justification;
+                if changed {
+                    activation.justification = justification.to_string();
+                }
+                Ok((false, changed, activation.expires_at))
+            }
+            None => {
+                let expires_at = now + self.duration;
+                activations.insert(
+                    identity_id.to_string(),
+                    Activation {
+                        expires_at,
+                        justification: justification.to_string(),
+                    },
+                );
+                Ok((true, true, expires_at))
+            }
+        }
+    }
+
+    /// [`activate`](Self::activate) against shared storage
+    async fn activate_shared(
+        &self,
+        storage: &dyn Storage,
+        identity_id: &str,
+        justification: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(bool, bool, DateTime<Utc>), BreakGlassError> {
+        let current = match storage
+            .find_break_glass_activation(identity_id)
+            .await
+            .map_err(storage_error)?
+        {
+            Some(current) => current,
+            None => {
+                let activation = DbBreakGlassActivation {
+                    identity_id: identity_id.to_string(),
+                    justification: justification.to_string(),
+                    expires_at: now + self.duration,
+                };
+                if storage
+                    .insert_break_glass_activation(&activation)
+                    .await
+                    .map_err(storage_error)?
+                {
+                    return Ok((true, true, activation.expires_at));
+                }
+                // Another replica activated the key first
+                storage
+                    .find_break_glass_activation(identity_id)
+                    .await
+                    .map_err(storage_error)?
+                    .ok_or_else(|| BreakGlassError::Storage("activation not found".into()))?
+            }
+        };
+
+        if current.expires_at <= now {
+            return Err(BreakGlassError::Expired(current.expires_at));
+        }
+        let changed = current.justification != justification
+            && storage
+                .update_break_glass_justification(identity_id, justification)
+                .await
+                .map_err(storage_error)?;
+        Ok((false, changed, current.expires_at))
+    }
+}
+
+fn storage_error(e: sqlx::Error) -> BreakGlassError {
+    BreakGlassError::Storage(e.to_string())
+}
+
+// ============================================================================
+// Webhook Notifications
+// ============================================================================
+
+#[derive(Clone)]
+struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    format: ApprovalWebhookFormat,
+    headers: HashMap<String, String>,
+}
+
+impl Webhook {
+    async fn notify(&self, used: &BreakGlassUse) {
+        let mut builder = self.client.post(&self.url).json(&self.payload(used));
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        match builder.send().await {
+            Ok(response) if response.status().is_success() => {
+                tracing::debug!(identity_id = %used.identity_id, "Break-glass webhook delivered");
+            }
+            Ok(response) => {
+                tracing::warn!(
+                    identity_id = %used.identity_id,
+                    status = %response.status(),
+                    "Break-glass webhook rejected"
+                );
+            }
+            Err(e) => {
+                tracing::warn!(
+                    identity_id = %used.identity_id,
+                    error = %e,
+                    "Break-glass webhook failed"
+                );
+            }
+        }
+    }
+
+    fn payload(&self, used: &BreakGlassUse) -> Value {
+        match self.format {
+            ApprovalWebhookFormat::Json => json!({
+                "type": "break_glass",
+                "use": used,
+            }),
+            ApprovalWebhookFormat::Slack => json!({
+                "text": format!(
+                    ":rotating_light: *Break-glass access {}*: `{}` until {}. Justification: {}",
+                    if used.activated { "activated" } else { "in use" },
+                    used.identity_id,
+                    used.expires_at.to_rfc3339(),
+                    used.justification,
+                ),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn break_glass() -> BreakGlass {
+        let key = ApiKeyConfig {
+            break_glass: true,
+            ..crate::server::tests::test_api_key("emergency", "emergency-secret")
+        };
+        let config = BreakGlassConfig {
+            duration_secs: 600,
+            ..Default::default()
+        };
+        BreakGlass::new(&config, &[key])
+    }
+
+    #[tokio::test]
+    async fn test_justification_is_required() {
+        let break_glass = break_glass();
+        assert!(break_glass.is_break_glass("emergency"));
+        assert!(!break_glass.is_break_glass("dev"));
+
+        assert_eq!(
+            break_glass.admit("emergency", None).await.unwrap_err(),
+            BreakGlassError::MissingJustification
+        );
+        assert_eq!(
+            break_glass
+                .admit("emergency", Some("   "))
+                .await
+                .unwrap_err(),
+            BreakGlassError::MissingJustification
+        );
+        assert_eq!(
+            break_glass
+                .admit("emergency", Some("fix"))
+                .await
+                .unwrap_err(),
+            BreakGlassError::JustificationTooShort(10)
+        );
+
+        // Rejected requests don't start the clock
+        let used = break_glass
+            .admit("emergency", Some("INC-1234 database outage"))
+            .await
+            .unwrap();
+        assert!(used.activated);
+        assert_eq!(used.justification, "INC-1234 database outage");
+    }
+
+    #[tokio::test]
+    async fn test_key_expires_after_first_use() {
+        let break_glass = break_glass();
+        let start = Utc::now();
+        let justification = Some("INC-1234 database outage");
+
+        let first = break_glass
+            .admit_at("emergency", justification, start)
+            .await
+            .unwrap();
+        assert!(first.activated);
+        assert_eq!(first.expires_at, start + chrono::Duration::seconds(600));
+
+        let later = break_glass
+            .admit_at(
+                "emergency",
+                justification,
+                start + chrono::Duration::seconds(599),
+            )
+            .await
+            .unwrap();
+        assert!(!later.activated);
+        assert_eq!(later.expires_at, first.expires_at);
+
+        assert_eq!(
+            break_glass
+                .admit_at(
+                    "emergency",
+                    justification,
+                    start + chrono::Duration::seconds(600),
+                )
+                .await
+                .unwrap_err(),
+            BreakGlassError::Expired(first.expires_at)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restart_rearms_expired_key() {
+        let start = Utc::now();
+        let justification = Some("INC-1234 database outage");
+        let break_glass = break_glass();
+        let first = break_glass
+            .admit_at("emergency", justification, start)
+            .await
+            .unwrap();
+        let after_expiry = first.expires_at + chrono::Duration::seconds(1);
+        assert!(break_glass
+            .admit_at("emergency", justification, after_expiry)
+            .await
+            .is_err());
+
+        // Without storage, a new gateway starts the clock again
+        let restarted = break_glass();
+        let used = restarted
+            .admit_at("emergency", justification, after_expiry)
+            .await
+            .unwrap();
+        assert!(used.activated);
+        assert!(used.expires_at > first.expires_at);
+    }
+
+    #[tokio::test]
+    async fn test_stored_activation_survives_restart() {
+        let db = crate::db::Database::new("sqlite::memory:").await.unwrap();
+        let start = Utc::now();
+        let justification = Some("INC-1234 database outage");
+
+        let replica_a = break_glass().with_storage(db.storage());
+        let first = replica_a
+            .admit_at("emergency", justification, start)
+            .await
+            .unwrap();
+        assert!(first.activated);
+
+        // Another replica, or a restarted gateway, shares the clock
+        let replica_b = break_glass().with_storage(db.storage());
+        let later = replica_b
+            .admit_at(
+                "emergency",
+                Some("INC-1234 still investigating"),
+                start + chrono::Duration::seconds(60),
+            )
+            .await
+            .unwrap();
+        assert!(!later.activated);
+        assert_eq!(later.expires_at, first.expires_at);
+
+        let after_expiry = first.expires_at + chrono::Duration::seconds(1);
+        let restarted = break_glass().with_storage(db.storage());
+        assert_eq!(
+            restarted
+                .admit_at("emergency", justification, after_expiry)
+                .await
+                .unwrap_err(),
+            BreakGlassError::Expired(first.expires_at)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duration_is_bounded() {
+        let key = ApiKeyConfig {
+            break_glass: true,
+            ..crate::server::tests::test_api_key("emergency", "emergency-secret")
+        };
+        let config = BreakGlassConfig {
+            duration_secs: u64::MAX,
+            ..Default::default()
+        };
+        let break_glass = BreakGlass::new(&config, &[key]);
+
+        let start = Utc::now();
+        let used = break_glass
+            .admit_at("emergency", Some("INC-1234 database outage"), start)
+            .await
+            .unwrap();
+        assert_eq!(
+            used.expires_at,
+            start + chrono::Duration::seconds(MAX_BREAK_GLASS_DURATION_SECS as i64)
+        );
+    }
+
+    #[test]
+    fn test_webhook_payloads() {
+        let used = BreakGlassUse {
+            identity_id: "emergency".to_string(),
+            justification: "INC-1234 database outage".to_string(),
+            activated: true,
+            expires_at: Utc::now(),
+            used_at: Utc::now(),
+        };
+        let webhook = |format| Webhook {
+            client: reqwest::Client::new(),
+            url: "https://example.com/hook".to_string(),
+            format,
+            headers: HashMap::new(),
+        };
+
+        let payload = webhook(ApprovalWebhookFormat::Json).payload(&used);
+        assert_eq!(payload["type"], "break_glass");
+        assert_eq!(payload["use"]["identity_id"], "emergency");
+        assert_eq!(payload["use"]["activated"], true);
+
+        let payload = webhook(ApprovalWebhookFormat::Slack).payload(&used);
+        let text = payload["text"].as_str().unwrap();
+        assert!(text.contains("activated*: `emergency`"));
+        assert!(text.contains("INC-1234 database outage"));
+    }
+}
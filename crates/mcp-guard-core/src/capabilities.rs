@@ -0,0 +1,188 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Capability downgrade in the `initialize` handshake
+//!
+//! A client that sees a capability will try to use it. When the gateway or
+//! its policy would break the flow (a refused `resources/subscribe`, an
+//! upstream asking a client for sampling the deployment does not want), the
+//! capability is removed from the handshake instead: `[capabilities]`
+//! `strip_client` from the client's `initialize` request, `strip_server` from
+//! the upstream's result. Server capabilities whose methods the method policy
+//! refuses are removed automatically.
+
+use serde_json::Value;
+
+use crate::config::CapabilitiesConfig;
+use crate::method_policy::MethodPolicy;
+use crate::transport::Message;
+
+/// Server capabilities and the method a client needs to use each one
+const CAPABILITY_METHODS: &[(&str, &str)] = &[
+    ("tools", "tools/list"),
+    ("resources", "resources/list"),
+    ("resources.subscribe", "resources/subscribe"),
+    ("prompts", "prompts/list"),
+    ("logging", "logging/setLevel"),
+    ("completions", "completion/complete"),
+];
+
+/// Remove `strip_client` capabilities from an `initialize` request
+///
+/// Other messages are left alone.
+pub fn downgrade_client(config: &CapabilitiesConfig, request: &mut Message) {
+    if request.method.as_deref() != Some("initialize") {
+        return;
+    }
+    let Some(capabilities) = request
+        .params
+        .as_mut()
+        .and_then(|params| params.get_mut("capabilities"))
+    else {
+        return;
+    };
+    for path in &config.strip_client {
+        remove_path(capabilities, path);
+    }
+}
+
+/// Remove `strip_server` capabilities, and those whose methods `route` may
+/// not use, from the result of an `initialize` request
+pub fn downgrade_server(
+    config: &CapabilitiesConfig,
+    policy: &MethodPolicy,
+    route: Option<&str>,
+    response: &mut Message,
+) {
+    let Some(capabilities) = response
+        .result
+        .as_mut()
+        .and_then(|result| result.get_mut("capabilities"))
+    else {
+        return;
+    };
+    for path in &config.strip_server {
+        remove_path(capabilities, path);
+    }
+    for (path, method) in CAPABILITY_METHODS {
+        if !policy.permits(route, method) {
+            remove_path(capabilities, path);
+        }
+    }
+}
+
+/// Remove the member at a dotted path, if present
+fn remove_path(capabilities: &mut Value, path: &str) {
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (
+            parent
+                .split('.')
+                .try_fold(&mut *capabilities, |value, segment| value.get_mut(segment)),
+            key,
+        ),
+        None => (Some(capabilities), path),
+    };
+    if let Some(removed) = parent
+        .and_then(Value::as_object_mut)
+        .and_then(|object| object.remove(key))
+    {
+        tracing::debug!(capability = %path, value = %removed, "Stripped capability");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use serde_json::json;
+
+    fn config(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_downgrade_client_request() {
+        let config = config(
+            r#"
+            [capabilities]
+            strip_client = ["sampling", "elicitation", "roots.listChanged"]
+
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+            "#,
+        );
+        let mut request = Message::request(
+            1,
+            "initialize",
+            Some(json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": {"sampling": {}, "elicitation": {}, "roots": {"listChanged": true}}
+            })),
+        );
+        downgrade_client(&config.capabilities, &mut request);
+        assert_eq!(
+            request.params.unwrap()["capabilities"],
+            json!({"roots": {}})
+        );
+
+        // Other methods are never touched
+        let params = json!({"capabilities": {"sampling": {}}});
+        let mut call = Message::request(2, "tools/call", Some(params.clone()));
+        downgrade_client(&config.capabilities, &mut call);
+        assert_eq!(call.params.unwrap(), params);
+    }
+
+    #[test]
+    fn test_downgrade_server_result() {
+        let config = config(
+            r#"
+            [methods]
+            deny = ["resources/subscribe"]
+
+            [capabilities]
+            strip_server = ["logging", "prompts.listChanged", "missing.path"]
+
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+            "#,
+        );
+        let policy = MethodPolicy::new(&config);
+        let mut response = Message::response(
+            json!(1),
+            json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": {
+                    "tools": {"listChanged": true},
+                    "resources": {"subscribe": true, "listChanged": true},
+                    "prompts": {"listChanged": true},
+                    "logging": {}
+                }
+            }),
+        );
+        downgrade_server(&config.capabilities, &policy, None, &mut response);
+        assert_eq!(
+            response.result.unwrap()["capabilities"],
+            json!({
+                "tools": {"listChanged": true},
+                "resources": {"listChanged": true},
+                "prompts": {}
+            })
+        );
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Request/response capture for debugging and replay
+//!
+//! When `[capture]` is enabled, a sampled fraction of MCP requests is written
+//! together with the upstream response to a JSON Lines file. Captures are
+//! redacted before they leave the request path, and written by a background
+//! task so capture never blocks a request. `mcp-guard replay` reads the file
+//! back with [`read_captures`] and re-sends the requests to an upstream.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+use crate::audit::{CompiledRedactionRules, RotatingFileWriter};
+use crate::config::{CaptureConfig, LogRotationConfig, RedactionRule};
+use crate::transport::Message;
+
+/// Captures buffered for the writer; further captures are dropped while it is full
+const CAPTURE_CHANNEL_SIZE: usize = 1000;
+
+// ============================================================================
+// Capture Records
+// ============================================================================
+
+/// One captured request and the response the upstream gave
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureRecord {
+    /// Unique capture ID
+    pub id: String,
+    /// When the request was received
+    pub timestamp: DateTime<Utc>,
+    /// Identity that made the request
+    pub identity_id: String,
+    /// Server route that handled the request (multi-server modes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// MCP method of the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Time taken by the upstream, including retries
+    pub duration_ms: u64,
+    /// Request as forwarded, after secret scrubbing
+    pub request: Message,
+    /// Response from the upstream, before response filtering
+    pub response: Message,
+}
+
+/// Whether a replayed response matches the captured one
+///
+/// Only `result` and `error` are compared; the JSON-RPC ID may differ.
+pub fn responses_match(captured: &Message, replayed: &Message) -> bool {
+    captured.result == replayed.result && captured.error == replayed.error
+}
+
+/// Read every capture record from a JSON Lines file
+///
+/// # Errors
+/// Fails on I/O errors or on a line that is not a capture record, naming the line.
+pub fn read_captures(path: &Path) -> io::Result<Vec<CaptureRecord>> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut records = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", number + 1, e),
+            )
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+// ============================================================================
+// Redaction
+// ============================================================================
+
+/// Redaction applied to captures: the audit rules followed by the capture rules
+///
+/// # Errors
+/// Returns an error if any pattern is not a valid regex.
+pub fn capture_redaction(
+    config: &CaptureConfig,
+    audit_rules: &[RedactionRule],
+) -> Result<CompiledRedactionRules, regex::Error> {
+    let rules: Vec<RedactionRule> = audit_rules
+        .iter()
+        .chain(&config.redaction_rules)
+        .cloned()
+        .collect();
+    CompiledRedactionRules::new(&rules)
+}
+
+/// Redact every string in a message, leaving its structure intact
+pub fn redact_message(rules: &CompiledRedactionRules, message: &Message) -> Message {
+    if rules.is_empty() {
+        return message.clone();
+    }
+    let mut value = serde_json::to_value(message).unwrap_or(Value::Null);
+    rules.redact_json(&mut value);
+    serde_json::from_value(value).unwrap_or_else(|_| message.clone())
+}
+
+// ============================================================================
+// Recorder
+// ============================================================================
+
+/// Samples requests and hands captures to the background writer
+pub struct CaptureRecorder {
+    sample_rate: f64,
+    methods: Vec<String>,
+    redaction: CompiledRedactionRules,
+    tx: Option<mpsc::Sender<CaptureRecord>>,
+}
+
+impl std::fmt::Debug for CaptureRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CaptureRecorder")
+            .field("enabled", &self.tx.is_some())
+            .field("sample_rate", &self.sample_rate)
+            .finish()
+    }
+}
+
+impl Default for CaptureRecorder {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+/// A sampled request waiting for its response
+pub struct PendingCapture {
+    timestamp: DateTime<Utc>,
+    started: Instant,
+    request: Message,
+}
+
+/// Handle for the capture writer task
+pub struct CaptureHandle {
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl CaptureHandle {
+    /// Wait for buffered captures to be written
+    ///
+    /// The writer finishes once every [`CaptureRecorder`] has been dropped.
+    pub async fn shutdown(self) {
+        if let Some(task) = self.task {
+            let _ = task.await;
+        }
+    }
+}
+
+impl CaptureRecorder {
+    /// Create a recorder that captures nothing
+    pub fn disabled() -> Self {
+        Self {
+            sample_rate: 0.0,
+            methods: Vec::new(),
+            redaction: CompiledRedactionRules::empty(),
+            tx: None,
+        }
+    }
+
+    /// Create a recorder and spawn the task writing its captures
+    ///
+    /// `audit_rules` are the `[[audit.redaction_rules]]`, applied before the
+    /// capture's own rules.
+    pub fn with_task(
+        config: &CaptureConfig,
+        audit_rules: &[RedactionRule],
+    ) -> io::Result<(Self, CaptureHandle)> {
+        if !config.enabled {
+            return Ok((Self::disabled(), CaptureHandle { task: None }));
+        }
+
+        let redaction = capture_redaction(config, audit_rules)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        let writer = RotatingFileWriter::new(
+            config.path.clone(),
+            config.rotation.clone().unwrap_or(LogRotationConfig {
+                enabled: false,
+                max_size_bytes: None,
+                max_age_secs: None,
+                max_backups: 0,
+                compress: false,
+            }),
+        )?;
+        let (tx, rx) = mpsc::channel(CAPTURE_CHANNEL_SIZE);
+        let task = tokio::spawn(run_capture_writer(rx, writer));
+
+        tracing::info!(
+            path = %config.path.display(),
+            sample_rate = config.sample_rate,
+            "Capturing sampled MCP traffic"
+        );
+        Ok((
+            Self {
+                sample_rate: config.sample_rate,
+                methods: config.methods.clone(),
+                redaction,
+                tx: Some(tx),
+            },
+            CaptureHandle { task: Some(task) },
+        ))
+    }
+
+    /// Decide whether to capture a request, keeping a copy if so
+    ///
+    /// Notifications are never captured since they have no response.
+    pub fn sample(&self, request: &Message) -> Option<PendingCapture> {
+        self.tx.as_ref()?;
+        if !request.is_request() {
+            return None;
+        }
+        if !self.methods.is_empty()
+            && !request
+                .method
+                .as_ref()
+                .is_some_and(|method| self.methods.contains(method))
+        {
+            return None;
+        }
+        if self.sample_rate < 1.0 && rand::random::<f64>() >= self.sample_rate {
+            return None;
+        }
+        Some(PendingCapture {
+            timestamp: Utc::now(),
+            started: Instant::now(),
+            request: request.clone(),
+        })
+    }
+
+    /// Record the response to a sampled request
+    pub fn record(
+        &self,
+        pending: PendingCapture,
+        identity_id: &str,
+        server: Option<&str>,
+        response: &Message,
+    ) {
+        let Some(ref tx) = self.tx else {
+            return;
+        };
+        let record = CaptureRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: pending.timestamp,
+            identity_id: self.redaction.redact(identity_id),
+            server: server.map(String::from),
+            method: pending.request.method.clone(),
+            duration_ms: pending.started.elapsed().as_millis() as u64,
+            request: redact_message(&self.redaction, &pending.request),
+            response: redact_message(&self.redaction, response),
+        };
+        if tx.try_send(record).is_err() {
+            tracing::debug!("Capture buffer full, dropping capture");
+        }
+    }
+}
+
+/// Background task appending captures to the capture file
+async fn run_capture_writer(mut rx: mpsc::Receiver<CaptureRecord>, mut writer: RotatingFileWriter) {
+    while let Some(record) = rx.recv().await {
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to serialize capture");
+                continue;
+            }
+        };
+        if let Err(e) = writer.write_line(&line) {
+            tracing::error!(error = %e, "Failed to write capture");
+        }
+        // Flush once the burst is written so the file is usable while running
+        if rx.is_empty() {
+            let _ = writer.flush();
+        }
+    }
+    let _ = writer.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture_config(path: &Path) -> CaptureConfig {
+        CaptureConfig {
+            enabled: true,
+            path: path.to_path_buf(),
+            sample_rate: 1.0,
+            ..Default::default()
+        }
+    }
+
+    fn redaction_rule(pattern: &str) -> RedactionRule {
+        RedactionRule {
+            name: "test".to_string(),
+            pattern: pattern.to_string(),
+            replacement: "[REDACTED]".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_disabled_recorder_samples_nothing() {
+        let recorder = CaptureRecorder::disabled();
+        assert!(recorder
+            .sample(&Message::request(1, "tools/list", None))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sample_filters_methods_and_notifications() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = capture_config(&dir.path().join("capture.jsonl"));
+        config.methods = vec!["tools/call".to_string()];
+        let (recorder, _handle) = CaptureRecorder::with_task(&config, &[]).unwrap();
+
+        assert!(recorder
+            .sample(&Message::request(1, "tools/call", None))
+            .is_some());
+        assert!(recorder
+            .sample(&Message::request(2, "tools/list", None))
+            .is_none());
+
+        let mut notification = Message::request(3, "tools/call", None);
+        notification.id = None;
+        assert!(recorder.sample(&notification).is_none());
+
+        config.methods.clear();
+        config.sample_rate = 0.0;
+        let (recorder, _handle) = CaptureRecorder::with_task(&config, &[]).unwrap();
+        assert!(recorder
+            .sample(&Message::request(1, "tools/call", None))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_captures_are_redacted_and_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capture.jsonl");
+        let mut config = capture_config(&path);
+        config.redaction_rules = vec![redaction_rule("hunter2")];
+        let audit_rules = vec![redaction_rule(r"sk-[a-z0-9]+")];
+        let (recorder, handle) = CaptureRecorder::with_task(&config, &audit_rules).unwrap();
+
+        let request = Message::request(
+            7,
+            "tools/call",
+            Some(serde_json::json!({
+                "name": "login",
+                "arguments": { "password": "hunter2", "keys": ["sk-abc123"] }
+            })),
+        );
+        let pending = recorder.sample(&request).unwrap();
+        let response = Message::response(
+            serde_json::json!(7),
+            serde_json::json!({ "token": "sk-def456" }),
+        );
+        recorder.record(pending, "alice", Some("auth"), &response);
+        drop(recorder);
+        handle.shutdown().await;
+
+        let records = read_captures(&path).unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.identity_id, "alice");
+        assert_eq!(record.server.as_deref(), Some("auth"));
+        assert_eq!(record.method.as_deref(), Some("tools/call"));
+        let arguments = &record.request.params.as_ref().unwrap()["arguments"];
+        assert_eq!(arguments["password"], "[REDACTED]");
+        assert_eq!(arguments["keys"][0], "[REDACTED]");
+        assert_eq!(
+            record.response.result.as_ref().unwrap()["token"],
+            "[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_responses_match_ignores_id() {
+        let captured = Message::response(serde_json::json!(1), serde_json::json!({ "ok": true }));
+        let same = Message::response(serde_json::json!(42), serde_json::json!({ "ok": true }));
+        let different = Message::response(serde_json::json!(1), serde_json::json!({ "ok": false }));
+        assert!(responses_match(&captured, &same));
+        assert!(!responses_match(&captured, &different));
+        assert!(!responses_match(
+            &captured,
+            &Message::error_response(Some(serde_json::json!(1)), -32000, "boom")
+        ));
+    }
+
+    #[test]
+    fn test_read_captures_reports_bad_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capture.jsonl");
+        std::fs::write(&path, "\nnot json\n").unwrap();
+        let err = read_captures(&path).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+}
@@ -21,13 +21,21 @@
 //!
 //! Available commands:
 //! - `init` - Generate a new configuration file (TOML or YAML)
-//! - `validate` - Validate configuration file syntax and semantics
+//! - `validate` - Validate configuration file syntax and semantics, flagging unknown keys
+//! - `config lint` - Flag insecure settings, optionally rewriting them with `--fix`
+//! - `config schema` - Print the JSON Schema for the configuration file
 //! - `keygen` - Generate a new API key with its hash for configuration
 //! - `hash-key` - Hash an existing API key for configuration
+//! - `hash-password` - Hash a password for an authorization server user
 //! - `run` - Start the MCP Guard HTTP proxy server
 //! - `serve` - Run as an MCP server (stdio mode) for use with Claude Desktop
 //! - `version` - Show version and build information
 //! - `check-upstream` - Test upstream MCP server connectivity
+//! - `doctor` - Run startup diagnostics and print a PASS/WARN/FAIL report
+//! - `test-call` - Send initialize, tools/list and a tools/call through the gateway
+//! - `bench` - Load test the middleware stack against an in-memory upstream
+//! - `audit report` - Summarize audit logs into per-identity access reports
+//! - `db migrate` / `db status` / `db reset` - Manage the database schema
 //!
 //! # Example
 //!
@@ -39,6 +47,9 @@
 //!
 //! # Run as stdio MCP server (for Claude Desktop)
 //! mcp-guard serve
+//!
+//! # Check what a key can see and call
+//! mcp-guard test-call --token mcp_xxx --tool read_file --args '{"path": "README.md"}'
 //! ```
 
 use clap::{Parser, Subcommand};
@@ -81,6 +92,12 @@ pub enum Commands {
     /// Validate configuration file
     Validate,
 
+    /// Inspect and maintain the configuration file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
     /// Generate a new API key
     Keygen {
         /// User/service identifier
@@ -100,6 +117,12 @@ pub enum Commands {
         apply_to_config: bool,
     },
 
+    /// Manage API keys in the configuration file
+    Keys {
+        #[command(subcommand)]
+        command: KeysCommands,
+    },
+
     /// Run the MCP Guard server
     Run {
         /// Override listen host
@@ -109,6 +132,19 @@ pub enum Commands {
         /// Override listen port
         #[arg(long)]
         port: Option<u16>,
+
+        /// Serve MCP over stdin/stdout instead of HTTP, enforcing the same
+        /// auth, authorization, rate limits and audit logging
+        #[arg(long, conflicts_with_all = ["host", "port"])]
+        stdio: bool,
+
+        /// API key or JWT that authenticates the stdio client (with --stdio)
+        #[arg(long, env = "MCP_GUARD_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+
+        /// Server route to forward to in multi-server configs (with --stdio)
+        #[arg(long)]
+        server: Option<String>,
     },
 
     /// Hash an API key for configuration
@@ -117,6 +153,12 @@ pub enum Commands {
         key: String,
     },
 
+    /// Hash a password for an authorization server user
+    HashPassword {
+        /// The password to hash
+        password: String,
+    },
+
     /// Show version and build information
     Version,
 
@@ -127,6 +169,143 @@ pub enum Commands {
         timeout: u64,
     },
 
+    /// Diagnose a configuration before starting the gateway with it
+    ///
+    /// Checks config validity, upstream connectivity for every route, JWKS and
+    /// OAuth endpoint reachability, audit sink writability, TLS certificate
+    /// expiry, license validity and DNS/SSRF policy, printing a PASS/WARN/FAIL
+    /// line per check. Exits non-zero if any check fails.
+    Doctor {
+        /// Timeout in seconds for each network check
+        #[arg(short, long, default_value = "10")]
+        timeout: u64,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Send initialize, tools/list and an optional tools/call, printing the results
+    ///
+    /// By default the gateway is started in-process from the config on a loopback
+    /// port, so authentication, authorization and routing behave exactly as in
+    /// `run`. Use --url to target a running gateway, or --direct to bypass the
+    /// gateway and talk to the upstream transport.
+    TestCall {
+        /// API key or JWT to authenticate with
+        #[arg(long, env = "MCP_GUARD_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+
+        /// URL of a running gateway (default: start one in-process)
+        #[arg(long, conflicts_with = "direct")]
+        url: Option<String>,
+
+        /// Server route to call in multi-server configs
+        #[arg(long)]
+        server: Option<String>,
+
+        /// Call the upstream transport directly, skipping auth and authorization
+        #[arg(long)]
+        direct: bool,
+
+        /// Tool to call after listing tools
+        #[arg(long)]
+        tool: Option<String>,
+
+        /// Tool arguments as a JSON object
+        #[arg(long, requires = "tool")]
+        args: Option<String>,
+
+        /// Timeout in seconds for each request
+        #[arg(short, long, default_value = "30")]
+        timeout: u64,
+    },
+
+    /// Re-send captured requests to an upstream and compare the responses
+    ///
+    /// Reads a file written by `[capture]` and replays its requests in order
+    /// against the configured upstream (or --url), reporting responses that
+    /// differ from the captured ones. Exits with an error if any request
+    /// fails or its response differs.
+    Replay {
+        /// Capture file to replay
+        file: PathBuf,
+
+        /// Server route to replay against in multi-server configs; only its
+        /// captures are replayed
+        #[arg(long)]
+        server: Option<String>,
+
+        /// HTTP MCP endpoint to replay against instead of the configured upstream
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Only replay captures of this MCP method
+        #[arg(long)]
+        method: Option<String>,
+
+        /// Stop after this many requests
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Timeout in seconds for each request
+        #[arg(short, long, default_value = "30")]
+        timeout: u64,
+    },
+
+    /// Load test the middleware stack against an in-memory upstream
+    ///
+    /// Serves the configured auth, authorization, filtering and audit
+    /// pipeline on a loopback port with an upstream that answers instantly,
+    /// then reports throughput and latency percentiles. With --max-p99-ms or
+    /// --min-rps, exits with an error if the run misses the target, for use
+    /// as a CI performance gate.
+    Bench {
+        /// Total requests to send
+        #[arg(short = 'n', long, default_value = "10000")]
+        requests: usize,
+
+        /// Requests in flight at once
+        #[arg(long, default_value = "32")]
+        concurrency: usize,
+
+        /// MCP method to send (`tools/call` calls the mock `echo` tool)
+        #[arg(long, default_value = "tools/call")]
+        method: String,
+
+        /// Size in bytes of the text in each upstream result
+        #[arg(long, default_value = "1024")]
+        payload_bytes: usize,
+
+        /// Keep the configured rate limit instead of disabling it
+        #[arg(long)]
+        rate_limit: bool,
+
+        /// Fail if p99 latency exceeds this many milliseconds
+        #[arg(long)]
+        max_p99_ms: Option<f64>,
+
+        /// Fail if throughput is below this many requests per second
+        #[arg(long)]
+        min_rps: Option<f64>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Work with audit logs
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommands,
+    },
+
+    /// Manage the database schema
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+
     /// Run as an MCP server (stdio mode) for use with Claude Desktop
     ///
     /// This mode allows mcp-guard to be launched as a subprocess by MCP clients.
@@ -134,6 +313,98 @@ pub enum Commands {
     Serve,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommands {
+    /// Check the configuration for insecure settings
+    ///
+    /// Goes beyond `validate`: flags configs that load fine but are unsafe to
+    /// deploy. Exits non-zero if any error-level findings remain.
+    Lint {
+        /// Rewrite the config file with all available fixes (TOML only)
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Print the JSON Schema for the configuration file
+    ///
+    /// Useful for editor completion and CI validation of config files.
+    Schema,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuditCommands {
+    /// Summarize audit logs into a per-identity access report
+    ///
+    /// Reads the audit file and its rotated backups and reports, for each
+    /// identity, the tools it called, its denials and its rate-limit events
+    /// over the time range. For SOC 2 / ISO 27001 access reviews.
+    Report {
+        /// Start of the range, inclusive (RFC 3339 or YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// End of the range, exclusive (RFC 3339 or YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Only report on this identity
+        #[arg(long)]
+        identity: Option<String>,
+
+        /// Output format (json or csv)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+
+        /// Write the report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Audit file to read (default: `audit.file` from the config)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommands {
+    /// Apply pending migrations
+    ///
+    /// For deployments that set `database.migrate = false` and upgrade the
+    /// schema as a separate step.
+    Migrate,
+
+    /// List migrations and whether each has been applied
+    ///
+    /// Exits non-zero if any migration is pending or does not match the
+    /// applied version.
+    Status,
+
+    /// Drop all tables and recreate the schema, deleting all data
+    Reset {
+        /// Confirm that all data should be deleted
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeysCommands {
+    /// Issue a new key for an identity and phase out the current one
+    ///
+    /// Adds a new `[[auth.api_keys]]` entry with the same id and permissions,
+    /// and sets `expires_at` on the existing entries so the old key keeps
+    /// working for the grace period. TOML config files only.
+    Rotate {
+        /// Id of the key to rotate
+        #[arg(long)]
+        id: String,
+
+        /// Hours the old key stays valid after rotation
+        #[arg(long, default_value = "24")]
+        grace_hours: u64,
+    },
+}
+
 impl Cli {
     /// Parse command-line arguments
     pub fn parse_args() -> Self {
@@ -173,6 +444,20 @@ pub fn hash_api_key(key: &str) -> String {
     )
 }
 
+/// Hash a password for `[[auth.authorization_server.users]]`
+///
+/// Uses argon2id with a random salt and returns the PHC string
+/// (`$argon2id$v=19$...`), which records the salt and parameters.
+pub fn hash_password(password: &str) -> String {
+    use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+
+    let salt = SaltString::generate(&mut OsRng);
+    argon2::Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("argon2 accepts any password with default parameters")
+        .to_string()
+}
+
 // ============================================================================
 // Config Generation
 // ============================================================================
@@ -432,3 +717,80 @@ pub fn apply_key_to_config(
 
     Ok(())
 }
+
+/// Rotate an API key in an existing config file
+///
+/// Copies the last `[[auth.api_keys]]` entry with the given id (permissions,
+/// tenant, description) into a new entry carrying `key_hash`, and caps `expires_at` on every existing entry
+/// for that id at now + `grace`, so clients can switch keys without downtime.
+/// Uses toml_edit to preserve comments and formatting.
+///
+/// # Returns
+/// The time at which the old key(s) stop working
+pub fn rotate_key_in_config(
+    config_path: &std::path::Path,
+    id: &str,
+    key_hash: &str,
+    grace: std::time::Duration,
+) -> Result<chrono::DateTime<chrono::Utc>, Box<dyn std::error::Error>> {
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use toml_edit::DocumentMut;
+
+    let content = std::fs::read_to_string(config_path)?;
+    let mut doc: DocumentMut = content.parse()?;
+
+    let now = Utc::now();
+    let old_expires_at = now + chrono::Duration::from_std(grace)?;
+    let old_expires_str = old_expires_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let api_keys = doc
+        .get_mut("auth")
+        .and_then(|auth| auth.get_mut("api_keys"))
+        .and_then(|keys| keys.as_array_of_tables_mut())
+        .ok_or("config has no [[auth.api_keys]] entries")?;
+
+    let mut template = None;
+    for entry in api_keys.iter_mut() {
+        if entry.get("id").and_then(|v| v.as_str()) != Some(id) {
+            continue;
+        }
+        // Never extend a key that already expires before the grace period ends
+        let current = entry
+            .get("expires_at")
+            .and_then(|v| v.as_str())
+            .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+            .map(|t| t.with_timezone(&Utc));
+        if current.map_or(true, |t| t > old_expires_at) {
+            entry.insert("expires_at", toml_edit::value(old_expires_str.as_str()));
+        }
+        template = Some(entry.clone());
+    }
+
+    let mut new_key = template.ok_or_else(|| format!("no API key with id '{}'", id))?;
+    new_key.insert("key_hash", toml_edit::value(key_hash));
+    new_key.remove("expires_at");
+    new_key.remove("not_before");
+    if !new_key.contains_key("description") {
+        new_key.insert(
+            "description",
+            toml_edit::value(format!(
+                "rotated {}",
+                now.to_rfc3339_opts(SecondsFormat::Secs, true)
+            )),
+        );
+    }
+    new_key.decor_mut().clear();
+    api_keys.push(new_key);
+
+    // Validate the modified config before writing
+    let config_str = doc.to_string();
+    let _: crate::config::Config =
+        toml::from_str(&config_str).map_err(|e| format!("Modified config is invalid: {}", e))?;
+
+    // Write atomically using temp file + rename
+    let temp_path = config_path.with_extension("toml.tmp");
+    std::fs::write(&temp_path, &config_str)?;
+    std::fs::rename(&temp_path, config_path)?;
+
+    Ok(old_expires_at)
+}
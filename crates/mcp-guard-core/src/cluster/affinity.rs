@@ -0,0 +1,502 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Session affinity for stdio upstreams
+//!
+//! A stdio upstream is a child process of the replica that spawned it, so a
+//! client's session with it only exists on that replica. [`Affinity`] places
+//! every replica on a consistent hash ring and assigns each request to a
+//! stdio-backed route by its affinity key (identity ID or `Mcp-Session-Id`).
+//! Adding or removing a replica only moves the keys next to it on the ring.
+//!
+//! The check runs after authentication and before rate limiting, so the
+//! owning replica authenticates the request again and is the only one that
+//! counts it. Forwarded requests carry [`FORWARDED_HEADER`] and are always
+//! served where they arrive, so a disagreement about the peer list cannot
+//! make requests bounce between replicas.
+
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, Request, Response, StatusCode};
+use sha2::{Digest, Sha256};
+
+use crate::config::{AffinityConfig, AffinityKey, AffinityMode, Config, TransportType};
+use crate::transport::TransportError;
+
+/// Header added to requests one replica forwards to another
+pub const FORWARDED_HEADER: &str = "x-mcp-guard-forwarded";
+
+/// Streamable HTTP session header used by `key = "session"`
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Ring positions per replica, evening out the share of keys each one owns
+const VIRTUAL_NODES: u32 = 64;
+
+/// Seconds to wait for a connection to the owning replica
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+
+/// Headers tied to one connection (hop-by-hop, plus `Host` and
+/// `Content-Length`), which are not passed on in either direction
+const HOP_BY_HOP: [HeaderName; 7] = [
+    header::CONNECTION,
+    header::HOST,
+    header::CONTENT_LENGTH,
+    header::TE,
+    header::TRAILER,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+];
+
+/// Assigns requests to stdio-backed routes to replicas
+pub struct Affinity {
+    replica_url: String,
+    peers: Vec<String>,
+    /// Ring position -> index into `peers`
+    ring: BTreeMap<u64, usize>,
+    key: AffinityKey,
+    mode: AffinityMode,
+    stdio_routes: StdioRoutes,
+    client: reqwest::Client,
+}
+
+impl Affinity {
+    pub fn new(config: &AffinityConfig, stdio_routes: StdioRoutes) -> Self {
+        let peers: Vec<String> = config.peers.iter().map(|p| normalize(p)).collect();
+        let mut ring = BTreeMap::new();
+        for (index, peer) in peers.iter().enumerate() {
+            for node in 0..VIRTUAL_NODES {
+                ring.insert(hash(&format!("{}#{}", peer, node)), index);
+            }
+        }
+        Self {
+            replica_url: normalize(&config.replica_url),
+            peers,
+            ring,
+            key: config.key,
+            mode: config.mode,
+            stdio_routes,
+            client: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Build from `cluster.affinity`; `None` when it is not configured or no
+    /// route is backed by a stdio upstream
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let affinity = config.cluster.affinity.as_ref()?;
+        let stdio_routes = StdioRoutes::from_config(config);
+        if stdio_routes.is_empty() {
+            return None;
+        }
+        Some(Self::new(affinity, stdio_routes))
+    }
+
+    /// Label for metrics: "forward" or "redirect"
+    pub fn mode_label(&self) -> &'static str {
+        match self.mode {
+            AffinityMode::Forward => "forward",
+            AffinityMode::Redirect => "redirect",
+        }
+    }
+
+    /// Replica owning `key`
+    pub fn owner(&self, key: &str) -> &str {
+        let position = hash(key);
+        let index = self
+            .ring
+            .range(position..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map_or(0, |(_, index)| *index);
+        &self.peers[index]
+    }
+
+    /// Replica a request should be sent to, or `None` to serve it here
+    pub fn remote_owner<B>(&self, request: &Request<B>, identity_id: &str) -> Option<&str> {
+        if request.headers().contains_key(FORWARDED_HEADER)
+            || !self.stdio_routes.contains(request.uri().path())
+        {
+            return None;
+        }
+        let session = match self.key {
+            AffinityKey::Session => request
+                .headers()
+                .get(SESSION_HEADER)
+                .and_then(|v| v.to_str().ok()),
+            AffinityKey::Identity => None,
+        };
+        let owner = self.owner(session.unwrap_or(identity_id));
+        (owner != self.replica_url).then_some(owner)
+    }
+
+    /// Send a request to the replica owning it
+    ///
+    /// `peer_ip` is appended to `X-Forwarded-For`; list the replicas in
+    /// `network_acl.trusted_proxy_ips` so the owner applies network rules to the
+    /// original client.
+    pub async fn dispatch(
+        &self,
+        owner: &str,
+        request: Request<Body>,
+        peer_ip: IpAddr,
+    ) -> Result<Response<Body>, TransportError> {
+        let path = request.uri().path_and_query().map_or("/", |p| p.as_str());
+        let url = format!("{}{}", owner, path);
+
+        if self.mode == AffinityMode::Redirect {
+            return Response::builder()
+                .status(StatusCode::TEMPORARY_REDIRECT)
+                .header(header::LOCATION, url)
+                .body(Body::empty())
+                .map_err(|e| TransportError::Http(e.to_string()));
+        }
+
+        let (parts, body) = request.into_parts();
+        let mut headers = without_hop_by_hop(parts.headers);
+        append_forwarded_for(&mut headers, peer_ip);
+        if let Ok(replica) = HeaderValue::from_str(&self.replica_url) {
+            headers.insert(FORWARDED_HEADER, replica);
+        }
+        // Requests are bounded by the body limit applied before authentication
+        let body = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .map_err(|e| TransportError::Receive(e.to_string()))?;
+
+        let response = self
+            .client
+            .request(parts.method, &url)
+            .headers(headers)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| TransportError::Http(e.to_string()))?;
+
+        let mut builder = Response::builder().status(response.status());
+        if let Some(response_headers) = builder.headers_mut() {
+            *response_headers = without_hop_by_hop(response.headers().clone());
+        }
+        // Streamed, so SSE responses from the owner arrive as they are sent
+        builder
+            .body(Body::from_stream(response.bytes_stream()))
+            .map_err(|e| TransportError::Http(e.to_string()))
+    }
+}
+
+/// Which MCP endpoints are served by a stdio upstream
+#[derive(Debug, Clone, Default)]
+pub struct StdioRoutes {
+    /// `/mcp` in single-server and aggregate mode
+    endpoint: bool,
+    /// Route path prefixes, longest first as the router matches them, and
+    /// whether each route is stdio
+    prefixes: Vec<(String, bool)>,
+}
+
+impl StdioRoutes {
+    pub fn from_config(config: &Config) -> Self {
+        let servers = &config.upstream.servers;
+        let is_stdio = |transport: &TransportType| matches!(transport, TransportType::Stdio);
+        if servers.is_empty() {
+            return Self {
+                endpoint: is_stdio(&config.upstream.transport),
+                prefixes: Vec::new(),
+            };
+        }
+        if config.is_aggregated() {
+            // One endpoint fans out to every server, so any stdio server pins it
+            return Self {
+                endpoint: servers.iter().any(|server| is_stdio(&server.transport)),
+                prefixes: Vec::new(),
+            };
+        }
+        let mut prefixes: Vec<_> = servers
+            .iter()
+            .map(|server| (server.path_prefix.clone(), is_stdio(&server.transport)))
+            .collect();
+        prefixes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        Self {
+            endpoint: false,
+            prefixes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.endpoint && !self.prefixes.iter().any(|(_, stdio)| *stdio)
+    }
+
+    /// Whether a request path reaches a stdio upstream
+    pub fn contains(&self, path: &str) -> bool {
+        match path.strip_prefix("/mcp") {
+            Some("") => self.endpoint,
+            // `/mcp/:server_name` is routed as `/:server_name`
+            Some(server) if server.starts_with('/') => self
+                .prefixes
+                .iter()
+                .find(|(prefix, _)| server.starts_with(prefix.as_str()))
+                .is_some_and(|(_, stdio)| *stdio),
+            _ => false,
+        }
+    }
+}
+
+fn normalize(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
+
+fn hash(value: &str) -> u64 {
+    let digest = Sha256::digest(value.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+fn without_hop_by_hop(mut headers: HeaderMap) -> HeaderMap {
+    for name in &HOP_BY_HOP {
+        headers.remove(name);
+    }
+    headers
+}
+
+fn append_forwarded_for(headers: &mut HeaderMap, peer_ip: IpAddr) {
+    let value = match headers.get("X-Forwarded-For").and_then(|v| v.to_str().ok()) {
+        Some(existing) => format!("{}, {}", existing, peer_ip),
+        None => peer_ip.to_string(),
+    };
+    if let Ok(value) = HeaderValue::from_str(&value) {
+        headers.insert("X-Forwarded-For", value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn affinity(replica: &str, key: AffinityKey) -> Affinity {
+        let config = AffinityConfig {
+            replica_url: replica.to_string(),
+            peers: vec![
+                "http://a:3000".to_string(),
+                "http://b:3000/".to_string(),
+                "http://c:3000".to_string(),
+            ],
+            key,
+            mode: AffinityMode::Forward,
+        };
+        Affinity::new(&config, routes())
+    }
+
+    fn routes() -> StdioRoutes {
+        StdioRoutes {
+            endpoint: false,
+            prefixes: vec![("/files".to_string(), true), ("/web".to_string(), false)],
+        }
+    }
+
+    fn request(path: &str) -> Request<Body> {
+        Request::post(path).body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_owner_is_stable_and_spread() {
+        let a = affinity("http://a:3000", AffinityKey::Identity);
+        let b = affinity("http://b:3000", AffinityKey::Identity);
+        let mut owners = HashSet::new();
+        for i in 0..100 {
+            let key = format!("user-{}", i);
+            assert_eq!(a.owner(&key), b.owner(&key));
+            owners.insert(a.owner(&key).to_string());
+        }
+        assert_eq!(owners.len(), 3);
+        assert!(owners.contains("http://b:3000"));
+    }
+
+    #[test]
+    fn test_removing_a_replica_only_moves_its_keys() {
+        let all = affinity("http://a:3000", AffinityKey::Identity);
+        let fewer = Affinity::new(
+            &AffinityConfig {
+                replica_url: "http://a:3000".to_string(),
+                peers: vec!["http://a:3000".to_string(), "http://b:3000".to_string()],
+                key: AffinityKey::Identity,
+                mode: AffinityMode::Forward,
+            },
+            StdioRoutes::default(),
+        );
+        for i in 0..100 {
+            let key = format!("user-{}", i);
+            if all.owner(&key) != "http://c:3000" {
+                assert_eq!(all.owner(&key), fewer.owner(&key));
+            }
+        }
+    }
+
+    #[test]
+    fn test_remote_owner() {
+        let identity = (0..100)
+            .map(|i| format!("user-{}", i))
+            .find(|key| {
+                affinity("http://a:3000", AffinityKey::Identity).owner(key) == "http://b:3000"
+            })
+            .unwrap();
+        let a = affinity("http://a:3000", AffinityKey::Identity);
+        let b = affinity("http://b:3000", AffinityKey::Identity);
+
+        assert_eq!(
+            a.remote_owner(&request("/mcp/files"), &identity),
+            Some("http://b:3000")
+        );
+        assert_eq!(b.remote_owner(&request("/mcp/files"), &identity), None);
+        // Only stdio-backed routes are pinned
+        assert_eq!(a.remote_owner(&request("/mcp/web"), &identity), None);
+
+        let mut forwarded = request("/mcp/files");
+        forwarded
+            .headers_mut()
+            .insert(FORWARDED_HEADER, HeaderValue::from_static("http://c:3000"));
+        assert_eq!(a.remote_owner(&forwarded, &identity), None);
+    }
+
+    #[test]
+    fn test_session_key() {
+        let a = affinity("http://a:3000", AffinityKey::Session);
+        let session = (0..100)
+            .map(|i| format!("session-{}", i))
+            .find(|key| a.owner(key) == "http://c:3000")
+            .unwrap();
+        let identity = (0..100)
+            .map(|i| format!("user-{}", i))
+            .find(|key| a.owner(key) == "http://a:3000")
+            .unwrap();
+
+        let mut with_session = request("/mcp/files");
+        with_session
+            .headers_mut()
+            .insert(SESSION_HEADER, HeaderValue::from_str(&session).unwrap());
+        assert_eq!(
+            a.remote_owner(&with_session, &identity),
+            Some("http://c:3000")
+        );
+        // Without a session the identity decides
+        assert_eq!(a.remote_owner(&request("/mcp/files"), &identity), None);
+    }
+
+    #[tokio::test]
+    async fn test_redirect() {
+        let mut config = affinity("http://a:3000", AffinityKey::Identity);
+        config.mode = AffinityMode::Redirect;
+        let response = config
+            .dispatch(
+                "http://b:3000",
+                request("/mcp/files?x=1"),
+                "10.0.0.1".parse().unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "http://b:3000/mcp/files?x=1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forward() {
+        use wiremock::matchers::{header as header_is, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let owner = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/mcp/files"))
+            .and(header_is("authorization", "Bearer key"))
+            .and(header_is(FORWARDED_HEADER, "http://a:3000"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"ok\":true}"))
+            .expect(1)
+            .mount(&owner)
+            .await;
+
+        let a = affinity("http://a:3000", AffinityKey::Identity);
+        // The original Host must not be passed on to the owner
+        let request = Request::post("/mcp/files")
+            .header(header::HOST, "a:3000")
+            .header("authorization", "Bearer key")
+            .header("X-Forwarded-For", "203.0.113.7")
+            .body(Body::from("{}"))
+            .unwrap();
+
+        let response = a
+            .dispatch(&owner.uri(), request, "10.0.0.1".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"{\"ok\":true}");
+
+        let received = owner.received_requests().await.unwrap();
+        assert_eq!(
+            received[0].headers["x-forwarded-for"],
+            "203.0.113.7, 10.0.0.1"
+        );
+    }
+
+    #[test]
+    fn test_stdio_routes() {
+        let config: Config = toml::from_str(
+            r#"
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+
+            [[upstream.servers]]
+            name = "files"
+            path_prefix = "/files"
+            transport = "stdio"
+            command = "mcp-files"
+
+            [[upstream.servers]]
+            name = "web"
+            path_prefix = "/web"
+            transport = "http"
+            url = "https://mcp.example.com"
+            "#,
+        )
+        .unwrap();
+        let routes = StdioRoutes::from_config(&config);
+        assert!(routes.contains("/mcp/files"));
+        assert!(!routes.contains("/mcp/web"));
+        assert!(!routes.contains("/mcp"));
+        assert!(!routes.contains("/mcpx/files"));
+
+        let config: Config = toml::from_str(
+            r#"
+            [upstream]
+            transport = "http"
+            url = "https://mcp.example.com"
+            "#,
+        )
+        .unwrap();
+        assert!(StdioRoutes::from_config(&config).is_empty());
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Shared state for running several gateway replicas
+//!
+//! With `cluster.enabled`, state that would otherwise live in one process is
+//! kept in the configured database, so a load balancer can send any request
+//! to any replica:
+//!
+//! - pending OAuth authorization code flows (the callback may reach a
+//!   different replica than the one that started the flow)
+//! - OAuth sessions, via [`SessionStore::with_storage`](crate::auth::SessionStore::with_storage)
+//! - per-identity rate limits, as one-second counters in `quota_usage`
+//!
+//! Each replica still applies its local rate limiter first; the shared
+//! counter caps what all replicas together let through.
+//!
+//! Stdio upstreams cannot be shared this way; [`affinity`] sends the requests
+//! for them to one replica instead.
+
+pub mod affinity;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use tokio_util::sync::CancellationToken;
+
+use crate::db::{DbOAuthState, Storage};
+
+/// Key prefix of rate limit counters in the `quota_usage` table
+const RATE_LIMIT_PREFIX: &str = "rate:";
+
+/// Seconds a finished rate limit window is kept before cleanup removes it
+const RATE_LIMIT_RETENTION_SECS: i64 = 60;
+
+/// State shared by all replicas through the database
+pub struct ClusterState {
+    storage: Arc<dyn Storage>,
+    /// How long a pending OAuth flow stays valid
+    oauth_state_ttl: Duration,
+}
+
+impl ClusterState {
+    pub fn new(storage: Arc<dyn Storage>, oauth_state_ttl: Duration) -> Self {
+        Self {
+            storage,
+            oauth_state_ttl,
+        }
+    }
+
+    /// Storage backing the shared state
+    pub fn storage(&self) -> Arc<dyn Storage> {
+        self.storage.clone()
+    }
+
+    /// Record a pending OAuth flow
+    pub async fn put_oauth_state(&self, state: &DbOAuthState) -> Result<(), sqlx::Error> {
+        self.storage.put_oauth_state(state).await
+    }
+
+    /// Remove a pending OAuth flow and return it, whichever replica started it
+    pub async fn take_oauth_state(&self, state: &str) -> Result<Option<DbOAuthState>, sqlx::Error> {
+        self.storage.take_oauth_state(state).await
+    }
+
+    /// Number of pending OAuth flows across the cluster, after removing
+    /// expired ones
+    pub async fn pending_oauth_states(&self) -> Result<usize, sqlx::Error> {
+        self.storage
+            .purge_oauth_states(self.oauth_state_cutoff())
+            .await?;
+        let count = self.storage.count_oauth_states().await?;
+        Ok(usize::try_from(count).unwrap_or(0))
+    }
+
+    /// Count a request against the identity's cluster-wide budget for the
+    /// current second
+    ///
+    /// Returns false once all replicas together have counted more than
+    /// `max_per_second` requests in that second. Fails open: if the database
+    /// cannot be reached, only the local limit applies.
+    pub async fn check_rate_limit(&self, identity_id: &str, max_per_second: u32) -> bool {
+        self.check_rate_limit_at(identity_id, max_per_second, Utc::now())
+            .await
+    }
+
+    async fn check_rate_limit_at(
+        &self,
+        identity_id: &str,
+        max_per_second: u32,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let window = Utc
+            .timestamp_opt(now.timestamp(), 0)
+            .single()
+            .unwrap_or(now);
+        let key = format!("{}{}", RATE_LIMIT_PREFIX, identity_id);
+        match self.storage.add_quota_usage(&key, window, 1).await {
+            Ok(used) => used <= i64::from(max_per_second),
+            Err(e) => {
+                tracing::warn!(
+                    identity_id = %identity_id,
+                    error = %e,
+                    "Shared rate limit unavailable, applying local limit only"
+                );
+                true
+            }
+        }
+    }
+
+    /// Remove expired OAuth flows, sessions and rate limit windows; returns
+    /// how many rows were removed
+    pub async fn cleanup_expired(&self) -> Result<u64, sqlx::Error> {
+        let states = self
+            .storage
+            .purge_oauth_states(self.oauth_state_cutoff())
+            .await?;
+        let sessions = self.storage.purge_oauth_sessions().await?;
+        let windows = self
+            .storage
+            .purge_quota_usage(
+                RATE_LIMIT_PREFIX,
+                Utc::now() - chrono::Duration::seconds(RATE_LIMIT_RETENTION_SECS),
+            )
+            .await?;
+        Ok(states + sessions + windows)
+    }
+
+    /// Start a background task running [`cleanup_expired`](Self::cleanup_expired)
+    /// every `interval_secs` until `shutdown_token` is cancelled
+    pub fn start_cleanup_task(
+        self: &Arc<Self>,
+        shutdown_token: CancellationToken,
+        interval_secs: u64,
+    ) -> tokio::task::JoinHandle<()> {
+        let cluster = Arc::clone(self);
+
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(Duration::from_secs(interval_secs));
+            // Skip the first immediate tick
+            interval_timer.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = shutdown_token.cancelled() => {
+                        tracing::debug!("Cluster cleanup task received shutdown signal");
+                        break;
+                    }
+                    _ = interval_timer.tick() => {
+                        match cluster.cleanup_expired().await {
+                            Ok(0) => {}
+                            Ok(removed) => {
+                                tracing::debug!(removed, "Cluster cleanup task removed expired entries");
+                            }
+                            Err(e) => {
+                                tracing::warn!(error = %e, "Cluster cleanup failed");
+                            }
+                        }
+                    }
+                }
+            }
+            tracing::debug!("Cluster cleanup task exiting");
+        })
+    }
+
+    fn oauth_state_cutoff(&self) -> DateTime<Utc> {
+        Utc::now()
+            - chrono::Duration::from_std(self.oauth_state_ttl).unwrap_or(chrono::Duration::zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    async fn cluster() -> (Database, ClusterState) {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        let cluster = ClusterState::new(db.storage(), Duration::from_secs(600));
+        (db, cluster)
+    }
+
+    fn oauth_state(state: &str, age_secs: i64) -> DbOAuthState {
+        DbOAuthState {
+            state: state.to_string(),
+            code_verifier: "verifier".to_string(),
+            client_ip: "10.0.0.1".to_string(),
+            redirect_uri: None,
+            created_at: Utc::now() - chrono::Duration::seconds(age_secs),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oauth_state_is_taken_once() {
+        let (_db, cluster) = cluster().await;
+        cluster
+            .put_oauth_state(&oauth_state("abc", 0))
+            .await
+            .unwrap();
+        assert_eq!(cluster.pending_oauth_states().await.unwrap(), 1);
+
+        let taken = cluster.take_oauth_state("abc").await.unwrap().unwrap();
+        assert_eq!(taken.code_verifier, "verifier");
+        assert_eq!(taken.client_ip, "10.0.0.1");
+        assert!(cluster.take_oauth_state("abc").await.unwrap().is_none());
+        assert_eq!(cluster.pending_oauth_states().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_expired_oauth_states_are_not_counted() {
+        let (_db, cluster) = cluster().await;
+        cluster
+            .put_oauth_state(&oauth_state("old", 3600))
+            .await
+            .unwrap();
+        cluster
+            .put_oauth_state(&oauth_state("new", 0))
+            .await
+            .unwrap();
+        assert_eq!(cluster.pending_oauth_states().await.unwrap(), 1);
+        assert!(cluster.take_oauth_state("old").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shared_rate_limit() {
+        let (db, cluster) = cluster().await;
+        // A second replica sharing the same database
+        let other = ClusterState::new(db.storage(), Duration::from_secs(600));
+
+        let now = Utc::now();
+        let mut allowed = 0;
+        for i in 0..6 {
+            let replica = if i % 2 == 0 { &cluster } else { &other };
+            if replica.check_rate_limit_at("alice", 3, now).await {
+                allowed += 1;
+            }
+        }
+        // Both replicas count against one budget
+        assert_eq!(allowed, 3);
+        assert!(cluster.check_rate_limit_at("bob", 3, now).await);
+
+        // The next second starts a new window
+        let next = now + chrono::Duration::seconds(1);
+        assert!(other.check_rate_limit_at("alice", 3, next).await);
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_removes_old_rate_limit_windows() {
+        let (db, cluster) = cluster().await;
+        let old = Utc::now() - chrono::Duration::seconds(RATE_LIMIT_RETENTION_SECS * 2);
+        let quotas = db.quotas();
+        quotas.add("rate:alice", old, 5).await.unwrap();
+        quotas.add("alice", old, 5).await.unwrap();
+
+        assert_eq!(cluster.cleanup_expired().await.unwrap(), 1);
+        assert_eq!(quotas.usage("rate:alice", old).await.unwrap(), 0);
+        // Other quota counters are left alone
+        assert_eq!(quotas.usage("alice", old).await.unwrap(), 5);
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Environment variable overlay
+//!
+//! Any setting can be supplied as `MCPGUARD_<PATH>`, where the path segments
+//! are the config keys joined by a double underscore:
+//!
+//! ```text
+//! MCPGUARD_SERVER__PORT=8080                 -> server.port = 8080
+//! MCPGUARD_AUTH__JWT__ISSUER=https://idp     -> auth.jwt.issuer = "https://idp"
+//! MCPGUARD_UPSTREAM__SERVERS__0__URL=...     -> upstream.servers[0].url
+//! ```
+//!
+//! Segments are lowercased to match the config keys. Numeric segments index
+//! into existing arrays. Values are read as JSON where they parse (numbers,
+//! booleans, arrays, objects, quoted strings) and as plain strings otherwise,
+//! so a string that looks like a number must be quoted: `'"12345"'`.
+//!
+//! The overlay is applied to the parsed file before deserialization, so the
+//! result is validated exactly like a config written in full.
+
+use serde_json::Value;
+
+use super::ConfigError;
+
+/// Prefix marking a variable as part of the overlay
+pub const ENV_PREFIX: &str = "MCPGUARD_";
+
+/// Separator between path segments
+pub const ENV_SEPARATOR: &str = "__";
+
+/// Overlay variables from `vars`, sorted by name
+///
+/// Sorting applies a whole-table variable such as `MCPGUARD_AUTH__JWT`
+/// before the `MCPGUARD_AUTH__JWT__*` variables that refine it.
+pub fn overlay_vars(vars: impl IntoIterator<Item = (String, String)>) -> Vec<(String, String)> {
+    let mut vars: Vec<_> = vars
+        .into_iter()
+        .filter(|(name, _)| name.len() > ENV_PREFIX.len() && name.starts_with(ENV_PREFIX))
+        .collect();
+    vars.sort();
+    vars
+}
+
+/// Apply overlay variables to a parsed config document
+pub fn apply_env_overlay(
+    document: &mut Value,
+    vars: &[(String, String)],
+) -> Result<(), ConfigError> {
+    for (name, raw) in vars {
+        let path: Vec<String> = name[ENV_PREFIX.len()..]
+            .split(ENV_SEPARATOR)
+            .map(str::to_lowercase)
+            .collect();
+        if path.iter().any(String::is_empty) {
+            return Err(ConfigError::Parse(format!("{}: empty path segment", name)));
+        }
+        let value = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.clone()));
+        set_path(document, &path, value)
+            .map_err(|reason| ConfigError::Parse(format!("{}: {}", name, reason)))?;
+    }
+    Ok(())
+}
+
+/// Set the value at `path`, creating intermediate tables as needed
+fn set_path(document: &mut Value, path: &[String], value: Value) -> Result<(), String> {
+    let mut node = document;
+    for (depth, segment) in path.iter().enumerate() {
+        let last = depth + 1 == path.len();
+        let key = path[..=depth].join(".");
+        node = match node {
+            Value::Object(table) => {
+                if last {
+                    table.insert(segment.clone(), value);
+                    return Ok(());
+                }
+                table
+                    .entry(segment.clone())
+                    .or_insert_with(|| Value::Object(Default::default()))
+            }
+            Value::Array(items) => {
+                let len = items.len();
+                let item = segment
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| items.get_mut(index))
+                    .ok_or_else(|| format!("`{}` is not an index into an array of {}", key, len))?;
+                if last {
+                    *item = value;
+                    return Ok(());
+                }
+                item
+            }
+            _ => {
+                return Err(format!(
+                    "`{}` cannot be set because its parent is not a table",
+                    key
+                ))
+            }
+        };
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        overlay_vars(
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        )
+    }
+
+    #[test]
+    fn test_overlay_vars_filters_and_sorts() {
+        let vars = vars(&[
+            ("MCPGUARD_AUTH__JWT__ISSUER", "a"),
+            ("PATH", "/usr/bin"),
+            ("MCP_GUARD_DATABASE_URL", "sqlite://x"),
+            ("MCPGUARD_", "ignored"),
+            ("MCPGUARD_AUTH__JWT", "{}"),
+        ]);
+        let names: Vec<_> = vars.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["MCPGUARD_AUTH__JWT", "MCPGUARD_AUTH__JWT__ISSUER"]);
+    }
+
+    #[test]
+    fn test_apply_env_overlay() {
+        let mut document = json!({
+            "server": {"port": 3000, "host": "127.0.0.1"},
+            "upstream": {"servers": [{"name": "github", "url": "http://a"}]},
+        });
+        apply_env_overlay(
+            &mut document,
+            &vars(&[
+                ("MCPGUARD_SERVER__PORT", "8080"),
+                ("MCPGUARD_AUTH__JWT__ISSUER", "https://idp.example.com"),
+                ("MCPGUARD_AUTH__JWT__AUDIENCE", "\"12345\""),
+                ("MCPGUARD_RATE_LIMIT__ENABLED", "false"),
+                ("MCPGUARD_UPSTREAM__SERVERS__0__URL", "http://b"),
+                ("MCPGUARD_AUDIT__EXPORT_HEADERS", r#"{"X-Token": "t"}"#),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(document["server"]["port"], 8080);
+        assert_eq!(document["server"]["host"], "127.0.0.1");
+        assert_eq!(document["auth"]["jwt"]["issuer"], "https://idp.example.com");
+        assert_eq!(document["auth"]["jwt"]["audience"], "12345");
+        assert_eq!(document["rate_limit"]["enabled"], false);
+        assert_eq!(document["upstream"]["servers"][0]["url"], "http://b");
+        assert_eq!(document["upstream"]["servers"][0]["name"], "github");
+        assert_eq!(document["audit"]["export_headers"]["X-Token"], "t");
+    }
+
+    #[test]
+    fn test_apply_env_overlay_rejects_bad_paths() {
+        let mut document = json!({"server": {"port": 3000}, "upstream": {"servers": []}});
+
+        let err = apply_env_overlay(&mut document, &vars(&[("MCPGUARD_SERVER__PORT__X", "1")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("MCPGUARD_SERVER__PORT__X"));
+
+        let err = apply_env_overlay(
+            &mut document,
+            &vars(&[("MCPGUARD_UPSTREAM__SERVERS__0__URL", "http://a")]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("upstream.servers.0"));
+
+        assert!(
+            apply_env_overlay(&mut document, &vars(&[("MCPGUARD_SERVER____PORT", "1")])).is_err()
+        );
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Config includes (`include = ["base.toml", "prod-overrides.toml"]`)
+//!
+//! Included files are merged underneath the including file in the order
+//! listed: later includes override earlier ones, and the including file
+//! overrides them all. Tables are merged key by key, recursively; arrays and
+//! scalars replace the value beneath them wholesale, so an included
+//! `[[auth.api_keys]]` list is either kept or replaced, never interleaved.
+//!
+//! Relative paths are resolved against the directory of the file that names
+//! them. Included files may include others; cycles are rejected.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use super::{read_document, ConfigError};
+
+/// Top-level key listing the files to include
+pub const INCLUDE_KEY: &str = "include";
+
+/// Merge the files named by `document`'s `include` key underneath it
+///
+/// `path` is the file `document` was read from. Nested includes are resolved
+/// too, so the result has no `include` key left.
+pub fn resolve_includes(document: Value, path: &Path) -> Result<Value, ConfigError> {
+    resolve(document, path, &mut Vec::new())
+}
+
+fn resolve(
+    mut document: Value,
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Value, ConfigError> {
+    let includes = take_includes(&mut document, path)?;
+    if includes.is_empty() {
+        return Ok(document);
+    }
+
+    stack.push(canonical(path)?);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut merged = Value::Object(Default::default());
+    for include in includes {
+        let include_path = dir.join(include);
+        if stack.contains(&canonical(&include_path)?) {
+            return Err(ConfigError::Parse(format!(
+                "include cycle: {} includes {}",
+                path.display(),
+                include_path.display()
+            )));
+        }
+        let included = read_document(&include_path).map_err(|e| {
+            ConfigError::Parse(format!("include {}: {}", include_path.display(), e))
+        })?;
+        merge(&mut merged, resolve(included, &include_path, stack)?);
+    }
+    stack.pop();
+
+    merge(&mut merged, document);
+    Ok(merged)
+}
+
+/// Remove and return the `include` list from a document
+fn take_includes(document: &mut Value, path: &Path) -> Result<Vec<PathBuf>, ConfigError> {
+    let Some(includes) = document.as_object_mut().and_then(|t| t.remove(INCLUDE_KEY)) else {
+        return Ok(Vec::new());
+    };
+    serde_json::from_value(includes).map_err(|_| {
+        ConfigError::Parse(format!(
+            "{}: `{}` must be a list of file paths",
+            path.display(),
+            INCLUDE_KEY
+        ))
+    })
+}
+
+fn canonical(path: &Path) -> Result<PathBuf, ConfigError> {
+    path.canonicalize()
+        .map_err(|e| ConfigError::Parse(format!("include {}: {}", path.display(), e)))
+}
+
+/// Deep-merge `overlay` into `base`
+///
+/// Tables merge recursively; any other value in `overlay` replaces `base`.
+pub fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_merge_is_deep_for_tables_only() {
+        let mut base = json!({
+            "server": {"host": "0.0.0.0", "port": 3000},
+            "auth": {"api_keys": [{"id": "a"}, {"id": "b"}]},
+        });
+        merge(
+            &mut base,
+            json!({
+                "server": {"port": 8080},
+                "auth": {"api_keys": [{"id": "c"}]},
+            }),
+        );
+        assert_eq!(
+            base,
+            json!({
+                "server": {"host": "0.0.0.0", "port": 8080},
+                "auth": {"api_keys": [{"id": "c"}]},
+            })
+        );
+    }
+
+    #[test]
+    fn test_resolve_includes_in_order() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("shared")).unwrap();
+        std::fs::write(
+            dir.path().join("shared/base.toml"),
+            "[server]\nhost = \"0.0.0.0\"\nport = 3000\n\n[rate_limit]\nrequests_per_second = 10\n",
+        )
+        .unwrap();
+        // Nested includes resolve against the including file's directory
+        std::fs::write(
+            dir.path().join("shared/audit.yaml"),
+            "include: [base.toml]\naudit:\n  enabled: true\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("prod.toml"),
+            "[server]\nport = 443\n\n[rate_limit]\nrequests_per_second = 50\n",
+        )
+        .unwrap();
+        let path = dir.path().join("mcp-guard.toml");
+        std::fs::write(&path, "").unwrap();
+        let document = json!({
+            "include": ["shared/audit.yaml", "prod.toml"],
+            "rate_limit": {"requests_per_second": 100},
+        });
+
+        let merged = resolve_includes(document, &path).unwrap();
+        assert_eq!(merged["server"]["host"], "0.0.0.0");
+        assert_eq!(merged["server"]["port"], 443);
+        assert_eq!(merged["audit"]["enabled"], true);
+        assert_eq!(merged["rate_limit"]["requests_per_second"], 100);
+        assert!(merged.get(INCLUDE_KEY).is_none());
+    }
+
+    #[test]
+    fn test_resolve_includes_errors() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.toml");
+        let b = dir.path().join("b.toml");
+        std::fs::write(&a, "include = [\"b.toml\"]\n").unwrap();
+        std::fs::write(&b, "include = [\"a.toml\"]\n").unwrap();
+
+        let err = resolve_includes(json!({"include": ["b.toml"]}), &a).unwrap_err();
+        assert!(err.to_string().contains("include cycle"));
+
+        let err = resolve_includes(json!({"include": ["missing.toml"]}), &a).unwrap_err();
+        assert!(err.to_string().contains("missing.toml"));
+
+        let err = resolve_includes(json!({"include": "b.toml"}), &a).unwrap_err();
+        assert!(err.to_string().contains("list of file paths"));
+    }
+}
@@ -0,0 +1,446 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Security linting for configuration files
+//!
+//! `Config::validate` rejects configs that cannot work; the linter flags configs
+//! that work but are unsafe to deploy. Each finding names the offending key,
+//! explains the risk and, where a safe rewrite exists, carries a [`LintFix`]
+//! that [`apply_lint_fixes`] can write back to a TOML file without disturbing
+//! comments or formatting.
+
+use std::fmt;
+
+use super::{Config, ConfigError};
+
+/// Proxy addresses trusted by the mTLS fix: a reverse proxy on the same host
+const LOOPBACK_PROXY_IPS: &[&str] = &["127.0.0.1", "::1"];
+
+// ============================================================================
+// Findings
+// ============================================================================
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    /// Risky but sometimes intentional
+    Warning,
+    /// Exposes credentials or lets callers bypass authentication
+    Error,
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintSeverity::Warning => write!(f, "warning"),
+            LintSeverity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Replacement value for a config key
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintFix {
+    /// Set a string key
+    SetString {
+        key: &'static [&'static str],
+        value: String,
+    },
+    /// Set a string array key
+    SetStringArray {
+        key: &'static [&'static str],
+        value: Vec<String>,
+    },
+}
+
+impl fmt::Display for LintFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintFix::SetString { key, value } => write!(f, "set {} = {:?}", key.join("."), value),
+            LintFix::SetStringArray { key, value } => {
+                write!(f, "set {} = {:?}", key.join("."), value)
+            }
+        }
+    }
+}
+
+/// A single lint result
+#[derive(Debug, Clone)]
+pub struct LintFinding {
+    /// Stable rule identifier (e.g. "public-bind-without-tls")
+    pub rule: &'static str,
+    pub severity: LintSeverity,
+    /// Config key the finding refers to
+    pub key: String,
+    /// What is wrong and why it matters
+    pub message: String,
+    /// How to address it by hand
+    pub suggestion: String,
+    /// Automatic rewrite applied by `--fix`, if one is safe
+    pub fix: Option<LintFix>,
+}
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+/// Check a parsed config for insecure settings
+///
+/// Findings are ordered most severe first.
+pub fn lint_config(config: &Config) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+
+    lint_oauth_redirect_uri(config, &mut findings);
+    lint_mtls_trusted_proxies(config, &mut findings);
+    lint_public_bind(config, &mut findings);
+    lint_allowed_tools(config, &mut findings);
+
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    findings
+}
+
+/// OAuth codes sent to a plain-HTTP callback can be intercepted
+fn lint_oauth_redirect_uri(config: &Config, findings: &mut Vec<LintFinding>) {
+    let Some(ref oauth) = config.auth.oauth else {
+        return;
+    };
+    let Some(rest) = oauth.redirect_uri.strip_prefix("http://") else {
+        return;
+    };
+    if is_loopback_url(&oauth.redirect_uri) {
+        return;
+    }
+
+    findings.push(LintFinding {
+        rule: "oauth-http-redirect-uri",
+        severity: LintSeverity::Error,
+        key: "auth.oauth.redirect_uri".to_string(),
+        message: format!(
+            "redirect_uri '{}' uses plain HTTP; authorization codes can be intercepted in transit",
+            oauth.redirect_uri
+        ),
+        suggestion: "serve the callback over HTTPS and register the https:// URI with the provider"
+            .to_string(),
+        fix: Some(LintFix::SetString {
+            key: &["auth", "oauth", "redirect_uri"],
+            value: format!("https://{}", rest),
+        }),
+    });
+}
+
+/// Without trusted proxies, any client can forge the client-certificate headers
+fn lint_mtls_trusted_proxies(config: &Config, findings: &mut Vec<LintFinding>) {
+    let Some(ref mtls) = config.auth.mtls else {
+        return;
+    };
+    if !mtls.enabled || !mtls.trusted_proxy_ips.is_empty() {
+        return;
+    }
+
+    findings.push(LintFinding {
+        rule: "mtls-untrusted-proxy",
+        severity: LintSeverity::Error,
+        key: "auth.mtls.trusted_proxy_ips".to_string(),
+        message: "mTLS is enabled but no trusted proxies are set; any client could spoof \
+                  certificate headers"
+            .to_string(),
+        suggestion: "list the addresses of the reverse proxy that terminates TLS".to_string(),
+        fix: Some(LintFix::SetStringArray {
+            key: &["auth", "mtls", "trusted_proxy_ips"],
+            value: LOOPBACK_PROXY_IPS.iter().map(|s| s.to_string()).collect(),
+        }),
+    });
+}
+
+/// Listening on all interfaces without TLS sends credentials in cleartext
+fn lint_public_bind(config: &Config, findings: &mut Vec<LintFinding>) {
+    let host = config.server.host.as_str();
+    if !matches!(host, "0.0.0.0" | "::" | "[::]") || config.server.tls.is_some() {
+        return;
+    }
+
+    findings.push(LintFinding {
+        rule: "public-bind-without-tls",
+        severity: LintSeverity::Warning,
+        key: "server.host".to_string(),
+        message: format!(
+            "binding to {} without [server.tls] exposes API keys and tokens in cleartext",
+            host
+        ),
+        suggestion: "configure [server.tls], or bind to 127.0.0.1 behind a TLS-terminating proxy"
+            .to_string(),
+        fix: Some(LintFix::SetString {
+            key: &["server", "host"],
+            value: "127.0.0.1".to_string(),
+        }),
+    });
+}
+
+/// Keys and scopes that grant every tool defeat per-tool authorization
+fn lint_allowed_tools(config: &Config, findings: &mut Vec<LintFinding>) {
+    for (i, key) in config.auth.api_keys.iter().enumerate() {
+        let unrestricted = key.allowed_tools.is_empty();
+        if unrestricted || key.allowed_tools.iter().any(|t| t == "*") {
+            findings.push(LintFinding {
+                rule: "broad-allowed-tools",
+                severity: LintSeverity::Warning,
+                key: format!("auth.api_keys[{}].allowed_tools", i),
+                message: format!(
+                    "API key '{}' may call every tool ({})",
+                    key.id,
+                    if unrestricted {
+                        "allowed_tools is empty"
+                    } else {
+                        "allowed_tools contains \"*\""
+                    }
+                ),
+                suggestion: "list only the tools this key needs".to_string(),
+                fix: None,
+            });
+        }
+    }
+
+    let mut scope_mappings = Vec::new();
+    for (i, jwt) in config.auth.jwt.iter().enumerate() {
+        let section = if config.auth.jwt.len() == 1 {
+            "auth.jwt".to_string()
+        } else {
+            format!("auth.jwt[{}]", i)
+        };
+        scope_mappings.push((section, &jwt.scope_tool_mapping));
+    }
+    if let Some(ref oauth) = config.auth.oauth {
+        scope_mappings.push(("auth.oauth".to_string(), &oauth.scope_tool_mapping));
+    }
+    if let Some(ref saml) = config.auth.saml {
+        scope_mappings.push(("auth.saml".to_string(), &saml.scope_tool_mapping));
+    }
+    for (section, mapping) in scope_mappings {
+        let mut scopes: Vec<&String> = mapping
+            .iter()
+            .filter(|(_, tools)| tools.iter().any(|t| t == "*"))
+            .map(|(scope, _)| scope)
+            .collect();
+        scopes.sort();
+        for scope in scopes {
+            findings.push(LintFinding {
+                rule: "broad-allowed-tools",
+                severity: LintSeverity::Warning,
+                key: format!("{}.scope_tool_mapping.\"{}\"", section, scope),
+                message: format!("scope '{}' grants every tool", scope),
+                suggestion: "map the scope to the specific tools it should unlock".to_string(),
+                fix: None,
+            });
+        }
+    }
+}
+
+fn is_loopback_url(url: &str) -> bool {
+    match url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host().map(|h| h.to_owned()))
+    {
+        Some(url::Host::Domain(domain)) => domain == "localhost",
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+// ============================================================================
+// Fixes
+// ============================================================================
+
+/// Apply every available fix to TOML config content
+///
+/// Uses toml_edit so comments and formatting in the original file survive.
+/// Returns the rewritten content and the number of fixes applied.
+pub fn apply_lint_fixes(
+    content: &str,
+    findings: &[LintFinding],
+) -> Result<(String, usize), ConfigError> {
+    use toml_edit::{Array, DocumentMut};
+
+    let mut doc: DocumentMut = content
+        .parse()
+        .map_err(|e: toml_edit::TomlError| ConfigError::Parse(e.to_string()))?;
+
+    let mut applied = 0;
+    for fix in findings.iter().filter_map(|f| f.fix.as_ref()) {
+        let (key, value) = match fix {
+            LintFix::SetString { key, value } => (key, toml_edit::value(value.as_str())),
+            LintFix::SetStringArray { key, value } => {
+                let array: Array = value.iter().map(String::as_str).collect();
+                (key, toml_edit::value(array))
+            }
+        };
+
+        let Some((last, parents)) = key.split_last() else {
+            continue;
+        };
+        let mut item = doc.as_item_mut();
+        for part in parents {
+            item = &mut item[part];
+        }
+        item[last] = value;
+        applied += 1;
+    }
+
+    Ok((doc.to_string(), applied))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    const INSECURE_CONFIG: &str = r#"
+# Production gateway
+[server]
+host = "0.0.0.0"
+port = 3000
+
+[upstream]
+transport = "stdio"
+command = "echo"
+
+[[auth.api_keys]]
+id = "ci"
+key_hash = "abc"
+allowed_tools = ["*"]
+
+[auth.oauth]
+provider = "github"
+client_id = "id"
+redirect_uri = "http://gateway.example.com/oauth/callback"
+
+[auth.mtls]
+enabled = true
+"#;
+
+    fn rules(findings: &[LintFinding]) -> Vec<&str> {
+        findings.iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn test_lint_detects_insecure_settings() {
+        let findings = lint_config(&parse(INSECURE_CONFIG));
+
+        assert_eq!(
+            rules(&findings),
+            vec![
+                "oauth-http-redirect-uri",
+                "mtls-untrusted-proxy",
+                "public-bind-without-tls",
+                "broad-allowed-tools",
+            ]
+        );
+        assert_eq!(findings[0].severity, LintSeverity::Error);
+        assert_eq!(findings[3].key, "auth.api_keys[0].allowed_tools");
+        assert!(findings[3].fix.is_none());
+    }
+
+    #[test]
+    fn test_lint_clean_config() {
+        let findings = lint_config(&parse(
+            r#"
+[server]
+host = "127.0.0.1"
+
+[upstream]
+transport = "stdio"
+command = "echo"
+
+[[auth.api_keys]]
+id = "ci"
+key_hash = "abc"
+allowed_tools = ["read_file"]
+
+[auth.oauth]
+provider = "github"
+client_id = "id"
+redirect_uri = "http://localhost:3000/oauth/callback"
+"#,
+        ));
+        assert!(findings.is_empty(), "unexpected findings: {:?}", findings);
+    }
+
+    #[test]
+    fn test_lint_public_bind_with_tls_allowed() {
+        let findings = lint_config(&parse(
+            r#"
+[server]
+host = "0.0.0.0"
+
+[server.tls]
+cert_path = "/etc/mcp-guard/cert.pem"
+key_path = "/etc/mcp-guard/key.pem"
+
+[upstream]
+transport = "stdio"
+command = "echo"
+"#,
+        ));
+        assert!(!rules(&findings).contains(&"public-bind-without-tls"));
+    }
+
+    #[test]
+    fn test_lint_wildcard_scope_mapping() {
+        let findings = lint_config(&parse(
+            r#"
+[upstream]
+transport = "stdio"
+command = "echo"
+
+[auth.jwt]
+mode = "simple"
+secret = "a-very-long-secret-that-is-at-least-32-characters"
+issuer = "issuer"
+audience = "audience"
+
+[auth.jwt.scope_tool_mapping]
+"admin" = ["*"]
+"read" = ["read_file"]
+"#,
+        ));
+        assert_eq!(rules(&findings), vec!["broad-allowed-tools"]);
+        assert_eq!(findings[0].key, "auth.jwt.scope_tool_mapping.\"admin\"");
+    }
+
+    #[test]
+    fn test_apply_fixes_preserves_comments() {
+        let findings = lint_config(&parse(INSECURE_CONFIG));
+        let (fixed, applied) = apply_lint_fixes(INSECURE_CONFIG, &findings).unwrap();
+
+        assert_eq!(applied, 3);
+        assert!(fixed.contains("# Production gateway"));
+        assert!(fixed.contains(r#"host = "127.0.0.1""#));
+        assert!(fixed.contains(r#"redirect_uri = "https://gateway.example.com/oauth/callback""#));
+        assert!(fixed.contains(r#"trusted_proxy_ips = ["127.0.0.1", "::1"]"#));
+
+        // Only the unfixable finding remains
+        let remaining = lint_config(&parse(&fixed));
+        assert_eq!(rules(&remaining), vec!["broad-allowed-tools"]);
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! JSON Schema export and unknown-key detection for configuration files
+//!
+//! serde ignores keys it does not recognize, so a misspelled `prot` or
+//! `rate_limt` silently falls back to the default. [`find_unknown_keys`]
+//! re-parses the file, collects every ignored key and suggests the closest
+//! known key from the [`config_schema`] at the same position.
+//!
+//! Keys inside `#[serde(flatten)]` sections (e.g. the JWT mode fields) are
+//! buffered by serde and cannot be reported.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::{is_yaml_path, Config, ConfigError};
+
+/// Minimum Jaro-Winkler similarity for a "did you mean" suggestion
+const SUGGESTION_THRESHOLD: f64 = 0.8;
+
+/// JSON Schema for the full [`Config`] type
+pub fn config_schema() -> Value {
+    let schema = schemars::schema_for!(Config);
+    serde_json::to_value(schema).unwrap_or(Value::Null)
+}
+
+// ============================================================================
+// Unknown Keys
+// ============================================================================
+
+/// A config key that serde ignored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKey {
+    /// Full key path (e.g. "auth.api_keys[0].alowed_tools")
+    pub key: String,
+    /// Closest known key at the same position, if any is similar enough
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown key '{}'", self.key)?;
+        if let Some(ref suggestion) = self.suggestion {
+            write!(f, " (did you mean '{}'?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Find keys in a config file that do not map to any config field
+pub fn find_unknown_keys(path: impl AsRef<Path>) -> Result<Vec<UnknownKey>, ConfigError> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)?;
+    unknown_keys_in(&content, is_yaml_path(path))
+}
+
+fn unknown_keys_in(content: &str, yaml: bool) -> Result<Vec<UnknownKey>, ConfigError> {
+    let mut ignored: Vec<Vec<Segment>> = Vec::new();
+    let mut record = |path: serde_ignored::Path| ignored.push(segments(&path));
+
+    if yaml {
+        let deserializer = serde_yaml::Deserializer::from_str(content);
+        serde_ignored::deserialize::<_, _, Config>(deserializer, &mut record)
+            .map_err(|e| ConfigError::Parse(e.to_string()))?;
+    } else {
+        let deserializer = toml::Deserializer::new(content);
+        serde_ignored::deserialize::<_, _, Config>(deserializer, &mut record)
+            .map_err(|e| ConfigError::Parse(e.to_string()))?;
+    }
+
+    let schema = config_schema();
+    Ok(ignored
+        .into_iter()
+        .map(|path| {
+            let suggestion = match path.split_last() {
+                Some((Segment::Key(key), parent)) => suggest(&schema, parent, key),
+                _ => None,
+            };
+            UnknownKey {
+                key: format_path(&path),
+                suggestion,
+            }
+        })
+        .collect())
+}
+
+fn segments(path: &serde_ignored::Path) -> Vec<Segment> {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => Vec::new(),
+        Path::Seq { parent, index } => {
+            let mut segments = segments(parent);
+            segments.push(Segment::Index(*index));
+            segments
+        }
+        Path::Map { parent, key } => {
+            let mut segments = segments(parent);
+            segments.push(Segment::Key(key.clone()));
+            segments
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => segments(parent),
+    }
+}
+
+fn format_path(path: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push_str(key);
+            }
+            Segment::Index(index) => out.push_str(&format!("[{}]", index)),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// Schema Lookup
+// ============================================================================
+
+/// Suggest the known key at `parent` most similar to `key`
+fn suggest(root: &Value, parent: &[Segment], key: &str) -> Option<String> {
+    let mut schemas = vec![root];
+    for segment in parent {
+        schemas = schemas
+            .into_iter()
+            .flat_map(|schema| branches(root, schema))
+            .filter_map(|schema| match segment {
+                Segment::Key(key) => schema
+                    .get("properties")
+                    .and_then(|p| p.get(key))
+                    .or_else(|| schema.get("additionalProperties")),
+                Segment::Index(_) => schema.get("items"),
+            })
+            .collect();
+    }
+
+    let known: BTreeSet<&str> = schemas
+        .into_iter()
+        .flat_map(|schema| branches(root, schema))
+        .filter_map(|schema| schema.get("properties").and_then(|p| p.as_object()))
+        .flat_map(|properties| properties.keys().map(String::as_str))
+        .collect();
+
+    known
+        .into_iter()
+        .map(|candidate| (candidate, strsim::jaro_winkler(key, candidate)))
+        .filter(|(_, score)| *score >= SUGGESTION_THRESHOLD)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(candidate, _)| candidate.to_string())
+}
+
+/// Resolve `$ref`s and expand `allOf`/`anyOf`/`oneOf` into concrete schemas
+fn branches<'a>(root: &'a Value, schema: &'a Value) -> Vec<&'a Value> {
+    let mut out = Vec::new();
+    let mut stack = vec![schema];
+    while let Some(schema) = stack.pop() {
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            if let Some(target) = reference
+                .strip_prefix("#/definitions/")
+                .and_then(|name| root.get("definitions")?.get(name))
+            {
+                stack.push(target);
+            }
+            continue;
+        }
+        for combinator in ["allOf", "anyOf", "oneOf"] {
+            if let Some(subschemas) = schema.get(combinator).and_then(|s| s.as_array()) {
+                stack.extend(subschemas);
+            }
+        }
+        out.push(schema);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_schema_covers_sections() {
+        let schema = config_schema();
+        let properties = schema["properties"].as_object().unwrap();
+        for section in [
+            "server",
+            "auth",
+            "rate_limit",
+            "audit",
+            "upstream",
+            "logging",
+        ] {
+            assert!(properties.contains_key(section), "missing {}", section);
+        }
+        assert!(schema["required"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::from("upstream")));
+    }
+
+    #[test]
+    fn test_unknown_keys_with_suggestions() {
+        let unknown = unknown_keys_in(
+            r#"
+            [server]
+            prot = 3000
+
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+
+            [[auth.api_keys]]
+            id = "a"
+            key_hash = "h"
+            alowed_tools = ["read"]
+
+            [rate_limt]
+            enabled = false
+
+            [custom]
+            x = 1
+            "#,
+            false,
+        )
+        .unwrap();
+
+        let by_key = |key: &str| unknown.iter().find(|u| u.key == key).cloned();
+        assert_eq!(
+            by_key("server.prot").unwrap().suggestion.as_deref(),
+            Some("port")
+        );
+        assert_eq!(
+            by_key("auth.api_keys[0].alowed_tools")
+                .unwrap()
+                .suggestion
+                .as_deref(),
+            Some("allowed_tools")
+        );
+        assert_eq!(
+            by_key("rate_limt").unwrap().suggestion.as_deref(),
+            Some("rate_limit")
+        );
+        assert_eq!(by_key("custom").unwrap().suggestion, None);
+        assert_eq!(
+            by_key("server.prot").unwrap().to_string(),
+            "unknown key 'server.prot' (did you mean 'port'?)"
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_yaml_and_clean_config() {
+        let unknown = unknown_keys_in(
+            "upstream:\n  transport: stdio\n  command: echo\n  comand: echo\n",
+            true,
+        )
+        .unwrap();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].key, "upstream.comand");
+        assert_eq!(unknown[0].suggestion.as_deref(), Some("command"));
+
+        let unknown = unknown_keys_in(
+            "[upstream]\ntransport = \"stdio\"\ncommand = \"echo\"\n",
+            false,
+        )
+        .unwrap();
+        assert!(unknown.is_empty());
+    }
+}
@@ -1,8 +1,38 @@
-use sqlx::postgres::PgPoolOptions;
-use sqlx::PgPool;
-use uuid::Uuid;
+//! Persistent storage
+//!
+//! Every query the gateway makes goes through the [`Storage`] trait, which
+//! covers API keys, sessions (OAuth clients and refresh tokens), quota usage,
+//! the audit log and dynamic routes. [`Database::connect`] picks the backend
+//! from the URL scheme in the `[database]` config section:
+//!
+//! - `postgres://` / `postgresql://` - [`PostgresStorage`]
+//! - `sqlite:` - [`SqliteStorage`]
+//!
+//! The repository types returned by [`Database`] are thin wrappers over the
+//! shared storage handle and are cheap to clone.
+//!
+//! Each backend embeds its own migrations (`migrations/postgres` and
+//! `migrations/sqlite`). They are applied on connect unless
+//! `database.migrate = false`, and can be managed with `mcp-guard db`.
+
+mod postgres;
+mod sqlite;
+
+pub use postgres::PostgresStorage;
+pub use sqlite::SqliteStorage;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::Pool;
+use uuid::Uuid;
+
+use crate::audit::{AuditEntry, AuditSeverity, EventType};
+use crate::config::DatabaseConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct DbUser {
@@ -26,76 +56,644 @@ pub struct DbApiKey {
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DbRevokedToken {
+    pub jti: Option<String>,
+    pub subject: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DbOAuthClient {
+    pub client_id: String,
+    pub client_secret_hash: Option<String>,
+    pub client_name: Option<String>,
+    pub redirect_uris: serde_json::Value,
+    pub metadata: serde_json::Value,
+    pub client_secret_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DbRefreshToken {
+    pub subject: String,
+    pub login: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A route added at runtime rather than through the config file
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DbRoute {
+    pub name: String,
+    /// Route definition, in the same shape as an `[[upstream.servers]]` entry
+    pub config: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An OAuth authorization code flow waiting for its callback
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DbOAuthState {
+    pub state: String,
+    pub code_verifier: String,
+    pub client_ip: String,
+    pub redirect_uri: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A server-side OAuth session, keyed by session token hash
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DbOAuthSession {
+    pub token_hash: String,
+    pub identity_id: String,
+    pub identity: serde_json::Value,
+    pub refresh_token: Option<String>,
+    pub access_expires_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The activation of a break-glass key
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DbBreakGlassActivation {
+    pub identity_id: String,
+    pub justification: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Fields of a client registered through `/oauth/register`
+pub struct NewOAuthClient<'a> {
+    pub client_id: &'a str,
+    pub client_secret_hash: Option<&'a str>,
+    pub client_name: Option<&'a str>,
+    pub redirect_uris: &'a serde_json::Value,
+    pub metadata: &'a serde_json::Value,
+    pub client_secret_expires_at: Option<DateTime<Utc>>,
+}
+
+/// Filter for [`Storage::query_audit`]; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    /// Only entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries before this time
+    pub until: Option<DateTime<Utc>>,
+    pub identity_id: Option<String>,
+    /// Maximum number of entries to return, newest first
+    pub limit: Option<i64>,
+}
+
+/// A row of the `audit_log` table
+#[derive(Debug, sqlx::FromRow)]
+struct AuditRow {
+    timestamp: DateTime<Utc>,
+    event_type: String,
+    identity_id: Option<String>,
+    method: Option<String>,
+    tool: Option<String>,
+    success: bool,
+    message: Option<String>,
+    duration_ms: Option<i64>,
+    request_id: Option<String>,
+    tenant: Option<String>,
+    severity: Option<String>,
+    /// [`AuthzRule`](crate::authz::AuthzRule) as JSON
+    authz_rule: Option<String>,
+}
+
+impl AuditRow {
+    fn into_entry(self) -> Result<AuditEntry, sqlx::Error> {
+        let event_type: EventType = from_column(&self.event_type)?;
+        let severity: Option<AuditSeverity> =
+            self.severity.as_deref().map(from_column).transpose()?;
+        let authz_rule = self
+            .authz_rule
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+        Ok(AuditEntry {
+            timestamp: self.timestamp,
+            event_type,
+            identity_id: self.identity_id,
+            method: self.method,
+            tool: self.tool,
+            success: self.success,
+            message: self.message,
+            duration_ms: self.duration_ms.map(|ms| ms.max(0) as u64),
+            request_id: self.request_id,
+            tenant: self.tenant,
+            severity,
+            authz_rule,
+        })
+    }
+}
+
+/// Name an enum is stored under, e.g. `EventType::ToolCall` -> `tool_call`
+fn to_column<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+fn from_column<T: serde::de::DeserializeOwned>(value: &str) -> Result<T, sqlx::Error> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+/// A migration embedded in this build and whether the database has it
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+    /// Applied, but from a different version of the migration file
+    pub checksum_mismatch: bool,
+}
+
+/// Tables created by the migrations, in the order `reset` drops them
+const TABLES: &[&str] = &[
+    "refresh_tokens",
+    "oauth_clients",
+    "revoked_tokens",
+    "api_keys",
+    "users",
+    "oauth_states",
+    "oauth_sessions",
+    "quota_usage",
+    "audit_log",
+    "routes",
+    "break_glass_activations",
+    "_sqlx_migrations",
+];
+
+/// Compare `migrator` against the migrations recorded in the database,
+/// creating the bookkeeping table if it does not exist yet
+async fn migration_status<DB>(
+    migrator: &Migrator,
+    pool: &Pool<DB>,
+) -> Result<Vec<MigrationStatus>, sqlx::Error>
+where
+    DB: sqlx::Database,
+    DB::Connection: Migrate,
+{
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied: HashMap<i64, Vec<u8>> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| (migration.version, migration.checksum.into_owned()))
+        .collect();
+
+    Ok(migrator
+        .iter()
+        .filter(|migration| migration.migration_type.is_up_migration())
+        .map(|migration| {
+            let checksum = applied.get(&migration.version);
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                applied: checksum.is_some(),
+                checksum_mismatch: checksum.is_some_and(|c| c[..] != migration.checksum[..]),
+            }
+        })
+        .collect())
+}
+
+/// Persistence operations shared by every database backend
+#[async_trait]
+pub trait Storage: Send + Sync {
+    // Schema
+
+    /// Apply pending migrations
+    async fn migrate(&self) -> Result<(), sqlx::Error>;
+
+    /// Every embedded migration and whether it has been applied
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, sqlx::Error>;
+
+    /// Drop every table and apply all migrations again, deleting all data
+    async fn reset(&self) -> Result<(), sqlx::Error>;
+
+    // Users
+
+    async fn create_user(&self, id: &str, email: &str, role: &str) -> Result<DbUser, sqlx::Error>;
+
+    async fn find_user(&self, id: &str) -> Result<Option<DbUser>, sqlx::Error>;
+
+    // Keys
+
+    async fn find_api_key(&self, key_hash: &str) -> Result<Option<DbApiKey>, sqlx::Error>;
+
+    /// Revocations that have not yet passed their `expires_at`
+    async fn list_active_revocations(&self) -> Result<Vec<DbRevokedToken>, sqlx::Error>;
+
+    // Sessions
+
+    async fn create_oauth_client(
+        &self,
+        client: NewOAuthClient<'_>,
+    ) -> Result<DbOAuthClient, sqlx::Error>;
+
+    async fn find_oauth_client(
+        &self,
+        client_id: &str,
+    ) -> Result<Option<DbOAuthClient>, sqlx::Error>;
+
+    async fn count_oauth_clients(&self) -> Result<i64, sqlx::Error>;
+
+    async fn create_refresh_token(
+        &self,
+        token_hash: &str,
+        subject: &str,
+        login: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Mark an unused, unexpired token as used and return it
+    async fn consume_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<DbRefreshToken>, sqlx::Error>;
+
+    async fn put_oauth_state(&self, state: &DbOAuthState) -> Result<(), sqlx::Error>;
+
+    /// Remove a pending OAuth flow and return it
+    async fn take_oauth_state(&self, state: &str) -> Result<Option<DbOAuthState>, sqlx::Error>;
+
+    async fn count_oauth_states(&self) -> Result<i64, sqlx::Error>;
+
+    /// Remove OAuth flows started before `before`; returns how many
+    async fn purge_oauth_states(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error>;
+
+    /// Create or replace an OAuth session
+    async fn put_oauth_session(&self, session: &DbOAuthSession) -> Result<(), sqlx::Error>;
+
+    async fn find_oauth_session(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<DbOAuthSession>, sqlx::Error>;
+
+    /// Returns false if there was no such session
+    async fn delete_oauth_session(&self, token_hash: &str) -> Result<bool, sqlx::Error>;
+
+    /// End every OAuth session of an identity; returns how many
+    async fn delete_identity_oauth_sessions(&self, identity_id: &str) -> Result<u64, sqlx::Error>;
+
+    async fn count_oauth_sessions(&self) -> Result<i64, sqlx::Error>;
+
+    /// Remove OAuth sessions past `expires_at`; returns how many
+    async fn purge_oauth_sessions(&self) -> Result<u64, sqlx::Error>;
+
+    // Quotas
+
+    /// Add `amount` to an identity's usage for the window starting at
+    /// `window_start` and return the new total
+    async fn add_quota_usage(
+        &self,
+        identity_id: &str,
+        window_start: DateTime<Utc>,
+        amount: i64,
+    ) -> Result<i64, sqlx::Error>;
+
+    /// Usage recorded for the window starting at `window_start` (0 if none)
+    async fn quota_usage(
+        &self,
+        identity_id: &str,
+        window_start: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error>;
+
+    /// Remove usage for windows starting before `before`, for identities
+    /// starting with `key_prefix`; returns how many
+    async fn purge_quota_usage(
+        &self,
+        key_prefix: &str,
+        before: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error>;
+
+    // Break glass
+
+    /// Record the activation unless the key already has one; returns false
+    /// if it did
+    async fn insert_break_glass_activation(
+        &self,
+        activation: &DbBreakGlassActivation,
+    ) -> Result<bool, sqlx::Error>;
+
+    async fn find_break_glass_activation(
+        &self,
+        identity_id: &str,
+    ) -> Result<Option<DbBreakGlassActivation>, sqlx::Error>;
+
+    /// Returns false if the key has no activation or already had that
+    /// justification
+    async fn update_break_glass_justification(
+        &self,
+        identity_id: &str,
+        justification: &str,
+    ) -> Result<bool, sqlx::Error>;
+
+    // Audit
+
+    async fn insert_audit_entries(&self, entries: &[AuditEntry]) -> Result<(), sqlx::Error>;
+
+    async fn query_audit(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, sqlx::Error>;
+
+    // Dynamic routes
+
+    async fn list_routes(&self) -> Result<Vec<DbRoute>, sqlx::Error>;
+
+    /// Create the route or replace its config
+    async fn upsert_route(
+        &self,
+        name: &str,
+        config: &serde_json::Value,
+    ) -> Result<DbRoute, sqlx::Error>;
+
+    /// Returns false if no route had that name
+    async fn delete_route(&self, name: &str) -> Result<bool, sqlx::Error>;
+}
+
 #[derive(Clone)]
 pub struct Database {
-    pool: PgPool,
+    storage: Arc<dyn Storage>,
 }
 
 impl Database {
+    /// Connect with the default pool size and run migrations
     pub async fn new(database_url: &str) -> Result<Self, sqlx::Error> {
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect(database_url)
-            .await?;
-        
-        // Run migrations
-        sqlx::migrate!("./migrations").run(&pool).await?;
+        Self::connect(&DatabaseConfig::from_url(database_url)).await
+    }
+
+    /// Connect to the backend named by the URL scheme, running migrations
+    /// first when `migrate` is set
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let storage: Arc<dyn Storage> = if is_postgres_url(&config.url) {
+            Arc::new(PostgresStorage::connect(config).await?)
+        } else if is_sqlite_url(&config.url) {
+            Arc::new(SqliteStorage::connect(config).await?)
+        } else {
+            return Err(sqlx::Error::Configuration(
+                "database URL must start with postgres://, postgresql:// or sqlite:".into(),
+            ));
+        };
+
+        Ok(Self { storage })
+    }
+
+    /// Wrap an existing backend
+    pub fn from_storage(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
 
-        Ok(Self { pool })
+    pub fn storage(&self) -> Arc<dyn Storage> {
+        self.storage.clone()
+    }
+
+    /// Apply pending migrations
+    pub async fn migrate(&self) -> Result<(), sqlx::Error> {
+        self.storage.migrate().await
+    }
+
+    /// Every embedded migration and whether it has been applied
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+        self.storage.migration_status().await
+    }
+
+    /// Drop every table and apply all migrations again, deleting all data
+    pub async fn reset(&self) -> Result<(), sqlx::Error> {
+        self.storage.reset().await
     }
 
     pub fn users(&self) -> UserRepository {
-        UserRepository { pool: self.pool.clone() }
+        UserRepository {
+            storage: self.storage.clone(),
+        }
     }
 
     pub fn api_keys(&self) -> ApiKeyRepository {
-        ApiKeyRepository { pool: self.pool.clone() }
+        ApiKeyRepository {
+            storage: self.storage.clone(),
+        }
+    }
+
+    pub fn revoked_tokens(&self) -> RevokedTokenRepository {
+        RevokedTokenRepository {
+            storage: self.storage.clone(),
+        }
+    }
+
+    pub fn oauth_clients(&self) -> OAuthClientRepository {
+        OAuthClientRepository {
+            storage: self.storage.clone(),
+        }
+    }
+
+    pub fn refresh_tokens(&self) -> RefreshTokenRepository {
+        RefreshTokenRepository {
+            storage: self.storage.clone(),
+        }
+    }
+
+    pub fn quotas(&self) -> QuotaRepository {
+        QuotaRepository {
+            storage: self.storage.clone(),
+        }
     }
+
+    pub fn audit_log(&self) -> AuditLogRepository {
+        AuditLogRepository {
+            storage: self.storage.clone(),
+        }
+    }
+
+    pub fn routes(&self) -> RouteRepository {
+        RouteRepository {
+            storage: self.storage.clone(),
+        }
+    }
+}
+
+pub(crate) fn is_postgres_url(url: &str) -> bool {
+    url.starts_with("postgres://") || url.starts_with("postgresql://")
 }
 
+pub(crate) fn is_sqlite_url(url: &str) -> bool {
+    url.starts_with("sqlite:")
+}
+
+#[derive(Clone)]
 pub struct UserRepository {
-    pool: PgPool,
+    storage: Arc<dyn Storage>,
 }
 
 impl UserRepository {
     pub async fn create(&self, id: &str, email: &str, role: &str) -> Result<DbUser, sqlx::Error> {
-        let user = sqlx::query_as::<_, DbUser>(
-            r#"
-            INSERT INTO users (id, email, role)
-            VALUES ($1, $2, $3)
-            RETURNING id, email, name, role, created_at, updated_at
-            "#
-        )
-        .bind(id)
-        .bind(email)
-        .bind(role)
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(user)
+        self.storage.create_user(id, email, role).await
     }
 
     pub async fn find_by_id(&self, id: &str) -> Result<Option<DbUser>, sqlx::Error> {
-        sqlx::query_as::<_, DbUser>(
-            r#"SELECT id, email, name, role, created_at, updated_at FROM users WHERE id = $1"#
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await
+        self.storage.find_user(id).await
     }
 }
 
+#[derive(Clone)]
 pub struct ApiKeyRepository {
-    pool: PgPool,
+    storage: Arc<dyn Storage>,
 }
 
 impl ApiKeyRepository {
     pub async fn find_by_hash(&self, hash: &str) -> Result<Option<DbApiKey>, sqlx::Error> {
-        sqlx::query_as::<_, DbApiKey>(
-            r#"SELECT id, user_id, key_hash, name, allowed_tools, rate_limit, expires_at, created_at FROM api_keys WHERE key_hash = $1"#
-        )
-        .bind(hash)
-        .fetch_optional(&self.pool)
-        .await
+        self.storage.find_api_key(hash).await
+    }
+}
+
+#[derive(Clone)]
+pub struct RevokedTokenRepository {
+    storage: Arc<dyn Storage>,
+}
+
+impl RevokedTokenRepository {
+    /// Revocations that have not yet passed their `expires_at`
+    pub async fn list_active(&self) -> Result<Vec<DbRevokedToken>, sqlx::Error> {
+        self.storage.list_active_revocations().await
+    }
+}
+
+#[derive(Clone)]
+pub struct OAuthClientRepository {
+    storage: Arc<dyn Storage>,
+}
+
+impl OAuthClientRepository {
+    pub async fn create(&self, client: NewOAuthClient<'_>) -> Result<DbOAuthClient, sqlx::Error> {
+        self.storage.create_oauth_client(client).await
+    }
+
+    pub async fn find_by_id(&self, client_id: &str) -> Result<Option<DbOAuthClient>, sqlx::Error> {
+        self.storage.find_oauth_client(client_id).await
+    }
+
+    pub async fn count(&self) -> Result<i64, sqlx::Error> {
+        self.storage.count_oauth_clients().await
+    }
+}
+
+#[derive(Clone)]
+pub struct RefreshTokenRepository {
+    storage: Arc<dyn Storage>,
+}
+
+impl RefreshTokenRepository {
+    pub async fn create(
+        &self,
+        token_hash: &str,
+        subject: &str,
+        login: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        self.storage
+            .create_refresh_token(token_hash, subject, login, expires_at)
+            .await
+    }
+
+    /// Mark an unused, unexpired token as used and return it
+    pub async fn consume(&self, token_hash: &str) -> Result<Option<DbRefreshToken>, sqlx::Error> {
+        self.storage.consume_refresh_token(token_hash).await
+    }
+}
+
+#[derive(Clone)]
+pub struct QuotaRepository {
+    storage: Arc<dyn Storage>,
+}
+
+impl QuotaRepository {
+    /// Add `amount` to the window's usage and return the new total
+    pub async fn add(
+        &self,
+        identity_id: &str,
+        window_start: DateTime<Utc>,
+        amount: i64,
+    ) -> Result<i64, sqlx::Error> {
+        self.storage
+            .add_quota_usage(identity_id, window_start, amount)
+            .await
+    }
+
+    pub async fn usage(
+        &self,
+        identity_id: &str,
+        window_start: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        self.storage.quota_usage(identity_id, window_start).await
+    }
+}
+
+#[derive(Clone)]
+pub struct AuditLogRepository {
+    storage: Arc<dyn Storage>,
+}
+
+impl AuditLogRepository {
+    pub async fn insert(&self, entries: &[AuditEntry]) -> Result<(), sqlx::Error> {
+        self.storage.insert_audit_entries(entries).await
+    }
+
+    pub async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, sqlx::Error> {
+        self.storage.query_audit(query).await
+    }
+}
+
+#[derive(Clone)]
+pub struct RouteRepository {
+    storage: Arc<dyn Storage>,
+}
+
+impl RouteRepository {
+    pub async fn list(&self) -> Result<Vec<DbRoute>, sqlx::Error> {
+        self.storage.list_routes().await
+    }
+
+    pub async fn upsert(
+        &self,
+        name: &str,
+        config: &serde_json::Value,
+    ) -> Result<DbRoute, sqlx::Error> {
+        self.storage.upsert_route(name, config).await
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<bool, sqlx::Error> {
+        self.storage.delete_route(name).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_url_schemes() {
+        assert!(is_postgres_url("postgres://localhost/mcp_guard"));
+        assert!(is_postgres_url("postgresql://localhost/mcp_guard"));
+        assert!(is_sqlite_url("sqlite://mcp-guard.db"));
+        assert!(is_sqlite_url("sqlite::memory:"));
+        assert!(!is_postgres_url("mysql://localhost/db"));
+        assert!(!is_sqlite_url("mysql://localhost/db"));
+    }
+
+    #[test]
+    fn test_enum_columns_round_trip() {
+        assert_eq!(to_column(&EventType::ToolCall), "tool_call");
+        assert_eq!(to_column(&AuditSeverity::High), "high");
+        let event: EventType = from_column("auth_failure").unwrap();
+        assert_eq!(event, EventType::AuthFailure);
+        assert!(from_column::<EventType>("nope").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_unknown_scheme() {
+        let err = Database::new("mysql://localhost/db").await.err().unwrap();
+        assert!(matches!(err, sqlx::Error::Configuration(_)));
     }
 }
@@ -0,0 +1,495 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! PostgreSQL storage backend
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use super::{
+    migration_status, to_column, AuditQuery, AuditRow, DbApiKey, DbBreakGlassActivation,
+    DbOAuthClient, DbOAuthSession, DbOAuthState, DbRefreshToken, DbRevokedToken, DbRoute, DbUser,
+    MigrationStatus, NewOAuthClient, Storage, TABLES,
+};
+use crate::audit::AuditEntry;
+use crate::config::DatabaseConfig;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// [`Storage`] backed by a PostgreSQL connection pool
+#[derive(Clone)]
+pub struct PostgresStorage {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.url)
+            .await?;
+
+        if config.migrate {
+            MIGRATOR.run(&pool).await?;
+        }
+
+        Ok(Self { pool })
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    async fn migrate(&self) -> Result<(), sqlx::Error> {
+        MIGRATOR.run(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+        migration_status(&MIGRATOR, &self.pool).await
+    }
+
+    async fn reset(&self) -> Result<(), sqlx::Error> {
+        for table in TABLES {
+            sqlx::query(&format!("DROP TABLE IF EXISTS {}", table))
+                .execute(&self.pool)
+                .await?;
+        }
+        self.migrate().await
+    }
+
+    async fn create_user(&self, id: &str, email: &str, role: &str) -> Result<DbUser, sqlx::Error> {
+        sqlx::query_as::<_, DbUser>(
+            r#"
+            INSERT INTO users (id, email, role)
+            VALUES ($1, $2, $3)
+            RETURNING id, email, name, role, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(email)
+        .bind(role)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn find_user(&self, id: &str) -> Result<Option<DbUser>, sqlx::Error> {
+        sqlx::query_as::<_, DbUser>(
+            r#"SELECT id, email, name, role, created_at, updated_at FROM users WHERE id = $1"#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> Result<Option<DbApiKey>, sqlx::Error> {
+        sqlx::query_as::<_, DbApiKey>(
+            r#"SELECT id, user_id, key_hash, name, allowed_tools, rate_limit, expires_at, created_at FROM api_keys WHERE key_hash = $1"#,
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn list_active_revocations(&self) -> Result<Vec<DbRevokedToken>, sqlx::Error> {
+        sqlx::query_as::<_, DbRevokedToken>(
+            r#"SELECT jti, subject FROM revoked_tokens WHERE expires_at IS NULL OR expires_at > NOW()"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn create_oauth_client(
+        &self,
+        client: NewOAuthClient<'_>,
+    ) -> Result<DbOAuthClient, sqlx::Error> {
+        sqlx::query_as::<_, DbOAuthClient>(
+            r#"
+            INSERT INTO oauth_clients (client_id, client_secret_hash, client_name, redirect_uris, metadata, client_secret_expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING client_id, client_secret_hash, client_name, redirect_uris, metadata, client_secret_expires_at, created_at
+            "#,
+        )
+        .bind(client.client_id)
+        .bind(client.client_secret_hash)
+        .bind(client.client_name)
+        .bind(client.redirect_uris)
+        .bind(client.metadata)
+        .bind(client.client_secret_expires_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn find_oauth_client(
+        &self,
+        client_id: &str,
+    ) -> Result<Option<DbOAuthClient>, sqlx::Error> {
+        sqlx::query_as::<_, DbOAuthClient>(
+            r#"SELECT client_id, client_secret_hash, client_name, redirect_uris, metadata, client_secret_expires_at, created_at FROM oauth_clients WHERE client_id = $1"#,
+        )
+        .bind(client_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn count_oauth_clients(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(r#"SELECT COUNT(*) FROM oauth_clients"#)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn create_refresh_token(
+        &self,
+        token_hash: &str,
+        subject: &str,
+        login: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"INSERT INTO refresh_tokens (token_hash, subject, login, expires_at) VALUES ($1, $2, $3, $4)"#,
+        )
+        .bind(token_hash)
+        .bind(subject)
+        .bind(login)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn consume_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<DbRefreshToken>, sqlx::Error> {
+        sqlx::query_as::<_, DbRefreshToken>(
+            r#"
+            UPDATE refresh_tokens SET used_at = NOW()
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING subject, login, expires_at
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn put_oauth_state(&self, state: &DbOAuthState) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"INSERT INTO oauth_states (state, code_verifier, client_ip, redirect_uri, created_at) VALUES ($1, $2, $3, $4, $5)"#,
+        )
+        .bind(&state.state)
+        .bind(&state.code_verifier)
+        .bind(&state.client_ip)
+        .bind(&state.redirect_uri)
+        .bind(state.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn take_oauth_state(&self, state: &str) -> Result<Option<DbOAuthState>, sqlx::Error> {
+        sqlx::query_as::<_, DbOAuthState>(
+            r#"DELETE FROM oauth_states WHERE state = $1 RETURNING state, code_verifier, client_ip, redirect_uri, created_at"#,
+        )
+        .bind(state)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn count_oauth_states(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(r#"SELECT COUNT(*) FROM oauth_states"#)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn purge_oauth_states(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM oauth_states WHERE created_at < $1"#)
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn put_oauth_session(&self, session: &DbOAuthSession) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO oauth_sessions (token_hash, identity_id, identity, refresh_token, access_expires_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (token_hash) DO UPDATE SET identity_id = EXCLUDED.identity_id, identity = EXCLUDED.identity,
+                refresh_token = EXCLUDED.refresh_token, access_expires_at = EXCLUDED.access_expires_at, expires_at = EXCLUDED.expires_at
+            "#,
+        )
+        .bind(&session.token_hash)
+        .bind(&session.identity_id)
+        .bind(&session.identity)
+        .bind(&session.refresh_token)
+        .bind(session.access_expires_at)
+        .bind(session.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_oauth_session(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<DbOAuthSession>, sqlx::Error> {
+        sqlx::query_as::<_, DbOAuthSession>(
+            r#"SELECT token_hash, identity_id, identity, refresh_token, access_expires_at, expires_at FROM oauth_sessions WHERE token_hash = $1"#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn delete_oauth_session(&self, token_hash: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM oauth_sessions WHERE token_hash = $1"#)
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_identity_oauth_sessions(&self, identity_id: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM oauth_sessions WHERE identity_id = $1"#)
+            .bind(identity_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn count_oauth_sessions(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(r#"SELECT COUNT(*) FROM oauth_sessions"#)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn purge_oauth_sessions(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM oauth_sessions WHERE expires_at <= NOW()"#)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn add_quota_usage(
+        &self,
+        identity_id: &str,
+        window_start: DateTime<Utc>,
+        amount: i64,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO quota_usage (identity_id, window_start, used)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (identity_id, window_start)
+            DO UPDATE SET used = quota_usage.used + EXCLUDED.used, updated_at = NOW()
+            RETURNING used
+            "#,
+        )
+        .bind(identity_id)
+        .bind(window_start)
+        .bind(amount)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn quota_usage(
+        &self,
+        identity_id: &str,
+        window_start: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        let used: Option<i64> = sqlx::query_scalar(
+            r#"SELECT used FROM quota_usage WHERE identity_id = $1 AND window_start = $2"#,
+        )
+        .bind(identity_id)
+        .bind(window_start)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(used.unwrap_or(0))
+    }
+
+    async fn purge_quota_usage(
+        &self,
+        key_prefix: &str,
+        before: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"DELETE FROM quota_usage WHERE identity_id LIKE $1 || '%' AND window_start < $2"#,
+        )
+        .bind(key_prefix)
+        .bind(before)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn insert_break_glass_activation(
+        &self,
+        activation: &DbBreakGlassActivation,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"INSERT INTO break_glass_activations (identity_id, justification, expires_at) VALUES ($1, $2, $3) ON CONFLICT (identity_id) DO NOTHING"#,
+        )
+        .bind(&activation.identity_id)
+        .bind(&activation.justification)
+        .bind(activation.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_break_glass_activation(
+        &self,
+        identity_id: &str,
+    ) -> Result<Option<DbBreakGlassActivation>, sqlx::Error> {
+        sqlx::query_as::<_, DbBreakGlassActivation>(
+            r#"SELECT identity_id, justification, expires_at FROM break_glass_activations WHERE identity_id = $1"#,
+        )
+        .bind(identity_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn update_break_glass_justification(
+        &self,
+        identity_id: &str,
+        justification: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"UPDATE break_glass_activations SET justification = $2 WHERE identity_id = $1 AND justification <> $2"#,
+        )
+        .bind(identity_id)
+        .bind(justification)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn insert_audit_entries(&self, entries: &[AuditEntry]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for entry in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO audit_log (timestamp, event_type, identity_id, method, tool, success, message, duration_ms, request_id, tenant, severity, authz_rule)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                "#,
+            )
+            .bind(entry.timestamp)
+            .bind(to_column(&entry.event_type))
+            .bind(&entry.identity_id)
+            .bind(&entry.method)
+            .bind(&entry.tool)
+            .bind(entry.success)
+            .bind(&entry.message)
+            .bind(entry.duration_ms.map(|ms| ms.min(i64::MAX as u64) as i64))
+            .bind(&entry.request_id)
+            .bind(&entry.tenant)
+            .bind(entry.severity.as_ref().map(to_column))
+            .bind(
+                entry
+                    .authz_rule
+                    .as_ref()
+                    .and_then(|rule| serde_json::to_string(rule).ok()),
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    async fn query_audit(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT timestamp, event_type, identity_id, method, tool, success, message, duration_ms, request_id, tenant, severity, authz_rule FROM audit_log WHERE TRUE",
+        );
+        if let Some(since) = query.since {
+            builder.push(" AND timestamp >= ").push_bind(since);
+        }
+        if let Some(until) = query.until {
+            builder.push(" AND timestamp < ").push_bind(until);
+        }
+        if let Some(identity_id) = &query.identity_id {
+            builder
+                .push(" AND identity_id = ")
+                .push_bind(identity_id.clone());
+        }
+        builder.push(" ORDER BY timestamp DESC, id DESC");
+        if let Some(limit) = query.limit {
+            builder.push(" LIMIT ").push_bind(limit);
+        }
+
+        builder
+            .build_query_as::<AuditRow>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(AuditRow::into_entry)
+            .collect()
+    }
+
+    async fn list_routes(&self) -> Result<Vec<DbRoute>, sqlx::Error> {
+        sqlx::query_as::<_, DbRoute>(
+            r#"SELECT name, config, created_at, updated_at FROM routes ORDER BY name"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn upsert_route(
+        &self,
+        name: &str,
+        config: &serde_json::Value,
+    ) -> Result<DbRoute, sqlx::Error> {
+        sqlx::query_as::<_, DbRoute>(
+            r#"
+            INSERT INTO routes (name, config)
+            VALUES ($1, $2)
+            ON CONFLICT (name) DO UPDATE SET config = EXCLUDED.config, updated_at = NOW()
+            RETURNING name, config, created_at, updated_at
+            "#,
+        )
+        .bind(name)
+        .bind(config)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn delete_route(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM routes WHERE name = $1"#)
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,782 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! SQLite storage backend
+//!
+//! Timestamps are stored as RFC 3339 text and compared through `julianday()`,
+//! JSON columns as text. `sqlite::memory:` databases live only as long as
+//! their connection, so the pool keeps exactly one open for them.
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use super::{
+    migration_status, to_column, AuditQuery, AuditRow, DbApiKey, DbBreakGlassActivation,
+    DbOAuthClient, DbOAuthSession, DbOAuthState, DbRefreshToken, DbRevokedToken, DbRoute, DbUser,
+    MigrationStatus, NewOAuthClient, Storage, TABLES,
+};
+use crate::audit::AuditEntry;
+use crate::config::DatabaseConfig;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+/// Current time in the format the migrations use for column defaults
+const NOW: &str = "strftime('%Y-%m-%dT%H:%M:%fZ', 'now')";
+
+/// [`Storage`] backed by a SQLite database file
+#[derive(Clone)]
+pub struct SqliteStorage {
+    pool: SqlitePool,
+}
+
+impl SqliteStorage {
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(&config.url)?.create_if_missing(true);
+
+        let pool = if config.url.contains(":memory:") {
+            SqlitePoolOptions::new()
+                .max_connections(1)
+                .min_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+        } else {
+            SqlitePoolOptions::new().max_connections(config.max_connections)
+        }
+        .connect_with(options)
+        .await?;
+
+        if config.migrate {
+            MIGRATOR.run(&pool).await?;
+        }
+
+        Ok(Self { pool })
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn migrate(&self) -> Result<(), sqlx::Error> {
+        MIGRATOR.run(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+        migration_status(&MIGRATOR, &self.pool).await
+    }
+
+    async fn reset(&self) -> Result<(), sqlx::Error> {
+        for table in TABLES {
+            sqlx::query(&format!("DROP TABLE IF EXISTS {}", table))
+                .execute(&self.pool)
+                .await?;
+        }
+        self.migrate().await
+    }
+
+    async fn create_user(&self, id: &str, email: &str, role: &str) -> Result<DbUser, sqlx::Error> {
+        sqlx::query_as::<_, DbUser>(
+            r#"
+            INSERT INTO users (id, email, role)
+            VALUES (?1, ?2, ?3)
+            RETURNING id, email, name, role, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(email)
+        .bind(role)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn find_user(&self, id: &str) -> Result<Option<DbUser>, sqlx::Error> {
+        sqlx::query_as::<_, DbUser>(
+            r#"SELECT id, email, name, role, created_at, updated_at FROM users WHERE id = ?1"#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> Result<Option<DbApiKey>, sqlx::Error> {
+        sqlx::query_as::<_, DbApiKey>(
+            r#"SELECT id, user_id, key_hash, name, allowed_tools, rate_limit, expires_at, created_at FROM api_keys WHERE key_hash = ?1"#,
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn list_active_revocations(&self) -> Result<Vec<DbRevokedToken>, sqlx::Error> {
+        sqlx::query_as::<_, DbRevokedToken>(
+            r#"SELECT jti, subject FROM revoked_tokens WHERE expires_at IS NULL OR julianday(expires_at) > julianday('now')"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn create_oauth_client(
+        &self,
+        client: NewOAuthClient<'_>,
+    ) -> Result<DbOAuthClient, sqlx::Error> {
+        sqlx::query_as::<_, DbOAuthClient>(
+            r#"
+            INSERT INTO oauth_clients (client_id, client_secret_hash, client_name, redirect_uris, metadata, client_secret_expires_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            RETURNING client_id, client_secret_hash, client_name, redirect_uris, metadata, client_secret_expires_at, created_at
+            "#,
+        )
+        .bind(client.client_id)
+        .bind(client.client_secret_hash)
+        .bind(client.client_name)
+        .bind(client.redirect_uris)
+        .bind(client.metadata)
+        .bind(client.client_secret_expires_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn find_oauth_client(
+        &self,
+        client_id: &str,
+    ) -> Result<Option<DbOAuthClient>, sqlx::Error> {
+        sqlx::query_as::<_, DbOAuthClient>(
+            r#"SELECT client_id, client_secret_hash, client_name, redirect_uris, metadata, client_secret_expires_at, created_at FROM oauth_clients WHERE client_id = ?1"#,
+        )
+        .bind(client_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn count_oauth_clients(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(r#"SELECT COUNT(*) FROM oauth_clients"#)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn create_refresh_token(
+        &self,
+        token_hash: &str,
+        subject: &str,
+        login: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"INSERT INTO refresh_tokens (token_hash, subject, login, expires_at) VALUES (?1, ?2, ?3, ?4)"#,
+        )
+        .bind(token_hash)
+        .bind(subject)
+        .bind(login)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn consume_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<DbRefreshToken>, sqlx::Error> {
+        sqlx::query_as::<_, DbRefreshToken>(&format!(
+            r#"
+            UPDATE refresh_tokens SET used_at = {NOW}
+            WHERE token_hash = ?1 AND used_at IS NULL AND julianday(expires_at) > julianday('now')
+            RETURNING subject, login, expires_at
+            "#
+        ))
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn put_oauth_state(&self, state: &DbOAuthState) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"INSERT INTO oauth_states (state, code_verifier, client_ip, redirect_uri, created_at) VALUES (?1, ?2, ?3, ?4, ?5)"#,
+        )
+        .bind(&state.state)
+        .bind(&state.code_verifier)
+        .bind(&state.client_ip)
+        .bind(&state.redirect_uri)
+        .bind(state.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn take_oauth_state(&self, state: &str) -> Result<Option<DbOAuthState>, sqlx::Error> {
+        sqlx::query_as::<_, DbOAuthState>(
+            r#"DELETE FROM oauth_states WHERE state = ?1 RETURNING state, code_verifier, client_ip, redirect_uri, created_at"#,
+        )
+        .bind(state)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn count_oauth_states(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(r#"SELECT COUNT(*) FROM oauth_states"#)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn purge_oauth_states(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result =
+            sqlx::query(r#"DELETE FROM oauth_states WHERE julianday(created_at) < julianday(?1)"#)
+                .bind(before)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn put_oauth_session(&self, session: &DbOAuthSession) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO oauth_sessions (token_hash, identity_id, identity, refresh_token, access_expires_at, expires_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT (token_hash) DO UPDATE SET identity_id = excluded.identity_id, identity = excluded.identity,
+                refresh_token = excluded.refresh_token, access_expires_at = excluded.access_expires_at, expires_at = excluded.expires_at
+            "#,
+        )
+        .bind(&session.token_hash)
+        .bind(&session.identity_id)
+        .bind(&session.identity)
+        .bind(&session.refresh_token)
+        .bind(session.access_expires_at)
+        .bind(session.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_oauth_session(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<DbOAuthSession>, sqlx::Error> {
+        sqlx::query_as::<_, DbOAuthSession>(
+            r#"SELECT token_hash, identity_id, identity, refresh_token, access_expires_at, expires_at FROM oauth_sessions WHERE token_hash = ?1"#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn delete_oauth_session(&self, token_hash: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM oauth_sessions WHERE token_hash = ?1"#)
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_identity_oauth_sessions(&self, identity_id: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM oauth_sessions WHERE identity_id = ?1"#)
+            .bind(identity_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn count_oauth_sessions(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(r#"SELECT COUNT(*) FROM oauth_sessions"#)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn purge_oauth_sessions(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"DELETE FROM oauth_sessions WHERE julianday(expires_at) <= julianday('now')"#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn add_quota_usage(
+        &self,
+        identity_id: &str,
+        window_start: DateTime<Utc>,
+        amount: i64,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(&format!(
+            r#"
+            INSERT INTO quota_usage (identity_id, window_start, used)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT (identity_id, window_start)
+            DO UPDATE SET used = quota_usage.used + excluded.used, updated_at = {NOW}
+            RETURNING used
+            "#
+        ))
+        .bind(identity_id)
+        .bind(window_start)
+        .bind(amount)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn quota_usage(
+        &self,
+        identity_id: &str,
+        window_start: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        let used: Option<i64> = sqlx::query_scalar(
+            r#"SELECT used FROM quota_usage WHERE identity_id = ?1 AND window_start = ?2"#,
+        )
+        .bind(identity_id)
+        .bind(window_start)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(used.unwrap_or(0))
+    }
+
+    async fn purge_quota_usage(
+        &self,
+        key_prefix: &str,
+        before: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"DELETE FROM quota_usage WHERE identity_id LIKE ?1 || '%' AND julianday(window_start) < julianday(?2)"#,
+        )
+        .bind(key_prefix)
+        .bind(before)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn insert_break_glass_activation(
+        &self,
+        activation: &DbBreakGlassActivation,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"INSERT INTO break_glass_activations (identity_id, justification, expires_at) VALUES (?1, ?2, ?3) ON CONFLICT (identity_id) DO NOTHING"#,
+        )
+        .bind(&activation.identity_id)
+        .bind(&activation.justification)
+        .bind(activation.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_break_glass_activation(
+        &self,
+        identity_id: &str,
+    ) -> Result<Option<DbBreakGlassActivation>, sqlx::Error> {
+        sqlx::query_as::<_, DbBreakGlassActivation>(
+            r#"SELECT identity_id, justification, expires_at FROM break_glass_activations WHERE identity_id = ?1"#,
+        )
+        .bind(identity_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn update_break_glass_justification(
+        &self,
+        identity_id: &str,
+        justification: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"UPDATE break_glass_activations SET justification = ?2 WHERE identity_id = ?1 AND justification <> ?2"#,
+        )
+        .bind(identity_id)
+        .bind(justification)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn insert_audit_entries(&self, entries: &[AuditEntry]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for entry in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO audit_log (timestamp, event_type, identity_id, method, tool, success, message, duration_ms, request_id, tenant, severity, authz_rule)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                "#,
+            )
+            .bind(entry.timestamp)
+            .bind(to_column(&entry.event_type))
+            .bind(&entry.identity_id)
+            .bind(&entry.method)
+            .bind(&entry.tool)
+            .bind(entry.success)
+            .bind(&entry.message)
+            .bind(entry.duration_ms.map(|ms| ms.min(i64::MAX as u64) as i64))
+            .bind(&entry.request_id)
+            .bind(&entry.tenant)
+            .bind(entry.severity.as_ref().map(to_column))
+            .bind(
+                entry
+                    .authz_rule
+                    .as_ref()
+                    .and_then(|rule| serde_json::to_string(rule).ok()),
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    async fn query_audit(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            "SELECT timestamp, event_type, identity_id, method, tool, success, message, duration_ms, request_id, tenant, severity, authz_rule FROM audit_log WHERE 1 = 1",
+        );
+        if let Some(since) = query.since {
+            builder
+                .push(" AND julianday(timestamp) >= julianday(")
+                .push_bind(since)
+                .push(")");
+        }
+        if let Some(until) = query.until {
+            builder
+                .push(" AND julianday(timestamp) < julianday(")
+                .push_bind(until)
+                .push(")");
+        }
+        if let Some(identity_id) = &query.identity_id {
+            builder
+                .push(" AND identity_id = ")
+                .push_bind(identity_id.clone());
+        }
+        builder.push(" ORDER BY julianday(timestamp) DESC, id DESC");
+        if let Some(limit) = query.limit {
+            builder.push(" LIMIT ").push_bind(limit);
+        }
+
+        builder
+            .build_query_as::<AuditRow>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(AuditRow::into_entry)
+            .collect()
+    }
+
+    async fn list_routes(&self) -> Result<Vec<DbRoute>, sqlx::Error> {
+        sqlx::query_as::<_, DbRoute>(
+            r#"SELECT name, config, created_at, updated_at FROM routes ORDER BY name"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn upsert_route(
+        &self,
+        name: &str,
+        config: &serde_json::Value,
+    ) -> Result<DbRoute, sqlx::Error> {
+        sqlx::query_as::<_, DbRoute>(&format!(
+            r#"
+            INSERT INTO routes (name, config)
+            VALUES (?1, ?2)
+            ON CONFLICT (name) DO UPDATE SET config = excluded.config, updated_at = {NOW}
+            RETURNING name, config, created_at, updated_at
+            "#
+        ))
+        .bind(name)
+        .bind(config)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn delete_route(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM routes WHERE name = ?1"#)
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditSeverity, EventType};
+    use crate::db::Database;
+    use chrono::{Duration, TimeZone};
+
+    async fn memory_db() -> Database {
+        Database::new("sqlite::memory:").await.unwrap()
+    }
+
+    fn audit_entry(identity: &str, seconds: i64, event_type: EventType) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc.timestamp_opt(1_700_000_000 + seconds, 0).unwrap(),
+            event_type,
+            identity_id: Some(identity.to_string()),
+            method: Some("tools/call".to_string()),
+            tool: Some("read_file".to_string()),
+            success: true,
+            message: None,
+            duration_ms: Some(12),
+            request_id: None,
+            tenant: None,
+            severity: None,
+            authz_rule: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_users_and_api_keys() {
+        let storage = SqliteStorage::connect(&DatabaseConfig::from_url("sqlite::memory:"))
+            .await
+            .unwrap();
+        let user = storage
+            .create_user("u1", "u1@example.com", "admin")
+            .await
+            .unwrap();
+        assert_eq!(user.role, "admin");
+        assert!(storage.find_user("u1").await.unwrap().is_some());
+        assert!(storage.find_user("missing").await.unwrap().is_none());
+
+        sqlx::query(
+            r#"INSERT INTO api_keys (user_id, key_hash, allowed_tools, rate_limit) VALUES ('u1', 'h', '["read_file"]', 10)"#,
+        )
+        .execute(storage.pool())
+        .await
+        .unwrap();
+        let key = storage.find_api_key("h").await.unwrap().unwrap();
+        assert_eq!(key.user_id.as_deref(), Some("u1"));
+        assert_eq!(key.allowed_tools, Some(serde_json::json!(["read_file"])));
+        assert_eq!(key.rate_limit, Some(10));
+        assert!(storage.find_api_key("other").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_revocations_skip_expired() {
+        let storage = SqliteStorage::connect(&DatabaseConfig::from_url("sqlite::memory:"))
+            .await
+            .unwrap();
+        for (jti, expires_at) in [
+            ("live", Some(Utc::now() + Duration::hours(1))),
+            ("expired", Some(Utc::now() - Duration::hours(1))),
+            ("forever", None),
+        ] {
+            sqlx::query("INSERT INTO revoked_tokens (jti, expires_at) VALUES (?1, ?2)")
+                .bind(jti)
+                .bind(expires_at)
+                .execute(storage.pool())
+                .await
+                .unwrap();
+        }
+
+        let mut active: Vec<String> = storage
+            .list_active_revocations()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter_map(|r| r.jti)
+            .collect();
+        active.sort();
+        assert_eq!(active, vec!["forever", "live"]);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_refresh_tokens_are_single_use() {
+        let db = memory_db().await;
+        let tokens = db.refresh_tokens();
+        tokens
+            .create("hash", "alice", "password", Utc::now() + Duration::hours(1))
+            .await
+            .unwrap();
+        tokens
+            .create("old", "alice", "password", Utc::now() - Duration::hours(1))
+            .await
+            .unwrap();
+
+        let token = tokens.consume("hash").await.unwrap().unwrap();
+        assert_eq!(token.subject, "alice");
+        assert!(tokens.consume("hash").await.unwrap().is_none());
+        assert!(tokens.consume("old").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_oauth_clients() {
+        let db = memory_db().await;
+        let redirect_uris = serde_json::json!(["https://app.example.com/cb"]);
+        let metadata = serde_json::json!({"client_name": "App"});
+        db.oauth_clients()
+            .create(NewOAuthClient {
+                client_id: "c1",
+                client_secret_hash: None,
+                client_name: Some("App"),
+                redirect_uris: &redirect_uris,
+                metadata: &metadata,
+                client_secret_expires_at: None,
+            })
+            .await
+            .unwrap();
+
+        let client = db.oauth_clients().find_by_id("c1").await.unwrap().unwrap();
+        assert_eq!(client.redirect_uris, redirect_uris);
+        assert_eq!(client.metadata, metadata);
+        assert_eq!(db.oauth_clients().count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_quota_usage_accumulates() {
+        let db = memory_db().await;
+        let window = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let quotas = db.quotas();
+
+        assert_eq!(quotas.usage("alice", window).await.unwrap(), 0);
+        assert_eq!(quotas.add("alice", window, 3).await.unwrap(), 3);
+        assert_eq!(quotas.add("alice", window, 2).await.unwrap(), 5);
+        assert_eq!(quotas.add("bob", window, 1).await.unwrap(), 1);
+        assert_eq!(
+            quotas
+                .usage("alice", window + Duration::days(1))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(quotas.usage("alice", window).await.unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_audit_round_trip_and_filters() {
+        let db = memory_db().await;
+        let mut flagged = audit_entry("alice", 20, EventType::AuthFailure);
+        flagged.success = false;
+        flagged.severity = Some(AuditSeverity::High);
+        flagged.authz_rule = Some(crate::authz::AuthzRule {
+            rule: "allowed_tools".to_string(),
+            pattern: None,
+            order: None,
+        });
+        db.audit_log()
+            .insert(&[
+                audit_entry("alice", 0, EventType::ToolCall),
+                audit_entry("bob", 10, EventType::ToolCall),
+                flagged,
+            ])
+            .await
+            .unwrap();
+
+        let all = db.audit_log().query(&AuditQuery::default()).await.unwrap();
+        assert_eq!(all.len(), 3);
+        // Newest first
+        assert_eq!(all[0].event_type, EventType::AuthFailure);
+        assert_eq!(all[0].severity, Some(AuditSeverity::High));
+        assert_eq!(all[0].authz_rule.as_ref().unwrap().rule, "allowed_tools");
+        assert!(all[1].authz_rule.is_none());
+        assert!(!all[0].success);
+        assert_eq!(all[0].duration_ms, Some(12));
+
+        let alice = db
+            .audit_log()
+            .query(&AuditQuery {
+                identity_id: Some("alice".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(alice.len(), 2);
+
+        let window = db
+            .audit_log()
+            .query(&AuditQuery {
+                since: Some(Utc.timestamp_opt(1_700_000_005, 0).unwrap()),
+                until: Some(Utc.timestamp_opt(1_700_000_020, 0).unwrap()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(window.len(), 1);
+        assert_eq!(window[0].identity_id.as_deref(), Some("bob"));
+
+        let limited = db
+            .audit_log()
+            .query(&AuditQuery {
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_routes() {
+        let db = memory_db().await;
+        let routes = db.routes();
+        assert!(routes.list().await.unwrap().is_empty());
+
+        let config = serde_json::json!({"transport": "http", "url": "http://localhost:3000"});
+        routes.upsert("github", &config).await.unwrap();
+        let updated = serde_json::json!({"transport": "http", "url": "http://localhost:4000"});
+        let route = routes.upsert("github", &updated).await.unwrap();
+        assert_eq!(route.config, updated);
+
+        let listed = routes.list().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].config, updated);
+
+        assert!(routes.delete("github").await.unwrap());
+        assert!(!routes.delete("github").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_skips_migrations_when_disabled() {
+        let config = DatabaseConfig {
+            migrate: false,
+            ..DatabaseConfig::from_url("sqlite::memory:")
+        };
+        let storage = SqliteStorage::connect(&config).await.unwrap();
+        assert!(storage.list_routes().await.is_err());
+
+        let status = storage.migration_status().await.unwrap();
+        assert_eq!(status.len(), MIGRATOR.iter().count());
+        assert!(status.iter().all(|m| !m.applied));
+
+        storage.migrate().await.unwrap();
+        let status = storage.migration_status().await.unwrap();
+        assert!(status.iter().all(|m| m.applied && !m.checksum_mismatch));
+        assert!(storage.list_routes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_reset_deletes_data() {
+        let db = memory_db().await;
+        let config = serde_json::json!({"transport": "http", "url": "http://localhost:3000"});
+        db.routes().upsert("github", &config).await.unwrap();
+
+        db.reset().await.unwrap();
+        assert!(db.routes().list().await.unwrap().is_empty());
+        let status = db.migration_status().await.unwrap();
+        assert!(status.iter().all(|m| m.applied));
+    }
+}
@@ -0,0 +1,558 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Startup self-test (`mcp-guard doctor`)
+//!
+//! [`run`] checks what a configuration needs from its environment before a
+//! gateway is started with it: that the config validates, that HTTP/SSE
+//! upstreams resolve to addresses the SSRF and egress policies allow, that
+//! every upstream answers, that JWKS and OAuth endpoints are reachable, that
+//! audit sinks accept writes, and that TLS certificates are not about to
+//! expire. Each check is reported as PASS, WARN or FAIL: failures would stop
+//! the gateway or break requests, warnings will soon or may.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::auth::OAuthAuthProvider;
+use crate::config::{lint_config, Config, JwtMode, LintSeverity, TransportType, UpstreamTlsConfig};
+use crate::transport::{check_upstream, validate_url_for_ssrf, UpstreamTarget};
+
+/// Certificates and licenses expiring within this many days are reported as a
+/// warning
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+// ============================================================================
+// Report
+// ============================================================================
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        })
+    }
+}
+
+/// One line of the report
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheck {
+    /// What was checked, e.g. `upstream 'github'`
+    pub name: String,
+    pub status: CheckStatus,
+    /// What was found
+    pub detail: String,
+}
+
+impl DoctorCheck {
+    pub fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Pass, detail)
+    }
+
+    pub fn warn(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Warn, detail)
+    }
+
+    pub fn fail(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, detail)
+    }
+}
+
+/// Results of a doctor run, in the order the checks ran
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    pub fn push(&mut self, check: DoctorCheck) {
+        self.checks.push(check);
+    }
+
+    /// Number of checks with `status`
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks.iter().filter(|c| c.status == status).count()
+    }
+
+    /// Worst status in the report; PASS when empty
+    pub fn status(&self) -> CheckStatus {
+        self.checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(CheckStatus::Pass)
+    }
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Run every check against `config`
+///
+/// `timeout` bounds each network check. The SSRF and egress policies are
+/// the installed ones, so call `configure_ssrf` and `configure_egress` first.
+pub async fn run(config: &Config, timeout: Duration) -> DoctorReport {
+    let mut report = DoctorReport::default();
+    check_config(config, &mut report);
+    check_network_policy(config, &mut report).await;
+    check_upstreams(config, timeout, &mut report).await;
+    check_identity_providers(config, timeout, &mut report).await;
+    check_audit_sinks(config, timeout, &mut report).await;
+    check_certificates(config, Utc::now(), &mut report);
+    report
+}
+
+fn check_config(config: &Config, report: &mut DoctorReport) {
+    report.push(match config.validate() {
+        Ok(()) => DoctorCheck::pass("config", "configuration is valid"),
+        Err(e) => DoctorCheck::fail("config", e.to_string()),
+    });
+
+    let findings = lint_config(config);
+    let errors = findings
+        .iter()
+        .filter(|f| f.severity == LintSeverity::Error)
+        .count();
+    report.push(match (errors, findings.len()) {
+        (_, 0) => DoctorCheck::pass("config lint", "no insecure settings"),
+        (0, warnings) => DoctorCheck::warn(
+            "config lint",
+            format!("{} warning(s); run `mcp-guard config lint`", warnings),
+        ),
+        (errors, _) => DoctorCheck::fail(
+            "config lint",
+            format!("{} error(s); run `mcp-guard config lint`", errors),
+        ),
+    });
+}
+
+/// Name and target of every upstream: `default` or each server route
+fn upstream_targets(config: &Config) -> Vec<(String, Result<UpstreamTarget<'_>, String>)> {
+    if config.is_multi_server() {
+        config
+            .upstream
+            .servers
+            .iter()
+            .map(|route| (route.name.clone(), UpstreamTarget::from_route(route)))
+            .collect()
+    } else {
+        vec![(
+            "default".to_string(),
+            UpstreamTarget::from_upstream(&config.upstream),
+        )]
+    }
+}
+
+/// Name and URL of every HTTP/SSE upstream
+fn upstream_urls(config: &Config) -> Vec<(String, &str)> {
+    let is_http =
+        |transport: &TransportType| matches!(transport, TransportType::Http | TransportType::Sse);
+    if config.is_multi_server() {
+        config
+            .upstream
+            .servers
+            .iter()
+            .filter(|route| is_http(&route.transport))
+            .filter_map(|route| Some((route.name.clone(), route.url.as_deref()?)))
+            .collect()
+    } else {
+        config
+            .upstream
+            .url
+            .as_deref()
+            .filter(|_| is_http(&config.upstream.transport))
+            .map(|url| vec![("default".to_string(), url)])
+            .unwrap_or_default()
+    }
+}
+
+/// DNS resolution and the SSRF and egress policies, for HTTP/SSE upstreams
+async fn check_network_policy(config: &Config, report: &mut DoctorReport) {
+    for (name, url) in upstream_urls(config) {
+        let check = format!("dns/ssrf '{}'", name);
+        if let Err(e) = crate::egress::check_egress(url) {
+            report.push(DoctorCheck::fail(check, format!("egress: {}", e)));
+            continue;
+        }
+        report.push(match validate_url_for_ssrf(url).await {
+            Ok(validated) if validated.resolved_ips.is_empty() => {
+                DoctorCheck::pass(check, format!("{} is allowed", validated.host))
+            }
+            Ok(validated) => {
+                let ips: Vec<String> = validated
+                    .resolved_ips
+                    .iter()
+                    .map(|addr| addr.ip().to_string())
+                    .collect();
+                DoctorCheck::pass(
+                    check,
+                    format!("{} resolves to {}, allowed", validated.host, ips.join(", ")),
+                )
+            }
+            Err(e) => DoctorCheck::fail(check, e.to_string()),
+        });
+    }
+}
+
+async fn check_upstreams(config: &Config, timeout: Duration, report: &mut DoctorReport) {
+    for (name, target) in upstream_targets(config) {
+        let check = format!("upstream '{}'", name);
+        let target = match target {
+            Ok(target) => target,
+            Err(e) => {
+                report.push(DoctorCheck::fail(check, e));
+                continue;
+            }
+        };
+        report.push(match check_upstream(&target, timeout).await {
+            Ok(result) => match (result.http_status, result.server_name) {
+                (Some(status), _) if status >= 500 => {
+                    DoctorCheck::warn(check, format!("reachable but answered HTTP {}", status))
+                }
+                (Some(status), _) => {
+                    DoctorCheck::pass(check, format!("HTTP {} in {}ms", status, result.latency_ms))
+                }
+                (None, Some(server)) => DoctorCheck::pass(
+                    check,
+                    format!(
+                        "{} v{} answered initialize in {}ms",
+                        server,
+                        result.server_version.as_deref().unwrap_or("unknown"),
+                        result.latency_ms
+                    ),
+                ),
+                (None, None) => DoctorCheck::pass(
+                    check,
+                    format!("answered initialize in {}ms", result.latency_ms),
+                ),
+            },
+            Err(e) => DoctorCheck::fail(check, e.to_string()),
+        });
+    }
+}
+
+fn http_client(timeout: Duration) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .redirect(crate::egress::redirect_policy())
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Whether `url` answers at all; 5xx responses are a warning
+async fn probe(client: &reqwest::Client, name: String, url: &str) -> DoctorCheck {
+    if let Err(e) = crate::egress::check_egress(url) {
+        return DoctorCheck::fail(name, format!("egress: {}", e));
+    }
+    match client.get(url).send().await {
+        Ok(response) if response.status().is_server_error() => {
+            DoctorCheck::warn(name, format!("{} answered {}", url, response.status()))
+        }
+        Ok(response) => DoctorCheck::pass(
+            name,
+            format!("{} reachable (HTTP {})", url, response.status().as_u16()),
+        ),
+        Err(e) => DoctorCheck::fail(name, format!("{}: {}", url, e)),
+    }
+}
+
+/// JWKS endpoints must serve at least one key; OAuth endpoints must answer
+async fn check_identity_providers(config: &Config, timeout: Duration, report: &mut DoctorReport) {
+    let has_jwks = config
+        .auth
+        .jwt
+        .iter()
+        .any(|jwt| matches!(jwt.mode, JwtMode::Jwks { .. }));
+    if !has_jwks && config.auth.oauth.is_none() {
+        return;
+    }
+    let client = match http_client(timeout) {
+        Ok(client) => client,
+        Err(e) => {
+            report.push(DoctorCheck::fail("http client", e));
+            return;
+        }
+    };
+
+    for jwt in &config.auth.jwt {
+        let JwtMode::Jwks { ref jwks_url, .. } = jwt.mode else {
+            continue;
+        };
+        report.push(check_jwks(&client, &jwt.issuer, jwks_url).await);
+    }
+
+    if let Some(ref oauth) = config.auth.oauth {
+        match OAuthAuthProvider::new(oauth.clone()) {
+            Ok(provider) => {
+                for url in provider.back_channel_urls() {
+                    report.push(probe(&client, "oauth".to_string(), url).await);
+                }
+            }
+            Err(e) => report.push(DoctorCheck::fail("oauth", e.to_string())),
+        }
+    }
+}
+
+async fn check_jwks(client: &reqwest::Client, issuer: &str, url: &str) -> DoctorCheck {
+    let name = format!("jwks '{}'", issuer);
+    if let Err(e) = crate::egress::check_egress(url) {
+        return DoctorCheck::fail(name, format!("egress: {}", e));
+    }
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(e) => return DoctorCheck::fail(name, format!("{}: {}", url, e)),
+    };
+    if !response.status().is_success() {
+        return DoctorCheck::fail(name, format!("{} answered {}", url, response.status()));
+    }
+    match response.json::<serde_json::Value>().await {
+        Ok(jwks) => match jwks["keys"].as_array().map(Vec::len) {
+            Some(0) | None => DoctorCheck::fail(name, format!("{} has no keys", url)),
+            Some(keys) => DoctorCheck::pass(name, format!("{} serves {} key(s)", url, keys)),
+        },
+        Err(e) => DoctorCheck::fail(name, format!("{} is not a JWK Set: {}", url, e)),
+    }
+}
+
+/// The audit file must accept appends; export and object storage must answer
+async fn check_audit_sinks(config: &Config, timeout: Duration, report: &mut DoctorReport) {
+    let audit = &config.audit;
+    if !audit.enabled {
+        return;
+    }
+    if let Some(ref path) = audit.file {
+        report.push(check_writable("audit file", path));
+    }
+    if audit.export_url.is_none() && audit.object_storage.is_none() {
+        return;
+    }
+    let client = match http_client(timeout) {
+        Ok(client) => client,
+        Err(e) => {
+            report.push(DoctorCheck::fail("http client", e));
+            return;
+        }
+    };
+    if let Some(ref url) = audit.export_url {
+        report.push(probe(&client, "audit export".to_string(), url).await);
+    }
+    if let Some(ref storage) = audit.object_storage {
+        report.push(
+            probe(
+                &client,
+                "audit object storage".to_string(),
+                &storage.endpoint,
+            )
+            .await,
+        );
+    }
+}
+
+/// Open `path` for appending, as the audit writer does, creating it if missing
+fn check_writable(name: &str, path: &Path) -> DoctorCheck {
+    match std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        Ok(_) => DoctorCheck::pass(name, format!("{} is writable", path.display())),
+        Err(e) => DoctorCheck::fail(name, format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Expiry of the server, client CA and upstream TLS certificates
+fn check_certificates(config: &Config, now: DateTime<Utc>, report: &mut DoctorReport) {
+    let mut certs: Vec<(String, &Path)> = Vec::new();
+    if let Some(ref tls) = config.server.tls {
+        certs.push((
+            "tls server certificate".to_string(),
+            tls.cert_path.as_path(),
+        ));
+        if let Some(ref ca) = tls.client_ca_path {
+            certs.push(("tls client CA".to_string(), ca.as_path()));
+        }
+    }
+    let upstreams: Vec<(&str, Option<&UpstreamTlsConfig>)> = if config.is_multi_server() {
+        config
+            .upstream
+            .servers
+            .iter()
+            .map(|route| (route.name.as_str(), route.tls.as_ref()))
+            .collect()
+    } else {
+        vec![("default", config.upstream.tls.as_ref())]
+    };
+    for (name, tls) in upstreams {
+        let Some(tls) = tls else {
+            continue;
+        };
+        if let Some(ref ca) = tls.ca_cert {
+            certs.push((format!("tls upstream '{}' CA", name), ca.as_path()));
+        }
+        if let Some(ref cert) = tls.client_cert {
+            certs.push((
+                format!("tls upstream '{}' client certificate", name),
+                cert.as_path(),
+            ));
+        }
+    }
+
+    for (name, path) in certs {
+        let check = match std::fs::read(path) {
+            Ok(pem) => match earliest_expiry(&pem) {
+                Ok(expires) => expiry_check(name, path, expires, now),
+                Err(e) => DoctorCheck::fail(name, format!("{}: {}", path.display(), e)),
+            },
+            Err(e) => DoctorCheck::fail(name, format!("{}: {}", path.display(), e)),
+        };
+        report.push(check);
+    }
+}
+
+/// Earliest `notAfter` of the certificates in a PEM document
+fn earliest_expiry(pem: &[u8]) -> Result<DateTime<Utc>, String> {
+    let mut earliest: Option<DateTime<Utc>> = None;
+    for pem in x509_parser::pem::Pem::iter_from_buffer(pem) {
+        let pem = pem.map_err(|e| format!("invalid PEM: {}", e))?;
+        if pem.label != "CERTIFICATE" {
+            continue;
+        }
+        let cert = pem
+            .parse_x509()
+            .map_err(|e| format!("invalid certificate: {}", e))?;
+        let not_after = DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0)
+            .ok_or("certificate expiry out of range")?;
+        earliest = Some(earliest.map_or(not_after, |e| e.min(not_after)));
+    }
+    earliest.ok_or_else(|| "no certificates found".to_string())
+}
+
+fn expiry_check(
+    name: String,
+    path: &Path,
+    expires: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> DoctorCheck {
+    let days = (expires - now).num_days();
+    let date = expires.format("%Y-%m-%d");
+    if expires <= now {
+        DoctorCheck::fail(name, format!("{} expired on {}", path.display(), date))
+    } else if days < EXPIRY_WARNING_DAYS {
+        DoctorCheck::warn(
+            name,
+            format!("{} expires in {} day(s), on {}", path.display(), days, date),
+        )
+    } else {
+        DoctorCheck::pass(name, format!("{} valid until {}", path.display(), date))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_status_is_worst_check() {
+        let mut report = DoctorReport::default();
+        assert_eq!(report.status(), CheckStatus::Pass);
+        report.push(DoctorCheck::pass("a", ""));
+        report.push(DoctorCheck::warn("b", ""));
+        assert_eq!(report.status(), CheckStatus::Warn);
+        report.push(DoctorCheck::fail("c", ""));
+        assert_eq!(report.status(), CheckStatus::Fail);
+        assert_eq!(report.count(CheckStatus::Pass), 1);
+        assert_eq!(CheckStatus::Warn.to_string(), "WARN");
+        assert_eq!(
+            serde_json::to_value(&report.checks[2]).unwrap()["status"],
+            "FAIL"
+        );
+    }
+
+    #[test]
+    fn test_certificate_expiry() {
+        let now = Utc::now();
+        let path = Path::new("cert.pem");
+        let check = |days| {
+            expiry_check(
+                "tls".to_string(),
+                path,
+                now + chrono::Duration::days(days),
+                now,
+            )
+        };
+        assert_eq!(check(90).status, CheckStatus::Pass);
+        assert_eq!(check(10).status, CheckStatus::Warn);
+        assert_eq!(check(-1).status, CheckStatus::Fail);
+
+        assert!(earliest_expiry(b"not a certificate").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_checks_mock_upstream_and_audit_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let audit_file = dir.path().join("audit.log");
+        let mut config: Config = toml::from_str(
+            r#"
+            [upstream]
+            transport = "mock"
+            "#,
+        )
+        .unwrap();
+        config.audit.enabled = true;
+        config.audit.file = Some(audit_file.clone());
+
+        let report = run(&config, Duration::from_secs(5)).await;
+        let status = |name: &str| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| c.status)
+        };
+        assert_eq!(status("config"), Some(CheckStatus::Pass));
+        assert_eq!(status("upstream 'default'"), Some(CheckStatus::Pass));
+        assert_eq!(status("audit file"), Some(CheckStatus::Pass));
+        assert!(audit_file.exists());
+
+        config.audit.file = Some(dir.path().join("missing/audit.log"));
+        let report = run(&config, Duration::from_secs(5)).await;
+        assert_eq!(report.status(), CheckStatus::Fail);
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Egress allowlist for outbound connections
+//!
+//! With `[egress] allow` set, the guard only talks to the listed hosts:
+//! HTTP/SSE upstreams, audit export and object storage, and the OAuth
+//! provider's back-channel endpoints. Configured URLs are checked when the
+//! configuration is validated; redirects are checked as they are followed, so
+//! an allowed host cannot hand a request on to one that is not.
+//!
+//! [`configure_egress`] installs the allowlist at startup.
+
+use std::net::IpAddr;
+use std::sync::{Arc, RwLock};
+
+use crate::config::EgressConfig;
+
+/// Redirects followed before a request fails, matching reqwest's default
+const MAX_REDIRECTS: usize = 10;
+
+/// `None` until configured, which allows every host
+static EGRESS_POLICY: RwLock<Option<Arc<EgressPolicy>>> = RwLock::new(None);
+
+/// Install the egress allowlist from `[egress]`
+///
+/// Call once at startup, before outbound clients are created.
+pub fn configure_egress(config: &EgressConfig) {
+    let policy = Arc::new(EgressPolicy::new(config));
+    if let Ok(mut current) = EGRESS_POLICY.write() {
+        *current = Some(policy);
+    }
+}
+
+/// Check a URL against the installed allowlist
+///
+/// Returns a description of the refusal if the URL's host is not allowed.
+pub fn check_egress(url: &str) -> Result<(), String> {
+    current_policy().check_url(url)
+}
+
+/// Redirect policy for outbound clients that refuses redirects to hosts
+/// outside the installed allowlist
+pub fn redirect_policy() -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        match current_policy().check_url(attempt.url().as_str()) {
+            Ok(()) => attempt.follow(),
+            Err(e) => attempt.error(e),
+        }
+    })
+}
+
+fn current_policy() -> Arc<EgressPolicy> {
+    EGRESS_POLICY
+        .read()
+        .ok()
+        .and_then(|policy| policy.clone())
+        .unwrap_or_default()
+}
+
+/// Compiled `[egress]` section
+#[derive(Debug, Clone, Default)]
+pub struct EgressPolicy {
+    /// Lowercase host names and IP addresses; entries starting with `.`
+    /// match subdomains
+    hosts: Vec<String>,
+}
+
+impl EgressPolicy {
+    pub fn new(config: &EgressConfig) -> Self {
+        Self {
+            hosts: config
+                .allow
+                .iter()
+                .map(|host| {
+                    let host = host.trim().to_ascii_lowercase();
+                    match host.parse::<IpAddr>() {
+                        Ok(ip) => ip.to_string(),
+                        Err(_) => host.strip_prefix('*').unwrap_or(&host).to_string(),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether an allowlist is configured; without one every host is allowed
+    pub fn is_enabled(&self) -> bool {
+        !self.hosts.is_empty()
+    }
+
+    /// Whether `host` is listed, or is a subdomain of a `*.` entry
+    pub fn allows_host(&self, host: &str) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        let host = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .trim_end_matches('.')
+            .to_ascii_lowercase();
+        let host = match host.parse::<IpAddr>() {
+            Ok(ip) => ip.to_string(),
+            Err(_) => host,
+        };
+        self.hosts.iter().any(|allowed| {
+            if allowed.starts_with('.') {
+                host.ends_with(allowed.as_str())
+            } else {
+                host == *allowed
+            }
+        })
+    }
+
+    /// Check the host of `url`
+    pub fn check_url(&self, url: &str) -> Result<(), String> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let parsed = url::Url::parse(url).map_err(|e| format!("invalid URL '{}': {}", url, e))?;
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| format!("URL '{}' has no host", url))?;
+        if self.allows_host(host) {
+            Ok(())
+        } else {
+            Err(format!("host '{}' is not in egress.allow", host))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow: &[&str]) -> EgressPolicy {
+        EgressPolicy::new(&EgressConfig {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn test_empty_allows_everything() {
+        let policy = policy(&[]);
+        assert!(!policy.is_enabled());
+        assert!(policy.check_url("https://anywhere.example.net/x").is_ok());
+    }
+
+    #[test]
+    fn test_hosts_and_wildcards() {
+        let policy = policy(&["mcp.example.com", "*.svc.internal", "10.0.0.5", "::1"]);
+        assert!(policy.check_url("https://mcp.example.com/mcp").is_ok());
+        assert!(policy.check_url("https://MCP.example.com./mcp").is_ok());
+        assert!(policy.check_url("https://api.mcp.example.com").is_err());
+        assert!(policy
+            .check_url("http://github.tools.svc.internal:8080")
+            .is_ok());
+        assert!(policy.check_url("http://svc.internal").is_err());
+        assert!(policy.check_url("http://evilsvc.internal").is_err());
+        assert!(policy.check_url("http://10.0.0.5:9000/mcp").is_ok());
+        assert!(policy.check_url("http://[::1]:8080/mcp").is_ok());
+
+        let err = policy
+            .check_url("https://attacker.example.net/x")
+            .unwrap_err();
+        assert!(err.contains("attacker.example.net"));
+        assert!(policy.check_url("not a url").is_err());
+    }
+}
@@ -0,0 +1,395 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Fair request queueing in front of shared upstreams
+//!
+//! A stdio upstream typically handles one request at a time, so a single
+//! chatty identity can keep every other caller waiting behind it. A
+//! [`FairQueue`] caps concurrent calls to an upstream and, when all slots are
+//! busy, queues callers per identity and hands out freed slots in weighted
+//! round-robin order: each identity with waiters gets up to its weight in
+//! consecutive slots before the next identity's turn.
+//!
+//! Callers obtain a [`FairPermit`] through [`FairQueue::acquire`]; dropping the
+//! permit passes the slot to the next waiter. Each identity's queue is bounded,
+//! and a caller arriving at a full queue is rejected with [`QueueFull`].
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+use crate::config::FairQueueConfig;
+
+/// A request rejected because its identity's queue is full
+#[derive(Debug, Clone)]
+pub struct QueueFull {
+    /// Upstream the request was queued for
+    pub upstream: String,
+    /// Seconds the client should wait before retrying
+    pub retry_after_secs: u64,
+}
+
+/// Waiters of one identity
+#[derive(Debug, Default)]
+struct IdentityQueue {
+    waiters: VecDeque<oneshot::Sender<FairPermit>>,
+    /// Slots granted in the identity's current turn
+    served: u32,
+}
+
+/// Scheduler state, guarded by a mutex that is never held across an await
+#[derive(Debug)]
+struct Slots {
+    /// Free slots; only non-zero while no one is waiting
+    available: usize,
+    queues: HashMap<String, IdentityQueue>,
+    /// Identities with waiters, in round-robin order
+    ring: VecDeque<String>,
+    depth: usize,
+}
+
+#[derive(Debug)]
+struct Shared {
+    upstream: String,
+    max_queue_per_identity: usize,
+    retry_after_secs: u64,
+    weights: HashMap<String, u32>,
+    slots: Mutex<Slots>,
+}
+
+impl Shared {
+    fn weight(&self, identity: &str) -> u32 {
+        self.weights.get(identity).copied().unwrap_or(1).max(1)
+    }
+
+    /// Hand a freed slot to the next waiter, or return it to the pool
+    fn release(self: &Arc<Self>) {
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let Some(identity) = slots.ring.front().cloned() else {
+                slots.available += 1;
+                return;
+            };
+            let weight = self.weight(&identity);
+
+            let queue = slots
+                .queues
+                .get_mut(&identity)
+                .expect("identity in ring has a queue");
+            let waiter = queue.waiters.pop_front();
+            queue.served += 1;
+            if queue.waiters.is_empty() {
+                slots.queues.remove(&identity);
+                slots.ring.pop_front();
+            } else if queue.served >= weight {
+                queue.served = 0;
+                slots.ring.rotate_left(1);
+            }
+            slots.depth -= 1;
+            crate::observability::set_fair_queue_depth(&self.upstream, slots.depth);
+
+            let permit = FairPermit {
+                shared: Some(self.clone()),
+            };
+            match waiter.map(|tx| tx.send(permit)) {
+                Some(Ok(())) => return,
+                // The waiter gave up; disarm the permit and try the next one
+                Some(Err(mut permit)) => {
+                    permit.shared = None;
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// A slot for one upstream call; dropping it frees the slot
+#[derive(Debug)]
+pub struct FairPermit {
+    shared: Option<Arc<Shared>>,
+}
+
+impl Drop for FairPermit {
+    fn drop(&mut self) {
+        if let Some(shared) = self.shared.take() {
+            shared.release();
+        }
+    }
+}
+
+/// Weighted round-robin queue for calls to one upstream
+#[derive(Debug, Clone, Default)]
+pub struct FairQueue {
+    /// None when fair queueing is disabled
+    shared: Option<Arc<Shared>>,
+}
+
+impl FairQueue {
+    /// Create a queue for `upstream` ("default" in single-server mode)
+    pub fn new(upstream: &str, config: &FairQueueConfig) -> Self {
+        if !config.enabled {
+            return Self::default();
+        }
+        Self {
+            shared: Some(Arc::new(Shared {
+                upstream: upstream.to_string(),
+                max_queue_per_identity: config.max_queue_per_identity,
+                retry_after_secs: config.retry_after_secs,
+                weights: config.weights.clone(),
+                slots: Mutex::new(Slots {
+                    available: config.max_concurrent.max(1),
+                    queues: HashMap::new(),
+                    ring: VecDeque::new(),
+                    depth: 0,
+                }),
+            })),
+        }
+    }
+
+    /// Number of callers currently waiting for a slot
+    pub fn depth(&self) -> usize {
+        self.shared.as_ref().map_or(0, |shared| {
+            shared.slots.lock().unwrap_or_else(|e| e.into_inner()).depth
+        })
+    }
+
+    /// Wait for a slot to call the upstream on behalf of `identity`
+    ///
+    /// Returns immediately when a slot is free and no one is waiting.
+    /// Cancelling the returned future gives up the caller's place in line.
+    pub async fn acquire(&self, identity: &str) -> Result<FairPermit, QueueFull> {
+        let Some(shared) = &self.shared else {
+            return Ok(FairPermit { shared: None });
+        };
+
+        let rx = {
+            let mut slots = shared.slots.lock().unwrap_or_else(|e| e.into_inner());
+            if slots.available > 0 && slots.ring.is_empty() {
+                slots.available -= 1;
+                return Ok(FairPermit {
+                    shared: Some(shared.clone()),
+                });
+            }
+
+            let queued = slots.queues.get(identity).map_or(0, |q| q.waiters.len());
+            if queued >= shared.max_queue_per_identity {
+                crate::observability::record_fair_queue_rejected(&shared.upstream);
+                tracing::warn!(
+                    upstream = %shared.upstream,
+                    identity_id = %identity,
+                    queued = queued,
+                    "Fair queue full for identity, rejecting request"
+                );
+                return Err(QueueFull {
+                    upstream: shared.upstream.clone(),
+                    retry_after_secs: shared.retry_after_secs,
+                });
+            }
+
+            let (tx, rx) = oneshot::channel();
+            if queued == 0 {
+                slots.ring.push_back(identity.to_string());
+            }
+            slots
+                .queues
+                .entry(identity.to_string())
+                .or_default()
+                .waiters
+                .push_back(tx);
+            slots.depth += 1;
+            crate::observability::set_fair_queue_depth(&shared.upstream, slots.depth);
+            rx
+        };
+
+        // The sender is only dropped after a permit was handed over, so a
+        // closed channel cannot happen while the queue is alive
+        rx.await.map_err(|_| QueueFull {
+            upstream: shared.upstream.clone(),
+            retry_after_secs: shared.retry_after_secs,
+        })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn queue(max_queue_per_identity: usize, weights: &[(&str, u32)]) -> FairQueue {
+        FairQueue::new(
+            "default",
+            &FairQueueConfig {
+                enabled: true,
+                max_concurrent: 1,
+                max_queue_per_identity,
+                weights: weights.iter().map(|(id, w)| (id.to_string(), *w)).collect(),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Queue `count` waiters for `identity` that log their identity when served
+    fn spawn_waiters(
+        queue: &FairQueue,
+        identity: &'static str,
+        count: usize,
+        served: &Arc<Mutex<Vec<&'static str>>>,
+    ) {
+        for _ in 0..count {
+            let queue = queue.clone();
+            let served = served.clone();
+            tokio::spawn(async move {
+                let _permit = queue.acquire(identity).await.unwrap();
+                served.lock().unwrap().push(identity);
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            });
+        }
+    }
+
+    async fn wait_for_depth(queue: &FairQueue, depth: usize) {
+        while queue.depth() != depth {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_across_identities() {
+        let queue = queue(16, &[]);
+        let served = Arc::new(Mutex::new(Vec::new()));
+
+        let busy = queue.acquire("alice").await.unwrap();
+        spawn_waiters(&queue, "alice", 4, &served);
+        wait_for_depth(&queue, 4).await;
+        spawn_waiters(&queue, "bob", 2, &served);
+        wait_for_depth(&queue, 6).await;
+
+        drop(busy);
+        wait_for_depth(&queue, 0).await;
+        while served.lock().unwrap().len() < 6 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        // Bob is not stuck behind all of Alice's queued calls
+        assert_eq!(
+            *served.lock().unwrap(),
+            vec!["alice", "bob", "alice", "bob", "alice", "alice"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weights() {
+        let queue = queue(16, &[("batch", 1), ("interactive", 3)]);
+        let served = Arc::new(Mutex::new(Vec::new()));
+
+        let busy = queue.acquire("batch").await.unwrap();
+        spawn_waiters(&queue, "interactive", 4, &served);
+        wait_for_depth(&queue, 4).await;
+        spawn_waiters(&queue, "batch", 2, &served);
+        wait_for_depth(&queue, 6).await;
+
+        drop(busy);
+        while served.lock().unwrap().len() < 6 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        assert_eq!(
+            *served.lock().unwrap(),
+            vec![
+                "interactive",
+                "interactive",
+                "interactive",
+                "batch",
+                "interactive",
+                "batch"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bounded_per_identity() {
+        let queue = queue(2, &[]);
+        let busy = queue.acquire("alice").await.unwrap();
+
+        let first = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire("alice").await.map(drop) }
+        });
+        let second = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire("alice").await.map(drop) }
+        });
+        wait_for_depth(&queue, 2).await;
+
+        let full = queue.acquire("alice").await.unwrap_err();
+        assert_eq!(full.upstream, "default");
+        assert_eq!(full.retry_after_secs, 1);
+
+        // Other identities still get a place in line
+        let bob = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire("bob").await.map(drop) }
+        });
+        wait_for_depth(&queue, 3).await;
+
+        drop(busy);
+        assert!(first.await.unwrap().is_ok());
+        assert!(second.await.unwrap().is_ok());
+        assert!(bob.await.unwrap().is_ok());
+        assert_eq!(queue.depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_passes_slot_on() {
+        let queue = queue(16, &[]);
+        let busy = queue.acquire("alice").await.unwrap();
+
+        let cancelled = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire("alice").await.map(drop) }
+        });
+        wait_for_depth(&queue, 1).await;
+        let waiting = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire("bob").await.map(drop) }
+        });
+        wait_for_depth(&queue, 2).await;
+
+        cancelled.abort();
+        let _ = cancelled.await;
+        drop(busy);
+
+        assert!(waiting.await.unwrap().is_ok());
+        // The slot is free again once bob is done
+        let _again = queue.acquire("carol").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_disabled_never_waits() {
+        let queue = FairQueue::default();
+        let permits: Vec<_> = futures::future::join_all((0..100).map(|_| queue.acquire("alice")))
+            .await
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(queue.depth(), 0);
+        drop::<Vec<FairPermit>>(permits);
+    }
+}
@@ -0,0 +1,438 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Rate limit management tools (`guard/limits/*`)
+//!
+//! Lets admins inspect and adjust throttling from an MCP client:
+//! - `guard/limits/get` - Current limit and bucket state of an identity
+//! - `guard/limits/raise` - Temporarily override an identity's limit
+//! - `guard/limits/reset` - Refill an identity's buckets and drop its override
+//! - `guard/limits/top` - Identities with the most requests
+//!
+//! The HTTP server answers these for identities listed in `admin.identities`.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::time::Duration;
+
+use super::{json_result, GuardToolError, GuardToolsProvider, ToolDefinition, ToolResult};
+use crate::identity_store::IdentityStore;
+use crate::rate_limit::RateLimitService;
+
+/// Default lifetime of a raised limit (1 hour)
+const DEFAULT_OVERRIDE_SECS: u64 = 3600;
+
+/// Longest a raised limit may last (24 hours)
+const MAX_OVERRIDE_SECS: u64 = 86_400;
+
+/// Default number of identities returned by `guard/limits/top`
+const DEFAULT_TOP_LIMIT: usize = 10;
+
+/// Rate limit management tools over the gateway's live limiter state
+pub struct LimitsGuardTools<'a> {
+    rate_limiter: &'a RateLimitService,
+    identities: &'a IdentityStore,
+}
+
+impl<'a> LimitsGuardTools<'a> {
+    pub fn new(rate_limiter: &'a RateLimitService, identities: &'a IdentityStore) -> Self {
+        Self {
+            rate_limiter,
+            identities,
+        }
+    }
+}
+
+#[async_trait]
+impl GuardToolsProvider for LimitsGuardTools<'_> {
+    fn list_tools(&self) -> Vec<ToolDefinition> {
+        let identity_only = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "identity_id": {
+                    "type": "string",
+                    "description": "Identity to manage"
+                }
+            },
+            "required": ["identity_id"],
+            "additionalProperties": false
+        });
+
+        vec![
+            ToolDefinition {
+                name: "guard/limits/get".to_string(),
+                description: "Show an identity's rate limit, bucket state and request counts"
+                    .to_string(),
+                input_schema: identity_only.clone(),
+            },
+            ToolDefinition {
+                name: "guard/limits/raise".to_string(),
+                description: "Temporarily override an identity's rate limit".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "identity_id": {
+                            "type": "string",
+                            "description": "Identity to manage"
+                        },
+                        "requests_per_second": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "New limit in requests per second"
+                        },
+                        "burst_size": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Burst size (default: half the rate)"
+                        },
+                        "duration_secs": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_OVERRIDE_SECS,
+                            "default": DEFAULT_OVERRIDE_SECS,
+                            "description": "How long the override lasts"
+                        }
+                    },
+                    "required": ["identity_id", "requests_per_second"],
+                    "additionalProperties": false
+                }),
+            },
+            ToolDefinition {
+                name: "guard/limits/reset".to_string(),
+                description: "Refill an identity's rate limit buckets and drop any override"
+                    .to_string(),
+                input_schema: identity_only,
+            },
+            ToolDefinition {
+                name: "guard/limits/top".to_string(),
+                description: "List the active identities with the most requests".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "default": DEFAULT_TOP_LIMIT,
+                            "description": "Maximum number of identities"
+                        },
+                        "sort": {
+                            "type": "string",
+                            "enum": ["requests", "rate_limited"],
+                            "default": "requests",
+                            "description": "Rank by total or rate-limited requests"
+                        }
+                    },
+                    "additionalProperties": false
+                }),
+            },
+        ]
+    }
+
+    async fn call_tool(&self, name: &str, args: Value) -> Result<ToolResult, GuardToolError> {
+        match name {
+            "guard/limits/get" => self.handle_get(args),
+            "guard/limits/raise" => self.handle_raise(args),
+            "guard/limits/reset" => self.handle_reset(args),
+            "guard/limits/top" => self.handle_top(args),
+            _ => Err(GuardToolError::NotFound(name.to_string())),
+        }
+    }
+}
+
+impl LimitsGuardTools<'_> {
+    fn handle_get(&self, args: Value) -> Result<ToolResult, GuardToolError> {
+        let identity_id = identity_arg(&args)?;
+        let activity = self.identities.get(identity_id);
+
+        let response = serde_json::json!({
+            "identity_id": identity_id,
+            "limit": self.rate_limiter.identity_limit(identity_id),
+            "last_request": activity.as_ref().map(|a| &a.rate_limit),
+            "request_count": activity.as_ref().map_or(0, |a| a.request_count),
+            "rate_limited_count": activity.as_ref().map_or(0, |a| a.rate_limited_count),
+        });
+        Ok(json_result(&response))
+    }
+
+    fn handle_raise(&self, args: Value) -> Result<ToolResult, GuardToolError> {
+        let identity_id = identity_arg(&args)?;
+        let rps = positive_arg(&args, "requests_per_second")?.ok_or_else(|| {
+            GuardToolError::InvalidArguments("Missing requests_per_second".to_string())
+        })?;
+        let burst = positive_arg(&args, "burst_size")?;
+        let duration_secs =
+            positive_arg(&args, "duration_secs")?.map_or(DEFAULT_OVERRIDE_SECS, u64::from);
+        if duration_secs > MAX_OVERRIDE_SECS {
+            return Err(GuardToolError::InvalidArguments(format!(
+                "duration_secs must be at most {}",
+                MAX_OVERRIDE_SECS
+            )));
+        }
+
+        let limit = self.rate_limiter.set_override(
+            identity_id,
+            rps,
+            burst,
+            Duration::from_secs(duration_secs),
+        );
+        tracing::info!(
+            identity_id = %identity_id,
+            requests_per_second = rps,
+            duration_secs,
+            "Rate limit overridden"
+        );
+
+        let response = serde_json::json!({
+            "identity_id": identity_id,
+            "limit": limit,
+        });
+        Ok(json_result(&response))
+    }
+
+    fn handle_reset(&self, args: Value) -> Result<ToolResult, GuardToolError> {
+        let identity_id = identity_arg(&args)?;
+        let had_state = self.rate_limiter.reset_identity(identity_id);
+        tracing::info!(identity_id = %identity_id, "Rate limit reset");
+
+        let response = serde_json::json!({
+            "identity_id": identity_id,
+            "reset": had_state,
+            "limit": self.rate_limiter.identity_limit(identity_id),
+        });
+        Ok(json_result(&response))
+    }
+
+    fn handle_top(&self, args: Value) -> Result<ToolResult, GuardToolError> {
+        let limit = positive_arg(&args, "limit")?.map_or(DEFAULT_TOP_LIMIT, |n| n as usize);
+        let by_rate_limited = match args.get("sort").and_then(|v| v.as_str()) {
+            None | Some("requests") => false,
+            Some("rate_limited") => true,
+            Some(other) => {
+                return Err(GuardToolError::InvalidArguments(format!(
+                    "Unknown sort: {}. Use 'requests' or 'rate_limited'",
+                    other
+                )))
+            }
+        };
+
+        let mut identities = self.identities.list();
+        identities.sort_by(|a, b| {
+            let (a_key, b_key) = if by_rate_limited {
+                (a.rate_limited_count, b.rate_limited_count)
+            } else {
+                (a.request_count, b.request_count)
+            };
+            b_key.cmp(&a_key).then_with(|| a.id.cmp(&b.id))
+        });
+        identities.truncate(limit);
+
+        let consumers: Vec<Value> = identities
+            .iter()
+            .map(|identity| {
+                serde_json::json!({
+                    "identity_id": identity.id,
+                    "request_count": identity.request_count,
+                    "rate_limited_count": identity.rate_limited_count,
+                    "last_seen": identity.last_seen,
+                    "limit": identity.rate_limit.limit,
+                })
+            })
+            .collect();
+        Ok(json_result(&serde_json::json!({ "identities": consumers })))
+    }
+}
+
+/// Whether a tool name belongs to the rate limit management tools
+pub fn is_limits_tool(name: &str) -> bool {
+    name.starts_with("guard/limits/")
+}
+
+fn identity_arg(args: &Value) -> Result<&str, GuardToolError> {
+    args.get("identity_id")
+        .and_then(|v| v.as_str())
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| GuardToolError::InvalidArguments("Missing identity_id".to_string()))
+}
+
+/// Read an optional integer argument that must be at least 1
+fn positive_arg(args: &Value, name: &str) -> Result<Option<u32>, GuardToolError> {
+    match args.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .filter(|n| *n >= 1)
+            .and_then(|n| u32::try_from(n).ok())
+            .map(Some)
+            .ok_or_else(|| {
+                GuardToolError::InvalidArguments(format!("{} must be a positive integer", name))
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Identity;
+    use crate::config::RateLimitConfig;
+
+    fn service() -> RateLimitService {
+        RateLimitService::new(&RateLimitConfig {
+            enabled: true,
+            requests_per_second: 1,
+            burst_size: 1,
+            tool_limits: vec![],
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
+        })
+    }
+
+    fn record(store: &IdentityStore, limiter: &RateLimitService, id: &str, requests: usize) {
+        let identity = Identity {
+            id: id.to_string(),
+            name: None,
+            allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims: Default::default(),
+            tenant: None,
+            argument_constraints: None,
+        };
+        for _ in 0..requests {
+            store.record_request(&identity, &limiter.check(id, None));
+        }
+    }
+
+    fn json(result: ToolResult) -> Value {
+        serde_json::from_str(&result.content.unwrap()[0].text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_raise_get_and_reset() {
+        let limiter = service();
+        let store = IdentityStore::default();
+        let tools = LimitsGuardTools::new(&limiter, &store);
+        record(&store, &limiter, "alice", 2);
+
+        let state = json(
+            tools
+                .call_tool(
+                    "guard/limits/get",
+                    serde_json::json!({"identity_id": "alice"}),
+                )
+                .await
+                .unwrap(),
+        );
+        assert_eq!(state["limit"]["limit"], 1);
+        assert_eq!(state["request_count"], 2);
+        assert_eq!(state["rate_limited_count"], 1);
+
+        let raised = json(
+            tools
+                .call_tool(
+                    "guard/limits/raise",
+                    serde_json::json!({
+                        "identity_id": "alice",
+                        "requests_per_second": 50,
+                        "duration_secs": 600
+                    }),
+                )
+                .await
+                .unwrap(),
+        );
+        assert_eq!(raised["limit"]["limit"], 50);
+        assert_eq!(raised["limit"]["burst"], 25);
+        assert!(limiter.check("alice", None).allowed);
+
+        let reset = json(
+            tools
+                .call_tool(
+                    "guard/limits/reset",
+                    serde_json::json!({"identity_id": "alice"}),
+                )
+                .await
+                .unwrap(),
+        );
+        assert_eq!(reset["reset"], true);
+        assert_eq!(reset["limit"]["limit"], 1);
+        assert!(reset["limit"]["override_expires_in_secs"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_arguments() {
+        let limiter = service();
+        let store = IdentityStore::default();
+        let tools = LimitsGuardTools::new(&limiter, &store);
+
+        for args in [
+            serde_json::json!({}),
+            serde_json::json!({"identity_id": "alice"}),
+            serde_json::json!({"identity_id": "alice", "requests_per_second": 0}),
+            serde_json::json!({
+                "identity_id": "alice",
+                "requests_per_second": 5,
+                "duration_secs": MAX_OVERRIDE_SECS + 1
+            }),
+        ] {
+            let result = tools.call_tool("guard/limits/raise", args).await;
+            assert!(matches!(result, Err(GuardToolError::InvalidArguments(_))));
+        }
+        let result = tools
+            .call_tool("guard/limits/top", serde_json::json!({"sort": "latency"}))
+            .await;
+        assert!(matches!(result, Err(GuardToolError::InvalidArguments(_))));
+    }
+
+    #[tokio::test]
+    async fn test_top_consumers() {
+        let limiter = service();
+        let store = IdentityStore::default();
+        let tools = LimitsGuardTools::new(&limiter, &store);
+        record(&store, &limiter, "alice", 1);
+        record(&store, &limiter, "bob", 4);
+        record(&store, &limiter, "carol", 2);
+
+        let top = json(
+            tools
+                .call_tool("guard/limits/top", serde_json::json!({"limit": 2}))
+                .await
+                .unwrap(),
+        );
+        let ids: Vec<&str> = top["identities"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["identity_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["bob", "carol"]);
+
+        let top = json(
+            tools
+                .call_tool(
+                    "guard/limits/top",
+                    serde_json::json!({"sort": "rate_limited"}),
+                )
+                .await
+                .unwrap(),
+        );
+        assert_eq!(top["identities"][0]["identity_id"], "bob");
+        assert_eq!(top["identities"][0]["rate_limited_count"], 3);
+        assert!(is_limits_tool("guard/limits/top"));
+        assert!(!is_limits_tool("guard/health"));
+    }
+}
@@ -19,6 +19,17 @@
 //!
 //! This module provides the `guard/*` tools that mcp-guard exposes as an MCP server.
 //! Free tier tools are public, enterprise tools require admin authentication.
+//! The `guard/limits/*` tools in [`limits`], the `guard/upstreams/*` tools
+//! in [`upstreams`] and `guard/policy/test` in [`policy`] are answered by the
+//! HTTP server for admin identities.
+
+mod limits;
+mod policy;
+mod upstreams;
+
+pub use limits::{is_limits_tool, LimitsGuardTools};
+pub use policy::{is_policy_tool, PolicyGuardTools};
+pub use upstreams::{is_upstreams_tool, UpstreamsGuardTools};
 
 use async_trait::async_trait;
 use metrics_exporter_prometheus::PrometheusHandle;
@@ -93,6 +104,11 @@ impl ToolResult {
     }
 }
 
+/// Render a JSON value as a pretty-printed text result
+fn json_result(value: &Value) -> ToolResult {
+    ToolResult::text(serde_json::to_string_pretty(value).unwrap_or_default())
+}
+
 /// Trait for providing guard tools
 #[async_trait]
 pub trait GuardToolsProvider: Send + Sync {
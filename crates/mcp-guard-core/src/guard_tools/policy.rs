@@ -0,0 +1,380 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Policy simulation tool (`guard/policy/test`)
+//!
+//! Evaluates a hypothetical request against the gateway's policies without
+//! forwarding it, and returns the decision of every stage in the order the
+//! gateway applies them: authentication, lockout, tenancy, rate limits, the
+//! server route, the method policy, decoy tools, authorization, secret
+//! scrubbing and approval. Admins use it to answer "why was this denied?"
+//! without reproducing the call. Every stage is evaluated, even after a
+//! denial, so one run shows all the rules a request would trip.
+//!
+//! Only `[[auth.api_keys]]` identities can be resolved by ID. Token-based
+//! identities (JWT, OAuth, mTLS) carry their policy in the credential, so
+//! they are evaluated without per-identity restrictions.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{json_result, GuardToolError, GuardToolsProvider, ToolDefinition, ToolResult};
+use crate::auth::{ApiKeyProvider, Identity};
+use crate::authz::AuthzDecision;
+use crate::server::AppState;
+use crate::transport::Message;
+
+/// Check if a tool name belongs to the policy tools
+pub fn is_policy_tool(name: &str) -> bool {
+    name.starts_with("guard/policy/")
+}
+
+/// Policy simulation over the gateway's live configuration and state
+pub struct PolicyGuardTools<'a> {
+    state: &'a AppState,
+}
+
+impl<'a> PolicyGuardTools<'a> {
+    pub fn new(state: &'a AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl GuardToolsProvider for PolicyGuardTools<'_> {
+    fn list_tools(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition {
+            name: "guard/policy/test".to_string(),
+            description: "Evaluate a hypothetical request against the gateway's policies \
+                          without executing it, and show each decision"
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "identity_id": {
+                        "type": "string",
+                        "description": "Identity making the request"
+                    },
+                    "method": {
+                        "type": "string",
+                        "default": "tools/call",
+                        "description": "JSON-RPC method"
+                    },
+                    "tool": {
+                        "type": "string",
+                        "description": "Tool name for tools/call (namespaced in aggregate mode)"
+                    },
+                    "arguments": {
+                        "type": "object",
+                        "description": "Tool call arguments"
+                    },
+                    "params": {
+                        "type": "object",
+                        "description": "Request params for other methods, e.g. {\"uri\": ...}"
+                    },
+                    "server": {
+                        "type": "string",
+                        "description": "Server route the request is sent to (multi-server mode)"
+                    }
+                },
+                "required": ["identity_id"],
+                "additionalProperties": false
+            }),
+        }]
+    }
+
+    async fn call_tool(&self, name: &str, args: Value) -> Result<ToolResult, GuardToolError> {
+        match name {
+            "guard/policy/test" => self.handle_test(args),
+            _ => Err(GuardToolError::NotFound(name.to_string())),
+        }
+    }
+}
+
+/// Decisions of one simulated request, in evaluation order
+#[derive(Default)]
+struct Trace {
+    steps: Vec<Value>,
+    /// First stage that denied the request, and why
+    denial: Option<(&'static str, String)>,
+}
+
+impl Trace {
+    /// Record a stage; `details` (an object) is merged into its entry
+    fn record(&mut self, step: &'static str, result: &str, details: Value) {
+        let mut entry = json!({ "step": step, "result": result });
+        if let (Some(entry), Value::Object(details)) = (entry.as_object_mut(), details) {
+            entry.extend(details);
+        }
+        self.steps.push(entry);
+    }
+
+    fn allow(&mut self, step: &'static str, details: Value) {
+        self.record(step, "allow", details);
+    }
+
+    fn deny(&mut self, step: &'static str, reason: String, details: Value) {
+        self.record(step, "deny", details);
+        if let Some(entry) = self.steps.last_mut().and_then(Value::as_object_mut) {
+            entry.insert("reason".to_string(), json!(reason));
+        }
+        self.denial.get_or_insert((step, reason));
+    }
+
+    /// Record a stage that informs but does not decide
+    fn info(&mut self, step: &'static str, details: Value) {
+        self.record(step, "info", details);
+    }
+
+    /// Record a stage that does not apply to this request or configuration
+    fn skip(&mut self, step: &'static str, detail: &str) {
+        self.record(step, "skip", json!({ "detail": detail }));
+    }
+}
+
+impl PolicyGuardTools<'_> {
+    fn handle_test(&self, args: Value) -> Result<ToolResult, GuardToolError> {
+        let state = self.state;
+        let identity_id = string_arg(&args, "identity_id")?
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| GuardToolError::InvalidArguments("Missing identity_id".to_string()))?;
+        let method = string_arg(&args, "method")?.unwrap_or("tools/call");
+        let tool = string_arg(&args, "tool")?;
+        let server = string_arg(&args, "server")?;
+        let message = simulated_request(method, tool, &args)?;
+
+        let mut trace = Trace::default();
+        let identity = self.authenticate(identity_id, &mut trace);
+
+        if state.identity_store.is_locked_out(identity_id) {
+            trace.deny("lockout", "Identity is locked out".to_string(), json!({}));
+        } else {
+            trace.allow("lockout", json!({}));
+        }
+
+        let identity = if state.tenants.is_enabled() {
+            match state.tenants.apply(identity.clone()) {
+                Ok(identity) => {
+                    trace.allow("tenancy", json!({ "tenant": identity.tenant }));
+                    identity
+                }
+                Err(e) => {
+                    trace.deny("tenancy", e.to_string(), json!({}));
+                    identity
+                }
+            }
+        } else {
+            trace.skip("tenancy", "No tenants configured");
+            identity
+        };
+
+        if state.config.rate_limit.enabled {
+            trace.info(
+                "rate_limit",
+                json!({
+                    "limit": state.rate_limiter.identity_limit(identity_id),
+                    "last_request": state.identity_store.get(identity_id).map(|a| a.rate_limit),
+                }),
+            );
+        } else {
+            trace.skip("rate_limit", "Rate limiting is disabled");
+        }
+
+        match (server, state.router.as_deref()) {
+            (Some(server), Some(router)) if router.find_route_by_name(server).is_none() => {
+                trace.deny("route", format!("Unknown server '{}'", server), json!({}));
+            }
+            (Some(server), _) if !state.tenants.allows_server(&identity, server) => {
+                trace.deny(
+                    "route",
+                    format!("Tenant may not use server '{}'", server),
+                    json!({ "server": server }),
+                );
+            }
+            (Some(server), _) => trace.allow("route", json!({ "server": server })),
+            (None, _) => trace.skip("route", "No server given"),
+        }
+
+        if state.method_policy.permits(server, method) {
+            trace.allow("methods", json!({ "method": method }));
+        } else {
+            trace.deny(
+                "methods",
+                format!("Method '{}' is not allowed", method),
+                json!({ "method": method }),
+            );
+        }
+
+        match tool {
+            Some(tool) if state.honeypot.is_decoy(tool) => trace.deny(
+                "honeypot",
+                format!("'{}' is a decoy tool; calling it is audited", tool),
+                json!({}),
+            ),
+            Some(_) => trace.allow("honeypot", json!({})),
+            None => trace.skip("honeypot", "Not a tool call"),
+        }
+
+        let allow_list = match method {
+            "tools/call" => Some(("allowed_tools", &identity.allowed_tools)),
+            "resources/read" => Some(("allowed_resources", &identity.allowed_resources)),
+            "prompts/get" => Some(("allowed_prompts", &identity.allowed_prompts)),
+            _ => None,
+        };
+        let mut details = allow_list.map_or_else(|| json!({}), |(key, list)| json!({ key: list }));
+        let (decision, rule) = state.evaluate_authorization(&identity, &message);
+        if let (Some(details), Some(rule)) = (details.as_object_mut(), rule) {
+            details.insert("rule".to_string(), json!(rule));
+        }
+        match decision {
+            AuthzDecision::Allow => trace.allow("authorization", details),
+            AuthzDecision::Deny(reason) => trace.deny("authorization", reason, details),
+        }
+
+        if state.scrubber.is_empty() {
+            trace.skip("scrubbing", "No scrubbing rules configured");
+        } else {
+            match state.scrubber.scrub(&mut message.clone()) {
+                Ok(rules) => trace.allow("scrubbing", json!({ "redacted_by": rules })),
+                Err(blocked) => trace.deny(
+                    "scrubbing",
+                    format!("Params contain a secret matching rule '{}'", blocked.rule),
+                    json!({ "rule": blocked.rule }),
+                ),
+            }
+        }
+
+        match tool.and_then(|tool| state.rate_limiter.tool_limit(tool)) {
+            Some((requests_per_second, burst_size)) => trace.info(
+                "tool_rate_limit",
+                json!({
+                    "requests_per_second": requests_per_second,
+                    "burst_size": burst_size,
+                }),
+            ),
+            None => trace.skip("tool_rate_limit", "No per-tool limit applies"),
+        }
+
+        match tool {
+            Some(tool) if state.approvals.requires_approval(tool) => trace.info(
+                "approval",
+                json!({ "detail": "Held until an approver decides" }),
+            ),
+            Some(_) => trace.allow("approval", json!({})),
+            None => trace.skip("approval", "Not a tool call"),
+        }
+
+        let (decision, denied_by, reason) = match trace.denial {
+            Some((step, reason)) => ("deny", Some(step), Some(reason)),
+            None => ("allow", None, None),
+        };
+        Ok(json_result(&json!({
+            "decision": decision,
+            "denied_by": denied_by,
+            "reason": reason,
+            "request": {
+                "identity_id": identity_id,
+                "method": method,
+                "tool": tool,
+                "server": server,
+            },
+            "steps": trace.steps,
+        })))
+    }
+
+    /// Resolve the identity the way the gateway would, recording how
+    fn authenticate(&self, identity_id: &str, trace: &mut Trace) -> Identity {
+        let api_keys = &self.state.config.auth.api_keys;
+        if !api_keys.iter().any(|key| key.id == identity_id) {
+            trace.info(
+                "authentication",
+                json!({
+                    "provider": self.state.auth_provider.name(),
+                    "detail": "Not an API key; evaluated without per-identity restrictions",
+                }),
+            );
+            return bare_identity(identity_id);
+        }
+        match ApiKeyProvider::new(api_keys.clone()).identity_for_id(identity_id) {
+            Ok(identity) => {
+                trace.allow("authentication", json!({ "provider": "api_key" }));
+                identity
+            }
+            Err(e) => {
+                trace.deny(
+                    "authentication",
+                    format!("API key '{}' cannot be used: {}", identity_id, e),
+                    json!({ "provider": "api_key" }),
+                );
+                bare_identity(identity_id)
+            }
+        }
+    }
+}
+
+/// Identity without restrictions of its own
+fn bare_identity(identity_id: &str) -> Identity {
+    Identity {
+        id: identity_id.to_string(),
+        name: None,
+        allowed_tools: None,
+        allowed_resources: None,
+        allowed_prompts: None,
+        rate_limit: None,
+        claims: Default::default(),
+        tenant: None,
+        argument_constraints: None,
+    }
+}
+
+/// The request being simulated
+fn simulated_request(
+    method: &str,
+    tool: Option<&str>,
+    args: &Value,
+) -> Result<Message, GuardToolError> {
+    let params = match (tool, args.get("params")) {
+        (Some(tool), _) => json!({
+            "name": tool,
+            "arguments": args.get("arguments").cloned().unwrap_or_else(|| json!({})),
+        }),
+        (None, Some(params)) if params.is_object() => params.clone(),
+        (None, Some(_)) => {
+            return Err(GuardToolError::InvalidArguments(
+                "params must be an object".to_string(),
+            ))
+        }
+        (None, None) => json!({}),
+    };
+    if method == "tools/call" && tool.is_none() {
+        return Err(GuardToolError::InvalidArguments(
+            "tools/call needs a tool".to_string(),
+        ));
+    }
+    Ok(Message::request(0, method, Some(params)))
+}
+
+fn string_arg<'a>(args: &'a Value, key: &str) -> Result<Option<&'a str>, GuardToolError> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(_) => Err(GuardToolError::InvalidArguments(format!(
+            "{} must be a string",
+            key
+        ))),
+    }
+}
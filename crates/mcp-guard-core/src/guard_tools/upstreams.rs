@@ -0,0 +1,488 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Upstream management tools (`guard/upstreams/*`)
+//!
+//! Lets admins operate upstream servers from an MCP client:
+//! - `guard/upstreams/list` - Routes with their transport, health and drain state
+//! - `guard/upstreams/restart` - Respawn a stdio upstream in place
+//! - `guard/upstreams/drain` - Take a route out of rotation, or put it back
+//! - `guard/upstreams/check` - Run an on-demand connectivity check
+//!
+//! In single-server mode the upstream is addressed as `default`. The HTTP
+//! server answers these for identities listed in `admin.identities`.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{json_result, GuardToolError, GuardToolsProvider, ToolDefinition, ToolResult};
+use crate::config::UpstreamConfig;
+use crate::router::ServerRouter;
+use crate::transport::{check_upstream, Transport, UpstreamTarget};
+
+/// Name of the upstream in single-server mode
+const DEFAULT_UPSTREAM: &str = "default";
+
+/// Default timeout of `guard/upstreams/check`
+const DEFAULT_CHECK_TIMEOUT_SECS: u64 = 5;
+
+/// Longest timeout accepted by `guard/upstreams/check`
+const MAX_CHECK_TIMEOUT_SECS: u64 = 60;
+
+/// Upstream management tools over the gateway's live transports
+pub struct UpstreamsGuardTools<'a> {
+    upstream: &'a UpstreamConfig,
+    router: Option<&'a ServerRouter>,
+    transport: Option<&'a Arc<dyn Transport>>,
+}
+
+impl<'a> UpstreamsGuardTools<'a> {
+    pub fn new(
+        upstream: &'a UpstreamConfig,
+        router: Option<&'a ServerRouter>,
+        transport: Option<&'a Arc<dyn Transport>>,
+    ) -> Self {
+        Self {
+            upstream,
+            router,
+            transport,
+        }
+    }
+}
+
+#[async_trait]
+impl GuardToolsProvider for UpstreamsGuardTools<'_> {
+    fn list_tools(&self) -> Vec<ToolDefinition> {
+        let server = serde_json::json!({
+            "type": "string",
+            "description": "Server route name ('default' in single-server mode)"
+        });
+
+        vec![
+            ToolDefinition {
+                name: "guard/upstreams/list".to_string(),
+                description:
+                    "List upstream servers with their transport, health, drain state and canary"
+                        .to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {},
+                    "additionalProperties": false
+                }),
+            },
+            ToolDefinition {
+                name: "guard/upstreams/restart".to_string(),
+                description: "Restart a stdio upstream process in place".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": { "server": server.clone() },
+                    "required": ["server"],
+                    "additionalProperties": false
+                }),
+            },
+            ToolDefinition {
+                name: "guard/upstreams/drain".to_string(),
+                description: "Stop sending new requests to a server route, or resume it"
+                    .to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "server": server.clone(),
+                        "draining": {
+                            "type": "boolean",
+                            "default": true,
+                            "description": "false puts the route back in rotation"
+                        }
+                    },
+                    "required": ["server"],
+                    "additionalProperties": false
+                }),
+            },
+            ToolDefinition {
+                name: "guard/upstreams/check".to_string(),
+                description: "Check that an upstream server is reachable and responding"
+                    .to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "server": server,
+                        "timeout_secs": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_CHECK_TIMEOUT_SECS,
+                            "default": DEFAULT_CHECK_TIMEOUT_SECS,
+                            "description": "How long to wait for the upstream"
+                        }
+                    },
+                    "required": ["server"],
+                    "additionalProperties": false
+                }),
+            },
+        ]
+    }
+
+    async fn call_tool(&self, name: &str, args: Value) -> Result<ToolResult, GuardToolError> {
+        match name {
+            "guard/upstreams/list" => Ok(self.handle_list()),
+            "guard/upstreams/restart" => self.handle_restart(args).await,
+            "guard/upstreams/drain" => self.handle_drain(args),
+            "guard/upstreams/check" => self.handle_check(args).await,
+            _ => Err(GuardToolError::NotFound(name.to_string())),
+        }
+    }
+}
+
+impl UpstreamsGuardTools<'_> {
+    fn handle_list(&self) -> ToolResult {
+        let upstreams: Vec<Value> = match (self.router, self.transport) {
+            (Some(router), _) => router
+                .route_names()
+                .into_iter()
+                .filter_map(|name| router.find_route_by_name(name))
+                .map(|route| {
+                    serde_json::json!({
+                        "name": route.config.name,
+                        "path_prefix": route.config.path_prefix,
+                        "transport": route.transport.transport_type(),
+                        "healthy": route.transport.is_healthy(),
+                        "draining": router.is_draining(&route.config.name),
+                        "canary": route.canary.as_ref().map(|canary| serde_json::json!({
+                            "transport": canary.transport.transport_type(),
+                            "healthy": canary.transport.is_healthy(),
+                            "percent": canary.config.percent,
+                        })),
+                    })
+                })
+                .collect(),
+            (None, Some(transport)) => vec![serde_json::json!({
+                "name": DEFAULT_UPSTREAM,
+                "transport": transport.transport_type(),
+                "healthy": transport.is_healthy(),
+                "draining": false,
+            })],
+            (None, None) => vec![],
+        };
+        json_result(&serde_json::json!({ "upstreams": upstreams }))
+    }
+
+    async fn handle_restart(&self, args: Value) -> Result<ToolResult, GuardToolError> {
+        let server = server_arg(&args)?;
+        let result = match (self.router, self.transport) {
+            (Some(router), _) => {
+                if router.find_route_by_name(server).is_none() {
+                    return Err(unknown_server(server));
+                }
+                router
+                    .restart_route(server)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            (None, Some(transport)) if server == DEFAULT_UPSTREAM => {
+                transport.restart().await.map_err(|e| e.to_string())
+            }
+            _ => return Err(unknown_server(server)),
+        };
+
+        match result {
+            Ok(()) => {
+                tracing::info!(server = %server, "Upstream restarted");
+                Ok(json_result(
+                    &serde_json::json!({ "server": server, "restarted": true }),
+                ))
+            }
+            Err(reason) => {
+                tracing::warn!(server = %server, error = %reason, "Upstream restart failed");
+                Ok(ToolResult::error(format!(
+                    "Failed to restart '{}': {}",
+                    server, reason
+                )))
+            }
+        }
+    }
+
+    fn handle_drain(&self, args: Value) -> Result<ToolResult, GuardToolError> {
+        let server = server_arg(&args)?;
+        let draining = match args.get("draining") {
+            None | Some(Value::Null) => true,
+            Some(value) => value.as_bool().ok_or_else(|| {
+                GuardToolError::InvalidArguments("draining must be a boolean".to_string())
+            })?,
+        };
+        let Some(router) = self.router else {
+            return Err(GuardToolError::InvalidArguments(
+                "Draining requires multi-server routing".to_string(),
+            ));
+        };
+        if !router.set_draining(server, draining) {
+            return Err(unknown_server(server));
+        }
+        tracing::info!(server = %server, draining, "Upstream drain state changed");
+
+        Ok(json_result(
+            &serde_json::json!({ "server": server, "draining": draining }),
+        ))
+    }
+
+    async fn handle_check(&self, args: Value) -> Result<ToolResult, GuardToolError> {
+        let server = server_arg(&args)?;
+        let timeout_secs = match args.get("timeout_secs") {
+            None | Some(Value::Null) => DEFAULT_CHECK_TIMEOUT_SECS,
+            Some(value) => value
+                .as_u64()
+                .filter(|secs| (1..=MAX_CHECK_TIMEOUT_SECS).contains(secs))
+                .ok_or_else(|| {
+                    GuardToolError::InvalidArguments(format!(
+                        "timeout_secs must be between 1 and {}",
+                        MAX_CHECK_TIMEOUT_SECS
+                    ))
+                })?,
+        };
+
+        let target = match self.router {
+            Some(router) => router
+                .find_route_by_name(server)
+                .map(|route| UpstreamTarget::from_route(&route.config)),
+            None if server == DEFAULT_UPSTREAM => {
+                Some(UpstreamTarget::from_upstream(self.upstream))
+            }
+            None => None,
+        }
+        .ok_or_else(|| unknown_server(server))?
+        .map_err(GuardToolError::Internal)?;
+
+        let response = match check_upstream(&target, Duration::from_secs(timeout_secs)).await {
+            Ok(check) => serde_json::json!({
+                "server": server,
+                "reachable": true,
+                "check": check,
+            }),
+            Err(e) => serde_json::json!({
+                "server": server,
+                "reachable": false,
+                "error": e.to_string(),
+            }),
+        };
+        Ok(json_result(&response))
+    }
+}
+
+/// Whether a tool name belongs to the upstream management tools
+pub fn is_upstreams_tool(name: &str) -> bool {
+    name.starts_with("guard/upstreams/")
+}
+
+fn server_arg(args: &Value) -> Result<&str, GuardToolError> {
+    args.get("server")
+        .and_then(|v| v.as_str())
+        .filter(|server| !server.is_empty())
+        .ok_or_else(|| GuardToolError::InvalidArguments("Missing server".to_string()))
+}
+
+fn unknown_server(server: &str) -> GuardToolError {
+    GuardToolError::InvalidArguments(format!("Unknown server: {}", server))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ServerRouteConfig, TransportType};
+    use crate::mocks::MockTransport;
+    use crate::router::ServerRoute;
+    use crate::transport::{Message, StdioTransport};
+
+    fn upstream_config(value: Value) -> UpstreamConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn route(name: &str, url: &str) -> ServerRoute {
+        ServerRoute {
+            config: ServerRouteConfig {
+                name: name.to_string(),
+                path_prefix: format!("/{}", name),
+                transport: TransportType::Http,
+                command: None,
+                args: vec![],
+                env: Default::default(),
+                cwd: None,
+                inherit_env: true,
+                spawn: Default::default(),
+                idle_ttl_secs: 300,
+                url: Some(url.to_string()),
+                strip_prefix: false,
+                headers: Default::default(),
+                tls: None,
+                dns_refresh_secs: 300,
+                socket_path: None,
+                retry: Default::default(),
+                timeouts: Default::default(),
+                fair_queue: Default::default(),
+                response_limits: Default::default(),
+                transforms: Default::default(),
+                catalog: Default::default(),
+                mock: Default::default(),
+                canary: None,
+                methods: Default::default(),
+            },
+            transport: Arc::new(MockTransport::new()),
+            canary: None,
+        }
+    }
+
+    fn json(result: ToolResult) -> Value {
+        serde_json::from_str(&result.content.unwrap()[0].text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_drain_and_check_routes() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let config = upstream_config(serde_json::json!({"transport": "http"}));
+        let router = ServerRouter::from_routes(vec![
+            route("github", &mock_server.uri()),
+            route("fs", "http://127.0.0.1:1"),
+        ]);
+        let tools = UpstreamsGuardTools::new(&config, Some(&router), None);
+
+        let drained = json(
+            tools
+                .call_tool(
+                    "guard/upstreams/drain",
+                    serde_json::json!({"server": "github"}),
+                )
+                .await
+                .unwrap(),
+        );
+        assert_eq!(drained["draining"], true);
+        assert!(router.is_draining("github"));
+
+        let list = json(
+            tools
+                .call_tool("guard/upstreams/list", serde_json::json!({}))
+                .await
+                .unwrap(),
+        );
+        let upstreams = list["upstreams"].as_array().unwrap();
+        assert_eq!(upstreams.len(), 2);
+        let github = upstreams.iter().find(|u| u["name"] == "github").unwrap();
+        assert_eq!(github["draining"], true);
+        assert_eq!(github["healthy"], true);
+
+        let check = json(
+            tools
+                .call_tool(
+                    "guard/upstreams/check",
+                    serde_json::json!({"server": "github"}),
+                )
+                .await
+                .unwrap(),
+        );
+        assert_eq!(check["reachable"], true);
+        assert_eq!(check["check"]["http_status"], 200);
+
+        let check = json(
+            tools
+                .call_tool(
+                    "guard/upstreams/check",
+                    serde_json::json!({"server": "fs", "timeout_secs": 2}),
+                )
+                .await
+                .unwrap(),
+        );
+        assert_eq!(check["reachable"], false);
+
+        // Only stdio upstreams can be restarted
+        let restart = tools
+            .call_tool(
+                "guard/upstreams/restart",
+                serde_json::json!({"server": "github"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(restart.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_restart_single_stdio_upstream() {
+        let config = upstream_config(serde_json::json!({"transport": "stdio", "command": "cat"}));
+        let transport: Arc<dyn Transport> =
+            Arc::new(StdioTransport::spawn("cat", &[]).await.unwrap());
+        let tools = UpstreamsGuardTools::new(&config, None, Some(&transport));
+
+        let restart = json(
+            tools
+                .call_tool(
+                    "guard/upstreams/restart",
+                    serde_json::json!({"server": "default"}),
+                )
+                .await
+                .unwrap(),
+        );
+        assert_eq!(restart["restarted"], true);
+
+        // The replacement process serves traffic
+        let message = Message::request(1, "ping", None);
+        transport.send(message).await.unwrap();
+        let echoed = transport.receive().await.unwrap();
+        assert_eq!(echoed.method.as_deref(), Some("ping"));
+        transport.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_arguments() {
+        let config = upstream_config(serde_json::json!({"transport": "http"}));
+        let router = ServerRouter::from_routes(vec![route("github", "http://127.0.0.1:1")]);
+        let tools = UpstreamsGuardTools::new(&config, Some(&router), None);
+
+        for (tool, args) in [
+            ("guard/upstreams/restart", serde_json::json!({})),
+            (
+                "guard/upstreams/drain",
+                serde_json::json!({"server": "slack"}),
+            ),
+            (
+                "guard/upstreams/drain",
+                serde_json::json!({"server": "github", "draining": "yes"}),
+            ),
+            (
+                "guard/upstreams/check",
+                serde_json::json!({"server": "github", "timeout_secs": 0}),
+            ),
+        ] {
+            let result = tools.call_tool(tool, args).await;
+            assert!(matches!(result, Err(GuardToolError::InvalidArguments(_))));
+        }
+
+        // Draining needs routes to take out of rotation
+        let single = UpstreamsGuardTools::new(&config, None, None);
+        let result = single
+            .call_tool(
+                "guard/upstreams/drain",
+                serde_json::json!({"server": "default"}),
+            )
+            .await;
+        assert!(matches!(result, Err(GuardToolError::InvalidArguments(_))));
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Decoy tools for intrusion detection
+//!
+//! The [`Honeypot`] adds the tools from `[honeypot]` to every `tools/list`
+//! response and answers calls to them itself. Nothing legitimate calls a
+//! decoy, so a call is reported as a likely credential compromise: the
+//! identity can be locked out in the [`IdentityStore`] and a webhook is
+//! notified. Decoys are matched before authorization, so an identity that
+//! is not allowed to call them still trips them.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::{ApprovalWebhookFormat, DecoyToolConfig, HoneypotConfig};
+use crate::identity_store::IdentityStore;
+
+/// Timeout for webhook notifications
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// Decoy tools and what happens when one is called
+#[derive(Default)]
+pub struct Honeypot {
+    tools: Vec<DecoyToolConfig>,
+    /// `Some(None)` locks out until restart
+    lockout: Option<Option<Duration>>,
+    webhook: Option<Webhook>,
+}
+
+impl std::fmt::Debug for Honeypot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Honeypot")
+            .field("tool_count", &self.tools.len())
+            .field("lockout", &self.lockout)
+            .field("webhook", &self.webhook.is_some())
+            .finish()
+    }
+}
+
+/// A call to a decoy tool
+#[derive(Debug, Clone, Serialize)]
+pub struct Trip {
+    pub identity_id: String,
+    pub tool: String,
+    /// Whether the identity was locked out
+    pub locked_out: bool,
+    pub triggered_at: DateTime<Utc>,
+}
+
+impl Honeypot {
+    /// Build the honeypot from configuration
+    pub fn new(config: &HoneypotConfig) -> Self {
+        Self {
+            tools: config.tools.clone(),
+            lockout: config.locks_out().then_some(match config.lockout_secs {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            }),
+            webhook: config.webhook_url.as_ref().map(|url| Webhook {
+                client: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                    .build()
+                    .unwrap_or_default(),
+                url: url.clone(),
+                format: config.webhook_format,
+                headers: config.webhook_headers.clone(),
+            }),
+        }
+    }
+
+    /// Whether any decoy tool is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.tools.is_empty()
+    }
+
+    /// Whether `tool` is a decoy
+    pub fn is_decoy(&self, tool: &str) -> bool {
+        self.tools.iter().any(|decoy| decoy.name == tool)
+    }
+
+    /// Decoys as `tools/list` entries
+    pub fn list_tools(&self) -> Vec<Value> {
+        self.tools
+            .iter()
+            .map(|tool| {
+                let mut entry = json!({
+                    "name": tool.name,
+                    "inputSchema": tool.input_schema,
+                });
+                if let Some(ref description) = tool.description {
+                    entry["description"] = json!(description);
+                }
+                entry
+            })
+            .collect()
+    }
+
+    /// Record a call to the decoy `tool`: lock the identity out if
+    /// configured and notify the webhook
+    pub fn trip(&self, identity_id: &str, tool: &str, identities: &IdentityStore) -> Trip {
+        if let Some(duration) = self.lockout {
+            identities.lock_out(identity_id, duration);
+        }
+        let trip = Trip {
+            identity_id: identity_id.to_string(),
+            tool: tool.to_string(),
+            locked_out: self.lockout.is_some(),
+            triggered_at: Utc::now(),
+        };
+        if let Some(ref webhook) = self.webhook {
+            let webhook = webhook.clone();
+            let trip = trip.clone();
+            tokio::spawn(async move { webhook.notify(&trip).await });
+        }
+        trip
+    }
+}
+
+// ============================================================================
+// Webhook Notifications
+// ============================================================================
+
+#[derive(Clone)]
+struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    format: ApprovalWebhookFormat,
+    headers: HashMap<String, String>,
+}
+
+impl Webhook {
+    async fn notify(&self, trip: &Trip) {
+        let mut builder = self.client.post(&self.url).json(&self.payload(trip));
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        match builder.send().await {
+            Ok(response) if response.status().is_success() => {
+                tracing::debug!(identity_id = %trip.identity_id, "Honeypot webhook delivered");
+            }
+            Ok(response) => {
+                tracing::warn!(
+                    identity_id = %trip.identity_id,
+                    status = %response.status(),
+                    "Honeypot webhook rejected"
+                );
+            }
+            Err(e) => {
+                tracing::warn!(
+                    identity_id = %trip.identity_id,
+                    error = %e,
+                    "Honeypot webhook failed"
+                );
+            }
+        }
+    }
+
+    fn payload(&self, trip: &Trip) -> Value {
+        match self.format {
+            ApprovalWebhookFormat::Json => json!({
+                "type": "honeypot_triggered",
+                "trip": trip,
+            }),
+            ApprovalWebhookFormat::Slack => json!({
+                "text": format!(
+                    ":rotating_light: *Decoy tool called*: `{}` called `{}` at {}{}. \
+                     Its credentials may be compromised.",
+                    trip.identity_id,
+                    trip.tool,
+                    trip.triggered_at.to_rfc3339(),
+                    if trip.locked_out { " and was locked out" } else { "" },
+                ),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> HoneypotConfig {
+        HoneypotConfig {
+            tools: vec![DecoyToolConfig {
+                name: "export_customer_database".to_string(),
+                description: Some("Export all customer records".to_string()),
+                input_schema: json!({ "type": "object" }),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decoys_are_listed() {
+        let honeypot = Honeypot::new(&config());
+        assert!(honeypot.is_enabled());
+        assert!(honeypot.is_decoy("export_customer_database"));
+        assert!(!honeypot.is_decoy("read_file"));
+        assert!(!Honeypot::default().is_enabled());
+
+        let tools = honeypot.list_tools();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0]["name"], "export_customer_database");
+        assert_eq!(tools[0]["description"], "Export all customer records");
+        assert_eq!(tools[0]["inputSchema"], json!({ "type": "object" }));
+    }
+
+    #[test]
+    fn test_trip_locks_out_when_configured() {
+        let identities = IdentityStore::default();
+
+        let trip = Honeypot::new(&config()).trip("alice", "export_customer_database", &identities);
+        assert!(!trip.locked_out);
+        assert!(!identities.is_locked_out("alice"));
+
+        let honeypot = Honeypot::new(&HoneypotConfig {
+            lockout_secs: 3600,
+            ..config()
+        });
+        let trip = honeypot.trip("alice", "export_customer_database", &identities);
+        assert!(trip.locked_out);
+        assert!(identities.is_locked_out("alice"));
+        assert!(!identities.is_locked_out("bob"));
+    }
+
+    #[test]
+    fn test_webhook_payloads() {
+        let trip = Trip {
+            identity_id: "alice".to_string(),
+            tool: "export_customer_database".to_string(),
+            locked_out: true,
+            triggered_at: Utc::now(),
+        };
+        let webhook = |format| Webhook {
+            client: reqwest::Client::new(),
+            url: "https://example.com/hook".to_string(),
+            format,
+            headers: HashMap::new(),
+        };
+
+        let payload = webhook(ApprovalWebhookFormat::Json).payload(&trip);
+        assert_eq!(payload["type"], "honeypot_triggered");
+        assert_eq!(payload["trip"]["identity_id"], "alice");
+        assert_eq!(payload["trip"]["locked_out"], true);
+
+        let payload = webhook(ApprovalWebhookFormat::Slack).payload(&trip);
+        let text = payload["text"].as_str().unwrap();
+        assert!(text.contains("`alice` called `export_customer_database`"));
+        assert!(text.contains("locked out"));
+    }
+}
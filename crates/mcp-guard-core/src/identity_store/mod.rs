@@ -0,0 +1,365 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Active identity registry for mcp-guard
+//!
+//! Tracks every identity that has made an authenticated request recently:
+//! when it was first and last seen, how many requests it made and the state
+//! of its rate limit bucket after its latest request. The registry backs the
+//! `/admin/identities` endpoints and the `mcp_guard_active_identities` gauge.
+//!
+//! Force-expiring an identity drops it from the registry and records a cutoff:
+//! tokens carrying an `iat` claim at or before the cutoff are rejected, so
+//! credentials issued before an offboarding stop working even if they have
+//! not expired yet.
+//!
+//! Locking an identity out (after it called a decoy tool) rejects all of its
+//! credentials, including API keys, until the lockout ends.
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::auth::Identity;
+use crate::rate_limit::RateLimitResult;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Identities seen within this window count as active (15 minutes).
+pub const DEFAULT_ACTIVE_WINDOW: Duration = Duration::from_secs(900);
+
+/// New identities recorded between sweeps of idle entries.
+/// Keeps the registry bounded by recent traffic without a background task.
+const CLEANUP_THRESHOLD: usize = 1000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Rate limit bucket state after an identity's latest request
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct RateBucketState {
+    /// Configured limit (requests per second)
+    pub limit: u32,
+    /// Approximate remaining requests in the current window
+    pub remaining: u32,
+    /// Unix timestamp when the bucket resets
+    pub reset_at: u64,
+}
+
+/// Snapshot of an active identity
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveIdentity {
+    pub id: String,
+    pub name: Option<String>,
+    /// Authentication method, when the provider records one (e.g. "mtls")
+    pub auth_method: Option<String>,
+    /// Unix timestamp of the first request in this registry
+    pub first_seen: u64,
+    /// Unix timestamp of the latest request
+    pub last_seen: u64,
+    /// Authenticated requests, including rate-limited ones
+    pub request_count: u64,
+    /// Requests rejected by the per-identity rate limit
+    pub rate_limited_count: u64,
+    pub rate_limit: RateBucketState,
+}
+
+struct IdentityRecord {
+    snapshot: ActiveIdentity,
+    last_seen_at: Instant,
+}
+
+/// Registry of recently active identities
+pub struct IdentityStore {
+    records: DashMap<String, IdentityRecord>,
+    /// Identity ID -> unix timestamp; tokens issued at or before it are rejected
+    expired: DashMap<String, u64>,
+    /// Identity ID -> unix timestamp the lockout ends; `None` until restart
+    locked_out: DashMap<String, Option<u64>>,
+    active_window: Duration,
+    insert_count: AtomicUsize,
+}
+
+impl Default for IdentityStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACTIVE_WINDOW)
+    }
+}
+
+impl std::fmt::Debug for IdentityStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdentityStore")
+            .field("records", &self.records.len())
+            .field("expired", &self.expired.len())
+            .field("locked_out", &self.locked_out.len())
+            .field("active_window", &self.active_window)
+            .finish()
+    }
+}
+
+impl IdentityStore {
+    /// Create an empty registry
+    pub fn new(active_window: Duration) -> Self {
+        Self {
+            records: DashMap::new(),
+            expired: DashMap::new(),
+            locked_out: DashMap::new(),
+            active_window,
+            insert_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Record an authenticated request and its rate limit outcome
+    pub fn record_request(&self, identity: &Identity, rate_limit: &RateLimitResult) {
+        let now = unix_now();
+        let bucket = RateBucketState {
+            limit: rate_limit.limit,
+            remaining: rate_limit.remaining,
+            reset_at: rate_limit.reset_at,
+        };
+
+        if !self.records.contains_key(&identity.id)
+            && self.insert_count.fetch_add(1, Ordering::Relaxed) + 1 >= CLEANUP_THRESHOLD
+        {
+            self.insert_count.store(0, Ordering::Relaxed);
+            self.cleanup_idle();
+        }
+
+        let mut record =
+            self.records
+                .entry(identity.id.clone())
+                .or_insert_with(|| IdentityRecord {
+                    snapshot: ActiveIdentity {
+                        id: identity.id.clone(),
+                        name: None,
+                        auth_method: None,
+                        first_seen: now,
+                        last_seen: now,
+                        request_count: 0,
+                        rate_limited_count: 0,
+                        rate_limit: bucket.clone(),
+                    },
+                    last_seen_at: Instant::now(),
+                });
+
+        let snapshot = &mut record.snapshot;
+        snapshot.name = identity.name.clone();
+        snapshot.auth_method = identity
+            .claims
+            .get("auth_method")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        snapshot.last_seen = now;
+        snapshot.request_count += 1;
+        if !rate_limit.allowed {
+            snapshot.rate_limited_count += 1;
+        }
+        snapshot.rate_limit = bucket;
+        record.last_seen_at = Instant::now();
+    }
+
+    /// Active identities, most recently seen first
+    pub fn list(&self) -> Vec<ActiveIdentity> {
+        let mut identities: Vec<ActiveIdentity> = self
+            .records
+            .iter()
+            .filter(|record| record.last_seen_at.elapsed() < self.active_window)
+            .map(|record| record.snapshot.clone())
+            .collect();
+        identities.sort_by(|a, b| b.last_seen.cmp(&a.last_seen).then(a.id.cmp(&b.id)));
+        identities
+    }
+
+    /// Look up one active identity
+    pub fn get(&self, identity_id: &str) -> Option<ActiveIdentity> {
+        self.records
+            .get(identity_id)
+            .filter(|record| record.last_seen_at.elapsed() < self.active_window)
+            .map(|record| record.snapshot.clone())
+    }
+
+    /// Number of active identities
+    pub fn active_count(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|record| record.last_seen_at.elapsed() < self.active_window)
+            .count()
+    }
+
+    /// Remove identities idle for longer than the active window
+    pub fn cleanup_idle(&self) {
+        self.records
+            .retain(|_, record| record.last_seen_at.elapsed() < self.active_window);
+    }
+
+    /// Force-expire an identity; returns whether it was active
+    ///
+    /// Tokens for this identity with an `iat` at or before now are rejected
+    /// from here on. Credentials without `iat` (API keys, client certificates)
+    /// are unaffected and must be removed from configuration.
+    pub fn expire(&self, identity_id: &str) -> bool {
+        self.expired.insert(identity_id.to_string(), unix_now());
+        self.records.remove(identity_id).is_some()
+    }
+
+    /// Whether the identity's credential was issued before a force-expiry
+    pub fn is_expired(&self, identity: &Identity) -> bool {
+        let Some(cutoff) = self.expired.get(&identity.id).map(|c| *c) else {
+            return false;
+        };
+        identity
+            .claims
+            .get("iat")
+            .and_then(|v| v.as_u64())
+            .is_some_and(|issued_at| issued_at <= cutoff)
+    }
+
+    /// Reject every credential of an identity for `duration` (`None`: until
+    /// restart)
+    pub fn lock_out(&self, identity_id: &str, duration: Option<Duration>) {
+        let until = duration.map(|duration| unix_now() + duration.as_secs());
+        self.locked_out.insert(identity_id.to_string(), until);
+        self.records.remove(identity_id);
+    }
+
+    /// Whether the identity is locked out
+    pub fn is_locked_out(&self, identity_id: &str) -> bool {
+        let Some(until) = self.locked_out.get(identity_id).map(|until| *until) else {
+            return false;
+        };
+        match until {
+            Some(until) if until <= unix_now() => {
+                self.locked_out.remove(identity_id);
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn identity(id: &str, claims: HashMap<String, serde_json::Value>) -> Identity {
+        Identity {
+            id: id.to_string(),
+            name: Some(format!("{} name", id)),
+            allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims,
+            tenant: None,
+            argument_constraints: None,
+        }
+    }
+
+    fn rate(allowed: bool, remaining: u32) -> RateLimitResult {
+        RateLimitResult {
+            allowed,
+            retry_after_secs: if allowed { None } else { Some(1) },
+            limit: 10,
+            remaining,
+            reset_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_record_and_list() {
+        let store = IdentityStore::default();
+        let mut claims = HashMap::new();
+        claims.insert("auth_method".to_string(), serde_json::json!("mtls"));
+        let alice = identity("alice", claims);
+
+        store.record_request(&alice, &rate(true, 4));
+        store.record_request(&alice, &rate(false, 0));
+        store.record_request(&identity("bob", HashMap::new()), &rate(true, 9));
+
+        assert_eq!(store.active_count(), 2);
+        let alice = store.get("alice").unwrap();
+        assert_eq!(alice.request_count, 2);
+        assert_eq!(alice.rate_limited_count, 1);
+        assert_eq!(alice.auth_method.as_deref(), Some("mtls"));
+        assert_eq!(alice.rate_limit.remaining, 0);
+        assert_eq!(store.list().len(), 2);
+    }
+
+    #[test]
+    fn test_idle_identities_are_not_listed() {
+        let store = IdentityStore::new(Duration::from_millis(0));
+        store.record_request(&identity("alice", HashMap::new()), &rate(true, 1));
+
+        assert!(store.list().is_empty());
+        assert!(store.get("alice").is_none());
+        store.cleanup_idle();
+        assert_eq!(store.records.len(), 0);
+    }
+
+    #[test]
+    fn test_expire_rejects_tokens_issued_before_cutoff() {
+        let store = IdentityStore::default();
+        let issued = |iat: u64| {
+            let mut claims = HashMap::new();
+            claims.insert("iat".to_string(), serde_json::json!(iat));
+            identity("alice", claims)
+        };
+        store.record_request(&issued(1), &rate(true, 1));
+
+        assert!(!store.is_expired(&issued(1)));
+        assert!(store.expire("alice"));
+        assert!(!store.expire("alice"));
+        assert!(store.get("alice").is_none());
+
+        assert!(store.is_expired(&issued(1)));
+        assert!(!store.is_expired(&issued(unix_now() + 60)));
+        // No iat claim: nothing to compare against
+        assert!(!store.is_expired(&identity("alice", HashMap::new())));
+        let mut bob = issued(1);
+        bob.id = "bob".to_string();
+        assert!(!store.is_expired(&bob));
+    }
+
+    #[test]
+    fn test_lock_out() {
+        let store = IdentityStore::default();
+        store.record_request(&identity("alice", HashMap::new()), &rate(true, 1));
+        assert!(!store.is_locked_out("alice"));
+
+        store.lock_out("alice", None);
+        assert!(store.is_locked_out("alice"));
+        assert!(store.get("alice").is_none());
+        assert!(!store.is_locked_out("bob"));
+
+        store.lock_out("bob", Some(Duration::ZERO));
+        assert!(!store.is_locked_out("bob"));
+        store.lock_out("bob", Some(Duration::from_secs(60)));
+        assert!(store.is_locked_out("bob"));
+    }
+}
@@ -0,0 +1,698 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Write-ahead journal for critical tool calls
+//!
+//! Tool calls matching `journal.tools` are appended to a JSON Lines file, and
+//! synced to disk, before they are forwarded; the upstream's answer is
+//! appended once it arrives. Calls that failed to reach the upstream, or were
+//! in flight when the gateway stopped, stay pending and are redelivered by a
+//! background task, giving at-least-once delivery.
+//!
+//! A call is identified by the identity, the upstream and its JSON-RPC ID.
+//! Repeating a delivered call returns the recorded response instead of
+//! forwarding it again; repeating one that is still pending is refused. The
+//! file is compacted when the journal is opened, dropping abandoned calls and
+//! answers older than `journal.dedup_window_secs`.
+
+use chrono::{DateTime, Utc};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::JournalConfig;
+use crate::observability::record_journal_event;
+use crate::transport::{Message, TransportError};
+
+/// Why a call could not be journaled
+#[derive(Debug, thiserror::Error)]
+pub enum JournalError {
+    #[error("Tool call with ID {0} is already being delivered")]
+    InProgress(String),
+
+    #[error("Failed to write journal: {0}")]
+    Write(#[from] io::Error),
+}
+
+/// Identifies a journaled call
+///
+/// The same JSON-RPC ID from the same identity to the same upstream is
+/// treated as the same call.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JournalKey {
+    /// Identity that made the call
+    pub identity_id: String,
+    /// Upstream the call is delivered to ("default" in single-server mode)
+    pub upstream: String,
+    /// JSON-RPC ID of the call, serialized
+    pub request_id: String,
+}
+
+/// What to do with a call after consulting the journal
+#[derive(Debug)]
+pub enum JournalStart {
+    /// Not a journaled call: forward it as usual
+    Skip,
+    /// Journaled: forward it and report the outcome under this key
+    Forward(JournalKey),
+    /// Already delivered: answer with the recorded response
+    Replay(Message),
+}
+
+/// One line of the journal file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum JournalRecord {
+    /// Call accepted, written before it is forwarded
+    Pending {
+        key: JournalKey,
+        timestamp: DateTime<Utc>,
+        /// Failed deliveries so far (only non-zero after compaction)
+        #[serde(default)]
+        attempts: u32,
+        request: Message,
+    },
+    /// A delivery failed
+    Failed {
+        key: JournalKey,
+        timestamp: DateTime<Utc>,
+        error: String,
+    },
+    /// The upstream answered
+    Completed {
+        key: JournalKey,
+        timestamp: DateTime<Utc>,
+        response: Message,
+    },
+    /// Delivery was given up after `max_attempts`
+    Abandoned {
+        key: JournalKey,
+        timestamp: DateTime<Utc>,
+        error: String,
+    },
+}
+
+/// Where a journaled call stands
+#[derive(Debug, Clone)]
+enum Delivery {
+    /// Being forwarded right now
+    InFlight,
+    /// Waiting for redelivery
+    Pending { next_attempt: Instant },
+    /// The upstream answered
+    Completed {
+        response: Message,
+        at: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    request: Message,
+    /// Failed deliveries so far
+    attempts: u32,
+    delivery: Delivery,
+}
+
+/// Journal of critical tool calls and their delivery state
+pub struct Journal {
+    patterns: Vec<Pattern>,
+    servers: Vec<String>,
+    max_attempts: u32,
+    retry_interval: Duration,
+    dedup_window: Duration,
+    file: Option<Mutex<File>>,
+    entries: Mutex<HashMap<JournalKey, Entry>>,
+}
+
+impl std::fmt::Debug for Journal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Journal")
+            .field("enabled", &self.is_enabled())
+            .field("pattern_count", &self.patterns.len())
+            .finish()
+    }
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl Journal {
+    /// Create a journal that records nothing
+    pub fn disabled() -> Self {
+        Self {
+            patterns: Vec::new(),
+            servers: Vec::new(),
+            max_attempts: 1,
+            retry_interval: Duration::from_secs(30),
+            dedup_window: Duration::ZERO,
+            file: None,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Open the journal file, recovering calls left pending by the last run
+    ///
+    /// The file is compacted first. Unreadable lines, such as one cut short
+    /// by a crash, are skipped with a warning. Invalid patterns are skipped;
+    /// configuration validation rejects them first.
+    pub fn open(config: &JournalConfig) -> io::Result<Self> {
+        if !config.enabled() {
+            return Ok(Self::disabled());
+        }
+        let dedup_window = Duration::from_secs(config.dedup_window_secs);
+        let entries = load(&config.path, dedup_window)?;
+        compact(&config.path, &entries)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)?;
+
+        let pending = entries
+            .values()
+            .filter(|entry| !matches!(entry.delivery, Delivery::Completed { .. }))
+            .count();
+        tracing::info!(
+            path = %config.path.display(),
+            pending = pending,
+            "Journaling critical tool calls"
+        );
+        Ok(Self {
+            patterns: config
+                .tools
+                .iter()
+                .filter_map(|p| Pattern::new(p).ok())
+                .collect(),
+            servers: config.servers.clone(),
+            max_attempts: config.max_attempts.max(1),
+            retry_interval: Duration::from_secs(config.retry_interval_secs.max(1)),
+            dedup_window,
+            file: Some(Mutex::new(file)),
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Whether any calls are journaled
+    pub fn is_enabled(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Interval between redelivery rounds
+    pub fn retry_interval(&self) -> Duration {
+        self.retry_interval
+    }
+
+    /// Whether a message sent to `upstream` is journaled
+    pub fn applies_to(&self, upstream: &str, message: &Message) -> bool {
+        self.is_enabled()
+            && message.is_request()
+            && message.method.as_deref() == Some("tools/call")
+            && (self.servers.is_empty() || self.servers.iter().any(|s| s == upstream))
+            && crate::authz::extract_tool_name(message)
+                .is_some_and(|tool| self.patterns.iter().any(|p| p.matches(tool)))
+    }
+
+    /// Record a call before it is forwarded
+    ///
+    /// # Errors
+    /// Returns `JournalError::InProgress` if a call with the same key has not
+    /// been delivered yet, and `JournalError::Write` if the call could not be
+    /// persisted; it must not be forwarded then.
+    pub fn begin(
+        &self,
+        identity_id: &str,
+        upstream: &str,
+        message: &Message,
+    ) -> Result<JournalStart, JournalError> {
+        if !self.applies_to(upstream, message) {
+            return Ok(JournalStart::Skip);
+        }
+        let key = JournalKey {
+            identity_id: identity_id.to_string(),
+            upstream: upstream.to_string(),
+            request_id: message
+                .id
+                .as_ref()
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+        };
+
+        {
+            let mut entries = self.lock_entries();
+            match entries.get(&key) {
+                Some(Entry {
+                    delivery: Delivery::Completed { response, .. },
+                    request,
+                    ..
+                }) if same_call(request, message) => {
+                    record_journal_event("deduplicated");
+                    return Ok(JournalStart::Replay(response.clone()));
+                }
+                // A delivered ID reused for a different call starts a new one
+                Some(Entry {
+                    delivery: Delivery::Completed { .. },
+                    ..
+                })
+                | None => {}
+                Some(_) => return Err(JournalError::InProgress(key.request_id)),
+            }
+            entries.insert(
+                key.clone(),
+                Entry {
+                    request: message.clone(),
+                    attempts: 0,
+                    delivery: Delivery::InFlight,
+                },
+            );
+        }
+
+        let record = JournalRecord::Pending {
+            key: key.clone(),
+            timestamp: Utc::now(),
+            attempts: 0,
+            request: message.clone(),
+        };
+        if let Err(e) = self.append(&record) {
+            self.lock_entries().remove(&key);
+            return Err(e.into());
+        }
+        record_journal_event("journaled");
+        Ok(JournalStart::Forward(key))
+    }
+
+    /// Record the outcome of delivering a journaled call
+    ///
+    /// A response, including a JSON-RPC error, completes the call. A failure
+    /// leaves it pending for redelivery until `max_attempts` is reached.
+    pub fn finish(&self, key: &JournalKey, result: Result<&Message, &TransportError>) {
+        match result {
+            Ok(response) => self.complete(key, response),
+            Err(e) => self.fail(key, &e.to_string()),
+        }
+    }
+
+    /// Record the upstream's answer to a journaled call
+    pub fn complete(&self, key: &JournalKey, response: &Message) {
+        let at = Utc::now();
+        if let Some(entry) = self.lock_entries().get_mut(key) {
+            entry.delivery = Delivery::Completed {
+                response: response.clone(),
+                at,
+            };
+        }
+        self.append_logged(&JournalRecord::Completed {
+            key: key.clone(),
+            timestamp: at,
+            response: response.clone(),
+        });
+    }
+
+    /// Record a failed delivery of a journaled call
+    pub fn fail(&self, key: &JournalKey, error: &str) {
+        let abandoned = {
+            let mut entries = self.lock_entries();
+            let Some(entry) = entries.get_mut(key) else {
+                return;
+            };
+            entry.attempts += 1;
+            if entry.attempts >= self.max_attempts {
+                entries.remove(key);
+                true
+            } else {
+                entry.delivery = Delivery::Pending {
+                    next_attempt: Instant::now() + self.retry_interval,
+                };
+                false
+            }
+        };
+
+        let timestamp = Utc::now();
+        self.append_logged(&JournalRecord::Failed {
+            key: key.clone(),
+            timestamp,
+            error: error.to_string(),
+        });
+        record_journal_event("failed");
+        if abandoned {
+            tracing::error!(
+                identity_id = %key.identity_id,
+                upstream = %key.upstream,
+                request_id = %key.request_id,
+                error = %error,
+                "Giving up on journaled tool call"
+            );
+            self.append_logged(&JournalRecord::Abandoned {
+                key: key.clone(),
+                timestamp,
+                error: error.to_string(),
+            });
+            record_journal_event("abandoned");
+        }
+    }
+
+    /// Take the calls due for redelivery, marking them in flight
+    pub fn due(&self) -> Vec<(JournalKey, Message)> {
+        let now = Instant::now();
+        let mut entries = self.lock_entries();
+        entries
+            .iter_mut()
+            .filter_map(|(key, entry)| match entry.delivery {
+                Delivery::Pending { next_attempt } if next_attempt <= now => {
+                    entry.delivery = Delivery::InFlight;
+                    Some((key.clone(), entry.request.clone()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Number of calls not yet delivered
+    pub fn pending(&self) -> usize {
+        self.lock_entries()
+            .values()
+            .filter(|entry| !matches!(entry.delivery, Delivery::Completed { .. }))
+            .count()
+    }
+
+    /// Forget delivered calls older than the deduplication window
+    pub fn prune(&self) {
+        let window = self.dedup_window;
+        self.lock_entries()
+            .retain(|_, entry| !expired(&entry.delivery, window));
+    }
+
+    fn lock_entries(&self) -> std::sync::MutexGuard<'_, HashMap<JournalKey, Entry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Append a record and sync it to disk
+    fn append(&self, record: &JournalRecord) -> io::Result<()> {
+        let Some(ref file) = self.file else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let mut file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        file.write_all(&line)?;
+        file.sync_data()
+    }
+
+    /// Append a record whose loss only weakens deduplication
+    fn append_logged(&self, record: &JournalRecord) {
+        if let Err(e) = self.append(record) {
+            tracing::error!(error = %e, "Failed to write journal");
+        }
+    }
+}
+
+/// Whether a repeated call carries the same request as the recorded one
+fn same_call(recorded: &Message, repeated: &Message) -> bool {
+    recorded.method == repeated.method && recorded.params == repeated.params
+}
+
+/// Whether a delivered call has outlived the deduplication window
+fn expired(delivery: &Delivery, window: Duration) -> bool {
+    match delivery {
+        Delivery::Completed { at, .. } => (Utc::now() - *at).to_std().is_ok_and(|age| age > window),
+        _ => false,
+    }
+}
+
+/// Rebuild the delivery state from the journal file
+fn load(path: &Path, dedup_window: Duration) -> io::Result<HashMap<JournalKey, Entry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut entries = HashMap::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: JournalRecord = match serde_json::from_str(&line) {
+            Ok(record) => record,
+            Err(e) => {
+                tracing::warn!(line = number + 1, error = %e, "Skipping unreadable journal line");
+                continue;
+            }
+        };
+        match record {
+            JournalRecord::Pending {
+                key,
+                attempts,
+                request,
+                ..
+            } => {
+                entries.insert(
+                    key,
+                    Entry {
+                        request,
+                        attempts,
+                        delivery: Delivery::Pending {
+                            next_attempt: Instant::now(),
+                        },
+                    },
+                );
+            }
+            JournalRecord::Failed { key, .. } => {
+                if let Some(entry) = entries.get_mut(&key) {
+                    entry.attempts += 1;
+                }
+            }
+            JournalRecord::Completed {
+                key,
+                timestamp,
+                response,
+            } => {
+                if let Some(entry) = entries.get_mut(&key) {
+                    entry.delivery = Delivery::Completed {
+                        response,
+                        at: timestamp,
+                    };
+                }
+            }
+            JournalRecord::Abandoned { key, .. } => {
+                entries.remove(&key);
+            }
+        }
+    }
+
+    entries.retain(|_, entry| !expired(&entry.delivery, dedup_window));
+    Ok(entries)
+}
+
+/// Rewrite the journal file with only the calls still worth remembering
+fn compact(path: &Path, entries: &HashMap<JournalKey, Entry>) -> io::Result<()> {
+    let mut temp = PathBuf::from(path);
+    temp.as_mut_os_string().push(".tmp");
+
+    let mut out = io::BufWriter::new(File::create(&temp)?);
+    let now = Utc::now();
+    for (key, entry) in entries {
+        let pending = JournalRecord::Pending {
+            key: key.clone(),
+            timestamp: now,
+            attempts: entry.attempts,
+            request: entry.request.clone(),
+        };
+        serde_json::to_writer(&mut out, &pending)?;
+        out.write_all(b"\n")?;
+        if let Delivery::Completed { response, at } = &entry.delivery {
+            let completed = JournalRecord::Completed {
+                key: key.clone(),
+                timestamp: *at,
+                response: response.clone(),
+            };
+            serde_json::to_writer(&mut out, &completed)?;
+            out.write_all(b"\n")?;
+        }
+    }
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    std::fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal_config(path: &Path) -> JournalConfig {
+        JournalConfig {
+            tools: vec!["create_*".to_string()],
+            path: path.to_path_buf(),
+            max_attempts: 2,
+            ..Default::default()
+        }
+    }
+
+    fn call(id: u64, tool: &str) -> Message {
+        Message::request(
+            id,
+            "tools/call",
+            Some(serde_json::json!({ "name": tool, "arguments": { "title": "Outage" } })),
+        )
+    }
+
+    fn forward_key(start: JournalStart) -> JournalKey {
+        match start {
+            JournalStart::Forward(key) => key,
+            other => panic!("expected Forward, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_disabled_journal_skips_everything() {
+        let journal = Journal::disabled();
+        assert!(matches!(
+            journal.begin("alice", "default", &call(1, "create_ticket")),
+            Ok(JournalStart::Skip)
+        ));
+    }
+
+    #[test]
+    fn test_only_matching_tool_calls_are_journaled() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = journal_config(&dir.path().join("journal.jsonl"));
+        config.servers = vec!["jira".to_string()];
+        let journal = Journal::open(&config).unwrap();
+
+        assert!(journal.applies_to("jira", &call(1, "create_ticket")));
+        assert!(!journal.applies_to("jira", &call(1, "list_tickets")));
+        assert!(!journal.applies_to("github", &call(1, "create_ticket")));
+        assert!(!journal.applies_to("jira", &Message::request(1, "tools/list", None)));
+    }
+
+    #[test]
+    fn test_delivered_call_is_deduplicated() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::open(&journal_config(&dir.path().join("journal.jsonl"))).unwrap();
+
+        let key = forward_key(
+            journal
+                .begin("alice", "default", &call(7, "create_ticket"))
+                .unwrap(),
+        );
+        // Repeated while in flight
+        assert!(matches!(
+            journal.begin("alice", "default", &call(7, "create_ticket")),
+            Err(JournalError::InProgress(_))
+        ));
+
+        let response = Message::response(serde_json::json!(7), serde_json::json!({ "id": "T-1" }));
+        journal.complete(&key, &response);
+        match journal
+            .begin("alice", "default", &call(7, "create_ticket"))
+            .unwrap()
+        {
+            JournalStart::Replay(replayed) => assert_eq!(replayed.result, response.result),
+            other => panic!("expected Replay, got {:?}", other),
+        }
+
+        // Another identity, or the same ID with other arguments, is a new call
+        forward_key(
+            journal
+                .begin("bob", "default", &call(7, "create_ticket"))
+                .unwrap(),
+        );
+        forward_key(
+            journal
+                .begin("alice", "default", &call(7, "create_issue"))
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_failed_call_is_redelivered_then_abandoned() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = journal_config(&dir.path().join("journal.jsonl"));
+        config.retry_interval_secs = 1;
+        let mut journal = Journal::open(&config).unwrap();
+        journal.retry_interval = Duration::ZERO;
+
+        let key = forward_key(
+            journal
+                .begin("alice", "default", &call(1, "create_ticket"))
+                .unwrap(),
+        );
+        assert!(journal.due().is_empty());
+
+        journal.fail(&key, "connection refused");
+        let due = journal.due();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0, key);
+        assert!(journal.due().is_empty());
+
+        journal.fail(&key, "connection refused");
+        assert_eq!(journal.pending(), 0);
+        assert!(journal.due().is_empty());
+    }
+
+    #[test]
+    fn test_pending_calls_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+        let config = journal_config(&path);
+
+        {
+            let journal = Journal::open(&config).unwrap();
+            let delivered = forward_key(
+                journal
+                    .begin("alice", "default", &call(1, "create_ticket"))
+                    .unwrap(),
+            );
+            journal.complete(
+                &delivered,
+                &Message::response(serde_json::json!(1), serde_json::json!({ "id": "T-1" })),
+            );
+            // In flight when the gateway stops
+            forward_key(
+                journal
+                    .begin("alice", "default", &call(2, "create_ticket"))
+                    .unwrap(),
+            );
+        }
+        // A torn final line is skipped
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"event\":\"comp").unwrap();
+
+        let journal = Journal::open(&config).unwrap();
+        assert_eq!(journal.pending(), 1);
+        let due = journal.due();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1.id, Some(serde_json::json!(2)));
+        assert!(matches!(
+            journal.begin("alice", "default", &call(1, "create_ticket")),
+            Ok(JournalStart::Replay(_))
+        ));
+
+        // Compaction leaves one record per pending call plus the delivered one
+        let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(lines, 3);
+    }
+}
@@ -20,19 +20,41 @@
 //! This crate provides authentication, authorization, rate limiting,
 //! and observability for Model Context Protocol (MCP) servers.
 
+pub mod alerting;
+pub mod approval;
 pub mod audit;
 pub mod auth;
 pub mod authz;
+pub mod bench;
+pub mod break_glass;
+pub mod capabilities;
+pub mod capture;
 pub mod cli;
+pub mod cluster;
 pub mod config;
+pub mod dlp;
+pub mod doctor;
+pub mod egress;
+pub mod fair_queue;
 pub mod guard_tools;
+pub mod honeypot;
+pub mod identity_store;
+pub mod journal;
+pub mod load_shed;
 pub mod mcp_server;
+pub mod method_policy;
+pub mod network_acl;
 pub mod observability;
 pub mod rate_limit;
+pub mod read_only;
 pub mod db;
 pub mod router;
+pub mod scrub;
 pub mod server;
+pub mod sessions;
+pub mod tenancy;
 pub mod tier;
+pub mod transform;
 pub mod transport;
 
 #[cfg(test)]
@@ -0,0 +1,401 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Adaptive load shedding for mcp-guard
+//!
+//! Tracks in-flight MCP requests and a smoothed upstream latency. When either
+//! approaches capacity, lower-priority requests are rejected up front with
+//! 503 + Retry-After so the upstream keeps serving important traffic instead
+//! of every caller timing out together.
+//!
+//! Requests are admitted through [`LoadShedder::admit`], which returns a
+//! [`LoadPermit`]. Dropping the permit ends the request and feeds its latency
+//! back into the load estimate.
+
+use glob::Pattern;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::auth::Identity;
+use crate::config::{LoadSheddingConfig, RequestPriority};
+use crate::transport::Message;
+
+/// Weight of each new latency sample in the moving average
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+
+/// Latency samples older than this are ignored. Once everything below
+/// `critical` is being shed no new samples arrive, so a stale average must
+/// expire rather than keep the gateway shedding forever.
+const LATENCY_SAMPLE_TTL: Duration = Duration::from_secs(10);
+
+/// Load fraction at which each priority starts being shed
+fn shed_threshold(priority: RequestPriority) -> Option<f64> {
+    match priority {
+        RequestPriority::Low => Some(0.7),
+        RequestPriority::Normal => Some(0.9),
+        RequestPriority::High => Some(1.0),
+        RequestPriority::Critical => None,
+    }
+}
+
+/// A request rejected by the load shedder
+#[derive(Debug, Clone)]
+pub struct Shed {
+    /// Priority the request was assigned
+    pub priority: RequestPriority,
+    /// Load at the time of rejection (1.0 = full capacity)
+    pub load: f64,
+    /// Seconds the client should wait before retrying
+    pub retry_after_secs: u64,
+}
+
+/// Shared counters, kept alive by the shedder and every outstanding permit
+#[derive(Debug)]
+struct LoadState {
+    started_at: Instant,
+    in_flight: AtomicUsize,
+    /// Smoothed upstream latency in microseconds
+    latency_ewma_us: AtomicU64,
+    /// Time of the last latency sample, in ms since `started_at` (0 = never)
+    last_sample_ms: AtomicU64,
+}
+
+impl LoadState {
+    fn record_latency(&self, latency: Duration) {
+        let sample = latency.as_micros().min(u64::MAX as u128) as u64;
+        let smooth = |current: u64| {
+            if current == 0 {
+                return Some(sample);
+            }
+            let next = current as f64 + (sample as f64 - current as f64) * LATENCY_EWMA_ALPHA;
+            Some(next as u64)
+        };
+        let _ = self
+            .latency_ewma_us
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, smooth);
+        let now_ms = self.started_at.elapsed().as_millis() as u64;
+        self.last_sample_ms.store(now_ms.max(1), Ordering::Relaxed);
+    }
+
+    /// Smoothed latency, or None if there is no recent sample
+    fn recent_latency(&self) -> Option<Duration> {
+        let last = self.last_sample_ms.load(Ordering::Relaxed);
+        if last == 0 {
+            return None;
+        }
+        let age_ms = (self.started_at.elapsed().as_millis() as u64).saturating_sub(last);
+        if age_ms > LATENCY_SAMPLE_TTL.as_millis() as u64 {
+            return None;
+        }
+        Some(Duration::from_micros(
+            self.latency_ewma_us.load(Ordering::Relaxed),
+        ))
+    }
+}
+
+/// Admission token for one request; dropping it ends the request
+#[derive(Debug)]
+pub struct LoadPermit {
+    state: Option<Arc<LoadState>>,
+    started: Instant,
+}
+
+impl Drop for LoadPermit {
+    fn drop(&mut self) {
+        if let Some(ref state) = self.state {
+            state.record_latency(self.started.elapsed());
+            let in_flight = state.in_flight.fetch_sub(1, Ordering::Relaxed) - 1;
+            crate::observability::set_in_flight_requests(in_flight);
+        }
+    }
+}
+
+/// Priority-aware load shedder
+#[derive(Debug)]
+pub struct LoadShedder {
+    enabled: bool,
+    max_in_flight: usize,
+    latency_threshold: Option<Duration>,
+    retry_after_secs: u64,
+    default_priority: RequestPriority,
+    identity_priorities: std::collections::HashMap<String, RequestPriority>,
+    tool_priorities: Vec<(Pattern, RequestPriority)>,
+    state: Arc<LoadState>,
+}
+
+impl Default for LoadShedder {
+    fn default() -> Self {
+        Self::new(&LoadSheddingConfig::default())
+    }
+}
+
+impl LoadShedder {
+    /// Create a load shedder from configuration
+    ///
+    /// Invalid tool patterns are skipped here; `Config::validate` rejects them.
+    pub fn new(config: &LoadSheddingConfig) -> Self {
+        let tool_priorities = config
+            .tool_priorities
+            .iter()
+            .filter_map(|t| match Pattern::new(&t.tool_pattern) {
+                Ok(pattern) => Some((pattern, t.priority)),
+                Err(e) => {
+                    tracing::warn!(
+                        pattern = %t.tool_pattern,
+                        error = %e,
+                        "Invalid load shedding tool pattern, ignoring"
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            enabled: config.enabled,
+            max_in_flight: config.max_in_flight.max(1),
+            latency_threshold: config.latency_threshold_ms.map(Duration::from_millis),
+            retry_after_secs: config.retry_after_secs,
+            default_priority: config.default_priority,
+            identity_priorities: config.identity_priorities.clone(),
+            tool_priorities,
+            state: Arc::new(LoadState {
+                started_at: Instant::now(),
+                in_flight: AtomicUsize::new(0),
+                latency_ewma_us: AtomicU64::new(0),
+                last_sample_ms: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Priority of a request: the higher of the identity and tool priorities
+    pub fn priority(&self, identity: &Identity, tool: Option<&str>) -> RequestPriority {
+        let identity_priority = self.identity_priorities.get(&identity.id).copied();
+        let tool_priority = tool.and_then(|name| {
+            self.tool_priorities
+                .iter()
+                .find(|(pattern, _)| pattern.matches(name))
+                .map(|(_, priority)| *priority)
+        });
+
+        identity_priority
+            .max(tool_priority)
+            .unwrap_or(self.default_priority)
+    }
+
+    /// Current load as a fraction of capacity (1.0 = full)
+    pub fn load(&self) -> f64 {
+        let in_flight = self.state.in_flight.load(Ordering::Relaxed) as f64;
+        let concurrency = in_flight / self.max_in_flight as f64;
+
+        let latency = match (self.latency_threshold, self.state.recent_latency()) {
+            (Some(threshold), Some(latency)) => latency.as_secs_f64() / threshold.as_secs_f64(),
+            _ => 0.0,
+        };
+
+        concurrency.max(latency)
+    }
+
+    /// Number of requests currently admitted
+    pub fn in_flight(&self) -> usize {
+        self.state.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Admit a request or shed it based on current load and its priority
+    pub fn admit(&self, identity: &Identity, message: &Message) -> Result<LoadPermit, Shed> {
+        if !self.enabled {
+            return Ok(LoadPermit {
+                state: None,
+                started: Instant::now(),
+            });
+        }
+
+        let tool = crate::authz::extract_tool_name(message);
+        let priority = self.priority(identity, tool);
+        let load = self.load();
+
+        if let Some(threshold) = shed_threshold(priority) {
+            if load >= threshold {
+                crate::observability::record_load_shed(priority);
+                tracing::warn!(
+                    identity_id = %identity.id,
+                    tool = ?tool,
+                    priority = ?priority,
+                    load = format!("{:.2}", load),
+                    "Shedding request under load"
+                );
+                return Err(Shed {
+                    priority,
+                    load,
+                    retry_after_secs: self.retry_after_secs,
+                });
+            }
+        }
+
+        let in_flight = self.state.in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+        crate::observability::set_in_flight_requests(in_flight);
+        Ok(LoadPermit {
+            state: Some(self.state.clone()),
+            started: Instant::now(),
+        })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ToolPriorityConfig;
+    use std::collections::HashMap;
+
+    fn identity(id: &str) -> Identity {
+        Identity {
+            id: id.to_string(),
+            name: None,
+            allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims: HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
+        }
+    }
+
+    fn tool_call(tool: &str) -> Message {
+        Message::request(
+            1,
+            "tools/call",
+            Some(serde_json::json!({"name": tool, "arguments": {}})),
+        )
+    }
+
+    fn shedder(max_in_flight: usize) -> LoadShedder {
+        LoadShedder::new(&LoadSheddingConfig {
+            enabled: true,
+            max_in_flight,
+            identity_priorities: HashMap::from([
+                ("ops-bot".to_string(), RequestPriority::Critical),
+                ("batch".to_string(), RequestPriority::Low),
+            ]),
+            tool_priorities: vec![
+                ToolPriorityConfig {
+                    tool_pattern: "search_*".to_string(),
+                    priority: RequestPriority::Low,
+                },
+                ToolPriorityConfig {
+                    tool_pattern: "page_oncall".to_string(),
+                    priority: RequestPriority::High,
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_priority_resolution() {
+        let shedder = shedder(10);
+
+        assert_eq!(
+            shedder.priority(&identity("alice"), None),
+            RequestPriority::Normal
+        );
+        assert_eq!(
+            shedder.priority(&identity("alice"), Some("search_docs")),
+            RequestPriority::Low
+        );
+        assert_eq!(
+            shedder.priority(&identity("batch"), Some("page_oncall")),
+            RequestPriority::High
+        );
+        assert_eq!(
+            shedder.priority(&identity("ops-bot"), Some("search_docs")),
+            RequestPriority::Critical
+        );
+    }
+
+    #[test]
+    fn test_sheds_lowest_priority_first() {
+        let shedder = shedder(10);
+        let alice = identity("alice");
+
+        // 7 of 10 in flight: low is shed, normal still admitted
+        let permits: Vec<_> = (0..7)
+            .map(|_| shedder.admit(&alice, &tool_call("read_file")).unwrap())
+            .collect();
+        let shed = shedder
+            .admit(&alice, &tool_call("search_docs"))
+            .unwrap_err();
+        assert_eq!(shed.priority, RequestPriority::Low);
+        assert_eq!(shed.retry_after_secs, 1);
+        let normal = shedder.admit(&alice, &tool_call("read_file")).unwrap();
+
+        // 9 of 10: normal is shed, high still admitted
+        let more = shedder.admit(&alice, &tool_call("read_file")).unwrap();
+        assert!(shedder.admit(&alice, &tool_call("read_file")).is_err());
+        let high = shedder.admit(&alice, &tool_call("page_oncall")).unwrap();
+
+        // Full: only critical gets through
+        assert!(shedder.admit(&alice, &tool_call("page_oncall")).is_err());
+        let critical = shedder
+            .admit(&identity("ops-bot"), &tool_call("search_docs"))
+            .unwrap();
+        assert_eq!(shedder.in_flight(), 11);
+
+        drop((permits, normal, more, high, critical));
+        assert_eq!(shedder.in_flight(), 0);
+        assert!(shedder.admit(&alice, &tool_call("search_docs")).is_ok());
+    }
+
+    #[test]
+    fn test_latency_drives_load() {
+        let shedder = LoadShedder::new(&LoadSheddingConfig {
+            enabled: true,
+            latency_threshold_ms: Some(100),
+            ..Default::default()
+        });
+
+        shedder.state.record_latency(Duration::from_millis(95));
+        assert!(shedder.load() >= 0.9);
+        assert!(shedder
+            .admit(&identity("alice"), &tool_call("read_file"))
+            .is_err());
+
+        // Faster samples pull the average back down
+        for _ in 0..20 {
+            shedder.state.record_latency(Duration::from_millis(10));
+        }
+        assert!(shedder.load() < 0.7);
+    }
+
+    #[test]
+    fn test_disabled_admits_everything() {
+        let shedder = LoadShedder::default();
+        let permits: Vec<_> = (0..1000)
+            .map(|_| {
+                shedder
+                    .admit(&identity("alice"), &tool_call("read_file"))
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(shedder.in_flight(), 0);
+        drop(permits);
+    }
+}
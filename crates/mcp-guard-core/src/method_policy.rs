@@ -0,0 +1,160 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Gateway-level method policies
+//!
+//! `[methods]` and the `methods` of each `[[upstream.servers]]` entry switch
+//! MCP methods off for everyone, whatever the caller's permissions. A method
+//! must pass the global policy and the policy of the route it goes to.
+
+use glob::Pattern;
+use std::collections::HashMap;
+
+use crate::config::{Config, MethodPolicyConfig};
+
+/// Compiled allow and deny patterns
+#[derive(Default)]
+struct Patterns {
+    allow: Vec<Pattern>,
+    deny: Vec<Pattern>,
+}
+
+impl Patterns {
+    fn new(config: &MethodPolicyConfig) -> Self {
+        // Patterns are checked when the configuration is validated
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .filter_map(|pattern| Pattern::new(pattern).ok())
+                .collect()
+        };
+        Self {
+            allow: compile(&config.allow),
+            deny: compile(&config.deny),
+        }
+    }
+
+    fn permits(&self, method: &str) -> bool {
+        !self.deny.iter().any(|pattern| pattern.matches(method))
+            && (self.allow.is_empty() || self.allow.iter().any(|p| p.matches(method)))
+    }
+}
+
+/// Global and per-route method policies
+#[derive(Default)]
+pub struct MethodPolicy {
+    global: Patterns,
+    routes: HashMap<String, Patterns>,
+}
+
+impl MethodPolicy {
+    /// Compile `[methods]` and the route policies from configuration
+    pub fn new(config: &Config) -> Self {
+        Self {
+            global: Patterns::new(&config.methods),
+            routes: config
+                .upstream
+                .servers
+                .iter()
+                .map(|server| (server.name.clone(), Patterns::new(&server.methods)))
+                .collect(),
+        }
+    }
+
+    /// Whether a method may be forwarded to a route (`None` in single-server
+    /// mode, or before the route is known)
+    pub fn permits(&self, route: Option<&str>, method: &str) -> bool {
+        self.global.permits(method)
+            && route
+                .and_then(|route| self.routes.get(route))
+                .map_or(true, |patterns| patterns.permits(method))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_no_policy_permits_everything() {
+        let policy = MethodPolicy::new(&config(
+            r#"
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+            "#,
+        ));
+        assert!(policy.permits(None, "resources/subscribe"));
+        assert!(policy.permits(Some("files"), "tools/call"));
+    }
+
+    #[test]
+    fn test_global_allow_and_deny() {
+        let policy = MethodPolicy::new(&config(
+            r#"
+            [methods]
+            allow = ["initialize", "ping", "tools/*", "notifications/*"]
+            deny = ["tools/call"]
+
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+            "#,
+        ));
+        assert!(policy.permits(None, "initialize"));
+        assert!(policy.permits(None, "tools/list"));
+        assert!(policy.permits(None, "notifications/initialized"));
+        // Deny wins over allow
+        assert!(!policy.permits(None, "tools/call"));
+        assert!(!policy.permits(None, "resources/read"));
+    }
+
+    #[test]
+    fn test_route_policy_adds_to_global() {
+        let policy = MethodPolicy::new(&config(
+            r#"
+            [methods]
+            deny = ["sampling/*"]
+
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+
+            [[upstream.servers]]
+            name = "files"
+            path_prefix = "/files"
+            transport = "stdio"
+            command = "echo"
+            methods = { deny = ["resources/subscribe"] }
+
+            [[upstream.servers]]
+            name = "search"
+            path_prefix = "/search"
+            transport = "stdio"
+            command = "echo"
+            "#,
+        ));
+        assert!(!policy.permits(Some("files"), "resources/subscribe"));
+        assert!(policy.permits(Some("search"), "resources/subscribe"));
+        assert!(policy.permits(None, "resources/subscribe"));
+        assert!(!policy.permits(Some("search"), "sampling/createMessage"));
+    }
+}
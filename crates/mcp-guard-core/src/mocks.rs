@@ -24,6 +24,7 @@ use crate::auth::{AuthError, AuthProvider, Identity};
 use crate::transport::{Message, Transport, TransportError};
 use async_trait::async_trait;
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 // ============================================================================
@@ -36,6 +37,7 @@ use std::sync::{Arc, Mutex};
 pub struct MockTransport {
     sent_messages: Arc<Mutex<Vec<Message>>>,
     pending_responses: Arc<Mutex<VecDeque<Result<Message, TransportError>>>>,
+    healthy: Arc<AtomicBool>,
 }
 
 impl MockTransport {
@@ -44,6 +46,7 @@ impl MockTransport {
         Self {
             sent_messages: Arc::new(Mutex::new(Vec::new())),
             pending_responses: Arc::new(Mutex::new(VecDeque::new())),
+            healthy: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -70,6 +73,11 @@ impl MockTransport {
     pub fn sent_count(&self) -> usize {
         self.sent_messages.lock().unwrap().len()
     }
+
+    /// Set the value reported by `is_healthy()`.
+    pub fn set_healthy(&self, healthy: bool) {
+        self.healthy.store(healthy, Ordering::Relaxed);
+    }
 }
 
 impl Default for MockTransport {
@@ -101,6 +109,10 @@ impl Transport for MockTransport {
     fn transport_type(&self) -> &'static str {
         "mock"
     }
+
+    fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
 }
 
 // ============================================================================
@@ -223,8 +235,12 @@ mod tests {
             id: "test-user".to_string(),
             name: Some("Test User".to_string()),
             allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
             rate_limit: None,
             claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
         };
         let provider = MockAuthProvider::accepting(identity.clone());
 
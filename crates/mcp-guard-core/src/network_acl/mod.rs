@@ -0,0 +1,523 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Network access control for mcp-guard
+//!
+//! Blocks clients by IP address/CIDR range and, with a GeoIP database, by
+//! country. Global rules run in [`crate::server::network_acl_middleware`]
+//! before authentication; per-API-key rules run once the key is known.
+//!
+//! Behind a reverse proxy, the client address is taken from `X-Forwarded-For`
+//! only when the connecting peer is listed in `trusted_proxy_ips`.
+
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use crate::auth::TrustedProxyValidator;
+use crate::config::{Config, NetworkAclRules};
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Network ACL setup error
+#[derive(Debug, thiserror::Error)]
+pub enum NetworkAclError {
+    #[error("Failed to read GeoIP database {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid GeoIP database entry on line {line}: {message}")]
+    Parse { line: usize, message: String },
+}
+
+/// Why a client was blocked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkBlock {
+    /// Address matched a deny entry
+    Denied,
+    /// Address not on a non-empty allow list
+    NotAllowed,
+    /// Country matched a deny entry
+    CountryDenied(String),
+    /// Country not on a non-empty allow list (None if unknown)
+    CountryNotAllowed(Option<String>),
+}
+
+impl NetworkBlock {
+    /// Low-cardinality reason used as a metric label
+    pub fn reason(&self) -> &'static str {
+        match self {
+            NetworkBlock::Denied => "ip_denied",
+            NetworkBlock::NotAllowed => "ip_not_allowed",
+            NetworkBlock::CountryDenied(_) => "country_denied",
+            NetworkBlock::CountryNotAllowed(_) => "country_not_allowed",
+        }
+    }
+}
+
+impl std::fmt::Display for NetworkBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkBlock::Denied => write!(f, "IP address is denied"),
+            NetworkBlock::NotAllowed => write!(f, "IP address is not on the allow list"),
+            NetworkBlock::CountryDenied(country) => write!(f, "country {} is denied", country),
+            NetworkBlock::CountryNotAllowed(Some(country)) => {
+                write!(f, "country {} is not on the allow list", country)
+            }
+            NetworkBlock::CountryNotAllowed(None) => {
+                write!(f, "country is unknown and an allow list is configured")
+            }
+        }
+    }
+}
+
+// ============================================================================
+// GeoIP Database
+// ============================================================================
+
+/// Network-to-country lookup table
+///
+/// Loaded from a CSV file with one `network,country_code` entry per line, where
+/// `network` is an IP address or CIDR range. Blank lines and lines starting
+/// with `#` are ignored. Networks must not overlap.
+#[derive(Debug, Default)]
+pub struct GeoIpDatabase {
+    /// (first, last, country), sorted by first address
+    v4: Vec<(u32, u32, String)>,
+    v6: Vec<(u128, u128, String)>,
+}
+
+impl GeoIpDatabase {
+    /// Load a database from a CSV file
+    pub fn load(path: &Path) -> Result<Self, NetworkAclError> {
+        let content = std::fs::read_to_string(path).map_err(|source| NetworkAclError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Parse database contents
+    pub fn parse(content: &str) -> Result<Self, NetworkAclError> {
+        let mut db = Self::default();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = |message: &str| NetworkAclError::Parse {
+                line: index + 1,
+                message: message.to_string(),
+            };
+
+            let (network, country) = line
+                .split_once(',')
+                .ok_or_else(|| parse_error("expected 'network,country_code'"))?;
+            let country = country.trim().to_ascii_uppercase();
+            if country.len() != 2 {
+                return Err(parse_error("country code must be 2 letters"));
+            }
+            let (ip, prefix_len) = match network.trim().split_once('/') {
+                Some((ip, prefix)) => (
+                    ip.parse::<IpAddr>()
+                        .map_err(|_| parse_error("invalid IP address"))?,
+                    Some(
+                        prefix
+                            .parse::<u8>()
+                            .map_err(|_| parse_error("invalid prefix length"))?,
+                    ),
+                ),
+                None => (
+                    network
+                        .trim()
+                        .parse::<IpAddr>()
+                        .map_err(|_| parse_error("invalid IP address"))?,
+                    None,
+                ),
+            };
+
+            match ip {
+                IpAddr::V4(ip) => {
+                    let prefix_len = prefix_len.unwrap_or(32);
+                    if prefix_len > 32 {
+                        return Err(parse_error("invalid prefix length"));
+                    }
+                    let host_bits = u32::MAX.checked_shr(prefix_len as u32).unwrap_or(0);
+                    let first = u32::from(ip) & !host_bits;
+                    db.v4.push((first, first | host_bits, country));
+                }
+                IpAddr::V6(ip) => {
+                    let prefix_len = prefix_len.unwrap_or(128);
+                    if prefix_len > 128 {
+                        return Err(parse_error("invalid prefix length"));
+                    }
+                    let host_bits = u128::MAX.checked_shr(prefix_len as u32).unwrap_or(0);
+                    let first = u128::from(ip) & !host_bits;
+                    db.v6.push((first, first | host_bits, country));
+                }
+            }
+        }
+        db.v4.sort_by_key(|entry| entry.0);
+        db.v6.sort_by_key(|entry| entry.0);
+        Ok(db)
+    }
+
+    /// Number of networks in the database
+    pub fn len(&self) -> usize {
+        self.v4.len() + self.v6.len()
+    }
+
+    /// Whether the database has no networks
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Country code for an address, if known
+    pub fn lookup(&self, ip: IpAddr) -> Option<&str> {
+        fn find<T: Ord + Copy>(ranges: &[(T, T, String)], ip: T) -> Option<&str> {
+            let index = ranges.partition_point(|(first, _, _)| *first <= ip);
+            let (_, last, country) = ranges.get(index.checked_sub(1)?)?;
+            (ip <= *last).then_some(country.as_str())
+        }
+        match ip {
+            IpAddr::V4(ip) => find(&self.v4, u32::from(ip)),
+            IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+                Some(ip) => find(&self.v4, u32::from(ip)),
+                None => find(&self.v6, u128::from(ip)),
+            },
+        }
+    }
+}
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+/// Rules with ranges parsed and country codes normalized
+#[derive(Debug)]
+struct CompiledRules {
+    allow: Option<TrustedProxyValidator>,
+    deny: TrustedProxyValidator,
+    allow_countries: Vec<String>,
+    deny_countries: Vec<String>,
+}
+
+impl CompiledRules {
+    fn new(rules: &NetworkAclRules) -> Self {
+        let countries = |codes: &[String]| codes.iter().map(|c| c.to_ascii_uppercase()).collect();
+        Self {
+            allow: (!rules.allow.is_empty()).then(|| TrustedProxyValidator::new(&rules.allow)),
+            deny: TrustedProxyValidator::new(&rules.deny),
+            allow_countries: countries(&rules.allow_countries),
+            deny_countries: countries(&rules.deny_countries),
+        }
+    }
+
+    fn check(&self, ip: IpAddr, geoip: Option<&GeoIpDatabase>) -> Result<(), NetworkBlock> {
+        if self.deny.is_trusted(&ip) {
+            return Err(NetworkBlock::Denied);
+        }
+        if let Some(ref allow) = self.allow {
+            if !allow.is_trusted(&ip) {
+                return Err(NetworkBlock::NotAllowed);
+            }
+        }
+
+        if self.allow_countries.is_empty() && self.deny_countries.is_empty() {
+            return Ok(());
+        }
+        let country = geoip.and_then(|db| db.lookup(ip));
+        if let Some(country) = country {
+            if self.deny_countries.iter().any(|c| c == country) {
+                return Err(NetworkBlock::CountryDenied(country.to_string()));
+            }
+        }
+        if !self.allow_countries.is_empty()
+            && !country.is_some_and(|country| self.allow_countries.iter().any(|c| c == country))
+        {
+            return Err(NetworkBlock::CountryNotAllowed(country.map(String::from)));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Network ACL
+// ============================================================================
+
+/// Global and per-API-key network access control
+#[derive(Debug)]
+pub struct NetworkAcl {
+    global: Option<CompiledRules>,
+    /// Per-API-key rules by identity ID
+    per_identity: HashMap<String, CompiledRules>,
+    geoip: Option<GeoIpDatabase>,
+    trusted_proxies: TrustedProxyValidator,
+}
+
+impl Default for NetworkAcl {
+    fn default() -> Self {
+        Self {
+            global: None,
+            per_identity: HashMap::new(),
+            geoip: None,
+            trusted_proxies: TrustedProxyValidator::new(&[]),
+        }
+    }
+}
+
+impl NetworkAcl {
+    /// Build the ACL from configuration, loading the GeoIP database if set
+    pub fn new(config: &Config) -> Result<Self, NetworkAclError> {
+        let acl = &config.network_acl;
+        let geoip = match acl.geoip_database {
+            Some(ref path) => {
+                let db = GeoIpDatabase::load(path)?;
+                tracing::info!(networks = db.len(), "Loaded GeoIP database");
+                Some(db)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            global: (!acl.rules.is_empty()).then(|| CompiledRules::new(&acl.rules)),
+            per_identity: config
+                .auth
+                .api_keys
+                .iter()
+                .filter_map(|key| {
+                    let rules = key.network.as_ref().filter(|rules| !rules.is_empty())?;
+                    Some((key.id.clone(), CompiledRules::new(rules)))
+                })
+                .collect(),
+            geoip,
+            trusted_proxies: TrustedProxyValidator::new(&acl.trusted_proxy_ips),
+        })
+    }
+
+    /// Whether global rules are configured
+    pub fn is_enabled(&self) -> bool {
+        self.global.is_some()
+    }
+
+    /// Resolve the client address, honoring `X-Forwarded-For` from trusted proxies
+    ///
+    /// Walks the header right to left and returns the first address that is
+    /// not itself a trusted proxy.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.trusted_proxies.is_trusted(&peer) {
+            return peer;
+        }
+        let Some(forwarded) = headers.get("X-Forwarded-For").and_then(|v| v.to_str().ok()) else {
+            return peer;
+        };
+
+        let mut client = peer;
+        for hop in forwarded.rsplit(',') {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => {
+                    client = ip;
+                    if !self.trusted_proxies.is_trusted(&ip) {
+                        break;
+                    }
+                }
+                // SECURITY: Stop at anything unparseable rather than skip past it
+                Err(_) => break,
+            }
+        }
+        client
+    }
+
+    /// Check the global rules
+    pub fn check(&self, ip: IpAddr) -> Result<(), NetworkBlock> {
+        match self.global {
+            Some(ref rules) => rules.check(ip, self.geoip.as_ref()),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the rules attached to an authenticated identity's API key
+    pub fn check_identity(&self, identity_id: &str, ip: IpAddr) -> Result<(), NetworkBlock> {
+        match self.per_identity.get(identity_id) {
+            Some(rules) => rules.check(ip, self.geoip.as_ref()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiKeyConfig;
+
+    const GEOIP_CSV: &str = "\
+# network,country
+203.0.113.0/24,au
+198.51.100.0/24,KP
+2001:db8::/32,DE
+";
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn create_acl(rules: NetworkAclRules, trusted_proxy_ips: Vec<String>) -> NetworkAcl {
+        let mut config: Config = toml::from_str(
+            r#"
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+            "#,
+        )
+        .unwrap();
+        config.network_acl.rules = rules;
+        config.network_acl.trusted_proxy_ips = trusted_proxy_ips;
+        let mut acl = NetworkAcl::new(&config).unwrap();
+        acl.geoip = Some(GeoIpDatabase::parse(GEOIP_CSV).unwrap());
+        acl
+    }
+
+    #[test]
+    fn test_geoip_lookup() {
+        let db = GeoIpDatabase::parse(GEOIP_CSV).unwrap();
+        assert_eq!(db.len(), 3);
+        assert_eq!(db.lookup(ip("203.0.113.77")), Some("AU"));
+        assert_eq!(db.lookup(ip("198.51.100.1")), Some("KP"));
+        assert_eq!(db.lookup(ip("2001:db8::1")), Some("DE"));
+        assert_eq!(db.lookup(ip("::ffff:203.0.113.5")), Some("AU"));
+        assert_eq!(db.lookup(ip("192.0.2.1")), None);
+    }
+
+    #[test]
+    fn test_geoip_parse_error_reports_line() {
+        let err = GeoIpDatabase::parse("203.0.113.0/24,AU\nnot-an-ip,US\n").unwrap_err();
+        assert!(matches!(err, NetworkAclError::Parse { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_deny_overrides_allow() {
+        let acl = create_acl(
+            NetworkAclRules {
+                allow: vec!["10.0.0.0/8".to_string()],
+                deny: vec!["10.13.0.0/16".to_string()],
+                ..Default::default()
+            },
+            vec![],
+        );
+        assert!(acl.is_enabled());
+        assert!(acl.check(ip("10.1.2.3")).is_ok());
+        assert_eq!(acl.check(ip("10.13.0.1")), Err(NetworkBlock::Denied));
+        assert_eq!(acl.check(ip("192.0.2.1")), Err(NetworkBlock::NotAllowed));
+    }
+
+    #[test]
+    fn test_country_rules() {
+        let acl = create_acl(
+            NetworkAclRules {
+                deny_countries: vec!["KP".to_string()],
+                ..Default::default()
+            },
+            vec![],
+        );
+        assert_eq!(
+            acl.check(ip("198.51.100.9")),
+            Err(NetworkBlock::CountryDenied("KP".to_string()))
+        );
+        assert!(acl.check(ip("192.0.2.1")).is_ok());
+
+        let acl = create_acl(
+            NetworkAclRules {
+                allow_countries: vec!["au".to_string()],
+                ..Default::default()
+            },
+            vec![],
+        );
+        assert!(acl.check(ip("203.0.113.5")).is_ok());
+        assert_eq!(
+            acl.check(ip("2001:db8::1")),
+            Err(NetworkBlock::CountryNotAllowed(Some("DE".to_string())))
+        );
+        assert_eq!(
+            acl.check(ip("192.0.2.1")),
+            Err(NetworkBlock::CountryNotAllowed(None))
+        );
+    }
+
+    #[test]
+    fn test_per_key_rules() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+            "#,
+        )
+        .unwrap();
+        config.auth.api_keys = vec![ApiKeyConfig {
+            id: "ci".to_string(),
+            key_hash: "hash".to_string(),
+            allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
+            rate_limit: None,
+            network: Some(NetworkAclRules {
+                allow: vec!["192.0.2.0/24".to_string()],
+                ..Default::default()
+            }),
+            tenant: None,
+            description: None,
+            not_before: None,
+            expires_at: None,
+            constraints: vec![],
+            access_windows: vec![],
+            grants: vec![],
+            break_glass: false,
+        }];
+        let acl = NetworkAcl::new(&config).unwrap();
+
+        assert!(!acl.is_enabled());
+        assert!(acl.check_identity("ci", ip("192.0.2.10")).is_ok());
+        assert_eq!(
+            acl.check_identity("ci", ip("198.51.100.1")),
+            Err(NetworkBlock::NotAllowed)
+        );
+        assert!(acl.check_identity("other", ip("198.51.100.1")).is_ok());
+    }
+
+    #[test]
+    fn test_client_ip_from_trusted_proxy() {
+        let acl = create_acl(NetworkAclRules::default(), vec!["10.0.0.0/8".to_string()]);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Forwarded-For",
+            "192.0.2.99, 203.0.113.7, 10.0.0.2".parse().unwrap(),
+        );
+
+        // Trusted peer: rightmost untrusted hop
+        assert_eq!(acl.client_ip(ip("10.0.0.1"), &headers), ip("203.0.113.7"));
+        // Untrusted peer: header ignored
+        assert_eq!(
+            acl.client_ip(ip("198.51.100.1"), &headers),
+            ip("198.51.100.1")
+        );
+    }
+}
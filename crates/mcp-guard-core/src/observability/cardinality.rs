@@ -0,0 +1,194 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Cardinality limits for metric labels (`[metrics] max_label_values`)
+//!
+//! Some labels take their values from clients: the MCP method of a retried
+//! call, the tool name of a decoy call. Each distinct value adds a series, so
+//! a client cycling through made-up names could grow the metrics without
+//! bound. Each capped label keeps the first values it sees, up to its limit,
+//! for the life of the process; later values are recorded as
+//! [`OTHER_LABEL_VALUE`] and counted in
+//! `mcp_guard_metric_label_overflow_total`.
+
+use metrics::counter;
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use super::identity::IDENTITY_LABEL;
+use crate::config::MetricsConfig;
+
+/// Value recorded once a label has reached its limit
+pub const OTHER_LABEL_VALUE: &str = "__other__";
+
+struct LabelValues {
+    max_values: usize,
+    limits: HashMap<String, usize>,
+    seen: HashMap<&'static str, HashSet<String>>,
+}
+
+impl LabelValues {
+    fn new(config: &MetricsConfig) -> Self {
+        let mut limits = config.label_limits.clone();
+        limits
+            .entry(IDENTITY_LABEL.to_string())
+            .or_insert(config.max_identities);
+        Self {
+            max_values: config.max_label_values,
+            limits,
+            seen: HashMap::new(),
+        }
+    }
+
+    fn limit(&self, label: &str) -> usize {
+        self.limits.get(label).copied().unwrap_or(self.max_values)
+    }
+
+    /// Whether `value` is already tracked (`Some(true)`), or can no longer be
+    /// (`Some(false)`); `None` if it would take a free slot
+    fn lookup(&self, label: &str, value: &str) -> Option<bool> {
+        let seen = self.seen.get(label)?;
+        if seen.contains(value) {
+            Some(true)
+        } else if seen.len() >= self.limit(label) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Track `value`, returning whether it fits under the label's limit
+    fn admit(&mut self, label: &'static str, value: &str) -> bool {
+        let limit = self.limit(label);
+        let seen = self.seen.entry(label).or_default();
+        if seen.contains(value) {
+            return true;
+        }
+        if seen.len() >= limit {
+            return false;
+        }
+        seen.insert(value.to_string());
+        if seen.len() == limit {
+            tracing::warn!(
+                label,
+                limit,
+                "Metric label reached its cardinality limit; new values are recorded as {}",
+                OTHER_LABEL_VALUE
+            );
+        }
+        true
+    }
+}
+
+/// `None` until configured, which applies the default limits
+static LABEL_VALUES: RwLock<Option<LabelValues>> = RwLock::new(None);
+
+/// Set label limits from `[metrics]`
+///
+/// Call once at startup, alongside `init_metrics`. Reconfiguring forgets
+/// the values seen so far.
+pub fn configure_label_limits(config: &MetricsConfig) {
+    if let Ok(mut current) = LABEL_VALUES.write() {
+        *current = Some(LabelValues::new(config));
+    }
+}
+
+/// Value to record for `label`: `value` itself, or [`OTHER_LABEL_VALUE`]
+/// once the label has reached its limit
+pub fn limit_label(label: &'static str, value: &str) -> String {
+    let known = LABEL_VALUES
+        .read()
+        .ok()
+        .and_then(|values| values.as_ref().and_then(|v| v.lookup(label, value)));
+    let admitted = match known {
+        Some(admitted) => admitted,
+        None => match LABEL_VALUES.write() {
+            Ok(mut values) => values
+                .get_or_insert_with(|| LabelValues::new(&MetricsConfig::default()))
+                .admit(label, value),
+            Err(_) => true,
+        },
+    };
+    if admitted {
+        value.to_string()
+    } else {
+        record_label_overflow(label);
+        OTHER_LABEL_VALUE.to_string()
+    }
+}
+
+/// Count a label value collapsed into an overflow bucket
+pub(super) fn record_label_overflow(label: &'static str) {
+    counter!(
+        "mcp_guard_metric_label_overflow_total",
+        "label" => label,
+    )
+    .increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The tracked values are process-wide, so tests use their own set
+
+    #[test]
+    fn test_label_values_are_capped() {
+        let mut config = MetricsConfig {
+            max_label_values: 2,
+            ..Default::default()
+        };
+        config.label_limits.insert("tool".to_string(), 1);
+        let mut values = LabelValues::new(&config);
+
+        assert_eq!(values.lookup("method", "tools/list"), None);
+        assert!(values.admit("method", "tools/list"));
+        assert!(values.admit("method", "tools/call"));
+        assert!(!values.admit("method", "made/up"));
+        // Values keep their label once admitted
+        assert!(values.admit("method", "tools/list"));
+        assert_eq!(values.lookup("method", "tools/call"), Some(true));
+        assert_eq!(values.lookup("method", "other/made/up"), Some(false));
+
+        // Per-label limits override the default
+        assert!(values.admit("tool", "read_file"));
+        assert!(!values.admit("tool", "write_file"));
+    }
+
+    #[test]
+    fn test_max_identities_limits_identity_label() {
+        let mut config = MetricsConfig {
+            max_identities: 1,
+            ..Default::default()
+        };
+        let mut values = LabelValues::new(&config);
+        assert!(values.admit("identity", "alice"));
+        assert!(!values.admit("identity", "bob"));
+
+        // An explicit label_limits entry wins
+        config.label_limits.insert("identity".to_string(), 2);
+        let mut values = LabelValues::new(&config);
+        assert!(values.admit("identity", "alice"));
+        assert!(values.admit("identity", "bob"));
+    }
+
+    #[test]
+    fn test_limit_label_passes_known_values() {
+        assert_eq!(limit_label("test_label", "value"), "value");
+        record_label_overflow("test_label");
+    }
+}
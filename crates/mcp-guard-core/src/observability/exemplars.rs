@@ -0,0 +1,287 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Trace exemplars for the latency histograms
+//!
+//! The Prometheus recorder has no notion of exemplars, so they are kept here:
+//! for every bucket of every latency series, the trace ID of the last sampled
+//! request that landed in it. Exemplars only exist in the OpenMetrics
+//! exposition format, so [`render_openmetrics`] converts the recorder's text
+//! output and appends them to the matching `_bucket` lines. Plain Prometheus
+//! scrapes are unaffected.
+
+use metrics_exporter_prometheus::PrometheusHandle;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bucket bounds (seconds) of the histograms that carry exemplars
+pub const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Histograms rendered with [`LATENCY_BUCKETS`] and given exemplars
+pub const EXEMPLAR_HISTOGRAMS: &[&str] = &[
+    "mcp_guard_request_duration_seconds",
+    "mcp_guard_route_latency_seconds",
+];
+
+/// Content type of [`render_openmetrics`] output
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Metric name and its labels, sorted, without `le`
+type SeriesKey = (String, Vec<(String, String)>);
+
+struct Exemplar {
+    trace_id: String,
+    value: f64,
+    timestamp: f64,
+}
+
+/// Latest exemplar per bucket index (`LATENCY_BUCKETS.len()` is `+Inf`)
+static EXEMPLARS: Mutex<BTreeMap<SeriesKey, BTreeMap<usize, Exemplar>>> =
+    Mutex::new(BTreeMap::new());
+
+/// Remember the active trace as the exemplar for `value` in a histogram
+///
+/// Does nothing unless the current span belongs to a sampled trace, which
+/// is only the case with `[tracing] enabled`.
+pub(super) fn record_exemplar(metric: &str, labels: &[(&str, &str)], value: f64) {
+    if let Some(trace_id) = sampled_trace_id() {
+        store_exemplar(metric, labels, value, trace_id);
+    }
+}
+
+fn sampled_trace_id() -> Option<String> {
+    use opentelemetry::trace::TraceContextExt;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = tracing::Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    (span_context.is_valid() && span_context.is_sampled())
+        .then(|| span_context.trace_id().to_string())
+}
+
+fn store_exemplar(metric: &str, labels: &[(&str, &str)], value: f64, trace_id: String) {
+    let mut labels: Vec<(String, String)> = labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    labels.sort();
+    let bucket = LATENCY_BUCKETS
+        .iter()
+        .position(|bound| value <= *bound)
+        .unwrap_or(LATENCY_BUCKETS.len());
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default();
+
+    if let Ok(mut exemplars) = EXEMPLARS.lock() {
+        exemplars
+            .entry((metric.to_string(), labels))
+            .or_default()
+            .insert(
+                bucket,
+                Exemplar {
+                    trace_id,
+                    value,
+                    timestamp,
+                },
+            );
+    }
+}
+
+/// Whether a scrape's `Accept` header asks for OpenMetrics
+pub fn accepts_openmetrics(accept: &str) -> bool {
+    accept
+        .split(',')
+        .any(|media| media.trim().starts_with("application/openmetrics-text"))
+}
+
+/// Render the recorder's metrics as OpenMetrics, with exemplars
+pub fn render_openmetrics(handle: &PrometheusHandle) -> String {
+    to_openmetrics(&handle.render())
+}
+
+/// Convert Prometheus text format to OpenMetrics
+///
+/// Counter families lose their `_total` suffix in `# TYPE`/`# HELP` (the
+/// samples keep it), blank lines are dropped, stored exemplars are appended
+/// to their bucket lines and the output ends with `# EOF`.
+fn to_openmetrics(text: &str) -> String {
+    let counters: HashSet<&str> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("# TYPE "))
+        .filter_map(|rest| rest.strip_suffix(" counter"))
+        .collect();
+    let exemplars = EXEMPLARS.lock().ok();
+
+    let mut out = String::with_capacity(text.len() + 16);
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        if let Some(rest) = line
+            .strip_prefix("# TYPE ")
+            .or_else(|| line.strip_prefix("# HELP "))
+        {
+            let name = rest.split(' ').next().unwrap_or_default();
+            if let Some(family) = name
+                .strip_suffix("_total")
+                .filter(|_| counters.contains(name))
+            {
+                out.push_str(&line[..7]);
+                out.push_str(family);
+                out.push_str(&rest[name.len()..]);
+                out.push('\n');
+                continue;
+            }
+        }
+
+        out.push_str(line);
+        if let Some(exemplar) = exemplars
+            .as_deref()
+            .and_then(|exemplars| bucket_exemplar(exemplars, line))
+        {
+            let _ = write!(
+                out,
+                " # {{trace_id=\"{}\"}} {} {:.3}",
+                exemplar.trace_id, exemplar.value, exemplar.timestamp
+            );
+        }
+        out.push('\n');
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+/// Exemplar stored for a `<histogram>_bucket{...}` sample line
+fn bucket_exemplar<'a>(
+    exemplars: &'a BTreeMap<SeriesKey, BTreeMap<usize, Exemplar>>,
+    line: &str,
+) -> Option<&'a Exemplar> {
+    let (name, rest) = line.split_once('{')?;
+    let metric = name.strip_suffix("_bucket")?;
+    if !EXEMPLAR_HISTOGRAMS.contains(&metric) {
+        return None;
+    }
+    let mut labels = parse_labels(rest)?;
+    let le = labels.iter().position(|(k, _)| k == "le")?;
+    let (_, le) = labels.remove(le);
+    let bucket = if le == "+Inf" {
+        LATENCY_BUCKETS.len()
+    } else {
+        let le: f64 = le.parse().ok()?;
+        LATENCY_BUCKETS.iter().position(|bound| *bound == le)?
+    };
+    labels.sort();
+    exemplars.get(&(metric.to_string(), labels))?.get(&bucket)
+}
+
+/// Parse `k="v",...}` up to the closing brace, unescaping the values
+fn parse_labels(text: &str) -> Option<Vec<(String, String)>> {
+    let mut labels = Vec::new();
+    let mut chars = text.chars();
+    loop {
+        let mut key = String::new();
+        for c in chars.by_ref() {
+            match c {
+                '=' => break,
+                '}' if key.is_empty() => return Some(labels),
+                ',' if key.is_empty() => {}
+                _ => key.push(c),
+            }
+        }
+        if chars.next()? != '"' {
+            return None;
+        }
+        let mut value = String::new();
+        loop {
+            match chars.next()? {
+                '\\' => value.push(match chars.next()? {
+                    'n' => '\n',
+                    c => c,
+                }),
+                '"' => break,
+                c => value.push(c),
+            }
+        }
+        labels.push((key, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exemplars_attach_to_their_bucket() {
+        store_exemplar(
+            "mcp_guard_request_duration_seconds",
+            &[("method", "EXEMPLAR")],
+            0.02,
+            "4bf92f3577b34da6a3ce929d0e0e4736".to_string(),
+        );
+        let text = "# HELP mcp_guard_requests_total Total HTTP requests\n\
+                    # TYPE mcp_guard_requests_total counter\n\
+                    mcp_guard_requests_total{method=\"POST\",status=\"200\"} 3\n\
+                    \n\
+                    # TYPE mcp_guard_request_duration_seconds histogram\n\
+                    mcp_guard_request_duration_seconds_bucket{method=\"EXEMPLAR\",le=\"0.01\"} 0\n\
+                    mcp_guard_request_duration_seconds_bucket{method=\"EXEMPLAR\",le=\"0.025\"} 1\n\
+                    mcp_guard_request_duration_seconds_bucket{method=\"EXEMPLAR\",le=\"+Inf\"} 1\n";
+
+        let rendered = to_openmetrics(text);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "# HELP mcp_guard_requests Total HTTP requests");
+        assert_eq!(lines[1], "# TYPE mcp_guard_requests counter");
+        assert_eq!(
+            lines[2],
+            "mcp_guard_requests_total{method=\"POST\",status=\"200\"} 3"
+        );
+        assert!(!lines[4].contains('#'));
+        assert!(lines[5].starts_with(
+            "mcp_guard_request_duration_seconds_bucket{method=\"EXEMPLAR\",le=\"0.025\"} 1 \
+             # {trace_id=\"4bf92f3577b34da6a3ce929d0e0e4736\"} 0.02 "
+        ));
+        assert!(!lines[6].contains("trace_id"));
+        assert_eq!(lines.last(), Some(&"# EOF"));
+    }
+
+    #[test]
+    fn test_accepts_openmetrics() {
+        assert!(accepts_openmetrics(
+            "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5"
+        ));
+        assert!(!accepts_openmetrics("text/plain;version=0.0.4"));
+        assert!(!accepts_openmetrics("*/*"));
+    }
+
+    #[test]
+    fn test_parse_labels() {
+        assert_eq!(
+            parse_labels("a=\"1\",b=\"x\\\"y\"} 5"),
+            Some(vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "x\"y".to_string()),
+            ])
+        );
+        assert_eq!(parse_labels("} 5"), Some(vec![]));
+        assert_eq!(parse_labels("a=1} 5"), None);
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Per-identity metrics (`[metrics] per_identity`)
+//!
+//! Labelling series with identity IDs makes their number grow with the user
+//! base, so the `identity` label is capped like any other client-driven label
+//! (see [`limit_label`]): `max_identities` sets its entry in the label
+//! limits, and identities past it are recorded as `__other__`. In privacy
+//! mode (`[telemetry_privacy]`) labels carry the salted hash, not the ID.
+
+use metrics::{counter, histogram};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::limit_label;
+use crate::config::MetricsConfig;
+
+/// Label the identity metrics are capped under in the label limits
+pub(super) const IDENTITY_LABEL: &str = "identity";
+
+static PER_IDENTITY: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable per-identity metrics from `[metrics]`
+///
+/// Call once at startup, alongside `init_metrics` and
+/// [`configure_label_limits`](super::configure_label_limits), which applies
+/// `max_identities`.
+pub fn configure_identity_metrics(config: &MetricsConfig) {
+    PER_IDENTITY.store(config.per_identity, Ordering::Relaxed);
+}
+
+/// Label to record `identity_id` under, or `None` when disabled
+///
+/// In privacy mode the label is the identity's salted hash.
+fn identity_label(identity_id: &str) -> Option<String> {
+    if !PER_IDENTITY.load(Ordering::Relaxed) {
+        return None;
+    }
+    Some(limit_label(
+        IDENTITY_LABEL,
+        &super::telemetry_identity(identity_id),
+    ))
+}
+
+/// Record the outcome of an authenticated request
+///
+/// # Arguments
+/// * `identity_id` - Authenticated identity
+/// * `status` - HTTP status returned to the client
+pub fn record_identity_request(identity_id: &str, status: u16) {
+    let Some(identity) = identity_label(identity_id) else {
+        return;
+    };
+    let result = match status {
+        429 => "rate_limited",
+        400.. => "error",
+        _ => "success",
+    };
+    counter!(
+        "mcp_guard_identity_requests_total",
+        "identity" => identity,
+        "result" => result,
+    )
+    .increment(1);
+}
+
+/// Record an upstream round trip made on behalf of an identity
+///
+/// # Arguments
+/// * `identity_id` - Identity whose request was forwarded
+/// * `duration` - Time taken for the upstream request
+pub fn record_identity_upstream_latency(identity_id: &str, duration: std::time::Duration) {
+    let Some(identity) = identity_label(identity_id) else {
+        return;
+    };
+    histogram!(
+        "mcp_guard_identity_upstream_latency_seconds",
+        "identity" => identity,
+    )
+    .record(duration.as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_labels_are_capped() {
+        let config = MetricsConfig {
+            per_identity: true,
+            max_identities: 2,
+            ..Default::default()
+        };
+        configure_identity_metrics(&config);
+        super::super::configure_label_limits(&config);
+        assert_eq!(identity_label("alice").as_deref(), Some("alice"));
+        assert_eq!(identity_label("bob").as_deref(), Some("bob"));
+        assert_eq!(
+            identity_label("carol").as_deref(),
+            Some(super::super::OTHER_LABEL_VALUE)
+        );
+        // Identities keep the label they were first given
+        assert_eq!(identity_label("alice").as_deref(), Some("alice"));
+        record_identity_request("carol", 429);
+        record_identity_upstream_latency("bob", std::time::Duration::from_millis(5));
+
+        configure_identity_metrics(&MetricsConfig::default());
+        assert!(identity_label("alice").is_none());
+    }
+}
@@ -26,30 +26,73 @@
 //! - `mcp_guard_auth_total` (counter) - labels: provider, result
 //! - `mcp_guard_rate_limit_total` (counter) - labels: allowed
 //! - `mcp_guard_active_identities` (gauge)
+//! - `mcp_guard_active_sessions` (gauge)
 //! - `mcp_guard_upstream_latency_seconds` (histogram) - labels: transport, result
 //! - `mcp_guard_upstream_requests_total` (counter) - labels: transport, result
+//! - `mcp_guard_identity_requests_total` (counter) - labels: identity, result
+//! - `mcp_guard_identity_upstream_latency_seconds` (histogram) - labels: identity
+//!
+//! The `identity` metrics are opt-in through `[metrics] per_identity`, with
+//! identities past `max_identities` recorded as `__other__`.
 //!
 //! ## OpenTelemetry Tracing (FR-OBS-03)
 //!
 //! - W3C trace context propagation (traceparent, tracestate headers)
+//! - The same context in forwarded MCP requests' `params._meta`, for stdio upstreams
 //! - OTLP export to Jaeger, Tempo, or other collectors
-//! - Configurable sampling rates (0.0-1.0)
+//! - Configurable sampling rates (0.0-1.0), overridable per route and MCP method
+//! - Trace ID exemplars on the latency histograms, served to OpenMetrics scrapes
 //!
 //! ## Audit Correlation (FR-AUDIT-06)
 //!
 //! - Trace ID included in all log messages for request correlation
+//!
+//! ## Logging
+//!
+//! - Text or JSON output, selected by `[logging] format`
+//! - Per-module level overrides without `RUST_LOG`
+//!
+//! ## Process-wide Settings
+//!
+//! Metrics are recorded through free functions, so the settings they read
+//! live in process-wide statics, like the Prometheus recorder itself:
+//! [`configure_identity_metrics`], [`configure_label_limits`] and
+//! [`configure_telemetry_privacy`] install theirs once at startup, and
+//! calling them again replaces what was installed.
 
 use metrics::{counter, gauge, histogram};
-use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
 use opentelemetry::trace::TracerProvider;
 use opentelemetry_sdk::{
     runtime,
-    trace::{RandomIdGenerator, Sampler, TracerProvider as SdkTracerProvider},
+    trace::{RandomIdGenerator, TracerProvider as SdkTracerProvider},
     Resource,
 };
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use serde_json::{Map, Value};
+use tracing_subscriber::{
+    fmt::format::FmtSpan, fmt::writer::BoxMakeWriter, layer::SubscriberExt, registry::LookupSpan,
+    util::SubscriberInitExt, EnvFilter, Layer,
+};
+
+use crate::config::{LogFormat, LoggingConfig, TracingConfig};
+use crate::transport::Message;
 
-use crate::config::TracingConfig;
+mod cardinality;
+mod exemplars;
+mod identity;
+mod privacy;
+mod sampling;
+
+pub use cardinality::{configure_label_limits, limit_label, OTHER_LABEL_VALUE};
+pub use exemplars::{
+    accepts_openmetrics, render_openmetrics, EXEMPLAR_HISTOGRAMS, LATENCY_BUCKETS,
+    OPENMETRICS_CONTENT_TYPE,
+};
+pub use identity::{
+    configure_identity_metrics, record_identity_request, record_identity_upstream_latency,
+};
+pub use privacy::{configure_telemetry_privacy, telemetry_identity, telemetry_privacy_enabled};
+use sampling::RuleSampler;
 
 /// Result of tracing initialization
 pub struct TracingGuard {
@@ -72,16 +115,18 @@ impl Drop for TracingGuard {
 /// # Arguments
 /// * `verbose` - Enable verbose (debug) logging
 /// * `tracing_config` - Optional OpenTelemetry configuration
+/// * `logging_config` - Optional log format and level configuration
 ///
 /// # Returns
 /// A TracingGuard that should be held for the lifetime of the application.
 /// When dropped, it will properly flush and shutdown the OpenTelemetry tracer.
-pub fn init_tracing(verbose: bool, tracing_config: Option<&TracingConfig>) -> TracingGuard {
-    let filter = if verbose {
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("debug"))
-    } else {
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"))
-    };
+pub fn init_tracing(
+    verbose: bool,
+    tracing_config: Option<&TracingConfig>,
+    logging_config: Option<&LoggingConfig>,
+) -> TracingGuard {
+    let format = logging_config.map(|c| c.format).unwrap_or_default();
+    let stderr = logging_config.is_some_and(|c| c.stderr);
 
     // Check if OpenTelemetry tracing is enabled
     let otel_enabled = tracing_config.map(|c| c.enabled).unwrap_or(false);
@@ -89,7 +134,12 @@ pub fn init_tracing(verbose: bool, tracing_config: Option<&TracingConfig>) -> Tr
     if otel_enabled {
         // Safe: otel_enabled is true only if tracing_config was Some with enabled=true
         let config = tracing_config.expect("tracing_config must be Some when otel_enabled is true");
-        match init_opentelemetry_tracing(verbose, config) {
+        match init_opentelemetry_tracing(
+            build_env_filter(verbose, logging_config),
+            format,
+            stderr,
+            config,
+        ) {
             Ok(guard) => return guard,
             Err(e) => {
                 eprintln!("Failed to initialize OpenTelemetry tracing: {}. Falling back to basic logging.", e);
@@ -99,42 +149,88 @@ pub fn init_tracing(verbose: bool, tracing_config: Option<&TracingConfig>) -> Tr
 
     // Basic tracing without OpenTelemetry
     tracing_subscriber::registry()
-        .with(filter)
-        .with(tracing_subscriber::fmt::layer())
+        .with(build_env_filter(verbose, logging_config))
+        .with(fmt_layer(format, stderr, false))
         .try_init()
         .ok();
 
     TracingGuard { _provider: None }
 }
 
+/// Build the log filter directives
+///
+/// The base level is `debug` with `--verbose`, otherwise `logging.level`
+/// (default `info`); per-module overrides are appended as `module=level`.
+pub fn log_filter_directives(verbose: bool, logging_config: Option<&LoggingConfig>) -> String {
+    let base = match logging_config {
+        _ if verbose => "debug",
+        Some(config) => config.level.as_str(),
+        None => "info",
+    };
+    let mut directives = base.to_string();
+    for (module, level) in logging_config.iter().flat_map(|c| &c.modules) {
+        directives.push_str(&format!(",{}={}", module, level));
+    }
+    directives
+}
+
+/// Build the log filter, preferring `RUST_LOG` when it is set
+fn build_env_filter(verbose: bool, logging_config: Option<&LoggingConfig>) -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        let directives = log_filter_directives(verbose, logging_config);
+        EnvFilter::try_new(&directives).unwrap_or_else(|e| {
+            eprintln!("Invalid log filter '{}': {}. Using 'info'.", directives, e);
+            EnvFilter::new("info")
+        })
+    })
+}
+
+/// Create the log output layer for the configured format and stream
+fn fmt_layer<S>(
+    format: LogFormat,
+    stderr: bool,
+    span_close_events: bool,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    let writer = if stderr {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_target(true);
+    let layer = if span_close_events {
+        layer.with_span_events(FmtSpan::CLOSE)
+    } else {
+        layer
+    };
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().with_current_span(true).boxed(),
+    }
+}
+
 /// Initialize OpenTelemetry tracing with OTLP export
 fn init_opentelemetry_tracing(
-    verbose: bool,
+    filter: EnvFilter,
+    format: LogFormat,
+    stderr: bool,
     config: &TracingConfig,
 ) -> Result<TracingGuard, Box<dyn std::error::Error + Send + Sync>> {
     use opentelemetry::KeyValue;
     use opentelemetry_otlp::WithExportConfig;
 
-    let filter = if verbose {
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("debug"))
-    } else {
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"))
-    };
-
     // Set up resource with service name
     let resource = Resource::new(vec![
         KeyValue::new("service.name", config.service_name.clone()),
         KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
     ]);
 
-    // Set up sampler based on sample rate
-    let sampler = if config.sample_rate >= 1.0 {
-        Sampler::AlwaysOn
-    } else if config.sample_rate <= 0.0 {
-        Sampler::AlwaysOff
-    } else {
-        Sampler::TraceIdRatioBased(config.sample_rate)
-    };
+    // Set up sampler based on sample rate and the per-route/method rules
+    let sampler = RuleSampler::new(config);
 
     // Build the tracer provider
     let provider = if let Some(ref endpoint) = config.otlp_endpoint {
@@ -165,16 +261,11 @@ fn init_opentelemetry_tracing(
     // Create OpenTelemetry tracing layer
     let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer);
 
-    // Create fmt layer with trace ID in logs (FR-AUDIT-06)
-    let fmt_layer = tracing_subscriber::fmt::layer()
-        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
-        .with_target(true);
-
-    // Combine layers
+    // Combine layers; the fmt layer carries the trace ID in logs (FR-AUDIT-06)
     tracing_subscriber::registry()
         .with(filter)
         .with(otel_layer)
-        .with(fmt_layer)
+        .with(fmt_layer(format, stderr, true))
         .try_init()
         .ok();
 
@@ -192,7 +283,7 @@ fn init_opentelemetry_tracing(
 /// a local recorder handle is returned instead, allowing metrics to still be
 /// rendered but not globally recorded.
 pub fn init_metrics() -> PrometheusHandle {
-    match PrometheusBuilder::new().install_recorder() {
+    match prometheus_builder().install_recorder() {
         Ok(handle) => handle,
         Err(e) => {
             tracing::warn!(
@@ -201,40 +292,75 @@ pub fn init_metrics() -> PrometheusHandle {
                  Metrics will still be available but won't be globally accessible."
             );
             // Fall back to a local recorder that can still render metrics
-            PrometheusBuilder::new().build_recorder().handle()
+            prometheus_builder().build_recorder().handle()
         }
     }
 }
 
+/// Recorder builder rendering the exemplar histograms with real buckets
+///
+/// Histograms without configured buckets are rendered as summaries, which
+/// cannot carry exemplars.
+fn prometheus_builder() -> PrometheusBuilder {
+    EXEMPLAR_HISTOGRAMS
+        .iter()
+        .try_fold(PrometheusBuilder::new(), |builder, metric| {
+            builder.set_buckets_for_metric(Matcher::Full(metric.to_string()), LATENCY_BUCKETS)
+        })
+        .expect("LATENCY_BUCKETS is not empty")
+}
+
 /// Create a Prometheus handle without installing a global recorder
 ///
 /// Useful for tests where multiple tests may run in parallel and each
 /// needs its own metrics handle. The returned handle can still render
 /// metrics but they won't be globally accessible.
 pub fn create_metrics_handle() -> PrometheusHandle {
-    let recorder = PrometheusBuilder::new().build_recorder();
+    let recorder = prometheus_builder().build_recorder();
     recorder.handle()
 }
 
 /// Record a completed request
 ///
+/// Inside a sampled trace, the trace ID is kept as the duration's exemplar.
+///
 /// # Arguments
 /// * `method` - HTTP method (e.g., "POST", "GET")
 /// * `status` - HTTP status code
 /// * `duration` - Request duration
 pub fn record_request(method: &str, status: u16, duration: std::time::Duration) {
+    let method = limit_label("method", method);
     counter!(
         "mcp_guard_requests_total",
-        "method" => method.to_string(),
+        "method" => method.clone(),
         "status" => status.to_string(),
     )
     .increment(1);
 
     histogram!(
         "mcp_guard_request_duration_seconds",
-        "method" => method.to_string(),
+        "method" => method.clone(),
     )
     .record(duration.as_secs_f64());
+    exemplars::record_exemplar(
+        "mcp_guard_request_duration_seconds",
+        &[("method", method.as_str())],
+        duration.as_secs_f64(),
+    );
+}
+
+/// Record an upstream response over its `response_limits.max_bytes`
+///
+/// # Arguments
+/// * `upstream` - Server route name (`default` in single-server mode)
+/// * `action` - "rejected" or "truncated"
+pub fn record_oversized_response(upstream: &str, action: &str) {
+    counter!(
+        "mcp_guard_oversized_responses_total",
+        "upstream" => upstream.to_string(),
+        "action" => action.to_string(),
+    )
+    .increment(1);
 }
 
 /// Record an authentication attempt
@@ -264,6 +390,18 @@ pub fn record_rate_limit(allowed: bool) {
     .increment(1);
 }
 
+/// Record a request rejected for exceeding `server.max_request_size`
+///
+/// # Arguments
+/// * `detection` - How the oversize was detected ("content_length" or "streaming")
+pub fn record_oversized_request(detection: &str) {
+    counter!(
+        "mcp_guard_oversized_requests_total",
+        "detection" => detection.to_string(),
+    )
+    .increment(1);
+}
+
 /// Update the active identities gauge
 ///
 /// # Arguments
@@ -272,6 +410,14 @@ pub fn set_active_identities(count: usize) {
     gauge!("mcp_guard_active_identities").set(count as f64);
 }
 
+/// Update the open client sessions gauge
+///
+/// # Arguments
+/// * `count` - Current number of open sessions
+pub fn set_active_sessions(count: usize) {
+    gauge!("mcp_guard_active_sessions").set(count as f64);
+}
+
 /// Record upstream request latency
 ///
 /// # Arguments
@@ -295,6 +441,318 @@ pub fn record_upstream_latency(transport: &str, duration: std::time::Duration, s
     .increment(1);
 }
 
+/// Record a retried upstream call
+///
+/// # Arguments
+/// * `transport` - Transport type (stdio, http, sse, unix)
+/// * `method` - MCP method being retried
+/// * `reason` - Error class that triggered the retry
+pub fn record_upstream_retry(transport: &str, method: &str, reason: &str) {
+    counter!(
+        "mcp_guard_upstream_retries_total",
+        "transport" => transport.to_string(),
+        "method" => limit_label("method", method),
+        "reason" => reason.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record a call forwarded to one of a route's targets
+///
+/// # Arguments
+/// * `upstream` - Server route name
+/// * `target` - "primary" or "canary"
+/// * `duration` - Time taken for the call, including retries
+/// * `result` - "success", "jsonrpc_error" (upstream answered with an error) or "error"
+pub fn record_route_call(
+    upstream: &str,
+    target: &str,
+    duration: std::time::Duration,
+    result: &str,
+) {
+    histogram!(
+        "mcp_guard_route_latency_seconds",
+        "upstream" => upstream.to_string(),
+        "target" => target.to_string(),
+    )
+    .record(duration.as_secs_f64());
+    exemplars::record_exemplar(
+        "mcp_guard_route_latency_seconds",
+        &[("upstream", upstream), ("target", target)],
+        duration.as_secs_f64(),
+    );
+
+    counter!(
+        "mcp_guard_route_requests_total",
+        "upstream" => upstream.to_string(),
+        "target" => target.to_string(),
+        "result" => result.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record the start of an on-demand stdio upstream
+///
+/// # Arguments
+/// * `upstream` - Server route name
+/// * `duration` - Time from spawning the process until it could take the call
+/// * `success` - Whether the process started
+pub fn record_upstream_spawn(upstream: &str, duration: std::time::Duration, success: bool) {
+    let result = if success { "success" } else { "error" };
+    histogram!(
+        "mcp_guard_upstream_spawn_seconds",
+        "upstream" => upstream.to_string(),
+    )
+    .record(duration.as_secs_f64());
+
+    counter!(
+        "mcp_guard_upstream_spawns_total",
+        "upstream" => upstream.to_string(),
+        "result" => result.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record an on-demand stdio upstream stopped after sitting idle
+///
+/// # Arguments
+/// * `upstream` - Server route name
+pub fn record_upstream_reaped(upstream: &str) {
+    counter!(
+        "mcp_guard_upstream_reaped_total",
+        "upstream" => upstream.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record an SSE stream reconnection attempt
+///
+/// # Arguments
+/// * `success` - Whether the stream was resumed
+pub fn record_sse_reconnect(success: bool) {
+    let result = if success { "success" } else { "failure" };
+    counter!(
+        "mcp_guard_sse_reconnects_total",
+        "result" => result.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record a periodic DNS re-resolution of an upstream host
+///
+/// # Arguments
+/// * `host` - Upstream host name
+/// * `result` - "unchanged", "repinned", "blocked" or "failed"
+pub fn record_dns_pin_refresh(host: &str, result: &str) {
+    counter!(
+        "mcp_guard_dns_pin_refreshes_total",
+        "host" => host.to_string(),
+        "result" => result.to_string(),
+    )
+    .increment(1);
+}
+
+/// Update the upstream health gauge
+///
+/// # Arguments
+/// * `upstream` - Upstream name ("default" in single-server mode)
+/// * `healthy` - Whether the upstream transport is currently usable
+pub fn set_upstream_healthy(upstream: &str, healthy: bool) {
+    gauge!(
+        "mcp_guard_upstream_healthy",
+        "upstream" => upstream.to_string(),
+    )
+    .set(if healthy { 1.0 } else { 0.0 });
+}
+
+/// Update the fair queue depth gauge
+///
+/// # Arguments
+/// * `upstream` - Upstream name ("default" in single-server mode)
+/// * `depth` - Calls waiting for a slot
+pub fn set_fair_queue_depth(upstream: &str, depth: usize) {
+    gauge!(
+        "mcp_guard_fair_queue_depth",
+        "upstream" => upstream.to_string(),
+    )
+    .set(depth as f64);
+}
+
+/// Record a request rejected because its identity's fair queue was full
+///
+/// # Arguments
+/// * `upstream` - Upstream name ("default" in single-server mode)
+pub fn record_fair_queue_rejected(upstream: &str) {
+    counter!(
+        "mcp_guard_fair_queue_rejected_total",
+        "upstream" => upstream.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record a request rejected by the load shedder
+///
+/// # Arguments
+/// * `priority` - Priority the shed request was assigned
+pub fn record_load_shed(priority: crate::config::RequestPriority) {
+    let priority = match priority {
+        crate::config::RequestPriority::Low => "low",
+        crate::config::RequestPriority::Normal => "normal",
+        crate::config::RequestPriority::High => "high",
+        crate::config::RequestPriority::Critical => "critical",
+    };
+    counter!(
+        "mcp_guard_load_shed_total",
+        "priority" => priority.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record a request blocked by network access control
+///
+/// # Arguments
+/// * `reason` - Block reason (e.g., "ip_denied", "country_not_allowed")
+/// * `scope` - "global" (before authentication) or "api_key"
+pub fn record_network_block(reason: &str, scope: &str) {
+    counter!(
+        "mcp_guard_network_blocked_total",
+        "reason" => reason.to_string(),
+        "scope" => scope.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record a request handed to the replica owning it (`cluster.affinity`)
+///
+/// # Arguments
+/// * `mode` - "forward" or "redirect"
+pub fn record_affinity_dispatch(mode: &str) {
+    counter!(
+        "mcp_guard_affinity_dispatched_total",
+        "mode" => mode.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record a request matched by a secret scrubbing rule
+///
+/// # Arguments
+/// * `rule` - Scrubbing rule name
+/// * `action` - "redact" or "block"
+pub fn record_secret_scrubbed(rule: &str, action: &str) {
+    counter!(
+        "mcp_guard_secrets_scrubbed_total",
+        "rule" => rule.to_string(),
+        "action" => action.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record how a tool call held for approval was resolved
+///
+/// # Arguments
+/// * `outcome` - "approved", "denied", "timed_out" or "rejected" (too many pending)
+pub fn record_approval(outcome: &str) {
+    counter!(
+        "mcp_guard_approvals_total",
+        "outcome" => outcome.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record a client message rejected by pre-flight JSON-RPC validation
+///
+/// # Arguments
+/// * `reason` - What was wrong with the message (version, ambiguous, id, ...)
+pub fn record_invalid_jsonrpc(reason: &str) {
+    counter!(
+        "mcp_guard_invalid_jsonrpc_total",
+        "reason" => reason.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record a call to a decoy tool
+///
+/// # Arguments
+/// * `tool` - Decoy tool name
+pub fn record_honeypot_trigger(tool: &str) {
+    counter!(
+        "mcp_guard_honeypot_triggers_total",
+        "tool" => limit_label("tool", tool),
+    )
+    .increment(1);
+}
+
+/// Record a request made with a break-glass key
+///
+/// # Arguments
+/// * `outcome` - "allowed" or "rejected"
+pub fn record_break_glass(outcome: &str) {
+    counter!(
+        "mcp_guard_break_glass_requests_total",
+        "outcome" => outcome.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record an alert email
+///
+/// # Arguments
+/// * `outcome` - "sent" or "failed"
+pub fn record_alert_email(outcome: &str) {
+    counter!(
+        "mcp_guard_alert_emails_total",
+        "outcome" => outcome.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record an audit entry lost because its queue was full
+///
+/// # Arguments
+/// * `sink` - "writer" (file and stdout), "export" or "archive"
+pub fn record_audit_dropped(sink: &str) {
+    counter!(
+        "mcp_guard_audit_dropped_total",
+        "sink" => sink.to_string(),
+    )
+    .increment(1);
+}
+
+/// Record a write-ahead journal event
+///
+/// # Arguments
+/// * `event` - "journaled", "deduplicated", "redelivered", "failed" or "abandoned"
+pub fn record_journal_event(event: &str) {
+    counter!(
+        "mcp_guard_journal_events_total",
+        "event" => event.to_string(),
+    )
+    .increment(1);
+}
+
+/// Update the seconds remaining until an API key expires
+///
+/// # Arguments
+/// * `key_id` - Configured API key id
+/// * `seconds` - Seconds until `expires_at` (negative once expired)
+pub fn set_api_key_expiry(key_id: &str, seconds: i64) {
+    gauge!(
+        "mcp_guard_api_key_expiry_seconds",
+        "key_id" => key_id.to_string(),
+    )
+    .set(seconds as f64);
+}
+
+/// Update the in-flight MCP requests gauge
+///
+/// # Arguments
+/// * `count` - Requests currently admitted by the load shedder
+pub fn set_in_flight_requests(count: usize) {
+    gauge!("mcp_guard_in_flight_requests").set(count as f64);
+}
+
 /// Get the current trace ID from the active span (if any)
 ///
 /// This can be used to include trace IDs in error responses or audit logs.
@@ -314,6 +772,72 @@ pub fn current_trace_id() -> Option<String> {
     }
 }
 
+/// Inject the current span's W3C trace context into an MCP request
+///
+/// Sets `traceparent` (and `tracestate`, if any) in `params._meta`, the
+/// metadata field MCP reserves for this kind of out-of-band data, so
+/// instrumented upstreams can continue the trace even over stdio. Values
+/// already there are replaced, since the gateway's span is the caller's
+/// child. Does nothing without an active trace, for notifications, or when
+/// `params` or `_meta` is not an object.
+pub fn inject_trace_meta(message: &mut Message) {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    inject_trace_meta_from(&tracing::Span::current().context(), message);
+}
+
+fn inject_trace_meta_from(context: &opentelemetry::Context, message: &mut Message) {
+    use opentelemetry::propagation::TextMapPropagator;
+    use opentelemetry::trace::TraceContextExt;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+
+    if !message.is_request() || !context.span().span_context().is_valid() {
+        return;
+    }
+    let params = message
+        .params
+        .get_or_insert_with(|| Value::Object(Map::new()));
+    let Some(meta) = params
+        .as_object_mut()
+        .map(|params| {
+            params
+                .entry("_meta")
+                .or_insert_with(|| Value::Object(Map::new()))
+        })
+        .and_then(Value::as_object_mut)
+    else {
+        return;
+    };
+    TraceContextPropagator::new().inject_context(context, &mut MetaInjector(meta));
+}
+
+/// Injector writing trace context into an MCP `_meta` object
+struct MetaInjector<'a>(&'a mut Map<String, Value>);
+
+impl opentelemetry::propagation::Injector for MetaInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_string(), Value::String(value));
+    }
+}
+
+/// Short, non-reversible form of an identity ID for span attributes
+///
+/// The first 16 hex digits of the ID's SHA-256: stable, so spans of one
+/// identity can be grouped, without exporting the ID itself (often an email
+/// or customer name) to the tracing backend. In privacy mode the hash is
+/// salted, matching the metric labels.
+pub fn hash_identity_id(identity_id: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    if let Some(hash) = privacy::salted_hash(identity_id) {
+        return hash;
+    }
+    Sha256::digest(identity_id.as_bytes())[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -339,6 +863,7 @@ mod tests {
         record_rate_limit(true);
         record_rate_limit(false);
         set_active_identities(5);
+        set_active_sessions(2);
     }
 
     #[test]
@@ -396,7 +921,7 @@ mod tests {
     #[test]
     fn test_init_tracing_basic() {
         // Should initialize basic logging without panic
-        let guard = init_tracing(true, None);
+        let guard = init_tracing(true, None, None);
         // Guard scope end should drop safely
         drop(guard);
     }
@@ -409,7 +934,7 @@ mod tests {
             ..Default::default()
         };
         // Should ignore config if enabled is false
-        let guard = init_tracing(true, Some(&config));
+        let guard = init_tracing(true, Some(&config), None);
         drop(guard);
     }
 
@@ -452,6 +977,8 @@ mod tests {
             service_name: "test-service".to_string(),
             sample_rate: 0.1,
             propagate_context: true,
+            propagate_meta: true,
+            sampling: Vec::new(),
         };
 
         assert!(config.enabled);
@@ -478,12 +1005,14 @@ mod tests {
     fn test_tracing_config_propagate_context() {
         let config = TracingConfig {
             propagate_context: false,
+            propagate_meta: true,
             ..Default::default()
         };
         assert!(!config.propagate_context);
 
         let config = TracingConfig {
             propagate_context: true,
+            propagate_meta: true,
             ..Default::default()
         };
         assert!(config.propagate_context);
@@ -497,18 +1026,103 @@ mod tests {
             service_name: "test".into(),
             sample_rate: 1.0,
             propagate_context: true,
+            propagate_meta: true,
+            sampling: Vec::new(),
         };
         // Should initialize partial tracing pipeline without OTLP
-        let guard = init_tracing(false, Some(&config));
+        let guard = init_tracing(false, Some(&config), None);
         drop(guard);
     }
 
     #[test]
     fn test_init_tracing_verbose_variations() {
-        let guard_false = init_tracing(false, None);
+        let guard_false = init_tracing(false, None, None);
         drop(guard_false);
 
-        let guard_true = init_tracing(true, None);
+        let guard_true = init_tracing(true, None, None);
         drop(guard_true);
     }
+
+    #[test]
+    fn test_log_filter_directives() {
+        assert_eq!(log_filter_directives(false, None), "info");
+        assert_eq!(log_filter_directives(true, None), "debug");
+
+        let mut logging = LoggingConfig {
+            format: LogFormat::Json,
+            level: "warn".to_string(),
+            ..Default::default()
+        };
+        logging
+            .modules
+            .insert("mcp_guard_core::transport".to_string(), "trace".to_string());
+        logging
+            .modules
+            .insert("hyper".to_string(), "error".to_string());
+
+        assert_eq!(
+            log_filter_directives(false, Some(&logging)),
+            "warn,hyper=error,mcp_guard_core::transport=trace"
+        );
+        // --verbose raises the base level but keeps module overrides
+        assert_eq!(
+            log_filter_directives(true, Some(&logging)),
+            "debug,hyper=error,mcp_guard_core::transport=trace"
+        );
+        assert!(EnvFilter::try_new(log_filter_directives(false, Some(&logging))).is_ok());
+
+        let guard = init_tracing(false, None, Some(&logging));
+        drop(guard);
+    }
+
+    #[test]
+    fn test_hash_identity_id() {
+        let hash = hash_identity_id("alice@example.com");
+        assert_eq!(hash.len(), 16);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(hash, hash_identity_id("alice@example.com"));
+        assert_ne!(hash, hash_identity_id("bob@example.com"));
+    }
+
+    #[test]
+    fn test_inject_trace_meta() {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+        use serde_json::json;
+
+        let context = opentelemetry::Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        ));
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        // Existing params and _meta entries are kept
+        let mut call = Message::request(
+            1,
+            "tools/call",
+            Some(json!({"name": "echo", "_meta": {"progressToken": 7}})),
+        );
+        inject_trace_meta_from(&context, &mut call);
+        let params = call.params.unwrap();
+        assert_eq!(params["name"], "echo");
+        assert_eq!(params["_meta"]["progressToken"], 7);
+        assert_eq!(params["_meta"]["traceparent"], traceparent);
+
+        // Requests without params get them
+        let mut list = Message::request(2, "tools/list", None);
+        inject_trace_meta_from(&context, &mut list);
+        assert_eq!(list.params.unwrap()["_meta"]["traceparent"], traceparent);
+
+        // No active trace, or not a request: untouched
+        let mut untraced = Message::request(3, "tools/list", None);
+        inject_trace_meta_from(&opentelemetry::Context::new(), &mut untraced);
+        assert!(untraced.params.is_none());
+        let mut response = Message::response(json!(4), json!({}));
+        inject_trace_meta_from(&context, &mut response);
+        assert!(response.params.is_none());
+    }
 }
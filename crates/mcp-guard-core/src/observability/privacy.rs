@@ -0,0 +1,95 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Telemetry privacy mode (`[telemetry_privacy]`)
+//!
+//! With a salt installed, identity IDs leave the process only as a keyed
+//! hash: per-identity metric labels, the `mcp.identity_hash` span attribute
+//! and the audit copies sent to export and object storage. The local audit
+//! file, logs and admin endpoints keep the real ID.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::RwLock;
+
+use crate::config::TelemetryPrivacyConfig;
+
+/// `None` while privacy mode is disabled
+static TELEMETRY_SALT: RwLock<Option<String>> = RwLock::new(None);
+
+/// Enable or disable privacy mode from `[telemetry_privacy]`
+///
+/// Call once at startup, before metrics are recorded or audit entries
+/// exported.
+pub fn configure_telemetry_privacy(config: &TelemetryPrivacyConfig) {
+    let salt = config.salt.clone().filter(|_| config.enabled);
+    if let Ok(mut current) = TELEMETRY_SALT.write() {
+        *current = salt;
+    }
+}
+
+/// Whether identity IDs are hashed before leaving the process
+pub fn telemetry_privacy_enabled() -> bool {
+    TELEMETRY_SALT
+        .read()
+        .map(|salt| salt.is_some())
+        .unwrap_or(false)
+}
+
+/// Identity as it may appear in telemetry
+///
+/// The salted hash in privacy mode, the ID itself otherwise.
+pub fn telemetry_identity(identity_id: &str) -> String {
+    salted_hash(identity_id).unwrap_or_else(|| identity_id.to_string())
+}
+
+/// Salted hash of `identity_id`, or `None` while disabled
+pub(super) fn salted_hash(identity_id: &str) -> Option<String> {
+    let salt = TELEMETRY_SALT.read().ok()?;
+    salt.as_deref().map(|salt| keyed_hash(salt, identity_id))
+}
+
+/// First 16 hex digits of HMAC-SHA256(salt, ID)
+fn keyed_hash(salt: &str, identity_id: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(salt.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(identity_id.as_bytes());
+    mac.finalize().into_bytes()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::hash_identity_id;
+
+    // The installed salt is process-wide, so tests hash with an explicit one
+
+    #[test]
+    fn test_keyed_hash() {
+        let salt = "0123456789abcdef";
+        let hash = keyed_hash(salt, "alice@example.com");
+        assert_eq!(hash.len(), 16);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(hash, keyed_hash(salt, "alice@example.com"));
+        assert_ne!(hash, keyed_hash(salt, "bob@example.com"));
+        assert_ne!(hash, keyed_hash("fedcba9876543210", "alice@example.com"));
+        assert_ne!(hash, hash_identity_id("alice@example.com"));
+    }
+}
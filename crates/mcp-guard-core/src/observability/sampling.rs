@@ -0,0 +1,228 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Head-based sampling with per-route and per-method rates
+//!
+//! The decision is made once, when a trace's root span starts: the request
+//! span created by the trace context middleware carries `mcp.upstream` and
+//! `mcp.method` attributes, which pick the rate from `[[tracing.sampling]]`.
+//! Spans below it follow their parent, so a trace is kept or dropped whole.
+//! A parent propagated from a client counts as a new head: the gateway's
+//! own rules decide, as they did with the single `sample_rate`.
+
+use glob::Pattern;
+use opentelemetry::trace::{
+    Link, SamplingDecision, SamplingResult, SpanKind, TraceContextExt, TraceId,
+};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_sdk::trace::{Sampler, ShouldSample};
+
+use crate::config::TracingConfig;
+
+/// Span attribute naming the server route of a request
+const UPSTREAM_ATTRIBUTE: &str = "mcp.upstream";
+
+/// Span attribute naming the MCP method of a request
+const METHOD_ATTRIBUTE: &str = "mcp.method";
+
+/// Sampler applying `[[tracing.sampling]]` before `tracing.sample_rate`
+#[derive(Debug, Clone)]
+pub struct RuleSampler {
+    default_rate: f64,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    route: Option<Pattern>,
+    method: Option<Pattern>,
+    sample_rate: f64,
+}
+
+impl Rule {
+    fn matches(&self, route: Option<&str>, method: Option<&str>) -> bool {
+        let matches = |pattern: &Option<Pattern>, value: Option<&str>| match pattern {
+            Some(pattern) => value.is_some_and(|value| pattern.matches(value)),
+            None => true,
+        };
+        matches(&self.route, route) && matches(&self.method, method)
+    }
+}
+
+impl RuleSampler {
+    /// Build the sampler from configuration; invalid patterns are rejected
+    /// by config validation and skipped here
+    pub fn new(config: &TracingConfig) -> Self {
+        let pattern = |glob: &Option<String>| glob.as_deref().map(Pattern::new);
+        Self {
+            default_rate: config.sample_rate,
+            rules: config
+                .sampling
+                .iter()
+                .filter_map(|rule| {
+                    Some(Rule {
+                        route: pattern(&rule.route).transpose().ok()?,
+                        method: pattern(&rule.method).transpose().ok()?,
+                        sample_rate: rule.sample_rate,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Rate for a request to `route` calling `method`; the first matching
+    /// rule wins
+    pub fn rate(&self, route: Option<&str>, method: Option<&str>) -> f64 {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(route, method))
+            .map_or(self.default_rate, |rule| rule.sample_rate)
+    }
+}
+
+impl ShouldSample for RuleSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        // Inside the gateway, a span is kept exactly when its parent is
+        if let Some(parent) = parent_context.filter(|cx| cx.has_active_span()) {
+            let span = parent.span();
+            let parent = span.span_context();
+            if parent.is_valid() && !parent.is_remote() {
+                return SamplingResult {
+                    decision: if parent.is_sampled() {
+                        SamplingDecision::RecordAndSample
+                    } else {
+                        SamplingDecision::Drop
+                    },
+                    attributes: Vec::new(),
+                    trace_state: parent.trace_state().clone(),
+                };
+            }
+        }
+
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.as_str())
+        };
+        let route = attribute(UPSTREAM_ATTRIBUTE);
+        let method = attribute(METHOD_ATTRIBUTE);
+        let rate = self.rate(route.as_deref(), method.as_deref());
+
+        let sampler = if rate >= 1.0 {
+            Sampler::AlwaysOn
+        } else if rate <= 0.0 {
+            Sampler::AlwaysOff
+        } else {
+            Sampler::TraceIdRatioBased(rate)
+        };
+        sampler.should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TracingSamplingRule;
+    use opentelemetry::trace::{SpanContext, SpanId, TraceFlags, TraceState};
+
+    fn sampler() -> RuleSampler {
+        RuleSampler::new(&TracingConfig {
+            sample_rate: 0.0,
+            sampling: vec![
+                TracingSamplingRule {
+                    route: Some("payments".to_string()),
+                    method: Some("tools/call".to_string()),
+                    sample_rate: 1.0,
+                },
+                TracingSamplingRule {
+                    route: None,
+                    method: Some("resources/*".to_string()),
+                    sample_rate: 0.5,
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn decide(sampler: &RuleSampler, parent: Option<&Context>, route: &str, method: &str) -> bool {
+        let attributes = [
+            KeyValue::new(UPSTREAM_ATTRIBUTE, route.to_string()),
+            KeyValue::new(METHOD_ATTRIBUTE, method.to_string()),
+        ];
+        let result = sampler.should_sample(
+            parent,
+            TraceId::from_u128(42),
+            "http_request",
+            &SpanKind::Internal,
+            &attributes,
+            &[],
+        );
+        result.decision == SamplingDecision::RecordAndSample
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let sampler = sampler();
+        assert_eq!(sampler.rate(Some("payments"), Some("tools/call")), 1.0);
+        assert_eq!(sampler.rate(Some("payments"), Some("tools/list")), 0.0);
+        assert_eq!(sampler.rate(Some("files"), Some("resources/read")), 0.5);
+        assert_eq!(sampler.rate(None, Some("resources/read")), 0.5);
+        // A route rule never matches a request whose route is unknown
+        assert_eq!(sampler.rate(None, Some("tools/call")), 0.0);
+        assert_eq!(sampler.rate(None, None), 0.0);
+    }
+
+    #[test]
+    fn test_root_spans_sampled_by_attributes() {
+        let sampler = sampler();
+        assert!(decide(&sampler, None, "payments", "tools/call"));
+        assert!(!decide(&sampler, None, "files", "tools/call"));
+    }
+
+    #[test]
+    fn test_local_children_follow_parent() {
+        let sampler = sampler();
+        let parent = |flags, remote| {
+            Context::new().with_remote_span_context(SpanContext::new(
+                TraceId::from_u128(42),
+                SpanId::from_u64(7),
+                flags,
+                remote,
+                TraceState::default(),
+            ))
+        };
+
+        // Sampled local parent keeps a child the default rate would drop
+        let cx = parent(TraceFlags::SAMPLED, false);
+        assert!(decide(&sampler, Some(&cx), "files", "tools/call"));
+        let cx = parent(TraceFlags::default(), false);
+        assert!(!decide(&sampler, Some(&cx), "payments", "tools/call"));
+
+        // A client's parent does not override the gateway's rules
+        let cx = parent(TraceFlags::default(), true);
+        assert!(decide(&sampler, Some(&cx), "payments", "tools/call"));
+    }
+}
@@ -24,16 +24,22 @@
 //! - Token bucket algorithm via Governor crate
 //! - TTL-based eviction to prevent memory growth
 //! - Background cleanup task to avoid inline latency spikes
+//! - Temporary per-identity overrides and resets at runtime
+//! - Time-of-day schedules for the default limit
+//! - Warm-up: new identities start with smaller buckets that grow over time
 //!
 //! See PRD FR-RATE-01 through FR-RATE-07 for requirements.
 
+use chrono::{DateTime, Datelike, Timelike, Utc, Weekday};
 use dashmap::DashMap;
 use glob::Pattern;
 use governor::{
     clock::{Clock, DefaultClock},
-    state::{InMemoryState, NotKeyed},
+    state::{keyed::DefaultKeyedStateStore, InMemoryState, NotKeyed},
     Quota, RateLimiter,
 };
+use serde::Serialize;
+use std::net::IpAddr;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -42,6 +48,12 @@ use tokio_util::sync::CancellationToken;
 /// Rate limiter type alias for a direct (non-keyed) token bucket limiter
 type Limiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
 
+/// Per-IP limiter type alias for a keyed token bucket limiter
+type IpLimiter = RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>, DefaultClock>;
+
+/// Client IPs tracked by an [`IpRateLimiter`] before idle ones are dropped
+const MAX_TRACKED_IPS: usize = 10_000;
+
 /// Default TTL for idle rate limiter entries.
 /// 1 hour balances memory cleanup with user experience (users reconnecting within
 /// an hour keep their rate limit state). Typical sessions are shorter.
@@ -59,10 +71,94 @@ const DEFAULT_RPS: NonZeroU32 = unsafe { NonZeroU32::new_unchecked(100) };
 /// SAFETY: 50 is non-zero, so new_unchecked is safe
 const DEFAULT_BURST: NonZeroU32 = unsafe { NonZeroU32::new_unchecked(50) };
 
+/// `_meta` key carrying rate limits in `tools/list` results
+pub const LIMITS_META_KEY: &str = "mcp-guard/limits";
+
 /// Entry in the rate limiter cache with last access time
 struct RateLimitEntry {
     limiter: Arc<Limiter>,
     last_access: Instant,
+    /// Quota the limiter was built with
+    rps: u32,
+    burst: u32,
+}
+
+/// Limit set at runtime that replaces an identity's configured limit until it expires
+struct LimitOverride {
+    rps: u32,
+    burst: u32,
+    expires_at: Instant,
+}
+
+/// Compiled time-of-day window (`[[rate_limit.schedules]]`)
+struct Schedule {
+    /// Days the window starts on, empty for every day
+    days: Vec<Weekday>,
+    /// Minutes since midnight UTC
+    start: u32,
+    end: u32,
+    rps: u32,
+    burst: u32,
+}
+
+impl Schedule {
+    fn compile(config: &crate::config::RateLimitScheduleConfig) -> Option<Self> {
+        let days = config
+            .days
+            .iter()
+            .map(|day| day.parse().ok())
+            .collect::<Option<Vec<Weekday>>>()?;
+        Some(Self {
+            days,
+            start: crate::config::parse_time_of_day(&config.start)?,
+            end: crate::config::parse_time_of_day(&config.end)?,
+            rps: config.requests_per_second,
+            burst: config.burst_size,
+        })
+    }
+
+    fn contains(&self, now: DateTime<Utc>) -> bool {
+        let minute = now.hour() * 60 + now.minute();
+        let today = now.weekday();
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute) && self.starts_on(today)
+        } else if minute >= self.start {
+            self.starts_on(today)
+        } else {
+            // Past midnight: the window opened the day before
+            minute < self.end && self.starts_on(today.pred())
+        }
+    }
+
+    fn starts_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+}
+
+/// Warm-up progress of an identity
+struct WarmupLevel {
+    /// Share of the limit currently granted
+    percent: u32,
+    /// When the current step started (in the future during a cooldown)
+    step_started: Instant,
+    last_access: Instant,
+}
+
+/// Current rate limit state of an identity
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct IdentityLimit {
+    /// Requests per second applied to the identity
+    pub limit: u32,
+    /// Burst size applied to the identity
+    pub burst: u32,
+    /// Whether the identity has a bucket (made a request within the entry TTL)
+    pub tracked: bool,
+    /// Seconds since the bucket was last used
+    pub idle_secs: Option<u64>,
+    /// Seconds until a temporary override lapses
+    pub override_expires_in_secs: Option<u64>,
+    /// Share of the limit granted so far, while warming up
+    pub warmup_percent: Option<u32>,
 }
 
 /// Result of a rate limit check
@@ -122,6 +218,14 @@ pub struct RateLimitService {
     tool_limiters: DashMap<String, RateLimitEntry>,
     /// Compiled tool patterns with their rate limits
     tool_patterns: Vec<ToolPattern>,
+    /// Temporary per-identity limits set at runtime
+    overrides: DashMap<String, LimitOverride>,
+    /// Time-of-day windows replacing the default limit
+    schedules: Vec<Schedule>,
+    /// Warm-up settings, `None` when disabled
+    warmup: Option<crate::config::RateLimitWarmupConfig>,
+    /// Warm-up progress per identity
+    warmup_levels: DashMap<String, WarmupLevel>,
     /// TTL for idle entries
     entry_ttl: Duration,
 }
@@ -159,13 +263,33 @@ impl RateLimitService {
             );
         }
 
+        let schedules = config
+            .schedules
+            .iter()
+            .filter_map(|schedule| {
+                let compiled = Schedule::compile(schedule);
+                if compiled.is_none() {
+                    tracing::warn!(
+                        start = %schedule.start,
+                        end = %schedule.end,
+                        "Invalid rate limit schedule, skipping"
+                    );
+                }
+                compiled
+            })
+            .collect();
+
         Self {
             enabled: config.enabled,
             default_rps: config.requests_per_second,
             default_burst: config.burst_size,
             identity_limiters: DashMap::new(),
             tool_limiters: DashMap::new(),
+            overrides: DashMap::new(),
             tool_patterns,
+            schedules,
+            warmup: (config.enabled && config.warmup.enabled).then(|| config.warmup.clone()),
+            warmup_levels: DashMap::new(),
             entry_ttl: DEFAULT_ENTRY_TTL,
         }
     }
@@ -179,33 +303,124 @@ impl RateLimitService {
         RateLimiter::direct(quota)
     }
 
+    /// Burst size for a custom per-identity limit (half the rate, at least 1)
+    fn custom_burst(rps: u32) -> u32 {
+        (rps as f32 * 0.5).max(1.0) as u32
+    }
+
+    /// Default limit and burst, from the first schedule window containing `now`
+    fn default_limit(&self, now: DateTime<Utc>) -> (u32, u32) {
+        self.schedules
+            .iter()
+            .find(|schedule| schedule.contains(now))
+            .map_or((self.default_rps, self.default_burst), |schedule| {
+                (schedule.rps, schedule.burst)
+            })
+    }
+
+    /// Limit and burst for an identity, honoring an unexpired override
+    ///
+    /// Without an override, the configured limit is scaled by the identity's
+    /// warm-up progress.
+    fn effective_limit(&self, identity_id: &str, custom_limit: Option<u32>) -> (u32, u32) {
+        if let Some((rps, burst, _)) = self.active_override(identity_id) {
+            return (rps, burst);
+        }
+        let (rps, burst) = match custom_limit {
+            // Use custom rate limit with proportional burst
+            Some(rps) => (rps, Self::custom_burst(rps)),
+            None => self.default_limit(Utc::now()),
+        };
+        match self.warmup_percent(identity_id) {
+            Some(percent) => (scale(rps, percent), scale(burst, percent)),
+            None => (rps, burst),
+        }
+    }
+
+    /// Warm-up progress of an identity, advanced by the steps earned since
+    /// the last request; `None` when warm-up is disabled
+    fn warmup_percent(&self, identity_id: &str) -> Option<u32> {
+        let warmup = self.warmup.as_ref()?;
+        let now = Instant::now();
+        let step = Duration::from_secs(warmup.step_secs);
+        let mut level = self
+            .warmup_levels
+            .entry(identity_id.to_string())
+            .or_insert_with(|| WarmupLevel {
+                percent: warmup.initial_percent.min(100),
+                step_started: now,
+                last_access: now,
+            });
+        level.last_access = now;
+        while level.percent < 100 && now.saturating_duration_since(level.step_started) >= step {
+            level.percent = (level.percent + warmup.step_percent).min(100);
+            level.step_started += step;
+        }
+        Some(level.percent)
+    }
+
+    /// Hold a rate-limited identity's warm-up for the cooldown
+    ///
+    /// The level is kept rather than lowered: a smaller quota would come with
+    /// a fresh, full bucket.
+    fn warmup_denied(&self, identity_id: &str) {
+        let Some(warmup) = self.warmup.as_ref() else {
+            return;
+        };
+        if let Some(mut level) = self.warmup_levels.get_mut(identity_id) {
+            level.step_started = Instant::now() + Duration::from_secs(warmup.cooldown_secs);
+        }
+    }
+
+    /// Look up an identity's override, dropping it once expired
+    fn active_override(&self, identity_id: &str) -> Option<(u32, u32, Instant)> {
+        if self.overrides.is_empty() {
+            return None;
+        }
+        let now = Instant::now();
+        let (rps, burst, expires_at) = {
+            let entry = self.overrides.get(identity_id)?;
+            (entry.rps, entry.burst, entry.expires_at)
+        };
+        if expires_at > now {
+            return Some((rps, burst, expires_at));
+        }
+        // The bucket was built for the override; start over at the configured limit
+        if self
+            .overrides
+            .remove_if(identity_id, |_, entry| entry.expires_at <= now)
+            .is_some()
+        {
+            self.identity_limiters.remove(identity_id);
+        }
+        None
+    }
+
     /// Get or create a rate limiter for the given identity, updating last access time
-    fn get_identity_limiter(&self, identity_id: &str, custom_limit: Option<u32>) -> Arc<Limiter> {
+    ///
+    /// A cached limiter built with a different quota (an override was set or
+    /// lapsed) is replaced with a fresh bucket.
+    fn get_identity_limiter(&self, identity_id: &str, rps: u32, burst: u32) -> Arc<Limiter> {
         let now = Instant::now();
 
         // Check if we already have a limiter for this identity
         if let Some(mut entry) = self.identity_limiters.get_mut(identity_id) {
-            entry.last_access = now;
-            return entry.limiter.clone();
+            if entry.rps == rps && entry.burst == burst {
+                entry.last_access = now;
+                return entry.limiter.clone();
+            }
         }
 
         // Note: Cleanup is now handled by a background task to avoid latency spikes
         // See start_cleanup_task() for the background cleanup implementation
 
         // Create a new limiter for this identity
-        let (rps, burst) = if let Some(custom_rps) = custom_limit {
-            // Use custom rate limit with proportional burst
-            let custom_burst = (custom_rps as f32 * 0.5).max(1.0) as u32;
-            (custom_rps, custom_burst)
-        } else {
-            // Use defaults
-            (self.default_rps, self.default_burst)
-        };
-
         let limiter = Arc::new(Self::create_limiter(rps, burst));
         let entry = RateLimitEntry {
             limiter: limiter.clone(),
             last_access: now,
+            rps,
+            burst,
         };
         self.identity_limiters
             .insert(identity_id.to_string(), entry);
@@ -227,6 +442,8 @@ impl RateLimitService {
         let entry = RateLimitEntry {
             limiter: limiter.clone(),
             last_access: now,
+            rps,
+            burst,
         };
         self.tool_limiters.insert(key.to_string(), entry);
         limiter
@@ -293,6 +510,11 @@ impl RateLimitService {
         self.tool_limiters
             .retain(|_, entry| now.duration_since(entry.last_access) < ttl);
 
+        self.overrides.retain(|_, entry| entry.expires_at > now);
+
+        self.warmup_levels
+            .retain(|_, level| now.duration_since(level.last_access) < ttl);
+
         tracing::debug!(
             identity_remaining = self.identity_limiters.len(),
             tool_remaining = self.tool_limiters.len(),
@@ -310,6 +532,17 @@ impl RateLimitService {
         !self.tool_patterns.is_empty()
     }
 
+    /// Rate and burst of the per-tool limit matching a tool, if any
+    pub fn tool_limit(&self, tool_name: &str) -> Option<(u32, u32)> {
+        if !self.enabled {
+            return None;
+        }
+        self.tool_patterns
+            .iter()
+            .find(|tp| tp.pattern.matches(tool_name))
+            .map(|tp| (tp.rps, tp.burst))
+    }
+
     /// Check if a request should be allowed for the given identity
     ///
     /// # Arguments
@@ -320,10 +553,7 @@ impl RateLimitService {
     /// A `RateLimitResult` indicating whether the request is allowed and retry-after time if denied
     pub fn check(&self, identity_id: &str, custom_limit: Option<u32>) -> RateLimitResult {
         // Calculate the effective limit for this identity
-        let limit = custom_limit.unwrap_or(self.default_rps);
-        let burst = custom_limit
-            .map(|rps| (rps as f32 * 0.5).max(1.0) as u32)
-            .unwrap_or(self.default_burst);
+        let (limit, burst) = self.effective_limit(identity_id, custom_limit);
 
         // Calculate reset timestamp (1 second from now, since we use per-second limits)
         let reset_at = std::time::SystemTime::now()
@@ -336,7 +566,7 @@ impl RateLimitService {
             return RateLimitResult::allowed(limit, burst, reset_at);
         }
 
-        let limiter = self.get_identity_limiter(identity_id, custom_limit);
+        let limiter = self.get_identity_limiter(identity_id, limit, burst);
 
         match limiter.check() {
             Ok(_) => {
@@ -346,6 +576,7 @@ impl RateLimitService {
                 RateLimitResult::allowed(limit, remaining, reset_at)
             }
             Err(not_until) => {
+                self.warmup_denied(identity_id);
                 // Calculate retry-after in seconds
                 let wait_duration = not_until.wait_time_from(DefaultClock::default().now());
                 let retry_secs = wait_duration.as_secs().max(1);
@@ -354,6 +585,16 @@ impl RateLimitService {
         }
     }
 
+    /// Most requests the identity may make within one second, or `None` when
+    /// rate limiting is disabled (the cluster-wide cap in cluster mode)
+    pub fn max_per_second(&self, identity_id: &str, custom_limit: Option<u32>) -> Option<u32> {
+        if !self.enabled {
+            return None;
+        }
+        let (rps, burst) = self.effective_limit(identity_id, custom_limit);
+        Some(rps.max(burst))
+    }
+
     /// Check rate limit, returning a simple bool (for backwards compatibility)
     pub fn check_allowed(&self, identity_id: &str, custom_limit: Option<u32>) -> bool {
         self.check(identity_id, custom_limit).allowed
@@ -365,7 +606,8 @@ impl RateLimitService {
             return;
         }
 
-        let limiter = self.get_identity_limiter(identity_id, custom_limit);
+        let (rps, burst) = self.effective_limit(identity_id, custom_limit);
+        let limiter = self.get_identity_limiter(identity_id, rps, burst);
         limiter.until_ready().await;
     }
 
@@ -377,6 +619,92 @@ impl RateLimitService {
     /// Clear rate limit state for a specific identity (e.g., on identity deletion)
     pub fn clear_identity(&self, identity_id: &str) {
         self.identity_limiters.remove(identity_id);
+        self.warmup_levels.remove(identity_id);
+    }
+
+    /// Current limit and bucket state for an identity
+    ///
+    /// Identities without a bucket report the override or (scheduled)
+    /// default limit; a custom per-identity limit and warm-up only show once
+    /// the identity has made a request.
+    pub fn identity_limit(&self, identity_id: &str) -> IdentityLimit {
+        let now = Instant::now();
+        let active_override = self.active_override(identity_id);
+        let bucket = self
+            .identity_limiters
+            .get(identity_id)
+            .map(|entry| (entry.rps, entry.burst, entry.last_access));
+
+        let (limit, burst) = match (active_override, bucket) {
+            (Some((rps, burst, _)), _) | (None, Some((rps, burst, _))) => (rps, burst),
+            (None, None) => self.default_limit(Utc::now()),
+        };
+        let warmup_percent = self
+            .warmup_levels
+            .get(identity_id)
+            .map(|level| level.percent)
+            .filter(|percent| *percent < 100);
+
+        IdentityLimit {
+            limit,
+            burst,
+            tracked: bucket.is_some(),
+            idle_secs: bucket.map(|(_, _, last_access)| now.duration_since(last_access).as_secs()),
+            override_expires_in_secs: active_override
+                .map(|(_, _, expires_at)| expires_at.duration_since(now).as_secs()),
+            warmup_percent,
+        }
+    }
+
+    /// Temporarily replace an identity's limit
+    ///
+    /// The identity gets a fresh bucket at the new limit on its next request
+    /// and returns to its configured limit once `ttl` lapses. Without a
+    /// `burst_size` the burst is half the rate, as for custom limits.
+    pub fn set_override(
+        &self,
+        identity_id: &str,
+        requests_per_second: u32,
+        burst_size: Option<u32>,
+        ttl: Duration,
+    ) -> IdentityLimit {
+        self.overrides.insert(
+            identity_id.to_string(),
+            LimitOverride {
+                rps: requests_per_second,
+                burst: burst_size.unwrap_or_else(|| Self::custom_burst(requests_per_second)),
+                expires_at: Instant::now() + ttl,
+            },
+        );
+        self.identity_limit(identity_id)
+    }
+
+    /// Drop an identity's override and buckets, including per-tool buckets
+    ///
+    /// Its next request starts with a full bucket at the configured limit,
+    /// skipping any remaining warm-up. Returns whether there was any state to
+    /// drop.
+    pub fn reset_identity(&self, identity_id: &str) -> bool {
+        let had_override = self.overrides.remove(identity_id).is_some();
+        let had_bucket = self.identity_limiters.remove(identity_id).is_some();
+        if self.warmup.is_some() {
+            let now = Instant::now();
+            self.warmup_levels.insert(
+                identity_id.to_string(),
+                WarmupLevel {
+                    percent: 100,
+                    step_started: now,
+                    last_access: now,
+                },
+            );
+        }
+
+        let prefix = format!("{}:", identity_id);
+        let tool_buckets = self.tool_limiters.len();
+        self.tool_limiters
+            .retain(|key, _| !key.starts_with(&prefix));
+
+        had_override || had_bucket || self.tool_limiters.len() < tool_buckets
     }
 
     /// Set a custom TTL for entry expiration (for testing)
@@ -440,12 +768,71 @@ impl RateLimitService {
     }
 }
 
+/// `percent` of a limit, at least 1
+fn scale(value: u32, percent: u32) -> u32 {
+    ((u64::from(value) * u64::from(percent) / 100) as u32).max(1)
+}
+
 impl Default for RateLimitService {
     fn default() -> Self {
         Self::new(&crate::config::RateLimitConfig::default())
     }
 }
 
+/// Per-client-IP rate limiter for unauthenticated endpoints
+///
+/// Requests without an identity (client registration, logins) cannot be
+/// limited by [`RateLimitService`], so they are limited by client IP instead,
+/// independently of `rate_limit.enabled`.
+pub struct IpRateLimiter {
+    limiter: IpLimiter,
+}
+
+impl IpRateLimiter {
+    /// Allow `requests` per minute from each IP, all of them in a burst
+    pub fn per_minute(requests: u32) -> Self {
+        let requests = NonZeroU32::new(requests).unwrap_or(NonZeroU32::MIN);
+        Self {
+            limiter: RateLimiter::keyed(Quota::per_minute(requests)),
+        }
+    }
+
+    /// Limiter for `/oauth/register` (`auth.oauth.registration.max_requests_per_minute`)
+    pub fn for_registration(config: &crate::config::Config) -> Self {
+        let registration = config
+            .auth
+            .oauth
+            .as_ref()
+            .and_then(|oauth| oauth.registration.clone())
+            .unwrap_or_default();
+        Self::per_minute(registration.max_requests_per_minute)
+    }
+
+    /// Take a request from `ip`'s bucket
+    ///
+    /// # Errors
+    /// Returns the seconds until `ip` may retry when its bucket is empty.
+    pub fn check(&self, ip: IpAddr) -> Result<(), u64> {
+        if self.limiter.len() > MAX_TRACKED_IPS {
+            // Drop buckets that have refilled; they behave like new ones
+            self.limiter.retain_recent();
+        }
+        self.limiter.check_key(&ip).map_err(|not_until| {
+            not_until
+                .wait_time_from(DefaultClock::default().now())
+                .as_secs()
+                .max(1)
+        })
+    }
+}
+
+impl Default for IpRateLimiter {
+    /// The client registration default
+    fn default() -> Self {
+        Self::per_minute(crate::config::OAuthRegistrationConfig::default().max_requests_per_minute)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     //! Unit tests for rate limiting service.
@@ -467,6 +854,9 @@ mod tests {
             requests_per_second: rps,
             burst_size: burst,
             tool_limits: Vec::new(),
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
         }
     }
 
@@ -558,6 +948,57 @@ mod tests {
         assert!(service.check("user", None).allowed);
     }
 
+    /// Verify overrides replace the configured limit until they lapse
+    #[test]
+    fn test_override_and_reset() {
+        let config = RateLimitConfig {
+            enabled: true,
+            requests_per_second: 1,
+            burst_size: 1,
+            tool_limits: vec![ToolRateLimitConfig {
+                tool_pattern: "execute_*".to_string(),
+                requests_per_second: 1,
+                burst_size: 1,
+            }],
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
+        };
+        let service = RateLimitService::new(&config);
+
+        assert!(service.check("user", None).allowed);
+        assert!(!service.check("user", None).allowed);
+
+        // A raised limit takes effect with a fresh bucket
+        let limit = service.set_override("user", 10, Some(3), Duration::from_secs(60));
+        assert_eq!((limit.limit, limit.burst), (10, 3));
+        assert!(limit.override_expires_in_secs.unwrap() > 0);
+        for _ in 0..3 {
+            assert!(service.check("user", None).allowed);
+        }
+        assert!(!service.check("user", None).allowed);
+        assert!(service.identity_limit("user").tracked);
+
+        // An expired override falls back to the configured limit
+        service.set_override("user", 10, None, Duration::ZERO);
+        let limit = service.identity_limit("user");
+        assert_eq!(limit.limit, 1);
+        assert!(!limit.tracked);
+        assert!(limit.override_expires_in_secs.is_none());
+        assert!(service.check("user", None).allowed);
+        assert!(!service.check("user", None).allowed);
+
+        // Reset drops identity and tool buckets
+        service.check_tool("user", "execute_code");
+        assert_eq!(service.tracked_tools(), 1);
+        assert!(service.reset_identity("user"));
+        assert_eq!(service.tracked_identities(), 0);
+        assert_eq!(service.tracked_tools(), 0);
+        assert!(!service.reset_identity("user"));
+        assert!(service.check("user", None).allowed);
+        assert!(!service.identity_limit("nobody").tracked);
+    }
+
     /// Verify backwards-compatible check_allowed returns simple bool
     #[test]
     fn test_check_allowed_backwards_compat() {
@@ -651,6 +1092,9 @@ mod tests {
                 requests_per_second: 5,
                 burst_size: 2,
             }],
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
         };
         let service = RateLimitService::new(&config);
 
@@ -670,6 +1114,9 @@ mod tests {
                 requests_per_second: 2,
                 burst_size: 2,
             }],
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
         };
         let service = RateLimitService::new(&config);
 
@@ -712,6 +1159,9 @@ mod tests {
                     burst_size: 3,
                 },
             ],
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
         };
         let service = RateLimitService::new(&config);
 
@@ -732,6 +1182,12 @@ mod tests {
 
         // read_file should not match any pattern
         assert!(service.check_tool("user", "read_file").is_none());
+
+        // Looking up a tool's limit does not create a limiter
+        let tracked = service.tracked_tools();
+        assert_eq!(service.tool_limit("write_file"), Some((5, 3)));
+        assert_eq!(service.tool_limit("read_file"), None);
+        assert_eq!(service.tracked_tools(), tracked);
     }
 
     /// Verify different identities have independent tool limiters
@@ -746,6 +1202,9 @@ mod tests {
                 requests_per_second: 1,
                 burst_size: 1,
             }],
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
         };
         let service = RateLimitService::new(&config);
 
@@ -793,6 +1252,9 @@ mod tests {
                 requests_per_second: 10,
                 burst_size: 5,
             }],
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
         };
         let service = RateLimitService::new(&config).with_ttl(Duration::ZERO);
 
@@ -805,4 +1267,95 @@ mod tests {
         service.cleanup_expired();
         assert_eq!(service.tracked_tools(), 0);
     }
+
+    /// Verify schedule windows, including ones past midnight
+    #[test]
+    fn test_schedule_windows() {
+        use crate::config::RateLimitScheduleConfig;
+        use chrono::TimeZone;
+
+        let mut config = test_config(true, 100, 50);
+        config.schedules = vec![
+            RateLimitScheduleConfig {
+                days: vec!["sat".to_string()],
+                start: "22:00".to_string(),
+                end: "06:00".to_string(),
+                requests_per_second: 5,
+                burst_size: 2,
+            },
+            RateLimitScheduleConfig {
+                days: Vec::new(),
+                start: "09:00".to_string(),
+                end: "17:00".to_string(),
+                requests_per_second: 50,
+                burst_size: 20,
+            },
+        ];
+        let service = RateLimitService::new(&config);
+        // 2025-01-04 is a Saturday
+        let at = |day, hour, minute| Utc.with_ymd_and_hms(2025, 1, day, hour, minute, 0).unwrap();
+
+        assert_eq!(service.default_limit(at(4, 23, 0)), (5, 2));
+        // Sunday morning still belongs to Saturday's window
+        assert_eq!(service.default_limit(at(5, 5, 59)), (5, 2));
+        assert_eq!(service.default_limit(at(5, 6, 0)), (100, 50));
+        // Friday night is not covered
+        assert_eq!(service.default_limit(at(3, 23, 0)), (100, 50));
+        assert_eq!(service.default_limit(at(3, 12, 0)), (50, 20));
+        assert_eq!(service.default_limit(at(3, 17, 0)), (100, 50));
+    }
+
+    /// Verify new identities warm up step by step and a denial holds them
+    #[test]
+    fn test_warmup_grows_and_cools_down() {
+        use crate::config::RateLimitWarmupConfig;
+
+        let mut config = test_config(true, 100, 40);
+        config.warmup = RateLimitWarmupConfig {
+            enabled: true,
+            initial_percent: 25,
+            step_percent: 25,
+            step_secs: 60,
+            cooldown_secs: 300,
+        };
+        let service = RateLimitService::new(&config);
+
+        assert_eq!(service.effective_limit("new", None), (25, 10));
+        assert_eq!(service.identity_limit("new").warmup_percent, Some(25));
+
+        // Two steps of good behavior
+        let earlier = Instant::now() - Duration::from_secs(125);
+        service.warmup_levels.get_mut("new").unwrap().step_started = earlier;
+        assert_eq!(service.effective_limit("new", None), (75, 30));
+
+        // Exhaust the bucket: growth is held for the cooldown
+        while service.check("new", None).allowed {}
+        assert!(service.warmup_levels.get("new").unwrap().step_started > Instant::now());
+        assert_eq!(service.effective_limit("new", None), (75, 30));
+
+        // Custom limits warm up too; overrides do not
+        assert_eq!(service.effective_limit("custom", Some(8)), (2, 1));
+        service.set_override("custom", 8, Some(8), Duration::from_secs(60));
+        assert_eq!(service.effective_limit("custom", Some(8)), (8, 8));
+
+        // A reset skips the rest of the warm-up
+        assert!(service.reset_identity("new"));
+        assert_eq!(service.effective_limit("new", None), (100, 40));
+        assert_eq!(service.identity_limit("new").warmup_percent, None);
+    }
+
+    #[test]
+    fn test_ip_rate_limiter() {
+        let limiter = IpRateLimiter::per_minute(2);
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+
+        assert!(limiter.check(client).is_ok());
+        assert!(limiter.check(client).is_ok());
+        let retry = limiter.check(client).unwrap_err();
+        assert!((1..=60).contains(&retry));
+
+        // Each IP has its own bucket
+        assert!(limiter.check(other).is_ok());
+    }
 }
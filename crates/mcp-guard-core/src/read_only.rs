@@ -0,0 +1,176 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Read-only mode
+//!
+//! `[read_only]` classifies tools as read or write by name and denies calls
+//! to write tools for identities in read-only mode: everyone while the mode
+//! is enabled, and the listed `identities` (e.g. auditors) at all times.
+//! Tools nobody classified are write tools, so a lockdown never lets an
+//! unknown tool through.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use glob::Pattern;
+
+use crate::config::ReadOnlyConfig;
+use crate::router::NAMESPACE_SEPARATOR;
+
+/// Tool classification and the identities it applies to
+#[derive(Debug)]
+pub struct ReadOnlyPolicy {
+    enabled: AtomicBool,
+    identities: Vec<String>,
+    read_tools: Vec<Pattern>,
+    write_tools: Vec<Pattern>,
+    /// Tool names carry a server namespace (aggregate mode)
+    namespaced: bool,
+}
+
+impl Default for ReadOnlyPolicy {
+    fn default() -> Self {
+        Self::new(&ReadOnlyConfig::default())
+    }
+}
+
+impl ReadOnlyPolicy {
+    /// Compile `[read_only]` from configuration
+    pub fn new(config: &ReadOnlyConfig) -> Self {
+        // Patterns are checked when the configuration is validated
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .filter_map(|pattern| Pattern::new(pattern).ok())
+                .collect()
+        };
+        Self {
+            enabled: AtomicBool::new(config.enabled),
+            identities: config.identities.clone(),
+            read_tools: compile(&config.read_tools),
+            write_tools: compile(&config.write_tools),
+            namespaced: false,
+        }
+    }
+
+    /// Classify namespaced tool names by their upstream name (aggregate mode)
+    pub fn with_namespaced_tools(mut self, namespaced: bool) -> Self {
+        self.namespaced = namespaced;
+        self
+    }
+
+    /// Whether read-only mode currently applies to every identity
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Switch read-only mode for every identity, returning the previous state
+    pub fn set_enabled(&self, enabled: bool) -> bool {
+        self.enabled.swap(enabled, Ordering::Relaxed)
+    }
+
+    /// Whether `identity_id` is restricted to read tools
+    pub fn applies_to(&self, identity_id: &str) -> bool {
+        self.is_enabled() || self.identities.iter().any(|id| id == identity_id)
+    }
+
+    /// Whether `tool` is classified as read
+    ///
+    /// In aggregate mode, namespaced tools (`server.tool`) are classified by
+    /// their full name or by the upstream tool name. Elsewhere a separator is
+    /// just part of the tool's name.
+    pub fn is_read_tool(&self, tool: &str) -> bool {
+        let upstream_tool = self
+            .namespaced
+            .then(|| tool.split_once(NAMESPACE_SEPARATOR))
+            .flatten()
+            .map(|(_, upstream_tool)| upstream_tool);
+        let names = std::iter::once(tool).chain(upstream_tool);
+        let matches = |patterns: &[Pattern]| {
+            names
+                .clone()
+                .any(|name| patterns.iter().any(|pattern| pattern.matches(name)))
+        };
+        matches(&self.read_tools) && !matches(&self.write_tools)
+    }
+
+    /// Whether `identity_id` may call `tool`
+    pub fn permits(&self, identity_id: &str, tool: &str) -> bool {
+        !self.applies_to(identity_id) || self.is_read_tool(tool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(toml: &str) -> ReadOnlyPolicy {
+        ReadOnlyPolicy::new(&toml::from_str(toml).unwrap())
+    }
+
+    #[test]
+    fn test_default_classification() {
+        let policy = ReadOnlyPolicy::default();
+        assert!(policy.is_read_tool("get_weather"));
+        assert!(policy.is_read_tool("list_files"));
+        assert!(!policy.is_read_tool("delete_file"));
+
+        // Off by default
+        assert!(policy.permits("alice", "delete_file"));
+    }
+
+    #[test]
+    fn test_namespace_only_stripped_in_aggregate_mode() {
+        let aggregated = ReadOnlyPolicy::default().with_namespaced_tools(true);
+        assert!(aggregated.is_read_tool("github.search_issues"));
+        assert!(!aggregated.is_read_tool("github.create_issue"));
+
+        // Outside aggregate mode the dot is part of the upstream's tool name,
+        // so a write tool can't pass as read by embedding one
+        let single = ReadOnlyPolicy::default();
+        assert!(!single.is_read_tool("github.search_issues"));
+        assert!(!single.is_read_tool("drop_table.get_rows"));
+    }
+
+    #[test]
+    fn test_write_tools_override_read_tools() {
+        let policy = policy(
+            r#"
+            enabled = true
+            read_tools = ["get_*", "query"]
+            write_tools = ["get_and_delete_*"]
+            "#,
+        );
+        assert!(policy.permits("alice", "query"));
+        assert!(policy.permits("alice", "get_item"));
+        assert!(!policy.permits("alice", "get_and_delete_item"));
+        assert!(!policy.permits("alice", "list_files"));
+    }
+
+    #[test]
+    fn test_identities_and_runtime_switch() {
+        let policy = policy(r#"identities = ["auditor"]"#);
+        assert!(!policy.permits("auditor", "write_file"));
+        assert!(policy.permits("auditor", "read_file"));
+        assert!(policy.permits("alice", "write_file"));
+
+        assert!(!policy.set_enabled(true));
+        assert!(!policy.permits("alice", "write_file"));
+        assert!(policy.set_enabled(false));
+        assert!(policy.permits("alice", "write_file"));
+        assert!(!policy.permits("auditor", "write_file"));
+    }
+}
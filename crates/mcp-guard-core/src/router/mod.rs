@@ -19,15 +19,39 @@
 //!
 //! Routes requests to different upstream MCP servers based on path prefix.
 //! This enables organizations to run multiple MCP servers behind a single gateway.
+//!
+//! In aggregate mode the same routes are instead merged behind a single endpoint:
+//! `tools/list` returns the union of all upstream tools namespaced by server name
+//! (e.g. `github.create_issue`) and `tools/call` is dispatched to the owning upstream.
 
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
+
+use dashmap::DashSet;
+use serde_json::Value;
 
-use crate::config::{ServerRouteConfig, TransportType};
+use crate::auth::Identity;
+use crate::config::{
+    CanaryConfig, ResponseLimitConfig, RetryConfig, ServerRouteConfig, SpawnMode, TimeoutConfig,
+    TransportType,
+};
+use crate::fair_queue::{FairQueue, QueueFull};
+use crate::observability::record_route_call;
+use crate::transform::ToolSurface;
 use crate::transport::{
-    HttpTransport, Message, SseTransport, StdioTransport, Transport, TransportError,
+    enforce_response_limit, forwarded_identity_id, with_forwarded_identity, HttpTransport,
+    LazyStdioTransport, Message, MockUpstreamTransport, RawMessage, SseTransport, StdioOptions,
+    StdioTransport, Transport, TransportError, UnixSocketTransport, UpstreamHeaders, UpstreamStats,
+    UpstreamTls,
 };
 
+/// Separator between the server name and the upstream tool name in aggregate mode.
+/// Server names may not contain it, so the first occurrence always marks the namespace.
+pub const NAMESPACE_SEPARATOR: char = '.';
+
 /// Router error types
 #[derive(Debug, thiserror::Error)]
 pub enum RouterError {
@@ -39,6 +63,18 @@ pub enum RouterError {
 
     #[error("Transport error: {0}")]
     Transport(#[from] TransportError),
+
+    #[error("Unknown tool namespace in: {0}")]
+    UnknownNamespace(String),
+
+    #[error("No upstream server responded to '{0}'")]
+    AllUpstreamsFailed(String),
+
+    #[error("Fair queue full for server '{}'", .0.upstream)]
+    QueueFull(QueueFull),
+
+    #[error("Server '{0}' is draining")]
+    Draining(String),
 }
 
 /// Server route with initialized transport
@@ -47,6 +83,96 @@ pub struct ServerRoute {
     pub config: ServerRouteConfig,
     /// Initialized transport
     pub transport: Arc<dyn Transport>,
+    /// Canary target taking a share of the route's traffic, if configured
+    pub canary: Option<Canary>,
+}
+
+/// Which of a route's upstreams a call was sent to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteTarget {
+    Primary,
+    Canary,
+}
+
+impl RouteTarget {
+    /// Label used in metrics and logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteTarget::Primary => "primary",
+            RouteTarget::Canary => "canary",
+        }
+    }
+}
+
+/// Canary upstream of a route and the rule deciding which calls it gets
+pub struct Canary {
+    /// Canary configuration
+    pub config: CanaryConfig,
+    /// Initialized canary transport
+    pub transport: Arc<dyn Transport>,
+    /// Calls split so far, for spreading `percent` evenly
+    calls: AtomicU64,
+}
+
+impl Canary {
+    /// Create a canary target from its configuration and transport
+    pub fn new(config: CanaryConfig, transport: Arc<dyn Transport>) -> Self {
+        Self {
+            config,
+            transport,
+            calls: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether a call from `identity` goes to the canary
+    pub fn selects(&self, identity: &Identity) -> bool {
+        if self.config.identities.contains(&identity.id) || self.matches_claims(identity) {
+            return true;
+        }
+
+        let share = self.config.percent / 100.0;
+        if share <= 0.0 {
+            return false;
+        }
+        if self.config.sticky {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            identity.id.hash(&mut hasher);
+            return ((hasher.finish() % 10_000) as f64) < share * 10_000.0;
+        }
+
+        // Call n goes to the canary whenever n * share crosses a whole number,
+        // so 5% means exactly one call in every twenty
+        let n = self.calls.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * share).floor() > (n * share).floor()
+    }
+
+    fn matches_claims(&self, identity: &Identity) -> bool {
+        !self.config.claims.is_empty()
+            && self
+                .config
+                .claims
+                .iter()
+                .all(|(claim, expected)| match identity.claims.get(claim) {
+                    Some(Value::String(value)) => value == expected,
+                    Some(Value::Array(values)) => {
+                        values.iter().any(|v| v.as_str() == Some(expected))
+                    }
+                    Some(value) => {
+                        serde_json::from_str::<Value>(expected).is_ok_and(|e| e == *value)
+                    }
+                    None => false,
+                })
+    }
+}
+
+impl ServerRoute {
+    /// Pick the transport for a call from `identity`
+    pub fn select(&self, identity: &Identity) -> (&Arc<dyn Transport>, RouteTarget) {
+        match &self.canary {
+            Some(canary) if canary.selects(identity) => (&canary.transport, RouteTarget::Canary),
+            _ => (&self.transport, RouteTarget::Primary),
+        }
+    }
 }
 
 /// Multi-server router that routes requests to different upstreams based on path
@@ -55,6 +181,12 @@ pub struct ServerRouter {
     routes: Vec<ServerRoute>,
     /// Default route (optional, used when no path prefix matches)
     default_route: Option<ServerRoute>,
+    /// Fair queue for each route, by server name
+    queues: HashMap<String, FairQueue>,
+    /// Names of routes taken out of rotation; they refuse new requests
+    draining: DashSet<String>,
+    /// Call outcomes per route, for `/health?deep=true`
+    stats: UpstreamStats,
 }
 
 impl std::fmt::Debug for ServerRouter {
@@ -93,16 +225,50 @@ impl ServerRouter {
 
         for config in configs {
             let transport = Self::create_transport(&config, validate_ssrf).await?;
-            routes.push(ServerRoute { config, transport });
+            let canary = match (&config.canary, config.canary_route()) {
+                (Some(canary), Some(canary_route)) => {
+                    let transport = Self::create_transport(&canary_route, validate_ssrf)
+                        .await
+                        .map_err(|e| match e {
+                            RouterError::TransportInit(name, msg) => {
+                                RouterError::TransportInit(format!("{} (canary)", name), msg)
+                            }
+                            e => e,
+                        })?;
+                    Some(Canary::new(canary.clone(), transport))
+                }
+                _ => None,
+            };
+            routes.push(ServerRoute {
+                config,
+                transport,
+                canary,
+            });
         }
 
+        Ok(Self::from_routes(routes))
+    }
+
+    /// Create a router from already-initialized routes
+    pub fn from_routes(mut routes: Vec<ServerRoute>) -> Self {
         // Sort routes by path prefix length (longer = more specific = higher priority)
-        routes.sort_by(|a, b| b.config.path_prefix.len().cmp(&a.config.path_prefix.len()));
+        routes.sort_by_key(|r| std::cmp::Reverse(r.config.path_prefix.len()));
 
-        Ok(Self {
+        let queues = routes
+            .iter()
+            .map(|r| {
+                let queue = FairQueue::new(&r.config.name, &r.config.fair_queue);
+                (r.config.name.clone(), queue)
+            })
+            .collect();
+
+        Self {
             routes,
             default_route: None,
-        })
+            queues,
+            draining: DashSet::new(),
+            stats: UpstreamStats::default(),
+        }
     }
 
     /// Create a transport from server route configuration
@@ -118,10 +284,25 @@ impl ServerRouter {
                         "stdio transport requires 'command'".to_string(),
                     )
                 })?;
-                let transport = StdioTransport::spawn(command, &config.args)
-                    .await
-                    .map_err(|e| RouterError::TransportInit(config.name.clone(), e.to_string()))?;
-                Ok(Arc::new(transport))
+                let options = StdioOptions::from_route(config);
+                let transport: Arc<dyn Transport> = match config.spawn {
+                    SpawnMode::Eager => Arc::new(
+                        StdioTransport::spawn_with(command, &config.args, options)
+                            .await
+                            .map_err(|e| {
+                                RouterError::TransportInit(config.name.clone(), e.to_string())
+                            })?,
+                    ),
+                    SpawnMode::OnDemand => LazyStdioTransport::new(
+                        &config.name,
+                        command,
+                        &config.args,
+                        options,
+                        config.idle_ttl(),
+                    )
+                    .map_err(|e| RouterError::TransportInit(config.name.clone(), e.to_string()))?,
+                };
+                Ok(transport)
             }
             TransportType::Http => {
                 let url = config.url.as_ref().ok_or_else(|| {
@@ -137,7 +318,18 @@ impl ServerRouter {
                 } else {
                     HttpTransport::new_unchecked(url.clone())
                 };
-                Ok(Arc::new(transport))
+                let transport = match Self::upstream_tls(config)? {
+                    Some(tls) => transport.with_tls(&tls).map_err(|e| {
+                        RouterError::TransportInit(config.name.clone(), e.to_string())
+                    })?,
+                    None => transport,
+                };
+                Ok(Arc::new(
+                    transport
+                        .with_upstream_headers(Self::upstream_headers(config)?)
+                        .with_timeout(config.timeouts.max())
+                        .with_dns_refresh(std::time::Duration::from_secs(config.dns_refresh_secs)),
+                ))
             }
             TransportType::Sse => {
                 let url = config.url.as_ref().ok_or_else(|| {
@@ -157,11 +349,51 @@ impl ServerRouter {
                             RouterError::TransportInit(config.name.clone(), e.to_string())
                         })?
                 };
+                let transport = match Self::upstream_tls(config)? {
+                    Some(tls) => transport.with_tls(&tls).map_err(|e| {
+                        RouterError::TransportInit(config.name.clone(), e.to_string())
+                    })?,
+                    None => transport,
+                };
+                Ok(Arc::new(
+                    transport
+                        .with_upstream_headers(Self::upstream_headers(config)?)
+                        .with_timeout(config.timeouts.max())
+                        .with_dns_refresh(std::time::Duration::from_secs(config.dns_refresh_secs)),
+                ))
+            }
+            TransportType::Unix => {
+                let path = config.socket_path.as_ref().ok_or_else(|| {
+                    RouterError::TransportInit(
+                        config.name.clone(),
+                        "unix transport requires 'socket_path'".to_string(),
+                    )
+                })?;
+                let transport = UnixSocketTransport::connect(path)
+                    .await
+                    .map_err(|e| RouterError::TransportInit(config.name.clone(), e.to_string()))?;
                 Ok(Arc::new(transport))
             }
+            TransportType::Mock => Ok(Arc::new(MockUpstreamTransport::new(config.mock.clone()))),
         }
     }
 
+    /// Compile a route's header forwarding/injection policy
+    fn upstream_headers(config: &ServerRouteConfig) -> Result<UpstreamHeaders, RouterError> {
+        UpstreamHeaders::compile(&config.headers)
+            .map_err(|e| RouterError::TransportInit(config.name.clone(), e))
+    }
+
+    /// Load a route's TLS settings, if it has any
+    fn upstream_tls(config: &ServerRouteConfig) -> Result<Option<UpstreamTls>, RouterError> {
+        config
+            .tls
+            .as_ref()
+            .map(UpstreamTls::load)
+            .transpose()
+            .map_err(|e| RouterError::TransportInit(config.name.clone(), e.to_string()))
+    }
+
     /// Set a default route for unmatched requests
     pub fn with_default(mut self, route: ServerRoute) -> Self {
         self.default_route = Some(route);
@@ -186,6 +418,48 @@ impl ServerRouter {
         self.find_route(path).map(|r| r.transport.clone())
     }
 
+    /// Get the transport a call from `identity` should use for a given path
+    ///
+    /// Routes with a canary send a share of their calls to it.
+    pub fn select_transport(
+        &self,
+        path: &str,
+        identity: &Identity,
+    ) -> Option<(Arc<dyn Transport>, RouteTarget)> {
+        self.find_route(path).map(|r| {
+            let (transport, target) = r.select(identity);
+            (transport.clone(), target)
+        })
+    }
+
+    /// Get the retry policy for a given path
+    pub fn get_retry_config(&self, path: &str) -> Option<&RetryConfig> {
+        self.find_route(path).map(|r| &r.config.retry)
+    }
+
+    /// Get the response size limit for a given path
+    pub fn get_response_limits(&self, path: &str) -> Option<&ResponseLimitConfig> {
+        self.find_route(path).map(|r| &r.config.response_limits)
+    }
+
+    /// Get the client-facing tool surface (transforms and catalog) for a given path
+    pub fn get_tool_surface(&self, path: &str) -> ToolSurface<'_> {
+        self.find_route(path)
+            .map(|r| ToolSurface::route(&r.config))
+            .unwrap_or_default()
+    }
+
+    /// Get the timeouts for a given path
+    pub fn get_timeout_config(&self, path: &str) -> Option<&TimeoutConfig> {
+        self.find_route(path).map(|r| &r.config.timeouts)
+    }
+
+    /// Get the fair queue for a given path
+    pub fn get_fair_queue(&self, path: &str) -> Option<&FairQueue> {
+        self.find_route(path)
+            .and_then(|r| self.queues.get(&r.config.name))
+    }
+
     /// Get the route name for a given path (for logging/metrics)
     pub fn get_route_name(&self, path: &str) -> Option<&str> {
         self.find_route(path).map(|r| r.config.name.as_str())
@@ -231,6 +505,28 @@ impl ServerRouter {
         self.routes.iter().map(|r| r.config.name.as_str()).collect()
     }
 
+    /// Get the health of every route's transport, by route name
+    pub fn route_health(&self) -> Vec<(&str, bool)> {
+        self.routes
+            .iter()
+            .map(|r| (r.config.name.as_str(), r.transport.is_healthy()))
+            .collect()
+    }
+
+    /// Get every route's transport type and health, by route name
+    pub fn route_transports(&self) -> Vec<(&str, &'static str, bool)> {
+        self.routes
+            .iter()
+            .map(|r| {
+                (
+                    r.config.name.as_str(),
+                    r.transport.transport_type(),
+                    r.transport.is_healthy(),
+                )
+            })
+            .collect()
+    }
+
     /// Check if any routes are configured
     pub fn has_routes(&self) -> bool {
         !self.routes.is_empty() || self.default_route.is_some()
@@ -240,6 +536,308 @@ impl ServerRouter {
     pub fn route_count(&self) -> usize {
         self.routes.len()
     }
+
+    /// Find a route by its configured server name
+    pub fn find_route_by_name(&self, name: &str) -> Option<&ServerRoute> {
+        self.routes.iter().find(|r| r.config.name == name)
+    }
+
+    /// Take a route out of rotation, or put it back
+    ///
+    /// A draining route refuses new requests while those already in flight
+    /// finish. Returns `false` if no route has that name.
+    pub fn set_draining(&self, name: &str, draining: bool) -> bool {
+        if self.find_route_by_name(name).is_none() {
+            return false;
+        }
+        if draining {
+            self.draining.insert(name.to_string());
+        } else {
+            self.draining.remove(name);
+        }
+        true
+    }
+
+    /// Whether a route has been taken out of rotation
+    pub fn is_draining(&self, name: &str) -> bool {
+        self.draining.contains(name)
+    }
+
+    /// Call outcomes per route, by server name
+    pub fn upstream_stats(&self) -> &UpstreamStats {
+        &self.stats
+    }
+
+    /// Restart a route's upstream in place (stdio routes only)
+    pub async fn restart_route(&self, name: &str) -> Result<(), RouterError> {
+        let route = self
+            .find_route_by_name(name)
+            .ok_or_else(|| RouterError::NoRoute(name.to_string()))?;
+        route.transport.restart().await.map_err(RouterError::from)
+    }
+}
+
+// ============================================================================
+// Namespace Aggregation
+// ============================================================================
+
+/// Build the namespaced tool name exposed to clients in aggregate mode
+pub fn namespaced_tool_name(server: &str, tool: &str) -> String {
+    format!("{}{}{}", server, NAMESPACE_SEPARATOR, tool)
+}
+
+impl ServerRouter {
+    /// Resolve a namespaced tool name to its route and the upstream tool name
+    ///
+    /// `github.create_issue` resolves to the `github` route and `create_issue`.
+    pub fn resolve_namespaced_tool<'a>(&self, tool: &'a str) -> Option<(&ServerRoute, &'a str)> {
+        let (server, upstream_tool) = tool.split_once(NAMESPACE_SEPARATOR)?;
+        if upstream_tool.is_empty() {
+            return None;
+        }
+        self.find_route_by_name(server)
+            .map(|route| (route, upstream_tool))
+    }
+
+    /// Send a request to a single route and wait for its response
+    ///
+    /// Waits for a slot in the route's fair queue, on behalf of the identity
+    /// of the client request being forwarded.
+    async fn exchange(
+        &self,
+        route: &ServerRoute,
+        message: Message,
+    ) -> Result<Message, RouterError> {
+        if self.is_draining(&route.config.name) {
+            return Err(RouterError::Draining(route.config.name.clone()));
+        }
+        let _slot = match self.queues.get(&route.config.name) {
+            Some(queue) => {
+                let identity = forwarded_identity_id().unwrap_or_default();
+                Some(
+                    queue
+                        .acquire(&identity)
+                        .await
+                        .map_err(RouterError::QueueFull)?,
+                )
+            }
+            None => None,
+        };
+        let (transport, target) = with_forwarded_identity(|identity| route.select(identity))
+            .unwrap_or((&route.transport, RouteTarget::Primary));
+        let start = Instant::now();
+        let result = crate::transport::exchange_raw(
+            transport.as_ref(),
+            message,
+            &route.config.retry,
+            &route.config.timeouts,
+        )
+        .await;
+        record_route_call(
+            &route.config.name,
+            target.as_str(),
+            start.elapsed(),
+            route_call_result(&result),
+        );
+        self.stats.record(&route.config.name, &result);
+        let response = result?.into_message()?;
+        Ok(enforce_response_limit(
+            response,
+            &route.config.response_limits,
+            &route.config.name,
+        ))
+    }
+
+    /// Send the same request to every route concurrently
+    ///
+    /// Returns one result per route, in route order. Draining routes are skipped.
+    async fn fan_out(
+        &self,
+        message: &Message,
+    ) -> Vec<(&ServerRoute, Result<Message, RouterError>)> {
+        let exchanges = self
+            .routes
+            .iter()
+            .filter(|route| !self.is_draining(&route.config.name))
+            .map(|route| async move { (route, self.exchange(route, message.clone()).await) });
+        futures::future::join_all(exchanges).await
+    }
+
+    /// Forward a notification to every route
+    ///
+    /// Notifications have no response, so delivery failures are only logged.
+    pub async fn broadcast_notification(&self, message: &Message) {
+        for route in &self.routes {
+            if let Err(e) = route.transport.send(message.clone()).await {
+                tracing::debug!(
+                    server = %route.config.name,
+                    error = %e,
+                    "Failed to forward notification to upstream"
+                );
+            }
+            route.notify_canary(message).await;
+        }
+    }
+
+    /// Initialize every upstream and answer as a single aggregated server
+    ///
+    /// The protocol version is taken from the first upstream that responds.
+    /// Fails only if no upstream could be initialized.
+    pub async fn aggregate_initialize(&self, request: &Message) -> Result<Message, RouterError> {
+        let id = request.id.clone().unwrap_or(Value::Null);
+        let mut protocol_version: Option<Value> = None;
+
+        for (route, result) in self.fan_out(request).await {
+            match result {
+                Ok(response) => {
+                    if let Some(error) = response.error {
+                        tracing::warn!(
+                            server = %route.config.name,
+                            error = %error,
+                            "Upstream rejected initialize"
+                        );
+                        continue;
+                    }
+                    if protocol_version.is_none() {
+                        protocol_version = response
+                            .result
+                            .as_ref()
+                            .and_then(|r| r.get("protocolVersion"))
+                            .cloned();
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        server = %route.config.name,
+                        error = %e,
+                        "Failed to initialize upstream"
+                    );
+                }
+            }
+        }
+
+        let protocol_version = protocol_version
+            .ok_or_else(|| RouterError::AllUpstreamsFailed("initialize".to_string()))?;
+
+        Ok(Message::response(
+            id,
+            serde_json::json!({
+                "protocolVersion": protocol_version,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "mcp-guard",
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            }),
+        ))
+    }
+
+    /// Merge `tools/list` from every upstream into one namespaced list
+    ///
+    /// Upstreams that fail or return an error are skipped so that one broken
+    /// server does not hide the tools of the others. Pagination cursors from
+    /// upstreams are not followed.
+    pub async fn aggregate_tools_list(&self, request: &Message) -> Message {
+        let id = request.id.clone().unwrap_or(Value::Null);
+        let mut tools: Vec<Value> = Vec::new();
+
+        for (route, result) in self.fan_out(request).await {
+            let mut response = match result {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::warn!(
+                        server = %route.config.name,
+                        error = %e,
+                        "Failed to list upstream tools"
+                    );
+                    continue;
+                }
+            };
+            ToolSurface::route(&route.config).rewrite_response(Some("tools/list"), &mut response);
+
+            let Some(upstream_tools) = response
+                .result
+                .and_then(|mut r| r.get_mut("tools").map(Value::take))
+            else {
+                tracing::warn!(
+                    server = %route.config.name,
+                    "Upstream tools/list response has no tools"
+                );
+                continue;
+            };
+
+            if let Value::Array(upstream_tools) = upstream_tools {
+                for mut tool in upstream_tools {
+                    let Some(name) = tool.get("name").and_then(|n| n.as_str()) else {
+                        continue;
+                    };
+                    let name = namespaced_tool_name(&route.config.name, name);
+                    tool["name"] = Value::String(name);
+                    tools.push(tool);
+                }
+            }
+        }
+
+        Message::response(id, serde_json::json!({ "tools": tools }))
+    }
+
+    /// Dispatch a namespaced `tools/call` to the upstream that owns the tool
+    ///
+    /// The namespace is stripped before forwarding so the upstream sees its own
+    /// tool name.
+    pub async fn dispatch_tool_call(&self, mut message: Message) -> Result<Message, RouterError> {
+        let tool = crate::authz::extract_tool_name(&message)
+            .ok_or_else(|| RouterError::UnknownNamespace(String::new()))?
+            .to_string();
+
+        let (route, upstream_tool) = self
+            .resolve_namespaced_tool(&tool)
+            .ok_or_else(|| RouterError::UnknownNamespace(tool.clone()))?;
+
+        if let Some(params) = message.params.as_mut() {
+            params["name"] = Value::String(upstream_tool.to_string());
+        }
+
+        // Tools outside the route's published catalog do not exist
+        let surface = ToolSurface::route(&route.config);
+        if !surface.is_published(&message) {
+            return Err(RouterError::UnknownNamespace(tool));
+        }
+        surface.transform_request(&mut message);
+
+        tracing::debug!(
+            server = %route.config.name,
+            tool = %upstream_tool,
+            "Dispatching aggregated tool call"
+        );
+
+        self.exchange(route, message).await
+    }
+}
+
+impl ServerRoute {
+    /// Forward a notification to the canary too, so that it sees the same
+    /// session lifecycle as the primary
+    pub async fn notify_canary(&self, message: &Message) {
+        if let Some(canary) = &self.canary {
+            if let Err(e) = canary.transport.send(message.clone()).await {
+                tracing::debug!(
+                    server = %self.config.name,
+                    error = %e,
+                    "Failed to forward notification to canary"
+                );
+            }
+        }
+    }
+}
+
+/// Result label for a forwarded call, as recorded by [`record_route_call`]
+pub fn route_call_result(result: &Result<RawMessage, TransportError>) -> &'static str {
+    match result {
+        Ok(response) if response.error.is_some() => "jsonrpc_error",
+        Ok(_) => "success",
+        Err(_) => "error",
+    }
 }
 
 /// Route matcher for extracting server name from path
@@ -289,8 +887,26 @@ mod tests {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: Some("http://localhost:8080".to_string()),
             strip_prefix: strip,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         }
     }
 
@@ -410,8 +1026,26 @@ mod tests {
             transport: TransportType::Stdio,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: None,
             strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         };
         assert!(config.validate().is_err());
 
@@ -427,8 +1061,26 @@ mod tests {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: None,
             strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         };
         assert!(config.validate().is_err());
     }
@@ -441,8 +1093,26 @@ mod tests {
             transport: TransportType::Sse,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: None,
             strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         };
         assert!(config.validate().is_err());
     }
@@ -460,8 +1130,26 @@ mod tests {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: Some("not-a-url".to_string()),
             strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         };
 
         let result = tokio::runtime::Runtime::new()
@@ -479,6 +1167,9 @@ mod tests {
         let router = ServerRouter {
             routes: vec![],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
 
         let test_message = Message::request(1, "ping", None);
@@ -493,6 +1184,9 @@ mod tests {
         let router = ServerRouter {
             routes: vec![],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
 
         let result = tokio::runtime::Runtime::new()
@@ -511,8 +1205,12 @@ mod tests {
             routes: vec![ServerRoute {
                 config: config.clone(),
                 transport: Arc::new(MockTransport::new()),
+                canary: None,
             }],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
 
         // Should strip prefix
@@ -527,8 +1225,12 @@ mod tests {
             routes: vec![ServerRoute {
                 config: config_no_strip,
                 transport: Arc::new(MockTransport::new()),
+                canary: None,
             }],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
         assert_eq!(
             router_no_strip.transform_path("/no-strip/foo"),
@@ -544,13 +1246,18 @@ mod tests {
                 ServerRoute {
                     config: create_test_route("s1", "/s1", false),
                     transport: Arc::new(MockTransport::new()),
+                    canary: None,
                 },
                 ServerRoute {
                     config: create_test_route("s2", "/s2", false),
                     transport: Arc::new(MockTransport::new()),
+                    canary: None,
                 },
             ],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
 
         assert_eq!(router.route_count(), 2);
@@ -570,14 +1277,19 @@ mod tests {
         let default_route = ServerRoute {
             config: default_config,
             transport: Arc::new(MockTransport::new()),
+            canary: None,
         };
 
         let router = ServerRouter {
             routes: vec![ServerRoute {
                 config: create_test_route("api", "/api", false),
                 transport: Arc::new(MockTransport::new()),
+                canary: None,
             }],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         }
         .with_default(default_route);
 
@@ -603,8 +1315,12 @@ mod tests {
             routes: vec![ServerRoute {
                 config: create_test_route("github", "/github", false),
                 transport: Arc::new(MockTransport::new()),
+                canary: None,
             }],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
 
         assert_eq!(router.get_route_name("/github/repos"), Some("github"));
@@ -619,8 +1335,12 @@ mod tests {
             routes: vec![ServerRoute {
                 config: create_test_route("test", "/test", false),
                 transport: Arc::new(MockTransport::new()),
+                canary: None,
             }],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
 
         // Should return transport for matching route
@@ -637,8 +1357,12 @@ mod tests {
             routes: vec![ServerRoute {
                 config: create_test_route("s1", "/s1", false),
                 transport: Arc::new(MockTransport::new()),
+                canary: None,
             }],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
 
         // Format should include route count and has_default
@@ -652,6 +1376,9 @@ mod tests {
         let router = ServerRouter {
             routes: vec![],
             default_route: None,
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
 
         assert!(!router.has_routes());
@@ -667,15 +1394,349 @@ mod tests {
         let default_route = ServerRoute {
             config: default_config,
             transport: Arc::new(MockTransport::new()),
+            canary: None,
         };
 
         let router = ServerRouter {
             routes: vec![],
             default_route: Some(default_route),
+            queues: Default::default(),
+            draining: Default::default(),
+            stats: Default::default(),
         };
 
         // Empty routes but has default means has_routes is true
         assert!(router.has_routes());
         assert_eq!(router.route_count(), 0); // route_count only counts routes, not default
     }
+
+    // ------------------------------------------------------------------------
+    // Namespace Aggregation Tests
+    // ------------------------------------------------------------------------
+
+    fn create_aggregated_router(
+        names: &[&str],
+    ) -> (ServerRouter, Vec<Arc<crate::mocks::MockTransport>>) {
+        let mut mocks = Vec::new();
+        let routes = names
+            .iter()
+            .map(|name| {
+                let mock = Arc::new(crate::mocks::MockTransport::new());
+                mocks.push(mock.clone());
+                ServerRoute {
+                    config: create_test_route(name, "", false),
+                    transport: mock,
+                    canary: None,
+                }
+            })
+            .collect();
+        (ServerRouter::from_routes(routes), mocks)
+    }
+
+    fn tools_response(names: &[&str]) -> Message {
+        let tools: Vec<Value> = names
+            .iter()
+            .map(|n| serde_json::json!({ "name": n, "inputSchema": {} }))
+            .collect();
+        Message::response(serde_json::json!(1), serde_json::json!({ "tools": tools }))
+    }
+
+    #[test]
+    fn test_resolve_namespaced_tool() {
+        let (router, _) = create_aggregated_router(&["github", "fs"]);
+
+        let (route, tool) = router
+            .resolve_namespaced_tool("github.create_issue")
+            .unwrap();
+        assert_eq!(route.config.name, "github");
+        assert_eq!(tool, "create_issue");
+
+        // Only the first separator marks the namespace
+        let (route, tool) = router.resolve_namespaced_tool("fs.read.file").unwrap();
+        assert_eq!(route.config.name, "fs");
+        assert_eq!(tool, "read.file");
+
+        assert!(router.resolve_namespaced_tool("create_issue").is_none());
+        assert!(router.resolve_namespaced_tool("unknown.tool").is_none());
+        assert!(router.resolve_namespaced_tool("github.").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_tools_list_merges_and_prefixes() {
+        let (router, mocks) = create_aggregated_router(&["github", "fs"]);
+        mocks[0].push_response(tools_response(&["create_issue"]));
+        mocks[1].push_response(tools_response(&["read_file", "write_file"]));
+
+        let request = Message::request(7, "tools/list", None);
+        let response = router.aggregate_tools_list(&request).await;
+
+        assert_eq!(response.id, Some(serde_json::json!(7)));
+        let names: Vec<&str> = response.result.as_ref().unwrap()["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["github.create_issue", "fs.read_file", "fs.write_file"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_tools_list_skips_failed_upstream() {
+        let (router, mocks) = create_aggregated_router(&["github", "fs"]);
+        mocks[0].push_error(TransportError::Timeout);
+        mocks[1].push_response(tools_response(&["read_file"]));
+
+        let request = Message::request(1, "tools/list", None);
+        let response = router.aggregate_tools_list(&request).await;
+
+        let tools = response.result.unwrap()["tools"]
+            .as_array()
+            .unwrap()
+            .clone();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0]["name"], "fs.read_file");
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_tool_call_strips_namespace() {
+        let (router, mocks) = create_aggregated_router(&["github", "fs"]);
+        mocks[1].push_response(Message::response(
+            serde_json::json!(3),
+            serde_json::json!({ "content": [] }),
+        ));
+
+        let request = Message::request(
+            3,
+            "tools/call",
+            Some(serde_json::json!({ "name": "fs.read_file", "arguments": { "path": "/tmp" } })),
+        );
+        let response = router.dispatch_tool_call(request).await.unwrap();
+        assert!(response.result.is_some());
+
+        assert_eq!(mocks[0].sent_count(), 0);
+        let sent = mocks[1].take_sent_messages();
+        assert_eq!(sent.len(), 1);
+        let params = sent[0].params.as_ref().unwrap();
+        assert_eq!(params["name"], "read_file");
+        assert_eq!(params["arguments"]["path"], "/tmp");
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_tool_call_unknown_namespace() {
+        let (router, _) = create_aggregated_router(&["github"]);
+
+        let request = Message::request(
+            1,
+            "tools/call",
+            Some(serde_json::json!({ "name": "slack.post_message" })),
+        );
+        let result = router.dispatch_tool_call(request).await;
+        assert!(
+            matches!(result, Err(RouterError::UnknownNamespace(t)) if t == "slack.post_message")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_draining_route_refuses_requests() {
+        let (router, mocks) = create_aggregated_router(&["github", "fs"]);
+        assert!(router.set_draining("github", true));
+        assert!(!router.set_draining("slack", true));
+        assert!(router.is_draining("github"));
+
+        let request = Message::request(
+            1,
+            "tools/call",
+            Some(serde_json::json!({ "name": "github.create_issue" })),
+        );
+        let result = router.dispatch_tool_call(request).await;
+        assert!(matches!(result, Err(RouterError::Draining(s)) if s == "github"));
+
+        // Fan-out skips the draining route entirely
+        mocks[1].push_response(tools_response(&["read_file"]));
+        let response = router
+            .aggregate_tools_list(&Message::request(2, "tools/list", None))
+            .await;
+        assert_eq!(
+            response.result.unwrap()["tools"].as_array().unwrap().len(),
+            1
+        );
+        assert_eq!(mocks[0].sent_count(), 0);
+
+        assert!(router.set_draining("github", false));
+        assert!(!router.is_draining("github"));
+    }
+
+    fn test_identity(id: &str) -> Identity {
+        Identity {
+            id: id.to_string(),
+            name: None,
+            allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims: HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
+        }
+    }
+
+    fn test_canary(config: CanaryConfig) -> Canary {
+        Canary::new(config, Arc::new(crate::mocks::MockTransport::new()))
+    }
+
+    #[test]
+    fn test_canary_percent_spreads_calls_evenly() {
+        let canary = test_canary(CanaryConfig {
+            percent: 5.0,
+            ..Default::default()
+        });
+        let identity = test_identity("user");
+
+        let picks: Vec<bool> = (0..100).map(|_| canary.selects(&identity)).collect();
+        assert_eq!(picks.iter().filter(|p| **p).count(), 5);
+        assert_eq!(picks[..20].iter().filter(|p| **p).count(), 1);
+
+        let never = test_canary(CanaryConfig::default());
+        assert!(!(0..100).any(|_| never.selects(&identity)));
+        let always = test_canary(CanaryConfig {
+            percent: 100.0,
+            ..Default::default()
+        });
+        assert!((0..100).all(|_| always.selects(&identity)));
+    }
+
+    #[test]
+    fn test_canary_sticky_percent_keeps_identity_on_one_target() {
+        let canary = test_canary(CanaryConfig {
+            percent: 50.0,
+            sticky: true,
+            ..Default::default()
+        });
+
+        let picks: Vec<bool> = (0..200)
+            .map(|i| canary.selects(&test_identity(&format!("user-{}", i))))
+            .collect();
+        let to_canary = picks.iter().filter(|p| **p).count();
+        assert!((50..150).contains(&to_canary), "{} of 200", to_canary);
+
+        for (i, picked) in picks.iter().enumerate().take(20) {
+            let identity = test_identity(&format!("user-{}", i));
+            assert_eq!(canary.selects(&identity), *picked);
+        }
+    }
+
+    #[test]
+    fn test_canary_selects_identities_and_claims() {
+        let canary = test_canary(CanaryConfig {
+            identities: vec!["qa-bot".to_string()],
+            claims: HashMap::from([("groups".to_string(), "beta".to_string())]),
+            ..Default::default()
+        });
+
+        assert!(canary.selects(&test_identity("qa-bot")));
+        assert!(!canary.selects(&test_identity("user")));
+
+        let mut beta = test_identity("user");
+        beta.claims
+            .insert("groups".to_string(), serde_json::json!(["staff", "beta"]));
+        assert!(canary.selects(&beta));
+
+        beta.claims
+            .insert("groups".to_string(), serde_json::json!("staff"));
+        assert!(!canary.selects(&beta));
+    }
+
+    #[tokio::test]
+    async fn test_canary_receives_selected_calls() {
+        let primary = Arc::new(crate::mocks::MockTransport::new());
+        let canary = Arc::new(crate::mocks::MockTransport::new());
+        let router = ServerRouter::from_routes(vec![ServerRoute {
+            config: create_test_route("github", "", false),
+            transport: primary.clone(),
+            canary: Some(Canary::new(
+                CanaryConfig {
+                    identities: vec!["qa-bot".to_string()],
+                    ..Default::default()
+                },
+                canary.clone(),
+            )),
+        }]);
+        let call = || {
+            Message::request(
+                1,
+                "tools/call",
+                Some(serde_json::json!({ "name": "github.create_issue" })),
+            )
+        };
+        let context = |id: &str| crate::transport::ForwardContext {
+            client_headers: Default::default(),
+            identity: test_identity(id),
+        };
+
+        canary.push_response(Message::response(
+            serde_json::json!(1),
+            serde_json::json!({}),
+        ));
+        crate::transport::with_forward_context(
+            context("qa-bot"),
+            router.dispatch_tool_call(call()),
+        )
+        .await
+        .unwrap();
+        primary.push_response(Message::response(
+            serde_json::json!(1),
+            serde_json::json!({}),
+        ));
+        crate::transport::with_forward_context(context("user"), router.dispatch_tool_call(call()))
+            .await
+            .unwrap();
+        assert_eq!(canary.sent_count(), 1);
+        assert_eq!(primary.sent_count(), 1);
+
+        let (_, target) = router
+            .select_transport("/anything", &test_identity("qa-bot"))
+            .unwrap();
+        assert_eq!(target, RouteTarget::Canary);
+
+        // Notifications reach both targets
+        let mut notification = Message::request(0, "notifications/initialized", None);
+        notification.id = None;
+        router.broadcast_notification(&notification).await;
+        assert_eq!(canary.sent_count(), 2);
+        assert_eq!(primary.sent_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_initialize() {
+        let (router, mocks) = create_aggregated_router(&["github", "fs"]);
+        mocks[0].push_error(TransportError::Timeout);
+        mocks[1].push_response(Message::response(
+            serde_json::json!(1),
+            serde_json::json!({ "protocolVersion": "2024-11-05", "capabilities": {} }),
+        ));
+
+        let request = Message::request(1, "initialize", Some(serde_json::json!({})));
+        let response = router.aggregate_initialize(&request).await.unwrap();
+        let result = response.result.unwrap();
+        assert_eq!(result["protocolVersion"], "2024-11-05");
+        assert_eq!(result["serverInfo"]["name"], "mcp-guard");
+        assert!(result["capabilities"]["tools"].is_object());
+
+        // Both upstreams received the initialize request
+        assert_eq!(mocks[0].sent_count(), 1);
+        assert_eq!(mocks[1].sent_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_initialize_all_failed() {
+        let (router, mocks) = create_aggregated_router(&["github"]);
+        mocks[0].push_error(TransportError::Timeout);
+
+        let request = Message::request(1, "initialize", None);
+        let result = router.aggregate_initialize(&request).await;
+        assert!(matches!(result, Err(RouterError::AllUpstreamsFailed(_))));
+    }
 }
@@ -0,0 +1,160 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Live audit stream for admins (`GET /admin/audit/stream`)
+//!
+//! Streams redacted audit entries as Server-Sent Events while the
+//! connection is open, so incident responders can follow activity without
+//! shell access to the audit file. Entries can be filtered by event type and
+//! identity; nothing logged before the connection is replayed.
+
+use axum::extract::{Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use futures::stream;
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::{require_admin, AppError, AppState};
+use crate::audit::{AuditEntry, EventType};
+use crate::auth::Identity;
+
+/// Query parameters for `/admin/audit/stream`
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct AuditStreamParams {
+    /// Comma-separated event types, e.g. `auth_failure,authz_denied`
+    event_type: Option<String>,
+    /// Only entries for this identity
+    identity: Option<String>,
+}
+
+/// Which entries a stream subscriber receives
+#[derive(Debug, Default)]
+struct AuditStreamFilter {
+    /// Empty for all event types
+    event_types: Vec<EventType>,
+    identity: Option<String>,
+}
+
+impl AuditStreamFilter {
+    fn new(params: AuditStreamParams) -> Result<Self, String> {
+        let event_types = params
+            .event_type
+            .iter()
+            .flat_map(|types| types.split(','))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                serde_json::from_value(serde_json::Value::String(name.to_string()))
+                    .map_err(|_| format!("Unknown event type: {}", name))
+            })
+            .collect::<Result<Vec<EventType>, String>>()?;
+        Ok(Self {
+            event_types,
+            identity: params.identity,
+        })
+    }
+
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&entry.event_type))
+            && self.identity.as_ref().map_or(true, |identity| {
+                entry.identity_id.as_ref() == Some(identity)
+            })
+    }
+}
+
+/// Stream audit entries to an admin as they are logged
+///
+/// Each entry is an event with the entry's JSON as data. A subscriber that
+/// falls behind gets a `lagged` event with the number of skipped entries.
+pub(crate) async fn admin_audit_stream(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(identity): axum::Extension<Identity>,
+    Query(params): Query<AuditStreamParams>,
+) -> Result<Response, AppError> {
+    require_admin(&state, &identity)?;
+    let filter = AuditStreamFilter::new(params).map_err(AppError::bad_request)?;
+
+    let entries = state.audit_logger.subscribe();
+    tracing::info!(admin = %identity.id, "Audit stream opened");
+
+    let events = stream::unfold((entries, filter), |(mut entries, filter)| async move {
+        loop {
+            let event = match entries.recv().await {
+                Ok(entry) if filter.matches(&entry) => entry_event(&entry),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    Event::default().event("lagged").data(skipped.to_string())
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok::<_, Infallible>(event), (entries, filter)));
+        }
+    });
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+fn entry_event(entry: &AuditEntry) -> Event {
+    Event::default()
+        .event("audit")
+        .data(serde_json::to_string(entry).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(
+        event_type: Option<&str>,
+        identity: Option<&str>,
+    ) -> Result<AuditStreamFilter, String> {
+        AuditStreamFilter::new(AuditStreamParams {
+            event_type: event_type.map(String::from),
+            identity: identity.map(String::from),
+        })
+    }
+
+    #[test]
+    fn test_filter_by_event_type_and_identity() {
+        let denied = AuditEntry::new(EventType::AuthzDenied).with_identity("alice");
+        let call = AuditEntry::new(EventType::ToolCall).with_identity("bob");
+        let anonymous = AuditEntry::new(EventType::AuthFailure);
+
+        let all = filter(None, None).unwrap();
+        assert!(all.matches(&denied) && all.matches(&call) && all.matches(&anonymous));
+
+        let types = filter(Some("authz_denied, auth_failure"), None).unwrap();
+        assert!(types.matches(&denied));
+        assert!(!types.matches(&call));
+        assert!(types.matches(&anonymous));
+
+        let alice = filter(Some("authz_denied,tool_call"), Some("alice")).unwrap();
+        assert!(alice.matches(&denied));
+        assert!(!alice.matches(&call));
+        assert!(!alice.matches(&anonymous));
+    }
+
+    #[test]
+    fn test_filter_rejects_unknown_event_type() {
+        let err = filter(Some("tool_call,tool_calls"), None).unwrap_err();
+        assert!(err.contains("tool_calls"));
+    }
+}
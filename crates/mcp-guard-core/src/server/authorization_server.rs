@@ -0,0 +1,488 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Endpoints of the built-in authorization server
+//!
+//! `POST /oauth/token` logs users in with the `password` grant, exchanges an
+//! API key for tokens with the `client_credentials` grant (the key is the
+//! client secret, sent in the form or with HTTP Basic), and redeems refresh
+//! tokens. `GET /.well-known/oauth-authorization-server` describes the server
+//! as RFC 8414 metadata.
+//!
+//! Failures are reported with the RFC 6749 section 5.2 error codes. Password
+//! logins for a locked-out username or client IP get `429 Too Many Requests`.
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::{AppError, AppState, OAuthTokenResponse};
+use crate::auth::{AuthError, IssuedTokens, Login};
+
+/// Where RFC 8414 puts authorization server metadata
+pub(super) const METADATA_PATH: &str = "/.well-known/oauth-authorization-server";
+
+/// Form parameters of the token endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct TokenParams {
+    pub grant_type: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub refresh_token: Option<String>,
+}
+
+/// Authorization server metadata (RFC 8414)
+#[derive(Debug, Serialize)]
+pub struct AuthorizationServerMetadata {
+    pub issuer: String,
+    pub token_endpoint: String,
+    pub grant_types_supported: Vec<&'static str>,
+    pub token_endpoint_auth_methods_supported: Vec<&'static str>,
+    /// Always empty: there is no authorization endpoint
+    pub response_types_supported: Vec<&'static str>,
+}
+
+/// Token endpoint error (RFC 6749 section 5.2)
+#[derive(Debug)]
+struct TokenError {
+    code: &'static str,
+    description: &'static str,
+}
+
+impl TokenError {
+    fn new(code: &'static str, description: &'static str) -> Self {
+        Self { code, description }
+    }
+}
+
+impl IntoResponse for TokenError {
+    fn into_response(self) -> Response {
+        let status = if self.code == "invalid_client" {
+            StatusCode::UNAUTHORIZED
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        let mut response = (
+            status,
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(serde_json::json!({
+                "error": self.code,
+                "error_description": self.description,
+            })),
+        )
+            .into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            response.headers_mut().insert(
+                header::WWW_AUTHENTICATE,
+                header::HeaderValue::from_static("Basic realm=\"mcp-guard\""),
+            );
+        }
+        response
+    }
+}
+
+/// Client credentials from HTTP Basic (`client_secret_basic`)
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    use base64::Engine;
+
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let encoded = value
+        .strip_prefix("Basic ")
+        .or_else(|| value.strip_prefix("basic "))?;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (id, secret) = decoded.split_once(':')?;
+    // RFC 6749 section 2.3.1: both parts are form-urlencoded
+    Some((
+        urlencoding::decode(id).ok()?.into_owned(),
+        urlencoding::decode(secret).ok()?.into_owned(),
+    ))
+}
+
+/// Token endpoint
+pub(super) async fn token(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
+    headers: HeaderMap,
+    Form(params): Form<TokenParams>,
+) -> Result<Response, AppError> {
+    let server = state
+        .authorization_server
+        .as_ref()
+        .ok_or_else(|| AppError::not_found("Authorization server not enabled"))?;
+
+    let issued = match params.grant_type.as_str() {
+        "password" => {
+            let (Some(username), Some(password)) = (params.username, params.password) else {
+                return Ok(TokenError::new(
+                    "invalid_request",
+                    "username and password are required",
+                )
+                .into_response());
+            };
+            let client_ip = state.network_acl.client_ip(addr.ip(), &headers);
+            if let Some(lockout) = server.login_lockout(&username, client_ip) {
+                tracing::warn!(
+                    username = %username,
+                    client_ip = %client_ip,
+                    "Authorization server password login locked out"
+                );
+                return Err(AppError::rate_limited(Some(lockout.as_secs().max(1))));
+            }
+            // Argon2 verification is CPU-bound
+            let login = {
+                let server = server.clone();
+                let username = username.clone();
+                tokio::task::spawn_blocking(move || {
+                    server.password_login(&username, &password, client_ip)
+                })
+                .await
+                .map_err(|e| AppError::internal(format!("Password check failed: {}", e)))?
+            };
+            let identity = match login {
+                Ok(identity) => identity,
+                Err(_) => {
+                    tracing::warn!(
+                        username = %username,
+                        "Authorization server password login failed"
+                    );
+                    return Ok(
+                        TokenError::new("invalid_grant", "Invalid username or password")
+                            .into_response(),
+                    );
+                }
+            };
+            server.issue(&identity, Login::Password).await
+        }
+        "client_credentials" if server.supports_api_key_login() => {
+            let (client_id, client_secret) = match basic_credentials(&headers) {
+                Some((id, secret)) => (Some(id), Some(secret)),
+                None => (params.client_id, params.client_secret),
+            };
+            let Some(client_secret) = client_secret else {
+                return Ok(
+                    TokenError::new("invalid_client", "Client authentication required")
+                        .into_response(),
+                );
+            };
+            let identity = match server.api_key_login(&client_secret).await {
+                // The client ID is optional, but must name the key when given
+                Ok(identity)
+                    if client_id.is_none()
+                        || client_id.as_deref() == Some(identity.id.as_str()) =>
+                {
+                    identity
+                }
+                _ => {
+                    tracing::warn!("Authorization server API key login failed");
+                    return Ok(
+                        TokenError::new("invalid_client", "Invalid client credentials")
+                            .into_response(),
+                    );
+                }
+            };
+            server.issue(&identity, Login::ApiKey).await
+        }
+        "refresh_token" if server.supports_refresh() => {
+            let Some(refresh_token) = params.refresh_token else {
+                return Ok(
+                    TokenError::new("invalid_request", "refresh_token is required").into_response(),
+                );
+            };
+            match server.refresh(&refresh_token).await {
+                Err(AuthError::Internal(e)) => Err(AuthError::Internal(e)),
+                Err(_) => {
+                    return Ok(TokenError::new(
+                        "invalid_grant",
+                        "Refresh token is invalid, expired or already used",
+                    )
+                    .into_response())
+                }
+                ok => ok,
+            }
+        }
+        _ => {
+            return Ok(
+                TokenError::new("unsupported_grant_type", "Grant type not supported")
+                    .into_response(),
+            )
+        }
+    };
+
+    let IssuedTokens {
+        access_token,
+        expires_in,
+        refresh_token,
+    } = issued.map_err(|e| {
+        tracing::error!(error = %e, "Failed to issue tokens");
+        AppError::internal("Failed to issue tokens")
+    })?;
+
+    Ok((
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(OAuthTokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: Some(expires_in),
+            refresh_token,
+            scope: None,
+        }),
+    )
+        .into_response())
+}
+
+/// `GET /.well-known/oauth-authorization-server`
+pub(super) async fn metadata(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<AuthorizationServerMetadata>, AppError> {
+    let server = state
+        .authorization_server
+        .as_ref()
+        .ok_or_else(|| AppError::not_found("Authorization server not enabled"))?;
+
+    let mut grant_types_supported = vec!["password"];
+    if server.supports_api_key_login() {
+        grant_types_supported.push("client_credentials");
+    }
+    if server.supports_refresh() {
+        grant_types_supported.push("refresh_token");
+    }
+
+    Ok(Json(AuthorizationServerMetadata {
+        issuer: server.issuer().to_string(),
+        token_endpoint: format!("{}/oauth/token", server.issuer()),
+        grant_types_supported,
+        token_endpoint_auth_methods_supported: vec!["client_secret_basic", "client_secret_post"],
+        response_types_supported: Vec::new(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AuthProvider, AuthorizationServer};
+    use crate::config::{ApiKeyConfig, AuthorizationServerConfig, AuthorizationServerUser};
+    use crate::server::tests::create_test_state;
+    use base64::Engine;
+
+    fn state() -> Arc<AppState> {
+        let config = AuthorizationServerConfig {
+            issuer: "https://mcp.example.com/".to_string(),
+            signing_secret: "built-in-authorization-server-secret".to_string(),
+            audience: "mcp-guard".to_string(),
+            access_token_ttl_secs: 300,
+            refresh_token_ttl_secs: 3600,
+            users: vec![AuthorizationServerUser {
+                username: "alice".to_string(),
+                password_hash: crate::cli::hash_password("correct horse"),
+                allowed_tools: vec![],
+                allowed_resources: vec![],
+                allowed_prompts: vec![],
+                rate_limit: None,
+            }],
+            api_key_login: true,
+            max_failed_logins: 2,
+            lockout_secs: 60,
+        };
+        let api_key: ApiKeyConfig = serde_json::from_value(serde_json::json!({
+            "id": "ci",
+            "key_hash": crate::cli::hash_api_key("mcp_ci_key"),
+        }))
+        .unwrap();
+
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        state.authorization_server = Some(Arc::new(
+            AuthorizationServer::new(config, vec![api_key], None).unwrap(),
+        ));
+        Arc::new(state)
+    }
+
+    async fn request_token(
+        state: Arc<AppState>,
+        headers: HeaderMap,
+        params: TokenParams,
+    ) -> (StatusCode, serde_json::Value) {
+        let peer = std::net::SocketAddr::from(([203, 0, 113, 7], 40000));
+        let response = token(State(state), ConnectInfo(peer), headers, Form(params))
+            .await
+            .into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_password_grant() {
+        let state = state();
+        let (status, body) = request_token(
+            state.clone(),
+            HeaderMap::new(),
+            TokenParams {
+                grant_type: "password".to_string(),
+                username: Some("alice".to_string()),
+                password: Some("correct horse".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["token_type"], "Bearer");
+        assert_eq!(body["expires_in"], 300);
+
+        let server = state.authorization_server.as_ref().unwrap();
+        let identity = server
+            .authenticate(body["access_token"].as_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(identity.id, "alice");
+
+        let (status, body) = request_token(
+            state,
+            HeaderMap::new(),
+            TokenParams {
+                grant_type: "password".to_string(),
+                username: Some("alice".to_string()),
+                password: Some("wrong".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_grant");
+    }
+
+    #[tokio::test]
+    async fn test_password_grant_lockout() {
+        let state = state();
+        let login = |password: &str| TokenParams {
+            grant_type: "password".to_string(),
+            username: Some("alice".to_string()),
+            password: Some(password.to_string()),
+            ..Default::default()
+        };
+
+        for _ in 0..2 {
+            let (status, _) = request_token(state.clone(), HeaderMap::new(), login("wrong")).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+        }
+        let (status, _) =
+            request_token(state.clone(), HeaderMap::new(), login("correct horse")).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_grant() {
+        let state = state();
+
+        let mut headers = HeaderMap::new();
+        let basic = base64::engine::general_purpose::STANDARD.encode("ci:mcp_ci_key");
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Basic {}", basic).parse().unwrap(),
+        );
+        let params = TokenParams {
+            grant_type: "client_credentials".to_string(),
+            ..Default::default()
+        };
+        let (status, _) = request_token(state.clone(), headers, params).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The client ID must name the key
+        let (status, body) = request_token(
+            state,
+            HeaderMap::new(),
+            TokenParams {
+                grant_type: "client_credentials".to_string(),
+                client_id: Some("someone-else".to_string()),
+                client_secret: Some("mcp_ci_key".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "invalid_client");
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_grants() {
+        // Refresh tokens need a database
+        let (status, body) = request_token(
+            state(),
+            HeaderMap::new(),
+            TokenParams {
+                grant_type: "refresh_token".to_string(),
+                refresh_token: Some("anything".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "unsupported_grant_type");
+    }
+
+    #[tokio::test]
+    async fn test_metadata() {
+        let Json(document) = metadata(State(state())).await.unwrap();
+        assert_eq!(document.issuer, "https://mcp.example.com");
+        assert_eq!(
+            document.token_endpoint,
+            "https://mcp.example.com/oauth/token"
+        );
+        assert_eq!(
+            document.grant_types_supported,
+            vec!["password", "client_credentials"]
+        );
+
+        assert!(metadata(State(create_test_state())).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_issuer_matches_metadata() {
+        let state = state();
+        let (status, body) = request_token(
+            state.clone(),
+            HeaderMap::new(),
+            TokenParams {
+                grant_type: "password".to_string(),
+                username: Some("alice".to_string()),
+                password: Some("correct horse".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let server = state.authorization_server.as_ref().unwrap();
+        let identity = server
+            .authenticate(body["access_token"].as_str().unwrap())
+            .await
+            .unwrap();
+        let Json(document) = metadata(State(state)).await.unwrap();
+        assert_eq!(identity.claims["iss"], document.issuer.as_str());
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Request body size enforcement and streaming JSON extraction
+//!
+//! `server.max_request_size` is enforced in two places:
+//! - [`body_limit_middleware`] rejects requests whose `Content-Length` exceeds
+//!   the limit before any bytes are read, and caps chunked bodies for every route
+//! - [`StreamingJson`] parses MCP messages straight off the body stream, aborting
+//!   as soon as the limit is crossed instead of buffering the whole payload first
+//!
+//! Oversized requests always receive a JSON 413 response and are counted in
+//! `mcp_guard_oversized_requests_total`.
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{FromRequest, State},
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::TryStreamExt;
+use serde::de::DeserializeOwned;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio_util::io::{StreamReader, SyncIoBridge};
+
+use crate::observability::record_oversized_request;
+use crate::server::jsonrpc_errors::effective_status;
+use crate::server::{AppError, AppState};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Bodies at or below this size are collected into memory and parsed in one pass.
+/// Larger (or chunked, unknown-length) bodies are parsed incrementally on the
+/// blocking pool so a near-limit message never needs a second full-size copy.
+const STREAMING_PARSE_THRESHOLD: usize = 64 * 1024;
+
+// ============================================================================
+// Middleware
+// ============================================================================
+
+/// Enforce `server.max_request_size` on every route
+///
+/// Requests declaring a `Content-Length` over the limit are rejected up front.
+/// Bodies without a declared length are wrapped so reads fail once the limit
+/// is crossed; any plain-text 413 produced downstream is rewritten as JSON.
+pub async fn body_limit_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let limit = state.config.server.max_request_size;
+
+    if let Some(declared) = content_length(&request) {
+        if declared > limit as u64 {
+            record_oversized_request("content_length");
+            tracing::warn!(
+                declared_bytes = declared,
+                limit_bytes = limit,
+                "Rejected request exceeding max_request_size"
+            );
+            return AppError::payload_too_large(limit).into_response();
+        }
+    }
+
+    let (parts, body) = request.into_parts();
+    let body = Body::new(http_body_util::Limited::new(body, limit));
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    if effective_status(&response) != StatusCode::PAYLOAD_TOO_LARGE {
+        return response;
+    }
+
+    record_oversized_request("streaming");
+    // JSON-RPC errors (see `jsonrpc_errors`) are JSON as well
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if is_json {
+        response
+    } else {
+        AppError::payload_too_large(limit).into_response()
+    }
+}
+
+fn content_length<B>(request: &Request<B>) -> Option<u64> {
+    request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+
+// ============================================================================
+// Extractor
+// ============================================================================
+
+/// JSON extractor that honours `server.max_request_size`
+///
+/// Unlike `axum::Json`, which buffers up to axum's fixed 2MB default, this reads
+/// up to the configured limit and parses large bodies as they stream in.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamingJson<T>(pub T);
+
+#[async_trait]
+impl<T> FromRequest<Arc<AppState>> for StreamingJson<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request<Body>, state: &Arc<AppState>) -> Result<Self, AppError> {
+        let limit = state.config.server.max_request_size;
+        let declared = content_length(&req);
+
+        if declared.is_some_and(|len| len > limit as u64) {
+            return Err(AppError::payload_too_large(limit));
+        }
+
+        let body = req.into_body();
+        let value = match declared {
+            Some(len) if len as usize <= STREAMING_PARSE_THRESHOLD => {
+                parse_buffered(body, limit).await?
+            }
+            _ => parse_streaming(body, limit).await?,
+        };
+
+        Ok(StreamingJson(value))
+    }
+}
+
+/// Collect a small body and parse it in one pass
+async fn parse_buffered<T: DeserializeOwned>(body: Body, limit: usize) -> Result<T, AppError> {
+    let bytes = axum::body::to_bytes(body, limit).await.map_err(|e| {
+        if is_length_limit(&e) {
+            AppError::payload_too_large(limit)
+        } else {
+            AppError::bad_request(format!("Failed to read request body: {}", e))
+        }
+    })?;
+
+    serde_json::from_slice(&bytes)
+        .map_err(|e| AppError::bad_request(format!("Invalid JSON body: {}", e)))
+}
+
+/// Parse a body incrementally as frames arrive, stopping at `limit` bytes
+async fn parse_streaming<T>(body: Body, limit: usize) -> Result<T, AppError>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    // Set when the limit is crossed, either by our own count or by the
+    // `Limited` wrapper installed in `body_limit_middleware`
+    let exceeded = Arc::new(AtomicBool::new(false));
+    let read_exceeded = exceeded.clone();
+    let count_exceeded = exceeded.clone();
+    let mut read = 0usize;
+
+    let stream = body
+        .into_data_stream()
+        .map_err(move |e| {
+            if is_length_limit(&e) {
+                read_exceeded.store(true, Ordering::Relaxed);
+            }
+            std::io::Error::other(e)
+        })
+        .and_then(move |chunk| {
+            read += chunk.len();
+            let result = if read > limit {
+                count_exceeded.store(true, Ordering::Relaxed);
+                Err(std::io::Error::other(
+                    "request body exceeds max_request_size",
+                ))
+            } else {
+                Ok(chunk)
+            };
+            std::future::ready(result)
+        });
+    // serde_json reads byte-by-byte, so buffer to avoid a bridge round-trip per byte
+    let reader = std::io::BufReader::new(SyncIoBridge::new(StreamReader::new(stream)));
+
+    let parsed = tokio::task::spawn_blocking(move || serde_json::from_reader::<_, T>(reader))
+        .await
+        .map_err(|e| AppError::internal(format!("JSON parse task failed: {}", e)))?;
+
+    parsed.map_err(|e| {
+        if exceeded.load(Ordering::Relaxed) {
+            AppError::payload_too_large(limit)
+        } else {
+            AppError::bad_request(format!("Invalid JSON body: {}", e))
+        }
+    })
+}
+
+/// Whether a body read failed because a `Limited` wrapper hit its cap
+pub(super) fn is_length_limit(err: &axum::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(e) = source {
+        if e.is::<http_body_util::LengthLimitError>() {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::Message;
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    fn create_state(max_request_size: usize) -> Arc<AppState> {
+        let mut state = Arc::try_unwrap(crate::server::tests::create_test_state())
+            .ok()
+            .unwrap();
+        state.config.server.max_request_size = max_request_size;
+        Arc::new(state)
+    }
+
+    async fn echo(StreamingJson(message): StreamingJson<Message>) -> axum::Json<Message> {
+        axum::Json(message)
+    }
+
+    fn create_app(state: Arc<AppState>) -> Router {
+        Router::new()
+            .route("/mcp", post(echo))
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                body_limit_middleware,
+            ))
+            .with_state(state)
+    }
+
+    fn large_message(padding: usize) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {"name": "write", "arguments": {"data": "x".repeat(padding)}}
+        }))
+        .unwrap()
+    }
+
+    fn chunked_body(bytes: Vec<u8>) -> Body {
+        let chunks: Vec<Result<Vec<u8>, std::io::Error>> =
+            bytes.chunks(8 * 1024).map(|c| Ok(c.to_vec())).collect();
+        Body::from_stream(futures::stream::iter(chunks))
+    }
+
+    async fn response_json(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_declared_oversized_body_rejected_with_json() {
+        let app = create_app(create_state(1024));
+        let body = large_message(4096);
+
+        let request = Request::post("/mcp")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let json = response_json(response).await;
+        assert_eq!(json["error"], "Request body too large");
+        assert_eq!(json["limit"], 1024);
+        assert!(json["error_id"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_chunked_oversized_body_rejected_with_json() {
+        let app = create_app(create_state(16 * 1024));
+
+        let request = Request::post("/mcp")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(chunked_body(large_message(64 * 1024)))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let json = response_json(response).await;
+        assert_eq!(json["limit"], 16 * 1024);
+    }
+
+    #[tokio::test]
+    async fn test_large_body_under_limit_streams() {
+        // Above axum's 2MB default but within the configured limit
+        let app = create_app(create_state(4 * 1024 * 1024));
+
+        let request = Request::post("/mcp")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(chunked_body(large_message(3 * 1024 * 1024)))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["method"], "tools/call");
+        assert_eq!(
+            json["params"]["arguments"]["data"].as_str().unwrap().len(),
+            3 * 1024 * 1024
+        );
+    }
+
+    #[tokio::test]
+    async fn test_small_body_buffered() {
+        let app = create_app(create_state(1024 * 1024));
+        let body = large_message(16);
+
+        let request = Request::post("/mcp")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_json_returns_bad_request() {
+        let app = create_app(create_state(1024 * 1024));
+
+        // Buffered (declared length) and streamed (chunked) parse paths
+        let requests = [
+            Request::post("/mcp")
+                .header(header::CONTENT_LENGTH, 9)
+                .body(Body::from("{not json"))
+                .unwrap(),
+            Request::post("/mcp")
+                .body(chunked_body(b"{not json".to_vec()))
+                .unwrap(),
+        ];
+
+        for request in requests {
+            let response = app.clone().oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let json = response_json(response).await;
+            assert!(json["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid JSON body"));
+        }
+    }
+}
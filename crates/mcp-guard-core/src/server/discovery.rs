@@ -0,0 +1,458 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! `GET /mcp`: what a client needs to know before its first message
+//!
+//! The MCP protocol revisions the gateway speaks, whether answers can be
+//! streamed, and how to authenticate, so clients can configure themselves.
+//! Authentication is described as OAuth 2.0 Protected Resource Metadata
+//! (RFC 9728), which the MCP authorization spec has clients look up at
+//! `/.well-known/oauth-protected-resource`; the same document is served there.
+//! Neither endpoint requires credentials.
+//!
+//! 401s from the MCP endpoints carry a `WWW-Authenticate: Bearer` challenge
+//! with the metadata URL (RFC 9728 section 5.1), so a client that was turned
+//! away knows where to find the authorization server.
+//!
+//! A GET accepting `text/event-stream` asks for the Streamable HTTP
+//! transport's standalone event stream, which the gateway does not offer;
+//! as the transport requires, it is answered with `405 Method Not Allowed`.
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+use super::jsonrpc_errors::effective_status;
+use super::upstream_requests::accepts_event_stream;
+use super::version::{auth_providers, AuthProviderInfo};
+use super::{AppError, AppState};
+
+/// Where RFC 9728 puts protected resource metadata
+const METADATA_PATH: &str = "/.well-known/oauth-protected-resource";
+
+/// MCP protocol revisions the gateway understands, newest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Response of `GET /mcp`
+#[derive(Debug, Serialize)]
+pub struct ServerDiscovery {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub protocol_versions: &'static [&'static str],
+    /// `single`, `multi` or `aggregate`
+    pub mode: &'static str,
+    /// Server route behind this endpoint, in multi-server mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    pub transport: TransportCapabilities,
+    pub auth: AuthRequirements,
+}
+
+/// How messages are exchanged with the endpoint
+#[derive(Debug, Serialize)]
+pub struct TransportCapabilities {
+    /// Always `streamable-http`
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Whether a POST accepting `text/event-stream` may be answered with a
+    /// stream carrying upstream-initiated messages
+    pub streaming: bool,
+    /// Whether a GET can open a standalone event stream
+    pub standalone_stream: bool,
+    /// Largest accepted request body, in bytes
+    pub max_request_size: usize,
+}
+
+/// How to authenticate to the endpoint
+#[derive(Debug, Serialize)]
+pub struct AuthRequirements {
+    /// Every MCP message must carry credentials
+    pub required: bool,
+    pub providers: Vec<AuthProviderInfo>,
+    pub protected_resource: ProtectedResourceMetadata,
+}
+
+/// OAuth 2.0 Protected Resource Metadata (RFC 9728)
+#[derive(Debug, Serialize)]
+pub struct ProtectedResourceMetadata {
+    /// URL of the MCP endpoint
+    pub resource: String,
+    /// JWT issuer, OAuth provider and built-in authorization server, if configured
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authorization_servers: Vec<String>,
+    pub bearer_methods_supported: Vec<&'static str>,
+    /// Scopes mapped to tools
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scopes_supported: Vec<String>,
+    pub resource_name: &'static str,
+}
+
+impl ServerDiscovery {
+    /// Describe the MCP endpoint at `resource`, serving `route` in
+    /// multi-server mode
+    pub fn new(state: &AppState, resource: String, route: Option<String>) -> Self {
+        let mode = if state.router.is_none() {
+            "single"
+        } else if state.config.is_aggregated() {
+            "aggregate"
+        } else {
+            "multi"
+        };
+        Self {
+            name: "mcp-guard",
+            version: env!("CARGO_PKG_VERSION"),
+            protocol_versions: SUPPORTED_PROTOCOL_VERSIONS,
+            mode,
+            route,
+            transport: TransportCapabilities {
+                kind: "streamable-http",
+                streaming: true,
+                standalone_stream: false,
+                max_request_size: state.config.server.max_request_size,
+            },
+            auth: AuthRequirements {
+                required: true,
+                providers: auth_providers(&state.config.auth),
+                protected_resource: ProtectedResourceMetadata::new(state, resource),
+            },
+        }
+    }
+}
+
+impl ProtectedResourceMetadata {
+    fn new(state: &AppState, resource: String) -> Self {
+        let auth = &state.config.auth;
+
+        let mut authorization_servers = Vec::new();
+        for jwt in &auth.jwt {
+            if !jwt.issuer.is_empty() && !authorization_servers.contains(&jwt.issuer) {
+                authorization_servers.push(jwt.issuer.clone());
+            }
+        }
+        if let Some(issuer) = state.oauth_provider.as_ref().and_then(|p| p.issuer()) {
+            if !authorization_servers.contains(&issuer) {
+                authorization_servers.push(issuer);
+            }
+        }
+        if let Some(ref server) = state.authorization_server {
+            let issuer = server.issuer().to_string();
+            if !authorization_servers.contains(&issuer) {
+                authorization_servers.push(issuer);
+            }
+        }
+
+        let mut scopes_supported: Vec<String> = auth
+            .jwt
+            .iter()
+            .flat_map(|jwt| jwt.scope_tool_mapping.keys())
+            .chain(
+                auth.oauth
+                    .iter()
+                    .flat_map(|oauth| oauth.scope_tool_mapping.keys().chain(oauth.scopes.iter())),
+            )
+            .cloned()
+            .collect();
+        scopes_supported.sort();
+        scopes_supported.dedup();
+
+        Self {
+            resource,
+            authorization_servers,
+            bearer_methods_supported: vec!["header"],
+            scopes_supported,
+            resource_name: "MCP Guard",
+        }
+    }
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// `GET /mcp` in single-server and aggregate mode
+pub(super) async fn mcp_discovery(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    if accepts_event_stream(&headers) {
+        return no_standalone_stream();
+    }
+    let resource = format!("{}/mcp", base_url(&state, &headers));
+    Json(ServerDiscovery::new(&state, resource, None)).into_response()
+}
+
+/// `GET /mcp/:server_name` in multi-server mode
+pub(super) async fn routed_mcp_discovery(
+    State(state): State<Arc<AppState>>,
+    Path(server_name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if !is_route(&state, &server_name) {
+        return AppError::not_found(format!("Unknown server route: {}", server_name))
+            .into_response();
+    }
+    if accepts_event_stream(&headers) {
+        return no_standalone_stream();
+    }
+    let resource = format!("{}/mcp/{}", base_url(&state, &headers), server_name);
+    Json(ServerDiscovery::new(&state, resource, Some(server_name))).into_response()
+}
+
+/// `GET /.well-known/oauth-protected-resource[/<path>]`
+///
+/// RFC 9728 places the metadata of `https://host/mcp` at
+/// `/.well-known/oauth-protected-resource/mcp`; the bare path describes the
+/// `/mcp` endpoint.
+pub(super) async fn protected_resource_metadata(
+    State(state): State<Arc<AppState>>,
+    path: Option<Path<String>>,
+    headers: HeaderMap,
+) -> Response {
+    let path = path.map_or_else(|| "mcp".to_string(), |Path(path)| path);
+    let path = path.trim_matches('/');
+    let known = match path.strip_prefix("mcp") {
+        Some("") => state.router.is_none() || state.config.is_aggregated(),
+        Some(route) => route
+            .strip_prefix('/')
+            .is_some_and(|route| !state.config.is_aggregated() && is_route(&state, route)),
+        None => false,
+    };
+    if !known {
+        return AppError::not_found(format!("No MCP endpoint at /{}", path)).into_response();
+    }
+    let resource = format!("{}/{}", base_url(&state, &headers), path);
+    Json(ProtectedResourceMetadata::new(&state, resource)).into_response()
+}
+
+/// Challenge 401s from the MCP endpoints with the resource metadata URL
+///
+/// `error="invalid_token"` is added when credentials were sent (RFC 6750
+/// section 3.1). Runs inside the JSON-RPC error rewriting, which keeps the
+/// header.
+pub(super) async fn www_authenticate_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let metadata_url = format!(
+        "{}{}{}",
+        base_url(&state, request.headers()),
+        METADATA_PATH,
+        request.uri().path()
+    );
+    let sent_credentials = request.headers().contains_key(header::AUTHORIZATION);
+
+    let mut response = next.run(request).await;
+    if effective_status(&response) != StatusCode::UNAUTHORIZED {
+        return response;
+    }
+
+    let challenge = if sent_credentials {
+        format!(
+            "Bearer error=\"invalid_token\", resource_metadata=\"{}\"",
+            metadata_url
+        )
+    } else {
+        format!("Bearer resource_metadata=\"{}\"", metadata_url)
+    };
+    if let Ok(value) = HeaderValue::from_str(&challenge) {
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, value);
+    }
+    response
+}
+
+fn is_route(state: &AppState, name: &str) -> bool {
+    state
+        .router
+        .as_ref()
+        .is_some_and(|router| router.find_route_by_name(name).is_some())
+}
+
+/// Same answer axum gives for a method with no handler
+fn no_standalone_stream() -> Response {
+    (
+        StatusCode::METHOD_NOT_ALLOWED,
+        [(header::ALLOW, "GET, POST")],
+    )
+        .into_response()
+}
+
+/// Scheme and authority clients reach the gateway at
+///
+/// Taken from the `Host` header, as the bind address is rarely what clients
+/// use; the scheme is `https` when the gateway terminates TLS or a proxy
+/// says it did.
+fn base_url(state: &AppState, headers: &HeaderMap) -> String {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let scheme = if state.config.server.tls.is_some() {
+        "https"
+    } else {
+        match header("x-forwarded-proto") {
+            Some("https") => "https",
+            _ => "http",
+        }
+    };
+    let host = header(header::HOST.as_str())
+        .filter(|host| !host.is_empty() && !host.contains(['/', '@', ' ', '"', '\\']))
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}:{}", state.config.server.host, state.config.server.port));
+    format!("{}://{}", scheme, host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{JwtConfig, JwtMode};
+    use crate::server::tests::create_test_state;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_discovery_single_server() {
+        let state = create_test_state();
+        let info = serde_json::to_value(ServerDiscovery::new(
+            &state,
+            "https://gw.example.com/mcp".to_string(),
+            None,
+        ))
+        .unwrap();
+
+        assert_eq!(info["protocol_versions"][0], SUPPORTED_PROTOCOL_VERSIONS[0]);
+        assert_eq!(info["mode"], "single");
+        assert!(info.get("route").is_none());
+        assert_eq!(info["transport"]["type"], "streamable-http");
+        assert_eq!(info["transport"]["streaming"], true);
+        assert_eq!(info["transport"]["standalone_stream"], false);
+        assert_eq!(info["auth"]["required"], true);
+        assert_eq!(
+            info["auth"]["protected_resource"]["resource"],
+            "https://gw.example.com/mcp"
+        );
+        assert_eq!(
+            info["auth"]["protected_resource"]["bearer_methods_supported"],
+            serde_json::json!(["header"])
+        );
+    }
+
+    #[test]
+    fn test_protected_resource_lists_jwt_issuer_and_scopes() {
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        state.config.auth.jwt = vec![JwtConfig {
+            mode: JwtMode::Simple {
+                secret: "a-test-secret-that-is-at-least-32-characters".to_string(),
+            },
+            issuer: "https://issuer.example.com".to_string(),
+            audience: "mcp-guard".to_string(),
+            user_id_claim: "sub".to_string(),
+            scopes_claim: "scope".to_string(),
+            scope_tool_mapping: HashMap::from([
+                ("write:files".to_string(), vec!["write_file".to_string()]),
+                ("read:files".to_string(), vec!["read_file".to_string()]),
+            ]),
+            leeway_secs: 0,
+            revocation: None,
+            identity: None,
+        }];
+
+        let metadata =
+            ProtectedResourceMetadata::new(&state, "http://localhost:3000/mcp".to_string());
+        assert_eq!(
+            metadata.authorization_servers,
+            vec!["https://issuer.example.com"]
+        );
+        assert_eq!(metadata.scopes_supported, vec!["read:files", "write:files"]);
+    }
+
+    #[test]
+    fn test_base_url_from_headers() {
+        let state = create_test_state();
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            base_url(&state, &headers),
+            format!(
+                "http://{}:{}",
+                state.config.server.host, state.config.server.port
+            )
+        );
+
+        headers.insert(header::HOST, HeaderValue::from_static("gw.example.com"));
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("https"));
+        assert_eq!(base_url(&state, &headers), "https://gw.example.com");
+
+        headers.insert(header::HOST, HeaderValue::from_static("evil.com/path"));
+        assert!(!base_url(&state, &headers).contains("evil.com"));
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_responses_are_challenged() {
+        use axum::{middleware, routing::post, Router};
+        use tower::ServiceExt;
+
+        async fn reject() -> AppError {
+            AppError::unauthorized("Invalid or missing credentials")
+        }
+        let state = create_test_state();
+        let app = Router::new()
+            .route("/mcp/github", post(reject))
+            .route("/mcp", post(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                www_authenticate_middleware,
+            ))
+            .with_state(state);
+        let request = |path: &str, token: Option<&str>| {
+            let mut builder = Request::post(path).header(header::HOST, "gw.example.com");
+            if let Some(token) = token {
+                builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("/mcp/github", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[header::WWW_AUTHENTICATE],
+            "Bearer resource_metadata=\"http://gw.example.com/.well-known/oauth-protected-resource/mcp/github\""
+        );
+
+        let response = app
+            .clone()
+            .oneshot(request("/mcp/github", Some("stale")))
+            .await
+            .unwrap();
+        let challenge = response.headers()[header::WWW_AUTHENTICATE]
+            .to_str()
+            .unwrap();
+        assert!(challenge.starts_with("Bearer error=\"invalid_token\", resource_metadata="));
+
+        let response = app.oneshot(request("/mcp", None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::WWW_AUTHENTICATE).is_none());
+    }
+}
@@ -0,0 +1,464 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Library mode: embed the guard in an existing Axum application
+//!
+//! [`GuardBuilder`] assembles the shared state from a [`Config`] plus the
+//! pieces that need I/O to create (the upstream transport, and optionally the
+//! auth provider and audit logger). The resulting [`Guard`] hands out router
+//! fragments to merge into the host application:
+//!
+//! ```ignore
+//! let guard = Guard::builder(config)
+//!     .transport(Arc::new(HttpTransport::new(upstream_url).await?))
+//!     .auth_provider(Arc::new(jwt_provider))
+//!     .build()?;
+//!
+//! let app = Router::new()
+//!     .route("/", get(index))
+//!     .merge(guard.mcp_routes())
+//!     .merge(guard.protect(Router::new().route("/internal", get(internal))));
+//!
+//! axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+//! ```
+//!
+//! The connect info is required: network ACLs, mTLS proxy checks and OAuth
+//! state binding all use the peer address.
+
+use axum::Router;
+use metrics_exporter_prometheus::PrometheusHandle;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use super::{mcp_routes, new_oauth_state_store, operational_routes, protect, AppState};
+use crate::audit::AuditLogger;
+use crate::auth::{
+    registered_auth_providers, ApiKeyProvider, AuthProvider, HmacAuthProvider, MtlsAuthProvider,
+    MultiProvider,
+};
+use crate::authz::ResponseFilterChain;
+use crate::capture::CaptureRecorder;
+use crate::config::{Config, ConfigError};
+use crate::fair_queue::FairQueue;
+use crate::identity_store::IdentityStore;
+use crate::journal::Journal;
+use crate::load_shed::LoadShedder;
+use crate::network_acl::NetworkAcl;
+use crate::rate_limit::RateLimitService;
+use crate::router::ServerRouter;
+use crate::scrub::Scrubber;
+use crate::tenancy::TenantRegistry;
+use crate::transport::Transport;
+
+// ============================================================================
+// Builder
+// ============================================================================
+
+/// Builder for an embedded [`Guard`]
+///
+/// Everything not set explicitly is derived from the config the same way the
+/// standalone server does it: rate limiting, load shedding, network ACLs,
+/// scrubbing, response filtering, mTLS and HMAC. Journaled tool calls are
+/// only redelivered if the host spawns [`redeliver_journal`](super::redeliver_journal).
+pub struct GuardBuilder {
+    config: Config,
+    auth_provider: Option<Arc<dyn AuthProvider>>,
+    transport: Option<Arc<dyn Transport>>,
+    server_router: Option<Arc<ServerRouter>>,
+    audit_logger: Option<Arc<AuditLogger>>,
+    capture: Option<CaptureRecorder>,
+    metrics_handle: Option<PrometheusHandle>,
+}
+
+impl GuardBuilder {
+    /// Start from a validated configuration
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            auth_provider: None,
+            transport: None,
+            server_router: None,
+            audit_logger: None,
+            capture: None,
+            metrics_handle: None,
+        }
+    }
+
+    /// Bearer token provider
+    ///
+    /// Defaults to the configured API keys plus any `[[auth.custom]]`
+    /// providers. JWT and OAuth providers start background tasks, so create
+    /// them yourself and pass them here (wrapped in a `MultiProvider` to
+    /// combine several).
+    pub fn auth_provider(mut self, provider: Arc<dyn AuthProvider>) -> Self {
+        self.auth_provider = Some(provider);
+        self
+    }
+
+    /// Upstream transport for single-server mode
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Server router for multi-server mode
+    pub fn server_router(mut self, router: Arc<ServerRouter>) -> Self {
+        self.server_router = Some(router);
+        self
+    }
+
+    /// Audit logger (defaults to a synchronous logger built from `[audit]`)
+    pub fn audit_logger(mut self, audit_logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(audit_logger);
+        self
+    }
+
+    /// Recorder for `[capture]` (defaults to capturing nothing; create one
+    /// with `CaptureRecorder::with_task`)
+    pub fn capture_recorder(mut self, recorder: CaptureRecorder) -> Self {
+        self.capture = Some(recorder);
+        self
+    }
+
+    /// Prometheus handle rendered by `/metrics` (defaults to installing the
+    /// global recorder if none is installed yet)
+    pub fn metrics_handle(mut self, handle: PrometheusHandle) -> Self {
+        self.metrics_handle = Some(handle);
+        self
+    }
+
+    /// Build the guard
+    pub fn build(self) -> Result<Guard, crate::Error> {
+        if self.transport.is_none() && self.server_router.is_none() {
+            return Err(ConfigError::Validation(
+                "embedded guard requires a transport or a server router".to_string(),
+            )
+            .into());
+        }
+
+        let config = self.config;
+        crate::egress::configure_egress(&config.egress);
+        let auth_provider = match self.auth_provider {
+            Some(provider) => provider,
+            None => default_auth_provider(&config)?,
+        };
+        let audit_logger = match self.audit_logger {
+            Some(logger) => logger,
+            None => Arc::new(AuditLogger::new(&config.audit)?),
+        };
+        let metrics_handle = self
+            .metrics_handle
+            .unwrap_or_else(crate::observability::init_metrics);
+        crate::observability::configure_identity_metrics(&config.metrics);
+        crate::observability::configure_label_limits(&config.metrics);
+        crate::observability::configure_telemetry_privacy(&config.telemetry_privacy);
+        crate::transport::configure_ssrf(&config.ssrf);
+        crate::transport::configure_request_signing(&config.request_signing).map_err(|e| {
+            crate::Error::Config(ConfigError::Validation(format!("request_signing: {}", e)))
+        })?;
+
+        let network_acl = NetworkAcl::new(&config).map_err(|e| {
+            crate::Error::Config(ConfigError::Validation(format!("network_acl: {}", e)))
+        })?;
+        let scrubber = Scrubber::new(&config.scrubbing).map_err(|e| {
+            crate::Error::Config(ConfigError::Validation(format!("scrubbing: {}", e)))
+        })?;
+        let response_filters =
+            ResponseFilterChain::new(&config.response_filtering).map_err(|e| {
+                crate::Error::Config(ConfigError::Validation(format!(
+                    "response_filtering: {}",
+                    e
+                )))
+            })?;
+        let mtls_provider = config
+            .auth
+            .mtls
+            .clone()
+            .filter(|mtls| mtls.enabled)
+            .map(|mtls| Arc::new(MtlsAuthProvider::new(mtls)));
+        let hmac_provider = config
+            .auth
+            .hmac
+            .clone()
+            .map(|hmac| Arc::new(HmacAuthProvider::new(hmac)));
+        let identity_store = Arc::new(IdentityStore::new(Duration::from_secs(
+            config.admin.active_window_secs,
+        )));
+        let journal = Journal::open(&config.journal)?;
+
+        let state = AppState {
+            rate_limiter: RateLimitService::new(&config.rate_limit),
+            load_shedder: LoadShedder::new(&config.load_shedding),
+            fair_queue: FairQueue::new("default", &config.upstream.fair_queue),
+            tenants: TenantRegistry::new(&config.tenancy)
+                .with_namespaced_tools(config.is_aggregated()),
+            approvals: crate::approval::ApprovalService::new(
+                &config.approval,
+                &config.admin.identities,
+            ),
+            honeypot: crate::honeypot::Honeypot::new(&config.honeypot),
+            read_only: crate::read_only::ReadOnlyPolicy::new(&config.read_only)
+                .with_namespaced_tools(config.is_aggregated()),
+            break_glass: crate::break_glass::BreakGlass::new(
+                &config.break_glass,
+                &config.auth.api_keys,
+            ),
+            method_policy: crate::method_policy::MethodPolicy::new(&config),
+            capture: self.capture.unwrap_or_default(),
+            journal,
+            upstream_requests: Default::default(),
+            registration_limiter: crate::rate_limit::IpRateLimiter::for_registration(&config),
+            auth_provider,
+            audit_logger,
+            transport: self.transport,
+            router: self.server_router,
+            metrics_handle,
+            oauth_provider: None,
+            oauth_state_store: new_oauth_state_store(),
+            session_store: None,
+            cluster: None,
+            affinity: None,
+            authorization_server: None,
+            started_at: Instant::now(),
+            ready: Arc::new(RwLock::new(true)),
+            mtls_provider,
+            hmac_provider,
+            network_acl,
+            scrubber,
+            response_filters,
+            identity_store,
+            sessions: crate::sessions::SessionRegistry::new(&config.sessions),
+            upstream_stats: Default::default(),
+            license_expires_at: None,
+            jwt_provider: None,
+            db: None,
+            config,
+        };
+        Ok(Guard::from_state(Arc::new(state)))
+    }
+}
+
+/// API keys plus registered custom providers, as the standalone server does
+fn default_auth_provider(config: &Config) -> Result<Arc<dyn AuthProvider>, crate::Error> {
+    let mut providers: Vec<Arc<dyn AuthProvider>> = Vec::new();
+    if !config.auth.api_keys.is_empty() {
+        providers.push(Arc::new(ApiKeyProvider::new(config.auth.api_keys.clone())));
+    }
+    let registry = registered_auth_providers();
+    for custom in &config.auth.custom {
+        providers.push(registry.build(custom)?);
+    }
+
+    Ok(match providers.len() {
+        0 => {
+            tracing::warn!(
+                "No authentication providers configured - all requests will be rejected"
+            );
+            Arc::new(ApiKeyProvider::new(vec![]))
+        }
+        1 => providers.remove(0),
+        _ => Arc::new(MultiProvider::new(providers)),
+    })
+}
+
+// ============================================================================
+// Guard
+// ============================================================================
+
+/// An assembled guard whose routes can be mounted in another application
+#[derive(Clone)]
+pub struct Guard {
+    state: Arc<AppState>,
+}
+
+impl Guard {
+    /// Start building a guard from configuration
+    pub fn builder(config: Config) -> GuardBuilder {
+        GuardBuilder::new(config)
+    }
+
+    /// Wrap state assembled elsewhere (e.g. by the CLI bootstrap)
+    pub fn from_state(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
+    /// Shared state, for inspecting the rate limiter, identity store, etc.
+    pub fn state(&self) -> &Arc<AppState> {
+        &self.state
+    }
+
+    /// MCP proxy routes (`POST /mcp`, or `/mcp/:server_name` when routing)
+    /// behind the full guard pipeline
+    ///
+    /// Use `Router::nest` to mount them under a prefix.
+    pub fn mcp_routes<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        mcp_routes(&self.state).with_state(self.state.clone())
+    }
+
+    /// `/health`, `/live`, `/ready`, `/metrics` and `/openapi.json`
+    pub fn operational_routes<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        operational_routes().with_state(self.state.clone())
+    }
+
+    /// Put the host's own routes behind the guard's network ACL,
+    /// authentication, rate limiting and audit logging
+    ///
+    /// Handlers receive the caller as `Extension<Identity>`.
+    pub fn protect<S>(&self, router: Router<S>) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        protect(router, &self.state)
+    }
+
+    /// Router for the operational listener when `[server.ops]` is configured
+    ///
+    /// [`into_router`](Self::into_router) leaves health, metrics and admin
+    /// endpoints out in that case; serve this router on the ops address.
+    pub fn ops_router(&self) -> Option<Router> {
+        self.state
+            .config
+            .server
+            .ops
+            .is_some()
+            .then(|| super::build_ops_router(self.state.clone()))
+    }
+
+    /// The complete standalone router, as served by `mcp-guard run`
+    pub fn into_router(self) -> Router {
+        super::build_router(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Identity, StaticTokenProvider};
+    use crate::mocks::MockTransport;
+    use crate::transport::Message;
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    fn test_config() -> Config {
+        toml::from_str(
+            r#"
+            [upstream]
+            transport = "stdio"
+            command = "echo"
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn request(uri: &str, token: Option<&str>, body: &str) -> Request<Body> {
+        let mut builder = Request::builder()
+            .method(if body.is_empty() { "GET" } else { "POST" })
+            .uri(uri)
+            .header("Content-Type", "application/json");
+        if let Some(token) = token {
+            builder = builder.header("Authorization", format!("Bearer {}", token));
+        }
+        let mut request = builder.body(Body::from(body.to_string())).unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(std::net::SocketAddr::from((
+                [127, 0, 0, 1],
+                3000,
+            ))));
+        request
+    }
+
+    #[test]
+    fn test_build_requires_upstream() {
+        let result = Guard::builder(test_config()).build();
+        assert!(matches!(result, Err(crate::Error::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_embedded_routes() {
+        let transport = MockTransport::new();
+        transport.push_response(Message::response(
+            serde_json::json!(1),
+            serde_json::json!({}),
+        ));
+        let guard = Guard::builder(test_config())
+            .transport(Arc::new(transport.clone()))
+            .auth_provider(Arc::new(
+                StaticTokenProvider::new("static").with_identity("secret", "alice"),
+            ))
+            .metrics_handle(crate::observability::create_metrics_handle())
+            .build()
+            .unwrap();
+
+        let host_routes = Router::new().route(
+            "/whoami",
+            get(|axum::Extension(identity): axum::Extension<Identity>| async move { identity.id }),
+        );
+        let app: Router = Router::new()
+            .route("/", get(|| async { "host app" }))
+            .merge(guard.protect(host_routes))
+            .merge(guard.mcp_routes())
+            .merge(guard.operational_routes());
+
+        let ping = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        let status = |response: axum::response::Response| response.status();
+
+        // Host routes are untouched
+        let response = app.clone().oneshot(request("/", None, "")).await.unwrap();
+        assert_eq!(status(response), StatusCode::OK);
+
+        // Protected routes require credentials
+        let response = app
+            .clone()
+            .oneshot(request("/whoami", None, ""))
+            .await
+            .unwrap();
+        assert_eq!(status(response), StatusCode::UNAUTHORIZED);
+        let response = app
+            .clone()
+            .oneshot(request("/whoami", Some("secret"), ""))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"alice");
+
+        // MCP requests are proxied upstream
+        let response = app
+            .clone()
+            .oneshot(request("/mcp", Some("secret"), ping))
+            .await
+            .unwrap();
+        assert_eq!(status(response), StatusCode::OK);
+        assert_eq!(transport.sent_count(), 1);
+
+        let response = app.oneshot(request("/live", None, "")).await.unwrap();
+        assert_eq!(status(response), StatusCode::OK);
+        assert_eq!(guard.state().identity_store.active_count(), 1);
+    }
+}
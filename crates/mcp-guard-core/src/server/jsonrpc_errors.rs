@@ -0,0 +1,244 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Gateway errors as JSON-RPC error responses
+//!
+//! Many MCP clients only understand JSON-RPC errors and treat any non-2xx
+//! status as a broken connection. With `server.error_format = "jsonrpc"`, or
+//! `X-MCP-Guard-Error-Format: jsonrpc` on a request, errors from the MCP
+//! endpoints (authentication, authorization, rate limiting, upstream
+//! failures) are answered with `200 OK` and a JSON-RPC error carrying the
+//! request's ID, a fixed code per failure and the usual error fields under
+//! `data`. Headers such as `Retry-After` are kept.
+//!
+//! The request ID is only known once the body is read, so in this mode the
+//! body is buffered before authentication. Notifications have no ID to answer
+//! and keep their HTTP errors.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+use super::body::is_length_limit;
+use super::{AppError, AppState};
+use crate::config::ErrorFormat;
+
+/// Request header choosing the error format for one request
+pub const ERROR_FORMAT_HEADER: &str = "x-mcp-guard-error-format";
+
+/// HTTP status of an error that was answered as a JSON-RPC error
+///
+/// Set as a response extension so metrics still count the failure.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonRpcErrorStatus(pub StatusCode);
+
+/// Status to report for a response: the original one if it was rewritten
+pub(crate) fn effective_status(response: &Response) -> StatusCode {
+    response
+        .extensions()
+        .get::<JsonRpcErrorStatus>()
+        .map_or(response.status(), |status| status.0)
+}
+
+/// JSON-RPC error code and `data.type` for an HTTP error status
+pub(crate) fn jsonrpc_error_code(status: StatusCode) -> (i32, &'static str) {
+    match status {
+        StatusCode::BAD_REQUEST => (-32600, "invalid_request"),
+        StatusCode::UNAUTHORIZED => (-32010, "unauthorized"),
+        StatusCode::FORBIDDEN => (-32011, "forbidden"),
+        StatusCode::NOT_FOUND => (-32012, "not_found"),
+        StatusCode::CONFLICT => (-32013, "conflict"),
+        StatusCode::PAYLOAD_TOO_LARGE => (-32014, "payload_too_large"),
+        StatusCode::TOO_MANY_REQUESTS => (-32020, "rate_limited"),
+        StatusCode::SERVICE_UNAVAILABLE => (-32021, "unavailable"),
+        StatusCode::BAD_GATEWAY => (-32000, "upstream_error"),
+        StatusCode::GATEWAY_TIMEOUT => (-32001, "upstream_timeout"),
+        _ => (-32603, "internal_error"),
+    }
+}
+
+fn wants_jsonrpc_errors(default: ErrorFormat, headers: &HeaderMap) -> bool {
+    let requested = headers
+        .get(ERROR_FORMAT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| match value.trim().to_ascii_lowercase().as_str() {
+            "jsonrpc" => Some(ErrorFormat::JsonRpc),
+            "http" => Some(ErrorFormat::Http),
+            _ => None,
+        });
+    requested.unwrap_or(default) == ErrorFormat::JsonRpc
+}
+
+#[derive(Deserialize)]
+struct RequestId {
+    #[serde(default)]
+    id: Option<Value>,
+}
+
+/// ID to answer with: `None` for notifications, `null` if unreadable
+fn request_id(body: &[u8]) -> Option<Value> {
+    match serde_json::from_slice::<RequestId>(body) {
+        Ok(request) => request.id,
+        Err(_) => Some(Value::Null),
+    }
+}
+
+/// Answer MCP endpoint errors as JSON-RPC errors when the client asks for it
+pub async fn jsonrpc_errors_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if !wants_jsonrpc_errors(state.config.server.error_format, request.headers()) {
+        return next.run(request).await;
+    }
+
+    let limit = state.config.server.max_request_size;
+    let (parts, body) = request.into_parts();
+    let (id, response) = match axum::body::to_bytes(body, limit).await {
+        Ok(bytes) => {
+            let id = request_id(&bytes);
+            let request = Request::from_parts(parts, Body::from(bytes));
+            (id, next.run(request).await)
+        }
+        Err(e) if is_length_limit(&e) => (
+            Some(Value::Null),
+            AppError::payload_too_large(limit).into_response(),
+        ),
+        Err(e) => (
+            Some(Value::Null),
+            AppError::bad_request(format!("Failed to read request body: {}", e)).into_response(),
+        ),
+    };
+
+    match id {
+        Some(id) => to_jsonrpc_error(response, id).await,
+        None => response,
+    }
+}
+
+/// Rewrite an HTTP error response as a JSON-RPC error for `id`
+///
+/// Successful responses are returned unchanged.
+pub(crate) async fn to_jsonrpc_error(response: Response, id: Value) -> Response {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .unwrap_or_default();
+    let mut data = match serde_json::from_slice(&body) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+    let message = match data.remove("error") {
+        Some(Value::String(message)) => message,
+        _ => status
+            .canonical_reason()
+            .unwrap_or("Request failed")
+            .to_string(),
+    };
+    let (code, kind) = jsonrpc_error_code(status);
+    data.insert("type".to_string(), json!(kind));
+    data.insert("http_status".to_string(), json!(status.as_u16()));
+
+    let error = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message, "data": data },
+    });
+    parts.status = StatusCode::OK;
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    parts.extensions.insert(JsonRpcErrorStatus(status));
+    Response::from_parts(parts, Body::from(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_errors_become_jsonrpc_errors() {
+        let mut response = AppError::rate_limited(Some(7)).into_response();
+        response
+            .headers_mut()
+            .insert("x-ratelimit-limit", HeaderValue::from_static("10"));
+
+        let response = to_jsonrpc_error(response, json!(42)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(effective_status(&response), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "7");
+        assert_eq!(response.headers()["x-ratelimit-limit"], "10");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["id"], 42);
+        assert_eq!(body["error"]["code"], -32020);
+        assert_eq!(body["error"]["message"], "Rate limit exceeded");
+        assert_eq!(body["error"]["data"]["type"], "rate_limited");
+        assert_eq!(body["error"]["data"]["http_status"], 429);
+        assert_eq!(body["error"]["data"]["retry_after"], 7);
+        assert!(body["error"]["data"]["error_id"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_success_is_unchanged() {
+        let response = to_jsonrpc_error(StatusCode::ACCEPTED.into_response(), json!(1)).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert!(response.extensions().get::<JsonRpcErrorStatus>().is_none());
+    }
+
+    #[test]
+    fn test_header_overrides_configured_format() {
+        let mut headers = HeaderMap::new();
+        assert!(!wants_jsonrpc_errors(ErrorFormat::Http, &headers));
+        assert!(wants_jsonrpc_errors(ErrorFormat::JsonRpc, &headers));
+
+        headers.insert(ERROR_FORMAT_HEADER, HeaderValue::from_static("JSONRPC"));
+        assert!(wants_jsonrpc_errors(ErrorFormat::Http, &headers));
+        headers.insert(ERROR_FORMAT_HEADER, HeaderValue::from_static("http"));
+        assert!(!wants_jsonrpc_errors(ErrorFormat::JsonRpc, &headers));
+        headers.insert(ERROR_FORMAT_HEADER, HeaderValue::from_static("xml"));
+        assert!(wants_jsonrpc_errors(ErrorFormat::JsonRpc, &headers));
+    }
+
+    #[test]
+    fn test_request_id() {
+        assert_eq!(
+            request_id(br#"{"jsonrpc":"2.0","id":"a","method":"x"}"#),
+            Some(json!("a"))
+        );
+        assert_eq!(request_id(br#"{"jsonrpc":"2.0","method":"x"}"#), None);
+        assert_eq!(request_id(b"not json"), Some(Value::Null));
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Pre-flight JSON-RPC validation of client messages
+//!
+//! Client messages are checked before authorization, routing or forwarding,
+//! so malformed JSON-RPC never reaches an upstream. A rejected message is
+//! answered with a `-32600 Invalid Request` error carrying its ID when the ID
+//! is usable, and counted in `mcp_guard_invalid_jsonrpc_total`.
+//!
+//! `server.jsonrpc_validation = "lenient"` (the default) only rejects
+//! messages whose kind is ambiguous or whose ID cannot be echoed back, and
+//! corrects a wrong `jsonrpc` version. `"strict"` rejects every deviation
+//! from JSON-RPC 2.0 it can see.
+
+use serde_json::Value;
+
+use crate::config::JsonRpcValidation;
+use crate::transport::Message;
+
+/// JSON-RPC error code for an invalid request object
+const INVALID_REQUEST: i32 = -32600;
+
+/// Why a client message was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Invalid {
+    /// `jsonrpc` is not "2.0"
+    Version,
+    /// A method together with a result or error, or both a result and an error
+    Ambiguous,
+    /// Neither a method nor a result or error
+    Empty,
+    /// The ID is not a string or (integer) number
+    Id,
+    /// An empty method name
+    Method,
+    /// Params that are not an object or array
+    Params,
+    /// A response without an ID
+    MissingId,
+}
+
+impl Invalid {
+    /// Metric label
+    pub(crate) fn reason(self) -> &'static str {
+        match self {
+            Self::Version => "version",
+            Self::Ambiguous => "ambiguous",
+            Self::Empty => "empty",
+            Self::Id => "id",
+            Self::Method => "method",
+            Self::Params => "params",
+            Self::MissingId => "missing_id",
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            Self::Version => "Invalid Request: jsonrpc must be \"2.0\"",
+            Self::Ambiguous => {
+                "Invalid Request: a message has either a method, a result or an error"
+            }
+            Self::Empty => "Invalid Request: missing method, result or error",
+            Self::Id => "Invalid Request: id must be a string or an integer",
+            Self::Method => "Invalid Request: method must not be empty",
+            Self::Params => "Invalid Request: params must be an object or an array",
+            Self::MissingId => "Invalid Request: a response must have an id",
+        }
+    }
+}
+
+/// Check a client message, correcting what the mode allows
+pub(crate) fn validate(message: &mut Message, mode: JsonRpcValidation) -> Result<(), Invalid> {
+    let strict = mode == JsonRpcValidation::Strict;
+    let has_outcome = message.result.is_some() || message.error.is_some();
+
+    if (message.method.is_some() && has_outcome)
+        || (message.result.is_some() && message.error.is_some())
+    {
+        return Err(Invalid::Ambiguous);
+    }
+    if message.method.is_none() && !has_outcome {
+        return Err(Invalid::Empty);
+    }
+    match message.id {
+        None | Some(Value::String(_)) => {}
+        Some(Value::Number(ref n)) if !strict || n.is_i64() || n.is_u64() => {}
+        Some(_) => return Err(Invalid::Id),
+    }
+    if message.jsonrpc != "2.0" {
+        if strict {
+            return Err(Invalid::Version);
+        }
+        message.jsonrpc = "2.0".to_string();
+    }
+    if strict {
+        if message.method.as_deref() == Some("") {
+            return Err(Invalid::Method);
+        }
+        if !matches!(
+            message.params,
+            None | Some(Value::Object(_) | Value::Array(_))
+        ) {
+            return Err(Invalid::Params);
+        }
+        if has_outcome && message.id.is_none() {
+            return Err(Invalid::MissingId);
+        }
+    }
+    Ok(())
+}
+
+/// `-32600` error answering a rejected message
+///
+/// The ID is echoed back when it is a string or number, and `null` otherwise.
+pub(crate) fn invalid_request(message: &Message, invalid: Invalid) -> Message {
+    let id = match message.id {
+        Some(ref id @ (Value::String(_) | Value::Number(_))) => id.clone(),
+        _ => Value::Null,
+    };
+    Message::error_response(Some(id), INVALID_REQUEST, invalid.message())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(value: Value) -> Message {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_valid_messages_pass() {
+        for mode in [JsonRpcValidation::Lenient, JsonRpcValidation::Strict] {
+            let mut request = Message::request(1, "tools/list", Some(json!({})));
+            assert_eq!(validate(&mut request, mode), Ok(()));
+            let mut notification =
+                parse(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}));
+            assert_eq!(validate(&mut notification, mode), Ok(()));
+            let mut response = Message::response(json!("abc"), json!({}));
+            assert_eq!(validate(&mut response, mode), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_ambiguous_and_bad_ids_rejected_in_both_modes() {
+        for mode in [JsonRpcValidation::Lenient, JsonRpcValidation::Strict] {
+            let mut both =
+                parse(json!({"jsonrpc": "2.0", "id": 1, "method": "ping", "result": {}}));
+            assert_eq!(validate(&mut both, mode), Err(Invalid::Ambiguous));
+            let mut outcome = parse(json!({"jsonrpc": "2.0", "id": 1, "result": {}, "error": {}}));
+            assert_eq!(validate(&mut outcome, mode), Err(Invalid::Ambiguous));
+            let mut empty = parse(json!({"jsonrpc": "2.0", "id": 1}));
+            assert_eq!(validate(&mut empty, mode), Err(Invalid::Empty));
+            let mut id = parse(json!({"jsonrpc": "2.0", "id": {"a": 1}, "method": "ping"}));
+            assert_eq!(validate(&mut id, mode), Err(Invalid::Id));
+            let mut id = parse(json!({"jsonrpc": "2.0", "id": true, "method": "ping"}));
+            assert_eq!(validate(&mut id, mode), Err(Invalid::Id));
+        }
+    }
+
+    #[test]
+    fn test_lenient_corrects_what_strict_rejects() {
+        let message = || parse(json!({"jsonrpc": "1.0", "id": 1.5, "method": "", "params": "x"}));
+
+        let mut lenient = message();
+        assert_eq!(validate(&mut lenient, JsonRpcValidation::Lenient), Ok(()));
+        assert_eq!(lenient.jsonrpc, "2.0");
+
+        let mut strict = message();
+        assert_eq!(
+            validate(&mut strict, JsonRpcValidation::Strict),
+            Err(Invalid::Id)
+        );
+        let mut strict = parse(json!({"jsonrpc": "1.0", "id": 1, "method": "ping"}));
+        assert_eq!(
+            validate(&mut strict, JsonRpcValidation::Strict),
+            Err(Invalid::Version)
+        );
+        let mut strict = parse(json!({"jsonrpc": "2.0", "id": 1, "method": ""}));
+        assert_eq!(
+            validate(&mut strict, JsonRpcValidation::Strict),
+            Err(Invalid::Method)
+        );
+        let mut strict = parse(json!({"jsonrpc": "2.0", "id": 1, "method": "ping", "params": 3}));
+        assert_eq!(
+            validate(&mut strict, JsonRpcValidation::Strict),
+            Err(Invalid::Params)
+        );
+        let mut strict = parse(json!({"jsonrpc": "2.0", "result": {}}));
+        assert_eq!(
+            validate(&mut strict, JsonRpcValidation::Strict),
+            Err(Invalid::MissingId)
+        );
+    }
+
+    #[test]
+    fn test_invalid_request_echoes_usable_id() {
+        let message = parse(json!({"jsonrpc": "2.0", "id": "a", "method": "ping", "result": {}}));
+        let response = invalid_request(&message, Invalid::Ambiguous);
+        assert_eq!(response.id, Some(json!("a")));
+        assert_eq!(response.error.unwrap()["code"], -32600);
+
+        let message = parse(json!({"jsonrpc": "2.0", "id": [1], "method": "ping"}));
+        let response = invalid_request(&message, Invalid::Id);
+        assert_eq!(response.id, Some(Value::Null));
+    }
+}
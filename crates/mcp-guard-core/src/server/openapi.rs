@@ -0,0 +1,852 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! OpenAPI description of the gateway's own HTTP API
+//!
+//! Served at `/openapi.json` so clients and API gateways can discover the
+//! endpoints programmatically. The document is assembled from the running
+//! configuration: only the MCP endpoint of the active mode and the OAuth and
+//! admin routes that are actually mounted are described.
+
+use serde_json::{json, Map, Value};
+
+use super::AppState;
+
+/// OpenAPI version of the generated document
+const OPENAPI_VERSION: &str = "3.1.0";
+
+/// Build the OpenAPI document for this gateway
+pub fn openapi_document(state: &AppState) -> Value {
+    let mut paths = Map::new();
+    operational_paths(&mut paths);
+    mcp_paths(state, &mut paths);
+    if state.oauth_provider.is_some() {
+        oauth_paths(&mut paths);
+    }
+    if state.authorization_server.is_some() {
+        authorization_server_paths(&mut paths);
+    }
+    admin_paths(state, &mut paths);
+
+    let mut description = String::from(
+        "HTTP API of the MCP Guard gateway. MCP traffic is JSON-RPC 2.0 over POST; \
+         see https://modelcontextprotocol.io for the message format.",
+    );
+    if state.config.server.ops.is_some() {
+        description.push_str(
+            " Operational and admin endpoints are served on the separate operational listener.",
+        );
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "MCP Guard",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": description,
+            "license": { "name": "AGPL-3.0", "identifier": "AGPL-3.0" },
+        },
+        "paths": paths,
+        "components": components(),
+        "tags": [
+            { "name": "mcp", "description": "MCP proxy endpoints" },
+            { "name": "operational", "description": "Health, readiness and metrics" },
+            { "name": "oauth", "description": "OAuth 2.1 authorization code and device flows" },
+            { "name": "admin", "description": "Administration API" },
+        ],
+    })
+}
+
+fn operational_paths(paths: &mut Map<String, Value>) {
+    paths.insert(
+        "/health".to_string(),
+        json!({ "get": {
+            "tags": ["operational"],
+            "summary": "Gateway status",
+            "operationId": "health",
+            "parameters": [{
+                "name": "deep",
+                "in": "query",
+                "description": "Include per-upstream, audit and JWKS detail",
+                "schema": { "type": "boolean", "default": false },
+            }],
+            "responses": { "200": json_response("Gateway status", "HealthResponse") },
+        }}),
+    );
+    paths.insert(
+        "/live".to_string(),
+        json!({ "get": {
+            "tags": ["operational"],
+            "summary": "Liveness probe",
+            "operationId": "live",
+            "responses": { "200": json_response("Process is alive", "LiveResponse") },
+        }}),
+    );
+    paths.insert(
+        "/ready".to_string(),
+        json!({ "get": {
+            "tags": ["operational"],
+            "summary": "Readiness probe",
+            "operationId": "ready",
+            "responses": {
+                "200": json_response("Ready to take traffic", "ReadyResponse"),
+                "503": json_response("Not ready", "ReadyResponse"),
+            },
+        }}),
+    );
+    paths.insert(
+        "/metrics".to_string(),
+        json!({ "get": {
+            "tags": ["operational"],
+            "summary": "Prometheus metrics",
+            "operationId": "metrics",
+            "responses": { "200": {
+                "description": "Metrics in Prometheus text format, or OpenMetrics with trace exemplars when the scraper accepts it",
+                "content": {
+                    "text/plain": { "schema": { "type": "string" } },
+                    "application/openmetrics-text": { "schema": { "type": "string" } },
+                },
+            }},
+        }}),
+    );
+    paths.insert(
+        "/version".to_string(),
+        json!({ "get": {
+            "tags": ["operational"],
+            "summary": "Build, license, auth provider, transport and limit inventory",
+            "operationId": "version",
+            "responses": { "200": json_response("Gateway inventory", "VersionResponse") },
+        }}),
+    );
+    paths.insert(
+        "/openapi.json".to_string(),
+        json!({ "get": {
+            "tags": ["operational"],
+            "summary": "This document",
+            "operationId": "openapi",
+            "responses": { "200": {
+                "description": "OpenAPI document",
+                "content": { "application/json": { "schema": { "type": "object" } } },
+            }},
+        }}),
+    );
+}
+
+fn mcp_paths(state: &AppState, paths: &mut Map<String, Value>) {
+    let mut responses = json!({
+        "200": json_response(
+            "JSON-RPC response, empty for notifications; also errors, if requested as JSON-RPC",
+            "JsonRpcMessage"
+        ),
+        "202": { "description": "Response to an upstream request accepted" },
+        "400": error_response("Malformed JSON-RPC message"),
+        "401": error_response("Missing or invalid credentials"),
+        "403": error_response("Tool, resource or prompt not allowed for this identity"),
+        "409": error_response("Journaled call with this ID is still being delivered"),
+        "413": error_response("Request body too large"),
+        "429": error_response("Rate limited or fair queue full; see Retry-After"),
+        "502": error_response("Upstream error"),
+        "503": error_response("Overloaded or upstream draining; see Retry-After"),
+        "504": error_response("Upstream timed out"),
+    });
+    // Clients accepting an event stream may get upstream-initiated messages first
+    responses["200"]["content"]["text/event-stream"] = json!({ "schema": { "type": "string" } });
+    let mut operation = json!({
+        "tags": ["mcp"],
+        "summary": "Send an MCP message",
+        "operationId": "mcp",
+        "security": [{ "bearerAuth": [] }],
+        "parameters": [{
+            "name": "X-MCP-Guard-Error-Format",
+            "in": "header",
+            "required": false,
+            "description": "`jsonrpc` to receive errors as JSON-RPC errors, `http` for HTTP errors",
+            "schema": { "type": "string", "enum": ["http", "jsonrpc"] },
+        }],
+        "requestBody": {
+            "required": true,
+            "content": { "application/json": {
+                "schema": { "$ref": "#/components/schemas/JsonRpcMessage" },
+            }},
+        },
+    });
+
+    let path = if state.router.is_some() && !state.config.is_aggregated() {
+        operation["summary"] = json!("Send an MCP message to a server route");
+        operation["operationId"] = json!("mcpRoute");
+        if let Some(parameters) = operation["parameters"].as_array_mut() {
+            parameters.insert(
+                0,
+                json!({
+                    "name": "server_name",
+                    "in": "path",
+                    "required": true,
+                    "description": "Server route name",
+                    "schema": { "type": "string", "enum": route_names(state) },
+                }),
+            );
+        }
+        responses["404"] = error_response("No such server route");
+        "/mcp/{server_name}"
+    } else {
+        "/mcp"
+    };
+    operation["responses"] = responses;
+    let mut discovery = json!({
+        "tags": ["mcp"],
+        "summary": "Protocol versions, streaming support and authentication requirements",
+        "operationId": "mcpDiscovery",
+        "responses": {
+            "200": json_response("Endpoint capabilities", "DiscoveryResponse"),
+            "405": { "description": "Standalone event stream requested; not supported" },
+        },
+    });
+    if let Some(parameter) = operation["parameters"]
+        .as_array()
+        .filter(|_| path != "/mcp")
+        .and_then(|parameters| parameters.first())
+    {
+        discovery["parameters"] = json!([parameter]);
+        discovery["responses"]["404"] = error_response("No such server route");
+    }
+    paths.insert(
+        path.to_string(),
+        json!({ "post": operation, "get": discovery }),
+    );
+
+    let metadata = json!({ "get": {
+        "tags": ["mcp"],
+        "summary": "OAuth protected resource metadata (RFC 9728) of an MCP endpoint",
+        "operationId": "protectedResourceMetadata",
+        "responses": {
+            "200": json_response("Protected resource metadata", "ProtectedResourceMetadata"),
+            "404": error_response("No MCP endpoint at this path"),
+        },
+    }});
+    let metadata_path = if path == "/mcp" {
+        "/.well-known/oauth-protected-resource"
+    } else {
+        "/.well-known/oauth-protected-resource/mcp/{server_name}"
+    };
+    paths.insert(metadata_path.to_string(), metadata);
+
+    if crate::transport::publishes_jwks(&state.config) {
+        paths.insert(
+            "/.well-known/jwks.json".to_string(),
+            json!({ "get": {
+                "tags": ["mcp"],
+                "summary": "Public keys that sign forwarded requests and identity assertions",
+                "operationId": "gatewayJwks",
+                "responses": { "200": {
+                    "description": "JWK Set with the gateway's signing keys",
+                    "content": { "application/json": { "schema": { "type": "object" } } },
+                }},
+            }}),
+        );
+    }
+
+    if state.router.is_some() {
+        paths.insert(
+            "/routes".to_string(),
+            json!({ "get": {
+                "tags": ["mcp"],
+                "summary": "List server routes",
+                "operationId": "listRoutes",
+                "responses": { "200": {
+                    "description": "Configured server routes",
+                    "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": {
+                            "routes": { "type": "array", "items": { "type": "string" } },
+                            "count": { "type": "integer" },
+                        },
+                    }}},
+                }},
+            }}),
+        );
+    }
+}
+
+fn route_names(state: &AppState) -> Vec<String> {
+    state
+        .router
+        .as_ref()
+        .map(|router| {
+            router
+                .route_names()
+                .iter()
+                .map(|name| name.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn oauth_paths(paths: &mut Map<String, Value>) {
+    paths.insert(
+        "/oauth/authorize".to_string(),
+        json!({ "get": {
+            "tags": ["oauth"],
+            "summary": "Start the authorization code flow (PKCE)",
+            "operationId": "oauthAuthorize",
+            "parameters": [
+                {
+                    "name": "redirect_uri",
+                    "in": "query",
+                    "description": "Where to send the client after login",
+                    "schema": { "type": "string", "format": "uri" },
+                },
+                { "name": "scope", "in": "query", "schema": { "type": "string" } },
+                { "name": "provider", "in": "query", "schema": { "type": "string" } },
+            ],
+            "responses": {
+                "307": { "description": "Redirect to the identity provider" },
+                "400": error_response("Invalid request"),
+            },
+        }}),
+    );
+    paths.insert(
+        "/oauth/callback".to_string(),
+        json!({ "get": {
+            "tags": ["oauth"],
+            "summary": "Identity provider callback",
+            "operationId": "oauthCallback",
+            "parameters": [
+                { "name": "code", "in": "query", "schema": { "type": "string" } },
+                { "name": "state", "in": "query", "schema": { "type": "string" } },
+                { "name": "error", "in": "query", "schema": { "type": "string" } },
+                {
+                    "name": "error_description",
+                    "in": "query",
+                    "schema": { "type": "string" },
+                },
+            ],
+            "responses": {
+                "200": { "description": "Tokens issued" },
+                "307": { "description": "Session established; redirect to the client" },
+                "400": error_response("Missing or unknown state, or provider error"),
+            },
+        }}),
+    );
+    paths.insert(
+        "/oauth/device".to_string(),
+        json!({ "post": {
+            "tags": ["oauth"],
+            "summary": "Start the device authorization flow",
+            "operationId": "oauthDevice",
+            "responses": {
+                "200": { "description": "Device and user codes" },
+                "400": error_response("Device flow not supported by the provider"),
+            },
+        }}),
+    );
+    paths.insert(
+        "/oauth/device/token".to_string(),
+        json!({ "post": {
+            "tags": ["oauth"],
+            "summary": "Poll for device flow tokens",
+            "operationId": "oauthDeviceToken",
+            "requestBody": {
+                "required": true,
+                "content": { "application/x-www-form-urlencoded": { "schema": {
+                    "type": "object",
+                    "required": ["device_code"],
+                    "properties": { "device_code": { "type": "string" } },
+                }}},
+            },
+            "responses": {
+                "200": { "description": "Tokens issued" },
+                "400": { "description": "Authorization pending, slow down, denied or expired" },
+            },
+        }}),
+    );
+    paths.insert(
+        "/oauth/logout".to_string(),
+        json!({ "post": {
+            "tags": ["oauth"],
+            "summary": "Revoke the session of the presented token",
+            "operationId": "oauthLogout",
+            "security": [{ "bearerAuth": [] }],
+            "responses": {
+                "204": { "description": "Session ended and cookie cleared" },
+            },
+        }}),
+    );
+    paths.insert(
+        "/oauth/register".to_string(),
+        json!({ "post": {
+            "tags": ["oauth"],
+            "summary": "Dynamic client registration (RFC 7591)",
+            "operationId": "oauthRegister",
+            "requestBody": {
+                "required": true,
+                "content": { "application/json": { "schema": { "type": "object" } } },
+            },
+            "responses": {
+                "201": { "description": "Client registered" },
+                "400": { "description": "invalid_redirect_uri or invalid_client_metadata" },
+                "404": error_response("Client registration not enabled"),
+            },
+        }}),
+    );
+}
+
+fn authorization_server_paths(paths: &mut Map<String, Value>) {
+    paths.insert(
+        "/oauth/token".to_string(),
+        json!({ "post": {
+            "tags": ["oauth"],
+            "summary": "Issue tokens from the built-in authorization server",
+            "operationId": "oauthToken",
+            "requestBody": {
+                "required": true,
+                "content": { "application/x-www-form-urlencoded": { "schema": {
+                    "type": "object",
+                    "required": ["grant_type"],
+                    "properties": {
+                        "grant_type": {
+                            "type": "string",
+                            "enum": ["password", "client_credentials", "refresh_token"],
+                        },
+                        "username": { "type": "string" },
+                        "password": { "type": "string" },
+                        "client_id": { "type": "string" },
+                        "client_secret": { "type": "string" },
+                        "refresh_token": { "type": "string" },
+                    },
+                }}},
+            },
+            "responses": {
+                "200": { "description": "Tokens issued" },
+                "400": { "description": "invalid_request, invalid_grant or unsupported_grant_type" },
+                "401": { "description": "invalid_client" },
+            },
+        }}),
+    );
+    paths.insert(
+        "/.well-known/oauth-authorization-server".to_string(),
+        json!({ "get": {
+            "tags": ["oauth"],
+            "summary": "Authorization server metadata (RFC 8414)",
+            "operationId": "authorizationServerMetadata",
+            "responses": {
+                "200": { "description": "Authorization server metadata" },
+            },
+        }}),
+    );
+}
+
+fn admin_paths(state: &AppState, paths: &mut Map<String, Value>) {
+    if state.config.admin.enabled() {
+        paths.insert(
+            "/admin/identities".to_string(),
+            json!({ "get": admin_operation(
+                "listIdentities",
+                "List recently active identities",
+                json_response("Active identities", "ObjectList"),
+            )}),
+        );
+        paths.insert(
+            "/admin/identities/{identity_id}".to_string(),
+            json!({
+                "parameters": [path_parameter("identity_id", "Identity ID")],
+                "delete": admin_operation(
+                    "expireIdentity",
+                    "Expire an identity's sessions and cached state",
+                    json!({ "description": "Identity expired" }),
+                ),
+            }),
+        );
+        let mut stream = admin_operation(
+            "streamAudit",
+            "Stream audit entries as they are logged",
+            json!({
+                "description": "Server-Sent Events, one `audit` event per entry",
+                "content": { "text/event-stream": { "schema": { "type": "string" } } },
+            }),
+        );
+        stream["parameters"] = json!([
+            {
+                "name": "event_type",
+                "in": "query",
+                "description": "Comma-separated event types to include (default: all)",
+                "schema": { "type": "string" },
+            },
+            {
+                "name": "identity",
+                "in": "query",
+                "description": "Only entries for this identity",
+                "schema": { "type": "string" },
+            },
+        ]);
+        stream["responses"]["400"] = error_response("Unknown event type");
+        paths.insert("/admin/audit/stream".to_string(), json!({ "get": stream }));
+        let mut switch = admin_operation(
+            "setReadOnly",
+            "Switch read-only mode for every identity",
+            json!({ "description": "Read-only mode switched" }),
+        );
+        switch["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": {
+                "type": "object",
+                "required": ["enabled"],
+                "properties": { "enabled": { "type": "boolean" } },
+            }}},
+        });
+        paths.insert(
+            "/admin/read-only".to_string(),
+            json!({
+                "get": admin_operation(
+                    "getReadOnly",
+                    "Show whether read-only mode applies to every identity",
+                    json!({ "description": "Read-only mode state" }),
+                ),
+                "put": switch,
+            }),
+        );
+    }
+
+    if state.config.admin.enabled() && state.sessions.is_enabled() {
+        let mut list = admin_operation(
+            "listSessions",
+            "List open client sessions",
+            json_response("Open sessions", "ObjectList"),
+        );
+        list["parameters"] = json!([{
+            "name": "identity",
+            "in": "query",
+            "description": "Only sessions of this identity",
+            "schema": { "type": "string" },
+        }]);
+        paths.insert("/admin/sessions".to_string(), json!({ "get": list }));
+        let mut terminate = admin_operation(
+            "terminateSession",
+            "Terminate a client session",
+            json!({ "description": "Session terminated" }),
+        );
+        terminate["responses"]["404"] = error_response("Session not found");
+        paths.insert(
+            "/admin/sessions/{session_id}".to_string(),
+            json!({
+                "parameters": [path_parameter("session_id", "Session ID")],
+                "delete": terminate,
+            }),
+        );
+    }
+
+    if state.config.approval.enabled() {
+        paths.insert(
+            "/admin/approvals".to_string(),
+            json!({ "get": admin_operation(
+                "listApprovals",
+                "List tool calls awaiting approval",
+                json_response("Pending approvals", "ObjectList"),
+            )}),
+        );
+        for (action, summary) in [
+            ("approve", "Approve a held tool call"),
+            ("deny", "Deny a held tool call"),
+        ] {
+            paths.insert(
+                format!("/admin/approvals/{{approval_id}}/{}", action),
+                json!({
+                    "parameters": [path_parameter("approval_id", "Approval request ID")],
+                    "post": admin_operation(
+                        &format!("{}Approval", action),
+                        summary,
+                        json!({ "description": "Decision recorded" }),
+                    ),
+                }),
+            );
+        }
+    }
+}
+
+/// Admin operation: bearer authenticated, limited to admin identities
+fn admin_operation(operation_id: &str, summary: &str, success: Value) -> Value {
+    json!({
+        "tags": ["admin"],
+        "summary": summary,
+        "operationId": operation_id,
+        "security": [{ "bearerAuth": [] }],
+        "responses": {
+            "200": success,
+            "401": error_response("Missing or invalid credentials"),
+            "403": error_response("Identity is not an admin or approver"),
+            "404": error_response("Not found"),
+        },
+    })
+}
+
+fn path_parameter(name: &str, description: &str) -> Value {
+    json!({
+        "name": name,
+        "in": "path",
+        "required": true,
+        "description": description,
+        "schema": { "type": "string" },
+    })
+}
+
+fn json_response(description: &str, schema: &str) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": {
+            "schema": { "$ref": format!("#/components/schemas/{}", schema) },
+        }},
+    })
+}
+
+fn error_response(description: &str) -> Value {
+    json_response(description, "Error")
+}
+
+fn components() -> Value {
+    json!({
+        "securitySchemes": {
+            "bearerAuth": {
+                "type": "http",
+                "scheme": "bearer",
+                "description": "API key, JWT or OAuth access token",
+            },
+        },
+        "schemas": {
+            "Error": {
+                "type": "object",
+                "required": ["error", "error_id"],
+                "properties": {
+                    "error": { "type": "string" },
+                    "error_id": {
+                        "type": "string",
+                        "description": "Correlates the response with gateway logs",
+                    },
+                },
+            },
+            "JsonRpcMessage": {
+                "type": "object",
+                "required": ["jsonrpc"],
+                "properties": {
+                    "jsonrpc": { "const": "2.0" },
+                    "id": { "type": ["string", "integer", "null"] },
+                    "method": { "type": "string" },
+                    "params": {},
+                    "result": {},
+                    "error": {
+                        "type": "object",
+                        "properties": {
+                            "code": { "type": "integer" },
+                            "message": { "type": "string" },
+                            "data": {},
+                        },
+                    },
+                },
+            },
+            "HealthResponse": {
+                "type": "object",
+                "required": ["status", "version", "uptime_secs"],
+                "properties": {
+                    "status": { "type": "string" },
+                    "version": { "type": "string" },
+                    "uptime_secs": { "type": "integer" },
+                    "checks": {
+                        "type": "object",
+                        "description": "Upstream, audit and JWKS detail (only with deep=true)",
+                    },
+                },
+            },
+            "VersionResponse": {
+                "type": "object",
+                "required": [
+                    "name", "version", "build", "features", "license",
+                    "auth_providers", "transports", "limits",
+                ],
+                "properties": {
+                    "name": { "type": "string" },
+                    "version": { "type": "string" },
+                    "build": {
+                        "type": "object",
+                        "description": "Package, MSRV, target and build profile",
+                    },
+                    "features": {
+                        "type": "object",
+                        "properties": {
+                            "compiled": { "type": "array", "items": { "type": "string" } },
+                            "available": { "type": "array", "items": { "type": "string" } },
+                            "in_use": { "type": "array", "items": { "type": "string" } },
+                        },
+                    },
+                    "license": {
+                        "type": "object",
+                        "properties": {
+                            "tier": { "type": "string", "enum": ["Free", "Pro", "Enterprise"] },
+                            "expires_at": { "type": "string", "format": "date-time" },
+                        },
+                    },
+                    "auth_providers": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["type"],
+                            "properties": { "type": { "type": "string" } },
+                        },
+                    },
+                    "transports": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "server": { "type": "string" },
+                                "transport": { "type": "string" },
+                                "healthy": { "type": "boolean" },
+                            },
+                        },
+                    },
+                    "limits": {
+                        "type": "object",
+                        "additionalProperties": { "type": "integer" },
+                    },
+                },
+            },
+            "DiscoveryResponse": {
+                "type": "object",
+                "required": ["name", "version", "protocol_versions", "mode", "transport", "auth"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "version": { "type": "string" },
+                    "protocol_versions": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Supported MCP protocol revisions, newest first",
+                    },
+                    "mode": { "type": "string", "enum": ["single", "multi", "aggregate"] },
+                    "route": { "type": "string" },
+                    "transport": {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "streamable-http" },
+                            "streaming": { "type": "boolean" },
+                            "standalone_stream": { "type": "boolean" },
+                            "max_request_size": { "type": "integer" },
+                        },
+                    },
+                    "auth": {
+                        "type": "object",
+                        "properties": {
+                            "required": { "type": "boolean" },
+                            "providers": { "$ref": "#/components/schemas/ObjectList" },
+                            "protected_resource": {
+                                "$ref": "#/components/schemas/ProtectedResourceMetadata",
+                            },
+                        },
+                    },
+                },
+            },
+            "ProtectedResourceMetadata": {
+                "type": "object",
+                "required": ["resource"],
+                "properties": {
+                    "resource": { "type": "string", "format": "uri" },
+                    "authorization_servers": { "type": "array", "items": { "type": "string" } },
+                    "bearer_methods_supported": { "type": "array", "items": { "type": "string" } },
+                    "scopes_supported": { "type": "array", "items": { "type": "string" } },
+                    "resource_name": { "type": "string" },
+                },
+            },
+            "LiveResponse": {
+                "type": "object",
+                "required": ["status"],
+                "properties": { "status": { "type": "string" } },
+            },
+            "ReadyResponse": {
+                "type": "object",
+                "required": ["ready", "version"],
+                "properties": {
+                    "ready": { "type": "boolean" },
+                    "version": { "type": "string" },
+                    "reason": { "type": "string" },
+                    "unhealthy_upstreams": { "type": "array", "items": { "type": "string" } },
+                },
+            },
+            "ObjectList": {
+                "type": "array",
+                "items": { "type": "object" },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::create_test_state;
+
+    #[test]
+    fn test_document_describes_single_server_mode() {
+        let state = create_test_state();
+        let document = openapi_document(&state);
+
+        assert_eq!(document["openapi"], OPENAPI_VERSION);
+        assert_eq!(document["info"]["version"], env!("CARGO_PKG_VERSION"));
+        let paths = document["paths"].as_object().unwrap();
+        for path in [
+            "/mcp",
+            "/health",
+            "/live",
+            "/ready",
+            "/metrics",
+            "/version",
+            "/openapi.json",
+        ] {
+            assert!(paths.contains_key(path), "missing {}", path);
+        }
+        assert!(!paths.contains_key("/mcp/{server_name}"));
+        assert!(!paths.contains_key("/routes"));
+        assert!(!paths.contains_key("/oauth/authorize"));
+        assert_eq!(
+            paths["/mcp"]["post"]["security"][0]["bearerAuth"],
+            json!([])
+        );
+        assert!(paths["/mcp"]["get"].get("security").is_none());
+        assert!(paths.contains_key("/.well-known/oauth-protected-resource"));
+    }
+
+    #[test]
+    fn test_references_resolve() {
+        let document = openapi_document(&create_test_state());
+        let schemas = document["components"]["schemas"].as_object().unwrap();
+
+        fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+            match value {
+                Value::Object(map) => {
+                    if let Some(Value::String(reference)) = map.get("$ref") {
+                        refs.push(reference.clone());
+                    }
+                    map.values().for_each(|v| collect_refs(v, refs));
+                }
+                Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+                _ => {}
+            }
+        }
+        let mut refs = Vec::new();
+        collect_refs(&document["paths"], &mut refs);
+        assert!(!refs.is_empty());
+        for reference in refs {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            assert!(schemas.contains_key(name), "unresolved {}", reference);
+        }
+    }
+}
@@ -0,0 +1,864 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! `POST /oauth/register`: OAuth 2.0 dynamic client registration (RFC 7591)
+//!
+//! MCP clients register themselves before starting the authorization code
+//! flow. In `proxy` mode the request is checked against the gateway's
+//! redirect URI policy and forwarded to the provider's registration endpoint;
+//! in `local` mode the gateway mints its own client credentials and keeps
+//! them in the `oauth_clients` table. With registration enabled,
+//! `/oauth/authorize` only accepts redirect URIs allowed by
+//! `allowed_redirect_uris`; in `local` mode a redirect URI also needs the
+//! `client_id` of a client that registered it.
+//!
+//! Malformed metadata is rejected with the RFC 7591 section 3.2.2 error codes
+//! (`invalid_redirect_uri`, `invalid_client_metadata`). The endpoint is
+//! unauthenticated, so requests are rate limited per client IP and `local`
+//! mode stops registering at `max_clients`.
+
+use axum::{
+    body::Bytes,
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::{generate_random_string, AppError, AppState};
+use crate::config::{OAuthRegistrationConfig, RegistrationMode};
+use crate::db::NewOAuthClient;
+
+/// Grant types a registered client may use
+const SUPPORTED_GRANT_TYPES: &[&str] = &[
+    "authorization_code",
+    "refresh_token",
+    "urn:ietf:params:oauth:grant-type:device_code",
+];
+
+/// Token endpoint authentication methods a registered client may use
+const SUPPORTED_AUTH_METHODS: &[&str] = &["none", "client_secret_basic", "client_secret_post"];
+
+/// Length of minted client secrets
+const CLIENT_SECRET_LENGTH: usize = 48;
+
+/// Hosts on which a redirect URI pattern may leave the port open
+const LOOPBACK_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+/// A pattern from `allowed_redirect_uris`
+///
+/// Patterns have the form `scheme://host[:port]/path`. The scheme, host and
+/// port are compared exactly, except that the host may start with `*.` to
+/// allow one subdomain label and loopback hosts may use `*` as the port. An
+/// omitted port is the scheme's default. Only the path is a glob, and its `*`
+/// does not match `/` (use `**` for any depth).
+#[derive(Debug)]
+pub struct RedirectUriPattern {
+    scheme: String,
+    host: HostPattern,
+    /// `None` for any port
+    port: Option<u16>,
+    path: glob::Pattern,
+}
+
+#[derive(Debug)]
+enum HostPattern {
+    Exact(String),
+    /// Any single label followed by this suffix, e.g. `.example.com`
+    Subdomain(String),
+}
+
+impl RedirectUriPattern {
+    /// Parse a pattern
+    ///
+    /// # Errors
+    /// Returns a description of the problem when the pattern is malformed.
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let (scheme, rest) = pattern
+            .split_once("://")
+            .ok_or("expected scheme://host/path")?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+        if authority.contains('@') {
+            return Err("user info is not allowed".to_string());
+        }
+        let (host, port) = match authority.rfind(':') {
+            Some(i) if !authority[i..].contains(']') => {
+                (&authority[..i], Some(&authority[i + 1..]))
+            }
+            _ => (authority, None),
+        };
+
+        let (subdomain, host) = match host.strip_prefix("*.") {
+            Some(suffix) => (true, suffix),
+            None => (false, host),
+        };
+        if host.contains('*') {
+            return Err("only a leading '*.' label is allowed in the host".to_string());
+        }
+        // Let the URL parser normalize the host and find the default port
+        let base = url::Url::parse(&format!("{}://{}/", scheme, host))
+            .map_err(|e| format!("invalid scheme or host: {}", e))?;
+        let host = base.host_str().ok_or("missing host")?.to_string();
+
+        let port = match port {
+            Some("*") if !subdomain && LOOPBACK_HOSTS.contains(&host.as_str()) => None,
+            Some("*") => return Err("'*' port is only allowed on loopback hosts".to_string()),
+            Some(port) => Some(
+                port.parse()
+                    .map_err(|_| format!("invalid port '{}'", port))?,
+            ),
+            None => Some(
+                base.port_or_known_default()
+                    .ok_or("scheme has no default port")?,
+            ),
+        };
+        let path = glob::Pattern::new(path).map_err(|e| format!("invalid path glob: {}", e))?;
+
+        Ok(Self {
+            scheme: base.scheme().to_string(),
+            host: if subdomain {
+                HostPattern::Subdomain(format!(".{}", host))
+            } else {
+                HostPattern::Exact(host)
+            },
+            port,
+            path,
+        })
+    }
+
+    /// Whether a parsed redirect URI matches
+    pub fn matches(&self, uri: &url::Url) -> bool {
+        let Some(host) = uri.host_str() else {
+            return false;
+        };
+        let host_matches = match self.host {
+            HostPattern::Exact(ref exact) => host == exact,
+            HostPattern::Subdomain(ref suffix) => host
+                .strip_suffix(suffix.as_str())
+                .is_some_and(|label| !label.is_empty() && !label.contains('.')),
+        };
+        let options = glob::MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+
+        uri.username().is_empty()
+            && uri.password().is_none()
+            && uri.scheme() == self.scheme
+            && host_matches
+            && self
+                .port
+                .map_or(true, |port| uri.port_or_known_default() == Some(port))
+            && self.path.matches_with(uri.path(), options)
+    }
+}
+
+/// Client metadata from a registration request (RFC 7591 section 2)
+///
+/// Fields the gateway does not use are ignored; missing ones take the
+/// defaults the RFC gives them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientMetadata {
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
+    #[serde(default = "default_grant_types")]
+    pub grant_types: Vec<String>,
+    #[serde(default = "default_response_types")]
+    pub response_types: Vec<String>,
+    #[serde(default = "default_auth_method")]
+    pub token_endpoint_auth_method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+fn default_grant_types() -> Vec<String> {
+    vec!["authorization_code".to_string()]
+}
+
+fn default_response_types() -> Vec<String> {
+    vec!["code".to_string()]
+}
+
+fn default_auth_method() -> String {
+    "client_secret_basic".to_string()
+}
+
+/// Response of a successful local registration (RFC 7591 section 3.2.1)
+#[derive(Debug, Serialize)]
+pub struct ClientInformation {
+    pub client_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    pub client_id_issued_at: i64,
+    /// Present with a secret; 0 when it never expires
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret_expires_at: Option<i64>,
+    #[serde(flatten)]
+    pub metadata: ClientMetadata,
+}
+
+/// Registration error (RFC 7591 section 3.2.2)
+#[derive(Debug, PartialEq)]
+pub(super) struct RegistrationError {
+    code: &'static str,
+    description: String,
+}
+
+impl RegistrationError {
+    fn redirect_uri(description: impl Into<String>) -> Self {
+        Self {
+            code: "invalid_redirect_uri",
+            description: description.into(),
+        }
+    }
+
+    fn metadata(description: impl Into<String>) -> Self {
+        Self {
+            code: "invalid_client_metadata",
+            description: description.into(),
+        }
+    }
+}
+
+impl IntoResponse for RegistrationError {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::BAD_REQUEST,
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(serde_json::json!({
+                "error": self.code,
+                "error_description": self.description,
+            })),
+        )
+            .into_response()
+    }
+}
+
+/// Check client metadata against what the gateway supports and the
+/// configured redirect URI patterns
+pub(super) fn validate_metadata(
+    metadata: &ClientMetadata,
+    config: &OAuthRegistrationConfig,
+) -> Result<(), RegistrationError> {
+    if let Some(grant) = metadata
+        .grant_types
+        .iter()
+        .find(|grant| !SUPPORTED_GRANT_TYPES.contains(&grant.as_str()))
+    {
+        return Err(RegistrationError::metadata(format!(
+            "Unsupported grant type '{}'",
+            grant
+        )));
+    }
+    if let Some(response_type) = metadata.response_types.iter().find(|t| *t != "code") {
+        return Err(RegistrationError::metadata(format!(
+            "Unsupported response type '{}'",
+            response_type
+        )));
+    }
+    if !SUPPORTED_AUTH_METHODS.contains(&metadata.token_endpoint_auth_method.as_str()) {
+        return Err(RegistrationError::metadata(format!(
+            "Unsupported token_endpoint_auth_method '{}'",
+            metadata.token_endpoint_auth_method
+        )));
+    }
+
+    let redirect_flow = metadata
+        .grant_types
+        .iter()
+        .any(|grant| grant == "authorization_code");
+    if redirect_flow && metadata.redirect_uris.is_empty() {
+        return Err(RegistrationError::redirect_uri(
+            "redirect_uris is required for the authorization_code grant",
+        ));
+    }
+
+    for uri in &metadata.redirect_uris {
+        let parsed = url::Url::parse(uri).map_err(|_| {
+            RegistrationError::redirect_uri(format!("Invalid redirect URI '{}'", uri))
+        })?;
+        if parsed.fragment().is_some() {
+            return Err(RegistrationError::redirect_uri(format!(
+                "Redirect URI '{}' must not contain a fragment",
+                uri
+            )));
+        }
+        if !redirect_uri_allowed(config, &parsed) {
+            return Err(RegistrationError::redirect_uri(format!(
+                "Redirect URI '{}' is not allowed",
+                uri
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `uri` matches `allowed_redirect_uris` (any URI when it is empty)
+fn redirect_uri_allowed(config: &OAuthRegistrationConfig, uri: &url::Url) -> bool {
+    // Invalid patterns are rejected by config validation
+    config.allowed_redirect_uris.is_empty()
+        || config
+            .allowed_redirect_uris
+            .iter()
+            .filter_map(|pattern| RedirectUriPattern::parse(pattern).ok())
+            .any(|pattern| pattern.matches(uri))
+}
+
+/// Dynamic client registration endpoint
+pub(super) async fn oauth_register(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, AppError> {
+    let config = state
+        .config
+        .auth
+        .oauth
+        .as_ref()
+        .and_then(|oauth| oauth.registration.as_ref())
+        .ok_or_else(|| AppError::not_found("Client registration not enabled"))?;
+
+    let client_ip = state.network_acl.client_ip(addr.ip(), &headers);
+    if let Err(retry_after) = state.registration_limiter.check(client_ip) {
+        tracing::warn!(client_ip = %client_ip, "Client registration rate limit exceeded");
+        return Err(AppError::rate_limited(Some(retry_after)));
+    }
+
+    let request: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(value @ serde_json::Value::Object(_)) => value,
+        _ => {
+            return Ok(
+                RegistrationError::metadata("Request body must be a JSON object").into_response(),
+            )
+        }
+    };
+    let metadata: ClientMetadata = match serde_json::from_value(request.clone()) {
+        Ok(metadata) => metadata,
+        Err(e) => return Ok(RegistrationError::metadata(e.to_string()).into_response()),
+    };
+    if let Err(e) = validate_metadata(&metadata, config) {
+        return Ok(e.into_response());
+    }
+
+    match config.mode {
+        RegistrationMode::Proxy => {
+            let oauth_provider = state
+                .oauth_provider
+                .as_ref()
+                .ok_or_else(|| AppError::internal("OAuth not configured"))?;
+            let (status, body) = oauth_provider
+                .register_client(&request)
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = %e, "Client registration request failed");
+                    AppError::internal("Client registration failed")
+                })?;
+
+            tracing::info!(status, "Proxied OAuth client registration");
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_GATEWAY);
+            Ok((status, [(header::CACHE_CONTROL, "no-store")], Json(body)).into_response())
+        }
+        RegistrationMode::Local => {
+            let db = state
+                .db
+                .as_ref()
+                .ok_or_else(|| AppError::internal("Client registration requires a database"))?;
+            let information = register_local(db, config, metadata).await?;
+
+            tracing::info!(
+                client_id = %information.client_id,
+                client_name = information.metadata.client_name.as_deref().unwrap_or(""),
+                "Registered OAuth client"
+            );
+            Ok((
+                StatusCode::CREATED,
+                [(header::CACHE_CONTROL, "no-store")],
+                Json(information),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Mint credentials for a client and store it
+async fn register_local(
+    db: &crate::db::Database,
+    config: &OAuthRegistrationConfig,
+    metadata: ClientMetadata,
+) -> Result<ClientInformation, AppError> {
+    if config.max_clients > 0 {
+        let registered = db.oauth_clients().count().await.map_err(|e| {
+            tracing::error!(error = %e, "Failed to count registered OAuth clients");
+            AppError::internal("Client registration failed")
+        })?;
+        if registered >= config.max_clients as i64 {
+            tracing::warn!(
+                max_clients = config.max_clients,
+                "Client registration limit reached"
+            );
+            return Err(AppError::forbidden("Client registration limit reached"));
+        }
+    }
+
+    let client_id = format!("mcpg_{}", uuid::Uuid::new_v4().simple());
+    let client_secret = (metadata.token_endpoint_auth_method != "none")
+        .then(|| generate_random_string(CLIENT_SECRET_LENGTH));
+    let secret_hash = client_secret.as_deref().map(crate::cli::hash_api_key);
+    let secret_expires_at = (config.client_secret_ttl_secs > 0 && client_secret.is_some())
+        .then(|| Utc::now() + Duration::seconds(config.client_secret_ttl_secs as i64));
+
+    let redirect_uris = serde_json::json!(metadata.redirect_uris);
+    let stored = serde_json::to_value(&metadata)
+        .map_err(|e| AppError::internal(format!("Failed to encode client metadata: {}", e)))?;
+    let client = db
+        .oauth_clients()
+        .create(NewOAuthClient {
+            client_id: &client_id,
+            client_secret_hash: secret_hash.as_deref(),
+            client_name: metadata.client_name.as_deref(),
+            redirect_uris: &redirect_uris,
+            metadata: &stored,
+            client_secret_expires_at: secret_expires_at,
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to store registered OAuth client");
+            AppError::internal("Client registration failed")
+        })?;
+
+    Ok(ClientInformation {
+        client_id: client.client_id,
+        client_secret_expires_at: client_secret
+            .as_ref()
+            .map(|_| secret_expires_at.map_or(0, |at| at.timestamp())),
+        client_secret,
+        client_id_issued_at: client.created_at.timestamp(),
+        metadata,
+    })
+}
+
+/// Reject an authorization request with a redirect URI registered clients
+/// may not use
+///
+/// With registration enabled, the redirect URI must match
+/// `allowed_redirect_uris` whether or not a client is named. Client IDs are
+/// only checked in `local` mode, where a redirect URI must come with the
+/// `client_id` of a client that registered it; in `proxy` mode they belong
+/// to the provider.
+pub(super) async fn check_client_redirect(
+    state: &AppState,
+    client_id: Option<&str>,
+    redirect_uri: Option<&str>,
+) -> Result<(), AppError> {
+    let Some(registration) = state
+        .config
+        .auth
+        .oauth
+        .as_ref()
+        .and_then(|oauth| oauth.registration.as_ref())
+    else {
+        return Ok(());
+    };
+
+    if let Some(uri) = redirect_uri {
+        let allowed =
+            url::Url::parse(uri).is_ok_and(|parsed| redirect_uri_allowed(registration, &parsed));
+        if !allowed {
+            return Err(AppError::bad_request("redirect_uri is not allowed"));
+        }
+    }
+    if registration.mode != RegistrationMode::Local {
+        return Ok(());
+    }
+    let client_id = match client_id {
+        Some(client_id) => client_id,
+        None if redirect_uri.is_some() => {
+            return Err(AppError::bad_request(
+                "client_id is required with redirect_uri",
+            ))
+        }
+        None => return Ok(()),
+    };
+    let Some(db) = state.db.as_ref() else {
+        return Ok(());
+    };
+
+    let client = db
+        .oauth_clients()
+        .find_by_id(client_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to look up OAuth client");
+            AppError::internal("Client lookup failed")
+        })?
+        .ok_or_else(|| AppError::bad_request("Unknown client_id"))?;
+
+    if let Some(uri) = redirect_uri {
+        let registered: Vec<String> =
+            serde_json::from_value(client.redirect_uris).unwrap_or_default();
+        if !registered.iter().any(|registered| registered == uri) {
+            return Err(AppError::bad_request(
+                "redirect_uri is not registered for this client",
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::OAuthAuthProvider;
+    use crate::config::{OAuthConfig, OAuthProvider};
+    use crate::rate_limit::IpRateLimiter;
+    use crate::server::tests::create_test_state;
+    use serde_json::json;
+
+    fn metadata(value: serde_json::Value) -> ClientMetadata {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn oauth_config(registration: OAuthRegistrationConfig) -> OAuthConfig {
+        OAuthConfig {
+            provider: OAuthProvider::Custom,
+            client_id: "client".to_string(),
+            client_secret: None,
+            authorization_url: Some("https://idp.example/authorize".to_string()),
+            token_url: Some("https://idp.example/token".to_string()),
+            introspection_url: None,
+            userinfo_url: Some("https://idp.example/userinfo".to_string()),
+            device_authorization_url: None,
+            redirect_uri: "http://localhost:3000/oauth/callback".to_string(),
+            scopes: vec![],
+            user_id_claim: "sub".to_string(),
+            scope_tool_mapping: Default::default(),
+            token_cache_ttl_secs: 300,
+            session: None,
+            registration: Some(registration),
+        }
+    }
+
+    async fn register(state: AppState, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        register_from(&Arc::new(state), body).await
+    }
+
+    async fn register_from(
+        state: &Arc<AppState>,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let peer = std::net::SocketAddr::from(([203, 0, 113, 7], 40000));
+        let response = oauth_register(
+            State(state.clone()),
+            ConnectInfo(peer),
+            HeaderMap::new(),
+            Bytes::from(body.to_string()),
+        )
+        .await
+        .into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn test_metadata_defaults() {
+        let metadata = metadata(json!({ "redirect_uris": ["https://app.example/cb"] }));
+        assert_eq!(metadata.grant_types, vec!["authorization_code"]);
+        assert_eq!(metadata.response_types, vec!["code"]);
+        assert_eq!(metadata.token_endpoint_auth_method, "client_secret_basic");
+    }
+
+    #[test]
+    fn test_validate_metadata() {
+        let config = OAuthRegistrationConfig::default();
+        let valid = metadata(json!({
+            "redirect_uris": ["http://127.0.0.1:8976/callback"],
+            "grant_types": ["authorization_code", "refresh_token"],
+            "token_endpoint_auth_method": "none"
+        }));
+        assert!(validate_metadata(&valid, &config).is_ok());
+
+        // Device flow clients have no redirect
+        let device = metadata(json!({
+            "grant_types": ["urn:ietf:params:oauth:grant-type:device_code"]
+        }));
+        assert!(validate_metadata(&device, &config).is_ok());
+
+        let missing = metadata(json!({}));
+        assert_eq!(
+            validate_metadata(&missing, &config).unwrap_err().code,
+            "invalid_redirect_uri"
+        );
+        let fragment = metadata(json!({ "redirect_uris": ["https://app.example/cb#x"] }));
+        assert_eq!(
+            validate_metadata(&fragment, &config).unwrap_err().code,
+            "invalid_redirect_uri"
+        );
+        let grant = metadata(json!({
+            "redirect_uris": ["https://app.example/cb"],
+            "grant_types": ["client_credentials"]
+        }));
+        assert_eq!(
+            validate_metadata(&grant, &config).unwrap_err().code,
+            "invalid_client_metadata"
+        );
+        let method = metadata(json!({
+            "redirect_uris": ["https://app.example/cb"],
+            "token_endpoint_auth_method": "private_key_jwt"
+        }));
+        assert_eq!(
+            validate_metadata(&method, &config).unwrap_err().code,
+            "invalid_client_metadata"
+        );
+    }
+
+    #[test]
+    fn test_validate_allowed_redirect_uris() {
+        let config = OAuthRegistrationConfig {
+            allowed_redirect_uris: vec!["http://127.0.0.1:*/*".to_string()],
+            ..Default::default()
+        };
+        let local = metadata(json!({ "redirect_uris": ["http://127.0.0.1:8976/callback"] }));
+        assert!(validate_metadata(&local, &config).is_ok());
+        let remote = metadata(json!({ "redirect_uris": ["https://evil.example/callback"] }));
+        assert_eq!(
+            validate_metadata(&remote, &config).unwrap_err().code,
+            "invalid_redirect_uri"
+        );
+    }
+
+    #[test]
+    fn test_redirect_uri_pattern_bypasses_rejected() {
+        let config = OAuthRegistrationConfig {
+            allowed_redirect_uris: vec![
+                "http://127.0.0.1:*/**".to_string(),
+                "https://*.example.com/*".to_string(),
+            ],
+            ..Default::default()
+        };
+        let check =
+            |uri: &str| validate_metadata(&metadata(json!({ "redirect_uris": [uri] })), &config);
+
+        assert!(check("http://127.0.0.1:8976/oauth/callback").is_ok());
+        assert!(check("https://app.example.com/callback").is_ok());
+        assert!(check("https://app.example.com:443/callback").is_ok());
+        for uri in [
+            // User info hiding the real host
+            "http://127.0.0.1:80@evil.com/x",
+            "https://app.example.com@evil.com/callback",
+            // Pattern suffix moved into the path
+            "https://evil.com/.example.com/x",
+            // More than one subdomain label, or none
+            "https://a.b.example.com/callback",
+            "https://example.com/callback",
+            "https://evilexample.com/callback",
+            // '*' in the path does not cross '/'
+            "https://app.example.com/a/b",
+            // Scheme and port are exact
+            "http://app.example.com/callback",
+            "https://app.example.com:8443/callback",
+            "https://127.0.0.1:8976/callback",
+        ] {
+            assert_eq!(
+                check(uri).unwrap_err().code,
+                "invalid_redirect_uri",
+                "{}",
+                uri
+            );
+        }
+    }
+
+    #[test]
+    fn test_redirect_uri_pattern_parse() {
+        assert!(RedirectUriPattern::parse("http://localhost:*/callback").is_ok());
+        assert!(RedirectUriPattern::parse("http://[::1]:*/callback").is_ok());
+        assert!(RedirectUriPattern::parse("https://app.example.com/*").is_ok());
+        // Open ports only on loopback
+        assert!(RedirectUriPattern::parse("https://app.example.com:*/*").is_err());
+        assert!(RedirectUriPattern::parse("https://*.example.com:*/*").is_err());
+        // Wildcards only as a leading label
+        assert!(RedirectUriPattern::parse("https://*/*").is_err());
+        assert!(RedirectUriPattern::parse("https://app.*.com/*").is_err());
+        assert!(RedirectUriPattern::parse("https://user@app.example.com/*").is_err());
+        assert!(RedirectUriPattern::parse("app.example.com/*").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_disabled() {
+        let state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        let (status, _) = register(state, json!({})).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_invalid_metadata() {
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        state.config.auth.oauth = Some(oauth_config(OAuthRegistrationConfig::default()));
+
+        let (status, body) = register(state, json!({ "client_name": "No redirect" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_redirect_uri");
+    }
+
+    #[tokio::test]
+    async fn test_register_proxies_to_provider() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/register"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "client_id": "from-provider",
+                "client_name": "Inspector"
+            })))
+            .mount(&server)
+            .await;
+
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        let config = oauth_config(OAuthRegistrationConfig {
+            mode: RegistrationMode::Proxy,
+            registration_url: Some(format!("{}/register", server.uri())),
+            ..Default::default()
+        });
+        state.oauth_provider = Some(Arc::new(OAuthAuthProvider::new(config.clone()).unwrap()));
+        state.config.auth.oauth = Some(config);
+
+        let (status, body) = register(
+            state,
+            json!({
+                "client_name": "Inspector",
+                "redirect_uris": ["http://127.0.0.1:6274/oauth/callback"]
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["client_id"], "from-provider");
+    }
+
+    #[tokio::test]
+    async fn test_register_rate_limited_per_ip() {
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        state.config.auth.oauth = Some(oauth_config(OAuthRegistrationConfig::default()));
+        state.registration_limiter = IpRateLimiter::per_minute(1);
+        let state = Arc::new(state);
+
+        let (status, _) = register_from(&state, json!({})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = register_from(&state, json!({})).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_register_local_without_database() {
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        state.config.auth.oauth = Some(oauth_config(OAuthRegistrationConfig::default()));
+
+        let (status, _) = register(
+            state,
+            json!({ "redirect_uris": ["http://127.0.0.1:6274/oauth/callback"] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_client_redirect_unchecked_without_registration() {
+        let state = create_test_state();
+        assert!(
+            check_client_redirect(&state, Some("anything"), Some("https://app.example/cb"))
+                .await
+                .is_ok()
+        );
+        assert!(
+            check_client_redirect(&state, None, Some("https://app.example/cb"))
+                .await
+                .is_ok()
+        );
+    }
+
+    async fn authorize(
+        state: &Arc<AppState>,
+        client_id: Option<&str>,
+        redirect_uri: Option<&str>,
+    ) -> StatusCode {
+        let peer = std::net::SocketAddr::from(([203, 0, 113, 7], 40000));
+        crate::server::oauth_authorize(
+            State(state.clone()),
+            ConnectInfo(peer),
+            axum::extract::Query(crate::server::OAuthAuthorizeParams {
+                provider: None,
+                redirect_uri: redirect_uri.map(String::from),
+                scope: None,
+                client_id: client_id.map(String::from),
+            }),
+        )
+        .await
+        .into_response()
+        .status()
+    }
+
+    #[tokio::test]
+    async fn test_authorize_checks_redirect_without_client_id() {
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        let config = oauth_config(OAuthRegistrationConfig {
+            allowed_redirect_uris: vec!["http://127.0.0.1:*/**".to_string()],
+            ..Default::default()
+        });
+        state.oauth_provider = Some(Arc::new(OAuthAuthProvider::new(config.clone()).unwrap()));
+        state.config.auth.oauth = Some(config);
+        state.db = Some(crate::db::Database::new("sqlite::memory:").await.unwrap());
+        let state = Arc::new(state);
+
+        let callback = "http://127.0.0.1:6274/oauth/callback";
+        let (status, body) = register_from(&state, json!({ "redirect_uris": [callback] })).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let client_id = body["client_id"].as_str().unwrap();
+
+        // Without a client_id the redirect URI is still checked
+        assert_eq!(
+            authorize(&state, None, Some("https://evil.example/cb")).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            authorize(&state, None, Some(callback)).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            authorize(&state, Some(client_id), Some(callback)).await,
+            StatusCode::TEMPORARY_REDIRECT
+        );
+        // The gateway's own callback needs no client
+        assert_eq!(
+            authorize(&state, None, None).await,
+            StatusCode::TEMPORARY_REDIRECT
+        );
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Route and MCP method of a request, for `[[tracing.sampling]]`
+//!
+//! Sampling is decided when the request span starts, so the attributes the
+//! rules match on have to be known before the request is handled. The route
+//! comes from the path; the method, and the route of an aggregated
+//! `tools/call`, only from the body. The body is therefore read here, but
+//! only when a configured rule needs it. This runs before authentication, so
+//! at most [`MAX_SAMPLED_BODY`] bytes are held; larger and compressed bodies
+//! are sampled without a method.
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, Method, Request},
+    response::{IntoResponse, Response},
+};
+use futures::{future, stream, StreamExt};
+
+use super::{AppError, AppState};
+use crate::transport::Message;
+
+/// Most of an unauthenticated body read to find the request's method
+const MAX_SAMPLED_BODY: usize = 64 * 1024;
+
+/// What the sampling rules are matched against
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct SamplingTarget {
+    /// Server route name (`default` in single-server mode)
+    pub upstream: Option<String>,
+    /// MCP method
+    pub method: Option<String>,
+}
+
+/// Find the route and method of an MCP request when sampling rules are set
+///
+/// Returns the request with its body restored, or the error response when
+/// the body cannot be read.
+pub(super) async fn sampling_target(
+    state: &AppState,
+    request: Request<Body>,
+) -> Result<(SamplingTarget, Request<Body>), Response> {
+    let tracing = &state.config.tracing;
+    let mut target = SamplingTarget::default();
+    if !tracing.enabled || tracing.sampling.is_empty() || request.method() != Method::POST {
+        return Ok((target, request));
+    }
+
+    let path = request.uri().path();
+    if state.router.is_none() {
+        if path != "/mcp" {
+            return Ok((target, request));
+        }
+        target.upstream = Some("default".to_string());
+    } else if state.config.is_aggregated() {
+        if path != "/mcp" {
+            return Ok((target, request));
+        }
+    } else {
+        match path.strip_prefix("/mcp/") {
+            Some(server) if !server.is_empty() && !server.contains('/') => {
+                target.upstream = Some(server.to_string());
+            }
+            _ => return Ok((target, request)),
+        }
+    }
+
+    let needs_body = tracing
+        .sampling
+        .iter()
+        .any(|rule| rule.method.is_some() || (rule.route.is_some() && target.upstream.is_none()));
+    if !needs_body || request.headers().contains_key(header::CONTENT_ENCODING) {
+        return Ok((target, request));
+    }
+
+    let (parts, body) = request.into_parts();
+    let mut frames = body.into_data_stream();
+    let mut bytes = Vec::new();
+    while let Some(frame) = frames.next().await {
+        let frame = frame.map_err(|e| {
+            AppError::bad_request(format!("Failed to read request body: {}", e)).into_response()
+        })?;
+        bytes.extend_from_slice(&frame);
+        if bytes.len() > MAX_SAMPLED_BODY {
+            // The handler reads the rest as it arrives, with the usual size limit
+            let read = stream::once(future::ready(Ok(Bytes::from(bytes))));
+            let body = Body::from_stream(read.chain(frames));
+            return Ok((target, Request::from_parts(parts, body)));
+        }
+    }
+
+    // Batches and invalid JSON are sampled without a method
+    if let Ok(message) = serde_json::from_slice::<Message>(&bytes) {
+        if target.upstream.is_none() {
+            target.upstream = state.router.as_ref().and_then(|router| {
+                let tool = crate::authz::extract_tool_name(&message)?;
+                let (route, _) = router.resolve_namespaced_tool(tool)?;
+                Some(route.config.name.clone())
+            });
+        }
+        target.method = message.method;
+    }
+
+    Ok((target, Request::from_parts(parts, Body::from(bytes))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TracingSamplingRule;
+    use crate::server::tests::create_test_state;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn post(path: &str, body: serde_json::Value) -> Request<Body> {
+        Request::post(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    fn with_rule(state: &mut AppState, method: Option<&str>) {
+        state.config.tracing.enabled = true;
+        state.config.tracing.sampling = vec![TracingSamplingRule {
+            route: Some("default".to_string()),
+            method: method.map(str::to_string),
+            sample_rate: 1.0,
+        }];
+    }
+
+    #[tokio::test]
+    async fn test_no_rules_leaves_request_alone() {
+        let state = create_test_state();
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+        let (target, _) = sampling_target(&state, post("/mcp", body)).await.unwrap();
+        assert_eq!(target, SamplingTarget::default());
+    }
+
+    #[tokio::test]
+    async fn test_method_read_from_body() {
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        with_rule(&mut state, Some("tools/*"));
+
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+        let (target, request) = sampling_target(&state, post("/mcp", body.clone()))
+            .await
+            .unwrap();
+        assert_eq!(target.upstream.as_deref(), Some("default"));
+        assert_eq!(target.method.as_deref(), Some("tools/list"));
+
+        // The handler still sees the whole body
+        let bytes = axum::body::to_bytes(request.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap(),
+            body
+        );
+
+        let (target, _) = sampling_target(&state, post("/health", body))
+            .await
+            .unwrap();
+        assert_eq!(target, SamplingTarget::default());
+    }
+
+    #[tokio::test]
+    async fn test_large_body_sampled_without_method() {
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        with_rule(&mut state, Some("tools/*"));
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": "upload", "arguments": { "data": "x".repeat(MAX_SAMPLED_BODY) } }
+        });
+        let (target, request) = sampling_target(&state, post("/mcp", body.clone()))
+            .await
+            .unwrap();
+        assert_eq!(target.upstream.as_deref(), Some("default"));
+        assert_eq!(target.method, None);
+
+        let bytes = axum::body::to_bytes(request.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap(),
+            body
+        );
+    }
+
+    #[tokio::test]
+    async fn test_route_rules_skip_the_body() {
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        with_rule(&mut state, None);
+
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+        let (target, _) = sampling_target(&state, post("/mcp", body)).await.unwrap();
+        assert_eq!(target.upstream.as_deref(), Some("default"));
+        assert_eq!(target.method, None);
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Stdio passthrough - the gateway as a stdio MCP server
+//!
+//! MCP clients such as Claude Desktop launch servers as subprocesses and speak
+//! newline-delimited JSON-RPC over stdin/stdout. [`StdioBridge`] lets such a
+//! client talk to the guard directly: each line read from stdin is sent
+//! through the same in-process router as an HTTP `POST /mcp`, carrying the
+//! configured bearer token, so authentication, authorization, rate limiting,
+//! scrubbing and audit logging behave exactly as over HTTP.
+//!
+//! Gateway rejections (401/403/429, ...) are turned into JSON-RPC error
+//! responses with the request's id; notifications never get a reply.
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderValue, Method, Request, StatusCode};
+use axum::Router;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tower::ServiceExt;
+
+use super::{build_router, AppState};
+
+/// JSON-RPC parse error
+const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC invalid request
+const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC internal error
+const INTERNAL_ERROR: i64 = -32603;
+/// Implementation-defined server error, used for gateway policy rejections
+const GATEWAY_REJECTED: i64 = -32000;
+
+/// Bridges newline-delimited JSON-RPC on a byte stream to the guard's router
+pub struct StdioBridge {
+    app: Router,
+    path: String,
+    authorization: HeaderValue,
+}
+
+impl StdioBridge {
+    /// Create a bridge that authenticates every message with `token`
+    ///
+    /// Multi-server configs that are not aggregated must name the `server`
+    /// route to forward to; other configs forward to `/mcp`.
+    pub fn new(
+        state: Arc<AppState>,
+        token: &str,
+        server: Option<&str>,
+    ) -> Result<Self, crate::Error> {
+        let path = match (&state.router, server) {
+            (Some(_), _) if state.config.is_aggregated() => "/mcp".to_string(),
+            (Some(router), Some(server)) => {
+                if router.get_transport(&format!("/{}", server)).is_none() {
+                    return Err(crate::Error::Server(format!(
+                        "No server route named '{}'",
+                        server
+                    )));
+                }
+                format!("/mcp/{}", server)
+            }
+            (Some(_), None) => {
+                return Err(crate::Error::Server(
+                    "Multi-server configs need a server route to forward stdio to \
+                     (or enable upstream aggregation)"
+                        .to_string(),
+                ))
+            }
+            (None, Some(_)) => {
+                return Err(crate::Error::Server(
+                    "A server route can only be selected in multi-server configs".to_string(),
+                ))
+            }
+            (None, None) => "/mcp".to_string(),
+        };
+
+        let mut authorization = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|_| crate::Error::Server("Token contains invalid characters".to_string()))?;
+        // SECURITY: Keep the credential out of debug output
+        authorization.set_sensitive(true);
+
+        // The stdio peer is the parent process on this host
+        let peer = ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 0)));
+        let app = build_router(state).layer(axum::Extension(peer));
+
+        Ok(Self {
+            app,
+            path,
+            authorization,
+        })
+    }
+
+    /// Serve messages from the process's stdin, writing responses to stdout
+    pub async fn serve_stdio(&self) -> Result<(), crate::Error> {
+        self.serve(tokio::io::stdin(), tokio::io::stdout()).await
+    }
+
+    /// Serve messages from `reader` until EOF, writing responses to `writer`
+    ///
+    /// Messages are handled one at a time, in order.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> Result<(), crate::Error>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if let Some(response) = self.handle_line(&line).await {
+                writer.write_all(response.as_bytes()).await?;
+                writer.write_all(b"\n").await?;
+                writer.flush().await?;
+            }
+        }
+        tracing::info!("Stdin closed, stopping stdio bridge");
+        Ok(())
+    }
+
+    /// Handle one JSON-RPC line, returning the response line (if any)
+    pub async fn handle_line(&self, line: &str) -> Option<String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+
+        let id = match serde_json::from_str::<Value>(line) {
+            Ok(value) => value.get("id").cloned().filter(|id| !id.is_null()),
+            Err(e) => {
+                return Some(error_line(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("Parse error: {}", e),
+                    None,
+                ))
+            }
+        };
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(&self.path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, self.authorization.clone())
+            .body(Body::from(line.to_string()))
+            .expect("static request parts are valid");
+
+        let response = match self.app.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(never) => match never {},
+        };
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap_or_default();
+
+        // Notifications are acknowledged with an empty body and never answered
+        let Some(id) = id else {
+            if !status.is_success() {
+                tracing::warn!(status = %status, "Notification rejected by gateway");
+            }
+            return None;
+        };
+
+        if status == StatusCode::OK {
+            return Some(String::from_utf8_lossy(&body).into_owned());
+        }
+
+        let details: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+        let message = details
+            .get("error")
+            .and_then(Value::as_str)
+            .or_else(|| status.canonical_reason())
+            .unwrap_or("Gateway error")
+            .to_string();
+        let code = match status {
+            StatusCode::BAD_REQUEST | StatusCode::PAYLOAD_TOO_LARGE => INVALID_REQUEST,
+            s if s.is_server_error() => INTERNAL_ERROR,
+            _ => GATEWAY_REJECTED,
+        };
+        let mut data = serde_json::json!({ "status": status.as_u16() });
+        if let Some(details) = details.as_object() {
+            for (key, value) in details.iter().filter(|(key, _)| *key != "error") {
+                data[key] = value.clone();
+            }
+        }
+        Some(error_line(id, code, &message, Some(data)))
+    }
+}
+
+/// Serialize a JSON-RPC error response
+fn error_line(id: Value, code: i64, message: &str, data: Option<Value>) -> String {
+    let mut error = serde_json::json!({ "code": code, "message": message });
+    if let Some(data) = data {
+        error["data"] = data;
+    }
+    serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": error }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::create_test_state;
+
+    fn bridge() -> StdioBridge {
+        StdioBridge::new(create_test_state(), "not-a-valid-key", None).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rejection_becomes_jsonrpc_error() {
+        let response = bridge()
+            .handle_line(r#"{"jsonrpc":"2.0","id":7,"method":"tools/list"}"#)
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["code"], GATEWAY_REJECTED);
+        assert_eq!(response["error"]["data"]["status"], 401);
+    }
+
+    #[tokio::test]
+    async fn test_forwards_through_guard_pipeline() {
+        use crate::auth::Identity;
+        use crate::mocks::{MockAuthProvider, MockTransport};
+        use crate::transport::Message;
+
+        let transport = Arc::new(MockTransport::new());
+        transport.push_response(Message {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::json!(3)),
+            method: None,
+            params: None,
+            result: Some(serde_json::json!({})),
+            error: None,
+        });
+        let mut state = Arc::try_unwrap(create_test_state()).ok().unwrap();
+        state.transport = Some(transport.clone());
+        state.auth_provider = Arc::new(MockAuthProvider::accepting(Identity {
+            id: "desktop".to_string(),
+            name: None,
+            allowed_tools: Some(vec!["read_file".to_string()]),
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims: std::collections::HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
+        }));
+        let bridge = StdioBridge::new(Arc::new(state), "token", None).unwrap();
+
+        let response = bridge
+            .handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"ping"}"#)
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["id"], 3);
+        assert!(response["result"].is_object());
+
+        // Authorization applies: the call never reaches the upstream
+        let response = bridge
+            .handle_line(
+                r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"delete_file"}}"#,
+            )
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["id"], 4);
+        assert_eq!(response["error"]["data"]["status"], 403);
+        assert_eq!(transport.sent_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_and_notifications() {
+        let bridge = bridge();
+
+        let response = bridge.handle_line("{not json").await.unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert!(response["id"].is_null());
+
+        // Even a rejected notification gets no reply
+        let notification = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        assert!(bridge.handle_line(notification).await.is_none());
+        assert!(bridge.handle_line("   ").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_serve_writes_one_line_per_response() {
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#,
+            "\n",
+        );
+        let mut output = Vec::new();
+        bridge().serve(input.as_bytes(), &mut output).await.unwrap();
+
+        let lines: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["id"], 1);
+        assert_eq!(lines[1]["id"], 2);
+    }
+
+    #[test]
+    fn test_server_route_requires_multi_server() {
+        let result = StdioBridge::new(create_test_state(), "token", Some("github"));
+        assert!(result.is_err());
+    }
+}
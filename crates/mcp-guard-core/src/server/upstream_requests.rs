@@ -0,0 +1,287 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Upstream-initiated requests over the client's HTTP connection
+//!
+//! A client that accepts `text/event-stream` may be answered with a stream
+//! instead of a single JSON body, as in MCP's Streamable HTTP transport:
+//! requests and notifications the upstream sends during the call become
+//! events, and the call's response is the last one. The answer stays plain
+//! JSON unless the upstream sends something first.
+//!
+//! One upstream serves many clients, so forwarded requests get gateway-wide
+//! unique IDs. The client POSTs its response to the same endpoint; it is
+//! only accepted from the identity the request went to, and is handed to the
+//! exchange still waiting on the upstream.
+
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use dashmap::DashMap;
+use futures::stream::{self, StreamExt};
+use serde_json::Value;
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+use super::jsonrpc_errors::to_jsonrpc_error;
+use super::{AppError, AppState};
+use crate::transport::{with_upstream_sink, Message, UpstreamMessage};
+
+/// Upstream messages buffered while the client reads the stream
+const UPSTREAM_MESSAGE_BUFFER: usize = 32;
+
+/// Upstream request waiting for the client's response
+struct PendingRequest {
+    /// Server route name (`default` for the `/mcp` endpoint of single-server
+    /// and aggregate mode)
+    upstream: String,
+    identity_id: String,
+    /// ID the upstream gave the request
+    id: Option<Value>,
+    reply: oneshot::Sender<Message>,
+}
+
+/// Upstream requests forwarded to clients, by gateway-assigned ID
+#[derive(Default)]
+pub struct UpstreamRequests {
+    pending: DashMap<String, PendingRequest>,
+}
+
+impl UpstreamRequests {
+    /// Number of forwarded requests the client has not answered yet
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Give a forwarded request a unique ID and remember where it came from
+    ///
+    /// Notifications are returned unchanged.
+    fn register(&self, upstream: &str, identity_id: &str, message: UpstreamMessage) -> Message {
+        let UpstreamMessage { mut message, reply } = message;
+        let Some(reply) = reply else {
+            return message;
+        };
+        // Requests of calls that have finished can no longer be answered
+        self.pending.retain(|_, pending| !pending.reply.is_closed());
+
+        let id = format!("mcp-guard-{}", uuid::Uuid::new_v4());
+        let upstream_id = message.id.replace(Value::String(id.clone()));
+        self.pending.insert(
+            id,
+            PendingRequest {
+                upstream: upstream.to_string(),
+                identity_id: identity_id.to_string(),
+                id: upstream_id,
+                reply,
+            },
+        );
+        message
+    }
+
+    /// Pass a client's response on to the upstream request it answers
+    fn answer(
+        &self,
+        upstream: &str,
+        identity_id: &str,
+        mut message: Message,
+    ) -> Result<(), AppError> {
+        let key =
+            message.id.as_ref().and_then(Value::as_str).ok_or_else(|| {
+                AppError::bad_request("Response does not answer a forwarded request")
+            })?;
+        // Another identity's request looks the same as one that does not exist
+        let (_, pending) = self
+            .pending
+            .remove_if(key, |_, pending| {
+                pending.upstream == upstream && pending.identity_id == identity_id
+            })
+            .ok_or_else(|| AppError::not_found("No pending upstream request with this ID"))?;
+
+        message.id = pending.id;
+        pending
+            .reply
+            .send(message)
+            .map_err(|_| AppError::conflict("The call this request belongs to has finished"))
+    }
+}
+
+/// Whether a client accepts a streamed answer
+pub(crate) fn accepts_event_stream(headers: &axum::http::HeaderMap) -> bool {
+    headers
+        .get(axum::http::header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+}
+
+/// Deliver a client's response to an upstream request (202 Accepted)
+pub(crate) fn answer_upstream_request(
+    state: &AppState,
+    upstream: &str,
+    identity_id: &str,
+    message: Message,
+) -> Result<Response, AppError> {
+    state
+        .upstream_requests
+        .answer(upstream, identity_id, message)?;
+    Ok(StatusCode::ACCEPTED.into_response())
+}
+
+/// Run `forward`, streaming any upstream-initiated messages to the client
+///
+/// Returns `forward`'s own response if it completes before the upstream
+/// sends anything.
+pub(crate) async fn stream_upstream_requests<F>(
+    state: Arc<AppState>,
+    upstream: String,
+    identity_id: String,
+    request_id: Option<Value>,
+    forward: F,
+) -> Result<Response, AppError>
+where
+    F: Future<Output = Result<Response, AppError>> + Send + 'static,
+{
+    let (sink, mut messages) = mpsc::channel(UPSTREAM_MESSAGE_BUFFER);
+    let mut forward = Box::pin(with_upstream_sink(sink, forward));
+    // A message sent before the response still goes out first
+    let first = tokio::select! {
+        biased;
+        Some(message) = messages.recv() => message,
+        result = &mut forward => return result,
+    };
+
+    let first = state
+        .upstream_requests
+        .register(&upstream, &identity_id, first);
+    let rest = stream::unfold(Some((forward, messages)), move |streaming| {
+        let state = state.clone();
+        let upstream = upstream.clone();
+        let identity_id = identity_id.clone();
+        let request_id = request_id.clone();
+        async move {
+            let (mut forward, mut messages) = streaming?;
+            tokio::select! {
+                biased;
+                Some(message) = messages.recv() => {
+                    let message = state
+                        .upstream_requests
+                        .register(&upstream, &identity_id, message);
+                    Some((event(&message), Some((forward, messages))))
+                }
+                result = &mut forward => {
+                    Some((final_event(result, request_id).await, None))
+                }
+            }
+        }
+    });
+
+    let events = stream::once(async move { event(&first) }).chain(rest);
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+fn event(message: &Message) -> Result<Event, Infallible> {
+    Ok(Event::default().data(serde_json::to_string(message).unwrap_or_default()))
+}
+
+/// Last event of a stream: the call's response
+///
+/// The status line went out with the first event, so a failed call is
+/// reported as a JSON-RPC error instead.
+async fn final_event(
+    result: Result<Response, AppError>,
+    request_id: Option<Value>,
+) -> Result<Event, Infallible> {
+    let response = result.unwrap_or_else(IntoResponse::into_response);
+    let response = to_jsonrpc_error(response, request_id.unwrap_or_default()).await;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap_or_default();
+    Ok(Event::default().data(String::from_utf8_lossy(&body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::AppErrorKind;
+    use serde_json::json;
+
+    fn sampling(reply: oneshot::Sender<Message>) -> UpstreamMessage {
+        UpstreamMessage {
+            message: Message::request(7, "sampling/createMessage", Some(json!({}))),
+            reply: Some(reply),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_responses_reach_the_upstream_request() {
+        let requests = UpstreamRequests::default();
+        let (reply, upstream) = oneshot::channel();
+        let forwarded = requests.register("default", "alice", sampling(reply));
+        let id = forwarded.id.clone().unwrap();
+        assert_ne!(id, json!(7));
+        assert_eq!(requests.pending_count(), 1);
+
+        // Only the identity the request went to, on the same upstream, can answer
+        let answer = Message::response(id.clone(), json!({"role": "assistant"}));
+        assert!(requests.answer("default", "bob", answer.clone()).is_err());
+        assert!(requests.answer("other", "alice", answer.clone()).is_err());
+
+        requests.answer("default", "alice", answer.clone()).unwrap();
+        assert_eq!(upstream.await.unwrap().id, Some(json!(7)));
+        assert!(requests.answer("default", "alice", answer).is_err());
+        assert_eq!(requests.pending_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_requests_of_finished_calls_are_dropped() {
+        let requests = UpstreamRequests::default();
+        let (reply, upstream) = oneshot::channel();
+        let forwarded = requests.register("default", "alice", sampling(reply));
+        drop(upstream);
+
+        let answer = Message::response(forwarded.id.unwrap(), json!({}));
+        let err = requests.answer("default", "alice", answer).unwrap_err();
+        assert!(matches!(err.kind, AppErrorKind::Conflict(_)));
+
+        let (reply, _upstream) = oneshot::channel();
+        requests.register("default", "alice", sampling(reply));
+        let (reply, upstream) = oneshot::channel();
+        requests.register("default", "alice", sampling(reply));
+        drop(upstream);
+        let (reply, _upstream) = oneshot::channel();
+        requests.register("default", "alice", sampling(reply));
+        assert_eq!(requests.pending_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_only_when_upstream_sends_first() {
+        let state = crate::server::tests::create_test_state();
+        let response = stream_upstream_requests(
+            state,
+            "default".to_string(),
+            "alice".to_string(),
+            Some(json!(1)),
+            async { Ok(StatusCode::OK.into_response()) },
+        )
+        .await
+        .unwrap();
+        assert!(response.headers().get("content-type").is_none());
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! `/version`: what a running gateway is and how it is set up
+//!
+//! The `mcp-guard version` output as JSON, extended with what only the
+//! running process knows (license expiry, configured auth providers, the
+//! upstream transports in use) and the limits compiled into the binary, for
+//! fleet inventory tooling. Only kinds and names are reported: no
+//! credentials, key hashes or upstream addresses.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::{AppState, MAX_PENDING_OAUTH_STATES};
+use crate::config::{AuthConfig, JwtMode, OAuthProvider};
+use crate::tier::{self, Tier};
+
+/// Response of `GET /version`
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub build: BuildInfo,
+    pub features: FeatureInfo,
+    pub license: LicenseInfo,
+    pub auth_providers: Vec<AuthProviderInfo>,
+    pub transports: Vec<TransportInfo>,
+    pub limits: Limits,
+}
+
+/// How the binary was built
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    pub package: &'static str,
+    pub description: &'static str,
+    pub license: &'static str,
+    pub repository: &'static str,
+    /// Minimum supported Rust version
+    pub rust_version: &'static str,
+    pub target_os: &'static str,
+    pub target_arch: &'static str,
+    /// `release` or `debug`
+    pub profile: &'static str,
+}
+
+/// Tier features compiled in and in use
+#[derive(Debug, Serialize)]
+pub struct FeatureInfo {
+    /// Cargo features the binary was built with
+    pub compiled: Vec<&'static str>,
+    /// Features of the compiled tier (see `tier::FEATURES`)
+    pub available: Vec<&'static str>,
+    /// Paid features the configuration turns on
+    pub in_use: Vec<&'static str>,
+}
+
+/// License tier of the binary
+#[derive(Debug, Serialize)]
+pub struct LicenseInfo {
+    pub tier: &'static str,
+    /// When the license key stops being accepted, if one was loaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Configured authentication provider
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthProviderInfo {
+    ApiKey {
+        keys: usize,
+    },
+    Jwt {
+        mode: &'static str,
+    },
+    #[serde(rename = "oauth")]
+    OAuth {
+        provider: OAuthProvider,
+    },
+    Mtls,
+    Saml,
+    Hmac,
+    AuthorizationServer {
+        issuer: String,
+    },
+    Custom {
+        name: String,
+        provider: String,
+    },
+}
+
+/// Upstream transport serving a route
+#[derive(Debug, Serialize)]
+pub struct TransportInfo {
+    /// Server route name (`default` in single-server mode)
+    pub server: String,
+    pub transport: &'static str,
+    pub healthy: bool,
+}
+
+/// Limits compiled into the binary
+#[derive(Debug, Serialize)]
+pub struct Limits {
+    /// Largest message read from an upstream, in bytes
+    pub max_message_size: usize,
+    /// Largest bearer JWT accepted, in bytes
+    pub max_jwt_size: usize,
+    /// OAuth authorization flows in progress at once
+    pub max_pending_oauth_states: usize,
+    /// Longest string field kept in an audit entry
+    pub max_audit_field_len: usize,
+    /// Highest `retry.max_attempts` accepted
+    pub max_retry_attempts: u32,
+    /// Highest upstream timeout accepted, in seconds
+    pub max_timeout_secs: u64,
+}
+
+impl VersionInfo {
+    /// Describe the gateway serving `state`
+    pub fn new(state: &AppState) -> Self {
+        let compiled = [
+            ("pro", cfg!(feature = "pro")),
+            ("enterprise", cfg!(feature = "enterprise")),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect();
+
+        Self {
+            name: "mcp-guard",
+            version: env!("CARGO_PKG_VERSION"),
+            build: BuildInfo {
+                package: env!("CARGO_PKG_NAME"),
+                description: env!("CARGO_PKG_DESCRIPTION"),
+                license: env!("CARGO_PKG_LICENSE"),
+                repository: env!("CARGO_PKG_REPOSITORY"),
+                rust_version: env!("CARGO_PKG_RUST_VERSION"),
+                target_os: std::env::consts::OS,
+                target_arch: std::env::consts::ARCH,
+                profile: if cfg!(debug_assertions) {
+                    "debug"
+                } else {
+                    "release"
+                },
+            },
+            features: FeatureInfo {
+                compiled,
+                available: tier::available_features(),
+                in_use: tier::gated_features(&state.config)
+                    .iter()
+                    .map(|feature| feature.name())
+                    .collect(),
+            },
+            license: LicenseInfo {
+                tier: Tier::compiled().as_str(),
+                expires_at: state.license_expires_at.map(DateTime::<Utc>::from),
+            },
+            auth_providers: auth_providers(&state.config.auth),
+            transports: transports(state),
+            limits: Limits {
+                max_message_size: crate::transport::MAX_MESSAGE_SIZE,
+                max_jwt_size: crate::auth::MAX_JWT_CLAIMS_SIZE,
+                max_pending_oauth_states: MAX_PENDING_OAUTH_STATES,
+                max_audit_field_len: crate::audit::MAX_AUDIT_FIELD_LEN,
+                max_retry_attempts: crate::config::MAX_RETRY_ATTEMPTS,
+                max_timeout_secs: crate::config::MAX_TIMEOUT_SECS,
+            },
+        }
+    }
+}
+
+pub(super) fn auth_providers(auth: &AuthConfig) -> Vec<AuthProviderInfo> {
+    let mut providers = Vec::new();
+    if !auth.api_keys.is_empty() {
+        providers.push(AuthProviderInfo::ApiKey {
+            keys: auth.api_keys.len(),
+        });
+    }
+    for jwt in &auth.jwt {
+        let mode = match jwt.mode {
+            JwtMode::Simple { .. } => "simple",
+            JwtMode::Jwks { .. } => "jwks",
+        };
+        providers.push(AuthProviderInfo::Jwt { mode });
+    }
+    if let Some(ref oauth) = auth.oauth {
+        providers.push(AuthProviderInfo::OAuth {
+            provider: oauth.provider.clone(),
+        });
+    }
+    if auth.mtls.as_ref().is_some_and(|mtls| mtls.enabled) {
+        providers.push(AuthProviderInfo::Mtls);
+    }
+    if auth.saml.is_some() {
+        providers.push(AuthProviderInfo::Saml);
+    }
+    if auth.hmac.is_some() {
+        providers.push(AuthProviderInfo::Hmac);
+    }
+    if let Some(ref server) = auth.authorization_server {
+        providers.push(AuthProviderInfo::AuthorizationServer {
+            issuer: server.issuer.clone(),
+        });
+    }
+    providers.extend(auth.custom.iter().map(|custom| AuthProviderInfo::Custom {
+        name: custom.name.clone(),
+        provider: custom.provider.clone(),
+    }));
+    providers
+}
+
+fn transports(state: &AppState) -> Vec<TransportInfo> {
+    if let Some(ref router) = state.router {
+        return router
+            .route_transports()
+            .into_iter()
+            .map(|(server, transport, healthy)| TransportInfo {
+                server: server.to_string(),
+                transport,
+                healthy,
+            })
+            .collect();
+    }
+    state
+        .transport
+        .iter()
+        .map(|transport| TransportInfo {
+            server: "default".to_string(),
+            transport: transport.transport_type(),
+            healthy: transport.is_healthy(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ApiKeyConfig, CustomAuthConfig};
+    use crate::server::tests::create_test_state;
+    use serde_json::json;
+
+    #[test]
+    fn test_version_info() {
+        let state = create_test_state();
+        let info = serde_json::to_value(VersionInfo::new(&state)).unwrap();
+
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(info["license"]["tier"], Tier::compiled().as_str());
+        assert!(info["license"].get("expires_at").is_none());
+        assert!(info["features"]["available"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("api_key_auth")));
+        assert_eq!(info["transports"][0]["server"], "default");
+        assert_eq!(
+            info["limits"]["max_message_size"],
+            crate::transport::MAX_MESSAGE_SIZE
+        );
+    }
+
+    #[test]
+    fn test_auth_providers_report_kinds_only() {
+        let mut auth = AuthConfig::default();
+        auth.api_keys.push(
+            serde_json::from_value::<ApiKeyConfig>(json!({ "id": "a", "key_hash": "secret" }))
+                .unwrap(),
+        );
+        auth.custom.push(CustomAuthConfig {
+            name: "corp".to_string(),
+            provider: "ldap".to_string(),
+            settings: json!({ "bind_password": "hunter2" }),
+        });
+
+        let providers = serde_json::to_value(auth_providers(&auth)).unwrap();
+        assert_eq!(
+            providers,
+            json!([
+                { "type": "api_key", "keys": 1 },
+                { "type": "custom", "name": "corp", "provider": "ldap" },
+            ])
+        );
+    }
+}
@@ -0,0 +1,364 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Client session registry for mcp-guard
+//!
+//! With `[sessions]` enabled, a client's `initialize` request opens a session
+//! and the gateway returns its ID in the `Mcp-Session-Id` header. Later
+//! requests carry the header; the registry checks that the session belongs
+//! to the caller and has not ended, and records the activity.
+//!
+//! A session ends after `idle_timeout_secs` without a request, after
+//! `max_lifetime_secs`, or when an admin terminates it. Ended sessions are
+//! forgotten, so their IDs are answered like unknown ones and the client has
+//! to initialize again. Sessions live in process memory, per replica.
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::SessionsConfig;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Header carrying the session ID, in responses to `initialize` and in later
+/// requests
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Why a session could not be opened or used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionError {
+    /// No open session with this ID for the caller
+    NotFound,
+    /// The session ended on its idle timeout or maximum lifetime
+    Expired,
+    /// The identity already has `max_per_identity` open sessions
+    LimitReached,
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Session not found"),
+            Self::Expired => write!(f, "Session expired"),
+            Self::LimitReached => write!(f, "Too many open sessions for this identity"),
+        }
+    }
+}
+
+/// ID the gateway will issue if the request turns out to be `initialize`
+///
+/// Set as a request extension for requests without a session header.
+#[derive(Debug, Clone)]
+pub struct PendingSession(pub String);
+
+/// Snapshot of an open session
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientSession {
+    pub id: String,
+    pub identity_id: String,
+    /// `clientInfo.name` from the `initialize` request
+    pub client_name: Option<String>,
+    /// `clientInfo.version` from the `initialize` request
+    pub client_version: Option<String>,
+    /// Unix timestamp of the `initialize` request
+    pub created_at: u64,
+    /// Unix timestamp of the latest request
+    pub last_seen: u64,
+    /// Requests made in the session, including `initialize`
+    pub request_count: u64,
+}
+
+struct SessionRecord {
+    snapshot: ClientSession,
+    created_at: Instant,
+    last_seen_at: Instant,
+}
+
+/// Registry of open client sessions
+pub struct SessionRegistry {
+    sessions: DashMap<String, SessionRecord>,
+    enabled: bool,
+    idle_timeout: Duration,
+    max_lifetime: Option<Duration>,
+    max_per_identity: usize,
+}
+
+impl Default for SessionRegistry {
+    fn default() -> Self {
+        Self::new(&SessionsConfig::default())
+    }
+}
+
+impl std::fmt::Debug for SessionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionRegistry")
+            .field("sessions", &self.sessions.len())
+            .field("enabled", &self.enabled)
+            .field("idle_timeout", &self.idle_timeout)
+            .field("max_lifetime", &self.max_lifetime)
+            .field("max_per_identity", &self.max_per_identity)
+            .finish()
+    }
+}
+
+impl SessionRegistry {
+    /// Create an empty registry
+    pub fn new(config: &SessionsConfig) -> Self {
+        Self {
+            sessions: DashMap::new(),
+            enabled: config.enabled,
+            idle_timeout: Duration::from_secs(config.idle_timeout_secs),
+            max_lifetime: (config.max_lifetime_secs > 0)
+                .then(|| Duration::from_secs(config.max_lifetime_secs)),
+            max_per_identity: config.max_per_identity,
+        }
+    }
+
+    /// Whether the gateway issues and enforces session IDs
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Generate an ID for a new session
+    pub fn new_id() -> String {
+        uuid::Uuid::new_v4().simple().to_string()
+    }
+
+    /// Open a session for an `initialize` request
+    ///
+    /// `client_info` is the request's `params.clientInfo`.
+    pub fn open(
+        &self,
+        id: &str,
+        identity_id: &str,
+        client_info: Option<&serde_json::Value>,
+    ) -> Result<(), SessionError> {
+        self.cleanup_expired();
+        if self.max_per_identity > 0 && self.count_for(identity_id) >= self.max_per_identity {
+            return Err(SessionError::LimitReached);
+        }
+
+        let info = |key: &str| {
+            client_info
+                .and_then(|info| info.get(key))
+                .and_then(|value| value.as_str())
+                .map(String::from)
+        };
+        let now = unix_now();
+        self.sessions.insert(
+            id.to_string(),
+            SessionRecord {
+                snapshot: ClientSession {
+                    id: id.to_string(),
+                    identity_id: identity_id.to_string(),
+                    client_name: info("name"),
+                    client_version: info("version"),
+                    created_at: now,
+                    last_seen: now,
+                    request_count: 1,
+                },
+                created_at: Instant::now(),
+                last_seen_at: Instant::now(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Record a request in a session
+    ///
+    /// Sessions of other identities are reported as not found.
+    pub fn touch(&self, id: &str, identity_id: &str) -> Result<(), SessionError> {
+        let mut record = self
+            .sessions
+            .get_mut(id)
+            .filter(|record| record.snapshot.identity_id == identity_id)
+            .ok_or(SessionError::NotFound)?;
+        if self.is_expired(&record) {
+            drop(record);
+            self.sessions.remove(id);
+            return Err(SessionError::Expired);
+        }
+        record.snapshot.last_seen = unix_now();
+        record.snapshot.request_count += 1;
+        record.last_seen_at = Instant::now();
+        Ok(())
+    }
+
+    /// Whether a session is open
+    pub fn contains(&self, id: &str) -> bool {
+        self.sessions
+            .get(id)
+            .is_some_and(|record| !self.is_expired(&record))
+    }
+
+    /// Open sessions, optionally of one identity, most recently used first
+    pub fn list(&self, identity_id: Option<&str>) -> Vec<ClientSession> {
+        let mut sessions: Vec<ClientSession> = self
+            .sessions
+            .iter()
+            .filter(|record| !self.is_expired(record))
+            .filter(|record| identity_id.map_or(true, |id| record.snapshot.identity_id == id))
+            .map(|record| record.snapshot.clone())
+            .collect();
+        sessions.sort_by(|a, b| b.last_seen.cmp(&a.last_seen).then(a.id.cmp(&b.id)));
+        sessions
+    }
+
+    /// Number of open sessions
+    pub fn active_count(&self) -> usize {
+        self.sessions
+            .iter()
+            .filter(|record| !self.is_expired(record))
+            .count()
+    }
+
+    /// End a session; returns whether it was open
+    pub fn terminate(&self, id: &str) -> bool {
+        self.sessions
+            .remove(id)
+            .is_some_and(|(_, record)| !self.is_expired(&record))
+    }
+
+    /// End every session of an identity; returns how many were open
+    pub fn terminate_identity(&self, identity_id: &str) -> usize {
+        let mut terminated = 0;
+        self.sessions.retain(|_, record| {
+            if record.snapshot.identity_id != identity_id {
+                return true;
+            }
+            if !self.is_expired(record) {
+                terminated += 1;
+            }
+            false
+        });
+        terminated
+    }
+
+    /// Forget sessions that ended on their idle timeout or maximum lifetime
+    pub fn cleanup_expired(&self) {
+        self.sessions.retain(|_, record| !self.is_expired(record));
+    }
+
+    fn count_for(&self, identity_id: &str) -> usize {
+        self.sessions
+            .iter()
+            .filter(|record| record.snapshot.identity_id == identity_id)
+            .count()
+    }
+
+    fn is_expired(&self, record: &SessionRecord) -> bool {
+        record.last_seen_at.elapsed() >= self.idle_timeout
+            || self
+                .max_lifetime
+                .is_some_and(|max_lifetime| record.created_at.elapsed() >= max_lifetime)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn registry(idle_timeout_secs: u64, max_per_identity: usize) -> SessionRegistry {
+        SessionRegistry::new(&SessionsConfig {
+            enabled: true,
+            idle_timeout_secs,
+            max_lifetime_secs: 0,
+            max_per_identity,
+        })
+    }
+
+    #[test]
+    fn test_open_touch_and_list() {
+        let registry = registry(60, 0);
+        let client = json!({ "name": "inspector", "version": "1.2.0" });
+        registry.open("s1", "alice", Some(&client)).unwrap();
+        registry.open("s2", "bob", None).unwrap();
+
+        assert!(registry.touch("s1", "alice").is_ok());
+        assert_eq!(registry.touch("s1", "bob"), Err(SessionError::NotFound));
+        assert_eq!(registry.touch("s3", "alice"), Err(SessionError::NotFound));
+
+        let sessions = registry.list(Some("alice"));
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].client_name.as_deref(), Some("inspector"));
+        assert_eq!(sessions[0].client_version.as_deref(), Some("1.2.0"));
+        assert_eq!(sessions[0].request_count, 2);
+        assert_eq!(registry.list(None).len(), 2);
+        assert_eq!(registry.active_count(), 2);
+    }
+
+    #[test]
+    fn test_max_per_identity() {
+        let registry = registry(60, 2);
+        registry.open("s1", "alice", None).unwrap();
+        registry.open("s2", "alice", None).unwrap();
+        assert_eq!(
+            registry.open("s3", "alice", None),
+            Err(SessionError::LimitReached)
+        );
+        assert!(registry.open("s4", "bob", None).is_ok());
+
+        assert!(registry.terminate("s1"));
+        assert!(!registry.terminate("s1"));
+        assert!(registry.open("s3", "alice", None).is_ok());
+        assert_eq!(registry.terminate_identity("alice"), 2);
+        assert_eq!(registry.list(None).len(), 1);
+    }
+
+    #[test]
+    fn test_idle_sessions_expire() {
+        let registry = registry(60, 1);
+        registry.open("s1", "alice", None).unwrap();
+        registry.sessions.get_mut("s1").unwrap().last_seen_at -= Duration::from_secs(61);
+
+        assert!(!registry.contains("s1"));
+        assert!(registry.list(None).is_empty());
+        // Expired sessions don't count against the limit
+        assert!(registry.open("s2", "alice", None).is_ok());
+        assert!(registry.sessions.get("s1").is_none());
+    }
+
+    #[test]
+    fn test_max_lifetime() {
+        let registry = SessionRegistry::new(&SessionsConfig {
+            enabled: true,
+            idle_timeout_secs: 60,
+            max_lifetime_secs: 3600,
+            max_per_identity: 0,
+        });
+        registry.open("s1", "alice", None).unwrap();
+        registry.sessions.get_mut("s1").unwrap().created_at -= Duration::from_secs(3600);
+
+        assert_eq!(registry.touch("s1", "alice"), Err(SessionError::Expired));
+        assert_eq!(registry.touch("s1", "alice"), Err(SessionError::NotFound));
+    }
+}
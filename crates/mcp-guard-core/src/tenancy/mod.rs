@@ -0,0 +1,355 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Multi-tenancy for mcp-guard
+//!
+//! A [`TenantRegistry`] assigns each authenticated identity to a tenant and
+//! applies the tenant's isolation policy on top of the identity's own:
+//!
+//! - `allowed_tools` is narrowed so an identity only keeps tools its tenant
+//!   also allows (authorization and list filtering then work unchanged)
+//! - `servers` limits which upstream servers the tenant's identities reach; in
+//!   aggregate mode this becomes a `<server>.*` tool restriction
+//! - `rate_limit` is one token bucket shared by all of the tenant's identities
+//!
+//! Identities without a tenant pass through untouched unless
+//! `tenancy.required` is set. An identity naming a tenant that is not
+//! configured is always rejected, so a forged or stale claim never falls
+//! back to the flat namespace.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::auth::Identity;
+use crate::authz::is_allowed;
+use crate::config::{RateLimitConfig, TenancyConfig, TenantConfig};
+use crate::rate_limit::{RateLimitResult, RateLimitService};
+use crate::router::namespaced_tool_name;
+
+/// Why an identity could not be assigned to a tenant
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TenantError {
+    #[error("Identity '{0}' does not belong to a tenant")]
+    Missing(String),
+
+    #[error("Identity '{0}' names unknown tenant '{1}'")]
+    Unknown(String, String),
+}
+
+/// Compiled tenant policies
+pub struct TenantRegistry {
+    claim: String,
+    required: bool,
+    /// Aggregate mode: tools are namespaced by server
+    namespaced: bool,
+    tenants: HashMap<String, TenantConfig>,
+    /// Shared per-tenant buckets, keyed by tenant ID
+    rate_limiter: RateLimitService,
+}
+
+impl std::fmt::Debug for TenantRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TenantRegistry")
+            .field("claim", &self.claim)
+            .field("required", &self.required)
+            .field("tenant_count", &self.tenants.len())
+            .finish()
+    }
+}
+
+impl Default for TenantRegistry {
+    fn default() -> Self {
+        Self::new(&TenancyConfig::default())
+    }
+}
+
+impl TenantRegistry {
+    /// Build the registry from configuration
+    pub fn new(config: &TenancyConfig) -> Self {
+        Self {
+            claim: config.claim.clone(),
+            required: config.required,
+            namespaced: false,
+            tenants: config
+                .tenants
+                .iter()
+                .map(|t| (t.id.clone(), t.clone()))
+                .collect(),
+            rate_limiter: RateLimitService::new(&RateLimitConfig {
+                enabled: true,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Enforce `servers` through namespaced tool names (aggregate mode)
+    pub fn with_namespaced_tools(mut self, namespaced: bool) -> Self {
+        self.namespaced = namespaced;
+        self
+    }
+
+    /// Whether any tenants are configured
+    pub fn is_enabled(&self) -> bool {
+        !self.tenants.is_empty()
+    }
+
+    /// Tenant ID of an identity: its own `tenant`, else the tenant claim
+    fn tenant_of(&self, identity: &Identity) -> Option<String> {
+        identity.tenant.clone().or_else(|| {
+            identity
+                .claims
+                .get(&self.claim)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        })
+    }
+
+    /// Assign `identity` to its tenant and narrow it to the tenant's policy
+    pub fn apply(&self, mut identity: Identity) -> Result<Identity, TenantError> {
+        if !self.is_enabled() {
+            return Ok(identity);
+        }
+
+        let Some(tenant_id) = self.tenant_of(&identity) else {
+            if self.required {
+                return Err(TenantError::Missing(identity.id));
+            }
+            return Ok(identity);
+        };
+        let Some(tenant) = self.tenants.get(&tenant_id) else {
+            return Err(TenantError::Unknown(identity.id, tenant_id));
+        };
+
+        if !tenant.allowed_tools.is_empty() {
+            identity.allowed_tools = Some(narrow_allow_list(
+                &identity.allowed_tools,
+                &tenant.allowed_tools,
+            ));
+        }
+        if self.namespaced && !tenant.servers.is_empty() {
+            let server_tools: Vec<String> = tenant
+                .servers
+                .iter()
+                .map(|server| namespaced_tool_name(server, "*"))
+                .collect();
+            identity.allowed_tools =
+                Some(narrow_allow_list(&identity.allowed_tools, &server_tools));
+        }
+        identity.tenant = Some(tenant_id);
+        Ok(identity)
+    }
+
+    /// Whether the identity's tenant may reach the upstream server `name`
+    pub fn allows_server(&self, identity: &Identity, name: &str) -> bool {
+        let Some(tenant) = identity.tenant.as_ref().and_then(|id| self.tenants.get(id)) else {
+            return true;
+        };
+        tenant.servers.is_empty() || tenant.servers.iter().any(|s| s == name)
+    }
+
+    /// Check the shared rate limit of the identity's tenant, if it has one
+    pub fn check_rate_limit(&self, identity: &Identity) -> Option<RateLimitResult> {
+        let id = identity.tenant.as_ref()?;
+        let limit = self.tenants.get(id)?.rate_limit?;
+        Some(self.rate_limiter.check(id, Some(limit)))
+    }
+}
+
+/// Allow list granting only names allowed by both `identity` and `tenant`
+///
+/// Glob patterns cannot be intersected exactly, so this keeps an entry from
+/// one side only when the other side provably allows everything it matches:
+/// a literal name the other side allows, a pattern the other side lists
+/// verbatim, or an entry starting with the prefix of a `prefix*` pattern on
+/// the other side. The result may be narrower than the true intersection but
+/// is never wider.
+fn narrow_allow_list(identity: &Option<Vec<String>>, tenant: &[String]) -> Vec<String> {
+    let Some(own) = identity else {
+        return tenant.to_vec();
+    };
+    let tenant_list = Some(tenant.to_vec());
+    let is_literal = |p: &str| !p.contains(['*', '?', '[']);
+    let covers = |pattern: &str, entry: &str| {
+        pattern == entry
+            || pattern
+                .strip_suffix('*')
+                .is_some_and(|prefix| is_literal(prefix) && entry.starts_with(prefix))
+    };
+    let covered_by = |list: &[String], allow: &Option<Vec<String>>, entry: &str| {
+        list.iter().any(|p| covers(p, entry)) || (is_literal(entry) && is_allowed(allow, entry))
+    };
+
+    let mut seen = HashSet::new();
+    own.iter()
+        .filter(|e| covered_by(tenant, &tenant_list, e))
+        .chain(tenant.iter().filter(|t| covered_by(own, identity, t)))
+        .filter(|e| seen.insert(e.as_str()))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(id: &str, allowed_tools: Option<Vec<&str>>) -> Identity {
+        Identity {
+            id: id.to_string(),
+            name: None,
+            allowed_tools: allowed_tools.map(|v| v.into_iter().map(String::from).collect()),
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims: HashMap::new(),
+            tenant: None,
+            argument_constraints: None,
+        }
+    }
+
+    fn registry(required: bool) -> TenantRegistry {
+        TenantRegistry::new(&TenancyConfig {
+            claim: "org".to_string(),
+            required,
+            tenants: vec![
+                TenantConfig {
+                    id: "acme".to_string(),
+                    allowed_tools: vec!["read_*".to_string(), "search".to_string()],
+                    servers: vec!["github".to_string()],
+                    rate_limit: Some(1),
+                },
+                TenantConfig {
+                    id: "globex".to_string(),
+                    allowed_tools: vec![],
+                    servers: vec![],
+                    rate_limit: None,
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn test_tenant_from_api_key_or_claim() {
+        let registry = registry(false);
+
+        let mut key = identity("svc", None);
+        key.tenant = Some("globex".to_string());
+        assert_eq!(
+            registry.apply(key).unwrap().tenant.as_deref(),
+            Some("globex")
+        );
+
+        let mut jwt = identity("user", None);
+        jwt.claims
+            .insert("org".to_string(), serde_json::json!("acme"));
+        assert_eq!(registry.apply(jwt).unwrap().tenant.as_deref(), Some("acme"));
+
+        // No tenant is fine unless required
+        assert!(registry
+            .apply(identity("anon", None))
+            .unwrap()
+            .tenant
+            .is_none());
+        let strict = self::registry(true);
+        assert_eq!(
+            strict.apply(identity("anon", None)).unwrap_err(),
+            TenantError::Missing("anon".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unknown_tenant_rejected() {
+        let mut jwt = identity("user", None);
+        jwt.claims
+            .insert("org".to_string(), serde_json::json!("initech"));
+        assert!(matches!(
+            registry(false).apply(jwt),
+            Err(TenantError::Unknown(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_allowed_tools_only_narrow() {
+        let registry = registry(false);
+        let apply = |tools: Option<Vec<&str>>| {
+            let mut id = identity("user", tools);
+            id.tenant = Some("acme".to_string());
+            registry.apply(id).unwrap().allowed_tools.unwrap()
+        };
+
+        assert_eq!(apply(None), vec!["read_*", "search"]);
+        assert_eq!(apply(Some(vec!["*"])), vec!["read_*", "search"]);
+        assert_eq!(
+            apply(Some(vec!["read_file", "write_file", "search"])),
+            vec!["read_file", "search"]
+        );
+        assert_eq!(apply(Some(vec!["read_src/*"])), vec!["read_src/*"]);
+        // Overlapping globs that cannot be proven contained are dropped
+        assert!(apply(Some(vec!["*_file"])).is_empty());
+
+        // Tenants without a tool list leave the identity alone
+        let mut id = identity("user", Some(vec!["write_file"]));
+        id.tenant = Some("globex".to_string());
+        assert_eq!(
+            registry.apply(id).unwrap().allowed_tools,
+            Some(vec!["write_file".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_servers_in_aggregate_mode() {
+        let registry = TenantRegistry::new(&TenancyConfig {
+            tenants: vec![TenantConfig {
+                id: "acme".to_string(),
+                allowed_tools: vec![],
+                servers: vec!["github".to_string()],
+                rate_limit: None,
+            }],
+            ..Default::default()
+        })
+        .with_namespaced_tools(true);
+        let mut id = identity("user", Some(vec!["github.create_issue", "jira.*"]));
+        id.tenant = Some("acme".to_string());
+        assert_eq!(
+            registry.apply(id).unwrap().allowed_tools,
+            Some(vec!["github.create_issue".to_string()])
+        );
+
+        let mut id = identity("user", None);
+        id.tenant = Some("acme".to_string());
+        assert_eq!(
+            registry.apply(id).unwrap().allowed_tools,
+            Some(vec!["github.*".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_servers_and_shared_rate_limit() {
+        let registry = registry(false);
+        let mut alice = identity("alice", None);
+        alice.tenant = Some("acme".to_string());
+        let mut bob = identity("bob", None);
+        bob.tenant = Some("acme".to_string());
+
+        assert!(registry.allows_server(&alice, "github"));
+        assert!(!registry.allows_server(&alice, "database"));
+        assert!(registry.allows_server(&identity("anon", None), "database"));
+
+        // One bucket for the whole tenant
+        assert!(registry.check_rate_limit(&alice).unwrap().allowed);
+        assert!(!registry.check_rate_limit(&bob).unwrap().allowed);
+        assert!(registry.check_rate_limit(&identity("anon", None)).is_none());
+    }
+}
@@ -19,6 +19,15 @@
 //!
 //! This module provides helpful error messages when users try to use features
 //! that require Pro or Enterprise licenses.
+//!
+//! Two layers apply:
+//! - [`validate_tier`] checks the configuration against the compiled feature
+//!   flags when the config is loaded.
+//! - [`enforce_tier`] checks it against the tier the license key actually
+//!   grants at startup, disabling paid features the gateway can safely run
+//!   without and refusing to start for the rest.
+
+use std::fmt;
 
 use crate::config::{Config, ConfigError};
 
@@ -60,7 +69,7 @@ fn validate_pro_features(config: &Config) -> Result<(), ConfigError> {
 
     // JWT JWKS mode requires Pro
     #[cfg(not(feature = "pro"))]
-    if let Some(ref jwt_config) = config.auth.jwt {
+    for jwt_config in &config.auth.jwt {
         if matches!(jwt_config.mode, JwtMode::Jwks { .. }) {
             return Err(ConfigError::Validation(format!(
                 "JWT JWKS mode (RS256/ES256) requires a Pro license.\n\n\
@@ -107,7 +116,7 @@ fn validate_pro_features(config: &Config) -> Result<(), ConfigError> {
                 PRICING_URL
             )));
         }
-        TransportType::Stdio => {}
+        TransportType::Stdio | TransportType::Unix | TransportType::Mock => {}
     }
 
     Ok(())
@@ -152,9 +161,9 @@ fn validate_enterprise_features(config: &Config) -> Result<(), ConfigError> {
         )));
     }
 
-    // SIEM audit log shipping requires Enterprise
+    // SIEM audit log shipping (and object storage archiving) requires Enterprise
     #[cfg(not(feature = "enterprise"))]
-    if config.audit.export_url.is_some() {
+    if config.audit.export_url.is_some() || config.audit.object_storage.is_some() {
         return Err(ConfigError::Validation(format!(
             "Audit log shipping to SIEM requires an Enterprise license.\n\n\
              The free tier supports local file and console logging only.\n\n\
@@ -202,9 +211,220 @@ fn validate_enterprise_features(config: &Config) -> Result<(), ConfigError> {
         )));
     }
 
+    // SAML federation requires Enterprise
+    #[cfg(not(feature = "enterprise"))]
+    if config.auth.saml.is_some() {
+        return Err(ConfigError::Validation(format!(
+            "SAML federation requires an Enterprise license.\n\n\
+             The free tier supports API key authentication only.\n\n\
+             Upgrade to Enterprise:\n\
+             → {}\n\n\
+             Or remove the [auth.saml] section from your config.",
+            PRICING_URL
+        )));
+    }
+
     Ok(())
 }
 
+/// License tier, ordered from least to most capable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tier {
+    Free,
+    Pro,
+    Enterprise,
+}
+
+impl Tier {
+    /// Highest tier compiled into this binary
+    pub fn compiled() -> Self {
+        if cfg!(feature = "enterprise") {
+            Tier::Enterprise
+        } else if cfg!(feature = "pro") {
+            Tier::Pro
+        } else {
+            Tier::Free
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Tier::Free => "Free",
+            Tier::Pro => "Pro",
+            Tier::Enterprise => "Enterprise",
+        }
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A paid feature that a configuration can turn on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GatedFeature {
+    OAuth,
+    JwtJwks,
+    HttpTransport,
+    SseTransport,
+    Mtls,
+    MultiServerRouting,
+    Saml,
+    PerToolRateLimit,
+    SiemExport,
+    OtlpExport,
+}
+
+impl GatedFeature {
+    /// Human-readable name for startup diagnostics
+    pub fn name(&self) -> &'static str {
+        match self {
+            GatedFeature::OAuth => "OAuth 2.1 authentication",
+            GatedFeature::JwtJwks => "JWT JWKS mode",
+            GatedFeature::HttpTransport => "HTTP transport",
+            GatedFeature::SseTransport => "SSE transport",
+            GatedFeature::Mtls => "mTLS authentication",
+            GatedFeature::MultiServerRouting => "Multi-server routing",
+            GatedFeature::Saml => "SAML federation",
+            GatedFeature::PerToolRateLimit => "Per-tool rate limiting",
+            GatedFeature::SiemExport => "SIEM audit log shipping",
+            GatedFeature::OtlpExport => "OpenTelemetry OTLP export",
+        }
+    }
+
+    /// Lowest tier that includes the feature
+    pub fn tier(&self) -> Tier {
+        match self {
+            GatedFeature::OAuth
+            | GatedFeature::JwtJwks
+            | GatedFeature::HttpTransport
+            | GatedFeature::SseTransport => Tier::Pro,
+            GatedFeature::Mtls
+            | GatedFeature::MultiServerRouting
+            | GatedFeature::Saml
+            | GatedFeature::PerToolRateLimit
+            | GatedFeature::SiemExport
+            | GatedFeature::OtlpExport => Tier::Enterprise,
+        }
+    }
+
+    /// Whether the gateway can run without the feature
+    ///
+    /// Only telemetry exports qualify: switching off auth providers,
+    /// transports, routes or rate limits would change what the gateway
+    /// protects, so those refuse to start instead.
+    pub fn degradable(&self) -> bool {
+        matches!(self, GatedFeature::SiemExport | GatedFeature::OtlpExport)
+    }
+
+    fn disable(&self, config: &mut Config) {
+        match self {
+            GatedFeature::SiemExport => {
+                config.audit.export_url = None;
+                config.audit.object_storage = None;
+            }
+            GatedFeature::OtlpExport => config.tracing.otlp_endpoint = None,
+            _ => {}
+        }
+    }
+}
+
+/// List the paid features a configuration turns on
+pub fn gated_features(config: &Config) -> Vec<GatedFeature> {
+    use crate::config::{JwtMode, TransportType};
+
+    let mut features = Vec::new();
+    if config.auth.oauth.is_some() {
+        features.push(GatedFeature::OAuth);
+    }
+    if config
+        .auth
+        .jwt
+        .iter()
+        .any(|jwt_config| matches!(jwt_config.mode, JwtMode::Jwks { .. }))
+    {
+        features.push(GatedFeature::JwtJwks);
+    }
+
+    let transports: Vec<&TransportType> = if config.upstream.servers.is_empty() {
+        vec![&config.upstream.transport]
+    } else {
+        config
+            .upstream
+            .servers
+            .iter()
+            .map(|s| &s.transport)
+            .collect()
+    };
+    if transports.iter().any(|t| matches!(t, TransportType::Http)) {
+        features.push(GatedFeature::HttpTransport);
+    }
+    if transports.iter().any(|t| matches!(t, TransportType::Sse)) {
+        features.push(GatedFeature::SseTransport);
+    }
+
+    if config.auth.mtls.as_ref().is_some_and(|m| m.enabled) {
+        features.push(GatedFeature::Mtls);
+    }
+    if !config.upstream.servers.is_empty() {
+        features.push(GatedFeature::MultiServerRouting);
+    }
+    if config.auth.saml.is_some() {
+        features.push(GatedFeature::Saml);
+    }
+    if !config.rate_limit.tool_limits.is_empty() {
+        features.push(GatedFeature::PerToolRateLimit);
+    }
+    if config.audit.export_url.is_some() || config.audit.object_storage.is_some() {
+        features.push(GatedFeature::SiemExport);
+    }
+    if config.tracing.enabled && config.tracing.otlp_endpoint.is_some() {
+        features.push(GatedFeature::OtlpExport);
+    }
+    features
+}
+
+/// Paid features switched off by [`enforce_tier`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TierEnforcement {
+    pub disabled: Vec<GatedFeature>,
+}
+
+/// Enforce the licensed tier on a configuration at startup
+///
+/// Features above `licensed` are switched off in `config` when the gateway
+/// can run without them; any other such feature is an error listing
+/// everything the license does not cover.
+pub fn enforce_tier(config: &mut Config, licensed: Tier) -> Result<TierEnforcement, ConfigError> {
+    let (degraded, blocked): (Vec<_>, Vec<_>) = gated_features(config)
+        .into_iter()
+        .filter(|feature| feature.tier() > licensed)
+        .partition(|feature| feature.degradable());
+
+    if !blocked.is_empty() {
+        let lines: Vec<String> = blocked
+            .iter()
+            .map(|feature| format!("  - {} ({})", feature.name(), feature.tier()))
+            .collect();
+        return Err(ConfigError::Validation(format!(
+            "Your configuration uses features not covered by the {} license:\n{}\n\n\
+             Upgrade your license:\n\
+             → {}\n\n\
+             Or remove these features from your config.",
+            licensed,
+            lines.join("\n"),
+            PRICING_URL
+        )));
+    }
+
+    for feature in &degraded {
+        feature.disable(config);
+    }
+    Ok(TierEnforcement { disabled: degraded })
+}
+
 /// Get the current tier name based on compiled features
 pub fn current_tier() -> &'static str {
     #[cfg(feature = "enterprise")]
@@ -221,28 +441,44 @@ pub fn current_tier() -> &'static str {
     }
 }
 
+/// Feature names understood by [`is_feature_available`], with the lowest
+/// tier that includes each
+pub const FEATURES: &[(&str, Tier)] = &[
+    ("api_key_auth", Tier::Free),
+    ("jwt_hs256", Tier::Free),
+    ("stdio_transport", Tier::Free),
+    ("global_rate_limit", Tier::Free),
+    ("file_audit", Tier::Free),
+    ("console_audit", Tier::Free),
+    ("prometheus_metrics", Tier::Free),
+    ("oauth", Tier::Pro),
+    ("jwt_jwks", Tier::Pro),
+    ("http_transport", Tier::Pro),
+    ("sse_transport", Tier::Pro),
+    ("per_identity_rate_limit", Tier::Pro),
+    ("mtls", Tier::Enterprise),
+    ("multi_server_routing", Tier::Enterprise),
+    ("siem_audit", Tier::Enterprise),
+    ("opentelemetry", Tier::Enterprise),
+    ("per_tool_rate_limit", Tier::Enterprise),
+    ("admin_guard_tools", Tier::Enterprise),
+];
+
 /// Check if a feature is available in the current tier
 pub fn is_feature_available(feature: &str) -> bool {
-    match feature {
-        // Free tier features
-        "api_key_auth" | "jwt_hs256" | "stdio_transport" | "global_rate_limit" | "file_audit"
-        | "console_audit" | "prometheus_metrics" => true,
-
-        // Pro tier features
-        "oauth" | "jwt_jwks" | "http_transport" | "sse_transport" | "per_identity_rate_limit" => {
-            cfg!(feature = "pro") || cfg!(feature = "enterprise")
-        }
-
-        // Enterprise tier features
-        "mtls"
-        | "multi_server_routing"
-        | "siem_audit"
-        | "opentelemetry"
-        | "per_tool_rate_limit"
-        | "admin_guard_tools" => cfg!(feature = "enterprise"),
+    FEATURES
+        .iter()
+        .find(|(name, _)| *name == feature)
+        .is_some_and(|(_, tier)| *tier <= Tier::compiled())
+}
 
-        _ => false,
-    }
+/// Features available in the current tier, in [`FEATURES`] order
+pub fn available_features() -> Vec<&'static str> {
+    FEATURES
+        .iter()
+        .filter(|(_, tier)| *tier <= Tier::compiled())
+        .map(|(name, _)| *name)
+        .collect()
 }
 
 #[cfg(test)]
@@ -264,10 +500,51 @@ mod tests {
                 transport: TransportType::Stdio,
                 command: Some("echo".to_string()),
                 args: vec![],
+                env: Default::default(),
+                cwd: None,
+                inherit_env: true,
                 url: None,
                 servers: vec![],
+                mode: Default::default(),
+                headers: Default::default(),
+                tls: None,
+                dns_refresh_secs: 300,
+                socket_path: None,
+                retry: Default::default(),
+                timeouts: Default::default(),
+                fair_queue: Default::default(),
+                response_limits: Default::default(),
+                transforms: Default::default(),
+                catalog: Default::default(),
+                mock: Default::default(),
             },
             database_url: None,
+            database: None,
+            cluster: Default::default(),
+            stripe_secret_key: None,
+            include: Vec::new(),
+            load_shedding: Default::default(),
+            network_acl: Default::default(),
+            ssrf: Default::default(),
+            egress: Default::default(),
+            request_signing: Default::default(),
+            telemetry_privacy: Default::default(),
+            scrubbing: Default::default(),
+            response_filtering: Default::default(),
+            logging: Default::default(),
+            metrics: Default::default(),
+            admin: Default::default(),
+            sessions: Default::default(),
+            tenancy: Default::default(),
+            approval: Default::default(),
+            honeypot: Default::default(),
+            break_glass: Default::default(),
+            methods: Default::default(),
+            read_only: Default::default(),
+            capabilities: Default::default(),
+            alerting: Default::default(),
+            capture: Default::default(),
+            journal: Default::default(),
         }
     }
 
@@ -297,6 +574,64 @@ mod tests {
         assert!(!is_feature_available("unknown_feature"));
     }
 
+    #[test]
+    fn test_enforce_tier_degrades_telemetry_exports() {
+        let mut config = create_minimal_config();
+        config.audit.export_url = Some("https://siem.example.com/logs".to_string());
+        config.audit.object_storage = Some(
+            toml::from_str(
+                r#"
+                endpoint = "https://s3.eu-west-1.amazonaws.com"
+                bucket = "audit"
+                access_key_id = "AKIA"
+                secret_access_key = "secret"
+                "#,
+            )
+            .unwrap(),
+        );
+        config.tracing.enabled = true;
+        config.tracing.otlp_endpoint = Some("http://jaeger:4317".to_string());
+
+        let enforcement = enforce_tier(&mut config.clone(), Tier::Enterprise).unwrap();
+        assert!(enforcement.disabled.is_empty());
+
+        let enforcement = enforce_tier(&mut config, Tier::Pro).unwrap();
+        assert_eq!(
+            enforcement.disabled,
+            vec![GatedFeature::SiemExport, GatedFeature::OtlpExport]
+        );
+        assert!(config.audit.export_url.is_none());
+        assert!(config.audit.object_storage.is_none());
+        assert!(config.tracing.otlp_endpoint.is_none());
+        assert!(gated_features(&config).is_empty());
+    }
+
+    #[test]
+    fn test_enforce_tier_refuses_protective_features() {
+        let mut config = create_minimal_config();
+        config.upstream.transport = TransportType::Sse;
+        config.upstream.url = Some("http://localhost:8080/sse".to_string());
+        config.audit.export_url = Some("https://siem.example.com/logs".to_string());
+
+        assert!(enforce_tier(&mut config.clone(), Tier::Pro).is_ok());
+
+        let err = enforce_tier(&mut config, Tier::Free)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Free license"));
+        assert!(err.contains("SSE transport (Pro)"));
+        assert!(!err.contains("SIEM"));
+        // Nothing is changed when startup is refused
+        assert!(config.audit.export_url.is_some());
+    }
+
+    #[test]
+    fn test_tier_ordering() {
+        assert!(Tier::Free < Tier::Pro);
+        assert!(Tier::Pro < Tier::Enterprise);
+        assert_eq!(Tier::compiled().as_str(), current_tier());
+    }
+
     #[test]
     fn test_validate_tier_minimal_config() {
         let config = create_minimal_config();
@@ -323,6 +658,9 @@ mod tests {
             user_id_claim: "sub".to_string(),
             scope_tool_mapping: Default::default(),
             token_cache_ttl_secs: 300,
+            session: None,
+            registration: None,
+            device_authorization_url: None,
         });
 
         let result = validate_tier(&config);
@@ -355,8 +693,11 @@ mod tests {
             enabled: true,
             identity_source: crate::config::MtlsIdentitySource::Cn,
             allowed_tools: vec![],
+            allowed_resources: vec![],
+            allowed_prompts: vec![],
             rate_limit: None,
             trusted_proxy_ips: vec!["10.0.0.0/8".to_string()],
+            spiffe: None,
         });
 
         let result = validate_tier(&config);
@@ -377,8 +718,26 @@ mod tests {
                 transport: TransportType::Stdio,
                 command: Some("echo".to_string()),
                 args: vec![],
+                env: Default::default(),
+                cwd: None,
+                inherit_env: true,
+                spawn: Default::default(),
+                idle_ttl_secs: 300,
                 url: None,
                 strip_prefix: false,
+                headers: Default::default(),
+                tls: None,
+                dns_refresh_secs: 300,
+                socket_path: None,
+                retry: Default::default(),
+                timeouts: Default::default(),
+                fair_queue: Default::default(),
+                response_limits: Default::default(),
+                transforms: Default::default(),
+                catalog: Default::default(),
+                mock: Default::default(),
+                canary: None,
+                methods: Default::default(),
             },
             ServerRouteConfig {
                 name: "server2".to_string(),
@@ -386,8 +745,26 @@ mod tests {
                 transport: TransportType::Stdio,
                 command: Some("echo".to_string()),
                 args: vec![],
+                env: Default::default(),
+                cwd: None,
+                inherit_env: true,
+                spawn: Default::default(),
+                idle_ttl_secs: 300,
                 url: None,
                 strip_prefix: false,
+                headers: Default::default(),
+                tls: None,
+                dns_refresh_secs: 300,
+                socket_path: None,
+                retry: Default::default(),
+                timeouts: Default::default(),
+                fair_queue: Default::default(),
+                response_limits: Default::default(),
+                transforms: Default::default(),
+                catalog: Default::default(),
+                mock: Default::default(),
+                canary: None,
+                methods: Default::default(),
             },
         ];
 
@@ -0,0 +1,392 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Per-route rewriting of tool calls
+//!
+//! `transforms` rules and the published `catalog` let the gateway present a
+//! stable tool surface over changing upstreams. Rewrites run after
+//! authorization, scrubbing and approval, so policies always see the tool
+//! name the client called, and change the `tools/call` request just before
+//! it is forwarded. `tools/list` responses are rewritten the other way: tools
+//! are listed under their client-facing names and, with a catalog, only the
+//! curated tools are listed.
+
+use serde_json::{Map, Value};
+
+use crate::config::{CatalogTool, TransformRule};
+use crate::transport::Message;
+
+/// Client-facing tool surface of one upstream
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToolSurface<'a> {
+    /// Rewrite rules for tool calls
+    pub transforms: &'a [TransformRule],
+    /// Published tools (empty = everything the upstream lists)
+    pub catalog: &'a [CatalogTool],
+}
+
+impl<'a> ToolSurface<'a> {
+    /// Surface of the single-server upstream
+    pub fn upstream(config: &'a crate::config::UpstreamConfig) -> Self {
+        Self {
+            transforms: &config.transforms,
+            catalog: &config.catalog,
+        }
+    }
+
+    /// Surface of a server route
+    pub fn route(config: &'a crate::config::ServerRouteConfig) -> Self {
+        Self {
+            transforms: &config.transforms,
+            catalog: &config.catalog,
+        }
+    }
+
+    /// Whether a message may be forwarded
+    ///
+    /// With a catalog, only calls to published tools are; any other message
+    /// always is.
+    pub fn is_published(&self, message: &Message) -> bool {
+        self.catalog.is_empty()
+            || message.method.as_deref() != Some("tools/call")
+            || crate::authz::extract_tool_name(message)
+                .is_some_and(|tool| self.catalog.iter().any(|entry| entry.name == tool))
+    }
+
+    /// Rewrite a `tools/call` request for the upstream
+    ///
+    /// Every transform matching the called tool is applied, then the tool is
+    /// mapped to its catalog `upstream_tool`. Other messages are left
+    /// untouched.
+    pub fn transform_request(&self, message: &mut Message) {
+        if message.method.as_deref() != Some("tools/call") {
+            return;
+        }
+        let Some(tool) = crate::authz::extract_tool_name(message).map(str::to_string) else {
+            return;
+        };
+        let Some(Value::Object(params)) = message.params.as_mut() else {
+            return;
+        };
+
+        apply_rules(self.transforms, &tool, params);
+        if let Some(upstream_tool) = self
+            .catalog
+            .iter()
+            .find(|entry| entry.name == tool)
+            .and_then(|entry| entry.upstream_tool.as_ref())
+        {
+            params.insert("name".to_string(), Value::String(upstream_tool.clone()));
+        }
+    }
+
+    /// Whether responses to `method` have to be rewritten
+    pub fn rewrites_response(&self, method: Option<&str>) -> bool {
+        method == Some("tools/list")
+            && (!self.catalog.is_empty() || self.transforms.iter().any(|r| r.rename.is_some()))
+    }
+
+    /// List tools under the names clients call them by
+    ///
+    /// Renamed tools get their client-facing name back. With a catalog, the
+    /// list is replaced by the published tools, in catalog order, with their
+    /// descriptions overridden; published tools the upstream does not list
+    /// are left out. Only applies to `tools/list` responses.
+    pub fn rewrite_response(&self, method: Option<&str>, response: &mut Message) {
+        if !self.rewrites_response(method) {
+            return;
+        }
+        let Some(Value::Array(tools)) = response
+            .result
+            .as_mut()
+            .and_then(|result| result.get_mut("tools"))
+        else {
+            return;
+        };
+
+        restore_tool_names(self.transforms, tools);
+        if !self.catalog.is_empty() {
+            *tools = publish_catalog(self.catalog, tools);
+        }
+    }
+}
+
+/// Apply every rule matching `tool` to the params of a `tools/call`
+fn apply_rules(rules: &[TransformRule], tool: &str, params: &mut Map<String, Value>) {
+    for rule in rules.iter().filter(|rule| matches_tool(rule, tool)) {
+        if let Some(ref upstream_tool) = rule.rename {
+            params.insert("name".to_string(), Value::String(upstream_tool.clone()));
+        }
+        if !rule.default_arguments.is_empty() {
+            if let Value::Object(arguments) = params
+                .entry("arguments")
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                for (name, value) in &rule.default_arguments {
+                    arguments
+                        .entry(name.clone())
+                        .or_insert_with(|| value.clone());
+                }
+            }
+        }
+        for path in &rule.strip_params {
+            strip_path(params, path);
+        }
+        if !rule.meta.is_empty() {
+            if let Value::Object(meta) = params
+                .entry("_meta")
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                for (name, value) in &rule.meta {
+                    meta.insert(name.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Give renamed tools their client-facing name back
+fn restore_tool_names(rules: &[TransformRule], tools: &mut [Value]) {
+    for tool in tools {
+        let Some(name) = tool.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        if let Some(rule) = rules
+            .iter()
+            .find(|rule| rule.rename.as_deref() == Some(name))
+        {
+            tool["name"] = Value::String(rule.tool.clone());
+        }
+    }
+}
+
+/// Published tools, in catalog order, built from the upstream's list
+fn publish_catalog(catalog: &[CatalogTool], tools: &[Value]) -> Vec<Value> {
+    catalog
+        .iter()
+        .filter_map(|entry| {
+            let upstream_tool = entry.upstream_tool.as_deref().unwrap_or(&entry.name);
+            let Some(tool) = tools
+                .iter()
+                .find(|tool| tool.get("name").and_then(Value::as_str) == Some(upstream_tool))
+            else {
+                tracing::debug!(
+                    tool = %entry.name,
+                    upstream_tool = %upstream_tool,
+                    "Published tool not listed by upstream"
+                );
+                return None;
+            };
+            let mut tool = tool.clone();
+            tool["name"] = Value::String(entry.name.clone());
+            if let Some(ref description) = entry.description {
+                tool["description"] = Value::String(description.clone());
+            }
+            Some(tool)
+        })
+        .collect()
+}
+
+fn matches_tool(rule: &TransformRule, tool: &str) -> bool {
+    // Patterns are validated with the rest of the configuration
+    glob::Pattern::new(&rule.tool).is_ok_and(|pattern| pattern.matches(tool))
+}
+
+/// Remove a dotted path (e.g. `arguments.debug`) from the params object
+fn strip_path(params: &mut Map<String, Value>, path: &str) {
+    let (parents, field) = match path.rsplit_once('.') {
+        Some((parents, field)) => (Some(parents), field),
+        None => (None, path),
+    };
+    let mut target = params;
+    for segment in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        match target.get_mut(segment) {
+            Some(Value::Object(next)) => target = next,
+            _ => return,
+        }
+    }
+    target.remove(field);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules() -> Vec<TransformRule> {
+        serde_json::from_value(json!([
+            {
+                "tool": "search",
+                "rename": "vector_search",
+                "default_arguments": { "limit": 10, "mode": "hybrid" },
+                "strip_params": ["arguments.debug", "_meta.progressToken"],
+                "meta": { "source": "mcp-guard" }
+            },
+            { "tool": "*", "meta": { "tenant": "acme" } }
+        ]))
+        .unwrap()
+    }
+
+    fn catalog() -> Vec<CatalogTool> {
+        serde_json::from_value(json!([
+            { "name": "search" },
+            { "name": "open_ticket", "upstream_tool": "jira_create", "description": "Open a ticket" }
+        ]))
+        .unwrap()
+    }
+
+    fn call(tool: &str, params: Value) -> Message {
+        let mut params = params;
+        params["name"] = json!(tool);
+        Message::request(1, "tools/call", Some(params))
+    }
+
+    fn tools_list(names: &[&str]) -> Message {
+        let tools: Vec<Value> = names
+            .iter()
+            .map(|name| json!({ "name": name, "description": "upstream" }))
+            .collect();
+        Message::response(json!(2), json!({ "tools": tools }))
+    }
+
+    fn listed_names(response: &Message) -> Vec<String> {
+        response.result.as_ref().unwrap()["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_tool_call_is_rewritten() {
+        let rules = rules();
+        let surface = ToolSurface {
+            transforms: &rules,
+            catalog: &[],
+        };
+        let mut message = call(
+            "search",
+            json!({
+                "arguments": { "query": "outage", "limit": 3, "debug": true },
+                "_meta": { "progressToken": "p1", "source": "client" }
+            }),
+        );
+        surface.transform_request(&mut message);
+
+        assert_eq!(
+            message.params,
+            Some(json!({
+                "name": "vector_search",
+                "arguments": { "query": "outage", "limit": 3, "mode": "hybrid" },
+                "_meta": { "source": "mcp-guard", "tenant": "acme" }
+            }))
+        );
+    }
+
+    #[test]
+    fn test_defaults_added_without_arguments() {
+        let rules = rules();
+        let surface = ToolSurface {
+            transforms: &rules,
+            catalog: &[],
+        };
+        let mut message = call("search", json!({}));
+        surface.transform_request(&mut message);
+
+        let params = message.params.unwrap();
+        assert_eq!(
+            params["arguments"],
+            json!({ "limit": 10, "mode": "hybrid" })
+        );
+    }
+
+    #[test]
+    fn test_only_matching_rules_apply() {
+        let rules = rules();
+        let surface = ToolSurface {
+            transforms: &rules,
+            catalog: &[],
+        };
+        let mut message = call("fetch", json!({ "arguments": { "debug": true } }));
+        surface.transform_request(&mut message);
+
+        let params = message.params.unwrap();
+        assert_eq!(params["name"], "fetch");
+        assert_eq!(params["arguments"], json!({ "debug": true }));
+        assert_eq!(params["_meta"], json!({ "tenant": "acme" }));
+
+        let mut list = Message::request(2, "tools/list", None);
+        surface.transform_request(&mut list);
+        assert!(list.params.is_none());
+    }
+
+    #[test]
+    fn test_tools_list_uses_client_names() {
+        let rules = rules();
+        let surface = ToolSurface {
+            transforms: &rules,
+            catalog: &[],
+        };
+        assert!(surface.rewrites_response(Some("tools/list")));
+        assert!(!surface.rewrites_response(Some("tools/call")));
+        assert!(!ToolSurface::default().rewrites_response(Some("tools/list")));
+
+        let mut response = tools_list(&["vector_search", "fetch"]);
+        surface.rewrite_response(Some("tools/list"), &mut response);
+        assert_eq!(listed_names(&response), vec!["search", "fetch"]);
+    }
+
+    #[test]
+    fn test_catalog_limits_calls() {
+        let catalog = catalog();
+        let surface = ToolSurface {
+            transforms: &[],
+            catalog: &catalog,
+        };
+        assert!(surface.is_published(&call("search", json!({}))));
+        assert!(!surface.is_published(&call("jira_create", json!({}))));
+        assert!(surface.is_published(&Message::request(2, "tools/list", None)));
+        assert!(ToolSurface::default().is_published(&call("anything", json!({}))));
+
+        let mut message = call("open_ticket", json!({ "arguments": {} }));
+        surface.transform_request(&mut message);
+        assert_eq!(message.params.unwrap()["name"], "jira_create");
+    }
+
+    #[test]
+    fn test_catalog_publishes_curated_list() {
+        let rules = rules();
+        let catalog = catalog();
+        let surface = ToolSurface {
+            transforms: &rules,
+            catalog: &catalog,
+        };
+        let mut response = tools_list(&["jira_create", "jira_delete", "vector_search"]);
+        surface.rewrite_response(Some("tools/list"), &mut response);
+
+        assert_eq!(listed_names(&response), vec!["search", "open_ticket"]);
+        let tools = &response.result.unwrap()["tools"];
+        assert_eq!(tools[0]["description"], "upstream");
+        assert_eq!(tools[1]["description"], "Open a ticket");
+
+        // Published tools the upstream does not list are left out
+        let mut response = tools_list(&["vector_search"]);
+        surface.rewrite_response(Some("tools/list"), &mut response);
+        assert_eq!(listed_names(&response), vec!["search"]);
+    }
+}
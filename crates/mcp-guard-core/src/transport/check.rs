@@ -0,0 +1,393 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! On-demand upstream connectivity checks
+//!
+//! Shared by `mcp-guard check-upstream` and the `guard/upstreams/check` tool.
+//! Each check opens its own connection (or process) next to the live one, so
+//! it never disturbs traffic already in flight.
+
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::{
+    Message, MockUpstreamTransport, StdioOptions, StdioTransport, Transport, TransportError,
+    UnixSocketTransport, UpstreamTls,
+};
+use crate::config::{
+    MockUpstreamConfig, ServerRouteConfig, TransportType, UpstreamConfig, UpstreamTlsConfig,
+};
+
+/// Where a connectivity check connects to
+#[derive(Debug, Clone)]
+pub enum UpstreamTarget<'a> {
+    /// Spawn the command and send `initialize`
+    Stdio {
+        command: &'a str,
+        args: &'a [String],
+        options: StdioOptions,
+    },
+    /// POST an empty body; any HTTP response counts as reachable
+    Http {
+        url: &'a str,
+        tls: Option<&'a UpstreamTlsConfig>,
+    },
+    /// Open the event stream; any HTTP response counts as reachable
+    Sse {
+        url: &'a str,
+        tls: Option<&'a UpstreamTlsConfig>,
+    },
+    /// Connect to the socket and send `initialize`
+    Unix(&'a Path),
+    /// Send `initialize` to the built-in mock
+    Mock(&'a MockUpstreamConfig),
+}
+
+impl<'a> UpstreamTarget<'a> {
+    /// Target of the single-server upstream
+    pub fn from_upstream(config: &'a UpstreamConfig) -> Result<Self, String> {
+        Self::resolve(
+            &config.transport,
+            config.command.as_deref(),
+            &config.args,
+            StdioOptions::from_upstream(config),
+            config.url.as_deref(),
+            config.tls.as_ref(),
+            config.socket_path.as_deref(),
+            &config.mock,
+        )
+    }
+
+    /// Target of a multi-server route
+    pub fn from_route(config: &'a ServerRouteConfig) -> Result<Self, String> {
+        Self::resolve(
+            &config.transport,
+            config.command.as_deref(),
+            &config.args,
+            StdioOptions::from_route(config),
+            config.url.as_deref(),
+            config.tls.as_ref(),
+            config.socket_path.as_deref(),
+            &config.mock,
+        )
+    }
+
+    fn resolve(
+        transport: &TransportType,
+        command: Option<&'a str>,
+        args: &'a [String],
+        options: StdioOptions,
+        url: Option<&'a str>,
+        tls: Option<&'a UpstreamTlsConfig>,
+        socket_path: Option<&'a Path>,
+        mock: &'a MockUpstreamConfig,
+    ) -> Result<Self, String> {
+        match transport {
+            TransportType::Stdio => command
+                .map(|command| Self::Stdio {
+                    command,
+                    args,
+                    options,
+                })
+                .ok_or_else(|| "stdio transport requires 'command'".to_string()),
+            TransportType::Http => url
+                .map(|url| Self::Http { url, tls })
+                .ok_or_else(|| "http transport requires 'url'".to_string()),
+            TransportType::Sse => url
+                .map(|url| Self::Sse { url, tls })
+                .ok_or_else(|| "sse transport requires 'url'".to_string()),
+            TransportType::Unix => socket_path
+                .map(Self::Unix)
+                .ok_or_else(|| "unix transport requires 'socket_path'".to_string()),
+            TransportType::Mock => Ok(Self::Mock(mock)),
+        }
+    }
+}
+
+/// What a successful connectivity check learned about the upstream
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UpstreamCheck {
+    /// Server name from the `initialize` response (stdio, unix and mock)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+    /// Server version from the `initialize` response (stdio, unix and mock)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_version: Option<String>,
+    /// HTTP status of the probe (http and sse)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    /// Content type of the probe response (sse)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Time taken by the check
+    pub latency_ms: u64,
+}
+
+/// Check that an upstream is reachable and, where possible, answers `initialize`
+///
+/// # Errors
+/// Returns `TransportError::Timeout` if the check does not finish within
+/// `timeout`, or the error that made the upstream unreachable.
+pub async fn check_upstream(
+    target: &UpstreamTarget<'_>,
+    timeout: Duration,
+) -> Result<UpstreamCheck, TransportError> {
+    let started = Instant::now();
+    let check = async {
+        match *target {
+            UpstreamTarget::Stdio {
+                command,
+                args,
+                ref options,
+            } => {
+                let transport =
+                    StdioTransport::spawn_unchecked_with(command, args, options.clone()).await?;
+                let result = initialize(&transport).await;
+                let _ = transport.close().await;
+                result
+            }
+            UpstreamTarget::Unix(path) => {
+                let transport = UnixSocketTransport::connect(path).await?;
+                let result = initialize(&transport).await;
+                let _ = transport.close().await;
+                result
+            }
+            UpstreamTarget::Mock(config) => {
+                initialize(&MockUpstreamTransport::new(config.clone())).await
+            }
+            UpstreamTarget::Http { url, tls } => {
+                let request = http_client(timeout, tls)?
+                    .post(url)
+                    .header("Content-Type", "application/json");
+                // Signed like forwarded traffic, for upstreams that require it
+                let response = super::signing::sign_request(request, "POST", url, b"{}")
+                    .body("{}")
+                    .send()
+                    .await
+                    .map_err(|e| TransportError::Http(e.to_string()))?;
+                Ok(UpstreamCheck {
+                    http_status: Some(response.status().as_u16()),
+                    ..Default::default()
+                })
+            }
+            UpstreamTarget::Sse { url, tls } => {
+                let response = http_client(timeout, tls)?
+                    .get(url)
+                    .header("Accept", "text/event-stream")
+                    .send()
+                    .await
+                    .map_err(|e| TransportError::Sse(e.to_string()))?;
+                Ok(UpstreamCheck {
+                    http_status: Some(response.status().as_u16()),
+                    content_type: response
+                        .headers()
+                        .get("content-type")
+                        .and_then(|v| v.to_str().ok())
+                        .map(String::from),
+                    ..Default::default()
+                })
+            }
+        }
+    };
+
+    let mut check = tokio::time::timeout(timeout, check)
+        .await
+        .map_err(|_| TransportError::Timeout)??;
+    check.latency_ms = started.elapsed().as_millis() as u64;
+    Ok(check)
+}
+
+fn http_client(
+    timeout: Duration,
+    tls: Option<&UpstreamTlsConfig>,
+) -> Result<reqwest::Client, TransportError> {
+    let mut builder = reqwest::Client::builder().timeout(timeout);
+    if let Some(tls) = tls {
+        builder = UpstreamTls::load(tls)?.apply(builder);
+    }
+    builder
+        .build()
+        .map_err(|e| TransportError::Http(e.to_string()))
+}
+
+/// Send an MCP `initialize` request and read the server info from the response
+async fn initialize(transport: &dyn Transport) -> Result<UpstreamCheck, TransportError> {
+    let request = Message::request(
+        1,
+        "initialize",
+        Some(serde_json::json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {
+                "name": "mcp-guard-check",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        })),
+    );
+    transport.send(request).await?;
+    let response = transport.receive().await?;
+
+    if response.result.is_none() && response.error.is_none() {
+        return Err(TransportError::InvalidMessage(
+            "Invalid JSON-RPC response".to_string(),
+        ));
+    }
+    let server_info = response.result.as_ref().and_then(|r| r.get("serverInfo"));
+    let field = |name: &str| {
+        server_info
+            .and_then(|info| info.get(name))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    };
+    Ok(UpstreamCheck {
+        server_name: field("name"),
+        server_version: field("version"),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[tokio::test]
+    async fn test_check_http_upstream_success() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let url = mock_server.uri();
+        let check = check_upstream(
+            &UpstreamTarget::Http {
+                url: &url,
+                tls: None,
+            },
+            TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(check.http_status, Some(200));
+    }
+
+    #[tokio::test]
+    async fn test_check_http_upstream_server_error() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+
+        // Even 500 is "reachable"
+        let url = mock_server.uri();
+        let check = check_upstream(
+            &UpstreamTarget::Http {
+                url: &url,
+                tls: None,
+            },
+            TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(check.http_status, Some(500));
+    }
+
+    #[tokio::test]
+    async fn test_check_sse_upstream_success() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("content-type", "text/event-stream"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let url = mock_server.uri();
+        let check = check_upstream(
+            &UpstreamTarget::Sse {
+                url: &url,
+                tls: None,
+            },
+            TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(check.content_type.as_deref(), Some("text/event-stream"));
+    }
+
+    #[tokio::test]
+    async fn test_check_sse_upstream_no_content_type() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let url = mock_server.uri();
+        let result = check_upstream(
+            &UpstreamTarget::Sse {
+                url: &url,
+                tls: None,
+            },
+            TIMEOUT,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_check_stdio_upstream_invalid_command() {
+        let target = UpstreamTarget::Stdio {
+            command: "/nonexistent/command",
+            args: &[],
+            options: StdioOptions::default(),
+        };
+        let result = check_upstream(&target, TIMEOUT).await;
+        assert!(matches!(result, Err(TransportError::Spawn(_))));
+    }
+
+    #[tokio::test]
+    async fn test_check_stdio_upstream_reads_server_info() {
+        let args = vec![
+            "-c".to_string(),
+            r#"read line; echo '{"jsonrpc":"2.0","id":1,"result":{"serverInfo":{"name":"demo","version":"1.2.0"}}}'"#
+                .to_string(),
+        ];
+        let target = UpstreamTarget::Stdio {
+            command: "sh",
+            args: &args,
+            options: StdioOptions::default(),
+        };
+        let check = check_upstream(&target, TIMEOUT).await.unwrap();
+        assert_eq!(check.server_name.as_deref(), Some("demo"));
+        assert_eq!(check.server_version.as_deref(), Some("1.2.0"));
+    }
+
+    #[test]
+    fn test_target_requires_transport_fields() {
+        let config: UpstreamConfig =
+            serde_json::from_value(serde_json::json!({"transport": "http"})).unwrap();
+        let err = UpstreamTarget::from_upstream(&config).unwrap_err();
+        assert!(err.contains("'url'"));
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! DNS pinning for HTTP and SSE upstreams
+//!
+//! Clients are pinned to an address that passed SSRF validation, so a DNS
+//! answer that changes between validation and connect cannot point them at an
+//! internal service. Providers do rotate addresses, though. With a refresh
+//! interval, the host is resolved again periodically, the new answer goes
+//! through the same SSRF checks, and the client is swapped for one pinned to a
+//! current address. A failed or blocked lookup keeps the previous pin.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use super::{validate_url_for_ssrf, TransportError, UpstreamTls, ValidatedUrl};
+use crate::observability::record_dns_pin_refresh;
+
+/// Host name resolved to a fixed, SSRF-validated address
+#[derive(Debug, Clone)]
+struct DnsPin {
+    host: String,
+    addr: SocketAddr,
+}
+
+impl DnsPin {
+    /// Pin to the first validated address, if DNS resolution was performed
+    fn first(validated_url: &ValidatedUrl) -> Option<Self> {
+        validated_url.resolved_ips.first().map(|addr| Self {
+            host: validated_url.host.clone(),
+            addr: *addr,
+        })
+    }
+}
+
+/// Build an HTTP client for an upstream with an optional DNS pin and TLS settings
+fn build_client(
+    dns_pin: Option<&DnsPin>,
+    tls: Option<&UpstreamTls>,
+) -> Result<reqwest::Client, TransportError> {
+    let mut builder = reqwest::Client::builder().redirect(crate::egress::redirect_policy());
+    if let Some(pin) = dns_pin {
+        builder = builder.resolve(&pin.host, pin.addr);
+    }
+    if let Some(tls) = tls {
+        builder = tls.apply(builder);
+    }
+    builder
+        .build()
+        .map_err(|e| TransportError::Tls(format!("Failed to build HTTP client: {}", e)))
+}
+
+/// Client, pin and TLS settings, replaced together on a re-pin
+struct PinnedState {
+    client: reqwest::Client,
+    dns_pin: Option<DnsPin>,
+    tls: Option<UpstreamTls>,
+}
+
+/// HTTP client of a transport, shared with its DNS refresh task
+pub(super) struct PinnedClient {
+    /// URL re-validated on every refresh
+    url: String,
+    state: RwLock<PinnedState>,
+}
+
+impl PinnedClient {
+    /// Client without a DNS pin, for unchecked transports
+    pub(super) fn unpinned(url: &str) -> Arc<Self> {
+        Arc::new(Self {
+            url: url.to_string(),
+            state: RwLock::new(PinnedState {
+                client: reqwest::Client::new(),
+                dns_pin: None,
+                tls: None,
+            }),
+        })
+    }
+
+    /// Client pinned to the first address of a validated URL
+    ///
+    /// SECURITY: This prevents DNS rebinding attacks by configuring the client
+    /// to use the IP addresses that were validated during SSRF checks.
+    pub(super) fn pinned(validated_url: &ValidatedUrl) -> Arc<Self> {
+        let dns_pin = DnsPin::first(validated_url);
+        if let Some(ref pin) = dns_pin {
+            tracing::debug!("Pinning DNS for '{}' to {}", pin.host, pin.addr.ip());
+        }
+        let client =
+            build_client(dns_pin.as_ref(), None).unwrap_or_else(|_| reqwest::Client::new());
+        Arc::new(Self {
+            url: validated_url.url.clone(),
+            state: RwLock::new(PinnedState {
+                client,
+                dns_pin,
+                tls: None,
+            }),
+        })
+    }
+
+    /// The client to send the next request with
+    pub(super) fn current(&self) -> reqwest::Client {
+        self.state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .client
+            .clone()
+    }
+
+    /// Rebuild the client with TLS settings, keeping the pin
+    pub(super) fn set_tls(&self, tls: &UpstreamTls) -> Result<(), TransportError> {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.client = build_client(state.dns_pin.as_ref(), Some(tls))?;
+        state.tls = Some(tls.clone());
+        Ok(())
+    }
+
+    /// Whether the client is pinned to a resolved host name
+    ///
+    /// Unpinned clients and IP literals have nothing to re-resolve.
+    fn pins_host_name(&self) -> bool {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.dns_pin.as_ref().is_some_and(|pin| {
+            pin.host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .is_err()
+        })
+    }
+
+    /// Resolve and validate the host again, re-pinning if the pinned
+    /// address is no longer among the answers
+    ///
+    /// Returns whether the client was replaced. On error the current pin is
+    /// kept.
+    async fn refresh(&self) -> Result<bool, TransportError> {
+        let validated_url = validate_url_for_ssrf(&self.url).await?;
+
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        let Some(previous) = state.dns_pin.as_ref().map(|pin| pin.addr) else {
+            return Ok(false);
+        };
+        if validated_url.resolved_ips.contains(&previous) {
+            return Ok(false);
+        }
+        let Some(pin) = DnsPin::first(&validated_url) else {
+            return Ok(false);
+        };
+        state.client = build_client(Some(&pin), state.tls.as_ref())?;
+        tracing::info!(
+            host = %pin.host,
+            previous = %previous.ip(),
+            address = %pin.addr.ip(),
+            "Upstream address changed, re-pinned DNS"
+        );
+        state.dns_pin = Some(pin);
+        Ok(true)
+    }
+
+    /// Re-resolve the host every `interval`; zero disables the refresh
+    ///
+    /// The task holds a weak reference and stops once the transport is dropped.
+    pub(super) fn start_refresh(self: &Arc<Self>, interval: Duration) {
+        if interval.is_zero() || !self.pins_host_name() {
+            return;
+        }
+        let host = self.host();
+        let weak = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let Some(pinned) = weak.upgrade() else {
+                    tracing::debug!(host = %host, "DNS refresh task shutting down");
+                    break;
+                };
+                let result = match pinned.refresh().await {
+                    Ok(true) => "repinned",
+                    Ok(false) => "unchanged",
+                    Err(TransportError::SsrfBlocked(e)) => {
+                        tracing::warn!(host = %host, error = %e, "DNS refresh blocked, keeping pin");
+                        "blocked"
+                    }
+                    Err(e) => {
+                        tracing::warn!(host = %host, error = %e, "DNS refresh failed, keeping pin");
+                        "failed"
+                    }
+                };
+                record_dns_pin_refresh(&host, result);
+            }
+        });
+    }
+
+    /// Host name the client is pinned for
+    fn host(&self) -> String {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state
+            .dns_pin
+            .as_ref()
+            .map(|pin| pin.host.clone())
+            .unwrap_or_default()
+    }
+
+    /// Address the client currently connects to
+    #[cfg(test)]
+    fn pinned_addr(&self) -> Option<SocketAddr> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.dns_pin.as_ref().map(|pin| pin.addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validated(url: &str, host: &str, addr: &str) -> ValidatedUrl {
+        ValidatedUrl {
+            url: url.to_string(),
+            resolved_ips: vec![addr.parse().unwrap()],
+            host: host.to_string(),
+            port: 8080,
+        }
+    }
+
+    #[test]
+    fn test_pins_host_name() {
+        assert!(!PinnedClient::unpinned("http://mcp.example.com").pins_host_name());
+
+        let literal = validated(
+            "http://93.184.216.34:8080",
+            "93.184.216.34",
+            "93.184.216.34:8080",
+        );
+        assert!(!PinnedClient::pinned(&literal).pins_host_name());
+
+        let v6 = validated(
+            "http://[2606:2800::1]:8080",
+            "[2606:2800::1]",
+            "[2606:2800::1]:8080",
+        );
+        assert!(!PinnedClient::pinned(&v6).pins_host_name());
+
+        let host = validated(
+            "http://mcp.example.com:8080",
+            "mcp.example.com",
+            "93.184.216.34:8080",
+        );
+        assert!(PinnedClient::pinned(&host).pins_host_name());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_keeps_pin_when_blocked() {
+        // "localhost" now resolves to a loopback address, which SSRF rules block
+        let pinned = PinnedClient::pinned(&validated(
+            "http://localhost:8080/mcp",
+            "localhost",
+            "93.184.216.34:8080",
+        ));
+        let err = pinned.refresh().await.unwrap_err();
+        assert!(matches!(err, TransportError::SsrfBlocked(_)));
+        assert_eq!(
+            pinned.pinned_addr(),
+            Some("93.184.216.34:8080".parse().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_unpinned_is_noop() {
+        let pinned = PinnedClient::unpinned("http://93.184.216.34:8080/mcp");
+        assert!(!pinned.refresh().await.unwrap());
+        assert_eq!(pinned.pinned_addr(), None);
+    }
+}
@@ -0,0 +1,792 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Upstream header forwarding and injection for HTTP/SSE transports
+//!
+//! Each HTTP-based upstream can be configured to:
+//! - forward an allowlist of client request headers
+//! - inject headers, either static or templated from the authenticated identity
+//!   (e.g. `X-Forwarded-User = "{identity.id}"`)
+//!
+//! The server scopes a [`ForwardContext`] around each proxied request; transports
+//! read it when building the upstream request. Requests made outside that scope
+//! (e.g. SSE reconnects) only receive headers that don't depend on it.
+
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::{Algorithm, EncodingKey};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::future::Future;
+
+use crate::auth::Identity;
+use crate::config::{AssertionAlgorithm, IdentityAssertionConfig, UpstreamHeadersConfig};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Headers that carry the client's gateway credentials.
+/// SECURITY: Forwarding these would hand the client's mcp-guard credential to
+/// the upstream server.
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie"];
+
+/// Hop-by-hop, framing and encoding headers managed by the HTTP client itself.
+/// The client negotiates gzip/br with the upstream and decodes responses.
+const RESERVED_HEADERS: &[&str] = &[
+    "accept-encoding",
+    "connection",
+    "content-encoding",
+    "content-length",
+    "content-type",
+    "host",
+    "keep-alive",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Minimum HS256 secret length for identity assertions (256 bits).
+const MIN_ASSERTION_SECRET_LEN: usize = 32;
+
+// ============================================================================
+// Request Context
+// ============================================================================
+
+/// Per-request data available to transports while forwarding a client request
+#[derive(Debug, Clone)]
+pub struct ForwardContext {
+    /// Headers of the incoming client request
+    pub client_headers: HeaderMap,
+    /// Authenticated identity of the caller
+    pub identity: Identity,
+}
+
+tokio::task_local! {
+    static FORWARD_CONTEXT: ForwardContext;
+}
+
+/// Run `f` with `context` visible to any transport it sends through
+pub async fn with_forward_context<F: Future>(context: ForwardContext, f: F) -> F::Output {
+    FORWARD_CONTEXT.scope(context, f).await
+}
+
+/// Value of a client request header in the current forward context, if any
+pub(crate) fn client_header(name: &str) -> Option<String> {
+    FORWARD_CONTEXT
+        .try_with(|ctx| {
+            ctx.client_headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        })
+        .ok()
+        .flatten()
+}
+
+/// ID of the identity in the current forward context, if any
+pub(crate) fn forwarded_identity_id() -> Option<String> {
+    FORWARD_CONTEXT.try_with(|ctx| ctx.identity.id.clone()).ok()
+}
+
+/// Apply `f` to the identity in the current forward context, if any
+pub(crate) fn with_forwarded_identity<R>(f: impl FnOnce(&Identity) -> R) -> Option<R> {
+    FORWARD_CONTEXT.try_with(|ctx| f(&ctx.identity)).ok()
+}
+
+// ============================================================================
+// Header Templates
+// ============================================================================
+
+/// A piece of a header value template
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    IdentityId,
+    IdentityName,
+    IdentityClaim(String),
+}
+
+/// Header value with optional `{identity.*}` placeholders
+#[derive(Debug, Clone, PartialEq)]
+struct HeaderTemplate {
+    segments: Vec<Segment>,
+}
+
+impl HeaderTemplate {
+    /// Parse a template such as `"user={identity.id}"`
+    ///
+    /// Supported placeholders: `{identity.id}`, `{identity.name}` and
+    /// `{identity.claims.<claim>}`.
+    fn parse(template: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .map(|i| start + i)
+                .ok_or_else(|| format!("unclosed placeholder in '{}'", template))?;
+
+            let placeholder = &rest[start + 1..end];
+            segments.push(match placeholder {
+                "identity.id" => Segment::IdentityId,
+                "identity.name" => Segment::IdentityName,
+                _ => match placeholder.strip_prefix("identity.claims.") {
+                    Some(claim) if !claim.is_empty() => Segment::IdentityClaim(claim.to_string()),
+                    _ => return Err(format!("unknown placeholder '{{{}}}'", placeholder)),
+                },
+            });
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Whether rendering needs an authenticated identity
+    fn needs_identity(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|s| !matches!(s, Segment::Literal(_)))
+    }
+
+    /// Render the template, or `None` if a referenced value is unavailable
+    fn render(&self, identity: Option<&Identity>) -> Option<String> {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(s) => out.push_str(s),
+                Segment::IdentityId => out.push_str(&identity?.id),
+                Segment::IdentityName => out.push_str(identity?.name.as_deref()?),
+                Segment::IdentityClaim(claim) => match identity?.claims.get(claim)? {
+                    serde_json::Value::String(s) => out.push_str(s),
+                    serde_json::Value::Number(n) => out.push_str(&n.to_string()),
+                    serde_json::Value::Bool(b) => out.push_str(&b.to_string()),
+                    _ => return None,
+                },
+            }
+        }
+        Some(out)
+    }
+}
+
+// ============================================================================
+// Identity Assertions
+// ============================================================================
+
+/// Claims of a signed identity assertion
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct AssertionClaims {
+    pub iss: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub aud: Option<String>,
+    pub sub: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+    /// Scopes granted to the caller (from the `scope`/`scp` claims, if any)
+    pub scopes: Vec<String>,
+    /// Tools the caller may use; `None` means unrestricted
+    pub allowed_tools: Option<Vec<String>>,
+    pub iat: u64,
+    pub exp: u64,
+    pub jti: String,
+}
+
+/// Signs on-behalf-of assertions with the gateway key
+#[derive(Clone)]
+struct IdentityAssertion {
+    header: HeaderName,
+    algorithm: Algorithm,
+    key: EncodingKey,
+    /// Public key for RS256/ES256, published in the gateway's JWK Set
+    jwk: Option<Jwk>,
+    issuer: String,
+    audience: Option<String>,
+    ttl_secs: u64,
+}
+
+impl std::fmt::Debug for IdentityAssertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // SECURITY: never print the signing key
+        f.debug_struct("IdentityAssertion")
+            .field("header", &self.header)
+            .field("algorithm", &self.algorithm)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("ttl_secs", &self.ttl_secs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl IdentityAssertion {
+    fn compile(config: &IdentityAssertionConfig) -> Result<Self, String> {
+        let header = parse_header_name(&config.header)?;
+
+        let (algorithm, key, jwk) = match config.algorithm {
+            AssertionAlgorithm::Hs256 => {
+                let secret = config
+                    .secret
+                    .as_ref()
+                    .ok_or("assertion algorithm HS256 requires 'secret'")?;
+                if secret.len() < MIN_ASSERTION_SECRET_LEN {
+                    return Err(format!(
+                        "assertion secret must be at least {} characters",
+                        MIN_ASSERTION_SECRET_LEN
+                    ));
+                }
+                (
+                    Algorithm::HS256,
+                    EncodingKey::from_secret(secret.as_bytes()),
+                    None,
+                )
+            }
+            AssertionAlgorithm::Rs256 | AssertionAlgorithm::Es256 => {
+                let path = config
+                    .private_key_path
+                    .as_ref()
+                    .ok_or("assertion algorithms RS256/ES256 require 'private_key_path'")?;
+                let pem = std::fs::read(path).map_err(|e| {
+                    format!("failed to read assertion key '{}': {}", path.display(), e)
+                })?;
+                let key = if config.algorithm == AssertionAlgorithm::Rs256 {
+                    EncodingKey::from_rsa_pem(&pem).map(|k| (Algorithm::RS256, k))
+                } else {
+                    EncodingKey::from_ec_pem(&pem).map(|k| (Algorithm::ES256, k))
+                };
+                let invalid =
+                    |e: String| format!("invalid assertion key '{}': {}", path.display(), e);
+                let (algorithm, key) = key.map_err(|e| invalid(e.to_string()))?;
+                // Public half, published in the gateway's JWK Set
+                let jwk = super::signing::decode_pem(&pem)
+                    .and_then(|der| super::signing::public_jwk(algorithm, &der))
+                    .map_err(invalid)?;
+                (algorithm, key, Some(jwk))
+            }
+        };
+
+        if config.ttl_secs == 0 {
+            return Err("assertion ttl_secs must be greater than 0".to_string());
+        }
+
+        Ok(Self {
+            header,
+            algorithm,
+            key,
+            jwk,
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            ttl_secs: config.ttl_secs,
+        })
+    }
+
+    fn sign(&self, identity: &Identity) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let claims = AssertionClaims {
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+            sub: identity.id.clone(),
+            name: identity.name.clone(),
+            scopes: identity_scopes(identity),
+            allowed_tools: identity.allowed_tools.clone(),
+            iat: now,
+            exp: now + self.ttl_secs,
+            jti: uuid::Uuid::new_v4().to_string(),
+        };
+
+        let mut header = jsonwebtoken::Header::new(self.algorithm);
+        header.kid = self.jwk.as_ref().and_then(|jwk| jwk.common.key_id.clone());
+        jsonwebtoken::encode(&header, &claims, &self.key)
+    }
+
+    /// Header value for a signed token (`Bearer` prefixed for Authorization)
+    fn header_value(&self, token: &str) -> Option<HeaderValue> {
+        if self.header == reqwest::header::AUTHORIZATION {
+            HeaderValue::from_str(&format!("Bearer {}", token)).ok()
+        } else {
+            HeaderValue::from_str(token).ok()
+        }
+    }
+}
+
+/// Scopes carried in an identity's token claims (`scope` string/array or `scp`)
+fn identity_scopes(identity: &Identity) -> Vec<String> {
+    let value = identity
+        .claims
+        .get("scope")
+        .or_else(|| identity.claims.get("scp"));
+    match value {
+        Some(serde_json::Value::String(s)) => s.split_whitespace().map(String::from).collect(),
+        Some(serde_json::Value::Array(arr)) => arr
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(String::from)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// ============================================================================
+// Upstream Header Policy
+// ============================================================================
+
+/// Compiled header forwarding/injection policy for one upstream
+#[derive(Debug, Clone, Default)]
+pub struct UpstreamHeaders {
+    forward: Vec<HeaderName>,
+    inject: Vec<(HeaderName, HeaderTemplate)>,
+    assertion: Option<IdentityAssertion>,
+}
+
+impl UpstreamHeaders {
+    /// Compile and validate a header configuration
+    pub fn compile(config: &UpstreamHeadersConfig) -> Result<Self, String> {
+        let mut forward = Vec::with_capacity(config.forward.len());
+        for name in &config.forward {
+            let header = parse_header_name(name)?;
+            if CREDENTIAL_HEADERS.contains(&header.as_str()) {
+                return Err(format!(
+                    "'{}' cannot be forwarded: it carries the client's gateway credentials",
+                    name
+                ));
+            }
+            forward.push(header);
+        }
+
+        let mut inject = Vec::with_capacity(config.inject.len());
+        for (name, value) in &config.inject {
+            let header = parse_header_name(name)?;
+            let template = HeaderTemplate::parse(value)
+                .map_err(|e| format!("invalid value for header '{}': {}", name, e))?;
+            inject.push((header, template));
+        }
+
+        let assertion = config
+            .assertion
+            .as_ref()
+            .map(IdentityAssertion::compile)
+            .transpose()?;
+
+        Ok(Self {
+            forward,
+            inject,
+            assertion,
+        })
+    }
+
+    /// Public key of an RS256/ES256 identity assertion, if configured
+    pub fn assertion_key(&self) -> Option<&Jwk> {
+        self.assertion.as_ref().and_then(|a| a.jwk.as_ref())
+    }
+
+    /// Whether the policy adds nothing to upstream requests
+    pub fn is_empty(&self) -> bool {
+        self.forward.is_empty() && self.inject.is_empty() && self.assertion.is_none()
+    }
+
+    /// Resolve the headers to add to an upstream request
+    ///
+    /// Injected headers replace forwarded ones of the same name, so a client
+    /// can't spoof a header the gateway sets (e.g. `X-Forwarded-User`).
+    pub fn resolve(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if self.is_empty() {
+            return headers;
+        }
+
+        let _ = FORWARD_CONTEXT.try_with(|ctx| {
+            for name in &self.forward {
+                for value in ctx.client_headers.get_all(name) {
+                    headers.append(name.clone(), value.clone());
+                }
+            }
+        });
+
+        for (name, template) in &self.inject {
+            let rendered = if template.needs_identity() {
+                FORWARD_CONTEXT
+                    .try_with(|ctx| template.render(Some(&ctx.identity)))
+                    .ok()
+                    .flatten()
+            } else {
+                template.render(None)
+            };
+
+            // SECURITY: HeaderValue rejects CR/LF, so identity data can't split headers
+            match rendered.and_then(|v| HeaderValue::from_str(&v).ok()) {
+                Some(value) => {
+                    headers.insert(name.clone(), value);
+                }
+                None => {
+                    headers.remove(name);
+                    tracing::debug!(header = %name, "Skipping injected header with no value");
+                }
+            }
+        }
+
+        // The assertion is set last so neither clients nor templates can override it
+        if let Some(ref assertion) = self.assertion {
+            headers.remove(&assertion.header);
+            let signed = FORWARD_CONTEXT.try_with(|ctx| assertion.sign(&ctx.identity));
+            match signed {
+                Ok(Ok(token)) => {
+                    if let Some(value) = assertion.header_value(&token) {
+                        headers.insert(assertion.header.clone(), value);
+                    }
+                }
+                Ok(Err(e)) => {
+                    tracing::error!(error = %e, "Failed to sign identity assertion");
+                }
+                // Outside a client request there is no identity to assert
+                Err(_) => {}
+            }
+        }
+
+        headers
+    }
+}
+
+pub(super) fn parse_header_name(name: &str) -> Result<HeaderName, String> {
+    let header = HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| format!("'{}' is not a valid header name", name))?;
+    if RESERVED_HEADERS.contains(&header.as_str()) {
+        return Err(format!(
+            "'{}' is managed by the transport and cannot be set",
+            name
+        ));
+    }
+    Ok(header)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn identity() -> Identity {
+        let mut claims = HashMap::new();
+        claims.insert("org".to_string(), serde_json::json!("acme"));
+        claims.insert("level".to_string(), serde_json::json!(3));
+        Identity {
+            id: "user-123".to_string(),
+            name: Some("Alice".to_string()),
+            allowed_tools: None,
+            allowed_resources: None,
+            allowed_prompts: None,
+            rate_limit: None,
+            claims,
+            tenant: None,
+            argument_constraints: None,
+        }
+    }
+
+    fn context(client_headers: &[(&str, &str)]) -> ForwardContext {
+        let mut headers = HeaderMap::new();
+        for (name, value) in client_headers {
+            headers.append(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        ForwardContext {
+            client_headers: headers,
+            identity: identity(),
+        }
+    }
+
+    fn compile(forward: &[&str], inject: &[(&str, &str)]) -> Result<UpstreamHeaders, String> {
+        UpstreamHeaders::compile(&UpstreamHeadersConfig {
+            forward: forward.iter().map(|s| s.to_string()).collect(),
+            inject: inject
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            assertion: None,
+        })
+    }
+
+    const ASSERTION_SECRET: &str = "assertion-secret-that-is-at-least-32-chars";
+
+    fn assertion_config() -> IdentityAssertionConfig {
+        IdentityAssertionConfig {
+            header: "X-MCP-Guard-Identity".to_string(),
+            algorithm: AssertionAlgorithm::Hs256,
+            secret: Some(ASSERTION_SECRET.to_string()),
+            private_key_path: None,
+            issuer: "mcp-guard".to_string(),
+            audience: Some("github-mcp".to_string()),
+            ttl_secs: 60,
+        }
+    }
+
+    fn compile_with_assertion(
+        forward: &[&str],
+        assertion: IdentityAssertionConfig,
+    ) -> Result<UpstreamHeaders, String> {
+        UpstreamHeaders::compile(&UpstreamHeadersConfig {
+            forward: forward.iter().map(|s| s.to_string()).collect(),
+            inject: HashMap::new(),
+            assertion: Some(assertion),
+        })
+    }
+
+    fn decode_assertion(token: &str) -> AssertionClaims {
+        let mut validation = jsonwebtoken::Validation::new(Algorithm::HS256);
+        validation.set_audience(&["github-mcp"]);
+        validation.set_issuer(&["mcp-guard"]);
+        jsonwebtoken::decode::<AssertionClaims>(
+            token,
+            &jsonwebtoken::DecodingKey::from_secret(ASSERTION_SECRET.as_bytes()),
+            &validation,
+        )
+        .unwrap()
+        .claims
+    }
+
+    #[test]
+    fn test_template_parse_and_render() {
+        let template = HeaderTemplate::parse("{identity.id}@{identity.claims.org}").unwrap();
+        assert!(template.needs_identity());
+        assert_eq!(
+            template.render(Some(&identity())).as_deref(),
+            Some("user-123@acme")
+        );
+
+        let template = HeaderTemplate::parse("level-{identity.claims.level}").unwrap();
+        assert_eq!(
+            template.render(Some(&identity())).as_deref(),
+            Some("level-3")
+        );
+
+        let template = HeaderTemplate::parse("static").unwrap();
+        assert!(!template.needs_identity());
+        assert_eq!(template.render(None).as_deref(), Some("static"));
+    }
+
+    #[test]
+    fn test_template_missing_value() {
+        let template = HeaderTemplate::parse("{identity.claims.missing}").unwrap();
+        assert!(template.render(Some(&identity())).is_none());
+        let template = HeaderTemplate::parse("{identity.id}").unwrap();
+        assert!(template.render(None).is_none());
+    }
+
+    #[test]
+    fn test_template_parse_errors() {
+        assert!(HeaderTemplate::parse("{identity.secret}").is_err());
+        assert!(HeaderTemplate::parse("{identity.claims.}").is_err());
+        assert!(HeaderTemplate::parse("{identity.id").is_err());
+    }
+
+    #[test]
+    fn test_compile_rejects_unsafe_headers() {
+        assert!(compile(&["authorization"], &[]).is_err());
+        assert!(compile(&["Cookie"], &[]).is_err());
+        assert!(compile(&["host"], &[]).is_err());
+        assert!(compile(&[], &[("Content-Length", "1")]).is_err());
+        assert!(compile(&["bad header"], &[]).is_err());
+        // Injecting a static upstream credential is allowed
+        assert!(compile(&[], &[("Authorization", "Bearer upstream-token")]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_forwards_and_injects() {
+        let policy = compile(
+            &["x-request-id", "accept-language"],
+            &[
+                ("X-Forwarded-User", "{identity.id}"),
+                ("X-Gateway", "mcp-guard"),
+            ],
+        )
+        .unwrap();
+
+        let headers = with_forward_context(
+            context(&[
+                ("x-request-id", "req-1"),
+                ("x-secret", "nope"),
+                ("authorization", "Bearer client"),
+            ]),
+            async { policy.resolve() },
+        )
+        .await;
+
+        assert_eq!(headers.get("x-request-id").unwrap(), "req-1");
+        assert_eq!(headers.get("x-forwarded-user").unwrap(), "user-123");
+        assert_eq!(headers.get("x-gateway").unwrap(), "mcp-guard");
+        assert!(headers.get("x-secret").is_none());
+        assert!(headers.get("authorization").is_none());
+        assert!(headers.get("accept-language").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_injected_overrides_forwarded() {
+        let policy = compile(
+            &["x-forwarded-user"],
+            &[("X-Forwarded-User", "{identity.id}")],
+        )
+        .unwrap();
+
+        let headers = with_forward_context(context(&[("x-forwarded-user", "admin")]), async {
+            policy.resolve()
+        })
+        .await;
+
+        let values: Vec<_> = headers.get_all("x-forwarded-user").iter().collect();
+        assert_eq!(values, vec!["user-123"]);
+    }
+
+    #[test]
+    fn test_resolve_without_context() {
+        let policy = compile(
+            &["x-request-id"],
+            &[
+                ("X-Forwarded-User", "{identity.id}"),
+                ("X-Gateway", "mcp-guard"),
+            ],
+        )
+        .unwrap();
+
+        // Only headers independent of the request are added
+        let headers = policy.resolve();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers.get("x-gateway").unwrap(), "mcp-guard");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_drops_spoofed_forwarded_when_template_fails() {
+        let policy = compile(
+            &["x-org"],
+            &[("X-Org", "{identity.claims.team}")], // claim not present
+        )
+        .unwrap();
+
+        let headers =
+            with_forward_context(context(&[("x-org", "evil")]), async { policy.resolve() }).await;
+        assert!(headers.get("x-org").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_assertion_signed_with_identity_claims() {
+        let policy = compile_with_assertion(&[], assertion_config()).unwrap();
+        // HS256 secrets are never published
+        assert!(policy.assertion_key().is_none());
+
+        let mut identity = identity();
+        identity.allowed_tools = Some(vec!["read_file".to_string()]);
+        identity.claims.insert(
+            "scope".to_string(),
+            serde_json::json!("read:files write:files"),
+        );
+        let context = ForwardContext {
+            client_headers: HeaderMap::new(),
+            identity,
+        };
+
+        let headers = with_forward_context(context, async { policy.resolve() }).await;
+        let token = headers
+            .get("x-mcp-guard-identity")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        let claims = decode_assertion(token);
+
+        assert_eq!(claims.sub, "user-123");
+        assert_eq!(claims.name.as_deref(), Some("Alice"));
+        assert_eq!(claims.scopes, vec!["read:files", "write:files"]);
+        assert_eq!(claims.allowed_tools, Some(vec!["read_file".to_string()]));
+        assert_eq!(claims.exp - claims.iat, 60);
+    }
+
+    #[tokio::test]
+    async fn test_assertion_cannot_be_spoofed_by_client() {
+        let policy = compile_with_assertion(&["x-mcp-guard-identity"], assertion_config()).unwrap();
+
+        let headers = with_forward_context(
+            context(&[("x-mcp-guard-identity", "forged.token.value")]),
+            async { policy.resolve() },
+        )
+        .await;
+
+        let values: Vec<_> = headers.get_all("x-mcp-guard-identity").iter().collect();
+        assert_eq!(values.len(), 1);
+        assert_ne!(values[0], "forged.token.value");
+        assert_eq!(
+            decode_assertion(values[0].to_str().unwrap()).sub,
+            "user-123"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_assertion_in_authorization_header_uses_bearer() {
+        let mut config = assertion_config();
+        config.header = "Authorization".to_string();
+        let policy = compile_with_assertion(&[], config).unwrap();
+
+        let headers = with_forward_context(context(&[]), async { policy.resolve() }).await;
+        let value = headers.get("authorization").unwrap().to_str().unwrap();
+        let token = value.strip_prefix("Bearer ").unwrap();
+        assert_eq!(decode_assertion(token).sub, "user-123");
+    }
+
+    #[test]
+    fn test_assertion_omitted_without_context() {
+        let policy = compile_with_assertion(&[], assertion_config()).unwrap();
+        assert!(policy.resolve().is_empty());
+    }
+
+    #[test]
+    fn test_assertion_compile_errors() {
+        let mut config = assertion_config();
+        config.secret = Some("too-short".to_string());
+        assert!(compile_with_assertion(&[], config).is_err());
+
+        let mut config = assertion_config();
+        config.secret = None;
+        assert!(compile_with_assertion(&[], config).is_err());
+
+        let mut config = assertion_config();
+        config.algorithm = AssertionAlgorithm::Rs256;
+        assert!(compile_with_assertion(&[], config.clone()).is_err());
+        config.private_key_path = Some("/nonexistent/key.pem".into());
+        let err = compile_with_assertion(&[], config).unwrap_err();
+        assert!(err.contains("failed to read assertion key"));
+
+        let mut config = assertion_config();
+        config.ttl_secs = 0;
+        assert!(compile_with_assertion(&[], config).is_err());
+    }
+
+    #[test]
+    fn test_assertion_debug_hides_key() {
+        let policy = compile_with_assertion(&[], assertion_config()).unwrap();
+        let debug = format!("{:?}", policy);
+        assert!(!debug.contains(ASSERTION_SECRET));
+        assert!(debug.contains("IdentityAssertion"));
+    }
+}
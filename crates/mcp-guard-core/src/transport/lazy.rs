@@ -0,0 +1,419 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! On-demand stdio upstreams
+//!
+//! A route with `spawn = "on_demand"` starts its process on the first call
+//! instead of at startup, and stops it again once no call has been made for
+//! the idle TTL. The next call starts a fresh process; the client's last
+//! `initialize` request is replayed to it first, so the upstream sees a
+//! normal MCP handshake.
+
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant};
+
+use super::{
+    validate_args_for_injection, validate_command_for_injection, Message, RawMessage, StdioOptions,
+    StdioTransport, Transport, TransportError,
+};
+use crate::observability::{record_upstream_reaped, record_upstream_spawn};
+
+/// Shortest interval between idle checks
+const MIN_REAP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Stdio transport that starts its process on first use and stops it when idle
+pub struct LazyStdioTransport {
+    /// Route name, for logs and metrics
+    name: String,
+    /// Command used to spawn the subprocess
+    command: String,
+    /// Arguments used to spawn the subprocess
+    args: Vec<String>,
+    /// Environment and working directory used to spawn the subprocess
+    options: StdioOptions,
+    /// Idle time after which the subprocess is stopped (`None`: never)
+    idle_ttl: Option<Duration>,
+    /// Currently running subprocess, if any
+    process: RwLock<Option<Arc<StdioTransport>>>,
+    /// Serializes spawning, reaping and the start of each send
+    lifecycle: tokio::sync::Mutex<()>,
+    /// Last `initialize` request, replayed to a respawned subprocess
+    handshake: Mutex<Option<Message>>,
+    /// Requests sent whose response has not been read yet
+    in_flight: AtomicUsize,
+    /// When a call last started or finished
+    last_used: Mutex<Instant>,
+}
+
+impl LazyStdioTransport {
+    /// Create an on-demand transport for `command`, without spawning it
+    ///
+    /// With an `idle_ttl`, a background task stops the subprocess once it has
+    /// been idle that long; the task ends when the transport is dropped.
+    ///
+    /// # Errors
+    /// Returns `TransportError::CommandValidation` if the command or arguments
+    /// contain shell metacharacters or attempt direct shell execution.
+    pub fn new(
+        name: &str,
+        command: &str,
+        args: &[String],
+        options: StdioOptions,
+        idle_ttl: Option<Duration>,
+    ) -> Result<Arc<Self>, TransportError> {
+        validate_command_for_injection(command)?;
+        validate_args_for_injection(args)?;
+        Ok(Self::new_unchecked(name, command, args, options, idle_ttl))
+    }
+
+    /// Create an on-demand transport without command validation
+    ///
+    /// # Safety
+    /// Same as [`StdioTransport::spawn_unchecked`].
+    pub fn new_unchecked(
+        name: &str,
+        command: &str,
+        args: &[String],
+        options: StdioOptions,
+        idle_ttl: Option<Duration>,
+    ) -> Arc<Self> {
+        let transport = Arc::new(Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.to_vec(),
+            options,
+            idle_ttl,
+            process: RwLock::new(None),
+            lifecycle: tokio::sync::Mutex::new(()),
+            handshake: Mutex::new(None),
+            in_flight: AtomicUsize::new(0),
+            last_used: Mutex::new(Instant::now()),
+        });
+        if let Some(ttl) = idle_ttl {
+            spawn_reaper(Arc::downgrade(&transport), ttl);
+        }
+        transport
+    }
+
+    /// Whether the subprocess is currently running
+    pub fn is_running(&self) -> bool {
+        self.current().is_some()
+    }
+
+    /// Stop the subprocess if it has been idle for the TTL
+    ///
+    /// Returns whether it was stopped. Called periodically by the reaper task.
+    pub async fn reap_if_idle(&self) -> bool {
+        let Some(ttl) = self.idle_ttl else {
+            return false;
+        };
+        let _lifecycle = self.lifecycle.lock().await;
+        let idle = self.idle_for();
+        if self.in_flight.load(Ordering::Acquire) > 0 || idle < ttl {
+            return false;
+        }
+        let Some(process) = self.take() else {
+            return false;
+        };
+
+        tracing::info!(
+            upstream = %self.name,
+            idle_secs = idle.as_secs(),
+            "Stopping idle stdio upstream"
+        );
+        record_upstream_reaped(&self.name);
+        if let Err(e) = process.close().await {
+            tracing::debug!(upstream = %self.name, error = %e, "Error stopping idle upstream");
+        }
+        true
+    }
+
+    /// The currently running subprocess
+    fn current(&self) -> Option<Arc<StdioTransport>> {
+        self.process
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Remove the running subprocess, leaving the transport idle
+    fn take(&self) -> Option<Arc<StdioTransport>> {
+        self.process
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+    }
+
+    fn touch(&self) {
+        *self
+            .last_used
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_used
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .elapsed()
+    }
+
+    /// The running subprocess, spawning it first if needed
+    ///
+    /// Must be called with `lifecycle` held.
+    async fn running(&self, replay_handshake: bool) -> Result<Arc<StdioTransport>, TransportError> {
+        if let Some(process) = self.current() {
+            return Ok(process);
+        }
+
+        let started = Instant::now();
+        let result = self.spawn(replay_handshake).await;
+        record_upstream_spawn(&self.name, started.elapsed(), result.is_ok());
+        let process = Arc::new(result?);
+        tracing::info!(
+            upstream = %self.name,
+            command = %self.command,
+            spawn_ms = started.elapsed().as_millis() as u64,
+            "Started on-demand stdio upstream"
+        );
+
+        *self
+            .process
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::clone(&process));
+        Ok(process)
+    }
+
+    async fn spawn(&self, replay_handshake: bool) -> Result<StdioTransport, TransportError> {
+        let process =
+            StdioTransport::spawn_unchecked_with(&self.command, &self.args, self.options.clone())
+                .await?;
+
+        let handshake = self
+            .handshake
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        if let Some(initialize) = handshake.filter(|_| replay_handshake) {
+            let replayed = async {
+                process.send(initialize).await?;
+                process.receive_raw().await?;
+                process
+                    .send(Message {
+                        jsonrpc: "2.0".to_string(),
+                        id: None,
+                        method: Some("notifications/initialized".to_string()),
+                        params: None,
+                        result: None,
+                        error: None,
+                    })
+                    .await
+            };
+            if let Err(e) = replayed.await {
+                let _ = process.close().await;
+                return Err(e);
+            }
+        }
+        Ok(process)
+    }
+}
+
+/// Decrements the in-flight count when a receive finishes or is cancelled
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1));
+    }
+}
+
+/// Periodically stop the transport's subprocess once it has been idle for `ttl`
+fn spawn_reaper(transport: Weak<LazyStdioTransport>, ttl: Duration) {
+    let interval = (ttl / 2).max(MIN_REAP_INTERVAL);
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            let Some(transport) = transport.upgrade() else {
+                break;
+            };
+            transport.reap_if_idle().await;
+        }
+    });
+}
+
+#[async_trait]
+impl Transport for LazyStdioTransport {
+    async fn send(&self, message: Message) -> Result<(), TransportError> {
+        let is_initialize = message.method.as_deref() == Some("initialize");
+        let is_request = message.is_request();
+
+        let process = {
+            let _lifecycle = self.lifecycle.lock().await;
+            let process = self.running(!is_initialize).await?;
+            if is_initialize {
+                *self
+                    .handshake
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(message.clone());
+            }
+            if is_request {
+                self.in_flight.fetch_add(1, Ordering::AcqRel);
+            }
+            self.touch();
+            process
+        };
+
+        let result = process.send(message).await;
+        if result.is_err() && is_request {
+            drop(InFlight(&self.in_flight));
+        }
+        result
+    }
+
+    async fn receive(&self) -> Result<Message, TransportError> {
+        self.receive_raw().await?.into_message()
+    }
+
+    async fn receive_raw(&self) -> Result<RawMessage, TransportError> {
+        let process = self.current().ok_or(TransportError::ConnectionClosed)?;
+        let _in_flight = InFlight(&self.in_flight);
+        let result = process.receive_raw().await;
+        self.touch();
+        result
+    }
+
+    async fn close(&self) -> Result<(), TransportError> {
+        let _lifecycle = self.lifecycle.lock().await;
+        match self.take() {
+            Some(process) => process.close().await,
+            None => Ok(()),
+        }
+    }
+
+    fn transport_type(&self) -> &'static str {
+        "stdio"
+    }
+
+    /// An idle transport is healthy: the next call starts the subprocess
+    fn is_healthy(&self) -> bool {
+        self.current().map_or(true, |process| process.is_healthy())
+    }
+
+    async fn restart(&self) -> Result<(), TransportError> {
+        let _lifecycle = self.lifecycle.lock().await;
+        match self.current() {
+            Some(process) => process.restart().await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Script answering each request with the process ID and the request count
+    fn counting_server() -> Vec<String> {
+        vec![
+            "-c".to_string(),
+            r#"n=0; while read line; do case "$line" in *'"id"'*) n=$((n+1)); printf '{"jsonrpc":"2.0","id":1,"result":{"pid":%s,"n":%s}}\n' $$ $n;; esac; done"#
+                .to_string(),
+        ]
+    }
+
+    async fn call(transport: &LazyStdioTransport, method: &str) -> serde_json::Value {
+        transport
+            .send(Message::request(1, method, None))
+            .await
+            .unwrap();
+        transport.receive().await.unwrap().result.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_spawns_on_first_call() {
+        let transport = LazyStdioTransport::new_unchecked(
+            "lazy",
+            "sh",
+            &counting_server(),
+            StdioOptions::default(),
+            None,
+        );
+        assert!(!transport.is_running());
+        assert!(transport.is_healthy());
+
+        let first = call(&transport, "tools/list").await;
+        assert!(transport.is_running());
+        let second = call(&transport, "tools/list").await;
+        assert_eq!(first["pid"], second["pid"]);
+        assert_eq!(second["n"], 2);
+
+        // Without a TTL the process is never reaped
+        assert!(!transport.reap_if_idle().await);
+        transport.close().await.unwrap();
+        assert!(!transport.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_reaps_idle_process_and_replays_initialize() {
+        let transport = LazyStdioTransport::new_unchecked(
+            "lazy",
+            "sh",
+            &counting_server(),
+            StdioOptions::default(),
+            Some(Duration::from_millis(50)),
+        );
+
+        let first = call(&transport, "initialize").await;
+        assert_eq!(first["n"], 1);
+        assert!(!transport.reap_if_idle().await);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(transport.reap_if_idle().await);
+        assert!(!transport.is_running());
+
+        // The respawned process sees the replayed initialize first
+        let after = call(&transport, "tools/list").await;
+        assert_ne!(first["pid"], after["pid"]);
+        assert_eq!(after["n"], 2);
+        transport.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pending_request_blocks_reaping() {
+        let transport = LazyStdioTransport::new_unchecked(
+            "lazy",
+            "sh",
+            &counting_server(),
+            StdioOptions::default(),
+            Some(Duration::from_millis(10)),
+        );
+        transport
+            .send(Message::request(1, "tools/list", None))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(!transport.reap_if_idle().await);
+
+        transport.receive().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(transport.reap_if_idle().await);
+    }
+}
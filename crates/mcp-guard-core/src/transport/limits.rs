@@ -0,0 +1,256 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Upstream response size limits
+//!
+//! Transports refuse to read messages over [`MAX_MESSAGE_SIZE`]; below that,
+//! each upstream can set a smaller `response_limits.max_bytes`. Oversized
+//! responses are replaced with a JSON-RPC error, or with the truncate policy
+//! have their `tools/call` content cut to fit and annotated so the client
+//! knows the result is incomplete.
+
+use serde_json::{json, Value};
+
+use super::{Message, MAX_MESSAGE_SIZE};
+use crate::config::{ResponseLimitConfig, ResponseSizePolicy};
+use crate::observability::record_oversized_response;
+
+/// JSON-RPC error code for responses rejected by the size limit
+const RESPONSE_TOO_LARGE: i32 = -32000;
+
+/// `_meta` key describing a truncated result
+pub const TRUNCATED_META_KEY: &str = "mcp-guard/truncated";
+
+/// Apply an upstream's response size limit
+///
+/// Returns the response unchanged if it fits, otherwise a truncated copy or a
+/// JSON-RPC error depending on the policy. `upstream` labels the metric
+/// (`default` in single-server mode).
+pub fn enforce_response_limit(
+    response: Message,
+    limits: &ResponseLimitConfig,
+    upstream: &str,
+) -> Message {
+    let max_bytes = limits.max_bytes.min(MAX_MESSAGE_SIZE);
+    let size = serialized_len(&response);
+    if size <= max_bytes {
+        return response;
+    }
+
+    if limits.policy == ResponseSizePolicy::Truncate {
+        let mut truncated = response.clone();
+        if truncate_content(&mut truncated, size, max_bytes) {
+            tracing::warn!(
+                upstream = %upstream,
+                size,
+                max_bytes,
+                "Truncated oversized upstream response"
+            );
+            record_oversized_response(upstream, "truncated");
+            return truncated;
+        }
+    }
+
+    tracing::warn!(
+        upstream = %upstream,
+        size,
+        max_bytes,
+        "Rejected oversized upstream response"
+    );
+    record_oversized_response(upstream, "rejected");
+    Message::error_response(
+        response.id,
+        RESPONSE_TOO_LARGE,
+        &format!(
+            "Upstream response of {} bytes exceeds the {} byte limit",
+            size, max_bytes
+        ),
+    )
+}
+
+fn serialized_len<T: serde::Serialize>(value: &T) -> usize {
+    serde_json::to_vec(value)
+        .map(|v| v.len())
+        .unwrap_or(usize::MAX)
+}
+
+/// Cut `result.content` down to fit in `max_bytes`, keeping items in order
+///
+/// Items that fit are kept whole; the first text item that does not fit is
+/// shortened, and everything after it is dropped. Returns false if the
+/// response has no content array or cannot fit even without content.
+fn truncate_content(response: &mut Message, original: usize, max_bytes: usize) -> bool {
+    let Some(result) = response.result.as_mut().and_then(Value::as_object_mut) else {
+        return false;
+    };
+    let Some(Value::Array(content)) = result.get_mut("content") else {
+        return false;
+    };
+    let items = std::mem::take(content);
+    let notice = json!({
+        "type": "text",
+        "text": format!(
+            "[mcp-guard] Response truncated: {} bytes exceeds the {} byte limit",
+            original, max_bytes
+        ),
+    });
+    content.push(notice.clone());
+
+    if let Some(meta) = result
+        .entry("_meta")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+    {
+        meta.insert(
+            TRUNCATED_META_KEY.to_string(),
+            json!({ "original_bytes": original, "max_bytes": max_bytes }),
+        );
+    }
+
+    let base = serialized_len(response);
+    if base > max_bytes {
+        return false;
+    }
+    // Each kept item also costs a separating comma
+    let mut budget = max_bytes - base;
+    let mut kept = Vec::new();
+    for item in items {
+        let cost = serialized_len(&item) + 1;
+        if cost <= budget {
+            budget -= cost;
+            kept.push(item);
+            continue;
+        }
+        if let Some(item) = shorten_text_item(&item, budget) {
+            kept.push(item);
+        }
+        break;
+    }
+    kept.push(notice);
+
+    if let Some(Value::Array(content)) = response
+        .result
+        .as_mut()
+        .and_then(|result| result.get_mut("content"))
+    {
+        *content = kept;
+    }
+    serialized_len(response) <= max_bytes
+}
+
+/// Shorten a text content item so it serializes (plus a comma) within `budget`
+fn shorten_text_item(item: &Value, budget: usize) -> Option<Value> {
+    let text = item.get("text")?.as_str()?;
+    if item.get("type").and_then(Value::as_str) != Some("text") {
+        return None;
+    }
+    let mut shortened = item.clone();
+    shortened["text"] = Value::String(String::new());
+    let overhead = serialized_len(&shortened) + 1;
+    if overhead >= budget {
+        return None;
+    }
+
+    // Escaping can expand the text, so shrink until the item fits
+    let mut allowed = budget - overhead;
+    loop {
+        let mut end = allowed.min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == 0 {
+            return None;
+        }
+        shortened["text"] = Value::String(text[..end].to_string());
+        let cost = serialized_len(&shortened) + 1;
+        if cost <= budget {
+            return Some(shortened);
+        }
+        allowed = end.saturating_sub(cost - budget);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_result(texts: &[&str]) -> Message {
+        let content: Vec<Value> = texts
+            .iter()
+            .map(|t| json!({ "type": "text", "text": t }))
+            .collect();
+        Message::response(json!(7), json!({ "content": content }))
+    }
+
+    fn limits(max_bytes: usize, policy: ResponseSizePolicy) -> ResponseLimitConfig {
+        ResponseLimitConfig { max_bytes, policy }
+    }
+
+    #[test]
+    fn test_small_responses_pass_through() {
+        let response = tool_result(&["hello"]);
+        let limited = enforce_response_limit(
+            response.clone(),
+            &limits(1024, ResponseSizePolicy::Reject),
+            "default",
+        );
+        assert_eq!(limited.result, response.result);
+    }
+
+    #[test]
+    fn test_reject_policy_returns_error() {
+        let big = "x".repeat(2000);
+        let limited = enforce_response_limit(
+            tool_result(&[&big]),
+            &limits(1024, ResponseSizePolicy::Reject),
+            "default",
+        );
+        assert!(limited.result.is_none());
+        assert_eq!(limited.id, Some(json!(7)));
+        assert_eq!(limited.error.unwrap()["code"], RESPONSE_TOO_LARGE);
+    }
+
+    #[test]
+    fn test_truncate_policy_cuts_content() {
+        let big = "é\"".repeat(1000);
+        let limited = enforce_response_limit(
+            tool_result(&["first", &big, "third"]),
+            &limits(600, ResponseSizePolicy::Truncate),
+            "default",
+        );
+        assert!(serialized_len(&limited) <= 600);
+
+        let result = limited.result.unwrap();
+        let content = result["content"].as_array().unwrap();
+        assert_eq!(content[0]["text"], "first");
+        assert!(big.starts_with(content[1]["text"].as_str().unwrap()));
+        let notice = content.last().unwrap()["text"].as_str().unwrap();
+        assert!(notice.contains("Response truncated"));
+        assert_eq!(result["_meta"][TRUNCATED_META_KEY]["max_bytes"], 600);
+    }
+
+    #[test]
+    fn test_truncate_falls_back_to_reject_without_content() {
+        let response = Message::response(json!(1), json!({ "tools": ["x".repeat(2000)] }));
+        let limited = enforce_response_limit(
+            response,
+            &limits(512, ResponseSizePolicy::Truncate),
+            "default",
+        );
+        assert!(limited.error.is_some());
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Built-in mock upstream (`transport = "mock"`)
+//!
+//! Serves the tools and canned responses from a [`MockUpstreamConfig`]
+//! without any process or connection, so policies, rate limits and authz
+//! can be tried out before a real MCP server exists. Answers are queued in
+//! the order requests are sent, like the line-based transports.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::{Message, Transport, TransportError};
+use crate::config::{MockToolConfig, MockUpstreamConfig};
+
+/// JSON-RPC error code for methods the mock does not answer
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// JSON-RPC error code for calls to tools the mock does not serve
+const INVALID_PARAMS: i32 = -32602;
+
+/// MCP protocol version reported when the client does not ask for one
+const DEFAULT_PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Transport answering from configuration instead of an upstream server
+pub struct MockUpstreamTransport {
+    config: MockUpstreamConfig,
+    /// Answers waiting to be received
+    tx: mpsc::UnboundedSender<Message>,
+    rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<Message>>,
+}
+
+impl MockUpstreamTransport {
+    /// Create a mock upstream serving `config`
+    pub fn new(config: MockUpstreamConfig) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            config,
+            tx,
+            rx: tokio::sync::Mutex::new(rx),
+        }
+    }
+
+    fn tool(&self, message: &Message) -> Option<&MockToolConfig> {
+        let name = message.params.as_ref()?.get("name")?.as_str()?;
+        self.config.tools.iter().find(|tool| tool.name == name)
+    }
+
+    /// Delay before answering `message`
+    fn latency(&self, message: &Message) -> Duration {
+        let tool_latency = match message.method.as_deref() {
+            Some("tools/call") => self.tool(message).map_or(0, |tool| tool.latency_ms),
+            _ => 0,
+        };
+        Duration::from_millis(self.config.latency_ms + tool_latency)
+    }
+
+    /// Answer to a request; `None` for notifications and responses
+    fn answer(&self, message: &Message) -> Option<Message> {
+        if !message.is_request() {
+            return None;
+        }
+        let id = message.id.clone()?;
+        let method = message.method.as_deref().unwrap_or_default();
+        if let Some(result) = self.config.responses.get(method) {
+            return Some(Message::response(id, result.clone()));
+        }
+
+        let answer = match method {
+            "initialize" => Message::response(id, self.initialize(message)),
+            "ping" => Message::response(id, json!({})),
+            "tools/list" => Message::response(id, json!({ "tools": self.tool_list() })),
+            "tools/call" => match self.tool(message) {
+                Some(tool) => call_tool(id, tool, message),
+                None => Message::error_response(Some(id), INVALID_PARAMS, "Unknown tool"),
+            },
+            _ => Message::error_response(Some(id), METHOD_NOT_FOUND, "Method not found"),
+        };
+        Some(answer)
+    }
+
+    fn initialize(&self, message: &Message) -> Value {
+        let protocol_version = message
+            .params
+            .as_ref()
+            .and_then(|params| params.get("protocolVersion"))
+            .cloned()
+            .unwrap_or_else(|| json!(DEFAULT_PROTOCOL_VERSION));
+        json!({
+            "protocolVersion": protocol_version,
+            "capabilities": { "tools": {} },
+            "serverInfo": {
+                "name": "mcp-guard-mock",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        })
+    }
+
+    fn tool_list(&self) -> Vec<Value> {
+        self.config
+            .tools
+            .iter()
+            .map(|tool| {
+                let mut entry = json!({
+                    "name": tool.name,
+                    "inputSchema": tool.input_schema,
+                });
+                if let Some(ref description) = tool.description {
+                    entry["description"] = json!(description);
+                }
+                entry
+            })
+            .collect()
+    }
+}
+
+fn call_tool(id: Value, tool: &MockToolConfig, message: &Message) -> Message {
+    if let Some(ref error) = tool.error {
+        return Message::error_response(Some(id), error.code, &error.message);
+    }
+    if let Some(ref result) = tool.result {
+        return Message::response(id, result.clone());
+    }
+    // Without canned text, echo the arguments back
+    let text = tool.text.clone().unwrap_or_else(|| {
+        let arguments = message
+            .params
+            .as_ref()
+            .and_then(|params| params.get("arguments"))
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        arguments.to_string()
+    });
+    Message::response(
+        id,
+        json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+    )
+}
+
+#[async_trait]
+impl Transport for MockUpstreamTransport {
+    async fn send(&self, message: Message) -> Result<(), TransportError> {
+        let Some(answer) = self.answer(&message) else {
+            return Ok(());
+        };
+        let latency = self.latency(&message);
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        self.tx
+            .send(answer)
+            .map_err(|e| TransportError::Send(e.to_string()))
+    }
+
+    async fn receive(&self) -> Result<Message, TransportError> {
+        self.rx
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(TransportError::ConnectionClosed)
+    }
+
+    async fn close(&self) -> Result<(), TransportError> {
+        Ok(())
+    }
+
+    fn transport_type(&self) -> &'static str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MockErrorConfig;
+
+    fn tool(name: &str) -> MockToolConfig {
+        MockToolConfig {
+            name: name.to_string(),
+            description: None,
+            input_schema: json!({ "type": "object" }),
+            text: None,
+            result: None,
+            error: None,
+            latency_ms: 0,
+        }
+    }
+
+    async fn call(transport: &MockUpstreamTransport, message: Message) -> Message {
+        transport.send(message).await.unwrap();
+        transport.receive().await.unwrap()
+    }
+
+    fn tools_call(name: &str) -> Message {
+        Message::request(
+            2,
+            "tools/call",
+            Some(json!({ "name": name, "arguments": { "path": "/tmp/x" } })),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_serves_configured_tools() {
+        let transport = MockUpstreamTransport::new(MockUpstreamConfig {
+            tools: vec![
+                MockToolConfig {
+                    description: Some("Read a file".to_string()),
+                    text: Some("hello".to_string()),
+                    ..tool("read_file")
+                },
+                tool("echo"),
+                MockToolConfig {
+                    error: Some(MockErrorConfig {
+                        code: -32000,
+                        message: "disk full".to_string(),
+                    }),
+                    ..tool("write_file")
+                },
+            ],
+            ..Default::default()
+        });
+
+        let list = call(&transport, Message::request(1, "tools/list", None)).await;
+        let tools = list.result.unwrap()["tools"].clone();
+        assert_eq!(tools.as_array().unwrap().len(), 3);
+        assert_eq!(tools[0]["description"], "Read a file");
+        assert!(tools[1].get("description").is_none());
+
+        let read = call(&transport, tools_call("read_file")).await;
+        assert_eq!(read.id, Some(json!(2)));
+        assert_eq!(read.result.unwrap()["content"][0]["text"], "hello");
+
+        let echo = call(&transport, tools_call("echo")).await;
+        assert_eq!(
+            echo.result.unwrap()["content"][0]["text"],
+            r#"{"path":"/tmp/x"}"#
+        );
+
+        let write = call(&transport, tools_call("write_file")).await;
+        assert_eq!(write.error.unwrap()["message"], "disk full");
+
+        let unknown = call(&transport, tools_call("delete_file")).await;
+        assert_eq!(unknown.error.unwrap()["code"], INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_canned_responses_and_unknown_methods() {
+        let transport = MockUpstreamTransport::new(MockUpstreamConfig {
+            responses: [("resources/list".to_string(), json!({ "resources": [] }))].into(),
+            ..Default::default()
+        });
+
+        let init = call(
+            &transport,
+            Message::request(
+                1,
+                "initialize",
+                Some(json!({ "protocolVersion": "2025-03-26" })),
+            ),
+        )
+        .await;
+        assert_eq!(init.result.unwrap()["protocolVersion"], "2025-03-26");
+
+        let resources = call(&transport, Message::request(2, "resources/list", None)).await;
+        assert_eq!(resources.result, Some(json!({ "resources": [] })));
+
+        let prompts = call(&transport, Message::request(3, "prompts/list", None)).await;
+        assert_eq!(prompts.error.unwrap()["code"], METHOD_NOT_FOUND);
+
+        // Notifications are not answered
+        let initialized = Message {
+            id: None,
+            ..Message::request(0, "notifications/initialized", None)
+        };
+        transport.send(initialized).await.unwrap();
+        let ping = call(&transport, Message::request(4, "ping", None)).await;
+        assert_eq!(ping.id, Some(json!(4)));
+    }
+}
@@ -20,12 +20,50 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
+use crate::observability::record_sse_reconnect;
+
+mod check;
+mod dns;
+mod headers;
+mod lazy;
+mod limits;
+mod mock;
+mod raw;
+mod retry;
+mod reverse;
+mod signing;
+mod ssrf;
+mod stats;
+mod tls;
+mod unix;
+
+pub use check::{check_upstream, UpstreamCheck, UpstreamTarget};
+use dns::PinnedClient;
+pub(crate) use headers::{forwarded_identity_id, with_forwarded_identity};
+pub use headers::{with_forward_context, AssertionClaims, ForwardContext, UpstreamHeaders};
+pub use lazy::LazyStdioTransport;
+pub use limits::{enforce_response_limit, TRUNCATED_META_KEY};
+pub use mock::MockUpstreamTransport;
+pub use raw::RawMessage;
+pub use retry::{exchange, exchange_raw, RETRY_HEADER};
+pub use reverse::{with_upstream_sink, UpstreamMessage, UpstreamSink, CLIENT_UNAVAILABLE_CODE};
+pub use signing::{
+    configure_request_signing, current_request_signer, gateway_jwks, publishes_jwks, RequestSigner,
+};
+pub use ssrf::configure_ssrf;
+use ssrf::SsrfPolicy;
+pub use stats::{UpstreamCallStats, UpstreamStats};
+pub use tls::{parse_spki_pin, UpstreamTls};
+pub use unix::UnixSocketTransport;
+
 // ============================================================================
 // Constants
 // ============================================================================
@@ -62,6 +100,17 @@ pub const MAX_MESSAGE_SIZE: usize = 10 * 1024 * 1024; // 10MB
 /// 100 provides ample buffering while preventing resource exhaustion.
 const MAX_PENDING_HTTP_RESPONSES: usize = 100;
 
+/// Initial delay before the first SSE reconnection attempt.
+/// Doubles on every failed attempt up to `SSE_RECONNECT_MAX_BACKOFF_MS`.
+const SSE_RECONNECT_INITIAL_BACKOFF_MS: u64 = 500;
+
+/// Upper bound on the delay between SSE reconnection attempts.
+const SSE_RECONNECT_MAX_BACKOFF_MS: u64 = 30_000;
+
+/// Number of reconnection attempts before an interrupted SSE stream is abandoned.
+/// With the backoff above this covers roughly 15 seconds of upstream disruption.
+const SSE_MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
 /// Transport error type
 #[derive(Debug, thiserror::Error)]
 pub enum TransportError {
@@ -95,11 +144,20 @@ pub enum TransportError {
     #[error("SSRF blocked: {0}")]
     SsrfBlocked(String),
 
+    #[error("Egress blocked: {0}")]
+    EgressBlocked(String),
+
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
 
     #[error("Command validation failed: {0}")]
     CommandValidation(String),
+
+    #[error("Operation not supported by {0} transport")]
+    Unsupported(&'static str),
+
+    #[error("TLS configuration error: {0}")]
+    Tls(String),
 }
 
 /// Truncate error body to prevent sensitive data leakage in logs
@@ -112,6 +170,29 @@ fn truncate_error_body(body: &str) -> String {
     }
 }
 
+/// Read a JSON response body, stopping once it exceeds [`MAX_MESSAGE_SIZE`]
+///
+/// SECURITY: reqwest decompresses gzip/br bodies transparently, so the size on
+/// the wire says little about the size in memory. Reading chunk by chunk keeps
+/// a small compressed body from expanding without bound.
+async fn read_limited_body(mut response: reqwest::Response) -> Result<Vec<u8>, TransportError> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| TransportError::Http(format!("Failed to read response body: {}", e)))?
+    {
+        if body.len() + chunk.len() > MAX_MESSAGE_SIZE {
+            return Err(TransportError::Http(format!(
+                "Response body exceeds maximum {} bytes",
+                MAX_MESSAGE_SIZE
+            )));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
 // ============================================================================
 // URL Validation (SSRF Prevention)
 // ============================================================================
@@ -187,7 +268,7 @@ impl ValidatedUrl {
 ///
 /// This function checks that a URL:
 /// - Has a valid HTTP or HTTPS scheme
-/// - Does not target private/internal IP ranges
+/// - Does not target private/internal IP ranges, unless allowed by `[ssrf]`
 /// - Does not target cloud metadata endpoints
 ///
 /// SECURITY: Returns a `ValidatedUrl` containing cached resolved IP addresses.
@@ -196,6 +277,35 @@ impl ValidatedUrl {
 ///
 /// Returns `ValidatedUrl` if safe, or an error describing why it's blocked.
 pub async fn validate_url_for_ssrf(url: &str) -> Result<ValidatedUrl, TransportError> {
+    validate_url_with_policy(url, &ssrf::current_policy()).await
+}
+
+/// Check that `ip`, reached through `host`, is neither a metadata endpoint
+/// nor a private address the policy does not allow
+fn check_address(host: &str, ip: &IpAddr, policy: &SsrfPolicy) -> Result<(), TransportError> {
+    if ssrf::is_metadata_ip(ip) {
+        return Err(TransportError::SsrfBlocked(format!(
+            "Access to cloud metadata endpoint '{}' is blocked",
+            ip
+        )));
+    }
+    if is_private_ip(ip) {
+        if !policy.allows_private(host, ip) {
+            return Err(TransportError::SsrfBlocked(format!(
+                "Access to private/internal IP address '{}' is blocked",
+                ip
+            )));
+        }
+        tracing::debug!(host = %host, ip = %ip, "Private upstream address allowed by [ssrf]");
+    }
+    Ok(())
+}
+
+/// [`validate_url_for_ssrf`] with an explicit policy
+async fn validate_url_with_policy(
+    url: &str,
+    policy: &SsrfPolicy,
+) -> Result<ValidatedUrl, TransportError> {
     // Parse the URL
     let parsed = url::Url::parse(url)
         .map_err(|e| TransportError::InvalidUrl(format!("Failed to parse URL: {}", e)))?;
@@ -244,12 +354,7 @@ pub async fn validate_url_for_ssrf(url: &str) -> Result<ValidatedUrl, TransportE
 
     // If the host is an IP address, check if it's private
     if let Ok(ip) = host.parse::<IpAddr>() {
-        if is_private_ip(&ip) {
-            return Err(TransportError::SsrfBlocked(format!(
-                "Access to private/internal IP address '{}' is blocked",
-                ip
-            )));
-        }
+        check_address(host, &ip, policy)?;
         // Direct IP - create socket addr and return
         let socket_addr = std::net::SocketAddr::new(ip, port);
         return Ok(ValidatedUrl {
@@ -268,13 +373,13 @@ pub async fn validate_url_for_ssrf(url: &str) -> Result<ValidatedUrl, TransportE
         Ok(addrs) => {
             let mut validated_ips = Vec::new();
             for addr in addrs {
-                if is_private_ip(&addr.ip()) {
-                    return Err(TransportError::SsrfBlocked(format!(
-                        "Hostname '{}' resolves to private/internal IP address '{}'",
+                check_address(host, &addr.ip(), policy).map_err(|_| {
+                    TransportError::SsrfBlocked(format!(
+                        "Hostname '{}' resolves to blocked IP address '{}'",
                         host,
                         addr.ip()
-                    )));
-                }
+                    ))
+                })?;
                 validated_ips.push(addr);
             }
 
@@ -525,11 +630,159 @@ pub trait Transport: Send + Sync {
     /// Receive a message from the upstream server
     async fn receive(&self) -> Result<Message, TransportError>;
 
+    /// Receive a message without parsing its payloads
+    ///
+    /// Transports that read raw JSON override this to skip building a
+    /// `serde_json::Value` tree; the default converts [`Transport::receive`].
+    async fn receive_raw(&self) -> Result<RawMessage, TransportError> {
+        RawMessage::from_message(&self.receive().await?)
+    }
+
     /// Close the transport
     async fn close(&self) -> Result<(), TransportError>;
 
     /// Get the transport type name for metrics
     fn transport_type(&self) -> &'static str;
+
+    /// Whether the upstream connection is currently usable
+    ///
+    /// Surfaced in `/ready` and the `mcp_guard_upstream_healthy` gauge.
+    /// Transports without a long-lived connection report healthy.
+    fn is_healthy(&self) -> bool {
+        true
+    }
+
+    /// Replace the upstream process or connection in place
+    ///
+    /// Only transports that own their upstream support this; the rest
+    /// return `TransportError::Unsupported`.
+    async fn restart(&self) -> Result<(), TransportError> {
+        Err(TransportError::Unsupported(self.transport_type()))
+    }
+}
+
+/// Spawn a task writing messages to `writer` as newline-delimited JSON
+///
+/// `peer` names the stream in logs (e.g. "stdin"). The task exits on shutdown,
+/// when the sending side of `rx` is dropped, or on the first write error.
+fn spawn_line_writer<W>(
+    mut writer: W,
+    mut rx: mpsc::Receiver<Message>,
+    shutdown: CancellationToken,
+    peer: &'static str,
+) -> tokio::task::JoinHandle<()>
+where
+    W: tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    tracing::debug!(peer, "Writer task received shutdown signal");
+                    break;
+                }
+                msg = rx.recv() => {
+                    match msg {
+                        Some(msg) => {
+                            let json = match serde_json::to_string(&msg) {
+                                Ok(j) => j,
+                                Err(e) => {
+                                    tracing::error!(error = %e, "Failed to serialize MCP message, dropping");
+                                    continue;
+                                }
+                            };
+                            if let Err(e) = writer.write_all(json.as_bytes()).await {
+                                tracing::error!(error = %e, peer, "Failed to write message, writer task exiting");
+                                break;
+                            }
+                            if let Err(e) = writer.write_all(b"\n").await {
+                                tracing::error!(error = %e, peer, "Failed to write newline, writer task exiting");
+                                break;
+                            }
+                            if let Err(e) = writer.flush().await {
+                                tracing::error!(error = %e, peer, "Failed to flush, writer task exiting");
+                                break;
+                            }
+                        }
+                        None => {
+                            tracing::debug!("Channel closed, writer task exiting");
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        tracing::debug!(peer, "Writer task exiting");
+    })
+}
+
+/// Spawn a task reading newline-delimited JSON messages from `reader`
+///
+/// Oversized and unparseable lines are dropped. The task exits on shutdown,
+/// EOF, read errors, or when the receiving side of `tx` is dropped.
+fn spawn_line_reader<R, T>(
+    reader: R,
+    tx: mpsc::Sender<T>,
+    shutdown: CancellationToken,
+    peer: &'static str,
+) -> tokio::task::JoinHandle<()>
+where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    tracing::debug!(peer, "Reader task received shutdown signal");
+                    break;
+                }
+                result = lines.next_line() => {
+                    match result {
+                        Ok(Some(line)) => {
+                            // SECURITY: Validate message size to prevent memory exhaustion
+                            if line.len() > MAX_MESSAGE_SIZE {
+                                tracing::error!(
+                                    size = line.len(),
+                                    max_size = MAX_MESSAGE_SIZE,
+                                    peer,
+                                    "Rejected oversized message from upstream, dropping"
+                                );
+                                // Don't send to channel - drop the message
+                                continue;
+                            }
+
+                            match serde_json::from_str::<T>(&line) {
+                                Ok(msg) => {
+                                    if tx.send(msg).await.is_err() {
+                                        tracing::debug!("Receiver dropped, reader task exiting");
+                                        break;
+                                    }
+                                }
+                                Err(e) => {
+                                    tracing::warn!(
+                                        error = %e,
+                                        line = %line.chars().take(100).collect::<String>(),
+                                        "Failed to parse MCP message, skipping"
+                                    );
+                                }
+                            }
+                        }
+                        Ok(None) => {
+                            tracing::debug!(peer, "EOF from upstream, reader task exiting");
+                            break;
+                        }
+                        Err(e) => {
+                            tracing::error!(error = %e, peer, "Failed to read from upstream, reader task exiting");
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        tracing::debug!(peer, "Reader task exiting");
+    })
 }
 
 /// Stdio transport for communicating with a subprocess
@@ -537,11 +790,91 @@ pub trait Transport: Send + Sync {
 /// Spawns an MCP server process and communicates via stdin/stdout using
 /// newline-delimited JSON. Background tasks handle reading and writing
 /// to avoid blocking the async runtime.
+///
+/// The subprocess can be replaced in place with [`Transport::restart`];
+/// requests in flight on the old process fail with `ConnectionClosed`.
 pub struct StdioTransport {
+    /// Command used to (re)spawn the subprocess
+    command: String,
+    /// Arguments used to (re)spawn the subprocess
+    args: Vec<String>,
+    /// Environment and working directory used to (re)spawn the subprocess
+    options: StdioOptions,
+    /// Currently running subprocess
+    process: std::sync::RwLock<Arc<StdioProcess>>,
+}
+
+/// Variables passed through to the subprocess when `inherit_env` is off
+///
+/// Enough for interpreters and package runners (`npx`, `uvx`) to locate
+/// themselves and their caches.
+pub const MINIMAL_INHERITED_ENV: &[&str] = &[
+    "HOME", "LANG", "LOGNAME", "PATH", "SHELL", "TERM", "TMPDIR", "USER",
+];
+
+/// Environment and working directory for a stdio upstream
+#[derive(Debug, Clone)]
+pub struct StdioOptions {
+    /// Variables set for the subprocess, on top of any inherited ones
+    pub env: HashMap<String, String>,
+    /// Working directory (default: the guard's own)
+    pub cwd: Option<std::path::PathBuf>,
+    /// Pass the guard's environment through; otherwise only
+    /// [`MINIMAL_INHERITED_ENV`] is kept
+    pub inherit_env: bool,
+}
+
+impl Default for StdioOptions {
+    fn default() -> Self {
+        Self {
+            env: HashMap::new(),
+            cwd: None,
+            inherit_env: true,
+        }
+    }
+}
+
+impl StdioOptions {
+    /// Options of the single-server upstream
+    pub fn from_upstream(config: &crate::config::UpstreamConfig) -> Self {
+        Self {
+            env: config.env.clone(),
+            cwd: config.cwd.clone(),
+            inherit_env: config.inherit_env,
+        }
+    }
+
+    /// Options of a multi-server route
+    pub fn from_route(config: &crate::config::ServerRouteConfig) -> Self {
+        Self {
+            env: config.env.clone(),
+            cwd: config.cwd.clone(),
+            inherit_env: config.inherit_env,
+        }
+    }
+
+    fn apply(&self, command: &mut Command) {
+        if !self.inherit_env {
+            command.env_clear();
+            for name in MINIMAL_INHERITED_ENV {
+                if let Some(value) = std::env::var_os(name) {
+                    command.env(name, value);
+                }
+            }
+        }
+        command.envs(&self.env);
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+    }
+}
+
+/// A running upstream subprocess and its I/O tasks
+struct StdioProcess {
     /// Sender for outbound messages to the subprocess
     tx: mpsc::Sender<Message>,
     /// Receiver for inbound messages from the subprocess (mutex for shared access)
-    rx: tokio::sync::Mutex<mpsc::Receiver<Message>>,
+    rx: tokio::sync::Mutex<mpsc::Receiver<RawMessage>>,
     /// Child process handle (kept alive for process lifetime)
     child: tokio::sync::Mutex<Child>,
     /// Background task writing messages to subprocess stdin
@@ -561,9 +894,22 @@ impl StdioTransport {
     /// Returns `TransportError::CommandValidation` if the command or arguments
     /// contain shell metacharacters or attempt direct shell execution.
     pub async fn spawn(command: &str, args: &[String]) -> Result<Self, TransportError> {
+        Self::spawn_with(command, args, StdioOptions::default()).await
+    }
+
+    /// Spawn a subprocess with command validation, environment and working directory
+    ///
+    /// # Errors
+    /// Returns `TransportError::CommandValidation` if the command or arguments
+    /// contain shell metacharacters or attempt direct shell execution.
+    pub async fn spawn_with(
+        command: &str,
+        args: &[String],
+        options: StdioOptions,
+    ) -> Result<Self, TransportError> {
         validate_command_for_injection(command)?;
         validate_args_for_injection(args)?;
-        Self::spawn_unchecked(command, args).await
+        Self::spawn_unchecked_with(command, args, options).await
     }
 
     /// Spawn a subprocess without command validation
@@ -573,8 +919,52 @@ impl StdioTransport {
     /// is from a trusted source (e.g., hardcoded in the application or validated
     /// through other means).
     pub async fn spawn_unchecked(command: &str, args: &[String]) -> Result<Self, TransportError> {
-        let mut child = Command::new(command)
-            .args(args)
+        Self::spawn_unchecked_with(command, args, StdioOptions::default()).await
+    }
+
+    /// Spawn a subprocess with environment and working directory, without
+    /// command validation
+    ///
+    /// # Safety
+    /// Same as [`StdioTransport::spawn_unchecked`].
+    pub async fn spawn_unchecked_with(
+        command: &str,
+        args: &[String],
+        options: StdioOptions,
+    ) -> Result<Self, TransportError> {
+        let process = StdioProcess::spawn(command, args, &options)?;
+        Ok(Self {
+            command: command.to_string(),
+            args: args.to_vec(),
+            options,
+            process: std::sync::RwLock::new(Arc::new(process)),
+        })
+    }
+
+    /// Check if the transport tasks are still running
+    pub fn is_healthy(&self) -> bool {
+        self.current().is_healthy()
+    }
+
+    /// The currently running subprocess
+    fn current(&self) -> Arc<StdioProcess> {
+        self.process
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl StdioProcess {
+    fn spawn(
+        command: &str,
+        args: &[String],
+        options: &StdioOptions,
+    ) -> Result<Self, TransportError> {
+        let mut command = Command::new(command);
+        command.args(args);
+        options.apply(&mut command);
+        let mut child = command
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::inherit())
@@ -591,111 +981,16 @@ impl StdioTransport {
             ))
         })?;
 
-        let (to_process_tx, mut to_process_rx) = mpsc::channel::<Message>(TRANSPORT_CHANNEL_SIZE);
-        let (from_process_tx, from_process_rx) = mpsc::channel::<Message>(TRANSPORT_CHANNEL_SIZE);
+        let (to_process_tx, to_process_rx) = mpsc::channel::<Message>(TRANSPORT_CHANNEL_SIZE);
+        let (from_process_tx, from_process_rx) =
+            mpsc::channel::<RawMessage>(TRANSPORT_CHANNEL_SIZE);
 
         // Create shutdown token for graceful shutdown coordination
         let shutdown_token = CancellationToken::new();
-        let writer_shutdown = shutdown_token.clone();
-        let reader_shutdown = shutdown_token.clone();
-
-        // Writer task with shutdown support
-        let writer_task = tokio::spawn(async move {
-            let mut stdin = stdin;
-            loop {
-                tokio::select! {
-                    _ = writer_shutdown.cancelled() => {
-                        tracing::debug!("Writer task received shutdown signal");
-                        break;
-                    }
-                    msg = to_process_rx.recv() => {
-                        match msg {
-                            Some(msg) => {
-                                let json = match serde_json::to_string(&msg) {
-                                    Ok(j) => j,
-                                    Err(e) => {
-                                        tracing::error!(error = %e, "Failed to serialize MCP message, dropping");
-                                        continue;
-                                    }
-                                };
-                                if let Err(e) = stdin.write_all(json.as_bytes()).await {
-                                    tracing::error!(error = %e, "Failed to write to stdin, writer task exiting");
-                                    break;
-                                }
-                                if let Err(e) = stdin.write_all(b"\n").await {
-                                    tracing::error!(error = %e, "Failed to write newline to stdin, writer task exiting");
-                                    break;
-                                }
-                                if let Err(e) = stdin.flush().await {
-                                    tracing::error!(error = %e, "Failed to flush stdin, writer task exiting");
-                                    break;
-                                }
-                            }
-                            None => {
-                                tracing::debug!("Channel closed, writer task exiting");
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
-            tracing::debug!("Writer task exiting");
-        });
 
-        // Reader task with shutdown support
-        let reader_task = tokio::spawn(async move {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
-            loop {
-                tokio::select! {
-                    _ = reader_shutdown.cancelled() => {
-                        tracing::debug!("Reader task received shutdown signal");
-                        break;
-                    }
-                    result = lines.next_line() => {
-                        match result {
-                            Ok(Some(line)) => {
-                                // SECURITY: Validate message size to prevent memory exhaustion
-                                if line.len() > MAX_MESSAGE_SIZE {
-                                    tracing::error!(
-                                        size = line.len(),
-                                        max_size = MAX_MESSAGE_SIZE,
-                                        "Rejected oversized message from subprocess stdout, dropping"
-                                    );
-                                    // Don't send to channel - drop the message
-                                    continue;
-                                }
-
-                                match serde_json::from_str::<Message>(&line) {
-                                    Ok(msg) => {
-                                        if from_process_tx.send(msg).await.is_err() {
-                                            tracing::debug!("Receiver dropped, reader task exiting");
-                                            break;
-                                        }
-                                    }
-                                    Err(e) => {
-                                        tracing::warn!(
-                                            error = %e,
-                                            line = %line.chars().take(100).collect::<String>(),
-                                            "Failed to parse MCP message, skipping"
-                                        );
-                                    }
-                                }
-                            }
-                            Ok(None) => {
-                                tracing::debug!("EOF from process, reader task exiting");
-                                break;
-                            }
-                            Err(e) => {
-                                tracing::error!(error = %e, "Failed to read from stdout, reader task exiting");
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
-            tracing::debug!("Reader task exiting");
-        });
+        let writer_task = spawn_line_writer(stdin, to_process_rx, shutdown_token.clone(), "stdin");
+        let reader_task =
+            spawn_line_reader(stdout, from_process_tx, shutdown_token.clone(), "stdout");
 
         Ok(Self {
             tx: to_process_tx,
@@ -707,29 +1002,9 @@ impl StdioTransport {
         })
     }
 
-    /// Check if the transport tasks are still running
-    pub fn is_healthy(&self) -> bool {
+    fn is_healthy(&self) -> bool {
         !self.writer_task.is_finished() && !self.reader_task.is_finished()
     }
-}
-
-#[async_trait]
-impl Transport for StdioTransport {
-    async fn send(&self, message: Message) -> Result<(), TransportError> {
-        self.tx
-            .send(message)
-            .await
-            .map_err(|e| TransportError::Send(e.to_string()))
-    }
-
-    async fn receive(&self) -> Result<Message, TransportError> {
-        self.rx
-            .lock()
-            .await
-            .recv()
-            .await
-            .ok_or(TransportError::ConnectionClosed)
-    }
 
     async fn close(&self) -> Result<(), TransportError> {
         // Signal background tasks to stop
@@ -776,10 +1051,60 @@ impl Transport for StdioTransport {
         child.kill().await?;
         Ok(())
     }
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    async fn send(&self, message: Message) -> Result<(), TransportError> {
+        self.current()
+            .tx
+            .send(message)
+            .await
+            .map_err(|e| TransportError::Send(e.to_string()))
+    }
+
+    async fn receive(&self) -> Result<Message, TransportError> {
+        self.receive_raw().await?.into_message()
+    }
+
+    async fn receive_raw(&self) -> Result<RawMessage, TransportError> {
+        self.current()
+            .rx
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(TransportError::ConnectionClosed)
+    }
+
+    async fn close(&self) -> Result<(), TransportError> {
+        self.current().close().await
+    }
 
     fn transport_type(&self) -> &'static str {
         "stdio"
     }
+
+    fn is_healthy(&self) -> bool {
+        StdioTransport::is_healthy(self)
+    }
+
+    async fn restart(&self) -> Result<(), TransportError> {
+        let replacement = Arc::new(StdioProcess::spawn(
+            &self.command,
+            &self.args,
+            &self.options,
+        )?);
+        let previous = std::mem::replace(
+            &mut *self
+                .process
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            replacement,
+        );
+        tracing::info!(command = %self.command, "Restarted stdio upstream");
+        previous.close().await
+    }
 }
 
 // ============================================================================
@@ -797,7 +1122,7 @@ impl Transport for StdioTransport {
 /// SSRF validation are cached and used for all subsequent requests.
 pub struct HttpTransport {
     /// Reusable HTTP client with connection pooling and optional DNS pinning
-    client: reqwest::Client,
+    client: Arc<PinnedClient>,
     /// Base URL of the upstream MCP server (e.g., "http://localhost:8080/mcp")
     url: String,
     /// Additional headers to include in requests (e.g., for upstream auth)
@@ -805,32 +1130,12 @@ pub struct HttpTransport {
     /// Request timeout (default: 30 seconds)
     timeout: std::time::Duration,
     /// Queue of responses waiting to be retrieved via `receive()`
-    pending_responses: tokio::sync::Mutex<Vec<Message>>,
+    pending_responses: tokio::sync::Mutex<Vec<RawMessage>>,
+    /// Per-request forwarded and identity-derived headers
+    upstream_headers: UpstreamHeaders,
 }
 
 impl HttpTransport {
-    /// Build a reqwest client with DNS pinning for the validated URL
-    ///
-    /// SECURITY: This prevents DNS rebinding attacks by configuring the client
-    /// to use the IP addresses that were validated during SSRF checks.
-    fn build_pinned_client(validated_url: &ValidatedUrl) -> reqwest::Client {
-        let mut builder = reqwest::Client::builder();
-
-        // Pin DNS resolution to the first validated IP address
-        // This prevents DNS rebinding attacks where the DNS changes between
-        // validation and actual request
-        if let Some(first_addr) = validated_url.resolved_ips.first() {
-            tracing::debug!(
-                "Pinning DNS for '{}' to {}",
-                validated_url.host,
-                first_addr.ip()
-            );
-            builder = builder.resolve(&validated_url.host, *first_addr);
-        }
-
-        builder.build().unwrap_or_else(|_| reqwest::Client::new())
-    }
-
     /// Create a new HTTP transport with SSRF validation and DNS pinning
     ///
     /// SECURITY: This validates the URL against SSRF attacks and pins DNS
@@ -838,17 +1143,19 @@ impl HttpTransport {
     ///
     /// # Errors
     /// Returns `TransportError::SsrfBlocked` if the URL targets a private/internal IP range
-    /// or cloud metadata endpoint.
+    /// or cloud metadata endpoint, and `TransportError::EgressBlocked` if its host is not
+    /// in `[egress] allow`.
     pub async fn new(url: String) -> Result<Self, TransportError> {
+        crate::egress::check_egress(&url).map_err(TransportError::EgressBlocked)?;
         let validated_url = validate_url_for_ssrf(&url).await?;
-        let client = Self::build_pinned_client(&validated_url);
 
         Ok(Self {
-            client,
+            client: PinnedClient::pinned(&validated_url),
             url,
             headers: HashMap::new(),
             timeout: std::time::Duration::from_secs(HTTP_REQUEST_TIMEOUT_SECS),
             pending_responses: tokio::sync::Mutex::new(Vec::new()),
+            upstream_headers: UpstreamHeaders::default(),
         })
     }
 
@@ -860,11 +1167,12 @@ impl HttpTransport {
     /// connecting to localhost for testing.
     pub fn new_unchecked(url: String) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: PinnedClient::unpinned(&url),
             url,
             headers: HashMap::new(),
             timeout: std::time::Duration::from_secs(HTTP_REQUEST_TIMEOUT_SECS),
             pending_responses: tokio::sync::Mutex::new(Vec::new()),
+            upstream_headers: UpstreamHeaders::default(),
         }
     }
 
@@ -875,28 +1183,62 @@ impl HttpTransport {
     ///
     /// # Errors
     /// Returns `TransportError::SsrfBlocked` if the URL targets a private/internal IP range
-    /// or cloud metadata endpoint.
+    /// or cloud metadata endpoint, and `TransportError::EgressBlocked` if its host is not
+    /// in `[egress] allow`.
     pub async fn with_config(
         url: String,
         headers: HashMap<String, String>,
         timeout_secs: u64,
     ) -> Result<Self, TransportError> {
+        crate::egress::check_egress(&url).map_err(TransportError::EgressBlocked)?;
         let validated_url = validate_url_for_ssrf(&url).await?;
-        let client = Self::build_pinned_client(&validated_url);
 
         Ok(Self {
-            client,
+            client: PinnedClient::pinned(&validated_url),
             url,
             headers,
             timeout: std::time::Duration::from_secs(timeout_secs),
             pending_responses: tokio::sync::Mutex::new(Vec::new()),
+            upstream_headers: UpstreamHeaders::default(),
         })
     }
 
+    /// Set the header forwarding/injection policy for upstream requests
+    pub fn with_upstream_headers(mut self, upstream_headers: UpstreamHeaders) -> Self {
+        self.upstream_headers = upstream_headers;
+        self
+    }
+
+    /// Connect with custom TLS settings, keeping the DNS pin
+    ///
+    /// # Errors
+    /// Returns `TransportError::Tls` if the HTTP client cannot be built.
+    pub fn with_tls(self, tls: &UpstreamTls) -> Result<Self, TransportError> {
+        self.client.set_tls(tls)?;
+        Ok(self)
+    }
+
+    /// Re-resolve the upstream host every `interval`, re-pinning when its
+    /// address changes; zero keeps the first address
+    ///
+    /// Spawns a task that ends when the transport is dropped. Has no effect
+    /// on unchecked transports or URLs with an IP address.
+    pub fn with_dns_refresh(self, interval: std::time::Duration) -> Self {
+        self.client.start_refresh(interval);
+        self
+    }
+
+    /// Set the request timeout (default: 30 seconds)
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// Send a request and get the response immediately
-    async fn send_request(&self, message: &Message) -> Result<Message, TransportError> {
+    async fn send_request(&self, message: &Message) -> Result<RawMessage, TransportError> {
         let mut request = self
             .client
+            .current()
             .post(&self.url)
             .header("Content-Type", "application/json")
             .timeout(self.timeout);
@@ -905,8 +1247,14 @@ impl HttpTransport {
         for (key, value) in &self.headers {
             request = request.header(key, value);
         }
+        request = request.headers(self.upstream_headers.resolve());
+
+        // Serialized up front so the signature covers the exact bytes sent
+        let body = serde_json::to_vec(message)
+            .map_err(|e| TransportError::InvalidMessage(e.to_string()))?;
+        request = signing::sign_request(request, "POST", &self.url, &body);
 
-        let response = request.json(message).send().await.map_err(|e| {
+        let response = request.body(body).send().await.map_err(|e| {
             if e.is_timeout() {
                 TransportError::Timeout
             } else {
@@ -937,20 +1285,10 @@ impl HttpTransport {
         }
 
         // Read response body with size limit
-        let body_bytes = response
-            .bytes()
-            .await
-            .map_err(|e| TransportError::Http(format!("Failed to read response body: {}", e)))?;
+        let body_bytes = read_limited_body(response).await?;
 
-        if body_bytes.len() > MAX_MESSAGE_SIZE {
-            return Err(TransportError::Http(format!(
-                "Response body {} bytes exceeds maximum {}",
-                body_bytes.len(),
-                MAX_MESSAGE_SIZE
-            )));
-        }
-
-        let response_message: Message = serde_json::from_slice(&body_bytes)
+        // Only the envelope is parsed; the result stays raw JSON until needed
+        let response_message: RawMessage = serde_json::from_slice(&body_bytes)
             .map_err(|e| TransportError::InvalidMessage(e.to_string()))?;
 
         Ok(response_message)
@@ -977,6 +1315,10 @@ impl Transport for HttpTransport {
     }
 
     async fn receive(&self) -> Result<Message, TransportError> {
+        self.receive_raw().await?.into_message()
+    }
+
+    async fn receive_raw(&self) -> Result<RawMessage, TransportError> {
         // Pop the next pending response
         self.pending_responses
             .lock()
@@ -1017,7 +1359,7 @@ impl Transport for HttpTransport {
 /// ```
 pub struct SseTransport {
     /// Reusable HTTP client with connection pooling and optional DNS pinning
-    client: reqwest::Client,
+    client: Arc<PinnedClient>,
     /// Base URL of the upstream MCP server SSE endpoint
     url: String,
     /// Additional headers to include in requests (e.g., for upstream auth)
@@ -1028,29 +1370,13 @@ pub struct SseTransport {
     rx: tokio::sync::Mutex<mpsc::Receiver<Message>>,
     /// Sender used by SSE stream handler to deliver parsed messages
     tx: mpsc::Sender<Message>,
+    /// Cleared while the upstream is unreachable or a stream is reconnecting
+    healthy: Arc<AtomicBool>,
+    /// Per-request forwarded and identity-derived headers
+    upstream_headers: UpstreamHeaders,
 }
 
 impl SseTransport {
-    /// Build a reqwest client with DNS pinning for the validated URL
-    ///
-    /// SECURITY: This prevents DNS rebinding attacks by configuring the client
-    /// to use the IP addresses that were validated during SSRF checks.
-    fn build_pinned_client(validated_url: &ValidatedUrl) -> reqwest::Client {
-        let mut builder = reqwest::Client::builder();
-
-        // Pin DNS resolution to the first validated IP address
-        if let Some(first_addr) = validated_url.resolved_ips.first() {
-            tracing::debug!(
-                "Pinning SSE DNS for '{}' to {}",
-                validated_url.host,
-                first_addr.ip()
-            );
-            builder = builder.resolve(&validated_url.host, *first_addr);
-        }
-
-        builder.build().unwrap_or_else(|_| reqwest::Client::new())
-    }
-
     /// Create a new SSE transport with SSRF validation and DNS pinning
     ///
     /// SECURITY: This validates the URL against SSRF attacks and pins DNS
@@ -1058,7 +1384,8 @@ impl SseTransport {
     ///
     /// # Errors
     /// Returns `TransportError::SsrfBlocked` if the URL targets a private/internal IP range
-    /// or cloud metadata endpoint.
+    /// or cloud metadata endpoint, and `TransportError::EgressBlocked` if its host is not
+    /// in `[egress] allow`.
     pub async fn connect(url: String) -> Result<Self, TransportError> {
         Self::connect_with_config(url, HashMap::new(), 30).await
     }
@@ -1080,12 +1407,14 @@ impl SseTransport {
     ///
     /// # Errors
     /// Returns `TransportError::SsrfBlocked` if the URL targets a private/internal IP range
-    /// or cloud metadata endpoint.
+    /// or cloud metadata endpoint, and `TransportError::EgressBlocked` if its host is not
+    /// in `[egress] allow`.
     pub async fn connect_with_config(
         url: String,
         headers: HashMap<String, String>,
         timeout_secs: u64,
     ) -> Result<Self, TransportError> {
+        crate::egress::check_egress(&url).map_err(TransportError::EgressBlocked)?;
         let validated_url = validate_url_for_ssrf(&url).await?;
         Self::connect_with_config_internal(url, headers, timeout_secs, Some(&validated_url)).await
     }
@@ -1112,8 +1441,8 @@ impl SseTransport {
         let (tx, rx) = mpsc::channel::<Message>(TRANSPORT_CHANNEL_SIZE);
 
         let client = match validated_url {
-            Some(v) => Self::build_pinned_client(v),
-            None => reqwest::Client::new(),
+            Some(v) => PinnedClient::pinned(v),
+            None => PinnedClient::unpinned(&url),
         };
 
         Ok(Self {
@@ -1123,13 +1452,61 @@ impl SseTransport {
             timeout: std::time::Duration::from_secs(timeout_secs),
             rx: tokio::sync::Mutex::new(rx),
             tx,
+            healthy: Arc::new(AtomicBool::new(true)),
+            upstream_headers: UpstreamHeaders::default(),
         })
     }
 
+    /// Set the header forwarding/injection policy for upstream requests
+    pub fn with_upstream_headers(mut self, upstream_headers: UpstreamHeaders) -> Self {
+        self.upstream_headers = upstream_headers;
+        self
+    }
+
+    /// Connect with custom TLS settings, keeping the DNS pin
+    ///
+    /// # Errors
+    /// Returns `TransportError::Tls` if the HTTP client cannot be built.
+    pub fn with_tls(self, tls: &UpstreamTls) -> Result<Self, TransportError> {
+        self.client.set_tls(tls)?;
+        Ok(self)
+    }
+
+    /// Re-resolve the upstream host every `interval`, re-pinning when its
+    /// address changes; zero keeps the first address
+    ///
+    /// Spawns a task that ends when the transport is dropped. Has no effect
+    /// on unchecked transports or URLs with an IP address.
+    pub fn with_dns_refresh(self, interval: std::time::Duration) -> Self {
+        self.client.start_refresh(interval);
+        self
+    }
+
+    /// Set the request timeout (default: 30 seconds)
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Context handed to stream tasks so they can resume after a disconnect
+    fn stream_context(&self) -> SseStreamContext {
+        SseStreamContext {
+            client: self.client.clone(),
+            url: self.url.clone(),
+            headers: self.headers.clone(),
+            timeout: self.timeout,
+            tx: self.tx.clone(),
+            healthy: self.healthy.clone(),
+            upstream_headers: self.upstream_headers.clone(),
+            max_reconnect_attempts: SSE_MAX_RECONNECT_ATTEMPTS,
+        }
+    }
+
     /// Send a request and handle SSE response stream
     async fn send_sse_request(&self, message: &Message) -> Result<(), TransportError> {
         let mut request = self
             .client
+            .current()
             .post(&self.url)
             .header("Content-Type", "application/json")
             .header("Accept", "text/event-stream, application/json")
@@ -1139,8 +1516,16 @@ impl SseTransport {
         for (key, value) in &self.headers {
             request = request.header(key, value);
         }
+        request = request.headers(self.upstream_headers.resolve());
+
+        // Serialized up front so the signature covers the exact bytes sent
+        let body = serde_json::to_vec(message)
+            .map_err(|e| TransportError::InvalidMessage(e.to_string()))?;
+        request = signing::sign_request(request, "POST", &self.url, &body);
 
-        let response = request.json(message).send().await.map_err(|e| {
+        let response = request.body(body).send().await.map_err(|e| {
+            // Connection-level failure: the upstream is unreachable
+            self.healthy.store(false, Ordering::Relaxed);
             if e.is_timeout() {
                 TransportError::Timeout
             } else {
@@ -1149,6 +1534,8 @@ impl SseTransport {
         })?;
 
         let status = response.status();
+        self.healthy
+            .store(!status.is_server_error(), Ordering::Relaxed);
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
             return Err(TransportError::Http(format!(
@@ -1166,64 +1553,16 @@ impl SseTransport {
             .unwrap_or("");
 
         if content_type.contains("text/event-stream") {
-            // Handle SSE stream
-            let tx = self.tx.clone();
-            let bytes_stream = response.bytes_stream();
-
-            // Spawn task to process SSE stream
-            tokio::spawn(async move {
-                use futures::StreamExt;
-                use tokio::io::AsyncBufReadExt;
-
-                let stream = tokio_util::io::StreamReader::new(
-                    bytes_stream.map(|r| r.map_err(std::io::Error::other)),
-                );
-                let mut reader = BufReader::new(stream);
-                let mut line = String::new();
-                let mut data_buffer = String::new();
-
-                loop {
-                    line.clear();
-                    match reader.read_line(&mut line).await {
-                        Ok(0) => break, // EOF
-                        Ok(_) => {
-                            let trimmed = line.trim();
-
-                            if let Some(data) = trimmed.strip_prefix("data:") {
-                                let new_data = data.trim();
-
-                                // SECURITY: Prevent unbounded buffer growth from malicious SSE streams
-                                if data_buffer.len() + new_data.len() > MAX_MESSAGE_SIZE {
-                                    tracing::error!(
-                                        current_size = data_buffer.len(),
-                                        new_data_size = new_data.len(),
-                                        max_size = MAX_MESSAGE_SIZE,
-                                        "SSE data buffer exceeded maximum size, resetting buffer"
-                                    );
-                                    data_buffer.clear(); // Reset to prevent memory exhaustion
-                                    continue;
-                                }
-
-                                data_buffer.push_str(new_data);
-                            } else if trimmed.is_empty() && !data_buffer.is_empty() {
-                                // Empty line signals end of event
-                                if let Ok(msg) = serde_json::from_str::<Message>(&data_buffer) {
-                                    if tx.send(msg).await.is_err() {
-                                        break;
-                                    }
-                                }
-                                data_buffer.clear();
-                            }
-                        }
-                        Err(_) => break,
-                    }
-                }
-            });
+            // Process the SSE stream in the background, resuming it if it drops
+            tokio::spawn(self.stream_context().run(response));
         } else {
             // Regular JSON response
-            let response_message: Message = response
-                .json()
-                .await
+            let body_bytes = read_limited_body(response).await?;
+            // Responses and notifications we send are acknowledged with an empty 202
+            if body_bytes.is_empty() && !message.is_request() {
+                return Ok(());
+            }
+            let response_message: Message = serde_json::from_slice(&body_bytes)
                 .map_err(|e| TransportError::InvalidMessage(e.to_string()))?;
 
             self.tx
@@ -1236,6 +1575,191 @@ impl SseTransport {
     }
 }
 
+/// How an SSE stream stopped being read
+enum SseStreamEnd {
+    /// Upstream closed the stream cleanly (or the transport was dropped)
+    Closed,
+    /// The connection failed mid-stream
+    Interrupted(std::io::Error),
+}
+
+/// Everything a background SSE task needs to read, and re-open, a stream
+///
+/// Reconnection follows the SSE resumability model: the client re-issues a GET
+/// to the endpoint carrying the last seen event ID in `Last-Event-ID`, and the
+/// upstream replays anything the client missed. Reconnects use the client's
+/// current DNS pin.
+#[derive(Clone)]
+struct SseStreamContext {
+    client: Arc<PinnedClient>,
+    url: String,
+    headers: HashMap<String, String>,
+    timeout: std::time::Duration,
+    tx: mpsc::Sender<Message>,
+    healthy: Arc<AtomicBool>,
+    upstream_headers: UpstreamHeaders,
+    max_reconnect_attempts: u32,
+}
+
+impl SseStreamContext {
+    /// Read a stream to completion, reconnecting with backoff when it is interrupted
+    async fn run(self, response: reqwest::Response) {
+        let mut last_event_id: Option<String> = None;
+        let mut response = response;
+
+        loop {
+            match self.consume(response, &mut last_event_id).await {
+                SseStreamEnd::Closed => return,
+                SseStreamEnd::Interrupted(e) => {
+                    tracing::warn!(
+                        error = %e,
+                        last_event_id = ?last_event_id,
+                        "SSE stream interrupted, reconnecting"
+                    );
+                    match self.reconnect(last_event_id.as_deref()).await {
+                        Some(resumed) => response = resumed,
+                        None => return,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parse events from a stream, forwarding messages and tracking the last event ID
+    async fn consume(
+        &self,
+        response: reqwest::Response,
+        last_event_id: &mut Option<String>,
+    ) -> SseStreamEnd {
+        use futures::StreamExt;
+
+        let stream = tokio_util::io::StreamReader::new(
+            response
+                .bytes_stream()
+                .map(|r| r.map_err(std::io::Error::other)),
+        );
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        let mut data_buffer = String::new();
+
+        loop {
+            line.clear();
+            match reader.read_line(&mut line).await {
+                Ok(0) => return SseStreamEnd::Closed, // EOF
+                Ok(_) => {
+                    let trimmed = line.trim();
+
+                    if let Some(data) = trimmed.strip_prefix("data:") {
+                        let new_data = data.trim();
+
+                        // SECURITY: Prevent unbounded buffer growth from malicious SSE streams
+                        if data_buffer.len() + new_data.len() > MAX_MESSAGE_SIZE {
+                            tracing::error!(
+                                current_size = data_buffer.len(),
+                                new_data_size = new_data.len(),
+                                max_size = MAX_MESSAGE_SIZE,
+                                "SSE data buffer exceeded maximum size, resetting buffer"
+                            );
+                            data_buffer.clear(); // Reset to prevent memory exhaustion
+                            continue;
+                        }
+
+                        data_buffer.push_str(new_data);
+                    } else if let Some(id) = trimmed.strip_prefix("id:") {
+                        // Per the SSE spec, IDs containing NULL are ignored
+                        let id = id.trim();
+                        if !id.contains('\0') {
+                            *last_event_id = Some(id.to_string());
+                        }
+                    } else if trimmed.is_empty() && !data_buffer.is_empty() {
+                        // Empty line signals end of event
+                        if let Ok(msg) = serde_json::from_str::<Message>(&data_buffer) {
+                            if self.tx.send(msg).await.is_err() {
+                                return SseStreamEnd::Closed;
+                            }
+                        }
+                        data_buffer.clear();
+                    }
+                }
+                Err(e) => return SseStreamEnd::Interrupted(e),
+            }
+        }
+    }
+
+    /// Re-open the stream with exponential backoff
+    ///
+    /// Marks the transport unhealthy for the duration; returns `None` once all
+    /// attempts have failed, leaving it unhealthy until the next successful request.
+    async fn reconnect(&self, last_event_id: Option<&str>) -> Option<reqwest::Response> {
+        self.healthy.store(false, Ordering::Relaxed);
+
+        for attempt in 0..self.max_reconnect_attempts {
+            tokio::time::sleep(sse_reconnect_backoff(attempt)).await;
+
+            let mut request = self
+                .client
+                .current()
+                .get(&self.url)
+                .header("Accept", "text/event-stream");
+            for (key, value) in &self.headers {
+                request = request.header(key, value);
+            }
+            // Runs outside the client request, so only context-free headers apply
+            request = request.headers(self.upstream_headers.resolve());
+            if let Some(id) = last_event_id {
+                request = request.header("Last-Event-ID", id);
+            }
+
+            // Only bound connection setup; the resumed stream itself may be long-lived
+            match tokio::time::timeout(self.timeout, request.send()).await {
+                Ok(Ok(response))
+                    if response.status().is_success()
+                        && response
+                            .headers()
+                            .get("content-type")
+                            .and_then(|v| v.to_str().ok())
+                            .is_some_and(|v| v.contains("text/event-stream")) =>
+                {
+                    tracing::info!(attempt = attempt + 1, "SSE stream resumed");
+                    record_sse_reconnect(true);
+                    self.healthy.store(true, Ordering::Relaxed);
+                    return Some(response);
+                }
+                Ok(Ok(response)) => {
+                    tracing::warn!(
+                        attempt = attempt + 1,
+                        status = %response.status(),
+                        "SSE reconnect rejected by upstream"
+                    );
+                }
+                Ok(Err(e)) => {
+                    tracing::warn!(attempt = attempt + 1, error = %e, "SSE reconnect failed");
+                }
+                Err(_) => {
+                    tracing::warn!(attempt = attempt + 1, "SSE reconnect timed out");
+                }
+            }
+            record_sse_reconnect(false);
+        }
+
+        tracing::error!(
+            attempts = self.max_reconnect_attempts,
+            "Giving up on interrupted SSE stream"
+        );
+        None
+    }
+}
+
+/// Delay before reconnection attempt `attempt` (zero-based)
+fn sse_reconnect_backoff(attempt: u32) -> Duration {
+    let factor = 1u64.checked_shl(attempt).unwrap_or(u64::MAX);
+    Duration::from_millis(
+        SSE_RECONNECT_INITIAL_BACKOFF_MS
+            .saturating_mul(factor)
+            .min(SSE_RECONNECT_MAX_BACKOFF_MS),
+    )
+}
+
 #[async_trait]
 impl Transport for SseTransport {
     async fn send(&self, message: Message) -> Result<(), TransportError> {
@@ -1259,6 +1783,10 @@ impl Transport for SseTransport {
     fn transport_type(&self) -> &'static str {
         "sse"
     }
+
+    fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
 }
 
 // ============================================================================
@@ -1373,6 +1901,62 @@ mod tests {
         assert_eq!(transport.url, "http://localhost:8080/mcp");
     }
 
+    #[tokio::test]
+    async fn test_http_transport_upstream_headers() {
+        use crate::auth::Identity;
+        use crate::config::UpstreamHeadersConfig;
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(header("x-forwarded-user", "user-123"))
+            .and(header("x-request-id", "req-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let policy = UpstreamHeaders::compile(&UpstreamHeadersConfig {
+            forward: vec!["x-request-id".to_string()],
+            inject: HashMap::from([("X-Forwarded-User".to_string(), "{identity.id}".to_string())]),
+            assertion: None,
+        })
+        .unwrap();
+        let transport = HttpTransport::new_unchecked(format!("{}/mcp", mock_server.uri()))
+            .with_upstream_headers(policy);
+
+        let mut client_headers = reqwest::header::HeaderMap::new();
+        client_headers.insert("x-request-id", "req-1".parse().unwrap());
+        let context = ForwardContext {
+            client_headers,
+            identity: Identity {
+                id: "user-123".to_string(),
+                name: None,
+                allowed_tools: None,
+                allowed_resources: None,
+                allowed_prompts: None,
+                rate_limit: None,
+                claims: HashMap::new(),
+                tenant: None,
+                argument_constraints: None,
+            },
+        };
+
+        with_forward_context(
+            context,
+            transport.send(Message::request(1, "tools/list", None)),
+        )
+        .await
+        .unwrap();
+        assert!(transport.receive().await.unwrap().result.is_some());
+    }
+
     #[tokio::test]
     async fn test_http_transport_success() {
         use wiremock::matchers::{method, path};
@@ -1688,6 +2272,35 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_spawn_with_env_and_cwd() {
+        let args = vec![
+            "-c".to_string(),
+            r#"read line; printf '{"jsonrpc":"2.0","id":1,"result":{"token":"%s","cwd":"%s","manifest":"%s","has_path":"%s"}}\n' "$MCP_TOKEN" "$(pwd)" "$CARGO_MANIFEST_DIR" "${PATH:+yes}""#
+                .to_string(),
+        ];
+        let options = StdioOptions {
+            env: HashMap::from([("MCP_TOKEN".to_string(), "secret".to_string())]),
+            cwd: Some(std::path::PathBuf::from("/")),
+            inherit_env: false,
+        };
+        let transport = StdioTransport::spawn_unchecked_with("sh", &args, options)
+            .await
+            .unwrap();
+        transport
+            .send(Message::request(1, "initialize", None))
+            .await
+            .unwrap();
+        let result = transport.receive().await.unwrap().result.unwrap();
+        assert_eq!(result["token"], "secret");
+        assert_eq!(result["cwd"], "/");
+        // Only the minimal set is inherited
+        assert_eq!(result["manifest"], "");
+        assert_eq!(result["has_path"], "yes");
+        let _ = transport.close().await;
+    }
+
     // ------------------------------------------------------------------------
     // SseTransport Tests
     // ------------------------------------------------------------------------
@@ -1755,6 +2368,120 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_sse_reconnect_backoff() {
+        assert_eq!(sse_reconnect_backoff(0), Duration::from_millis(500));
+        assert_eq!(sse_reconnect_backoff(1), Duration::from_millis(1000));
+        assert_eq!(sse_reconnect_backoff(3), Duration::from_millis(4000));
+        // Capped, and never overflows
+        assert_eq!(sse_reconnect_backoff(10), Duration::from_millis(30_000));
+        assert_eq!(sse_reconnect_backoff(100), Duration::from_millis(30_000));
+    }
+
+    #[tokio::test]
+    async fn test_sse_consume_tracks_last_event_id() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let sse_body =
+            "id: 7\nevent: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n\n";
+        Mock::given(method("GET"))
+            .and(path("/sse"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(sse_body, "text/event-stream"))
+            .mount(&mock_server)
+            .await;
+
+        let transport = SseTransport::connect_unchecked(format!("{}/sse", mock_server.uri()))
+            .await
+            .unwrap();
+        let response = reqwest::get(format!("{}/sse", mock_server.uri()))
+            .await
+            .unwrap();
+
+        let mut last_event_id = None;
+        let end = transport
+            .stream_context()
+            .consume(response, &mut last_event_id)
+            .await;
+
+        assert!(matches!(end, SseStreamEnd::Closed));
+        assert_eq!(last_event_id.as_deref(), Some("7"));
+        assert!(transport.receive().await.unwrap().result.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sse_reconnect_sends_last_event_id() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sse"))
+            .and(header("Last-Event-ID", "42"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "id: 43\ndata: {\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{}}\n\n",
+                "text/event-stream",
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let transport = SseTransport::connect_unchecked(format!("{}/sse", mock_server.uri()))
+            .await
+            .unwrap();
+        let context = transport.stream_context();
+
+        let resumed = context.reconnect(Some("42")).await;
+        assert!(resumed.is_some());
+        assert!(transport.is_healthy());
+
+        let mut last_event_id = Some("42".to_string());
+        context.consume(resumed.unwrap(), &mut last_event_id).await;
+        assert_eq!(last_event_id.as_deref(), Some("43"));
+        assert_eq!(
+            transport.receive().await.unwrap().id,
+            Some(serde_json::json!(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sse_reconnect_failure_marks_unhealthy() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&mock_server)
+            .await;
+
+        let transport = SseTransport::connect_unchecked(format!("{}/sse", mock_server.uri()))
+            .await
+            .unwrap();
+        assert!(transport.is_healthy());
+
+        let mut context = transport.stream_context();
+        context.max_reconnect_attempts = 1;
+
+        assert!(context.reconnect(None).await.is_none());
+        assert!(!transport.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_sse_send_failure_marks_unhealthy() {
+        // Nothing listens on port 1
+        let transport = SseTransport::connect_unchecked("http://127.0.0.1:1/sse".to_string())
+            .await
+            .unwrap();
+
+        let result = transport
+            .send(Message::request(1, "tools/list", None))
+            .await;
+        assert!(result.is_err());
+        assert!(!transport.is_healthy());
+    }
+
     #[tokio::test]
     async fn test_sse_ssrf_blocks_private_ip() {
         let result = SseTransport::connect("http://192.168.1.1/sse".to_string()).await;
@@ -1954,6 +2681,48 @@ mod tests {
             .is_err());
     }
 
+    #[tokio::test]
+    async fn test_ssrf_policy_allowlist() {
+        let policy = SsrfPolicy::new(&crate::config::SsrfConfig {
+            mode: crate::config::SsrfMode::Strict,
+            allow: vec!["10.20.0.0/16".to_string(), "localhost".to_string()],
+        });
+        let validated = validate_url_with_policy("http://10.20.0.5:8080/mcp", &policy)
+            .await
+            .unwrap();
+        assert_eq!(
+            validated.resolved_ips,
+            vec!["10.20.0.5:8080".parse().unwrap()]
+        );
+        assert!(validate_url_with_policy("http://10.21.0.5/mcp", &policy)
+            .await
+            .is_err());
+        assert!(
+            validate_url_with_policy("http://localhost:8080/mcp", &policy)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ssrf_permissive_still_blocks_metadata() {
+        let policy = SsrfPolicy::new(&crate::config::SsrfConfig {
+            mode: crate::config::SsrfMode::Permissive,
+            allow: vec![],
+        });
+        assert!(validate_url_with_policy("http://192.168.1.10/mcp", &policy)
+            .await
+            .is_ok());
+        for url in [
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.100.100.200/",
+            "http://metadata.google.internal/",
+        ] {
+            let err = validate_url_with_policy(url, &policy).await.unwrap_err();
+            assert!(matches!(err, TransportError::SsrfBlocked(_)), "{}", url);
+        }
+    }
+
     #[test]
     fn test_message_notification() {
         let notification = Message {
@@ -0,0 +1,125 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Envelope-only JSON-RPC messages
+//!
+//! [`Message`] parses `params`, `result` and `error` into `serde_json::Value`
+//! trees, which for a multi-megabyte tool result means thousands of
+//! allocations to build a tree that is only serialized again. [`RawMessage`]
+//! parses the envelope (`jsonrpc`, `id`, `method`) and keeps the payloads as
+//! validated but unparsed JSON text, so responses nobody needs to inspect can
+//! be passed through as-is.
+
+use serde_json::value::{to_raw_value, RawValue};
+use serde_json::Value;
+
+use super::{Message, TransportError};
+
+/// JSON-RPC message with unparsed payloads
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RawMessage {
+    /// JSON-RPC version, always "2.0"
+    pub jsonrpc: String,
+    /// Request/response ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    /// Method name for requests/notifications
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Method parameters, unparsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Box<RawValue>>,
+    /// Successful response data, unparsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Box<RawValue>>,
+    /// Error response data, unparsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Box<RawValue>>,
+}
+
+impl RawMessage {
+    /// Convert a parsed message, serializing its payloads
+    pub fn from_message(message: &Message) -> Result<Self, TransportError> {
+        let raw = |value: &Option<Value>| {
+            value
+                .as_ref()
+                .map(to_raw_value)
+                .transpose()
+                .map_err(|e| TransportError::InvalidMessage(e.to_string()))
+        };
+        Ok(Self {
+            jsonrpc: message.jsonrpc.clone(),
+            id: message.id.clone(),
+            method: message.method.clone(),
+            params: raw(&message.params)?,
+            result: raw(&message.result)?,
+            error: raw(&message.error)?,
+        })
+    }
+
+    /// Parse the payloads into a [`Message`]
+    pub fn into_message(self) -> Result<Message, TransportError> {
+        let parse = |value: Option<Box<RawValue>>| {
+            value
+                .map(|raw| serde_json::from_str(raw.get()))
+                .transpose()
+                .map_err(|e| TransportError::InvalidMessage(e.to_string()))
+        };
+        Ok(Message {
+            jsonrpc: self.jsonrpc,
+            id: self.id,
+            method: self.method,
+            params: parse(self.params)?,
+            result: parse(self.result)?,
+            error: parse(self.error)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_payload_passes_through_unchanged() {
+        // Formatting inside the payload is preserved, so it was never re-encoded
+        let line =
+            r#"{"jsonrpc":"2.0","id":3,"result":{"content": [ {"type":"text","text":"hi"} ]}}"#;
+        let raw: RawMessage = serde_json::from_str(line).unwrap();
+        assert_eq!(raw.id, Some(json!(3)));
+        assert!(raw.method.is_none());
+        assert_eq!(serde_json::to_string(&raw).unwrap(), line);
+    }
+
+    #[test]
+    fn test_round_trip_through_message() {
+        let message = Message::request(1, "tools/call", Some(json!({"name": "echo"})));
+        let raw = RawMessage::from_message(&message).unwrap();
+        assert_eq!(raw.method.as_deref(), Some("tools/call"));
+
+        let parsed = raw.into_message().unwrap();
+        assert_eq!(parsed.params, Some(json!({"name": "echo"})));
+        assert!(parsed.is_request());
+    }
+
+    #[test]
+    fn test_invalid_json_is_rejected() {
+        let line = r#"{"jsonrpc":"2.0","id":1,"result":{"unterminated": }"#;
+        assert!(serde_json::from_str::<RawMessage>(line).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Retrying request/response exchanges with an upstream
+//!
+//! [`exchange`] sends one request and waits for its response, replaying it on
+//! failure according to a [`RetryConfig`]. Only methods the policy lists as
+//! safe are ever replayed, and only for the configured error classes;
+//! JSON-RPC error responses are answers, not failures, and are never retried.
+//!
+//! Every attempt is bounded by the method's timeout from a [`TimeoutConfig`].
+//! [`exchange_raw`] does the same but leaves the response payload unparsed.
+//! Requests and notifications the upstream sends while an attempt waits are
+//! handed to [`super::reverse`], and the client's answers written back.
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+use super::{
+    headers::{client_header, forwarded_identity_id},
+    reverse, Message, RawMessage, Transport, TransportError,
+};
+use crate::config::{RetryConfig, RetryableError, TimeoutConfig};
+use crate::observability::{
+    record_identity_upstream_latency, record_upstream_latency, record_upstream_retry,
+};
+
+/// Request header that lowers the attempt count for a single request
+///
+/// The value is the total number of attempts (`1` disables retries). Values
+/// above the configured `max_attempts` are clamped, so clients cannot use it
+/// to amplify load on a failing upstream.
+pub const RETRY_HEADER: &str = "x-mcp-guard-retry";
+
+/// Classify a transport failure for the retry policy
+///
+/// Returns `None` for failures that would fail the same way again (invalid
+/// messages, SSRF blocks, bad configuration).
+fn classify(error: &TransportError) -> Option<RetryableError> {
+    match error {
+        TransportError::Spawn(_)
+        | TransportError::ProcessExited
+        | TransportError::Send(_)
+        | TransportError::ConnectionClosed => Some(RetryableError::Connection),
+        TransportError::Timeout => Some(RetryableError::Timeout),
+        TransportError::Receive(_) | TransportError::Http(_) | TransportError::Sse(_) => {
+            Some(RetryableError::Upstream)
+        }
+        TransportError::InvalidMessage(_)
+        | TransportError::SsrfBlocked(_)
+        | TransportError::EgressBlocked(_)
+        | TransportError::InvalidUrl(_)
+        | TransportError::CommandValidation(_)
+        | TransportError::Unsupported(_)
+        | TransportError::Tls(_) => None,
+    }
+}
+
+/// Send `message` and wait for the response, retrying per `policy`
+///
+/// Each attempt gets the full method timeout from `timeouts`. Upstream latency
+/// is recorded for every attempt.
+pub async fn exchange(
+    transport: &dyn Transport,
+    message: Message,
+    policy: &RetryConfig,
+    timeouts: &TimeoutConfig,
+) -> Result<Message, TransportError> {
+    exchange_raw(transport, message, policy, timeouts)
+        .await?
+        .into_message()
+}
+
+/// [`exchange`] without parsing the response payload
+///
+/// Used when the response is passed to the client untouched.
+pub async fn exchange_raw(
+    transport: &dyn Transport,
+    message: Message,
+    policy: &RetryConfig,
+    timeouts: &TimeoutConfig,
+) -> Result<RawMessage, TransportError> {
+    let method = message.method.clone().unwrap_or_default();
+    let timeout = timeouts.for_method(&method);
+    let requested = client_header(RETRY_HEADER).and_then(|v| v.trim().parse().ok());
+    let attempts = policy.attempts_for(&method, requested);
+    if attempts == 1 {
+        return attempt(transport, message, timeout).await;
+    }
+
+    let mut retry = 0;
+    loop {
+        let error = match attempt(transport, message.clone(), timeout).await {
+            Ok(response) => return Ok(response),
+            Err(e) => e,
+        };
+        retry += 1;
+
+        let reason = match classify(&error) {
+            Some(reason) if retry < attempts && policy.retry_on.contains(&reason) => reason,
+            _ => return Err(error),
+        };
+
+        // Equal jitter: at least half the backoff, so retries stay spread out
+        let backoff = policy.backoff(retry);
+        let delay = backoff / 2 + rand::thread_rng().gen_range(Duration::ZERO..=backoff / 2);
+        tracing::debug!(
+            method = %method,
+            retry = retry,
+            reason = reason.as_str(),
+            delay_ms = delay.as_millis() as u64,
+            error = %error,
+            "Retrying upstream call"
+        );
+        record_upstream_retry(transport.transport_type(), &method, reason.as_str());
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// One send/receive round trip, failing with `Timeout` after `timeout`
+///
+/// The timeout covers any upstream-initiated requests answered on the way.
+async fn attempt(
+    transport: &dyn Transport,
+    message: Message,
+    timeout: Duration,
+) -> Result<RawMessage, TransportError> {
+    let start = Instant::now();
+    let round_trip = async {
+        transport.send(message).await?;
+        let mut replies = FuturesUnordered::new();
+        loop {
+            tokio::select! {
+                received = transport.receive_raw() => {
+                    let received = received?;
+                    // Only requests and notifications carry a method
+                    if received.method.is_none() {
+                        return Ok(received);
+                    }
+                    if let Some(reply) = reverse::dispatch(transport, received).await? {
+                        replies.push(reply);
+                    }
+                }
+                Some(Ok(reply)) = replies.next(), if !replies.is_empty() => {
+                    transport.send(reply).await?;
+                }
+            }
+        }
+    };
+    let result = tokio::time::timeout(timeout, round_trip)
+        .await
+        .unwrap_or(Err(TransportError::Timeout));
+    let elapsed = start.elapsed();
+    record_upstream_latency(transport.transport_type(), elapsed, result.is_ok());
+    if let Some(identity_id) = forwarded_identity_id() {
+        record_identity_upstream_latency(&identity_id, elapsed);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::MockTransport;
+
+    fn policy(max_attempts: u32) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 2,
+            ..Default::default()
+        }
+    }
+
+    fn ok_response() -> Message {
+        Message::response(serde_json::json!(1), serde_json::json!({}))
+    }
+
+    #[tokio::test]
+    async fn test_retries_idempotent_method_until_success() {
+        let transport = MockTransport::new();
+        transport.push_error(TransportError::ConnectionClosed);
+        transport.push_error(TransportError::Timeout);
+        transport.push_response(ok_response());
+
+        let request = Message::request(1, "tools/list", None);
+        let response = exchange(&transport, request, &policy(3), &TimeoutConfig::default())
+            .await
+            .unwrap();
+
+        assert!(response.result.is_some());
+        assert_eq!(transport.sent_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_upstream_initiated_messages_are_not_responses() {
+        let transport = MockTransport::new();
+        transport.push_response(Message {
+            id: None,
+            ..Message::request(0, "notifications/progress", None)
+        });
+        transport.push_response(Message::request(9, "sampling/createMessage", None));
+        transport.push_response(ok_response());
+
+        let request = Message::request(1, "tools/call", Some(serde_json::json!({"name": "x"})));
+        let response = exchange(&transport, request, &policy(1), &TimeoutConfig::default())
+            .await
+            .unwrap();
+
+        assert_eq!(response.id, Some(serde_json::json!(1)));
+        // The call, then the declined sampling request
+        let sent = transport.take_sent_messages();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].id, Some(serde_json::json!(9)));
+        assert!(sent[1].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_never_retries_tool_calls_or_unlisted_errors() {
+        let transport = MockTransport::new();
+        transport.push_error(TransportError::ConnectionClosed);
+        transport.push_response(ok_response());
+
+        let request = Message::request(1, "tools/call", Some(serde_json::json!({"name": "x"})));
+        assert!(
+            exchange(&transport, request, &policy(3), &TimeoutConfig::default())
+                .await
+                .is_err()
+        );
+        assert_eq!(transport.sent_count(), 1);
+
+        // "upstream" errors are not in the default retry_on list
+        let transport = MockTransport::new();
+        transport.push_error(TransportError::Http("503".to_string()));
+        transport.push_response(ok_response());
+
+        let request = Message::request(1, "tools/list", None);
+        assert!(
+            exchange(&transport, request, &policy(3), &TimeoutConfig::default())
+                .await
+                .is_err()
+        );
+        assert_eq!(transport.sent_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_header_can_only_lower_attempts() {
+        use crate::auth::Identity;
+        use crate::transport::{with_forward_context, ForwardContext};
+
+        let context = |value: &str| {
+            let mut client_headers = axum::http::HeaderMap::new();
+            client_headers.insert(RETRY_HEADER, value.parse().unwrap());
+            ForwardContext {
+                client_headers,
+                identity: Identity {
+                    id: "test".to_string(),
+                    name: None,
+                    allowed_tools: None,
+                    allowed_resources: None,
+                    allowed_prompts: None,
+                    rate_limit: None,
+                    claims: std::collections::HashMap::new(),
+                    tenant: None,
+                    argument_constraints: None,
+                },
+            }
+        };
+
+        let transport = MockTransport::new();
+        transport.push_error(TransportError::ConnectionClosed);
+        transport.push_response(ok_response());
+        let request = Message::request(1, "tools/list", None);
+        let result = with_forward_context(
+            context("1"),
+            exchange(&transport, request, &policy(3), &TimeoutConfig::default()),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(transport.sent_count(), 1);
+
+        let transport = MockTransport::new();
+        for _ in 0..5 {
+            transport.push_error(TransportError::ConnectionClosed);
+        }
+        let request = Message::request(1, "tools/list", None);
+        let result = with_forward_context(
+            context("10"),
+            exchange(&transport, request, &policy(2), &TimeoutConfig::default()),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(transport.sent_count(), 2);
+    }
+
+    /// Transport whose responses never arrive
+    struct SilentTransport;
+
+    #[async_trait::async_trait]
+    impl Transport for SilentTransport {
+        async fn send(&self, _message: Message) -> Result<(), TransportError> {
+            Ok(())
+        }
+
+        async fn receive(&self) -> Result<Message, TransportError> {
+            std::future::pending().await
+        }
+
+        async fn close(&self) -> Result<(), TransportError> {
+            Ok(())
+        }
+
+        fn transport_type(&self) -> &'static str {
+            "silent"
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_method_timeout() {
+        let timeouts = TimeoutConfig {
+            default_secs: 30,
+            methods: [("tools/list".to_string(), 5)].into_iter().collect(),
+            ..Default::default()
+        };
+
+        let start = tokio::time::Instant::now();
+        let request = Message::request(1, "tools/list", None);
+        let result = exchange(&SilentTransport, request, &policy(1), &timeouts).await;
+        assert!(matches!(result, Err(TransportError::Timeout)));
+        assert_eq!(start.elapsed().as_secs(), 5);
+
+        let start = tokio::time::Instant::now();
+        let request = Message::request(2, "tools/call", None);
+        let result = exchange(&SilentTransport, request, &policy(1), &timeouts).await;
+        assert!(matches!(result, Err(TransportError::Timeout)));
+        assert_eq!(start.elapsed().as_secs(), 30);
+    }
+}
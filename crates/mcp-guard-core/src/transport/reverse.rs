@@ -0,0 +1,157 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Messages an upstream sends on its own during a call
+//!
+//! While handling a request, newer MCP servers may send requests back to the
+//! client (`sampling/createMessage`, `elicitation/create`, `roots/list`) and
+//! notifications such as `notifications/progress`. They arrive on the same
+//! stream as the response being waited for.
+//!
+//! [`with_upstream_sink`] lets the code forwarding a call take them: each
+//! request comes with a reply channel, and the client's answer sent there is
+//! written back to the upstream by the exchange still waiting on it. Without
+//! a sink, or when it is full, requests are declined with a JSON-RPC error so
+//! the upstream does not wait forever, and notifications are dropped.
+
+use std::future::Future;
+use tokio::sync::{mpsc, oneshot};
+
+use super::{Message, RawMessage, Transport, TransportError};
+
+/// JSON-RPC error code for upstream requests no client can answer
+pub const CLIENT_UNAVAILABLE_CODE: i32 = -32601;
+
+/// Message an upstream sent on its own while a call was in flight
+#[derive(Debug)]
+pub struct UpstreamMessage {
+    /// Request or notification, with the upstream's own ID
+    pub message: Message,
+    /// Where the client's response goes; `None` for notifications
+    pub reply: Option<oneshot::Sender<Message>>,
+}
+
+/// Channel upstream-initiated messages are delivered to
+pub type UpstreamSink = mpsc::Sender<UpstreamMessage>;
+
+tokio::task_local! {
+    static UPSTREAM_SINK: UpstreamSink;
+}
+
+/// Run `f` with upstream-initiated messages delivered to `sink`
+pub async fn with_upstream_sink<F: Future>(sink: UpstreamSink, f: F) -> F::Output {
+    UPSTREAM_SINK.scope(sink, f).await
+}
+
+/// Hand a request or notification received mid-call to the current sink
+///
+/// Returns where the client's response will arrive for delivered requests.
+/// Undeliverable requests are answered with [`CLIENT_UNAVAILABLE_CODE`].
+pub(crate) async fn dispatch(
+    transport: &dyn Transport,
+    message: RawMessage,
+) -> Result<Option<oneshot::Receiver<Message>>, TransportError> {
+    let message = message.into_message()?;
+    let (reply, receiver) = if message.is_request() {
+        let (reply, receiver) = oneshot::channel();
+        (Some(reply), Some(receiver))
+    } else {
+        (None, None)
+    };
+    let upstream_message = UpstreamMessage { message, reply };
+
+    let undelivered = match UPSTREAM_SINK.try_with(Clone::clone) {
+        Ok(sink) => match sink.try_send(upstream_message) {
+            Ok(()) => return Ok(receiver),
+            Err(e) => e.into_inner(),
+        },
+        Err(_) => upstream_message,
+    };
+
+    let message = undelivered.message;
+    tracing::debug!(
+        method = message.method.as_deref().unwrap_or_default(),
+        "Upstream-initiated message has no client to go to"
+    );
+    if message.is_request() {
+        transport
+            .send(Message::error_response(
+                message.id,
+                CLIENT_UNAVAILABLE_CODE,
+                "Client cannot receive server-initiated requests",
+            ))
+            .await?;
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::MockTransport;
+    use serde_json::json;
+
+    fn raw(message: &Message) -> RawMessage {
+        RawMessage::from_message(message).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_sink_are_declined() {
+        let transport = MockTransport::new();
+        let sampling = Message::request(7, "sampling/createMessage", Some(json!({})));
+        assert!(dispatch(&transport, raw(&sampling))
+            .await
+            .unwrap()
+            .is_none());
+
+        let notification = Message {
+            id: None,
+            ..Message::request(0, "notifications/progress", None)
+        };
+        assert!(dispatch(&transport, raw(&notification))
+            .await
+            .unwrap()
+            .is_none());
+
+        let sent = transport.take_sent_messages();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].id, Some(json!(7)));
+        assert_eq!(
+            sent[0].error.as_ref().unwrap()["code"],
+            CLIENT_UNAVAILABLE_CODE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requests_reach_sink_with_reply_channel() {
+        let transport = MockTransport::new();
+        let (sink, mut messages) = mpsc::channel(4);
+        let sampling = Message::request(7, "sampling/createMessage", Some(json!({})));
+
+        let receiver = with_upstream_sink(sink, dispatch(&transport, raw(&sampling)))
+            .await
+            .unwrap()
+            .unwrap();
+        let delivered = messages.recv().await.unwrap();
+        assert_eq!(delivered.message.id, Some(json!(7)));
+
+        let answer = Message::response(json!(7), json!({"role": "assistant"}));
+        delivered.reply.unwrap().send(answer).unwrap();
+        assert_eq!(receiver.await.unwrap().id, Some(json!(7)));
+        assert_eq!(transport.sent_count(), 0);
+    }
+}
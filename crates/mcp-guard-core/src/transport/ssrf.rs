@@ -0,0 +1,175 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! SSRF policy for HTTP/SSE upstream URLs
+//!
+//! Upstreams that are or resolve to private addresses are refused unless the
+//! `[ssrf]` section allows them, by network or by host name, or is set to
+//! permissive. Cloud metadata endpoints are refused whatever the policy.
+//!
+//! The policy is process-wide: [`configure_ssrf`] installs it at startup and
+//! every later [`validate_url_for_ssrf`](super::validate_url_for_ssrf) call,
+//! including periodic DNS re-resolutions, applies it.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, RwLock};
+
+use crate::auth::TrustedProxyValidator;
+use crate::config::{SsrfConfig, SsrfMode};
+
+/// Addresses of cloud metadata services
+const METADATA_IPS: [IpAddr; 3] = [
+    IpAddr::V4(Ipv4Addr::new(169, 254, 169, 254)),
+    // Alibaba Cloud
+    IpAddr::V4(Ipv4Addr::new(100, 100, 100, 200)),
+    // AWS over IPv6
+    IpAddr::V6(Ipv6Addr::new(0xfd00, 0xec2, 0, 0, 0, 0, 0, 0x254)),
+];
+
+/// `None` until configured, which means the strict default
+static SSRF_POLICY: RwLock<Option<Arc<SsrfPolicy>>> = RwLock::new(None);
+
+/// Install the SSRF policy from `[ssrf]`
+///
+/// Call once at startup, before any upstream transport is created.
+pub fn configure_ssrf(config: &SsrfConfig) {
+    let policy = Arc::new(SsrfPolicy::new(config));
+    if let Ok(mut current) = SSRF_POLICY.write() {
+        *current = Some(policy);
+    }
+}
+
+/// The installed policy, or the strict default
+pub(super) fn current_policy() -> Arc<SsrfPolicy> {
+    SSRF_POLICY
+        .read()
+        .ok()
+        .and_then(|policy| policy.clone())
+        .unwrap_or_default()
+}
+
+/// Whether `ip` is a cloud metadata endpoint, refused under any policy
+pub(super) fn is_metadata_ip(ip: &IpAddr) -> bool {
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(*ip),
+        IpAddr::V4(_) => *ip,
+    };
+    METADATA_IPS.contains(&ip)
+}
+
+/// Compiled `[ssrf]` section
+#[derive(Debug, Clone)]
+pub(super) struct SsrfPolicy {
+    mode: SsrfMode,
+    networks: TrustedProxyValidator,
+    /// Lowercase host names; entries starting with `.` match subdomains
+    hosts: Vec<String>,
+}
+
+impl Default for SsrfPolicy {
+    fn default() -> Self {
+        Self::new(&SsrfConfig::default())
+    }
+}
+
+impl SsrfPolicy {
+    pub(super) fn new(config: &SsrfConfig) -> Self {
+        // Entries that are not IPs or CIDRs are host names; config validation
+        // rejects anything else
+        let (networks, hosts): (Vec<String>, Vec<String>) = config
+            .allow
+            .iter()
+            .cloned()
+            .partition(|entry| entry.contains('/') || entry.trim().parse::<IpAddr>().is_ok());
+        Self {
+            mode: config.mode,
+            networks: TrustedProxyValidator::new(&networks),
+            hosts: hosts
+                .iter()
+                .map(|host| host.strip_prefix('*').unwrap_or(host).to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Whether `host` may connect to the private address `ip`
+    pub(super) fn allows_private(&self, host: &str, ip: &IpAddr) -> bool {
+        match self.mode {
+            SsrfMode::Permissive => true,
+            SsrfMode::Strict => self.networks.is_trusted(ip) || self.allows_host(host),
+        }
+    }
+
+    /// Whether `host` is listed, or is a subdomain of a `*.` entry
+    fn allows_host(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        self.hosts.iter().any(|allowed| {
+            if allowed.starts_with('.') {
+                host.ends_with(allowed.as_str())
+            } else {
+                host == *allowed
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(mode: SsrfMode, allow: &[&str]) -> SsrfPolicy {
+        SsrfPolicy::new(&SsrfConfig {
+            mode,
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn test_strict_allowlist() {
+        let policy = policy(
+            SsrfMode::Strict,
+            &["10.20.0.0/16", "192.168.1.5", "mcp.internal", "*.svc.local"],
+        );
+        let ip: IpAddr = "10.20.3.4".parse().unwrap();
+        assert!(policy.allows_private("anything", &ip));
+        assert!(policy.allows_private("x", &"192.168.1.5".parse().unwrap()));
+        assert!(!policy.allows_private("x", &"10.21.0.1".parse().unwrap()));
+
+        let other: IpAddr = "172.16.0.9".parse().unwrap();
+        assert!(policy.allows_private("mcp.internal", &other));
+        assert!(policy.allows_private("MCP.Internal.", &other));
+        assert!(!policy.allows_private("api.mcp.internal", &other));
+        assert!(policy.allows_private("github.tools.svc.local", &other));
+        assert!(!policy.allows_private("svc.local", &other));
+        assert!(!policy.allows_private("evilsvc.local", &other));
+    }
+
+    #[test]
+    fn test_default_and_permissive() {
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        assert!(!SsrfPolicy::default().allows_private("mcp.internal", &ip));
+        assert!(policy(SsrfMode::Permissive, &[]).allows_private("mcp.internal", &ip));
+    }
+
+    #[test]
+    fn test_is_metadata_ip() {
+        assert!(is_metadata_ip(&"169.254.169.254".parse().unwrap()));
+        assert!(is_metadata_ip(&"100.100.100.200".parse().unwrap()));
+        assert!(is_metadata_ip(&"fd00:ec2::254".parse().unwrap()));
+        assert!(is_metadata_ip(&"::ffff:169.254.169.254".parse().unwrap()));
+        assert!(!is_metadata_ip(&"169.254.1.1".parse().unwrap()));
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Upstream call outcomes for `/health?deep=true`
+//!
+//! Transport health flags only say whether a connection is up. These stats
+//! record what happened to the calls actually forwarded through it.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+
+use super::TransportError;
+
+/// Call outcomes for one upstream
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct UpstreamCallStats {
+    /// Last call that got a response (JSON-RPC errors included)
+    pub last_success: Option<DateTime<Utc>>,
+    /// Last call that failed at the transport level
+    pub last_failure: Option<DateTime<Utc>>,
+    /// Failed calls since the last success
+    pub consecutive_failures: u64,
+    /// Error from the last failed call
+    pub last_error: Option<String>,
+}
+
+/// Call outcomes for every upstream, by name
+#[derive(Debug, Default)]
+pub struct UpstreamStats {
+    upstreams: DashMap<String, UpstreamCallStats>,
+}
+
+impl UpstreamStats {
+    /// Record the result of a forwarded call
+    ///
+    /// Pass the result of the whole exchange, after retries.
+    pub fn record<T>(&self, upstream: &str, result: &Result<T, TransportError>) {
+        let mut stats = self.upstreams.entry(upstream.to_string()).or_default();
+        match result {
+            Ok(_) => {
+                stats.last_success = Some(Utc::now());
+                stats.consecutive_failures = 0;
+            }
+            Err(e) => {
+                stats.last_failure = Some(Utc::now());
+                stats.consecutive_failures += 1;
+                stats.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Outcomes recorded for `upstream` (empty if it has not been called)
+    pub fn get(&self, upstream: &str) -> UpstreamCallStats {
+        self.upstreams
+            .get(upstream)
+            .map(|stats| stats.value().clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_tracks_consecutive_failures() {
+        let stats = UpstreamStats::default();
+        assert!(stats.get("github").last_success.is_none());
+
+        stats.record::<()>("github", &Err(TransportError::Timeout));
+        stats.record::<()>("github", &Err(TransportError::Timeout));
+        let github = stats.get("github");
+        assert_eq!(github.consecutive_failures, 2);
+        assert!(github.last_failure.is_some());
+        assert!(github.last_error.is_some());
+
+        stats.record("github", &Ok(()));
+        let github = stats.get("github");
+        assert_eq!(github.consecutive_failures, 0);
+        assert!(github.last_success.is_some());
+        // The last error is kept for diagnosis
+        assert!(github.last_error.is_some());
+
+        assert_eq!(stats.get("filesystem").consecutive_failures, 0);
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! TLS settings for HTTP/SSE upstreams
+//!
+//! [`UpstreamTls`] turns an `[upstream.tls]` section into a rustls client
+//! configuration handed to reqwest: the built-in web PKI roots plus any
+//! configured CA, an optional client certificate for mutual TLS, a minimum
+//! protocol version and SPKI pins.
+//!
+//! Pins are checked after the normal certificate validation succeeded, so a
+//! pin narrows which certificates are accepted and never widens it. Any
+//! certificate the server presents may match, which allows pinning an
+//! intermediate CA's key and rotating leaf certificates freely.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use base64::Engine;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use sha2::{Digest, Sha256};
+
+use super::TransportError;
+use crate::config::{TlsVersion, UpstreamTlsConfig};
+
+/// Prefix of an SPKI pin; SHA-256 is the only supported hash
+const PIN_PREFIX: &str = "sha256/";
+
+/// Parse a `sha256/<base64>` pin into the SHA-256 digest it names
+pub fn parse_spki_pin(pin: &str) -> Result<[u8; 32], String> {
+    let encoded = pin
+        .strip_prefix(PIN_PREFIX)
+        .ok_or_else(|| format!("pin '{}' must start with '{}'", pin, PIN_PREFIX))?;
+    let digest = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("pin '{}' is not valid base64: {}", pin, e))?;
+    digest
+        .try_into()
+        .map_err(|_| format!("pin '{}' is not a SHA-256 digest (32 bytes)", pin))
+}
+
+/// Client TLS configuration for one upstream
+#[derive(Clone)]
+pub struct UpstreamTls {
+    config: rustls::ClientConfig,
+}
+
+impl UpstreamTls {
+    /// Load certificates and keys named by the configuration
+    ///
+    /// # Errors
+    /// Returns `TransportError::Tls` when a file cannot be read or parsed, or
+    /// the key does not match the certificate.
+    pub fn load(tls: &UpstreamTlsConfig) -> Result<Self, TransportError> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        if let Some(ref path) = tls.ca_cert {
+            let (added, _) = roots.add_parsable_certificates(read_certs(path)?);
+            if added == 0 {
+                return Err(TransportError::Tls(format!(
+                    "no CA certificate in {}",
+                    path.display()
+                )));
+            }
+        }
+
+        let verifier =
+            WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                .build()
+                .map_err(|e| TransportError::Tls(e.to_string()))?;
+        let pins = tls
+            .spki_pins
+            .iter()
+            .map(|pin| parse_spki_pin(pin))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(TransportError::Tls)?;
+
+        let versions: &[&rustls::SupportedProtocolVersion] = match tls.min_version {
+            TlsVersion::Tls12 => &[&rustls::version::TLS13, &rustls::version::TLS12],
+            TlsVersion::Tls13 => &[&rustls::version::TLS13],
+        };
+        let builder = rustls::ClientConfig::builder_with_provider(provider)
+            .with_protocol_versions(versions)
+            .map_err(|e| TransportError::Tls(e.to_string()))?;
+        let builder = if pins.is_empty() {
+            builder.with_webpki_verifier(verifier)
+        } else {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinnedVerifier {
+                    inner: verifier,
+                    pins,
+                }))
+        };
+
+        let config = match (&tls.client_cert, &tls.client_key) {
+            (Some(cert), Some(key)) => builder
+                .with_client_auth_cert(read_certs(cert)?, read_key(key)?)
+                .map_err(|e| TransportError::Tls(format!("client certificate: {}", e)))?,
+            _ => builder.with_no_client_auth(),
+        };
+        Ok(Self { config })
+    }
+
+    /// Use these settings for connections made by `builder`
+    pub(crate) fn apply(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        builder.use_preconfigured_tls(self.config.clone())
+    }
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TransportError> {
+    CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| TransportError::Tls(format!("{}: {}", path.display(), e)))
+}
+
+fn read_key(path: &Path) -> Result<PrivateKeyDer<'static>, TransportError> {
+    PrivateKeyDer::from_pem_file(path)
+        .map_err(|e| TransportError::Tls(format!("{}: {}", path.display(), e)))
+}
+
+/// SHA-256 of a certificate's SubjectPublicKeyInfo
+fn spki_digest(cert: &CertificateDer<'_>) -> Option<[u8; 32]> {
+    let (_, parsed) = x509_parser::parse_x509_certificate(cert.as_ref()).ok()?;
+    Some(Sha256::digest(parsed.tbs_certificate.subject_pki.raw).into())
+}
+
+/// Web PKI validation followed by an SPKI pin check
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<[u8; 32]>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+        let pinned = std::iter::once(end_entity)
+            .chain(intermediates)
+            .filter_map(spki_digest)
+            .any(|digest| self.pins.contains(&digest));
+        if !pinned {
+            return Err(rustls::Error::General(
+                "no certificate matches the configured SPKI pins".to_string(),
+            ));
+        }
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const PIN: &str = "sha256/47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=";
+
+    #[test]
+    fn test_parse_spki_pin() {
+        assert_eq!(
+            parse_spki_pin(PIN).unwrap(),
+            <[u8; 32]>::from(Sha256::digest(b""))
+        );
+
+        let err = parse_spki_pin("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=").unwrap_err();
+        assert!(err.contains("must start with 'sha256/'"), "{}", err);
+        let err = parse_spki_pin("sha256/not base64!").unwrap_err();
+        assert!(err.contains("not valid base64"), "{}", err);
+        let err = parse_spki_pin("sha256/AAAA").unwrap_err();
+        assert!(err.contains("32 bytes"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_without_files() {
+        let tls = UpstreamTlsConfig {
+            min_version: TlsVersion::Tls13,
+            spki_pins: vec![PIN.to_string()],
+            ..Default::default()
+        };
+        let loaded = UpstreamTls::load(&tls).unwrap();
+        assert!(loaded.apply(reqwest::Client::builder()).build().is_ok());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let missing = UpstreamTlsConfig {
+            ca_cert: Some("/nonexistent/ca.pem".into()),
+            ..Default::default()
+        };
+        let err = UpstreamTls::load(&missing).err().unwrap().to_string();
+        assert!(err.contains("/nonexistent/ca.pem"), "{}", err);
+
+        let mut empty = tempfile::NamedTempFile::new().unwrap();
+        writeln!(empty, "not a certificate").unwrap();
+        let no_certs = UpstreamTlsConfig {
+            ca_cert: Some(empty.path().to_path_buf()),
+            ..Default::default()
+        };
+        let err = UpstreamTls::load(&no_certs).err().unwrap().to_string();
+        assert!(err.contains("no CA certificate"), "{}", err);
+
+        let no_key = UpstreamTlsConfig {
+            client_cert: Some(empty.path().to_path_buf()),
+            client_key: Some(empty.path().to_path_buf()),
+            ..Default::default()
+        };
+        assert!(UpstreamTls::load(&no_key).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! Unix domain socket transport
+//!
+//! Connects to an MCP server listening on a unix socket and exchanges
+//! newline-delimited JSON, the same framing as the stdio transport. Common for
+//! local sidecars that should not expose a TCP port.
+
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    spawn_line_reader, spawn_line_writer, Message, RawMessage, Transport, TransportError,
+    TRANSPORT_CHANNEL_SIZE,
+};
+
+/// Transport for an upstream listening on a unix domain socket
+pub struct UnixSocketTransport {
+    /// Socket path, for logs
+    path: PathBuf,
+    /// Sender for outbound messages to the socket
+    tx: mpsc::Sender<Message>,
+    /// Receiver for inbound messages from the socket (mutex for shared access)
+    rx: tokio::sync::Mutex<mpsc::Receiver<RawMessage>>,
+    /// Background task writing messages to the socket
+    writer_task: tokio::task::JoinHandle<()>,
+    /// Background task reading messages from the socket
+    reader_task: tokio::task::JoinHandle<()>,
+    /// Cancellation token for graceful shutdown
+    shutdown_token: CancellationToken,
+}
+
+impl UnixSocketTransport {
+    /// Connect to the socket at `path`
+    #[cfg(unix)]
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        let path = path.as_ref().to_path_buf();
+        let stream = tokio::net::UnixStream::connect(&path).await?;
+        let (read_half, write_half) = stream.into_split();
+
+        let (to_socket_tx, to_socket_rx) = mpsc::channel::<Message>(TRANSPORT_CHANNEL_SIZE);
+        let (from_socket_tx, from_socket_rx) = mpsc::channel::<RawMessage>(TRANSPORT_CHANNEL_SIZE);
+        let shutdown_token = CancellationToken::new();
+
+        let writer_task =
+            spawn_line_writer(write_half, to_socket_rx, shutdown_token.clone(), "socket");
+        let reader_task =
+            spawn_line_reader(read_half, from_socket_tx, shutdown_token.clone(), "socket");
+
+        tracing::debug!(path = %path.display(), "Connected to unix socket upstream");
+        Ok(Self {
+            path,
+            tx: to_socket_tx,
+            rx: tokio::sync::Mutex::new(from_socket_rx),
+            writer_task,
+            reader_task,
+            shutdown_token,
+        })
+    }
+
+    /// Unix sockets are not available on this platform
+    #[cfg(not(unix))]
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        Err(TransportError::Spawn(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "unix socket transport is not supported on this platform ({})",
+                path.as_ref().display()
+            ),
+        )))
+    }
+
+    /// Socket path this transport is connected to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl Transport for UnixSocketTransport {
+    async fn send(&self, message: Message) -> Result<(), TransportError> {
+        self.tx
+            .send(message)
+            .await
+            .map_err(|e| TransportError::Send(e.to_string()))
+    }
+
+    async fn receive(&self) -> Result<Message, TransportError> {
+        self.receive_raw().await?.into_message()
+    }
+
+    async fn receive_raw(&self) -> Result<RawMessage, TransportError> {
+        self.rx
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(TransportError::ConnectionClosed)
+    }
+
+    async fn close(&self) -> Result<(), TransportError> {
+        self.shutdown_token.cancel();
+        Ok(())
+    }
+
+    fn transport_type(&self) -> &'static str {
+        "unix"
+    }
+
+    fn is_healthy(&self) -> bool {
+        !self.writer_task.is_finished() && !self.reader_task.is_finished()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    #[tokio::test]
+    async fn test_unix_socket_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("upstream.sock");
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+
+        // Echo server answering each request with its id
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read_half, mut write_half) = stream.into_split();
+            let mut lines = BufReader::new(read_half).lines();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let request: Message = serde_json::from_str(&line).unwrap();
+                let response = Message::response(
+                    request.id.unwrap(),
+                    serde_json::json!({"method": request.method}),
+                );
+                let mut json = serde_json::to_vec(&response).unwrap();
+                json.push(b'\n');
+                write_half.write_all(&json).await.unwrap();
+            }
+        });
+
+        let transport = UnixSocketTransport::connect(&path).await.unwrap();
+        assert_eq!(transport.path(), path.as_path());
+        assert_eq!(transport.transport_type(), "unix");
+
+        transport
+            .send(Message::request(serde_json::json!(7), "ping", None))
+            .await
+            .unwrap();
+        let response = transport.receive().await.unwrap();
+        assert_eq!(response.id, Some(serde_json::json!(7)));
+        assert_eq!(response.result.unwrap()["method"], "ping");
+        assert!(transport.is_healthy());
+
+        transport.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connect_missing_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = UnixSocketTransport::connect(dir.path().join("missing.sock")).await;
+        assert!(matches!(result, Err(TransportError::Spawn(_))));
+    }
+}
@@ -1,5 +1,5 @@
 use mcp_guard_core::audit::{AuditEntry, AuditLogger, EventType};
-use mcp_guard_core::config::AuditConfig;
+use mcp_guard_core::config::{AuditConfig, AuditOverflowStrategy, FsyncPolicy};
 use std::collections::HashMap;
 use std::time::Duration;
 use wiremock::matchers::{method, path};
@@ -10,6 +10,11 @@ fn test_export_config(export_url: String) -> AuditConfig {
     AuditConfig {
         enabled: true,
         file: None,
+        file_batch_size: 1,
+        file_flush_interval_ms: 1000,
+        file_fsync: FsyncPolicy::Never,
+        overflow: AuditOverflowStrategy::DropNewest,
+        overflow_block_ms: 100,
         stdout: false,
         export_url: Some(export_url),
         export_headers: HashMap::new(),
@@ -17,6 +22,8 @@ fn test_export_config(export_url: String) -> AuditConfig {
         export_interval_secs: 1,
         redaction_rules: Vec::new(),
         rotation: None,
+        object_storage: None,
+        detectors: Vec::new(),
     }
 }
 
@@ -54,10 +54,51 @@ fn test_config_validation_stdio() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     assert!(config.validate().is_ok());
@@ -76,10 +117,51 @@ fn test_config_validation_stdio_missing_command() {
             transport: TransportType::Stdio,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let result = config.validate();
@@ -102,10 +184,23 @@ fn test_config_validation_http_missing_url() {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        sessions: Default::default(),
     };
 
     let result = config.validate();
@@ -134,7 +229,18 @@ async fn test_auth_provider_api_key() {
         id: "test_user".to_string(),
         key_hash: hash,
         allowed_tools: vec!["read".to_string()],
+        allowed_resources: vec![],
+        allowed_prompts: vec![],
         rate_limit: Some(50),
+        network: None,
+        tenant: None,
+        description: None,
+        not_before: None,
+        expires_at: None,
+        constraints: vec![],
+        access_windows: vec![],
+        grants: vec![],
+        break_glass: false,
     };
 
     let provider = ApiKeyProvider::new(vec![config]);
@@ -159,7 +265,18 @@ async fn test_auth_provider_invalid_key() {
         id: "test_user".to_string(),
         key_hash: hash,
         allowed_tools: vec![],
+        allowed_resources: vec![],
+        allowed_prompts: vec![],
         rate_limit: None,
+        network: None,
+        tenant: None,
+        description: None,
+        not_before: None,
+        expires_at: None,
+        constraints: vec![],
+        access_windows: vec![],
+        grants: vec![],
+        break_glass: false,
     };
 
     let provider = ApiKeyProvider::new(vec![config]);
@@ -179,8 +296,12 @@ fn test_authz_tool_authorization() {
         id: "user1".to_string(),
         name: None,
         allowed_tools: None,
+        allowed_resources: None,
+        allowed_prompts: None,
         rate_limit: None,
         claims: std::collections::HashMap::new(),
+        tenant: None,
+        argument_constraints: None,
     };
 
     assert!(authorize_tool_call(&unrestricted, "any_tool"));
@@ -190,8 +311,12 @@ fn test_authz_tool_authorization() {
         id: "user2".to_string(),
         name: None,
         allowed_tools: Some(vec!["read".to_string(), "list".to_string()]),
+        allowed_resources: None,
+        allowed_prompts: None,
         rate_limit: None,
         claims: std::collections::HashMap::new(),
+        tenant: None,
+        argument_constraints: None,
     };
 
     assert!(authorize_tool_call(&restricted, "read"));
@@ -209,6 +334,9 @@ fn test_rate_limiter() {
         requests_per_second: 1,
         burst_size: 2,
         tool_limits: Vec::new(),
+        schedules: Vec::new(),
+        warmup: Default::default(),
+        expose_in_tools_list: false,
     };
 
     let limiter = RateLimitService::new(&config);
@@ -232,6 +360,9 @@ fn test_rate_limiter_disabled() {
         requests_per_second: 1,
         burst_size: 1,
         tool_limits: Vec::new(),
+        schedules: Vec::new(),
+        warmup: Default::default(),
+        expose_in_tools_list: false,
     };
 
     let limiter = RateLimitService::new(&config);
@@ -316,10 +447,23 @@ fn test_config_validation_http_valid() {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: Some("http://localhost:8080/mcp".to_string()),
             servers: vec![],
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        sessions: Default::default(),
     };
 
     assert!(config.validate().is_ok());
@@ -339,10 +483,23 @@ fn test_config_validation_sse_valid() {
             transport: TransportType::Sse,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: Some("http://localhost:8080/mcp/stream".to_string()),
             servers: vec![],
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        sessions: Default::default(),
     };
 
     assert!(config.validate().is_ok());
@@ -362,10 +519,51 @@ fn test_config_validation_http_requires_pro() {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: Some("http://localhost:8080/mcp".to_string()),
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let result = config.validate();
@@ -386,10 +584,51 @@ fn test_config_validation_sse_requires_pro() {
             transport: TransportType::Sse,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: Some("http://localhost:8080/mcp/stream".to_string()),
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let result = config.validate();
@@ -412,10 +651,23 @@ fn test_config_validation_sse_missing_url() {
             transport: TransportType::Sse,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        sessions: Default::default(),
     };
 
     let result = config.validate();
@@ -441,9 +693,51 @@ fn test_config_validation_port_zero() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
+        database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let result = config.validate();
@@ -461,6 +755,9 @@ fn test_config_validation_rate_limit_zero_rps() {
             requests_per_second: 0, // Invalid: zero RPS
             burst_size: 10,
             tool_limits: Vec::new(),
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
         },
         audit: Default::default(),
         tracing: TracingConfig::default(),
@@ -468,9 +765,51 @@ fn test_config_validation_rate_limit_zero_rps() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
+        database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let result = config.validate();
@@ -491,6 +830,9 @@ fn test_config_validation_rate_limit_zero_burst() {
             requests_per_second: 100,
             burst_size: 0, // Invalid: zero burst
             tool_limits: Vec::new(),
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
         },
         audit: Default::default(),
         tracing: TracingConfig::default(),
@@ -498,9 +840,51 @@ fn test_config_validation_rate_limit_zero_burst() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
+        database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let result = config.validate();
@@ -511,7 +895,7 @@ fn test_config_validation_rate_limit_zero_burst() {
 #[cfg(feature = "enterprise")]
 #[test]
 fn test_config_validation_audit_invalid_export_url() {
-    use mcp_guard_core::config::AuditConfig;
+    use mcp_guard_core::config::{AuditConfig, AuditOverflowStrategy, FsyncPolicy};
 
     let config = Config {
         server: Default::default(),
@@ -520,6 +904,11 @@ fn test_config_validation_audit_invalid_export_url() {
         audit: AuditConfig {
             enabled: true,
             file: None,
+            file_batch_size: 1,
+            file_flush_interval_ms: 1000,
+            file_fsync: FsyncPolicy::Never,
+            overflow: AuditOverflowStrategy::DropNewest,
+            overflow_block_ms: 100,
             stdout: true,
             export_url: Some("not-a-valid-url".to_string()), // Invalid URL
             export_batch_size: 100,
@@ -527,12 +916,16 @@ fn test_config_validation_audit_invalid_export_url() {
             export_headers: Default::default(),
             redaction_rules: Vec::new(),
             rotation: None,
+            object_storage: None,
         },
         tracing: TracingConfig::default(),
         upstream: UpstreamConfig {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
         },
@@ -546,7 +939,7 @@ fn test_config_validation_audit_invalid_export_url() {
 #[cfg(feature = "enterprise")]
 #[test]
 fn test_config_validation_audit_zero_batch_size() {
-    use mcp_guard_core::config::AuditConfig;
+    use mcp_guard_core::config::{AuditConfig, AuditOverflowStrategy, FsyncPolicy};
 
     let config = Config {
         server: Default::default(),
@@ -555,6 +948,11 @@ fn test_config_validation_audit_zero_batch_size() {
         audit: AuditConfig {
             enabled: true,
             file: None,
+            file_batch_size: 1,
+            file_flush_interval_ms: 1000,
+            file_fsync: FsyncPolicy::Never,
+            overflow: AuditOverflowStrategy::DropNewest,
+            overflow_block_ms: 100,
             stdout: true,
             export_url: Some("https://siem.example.com/logs".to_string()),
             export_batch_size: 0, // Invalid: zero batch size
@@ -562,12 +960,16 @@ fn test_config_validation_audit_zero_batch_size() {
             export_headers: Default::default(),
             redaction_rules: Vec::new(),
             rotation: None,
+            object_storage: None,
         },
         tracing: TracingConfig::default(),
         upstream: UpstreamConfig {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
         },
@@ -594,14 +996,58 @@ fn test_config_validation_tracing_invalid_sample_rate() {
             otlp_endpoint: None,
             sample_rate: 1.5, // Invalid: > 1.0
             propagate_context: true,
+            propagate_meta: true,
+            sampling: Vec::new(),
         },
         upstream: UpstreamConfig {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
+        database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let result = config.validate();
@@ -689,8 +1135,12 @@ fn test_tools_list_filtering_integration() {
         id: "read_only_user".to_string(),
         name: Some("Read Only User".to_string()),
         allowed_tools: Some(vec!["read_file".to_string(), "list_directory".to_string()]),
+        allowed_resources: None,
+        allowed_prompts: None,
         rate_limit: None,
         claims: HashMap::new(),
+        tenant: None,
+        argument_constraints: None,
     };
 
     let filtered = filter_tools_list_response(response.clone(), &read_only);
@@ -711,7 +1161,11 @@ fn test_tools_list_filtering_integration() {
         name: Some("Admin User".to_string()),
         allowed_tools: None, // No restrictions
         rate_limit: None,
+        allowed_resources: None,
+        allowed_prompts: None,
         claims: HashMap::new(),
+        tenant: None,
+        argument_constraints: None,
     };
 
     let unfiltered = filter_tools_list_response(response.clone(), &admin);
@@ -780,10 +1234,51 @@ async fn test_health_endpoint_response_structure() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     // Create minimal app state
@@ -801,6 +1296,31 @@ async fn test_health_endpoint_response_structure() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -886,10 +1406,51 @@ async fn test_live_endpoint() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let state = Arc::new(AppState {
@@ -906,6 +1467,31 @@ async fn test_live_endpoint() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -951,10 +1537,51 @@ async fn test_ready_endpoint_when_ready() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let state = Arc::new(AppState {
@@ -971,6 +1598,31 @@ async fn test_ready_endpoint_when_ready() {
         ready: Arc::new(RwLock::new(true)), // Ready = true
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -1021,10 +1673,51 @@ async fn test_ready_endpoint_when_not_ready() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let state = Arc::new(AppState {
@@ -1041,6 +1734,31 @@ async fn test_ready_endpoint_when_not_ready() {
         ready: Arc::new(RwLock::new(false)), // Ready = false
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -1073,6 +1791,7 @@ fn test_cli_version_command() {
     use assert_cmd::Command;
     use predicates::prelude::*;
 
+    #[allow(deprecated)]
     let mut cmd = Command::cargo_bin("mcp-guard").unwrap();
     cmd.arg("version");
 
@@ -1090,6 +1809,7 @@ fn test_cli_help_includes_new_commands() {
     use assert_cmd::Command;
     use predicates::prelude::*;
 
+    #[allow(deprecated)]
     let mut cmd = Command::cargo_bin("mcp-guard").unwrap();
     cmd.arg("--help");
 
@@ -1110,6 +1830,7 @@ fn test_cli_check_upstream_help() {
     use assert_cmd::Command;
     use predicates::prelude::*;
 
+    #[allow(deprecated)]
     let mut cmd = Command::cargo_bin("mcp-guard").unwrap();
     cmd.args(["check-upstream", "--help"]);
 
@@ -1124,6 +1845,7 @@ fn test_cli_check_upstream_missing_config() {
     use assert_cmd::Command;
     use predicates::prelude::*;
 
+    #[allow(deprecated)]
     let mut cmd = Command::cargo_bin("mcp-guard").unwrap();
     cmd.args(["--config", "nonexistent.toml", "check-upstream"]);
 
@@ -1165,10 +1887,51 @@ async fn test_oauth_authorize_not_configured() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let state = Arc::new(AppState {
@@ -1185,6 +1948,31 @@ async fn test_oauth_authorize_not_configured() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -1235,10 +2023,51 @@ async fn test_oauth_authorize_generates_redirect() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let oauth_config = OAuthConfig {
@@ -1254,6 +2083,9 @@ async fn test_oauth_authorize_generates_redirect() {
         user_id_claim: "sub".to_string(),
         scope_tool_mapping: HashMap::new(),
         token_cache_ttl_secs: 300,
+        session: None,
+        registration: None,
+        device_authorization_url: None,
     };
 
     let oauth_provider = OAuthAuthProvider::new(oauth_config).unwrap();
@@ -1272,6 +2104,31 @@ async fn test_oauth_authorize_generates_redirect() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -1336,10 +2193,51 @@ async fn test_oauth_callback_rejects_missing_state() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let oauth_config = OAuthConfig {
@@ -1355,6 +2253,9 @@ async fn test_oauth_callback_rejects_missing_state() {
         user_id_claim: "sub".to_string(),
         scope_tool_mapping: HashMap::new(),
         token_cache_ttl_secs: 300,
+        session: None,
+        registration: None,
+        device_authorization_url: None,
     };
 
     let oauth_provider = OAuthAuthProvider::new(oauth_config).unwrap();
@@ -1373,6 +2274,31 @@ async fn test_oauth_callback_rejects_missing_state() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -1422,10 +2348,51 @@ async fn test_oauth_callback_rejects_invalid_state() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let oauth_config = OAuthConfig {
@@ -1441,6 +2408,9 @@ async fn test_oauth_callback_rejects_invalid_state() {
         user_id_claim: "sub".to_string(),
         scope_tool_mapping: HashMap::new(),
         token_cache_ttl_secs: 300,
+        session: None,
+        registration: None,
+        device_authorization_url: None,
     };
 
     let oauth_provider = OAuthAuthProvider::new(oauth_config).unwrap();
@@ -1459,6 +2429,31 @@ async fn test_oauth_callback_rejects_invalid_state() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -1508,10 +2503,51 @@ async fn test_oauth_callback_handles_provider_error() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let oauth_config = OAuthConfig {
@@ -1527,6 +2563,9 @@ async fn test_oauth_callback_handles_provider_error() {
         user_id_claim: "sub".to_string(),
         scope_tool_mapping: HashMap::new(),
         token_cache_ttl_secs: 300,
+        session: None,
+        registration: None,
+        device_authorization_url: None,
     };
 
     let oauth_provider = OAuthAuthProvider::new(oauth_config).unwrap();
@@ -1545,6 +2584,31 @@ async fn test_oauth_callback_handles_provider_error() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -1583,8 +2647,26 @@ fn test_route_matcher_basic() {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: Some("http://localhost:8081".to_string()),
             strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         },
         ServerRouteConfig {
             name: "filesystem".to_string(),
@@ -1592,8 +2674,26 @@ fn test_route_matcher_basic() {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: Some("http://localhost:8082".to_string()),
             strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         },
     ];
 
@@ -1617,8 +2717,26 @@ fn test_route_matcher_longest_prefix() {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: Some("http://localhost:8081".to_string()),
             strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         },
         ServerRouteConfig {
             name: "api-v2".to_string(),
@@ -1626,8 +2744,26 @@ fn test_route_matcher_longest_prefix() {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: Some("http://localhost:8082".to_string()),
             strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         },
     ];
 
@@ -1666,6 +2802,9 @@ async fn test_routes_endpoint_lists_servers() {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: Some("http://localhost:8081".to_string()),
             servers: vec![
                 ServerRouteConfig {
@@ -1674,8 +2813,26 @@ async fn test_routes_endpoint_lists_servers() {
                     transport: TransportType::Http,
                     command: None,
                     args: vec![],
+                    env: Default::default(),
+                    cwd: None,
+                    inherit_env: true,
+                    spawn: Default::default(),
+                    idle_ttl_secs: 300,
                     url: Some("http://localhost:8081".to_string()),
                     strip_prefix: false,
+                    headers: Default::default(),
+                    tls: None,
+                    dns_refresh_secs: 300,
+                    socket_path: None,
+                    retry: Default::default(),
+                    timeouts: Default::default(),
+                    fair_queue: Default::default(),
+                    response_limits: Default::default(),
+                    transforms: Default::default(),
+                    catalog: Default::default(),
+                    mock: Default::default(),
+                    canary: None,
+                    methods: Default::default(),
                 },
                 ServerRouteConfig {
                     name: "filesystem".to_string(),
@@ -1683,11 +2840,68 @@ async fn test_routes_endpoint_lists_servers() {
                     transport: TransportType::Http,
                     command: None,
                     args: vec![],
+                    env: Default::default(),
+                    cwd: None,
+                    inherit_env: true,
+                    spawn: Default::default(),
+                    idle_ttl_secs: 300,
                     url: Some("http://localhost:8082".to_string()),
                     strip_prefix: false,
+                    headers: Default::default(),
+                    tls: None,
+                    dns_refresh_secs: 300,
+                    socket_path: None,
+                    retry: Default::default(),
+                    timeouts: Default::default(),
+                    fair_queue: Default::default(),
+                    response_limits: Default::default(),
+                    transforms: Default::default(),
+                    catalog: Default::default(),
+                    mock: Default::default(),
+                    canary: None,
+                    methods: Default::default(),
                 },
             ],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
+        database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     // Create router from server routes (using unchecked for localhost in tests)
@@ -1711,6 +2925,31 @@ async fn test_routes_endpoint_lists_servers() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -1765,10 +3004,51 @@ async fn test_routes_endpoint_unavailable_when_single_server() {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
-            servers: vec![], // No multi-server routing
+            servers: vec![], // No multi-server routing,
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     };
 
     let state = Arc::new(AppState {
@@ -1785,6 +3065,31 @@ async fn test_routes_endpoint_unavailable_when_single_server() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -1820,8 +3125,26 @@ fn test_server_route_config_validation() {
         transport: TransportType::Http,
         command: None,
         args: vec![],
+        env: Default::default(),
+        cwd: None,
+        inherit_env: true,
+        spawn: Default::default(),
+        idle_ttl_secs: 300,
         url: Some("http://localhost:8080".to_string()),
         strip_prefix: false,
+        headers: Default::default(),
+        tls: None,
+        dns_refresh_secs: 300,
+        socket_path: None,
+        retry: Default::default(),
+        timeouts: Default::default(),
+        fair_queue: Default::default(),
+        response_limits: Default::default(),
+        transforms: Default::default(),
+        catalog: Default::default(),
+        mock: Default::default(),
+        canary: None,
+        methods: Default::default(),
     };
     assert!(valid.validate().is_ok());
 
@@ -1832,8 +3155,26 @@ fn test_server_route_config_validation() {
         transport: TransportType::Http,
         command: None,
         args: vec![],
+        env: Default::default(),
+        cwd: None,
+        inherit_env: true,
+        spawn: Default::default(),
+        idle_ttl_secs: 300,
         url: Some("http://localhost:8080".to_string()),
         strip_prefix: false,
+        headers: Default::default(),
+        tls: None,
+        dns_refresh_secs: 300,
+        socket_path: None,
+        retry: Default::default(),
+        timeouts: Default::default(),
+        fair_queue: Default::default(),
+        response_limits: Default::default(),
+        transforms: Default::default(),
+        catalog: Default::default(),
+        mock: Default::default(),
+        canary: None,
+        methods: Default::default(),
     };
     assert!(invalid_prefix.validate().is_err());
 
@@ -1844,8 +3185,26 @@ fn test_server_route_config_validation() {
         transport: TransportType::Http,
         command: None,
         args: vec![],
+        env: Default::default(),
+        cwd: None,
+        inherit_env: true,
+        spawn: Default::default(),
+        idle_ttl_secs: 300,
         url: Some("http://localhost:8080".to_string()),
         strip_prefix: false,
+        headers: Default::default(),
+        tls: None,
+        dns_refresh_secs: 300,
+        socket_path: None,
+        retry: Default::default(),
+        timeouts: Default::default(),
+        fair_queue: Default::default(),
+        response_limits: Default::default(),
+        transforms: Default::default(),
+        catalog: Default::default(),
+        mock: Default::default(),
+        canary: None,
+        methods: Default::default(),
     };
     assert!(invalid_name.validate().is_err());
 }
@@ -35,6 +35,8 @@ fn create_hs256_config() -> JwtConfig {
         scopes_claim: "scope".to_string(),
         scope_tool_mapping: HashMap::new(),
         leeway_secs: 0,
+        revocation: None,
+        identity: None,
     }
 }
 
@@ -170,6 +172,8 @@ async fn test_jwt_scope_string_format() {
         scopes_claim: "scope".to_string(),
         scope_tool_mapping: scope_mapping,
         leeway_secs: 0,
+        revocation: None,
+        identity: None,
     };
     let provider = JwtProvider::new(config).unwrap();
 
@@ -199,6 +203,8 @@ async fn test_jwt_scope_array_format() {
         scopes_claim: "permissions".to_string(), // Array format
         scope_tool_mapping: scope_mapping,
         leeway_secs: 0,
+        revocation: None,
+        identity: None,
     };
     let provider = JwtProvider::new(config).unwrap();
 
@@ -241,6 +247,8 @@ async fn test_jwt_custom_user_id_claim() {
         scopes_claim: "scope".to_string(),
         scope_tool_mapping: HashMap::new(),
         leeway_secs: 0,
+        revocation: None,
+        identity: None,
     };
     let provider = JwtProvider::new(config).unwrap();
 
@@ -266,6 +274,7 @@ async fn test_jwt_jwks_config_creation() {
             jwks_url: format!("{}/jwks", mock_server.uri()),
             algorithms: vec!["RS256".to_string()],
             cache_duration_secs: 3600,
+            min_refresh_interval_secs: 30,
         },
         issuer: "https://issuer.example.com".to_string(),
         audience: "test-audience".to_string(),
@@ -273,6 +282,8 @@ async fn test_jwt_jwks_config_creation() {
         scopes_claim: "scope".to_string(),
         scope_tool_mapping: HashMap::new(),
         leeway_secs: 0,
+        revocation: None,
+        identity: None,
     };
 
     let provider = JwtProvider::new(config);
@@ -297,6 +308,7 @@ async fn test_jwt_jwks_missing_kid_error() {
             jwks_url: format!("{}/jwks", mock_server.uri()),
             algorithms: vec!["RS256".to_string()],
             cache_duration_secs: 3600,
+            min_refresh_interval_secs: 30,
         },
         issuer: "https://issuer.example.com".to_string(),
         audience: "test-audience".to_string(),
@@ -304,6 +316,8 @@ async fn test_jwt_jwks_missing_kid_error() {
         scopes_claim: "scope".to_string(),
         scope_tool_mapping: HashMap::new(),
         leeway_secs: 0,
+        revocation: None,
+        identity: None,
     };
 
     let provider = JwtProvider::new(config).unwrap();
@@ -332,7 +346,9 @@ async fn test_jwt_leeway_allows_slightly_expired() {
         user_id_claim: "sub".to_string(),
         scopes_claim: "scope".to_string(),
         scope_tool_mapping: HashMap::new(),
-        leeway_secs: 60, // 60 seconds leeway
+        leeway_secs: 60, // 60 seconds leeway,
+        revocation: None,
+        identity: None,
     };
     let provider = JwtProvider::new(config).unwrap();
 
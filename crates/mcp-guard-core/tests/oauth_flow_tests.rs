@@ -10,10 +10,10 @@ use axum::{
 };
 use mcp_guard_core::{
     audit::AuditLogger,
-    auth::{ApiKeyProvider, OAuthAuthProvider},
+    auth::{ApiKeyProvider, JwtProvider, OAuthAuthProvider, SessionStore},
     config::{
-        AuditConfig, Config, OAuthConfig, OAuthProvider as OAuthProviderType, RateLimitConfig,
-        TracingConfig, TransportType, UpstreamConfig,
+        AuditConfig, Config, JwtConfig, JwtMode, OAuthConfig, OAuthProvider as OAuthProviderType,
+        OAuthSessionConfig, RateLimitConfig, TracingConfig, TransportType, UpstreamConfig,
     },
     observability::create_metrics_handle,
     rate_limit::RateLimitService,
@@ -43,9 +43,51 @@ fn create_test_config() -> Config {
             transport: TransportType::Stdio,
             command: Some("echo".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
+        database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     }
 }
 
@@ -64,9 +106,44 @@ fn create_oauth_config(mock_server_uri: &str) -> OAuthConfig {
         user_id_claim: "sub".to_string(),
         scope_tool_mapping: HashMap::new(),
         token_cache_ttl_secs: 300,
+        session: None,
+        registration: None,
+        device_authorization_url: None,
     }
 }
 
+/// Session JWT provider used by the callback to mint tokens after a successful exchange
+fn create_session_jwt_provider() -> Arc<JwtProvider> {
+    Arc::new(
+        JwtProvider::new(JwtConfig {
+            mode: JwtMode::Simple {
+                secret: "test-session-secret-that-is-at-least-32-chars".to_string(),
+            },
+            issuer: "mcp-guard".to_string(),
+            audience: "mcp-guard".to_string(),
+            user_id_claim: "sub".to_string(),
+            scopes_claim: "scope".to_string(),
+            scope_tool_mapping: HashMap::new(),
+            leeway_secs: 0,
+            revocation: None,
+            identity: None,
+        })
+        .unwrap(),
+    )
+}
+
+/// Mock the userinfo endpoint the callback uses to resolve the user's identity
+async fn mount_userinfo(mock_server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/userinfo"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "sub": "user-123",
+            "name": "Test User"
+        })))
+        .mount(mock_server)
+        .await;
+}
+
 // =============================================================================
 // OAuth State Management Tests
 // =============================================================================
@@ -95,6 +172,31 @@ async fn test_oauth_authorize_stores_pkce_state() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -158,6 +260,7 @@ async fn test_oauth_callback_successful_token_exchange() {
         })))
         .mount(&mock_server)
         .await;
+    mount_userinfo(&mock_server).await;
 
     let mut config = create_test_config();
     let oauth_config = create_oauth_config(&mock_server.uri());
@@ -177,6 +280,7 @@ async fn test_oauth_callback_successful_token_exchange() {
             code_verifier: test_verifier.to_string(),
             created_at: Instant::now(),
             client_ip,
+            redirect_uri: None,
         },
     );
 
@@ -194,6 +298,31 @@ async fn test_oauth_callback_successful_token_exchange() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: Some(create_session_jwt_provider()),
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -250,6 +379,7 @@ async fn test_oauth_callback_rejects_ip_mismatch() {
             code_verifier: "verifier".to_string(),
             created_at: Instant::now(),
             client_ip: original_ip,
+            redirect_uri: None,
         },
     );
 
@@ -267,6 +397,31 @@ async fn test_oauth_callback_rejects_ip_mismatch() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -304,6 +459,7 @@ async fn test_oauth_callback_rejects_missing_code() {
             code_verifier: "verifier".to_string(),
             created_at: Instant::now(),
             client_ip,
+            redirect_uri: None,
         },
     );
 
@@ -321,6 +477,31 @@ async fn test_oauth_callback_rejects_missing_code() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -373,6 +554,7 @@ async fn test_oauth_callback_token_exchange_failure() {
             code_verifier: "verifier".to_string(),
             created_at: Instant::now(),
             client_ip,
+            redirect_uri: None,
         },
     );
 
@@ -390,6 +572,31 @@ async fn test_oauth_callback_token_exchange_failure() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -424,6 +631,7 @@ async fn test_oauth_callback_minimal_token_response() {
         })))
         .mount(&mock_server)
         .await;
+    mount_userinfo(&mock_server).await;
 
     let mut config = create_test_config();
     let oauth_config = create_oauth_config(&mock_server.uri());
@@ -441,6 +649,7 @@ async fn test_oauth_callback_minimal_token_response() {
             code_verifier: "verifier".to_string(),
             created_at: Instant::now(),
             client_ip,
+            redirect_uri: None,
         },
     );
 
@@ -458,6 +667,31 @@ async fn test_oauth_callback_minimal_token_response() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: Some(create_session_jwt_provider()),
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -518,6 +752,7 @@ async fn test_oauth_callback_missing_access_token() {
             code_verifier: "verifier".to_string(),
             created_at: Instant::now(),
             client_ip,
+            redirect_uri: None,
         },
     );
 
@@ -535,6 +770,31 @@ async fn test_oauth_callback_missing_access_token() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -570,6 +830,7 @@ async fn test_oauth_callback_confidential_client() {
         .expect(1)
         .mount(&mock_server)
         .await;
+    mount_userinfo(&mock_server).await;
 
     let mut config = create_test_config();
     let oauth_config = create_oauth_config(&mock_server.uri());
@@ -587,6 +848,7 @@ async fn test_oauth_callback_confidential_client() {
             code_verifier: "verifier".to_string(),
             created_at: Instant::now(),
             client_ip,
+            redirect_uri: None,
         },
     );
 
@@ -604,6 +866,31 @@ async fn test_oauth_callback_confidential_client() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: Some(create_session_jwt_provider()),
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -643,6 +930,7 @@ async fn test_oauth_callback_public_client() {
         })))
         .mount(&mock_server)
         .await;
+    mount_userinfo(&mock_server).await;
 
     let mut config = create_test_config();
     let mut oauth_config = create_oauth_config(&mock_server.uri());
@@ -661,6 +949,7 @@ async fn test_oauth_callback_public_client() {
             code_verifier: "verifier".to_string(),
             created_at: Instant::now(),
             client_ip,
+            redirect_uri: None,
         },
     );
 
@@ -678,6 +967,31 @@ async fn test_oauth_callback_public_client() {
         ready: Arc::new(RwLock::new(true)),
         mtls_provider: None,
         db: None,
+        jwt_provider: Some(create_session_jwt_provider()),
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     let app = build_router(state);
@@ -696,3 +1010,344 @@ async fn test_oauth_callback_public_client() {
 
     assert_eq!(response.status(), StatusCode::OK);
 }
+
+// =============================================================================
+// Server-side Session Tests
+// =============================================================================
+
+/// Test that the callback keeps provider tokens server-side when sessions are enabled
+#[tokio::test]
+async fn test_oauth_callback_issues_gateway_session() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains("grant_type=authorization_code"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "provider_access_token",
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "refresh_token": "provider_refresh_token"
+        })))
+        .mount(&mock_server)
+        .await;
+    mount_userinfo(&mock_server).await;
+
+    let mut config = create_test_config();
+    let mut oauth_config = create_oauth_config(&mock_server.uri());
+    oauth_config.session = Some(OAuthSessionConfig::default());
+    config.auth.oauth = Some(oauth_config.clone());
+
+    let oauth_state_store = new_oauth_state_store();
+    oauth_state_store.insert(
+        "session_state".to_string(),
+        PkceState {
+            code_verifier: "verifier".to_string(),
+            created_at: Instant::now(),
+            client_ip: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+            redirect_uri: None,
+        },
+    );
+    let session_store = Arc::new(SessionStore::new(OAuthSessionConfig::default()));
+
+    let state = Arc::new(AppState {
+        config: config.clone(),
+        auth_provider: Arc::new(ApiKeyProvider::new(vec![])),
+        rate_limiter: RateLimitService::new(&config.rate_limit),
+        audit_logger: Arc::new(AuditLogger::new(&config.audit).unwrap()),
+        transport: Some(Arc::new(StdioTransport::spawn("echo", &[]).await.unwrap())),
+        router: None,
+        metrics_handle: create_metrics_handle(),
+        oauth_provider: Some(Arc::new(OAuthAuthProvider::new(oauth_config).unwrap())),
+        oauth_state_store,
+        started_at: Instant::now(),
+        ready: Arc::new(RwLock::new(true)),
+        mtls_provider: None,
+        db: None,
+        // No JWT provider needed: sessions replace minted JWTs
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: Some(session_store.clone()),
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
+    });
+
+    let app = build_router(state);
+
+    let mut request = Request::builder()
+        .uri("/oauth/callback?code=valid_auth_code&state=session_state")
+        .body(Body::empty())
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(axum::extract::ConnectInfo(SocketAddr::from((
+            [127, 0, 0, 1],
+            3000,
+        ))));
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let cookie = response
+        .headers()
+        .get("set-cookie")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(cookie.starts_with("mcp_guard_session=mcpg_sess_"));
+    assert!(cookie.contains("HttpOnly"));
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let session_token = json["access_token"].as_str().unwrap().to_string();
+
+    // Provider tokens never reach the client
+    assert!(session_token.starts_with("mcpg_sess_"));
+    assert!(json["refresh_token"].is_null());
+    assert_eq!(json["expires_in"], 86400);
+    assert_eq!(session_store.len(), 1);
+
+    // Logout ends the session and clears the cookie
+    let request = Request::builder()
+        .method("POST")
+        .uri("/oauth/logout")
+        .header("Authorization", format!("Bearer {}", session_token))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(response
+        .headers()
+        .get("set-cookie")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .contains("Max-Age=0"));
+    assert!(session_store.is_empty());
+}
+
+// =============================================================================
+// Device Code Flow Tests (RFC 8628)
+// =============================================================================
+
+/// Test the device code flow end to end: start, pending poll, then approval
+#[tokio::test]
+async fn test_oauth_device_code_flow() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/device/code"))
+        .and(body_string_contains("client_id=test_client_id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "device_code": "provider_device_code",
+            "user_code": "WDJB-MJHT",
+            "verification_uri": "https://idp.example.com/device",
+            "expires_in": 900
+        })))
+        .mount(&mock_server)
+        .await;
+    // First poll: user hasn't approved yet
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains("device_code=provider_device_code"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": "authorization_pending"
+        })))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains(
+            "grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Adevice_code",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "device_access_token",
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "refresh_token": "device_refresh_token"
+        })))
+        .mount(&mock_server)
+        .await;
+    mount_userinfo(&mock_server).await;
+
+    let mut config = create_test_config();
+    let mut oauth_config = create_oauth_config(&mock_server.uri());
+    oauth_config.device_authorization_url = Some(format!("{}/device/code", mock_server.uri()));
+    config.auth.oauth = Some(oauth_config.clone());
+    let session_store = Arc::new(SessionStore::new(OAuthSessionConfig::default()));
+
+    let state = Arc::new(AppState {
+        config: config.clone(),
+        auth_provider: Arc::new(ApiKeyProvider::new(vec![])),
+        rate_limiter: RateLimitService::new(&config.rate_limit),
+        audit_logger: Arc::new(AuditLogger::new(&config.audit).unwrap()),
+        transport: Some(Arc::new(StdioTransport::spawn("echo", &[]).await.unwrap())),
+        router: None,
+        metrics_handle: create_metrics_handle(),
+        oauth_provider: Some(Arc::new(OAuthAuthProvider::new(oauth_config).unwrap())),
+        oauth_state_store: new_oauth_state_store(),
+        started_at: Instant::now(),
+        ready: Arc::new(RwLock::new(true)),
+        mtls_provider: None,
+        db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: Some(session_store.clone()),
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
+    });
+
+    let app = build_router(state);
+
+    // Start the flow
+    let request = Request::builder()
+        .method("POST")
+        .uri("/oauth/device")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["user_code"], "WDJB-MJHT");
+    assert_eq!(json["interval"], 5);
+    let device_code = json["device_code"].as_str().unwrap().to_string();
+
+    let poll = |app: axum::Router| {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/oauth/device/token")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(Body::from(format!("device_code={}", device_code)))
+            .unwrap();
+        app.oneshot(request)
+    };
+
+    // Pending until the user approves
+    let response = poll(app.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"], "authorization_pending");
+
+    // Approved: client gets a gateway session token
+    let response = poll(app).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(json["access_token"]
+        .as_str()
+        .unwrap()
+        .starts_with("mcpg_sess_"));
+    assert_eq!(session_store.len(), 1);
+}
+
+/// Test that the device endpoint reports unsupported providers
+#[tokio::test]
+async fn test_oauth_device_flow_unsupported_provider() {
+    let mock_server = MockServer::start().await;
+
+    let mut config = create_test_config();
+    let oauth_config = create_oauth_config(&mock_server.uri());
+    config.auth.oauth = Some(oauth_config.clone());
+
+    let state = Arc::new(AppState {
+        config: config.clone(),
+        auth_provider: Arc::new(ApiKeyProvider::new(vec![])),
+        rate_limiter: RateLimitService::new(&config.rate_limit),
+        audit_logger: Arc::new(AuditLogger::new(&config.audit).unwrap()),
+        transport: Some(Arc::new(StdioTransport::spawn("echo", &[]).await.unwrap())),
+        router: None,
+        metrics_handle: create_metrics_handle(),
+        oauth_provider: Some(Arc::new(OAuthAuthProvider::new(oauth_config).unwrap())),
+        oauth_state_store: new_oauth_state_store(),
+        started_at: Instant::now(),
+        ready: Arc::new(RwLock::new(true)),
+        mtls_provider: None,
+        db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
+    });
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/oauth/device")
+        .body(Body::empty())
+        .unwrap();
+    let response = build_router(state).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -10,7 +10,7 @@ use wiremock::{
 
 use mcp_guard_core::{
     auth::{AuthProvider, OAuthAuthProvider},
-    config::{OAuthConfig, OAuthProvider},
+    config::{OAuthConfig, OAuthProvider, OAuthRegistrationConfig, RegistrationMode},
 };
 
 /// Create an OAuth config pointing to a mock server
@@ -28,6 +28,9 @@ fn create_oauth_config(mock_server_uri: &str) -> OAuthConfig {
         user_id_claim: "sub".to_string(),
         scope_tool_mapping: HashMap::new(),
         token_cache_ttl_secs: 300,
+        session: None,
+        registration: None,
+        device_authorization_url: None,
     }
 }
 
@@ -46,6 +49,9 @@ fn create_oauth_config_userinfo_only(mock_server_uri: &str) -> OAuthConfig {
         user_id_claim: "sub".to_string(),
         scope_tool_mapping: HashMap::new(),
         token_cache_ttl_secs: 300,
+        session: None,
+        registration: None,
+        device_authorization_url: None,
     }
 }
 
@@ -377,6 +383,9 @@ fn test_oauth_provider_name() {
         user_id_claim: "sub".to_string(),
         scope_tool_mapping: HashMap::new(),
         token_cache_ttl_secs: 300,
+        session: None,
+        registration: None,
+        device_authorization_url: None,
     };
 
     let provider = OAuthAuthProvider::new(config).unwrap();
@@ -416,3 +425,69 @@ async fn test_oauth_expired_token_in_response() {
     // Should fail due to expiration
     assert!(result.is_err());
 }
+
+// =============================================================================
+// Dynamic Client Registration Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_oauth_register_client_proxies_to_provider() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/register"))
+        .and(header("authorization", "Bearer initial-token"))
+        .and(body_string_contains("https://client.example/callback"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+            "client_id": "provider-client",
+            "redirect_uris": ["https://client.example/callback"]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let mut config = create_oauth_config(&mock_server.uri());
+    config.registration = Some(OAuthRegistrationConfig {
+        mode: RegistrationMode::Proxy,
+        registration_url: Some(format!("{}/register", mock_server.uri())),
+        initial_access_token: Some("initial-token".to_string()),
+        ..Default::default()
+    });
+    let provider = OAuthAuthProvider::new(config).unwrap();
+
+    let (status, body) = provider
+        .register_client(&serde_json::json!({
+            "redirect_uris": ["https://client.example/callback"]
+        }))
+        .await
+        .unwrap();
+    assert_eq!(status, 201);
+    assert_eq!(body["client_id"], "provider-client");
+}
+
+#[tokio::test]
+async fn test_oauth_register_client_passes_provider_errors_through() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/register"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": "invalid_redirect_uri"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let mut config = create_oauth_config(&mock_server.uri());
+    config.registration = Some(OAuthRegistrationConfig {
+        mode: RegistrationMode::Proxy,
+        registration_url: Some(format!("{}/register", mock_server.uri())),
+        ..Default::default()
+    });
+    let provider = OAuthAuthProvider::new(config).unwrap();
+
+    let (status, body) = provider
+        .register_client(&serde_json::json!({ "redirect_uris": [] }))
+        .await
+        .unwrap();
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "invalid_redirect_uri");
+}
@@ -3,7 +3,7 @@
 //! These tests verify app state creation and key component behavior.
 
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Instant;
 use tokio::sync::RwLock;
 
 use mcp_guard_core::{
@@ -20,7 +20,7 @@ use mcp_guard_core::{
 };
 
 /// Create a minimal test configuration
-fn create_test_config(port: u16) -> Config {
+fn create_test_config(_port: u16) -> Config {
     Config {
         server: ServerConfig::default(),
         upstream: UpstreamConfig {
@@ -28,28 +28,88 @@ fn create_test_config(port: u16) -> Config {
             // Use 'cat' directly as the command (no shell needed)
             command: Some("cat".to_string()),
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
             url: None,
             servers: vec![],
+            mode: Default::default(),
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
         },
         auth: mcp_guard_core::config::AuthConfig {
             api_keys: vec![ApiKeyConfig {
                 id: "test-client".to_string(),
                 key_hash: hash_api_key("test-api-key"),
                 allowed_tools: vec![],
+                allowed_resources: vec![],
+                allowed_prompts: vec![],
                 rate_limit: None,
+                network: None,
+                tenant: None,
+                description: None,
+                not_before: None,
+                expires_at: None,
+                constraints: vec![],
+                access_windows: vec![],
+                grants: vec![],
+                break_glass: false,
             }],
-            jwt: None,
+            jwt: Vec::new(),
             oauth: None,
             mtls: None,
+            saml: None,
+            hmac: None,
+            authorization_server: None,
+            custom: vec![],
         },
         rate_limit: RateLimitConfig {
             enabled: false,
             requests_per_second: 10,
             burst_size: 20,
             tool_limits: Vec::new(),
+            schedules: Vec::new(),
+            warmup: Default::default(),
+            expose_in_tools_list: false,
         },
         audit: AuditConfig::default(),
         tracing: TracingConfig::default(),
+        database_url: None,
+        database: None,
+        cluster: Default::default(),
+        stripe_secret_key: None,
+        include: Vec::new(),
+        load_shedding: Default::default(),
+        network_acl: Default::default(),
+        ssrf: Default::default(),
+        egress: Default::default(),
+        request_signing: Default::default(),
+        telemetry_privacy: Default::default(),
+        scrubbing: Default::default(),
+        response_filtering: Default::default(),
+        logging: Default::default(),
+        metrics: Default::default(),
+        admin: Default::default(),
+        sessions: Default::default(),
+        tenancy: Default::default(),
+        approval: Default::default(),
+        honeypot: Default::default(),
+        break_glass: Default::default(),
+        methods: Default::default(),
+        read_only: Default::default(),
+        capabilities: Default::default(),
+        alerting: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
     }
 }
 
@@ -102,6 +162,31 @@ async fn test_app_state_creation_with_api_key() {
         ready,
         mtls_provider: None,
         db: None,
+        jwt_provider: None,
+        load_shedder: Default::default(),
+        session_store: None,
+        cluster: None,
+        affinity: None,
+        authorization_server: None,
+        hmac_provider: None,
+        network_acl: Default::default(),
+        scrubber: Default::default(),
+        response_filters: Default::default(),
+        identity_store: Default::default(),
+        sessions: Default::default(),
+        upstream_stats: Default::default(),
+        license_expires_at: None,
+        fair_queue: Default::default(),
+        tenants: Default::default(),
+        approvals: Default::default(),
+        honeypot: Default::default(),
+        read_only: Default::default(),
+        break_glass: Default::default(),
+        method_policy: Default::default(),
+        capture: Default::default(),
+        journal: Default::default(),
+        upstream_requests: Default::default(),
+        registration_limiter: Default::default(),
     });
 
     // Verify state is created correctly
@@ -155,14 +240,36 @@ async fn test_multi_provider_auth_fallback() {
         id: "user1".to_string(),
         key_hash: hash_api_key("key1"),
         allowed_tools: vec![],
+        allowed_resources: vec![],
+        allowed_prompts: vec![],
         rate_limit: None,
+        network: None,
+        tenant: None,
+        description: None,
+        not_before: None,
+        expires_at: None,
+        constraints: vec![],
+        access_windows: vec![],
+        grants: vec![],
+        break_glass: false,
     }])) as Arc<dyn AuthProvider>;
 
     let provider2 = Arc::new(ApiKeyProvider::new(vec![ApiKeyConfig {
         id: "user2".to_string(),
         key_hash: hash_api_key("key2"),
         allowed_tools: vec![],
+        allowed_resources: vec![],
+        allowed_prompts: vec![],
         rate_limit: None,
+        network: None,
+        tenant: None,
+        description: None,
+        not_before: None,
+        expires_at: None,
+        constraints: vec![],
+        access_windows: vec![],
+        grants: vec![],
+        break_glass: false,
     }])) as Arc<dyn AuthProvider>;
 
     let multi_provider = MultiProvider::new(vec![provider1, provider2]);
@@ -197,6 +304,7 @@ async fn test_oauth_state_store_operations() {
             code_verifier: "verifier1".to_string(),
             created_at: Instant::now(),
             client_ip: std::net::IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 1)),
+            redirect_uri: None,
         },
     );
 
@@ -226,6 +334,7 @@ async fn test_oauth_state_store_multiple_entries() {
                 code_verifier: format!("verifier{}", i),
                 created_at: Instant::now(),
                 client_ip: std::net::IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 1)),
+                redirect_uri: None,
             },
         );
     }
@@ -291,8 +400,26 @@ fn test_config_is_multi_server() {
             transport: TransportType::Http,
             command: None,
             args: vec![],
+            env: Default::default(),
+            cwd: None,
+            inherit_env: true,
+            spawn: Default::default(),
+            idle_ttl_secs: 300,
             url: Some("http://localhost:8080".to_string()),
             strip_prefix: false,
+            headers: Default::default(),
+            tls: None,
+            dns_refresh_secs: 300,
+            socket_path: None,
+            retry: Default::default(),
+            timeouts: Default::default(),
+            fair_queue: Default::default(),
+            response_limits: Default::default(),
+            transforms: Default::default(),
+            catalog: Default::default(),
+            mock: Default::default(),
+            canary: None,
+            methods: Default::default(),
         });
 
     assert!(config.is_multi_server());
@@ -365,6 +492,9 @@ fn test_rate_limiter_respects_identity_override() {
         requests_per_second: 10,
         burst_size: 20,
         tool_limits: Vec::new(),
+        schedules: Vec::new(),
+        warmup: Default::default(),
+        expose_in_tools_list: false,
     };
 
     let rate_limiter = RateLimitService::new(&config);
@@ -387,6 +517,9 @@ fn test_rate_limiter_tracks_identities() {
         requests_per_second: 10,
         burst_size: 20,
         tool_limits: Vec::new(),
+        schedules: Vec::new(),
+        warmup: Default::default(),
+        expose_in_tools_list: false,
     };
 
     let rate_limiter = RateLimitService::new(&config);
@@ -418,6 +551,9 @@ fn test_upstream_config_multi_server() {
         transport: TransportType::Stdio,
         command: None,
         args: vec![],
+        env: Default::default(),
+        cwd: None,
+        inherit_env: true,
         url: None,
         servers: vec![
             mcp_guard_core::config::ServerRouteConfig {
@@ -426,8 +562,26 @@ fn test_upstream_config_multi_server() {
                 transport: TransportType::Http,
                 command: None,
                 args: vec![],
+                env: Default::default(),
+                cwd: None,
+                inherit_env: true,
+                spawn: Default::default(),
+                idle_ttl_secs: 300,
                 url: Some("http://localhost:8081".to_string()),
                 strip_prefix: true,
+                headers: Default::default(),
+                tls: None,
+                dns_refresh_secs: 300,
+                socket_path: None,
+                retry: Default::default(),
+                timeouts: Default::default(),
+                fair_queue: Default::default(),
+                response_limits: Default::default(),
+                transforms: Default::default(),
+                catalog: Default::default(),
+                mock: Default::default(),
+                canary: None,
+                methods: Default::default(),
             },
             mcp_guard_core::config::ServerRouteConfig {
                 name: "server2".to_string(),
@@ -435,10 +589,40 @@ fn test_upstream_config_multi_server() {
                 transport: TransportType::Http,
                 command: None,
                 args: vec![],
+                env: Default::default(),
+                cwd: None,
+                inherit_env: true,
+                spawn: Default::default(),
+                idle_ttl_secs: 300,
                 url: Some("http://localhost:8082".to_string()),
                 strip_prefix: false,
+                headers: Default::default(),
+                tls: None,
+                dns_refresh_secs: 300,
+                socket_path: None,
+                retry: Default::default(),
+                timeouts: Default::default(),
+                fair_queue: Default::default(),
+                response_limits: Default::default(),
+                transforms: Default::default(),
+                catalog: Default::default(),
+                mock: Default::default(),
+                canary: None,
+                methods: Default::default(),
             },
         ],
+        mode: Default::default(),
+        headers: Default::default(),
+        tls: None,
+        dns_refresh_secs: 300,
+        socket_path: None,
+        retry: Default::default(),
+        timeouts: Default::default(),
+        fair_queue: Default::default(),
+        response_limits: Default::default(),
+        transforms: Default::default(),
+        catalog: Default::default(),
+        mock: Default::default(),
     };
 
     assert_eq!(config.servers.len(), 2);
@@ -344,7 +344,7 @@ impl EnterpriseGuardTools {
                     },
                     "auth": {
                         "api_keys_count": config.auth.api_keys.len(),
-                        "jwt_enabled": config.auth.jwt.is_some(),
+                        "jwt_enabled": !config.auth.jwt.is_empty(),
                         "oauth_enabled": config.auth.oauth.is_some(),
                         "mtls_enabled": config.auth.mtls.as_ref().map(|m| m.enabled).unwrap_or(false)
                     }
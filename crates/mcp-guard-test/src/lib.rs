@@ -0,0 +1,516 @@
+// Copyright (c) 2025 Austin Green
+// SPDX-License-Identifier: AGPL-3.0
+//
+// This file is part of MCP-Guard.
+//
+// MCP-Guard is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// MCP-Guard is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with MCP-Guard. If not, see <https://www.gnu.org/licenses/>.
+//! In-process mcp-guard for integration tests
+//!
+//! [`TestGuard`] serves the complete gateway, the same router `mcp-guard run`
+//! serves, on a loopback port inside the test process. Upstreams are the
+//! built-in mock (`transport = "mock"`) or any [`Transport`] passed in, so a
+//! test can check its authentication, authorization, rate limit and audit
+//! policies without spawning processes.
+//!
+//! ```no_run
+//! use mcp_guard_test::TestGuard;
+//! use serde_json::json;
+//!
+//! # async fn example() -> Result<(), mcp_guard_test::Error> {
+//! let guard = TestGuard::from_toml(
+//!     r#"
+//!     [upstream]
+//!     transport = "mock"
+//!
+//!     [[upstream.mock.tools]]
+//!     name = "read_file"
+//!     text = "hello"
+//!     "#,
+//! )?
+//! .api_key("alice", |key| key.allowed_tools = vec!["read_file".to_string()])
+//! .start()
+//! .await?;
+//!
+//! let alice = guard.client(guard.api_key("alice"));
+//! let result = alice.call_tool("read_file", json!({ "path": "/tmp/x" })).await?;
+//! assert_eq!(result["content"][0]["text"], "hello");
+//! # Ok(())
+//! # }
+//! ```
+
+use axum::Router;
+use mcp_guard_core::auth::{
+    registered_auth_providers, ApiKeyProvider, AuthProvider, JwtProvider, MultiProvider,
+};
+use mcp_guard_core::cli::{generate_api_key, hash_api_key};
+use mcp_guard_core::config::{ApiKeyConfig, Config, ConfigError, JwtMode, TransportType};
+use mcp_guard_core::router::ServerRouter;
+use mcp_guard_core::server::{AppState, Guard};
+use mcp_guard_core::transport::{MockUpstreamTransport, Transport};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tempfile::TempDir;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// Lifetime of tokens minted by [`TestGuard::jwt`]
+const JWT_LIFETIME_SECS: u64 = 3600;
+
+/// Errors from starting or talking to a [`TestGuard`]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Configuration error: {0}")]
+    Config(#[from] ConfigError),
+
+    #[error("Gateway error: {0}")]
+    Guard(#[from] mcp_guard_core::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The gateway answered with an HTTP error
+    #[error("HTTP {status}: {body}")]
+    Status { status: u16, body: Value },
+
+    /// The upstream (or the gateway, in JSON-RPC error mode) answered with a
+    /// JSON-RPC error
+    #[error("JSON-RPC error {code}: {message}")]
+    JsonRpc {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+
+    #[error("Token error: {0}")]
+    Token(String),
+}
+
+impl Error {
+    /// HTTP status of a [`Status`](Error::Status) error
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+/// Builder for a [`TestGuard`]
+pub struct TestGuardBuilder {
+    config: Config,
+    keys: HashMap<String, String>,
+    transport: Option<Arc<dyn Transport>>,
+    auth_provider: Option<Arc<dyn AuthProvider>>,
+}
+
+impl TestGuardBuilder {
+    /// Add an API key for `id` with a freshly generated secret
+    ///
+    /// `configure` can restrict the key (allowed tools, rate limit, tenant,
+    /// ...). The secret is available from [`TestGuard::api_key`] once
+    /// started. A key already configured with this ID is replaced.
+    pub fn api_key(mut self, id: &str, configure: impl FnOnce(&mut ApiKeyConfig)) -> Self {
+        let secret = generate_api_key();
+        let mut key: ApiKeyConfig =
+            serde_json::from_value(json!({ "id": id, "key_hash": hash_api_key(&secret) }))
+                .expect("API key config with id and key_hash");
+        configure(&mut key);
+
+        self.config
+            .auth
+            .api_keys
+            .retain(|existing| existing.id != id);
+        self.config.auth.api_keys.push(key);
+        self.keys.insert(id.to_string(), secret);
+        self
+    }
+
+    /// Change the configuration before the gateway starts
+    pub fn configure(mut self, configure: impl FnOnce(&mut Config)) -> Self {
+        configure(&mut self.config);
+        self
+    }
+
+    /// Upstream for single-server mode
+    ///
+    /// Only needed when `upstream.transport` is not `mock`.
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Bearer token provider, replacing the configured API keys, simple-mode
+    /// JWT and `[[auth.custom]]` providers
+    pub fn auth_provider(mut self, provider: Arc<dyn AuthProvider>) -> Self {
+        self.auth_provider = Some(provider);
+        self
+    }
+
+    /// Validate the configuration and start serving on a loopback port
+    ///
+    /// The audit log and call journal are redirected into a temporary
+    /// directory that lives as long as the [`TestGuard`].
+    pub async fn start(self) -> Result<TestGuard, Error> {
+        let dir = tempfile::tempdir()?;
+        let mut config = self.config;
+        config.audit.file = Some(dir.path().join("audit.log"));
+        config.audit.stdout = false;
+        if config.journal.path.is_relative() {
+            config.journal.path = dir.path().join(&config.journal.path);
+        }
+        config.validate()?;
+
+        let auth_provider = match self.auth_provider {
+            Some(provider) => provider,
+            None => default_auth_provider(&config)?,
+        };
+        let mut builder = Guard::builder(config.clone())
+            .auth_provider(auth_provider)
+            .metrics_handle(mcp_guard_core::observability::create_metrics_handle());
+        if config.is_multi_server() {
+            // Tests commonly point routes at local mock servers, which SSRF
+            // validation would reject
+            let router = ServerRouter::new_unchecked(config.upstream.servers.clone())
+                .await
+                .map_err(mcp_guard_core::Error::from)?;
+            builder = builder.server_router(Arc::new(router));
+        } else {
+            builder = builder.transport(match self.transport {
+                Some(transport) => transport,
+                None => default_transport(&config)?,
+            });
+        }
+        let guard = builder.build()?;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let app: Router = guard.clone().into_router();
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(async {
+                let _ = stopped.await;
+            })
+            .await;
+        });
+
+        Ok(TestGuard {
+            addr,
+            guard,
+            keys: self.keys,
+            http: reqwest::Client::new(),
+            shutdown: Some(shutdown),
+            server: Some(server),
+            dir,
+        })
+    }
+}
+
+/// API keys and custom providers, as the standalone gateway builds them,
+/// plus the JWT provider in simple mode
+fn default_auth_provider(config: &Config) -> Result<Arc<dyn AuthProvider>, Error> {
+    let mut providers: Vec<Arc<dyn AuthProvider>> = Vec::new();
+    if !config.auth.api_keys.is_empty() {
+        providers.push(Arc::new(ApiKeyProvider::new(config.auth.api_keys.clone())));
+    }
+    let simple: Vec<_> = config
+        .auth
+        .jwt
+        .iter()
+        .filter(|jwt| matches!(jwt.mode, JwtMode::Simple { .. }))
+        .cloned()
+        .collect();
+    if !simple.is_empty() {
+        let provider =
+            JwtProvider::with_issuers(simple, None).map_err(mcp_guard_core::Error::from)?;
+        providers.push(Arc::new(provider));
+    }
+    let registry = registered_auth_providers();
+    for custom in &config.auth.custom {
+        providers.push(
+            registry
+                .build(custom)
+                .map_err(mcp_guard_core::Error::from)?,
+        );
+    }
+    Ok(Arc::new(MultiProvider::new(providers)))
+}
+
+fn default_transport(config: &Config) -> Result<Arc<dyn Transport>, Error> {
+    match config.upstream.transport {
+        TransportType::Mock => Ok(Arc::new(MockUpstreamTransport::new(
+            config.upstream.mock.clone(),
+        ))),
+        ref transport => Err(ConfigError::Validation(format!(
+            "upstream.transport: TestGuard starts only mock upstreams, pass a transport for {:?}",
+            transport
+        ))
+        .into()),
+    }
+}
+
+/// The gateway running in-process; stops when dropped
+pub struct TestGuard {
+    addr: SocketAddr,
+    guard: Guard,
+    keys: HashMap<String, String>,
+    http: reqwest::Client,
+    shutdown: Option<oneshot::Sender<()>>,
+    server: Option<JoinHandle<()>>,
+    dir: TempDir,
+}
+
+impl TestGuard {
+    /// Start building from a configuration
+    pub fn builder(config: Config) -> TestGuardBuilder {
+        TestGuardBuilder {
+            config,
+            keys: HashMap::new(),
+            transport: None,
+            auth_provider: None,
+        }
+    }
+
+    /// Start building from TOML, as it would appear in `mcp-guard.toml`
+    pub fn from_toml(toml: &str) -> Result<TestGuardBuilder, Error> {
+        let config = toml::from_str(toml).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        Ok(Self::builder(config))
+    }
+
+    /// Address the gateway listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// URL of `path` on the gateway (e.g. `/health`)
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// Shared state, for inspecting the rate limiter, identity store, etc.
+    pub fn state(&self) -> &Arc<AppState> {
+        self.guard.state()
+    }
+
+    /// Temporary directory holding the audit log and journal
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Secret of an API key added with [`TestGuardBuilder::api_key`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if no key was added for `id`.
+    pub fn api_key(&self, id: &str) -> &str {
+        self.keys
+            .get(id)
+            .unwrap_or_else(|| panic!("no test API key for '{}'", id))
+    }
+
+    /// Mint an HS256 token for `subject` with the configured simple-mode
+    /// JWT secret, issuer and audience (the first `[[auth.jwt]]` entry when
+    /// several issuers are configured)
+    ///
+    /// `claims` (an object) is merged over the standard claims, e.g.
+    /// `json!({ "scope": "read:files" })` or `json!({ "exp": 0 })`.
+    pub fn jwt(&self, subject: &str, claims: Value) -> Result<String, Error> {
+        let jwt = self
+            .state()
+            .config
+            .auth
+            .jwt
+            .first()
+            .ok_or_else(|| Error::Token("auth.jwt is not configured".to_string()))?;
+        let JwtMode::Simple { ref secret } = jwt.mode else {
+            return Err(Error::Token(
+                "tokens can only be minted in simple JWT mode".to_string(),
+            ));
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut token_claims = json!({
+            "iss": jwt.issuer,
+            "aud": jwt.audience,
+            "iat": now,
+            "exp": now + JWT_LIFETIME_SECS,
+        });
+        token_claims[jwt.user_id_claim.as_str()] = json!(subject);
+        if let Value::Object(extra) = claims {
+            for (name, value) in extra {
+                token_claims[name.as_str()] = value;
+            }
+        }
+
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+            &token_claims,
+            &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .map_err(|e| Error::Token(e.to_string()))
+    }
+
+    /// Client sending `token` as its bearer token
+    pub fn client(&self, token: impl Into<String>) -> TestClient {
+        TestClient {
+            token: Some(token.into()),
+            ..self.anonymous()
+        }
+    }
+
+    /// Client sending no credentials
+    pub fn anonymous(&self) -> TestClient {
+        TestClient {
+            http: self.http.clone(),
+            base_url: self.url(""),
+            server: None,
+            token: None,
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Audit log entries written so far, oldest first
+    pub fn audit_events(&self) -> Result<Vec<Value>, Error> {
+        let path = self.dir.path().join("audit.log");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        std::fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .map_err(|e| Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+            })
+            .collect()
+    }
+
+    /// Stop serving and wait for in-flight requests to finish
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(server) = self.server.take() {
+            let _ = server.await;
+        }
+    }
+}
+
+impl Drop for TestGuard {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// MCP client for a [`TestGuard`]
+#[derive(Clone)]
+pub struct TestClient {
+    http: reqwest::Client,
+    base_url: String,
+    /// Server route, in multi-server routing mode
+    server: Option<String>,
+    token: Option<String>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl TestClient {
+    /// Send to `/mcp/{name}` instead of `/mcp` (multi-server routing)
+    pub fn server(mut self, name: &str) -> Self {
+        self.server = Some(name.to_string());
+        self
+    }
+
+    /// POST request to the MCP endpoint with this client's credentials,
+    /// for checking statuses and headers directly
+    pub fn post(&self) -> reqwest::RequestBuilder {
+        let endpoint = match self.server {
+            Some(ref server) => format!("{}/mcp/{}", self.base_url, server),
+            None => format!("{}/mcp", self.base_url),
+        };
+        let request = self.http.post(endpoint);
+        match self.token {
+            Some(ref token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Send a raw JSON-RPC message
+    pub async fn send(&self, message: &Value) -> Result<reqwest::Response, Error> {
+        Ok(self.post().json(message).send().await?)
+    }
+
+    /// Send a request and return its `result`
+    ///
+    /// HTTP errors become [`Error::Status`] and JSON-RPC errors
+    /// [`Error::JsonRpc`].
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let response = self
+            .send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            return Err(Error::Status {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        if let Some(error) = body.get("error") {
+            return Err(Error::JsonRpc {
+                code: error["code"].as_i64().unwrap_or_default(),
+                message: error["message"].as_str().unwrap_or_default().to_string(),
+                data: error.get("data").cloned(),
+            });
+        }
+        Ok(body.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Names of the tools this client can see
+    pub async fn list_tools(&self) -> Result<Vec<String>, Error> {
+        let result = self.request("tools/list", json!({})).await?;
+        Ok(result["tools"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|tool| tool["name"].as_str().map(str::to_string))
+            .collect())
+    }
+
+    /// Call a tool and return its result
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value, Error> {
+        self.request(
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )
+        .await
+    }
+}
@@ -0,0 +1,127 @@
+//! Integration tests for the in-process test gateway
+
+use mcp_guard_test::{Error, TestGuard};
+use serde_json::json;
+
+const MOCK_UPSTREAM: &str = r#"
+[upstream]
+transport = "mock"
+
+[[upstream.mock.tools]]
+name = "read_file"
+text = "hello"
+
+[[upstream.mock.tools]]
+name = "delete_file"
+"#;
+
+#[tokio::test]
+async fn test_api_key_policies() {
+    let guard = TestGuard::from_toml(MOCK_UPSTREAM)
+        .unwrap()
+        .api_key("alice", |key| {
+            key.allowed_tools = vec!["read_file".to_string()]
+        })
+        .api_key("admin", |_| {})
+        .start()
+        .await
+        .unwrap();
+
+    let alice = guard.client(guard.api_key("alice"));
+    assert_eq!(alice.list_tools().await.unwrap(), vec!["read_file"]);
+    let result = alice
+        .call_tool("read_file", json!({ "path": "/tmp/x" }))
+        .await
+        .unwrap();
+    assert_eq!(result["content"][0]["text"], "hello");
+
+    let err = alice
+        .call_tool("delete_file", json!({ "path": "/tmp/x" }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(403));
+
+    let admin = guard.client(guard.api_key("admin"));
+    assert_eq!(admin.list_tools().await.unwrap().len(), 2);
+
+    let err = guard.anonymous().list_tools().await.unwrap_err();
+    assert_eq!(err.status(), Some(401));
+    let err = guard.client("mcp_wrong").list_tools().await.unwrap_err();
+    assert_eq!(err.status(), Some(401));
+
+    let events = guard.audit_events().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| event["event_type"] == "authz_denied" && event["identity_id"] == "alice"));
+}
+
+#[tokio::test]
+async fn test_minted_jwts_are_accepted() {
+    let guard = TestGuard::from_toml(&format!(
+        r#"
+        {MOCK_UPSTREAM}
+
+        [auth.jwt]
+        mode = "simple"
+        secret = "a-test-secret-that-is-at-least-32-characters"
+        issuer = "https://issuer.test"
+        audience = "mcp-guard"
+
+        [auth.jwt.scope_tool_mapping]
+        "read:files" = ["read_file"]
+        "#
+    ))
+    .unwrap()
+    .start()
+    .await
+    .unwrap();
+
+    let token = guard.jwt("bob", json!({ "scope": "read:files" })).unwrap();
+    let bob = guard.client(token);
+    assert_eq!(bob.list_tools().await.unwrap(), vec!["read_file"]);
+
+    let expired = guard
+        .jwt("bob", json!({ "scope": "read:files", "exp": 1 }))
+        .unwrap();
+    let err = guard.client(expired).list_tools().await.unwrap_err();
+    assert_eq!(err.status(), Some(401));
+}
+
+#[tokio::test]
+async fn test_rate_limits_and_jsonrpc_errors() {
+    let guard = TestGuard::from_toml(MOCK_UPSTREAM)
+        .unwrap()
+        .configure(|config| {
+            config.rate_limit.requests_per_second = 1;
+            config.rate_limit.burst_size = 1;
+        })
+        .api_key("alice", |_| {})
+        .start()
+        .await
+        .unwrap();
+
+    let alice = guard.client(guard.api_key("alice"));
+    alice.request("ping", json!({})).await.unwrap();
+    let err = alice.request("ping", json!({})).await.unwrap_err();
+    assert_eq!(err.status(), Some(429));
+
+    let response = alice
+        .post()
+        .header("x-mcp-guard-error-format", "jsonrpc")
+        .json(&json!({ "jsonrpc": "2.0", "id": 9, "method": "ping" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["id"], 9);
+    assert_eq!(body["error"]["data"]["type"], "rate_limited");
+
+    assert!(matches!(
+        TestGuard::from_toml("[upstream]\ntransport = \"stdio\"\ncommand = \"cat\"")
+            .unwrap()
+            .start()
+            .await,
+        Err(Error::Config(_))
+    ));
+}